@@ -0,0 +1,185 @@
+//! Throughput of event decoding over batches of transaction logs
+//!
+//! The corpus mimics a busy stretch of the program: buys and sells sent by trading bots across
+//! a handful of mints, with the compute budget, token and system program lines they log, and a
+//! curve completing now and then. Each benchmark decodes the whole batch.
+//!
+//! `reference` is the decoder `parse_logs` replaced, which decoded every `Program data:` line
+//! into a fresh buffer before looking at its discriminator. Compare the other benchmarks against
+//! it with `cargo bench --bench parse_logs`.
+//!
+//! The corpus is generated rather than captured from chain, and no timings are recorded here:
+//! run the benchmark on the target machine to get numbers for it.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use pumpfun::{
+    constants::accounts::PUMPFUN,
+    events::{self, CompleteEvent, Event, LogParser, TradeEvent, PROGRAM_DATA_PREFIX},
+};
+use solana_sdk::pubkey::Pubkey;
+
+/// Number of transactions in the batch
+const TRANSACTIONS: usize = 1_000;
+/// Number of mints the trades are spread over
+const MINTS: usize = 20;
+/// One transaction in this many completes its curve
+const COMPLETE_EVERY: usize = 50;
+
+/// Encodes an event as the log line the program writes it to
+fn program_data<T: Event + BorshSerialize>(event: &T) -> String {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    event.serialize(&mut data).unwrap();
+    format!("{}{}", PROGRAM_DATA_PREFIX, STANDARD.encode(data))
+}
+
+/// Builds the logs of a batch of trades, one vector of lines per transaction
+fn corpus(mints: &[Pubkey]) -> Vec<Vec<String>> {
+    let compute_budget = "ComputeBudget111111111111111111111111111111";
+    let token = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+    let system = "11111111111111111111111111111111";
+
+    (0..TRANSACTIONS)
+        .map(|i| {
+            let is_buy = i % 3 != 0;
+            let trade = TradeEvent {
+                mint: mints[i % mints.len()],
+                sol_amount: 100_000_000 + i as u64 * 1_000,
+                token_amount: 3_000_000_000_000 + i as u64,
+                is_buy,
+                user: Pubkey::new_unique(),
+                timestamp: 1_735_689_600 + i as i64,
+                virtual_sol_reserves: 31_000_000_000,
+                virtual_token_reserves: 1_038_387_096_774_194,
+                real_sol_reserves: 1_000_000_000,
+                real_token_reserves: 758_487_096_774_194,
+            };
+
+            let mut logs = vec![
+                format!("Program {compute_budget} invoke [1]"),
+                format!("Program {compute_budget} success"),
+                format!("Program {compute_budget} invoke [1]"),
+                format!("Program {compute_budget} success"),
+                format!("Program {PUMPFUN} invoke [1]"),
+                format!(
+                    "Program log: Instruction: {}",
+                    if is_buy { "Buy" } else { "Sell" }
+                ),
+                format!("Program {token} invoke [2]"),
+                "Program log: Instruction: Transfer".to_string(),
+                format!("Program {token} consumed 4645 of 58097 compute units"),
+                format!("Program {token} success"),
+            ];
+            if is_buy {
+                logs.push(format!("Program {system} invoke [2]"));
+                logs.push(format!("Program {system} success"));
+            }
+            logs.push(format!("Program {PUMPFUN} invoke [2]"));
+            logs.push(format!(
+                "Program {PUMPFUN} consumed 2003 of 45798 compute units"
+            ));
+            logs.push(format!("Program {PUMPFUN} success"));
+            logs.push(program_data(&trade));
+            if i % COMPLETE_EVERY == COMPLETE_EVERY - 1 {
+                logs.push(program_data(&CompleteEvent {
+                    user: trade.user,
+                    mint: trade.mint,
+                    bonding_curve: Pubkey::new_unique(),
+                    timestamp: trade.timestamp,
+                }));
+            }
+            logs.push(format!(
+                "Program {PUMPFUN} consumed 34567 of 78000 compute units"
+            ));
+            logs.push(format!("Program {PUMPFUN} success"));
+            logs
+        })
+        .collect()
+}
+
+/// Decodes events the way `parse_logs` did before it rejected lines from their prefix
+fn reference<T: Event>(logs: &[String]) -> Vec<T> {
+    logs.iter()
+        .filter_map(|log| log.strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| STANDARD.decode(data).ok())
+        .filter_map(|data| events::decode_event(&data))
+        .collect()
+}
+
+fn bench_parse_logs(c: &mut Criterion) {
+    let mints: Vec<Pubkey> = (0..MINTS).map(|_| Pubkey::new_unique()).collect();
+    let batch = corpus(&mints);
+    let lines: usize = batch.iter().map(Vec::len).sum();
+
+    let mut group = c.benchmark_group("parse_logs");
+    group.throughput(Throughput::Elements(lines as u64));
+
+    group.bench_function("reference", |b| {
+        b.iter(|| {
+            for logs in &batch {
+                black_box(reference::<TradeEvent>(black_box(logs)));
+            }
+        })
+    });
+    group.bench_function("trades", |b| {
+        b.iter(|| {
+            for logs in &batch {
+                black_box(events::parse_logs::<TradeEvent>(black_box(logs)));
+            }
+        })
+    });
+    group.bench_function("trades_reused_parser", |b| {
+        let mut parser = LogParser::<TradeEvent>::new();
+        b.iter(|| {
+            for logs in &batch {
+                black_box(parser.parse(black_box(logs)));
+            }
+        })
+    });
+
+    // Only one mint is of interest, so most trades are skipped before they are deserialized
+    group.bench_function("trades_of_one_mint", |b| {
+        let mint = mints[0];
+        let mut parser = LogParser::<TradeEvent>::new()
+            .with_filter(move |payload| payload.starts_with(mint.as_ref()));
+        b.iter(|| {
+            for logs in &batch {
+                black_box(parser.parse(black_box(logs)));
+            }
+        })
+    });
+
+    // The mint is compared after decoding the first 56 characters of each line, so trades of
+    // other mints are never decoded in full
+    group.bench_function("trades_of_one_mint_prefix", |b| {
+        let mut parser = LogParser::<TradeEvent>::new().with_payload_prefix(mints[0]);
+        b.iter(|| {
+            for logs in &batch {
+                black_box(parser.parse(black_box(logs)));
+            }
+        })
+    });
+
+    // Trade payloads are rejected from their first characters, without decoding them
+    group.bench_function("completions_reference", |b| {
+        b.iter(|| {
+            for logs in &batch {
+                black_box(reference::<CompleteEvent>(black_box(logs)));
+            }
+        })
+    });
+    group.bench_function("completions", |b| {
+        let mut parser = LogParser::<CompleteEvent>::new();
+        b.iter(|| {
+            for logs in &batch {
+                black_box(parser.parse(black_box(logs)));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse_logs);
+criterion_main!(benches);
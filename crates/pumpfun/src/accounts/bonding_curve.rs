@@ -24,11 +24,178 @@
 //! - `get_market_cap_sol`: Calculates the current market cap in SOL
 //! - `get_final_market_cap_sol`: Calculates the final market cap in SOL after all tokens are sold
 //! - `get_buy_out_price`: Calculates the price to buy out all remaining tokens
+//! - `sol_to_graduate`: Calculates the SOL still required for the curve to complete
+//! - `price_after_buy`: Calculates the spot price the curve would have after a buy
+//! - `price_after_sell`: Calculates the spot price the curve would have after a sell
+//! - `sol_to_reach_market_cap`: Calculates the SOL buy needed to reach a target market cap
+//! - `simulate_sequence`: Applies a sequence of planned trades to a working copy of the reserves
+//! - `depth`: Samples the cumulative amounts obtainable on one side of the curve for a depth chart
+//! - `invariant_k`: Calculates the constant-product invariant `k` of the virtual reserves
+//! - `check_invariant`: Validates the reserves against the initial configuration
+//! - `buy_breakdown`: Splits the SOL spent on a buy into fees, curve input and change
+//! - `buy_breakdown_with_fees`: Same split at fee rates the global account does not hold
+//!
+//! # Conversions
+//!
+//! The account converts to and from `pumpfun_cpi::BondingCurve` with `From`, so state fetched
+//! off-chain can be fed to CPI test code and back. The CPI type has no `discriminator` field:
+//! converting to it drops the field, and converting from it sets the account's Anchor
+//! discriminator. Both types serialize to the same bytes.
 
+use super::{GlobalAccount, ProgramAccount};
 use borsh::{BorshDeserialize, BorshSerialize};
+use pumpfun_cpi as cpi;
+use solana_sdk::pubkey::Pubkey;
+
+/// Offset of the creator in the data of bonding curves created by versions with creator vaults
+///
+/// Those versions store it right after the fields of the original layout.
+const CREATOR_OFFSET: usize = 49;
+
+/// Errors from applying trades to a bonding curve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CurveError {
+    /// The curve has completed and no longer accepts trades
+    Complete,
+    /// The target market cap is below the current market cap
+    TargetBelowCurrent,
+    /// The target market cap is beyond the market cap at graduation
+    TargetBeyondGraduation,
+    /// A planned trade in a sequence could not be applied
+    InfeasibleTrade {
+        /// Position of the trade in the sequence
+        index: usize,
+        /// Why the trade could not be applied
+        reason: &'static str,
+    },
+    /// The invariant is below the initial invariant, which no sequence of trades can produce
+    InvariantBelowInitial {
+        /// Invariant of the curve
+        invariant: u128,
+        /// Invariant the curve was created with
+        initial: u128,
+    },
+    /// The virtual and real reserves are offset differently than at creation
+    ReserveOffsetMismatch,
+}
+
+impl std::fmt::Display for CurveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Complete => write!(f, "Curve is complete"),
+            Self::TargetBelowCurrent => write!(f, "Target is below the current market cap"),
+            Self::TargetBeyondGraduation => {
+                write!(f, "Target is beyond the market cap at graduation")
+            }
+            Self::InfeasibleTrade { index, reason } => {
+                write!(f, "Trade {} is infeasible: {}", index, reason)
+            }
+            Self::InvariantBelowInitial { invariant, initial } => {
+                write!(
+                    f,
+                    "Invariant {} is below the initial {}",
+                    invariant, initial
+                )
+            }
+            Self::ReserveOffsetMismatch => {
+                write!(f, "Virtual and real reserves are offset unexpectedly")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
+
+/// A trade to apply to a bonding curve in a simulated sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlannedTrade {
+    /// Buy tokens by spending the given amount of SOL in lamports (before fees)
+    Buy {
+        /// Amount of SOL to spend
+        sol_amount: u64,
+    },
+    /// Sell the given amount of tokens in base units
+    Sell {
+        /// Amount of tokens to sell
+        token_amount: u64,
+    },
+}
+
+/// Fill of a single trade in a simulated sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradeFill {
+    /// Amount of tokens bought or sold
+    pub token_amount: u64,
+    /// Amount of SOL that entered (buy) or left (sell) the curve, excluding fees
+    pub sol_amount: u64,
+    /// Fee charged on the trade in lamports
+    pub fee: u64,
+}
+
+/// Projected result of a simulated trade sequence
+#[derive(Debug, Clone)]
+pub struct SequenceOutcome {
+    /// Fill of each trade, in sequence order
+    pub fills: Vec<TradeFill>,
+    /// Total fees charged across all trades in lamports
+    pub total_fees: u64,
+    /// State of the curve after the last trade
+    pub final_curve: BondingCurveAccount,
+    /// Spot price after the last trade in lamports per token base unit
+    pub final_price: f64,
+}
+
+/// Side of the curve sampled by a depth chart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthSide {
+    /// Spend SOL to receive tokens
+    Buy,
+    /// Spend tokens to receive SOL
+    Sell,
+}
+
+/// A point on a depth chart
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthPoint {
+    /// Marginal price after trading up to this point, in lamports per token base unit
+    pub price: f64,
+    /// Cumulative amount paid in: SOL including fees for buys, tokens for sells
+    pub cumulative_in: u64,
+    /// Cumulative amount received: tokens for buys, SOL after fees for sells
+    pub cumulative_out: u64,
+}
+
+/// Allocation of the SOL spent on a buy
+///
+/// `protocol_fee + creator_fee + curve_sol + unspent` always adds up to `sol_in`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuyBreakdown {
+    /// Total SOL spent in lamports, including fees
+    pub sol_in: u64,
+    /// Fee paid to the protocol fee recipient in lamports
+    pub protocol_fee: u64,
+    /// Fee paid to the token creator in lamports
+    pub creator_fee: u64,
+    /// SOL that enters the curve's reserves in lamports
+    pub curve_sol: u64,
+    /// SOL left over by rounding or the curve's remaining supply in lamports
+    pub unspent: u64,
+    /// Amount of tokens received in base units
+    pub token_amount: u64,
+}
 
 /// Represents a bonding curve for token pricing and liquidity management
-#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    serde::Serialize,
+    serde::Deserialize,
+)]
 pub struct BondingCurveAccount {
     /// Unique identifier for the bonding curve
     pub discriminator: u64,
@@ -103,8 +270,8 @@ impl BondingCurveAccount {
         // Calculate the new virtual token reserves after the purchase
         let r: u128 = n / i + 1;
 
-        // Calculate the amount of tokens to be purchased
-        let s: u128 = (self.virtual_token_reserves as u128) - r;
+        // Calculate the amount of tokens to be purchased, none if the curve has no tokens
+        let s: u128 = (self.virtual_token_reserves as u128).saturating_sub(r);
 
         // Convert back to u64 and return the minimum of calculated tokens and real reserves
         let s_u64 = s as u64;
@@ -141,7 +308,7 @@ impl BondingCurveAccount {
         let a: u128 = (n * (fee_basis_points as u128)) / 10000;
 
         // Return the net amount after deducting the fee, converting back to u64
-        Ok((n - a) as u64)
+        Ok(n.saturating_sub(a) as u64)
     }
 
     /// Calculates the current market cap in SOL
@@ -150,8 +317,11 @@ impl BondingCurveAccount {
             return 0;
         }
 
-        ((self.token_total_supply as u128) * (self.virtual_sol_reserves as u128)
-            / (self.virtual_token_reserves as u128)) as u64
+        let market_cap: u128 = (self.token_total_supply as u128)
+            * (self.virtual_sol_reserves as u128)
+            / (self.virtual_token_reserves as u128);
+
+        market_cap.min(u64::MAX as u128) as u64
     }
 
     /// Calculates the final market cap in SOL after all tokens are sold
@@ -174,27 +344,522 @@ impl BondingCurveAccount {
 
     /// Calculates the price to buy out all remaining tokens
     ///
+    /// Amounts above the real token reserves are capped, since the curve cannot give out more.
+    ///
     /// # Arguments
     /// * `amount` - Amount of tokens to buy
     /// * `fee_basis_points` - Fee in basis points (1/100th of a percent)
     pub fn get_buy_out_price(&self, amount: u64, fee_basis_points: u64) -> u64 {
-        // Get the effective amount of sol tokens
-        let sol_tokens: u128 = if amount < self.real_sol_reserves {
-            self.real_sol_reserves as u128
-        } else {
-            amount as u128
-        };
+        // Get the effective amount of tokens the curve can give out
+        let tokens: u128 = amount.min(self.real_token_reserves) as u128;
 
-        // Calculate total sell value
-        let total_sell_value: u128 = (sol_tokens * (self.virtual_sol_reserves as u128))
-            / ((self.virtual_token_reserves as u128) - sol_tokens)
-            + 1;
+        // The curve cannot give out all of its virtual tokens at any price
+        let remaining: u128 = (self.virtual_token_reserves as u128).saturating_sub(tokens);
+        if remaining == 0 {
+            return u64::MAX;
+        }
+
+        // Calculate total sell value, capped so the fee cannot overflow; the total saturates anyway
+        let total_sell_value: u128 =
+            ((tokens * (self.virtual_sol_reserves as u128)) / remaining + 1).min(u64::MAX as u128);
 
         // Calculate fee
         let fee: u128 = (total_sell_value * (fee_basis_points as u128)) / 10000;
 
-        // Return total including fee, converting back to u64
-        (total_sell_value + fee) as u64
+        // Return total including fee, saturating at the largest u64
+        (total_sell_value + fee).min(u64::MAX as u128) as u64
+    }
+
+    /// Calculates the SOL still required for the curve to complete
+    ///
+    /// This is the amount of SOL (before fees) that must flow into the curve to buy all
+    /// remaining real token reserves, at which point the curve graduates.
+    ///
+    /// # Returns
+    /// Amount of SOL in lamports, or 0 if the curve is already complete
+    pub fn sol_to_graduate(&self) -> u64 {
+        if self.complete || self.real_token_reserves == 0 {
+            return 0;
+        }
+
+        // Remaining virtual tokens once all real tokens are bought
+        let remaining: u128 =
+            (self.virtual_token_reserves as u128).saturating_sub(self.real_token_reserves as u128);
+        if remaining == 0 {
+            return u64::MAX;
+        }
+
+        // SOL in required to take the real token reserves out of the curve
+        let sol: u128 = (self.real_token_reserves as u128) * (self.virtual_sol_reserves as u128)
+            / remaining
+            + 1;
+
+        sol.min(u64::MAX as u128) as u64
+    }
+
+    /// Reads the creator from the raw data of a bonding curve account
+    ///
+    /// # Arguments
+    /// * `data` - Account data as fetched from the cluster
+    ///
+    /// # Returns
+    /// The creator stored after the original fields, or None if the curve predates creator vaults
+    pub(crate) fn creator_from_data(data: &[u8]) -> Option<Pubkey> {
+        let creator = data.get(CREATOR_OFFSET..CREATOR_OFFSET + 32)?;
+        Some(Pubkey::try_from(creator).expect("slice of 32 bytes"))
+    }
+
+    /// Calculates the constant-product invariant of the virtual reserves
+    ///
+    /// The invariant barely moves over a curve's life, so it identifies the global parameters
+    /// the curve was created under, see [`crate::analytics::ParameterEra`].
+    ///
+    /// # Returns
+    /// `k = virtual_sol_reserves * virtual_token_reserves`, which cannot overflow a u128
+    pub fn invariant_k(&self) -> u128 {
+        (self.virtual_sol_reserves as u128) * (self.virtual_token_reserves as u128)
+    }
+
+    /// Validates the reserves against the configuration the curve was created with
+    ///
+    /// Fees are paid to the fee recipient outside the reserves, so they need no adjustment: a
+    /// valid curve's invariant starts at the global account's initial invariant and only grows
+    /// through rounding, which always favours the curve. Each trade adds less than one unit of
+    /// the larger virtual reserve, so in practice `k` stays within a tiny fraction of the
+    /// initial value (about 1e-10 per trade at the default reserves); a valid range is
+    /// `initial <= k`, with growth proportional to the number of trades. Trades also move the
+    /// virtual and real reserves together, so their differences stay what they were at creation.
+    ///
+    /// Compare against the global account as it was when the curve was created; curves created
+    /// before a parameter change will not match the current one. Completed curves whose
+    /// reserves have been migrated no longer satisfy these checks.
+    ///
+    /// # Arguments
+    /// * `global` - Global account the curve was created under
+    ///
+    /// # Returns
+    /// * `Ok(())` - The reserves are consistent with the initial configuration
+    /// * `Err(CurveError)` - The invariant fell below the initial one, or the reserves are
+    ///   offset differently than at creation, indicating corrupted or manipulated state
+    pub fn check_invariant(&self, global: &GlobalAccount) -> Result<(), CurveError> {
+        let initial = global.initial_invariant_k();
+        let invariant = self.invariant_k();
+        if invariant < initial {
+            return Err(CurveError::InvariantBelowInitial { invariant, initial });
+        }
+
+        let token_offset = global
+            .initial_virtual_token_reserves
+            .checked_sub(global.initial_real_token_reserves);
+        let sol_offset = Some(global.initial_virtual_sol_reserves);
+        if self
+            .virtual_token_reserves
+            .checked_sub(self.real_token_reserves)
+            != token_offset
+            || self
+                .virtual_sol_reserves
+                .checked_sub(self.real_sol_reserves)
+                != sol_offset
+        {
+            return Err(CurveError::ReserveOffsetMismatch);
+        }
+
+        Ok(())
+    }
+
+    /// Calculates the smallest SOL buy that pushes the market cap to a target
+    ///
+    /// This inverts `get_market_cap_sol` over the curve by searching the buy sizes up to
+    /// `sol_to_graduate`, applying each with the program's own math.
+    ///
+    /// # Arguments
+    /// * `target_mcap_sol` - Target market cap in lamports
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - SOL to spend in lamports, excluding fees; 0 if the target is already reached
+    /// * `Err(CurveError)` - If the curve is complete, the target is below the current market
+    ///   cap, or the curve graduates before reaching it
+    pub fn sol_to_reach_market_cap(&self, target_mcap_sol: u64) -> Result<u64, CurveError> {
+        if self.complete {
+            return Err(CurveError::Complete);
+        }
+
+        let current = self.get_market_cap_sol();
+        if target_mcap_sol < current {
+            return Err(CurveError::TargetBelowCurrent);
+        }
+        if target_mcap_sol == current {
+            return Ok(0);
+        }
+
+        let market_cap_after = |sol_amount: u64| {
+            self.simulate_sequence(&[PlannedTrade::Buy { sol_amount }], 0)
+                .map(|outcome| outcome.final_curve.get_market_cap_sol())
+        };
+
+        // The market cap only grows with the buy size, so binary search for the smallest buy
+        let mut high = self.sol_to_graduate();
+        if market_cap_after(high)? < target_mcap_sol {
+            return Err(CurveError::TargetBeyondGraduation);
+        }
+        let mut low = 0;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if market_cap_after(mid)? >= target_mcap_sol {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        Ok(high)
+    }
+
+    /// Applies a sequence of planned trades to a working copy of the reserves
+    ///
+    /// Trades are applied in order using the program's own math: buys take tokens out of the
+    /// curve for the SOL spent (capped at the real token reserves, completing the curve when
+    /// they run out) and sells return tokens for SOL. This is pure and never touches the network.
+    ///
+    /// # Arguments
+    /// * `trades` - Trades to apply in order
+    /// * `fee_basis_points` - Fee in basis points (1/100th of a percent)
+    ///
+    /// # Returns
+    /// * `Ok(SequenceOutcome)` - Per-trade fills, cumulative fees and the final reserves and price
+    /// * `Err(CurveError)` - The index of the first trade that cannot be applied
+    pub fn simulate_sequence(
+        &self,
+        trades: &[PlannedTrade],
+        fee_basis_points: u64,
+    ) -> Result<SequenceOutcome, CurveError> {
+        let mut curve = self.clone();
+        let mut fills = Vec::with_capacity(trades.len());
+        let mut total_fees: u64 = 0;
+
+        for (index, trade) in trades.iter().enumerate() {
+            let infeasible = |reason| CurveError::InfeasibleTrade { index, reason };
+            if curve.complete {
+                return Err(infeasible("Curve is complete"));
+            }
+
+            let fill = match *trade {
+                PlannedTrade::Buy { sol_amount } => {
+                    let token_amount = curve.get_buy_price(sol_amount).map_err(infeasible)?;
+                    if token_amount == 0 {
+                        TradeFill {
+                            token_amount: 0,
+                            sol_amount: 0,
+                            fee: 0,
+                        }
+                    } else {
+                        // SOL charged by the program for the tokens received
+                        let sol_cost: u128 = ((token_amount as u128)
+                            * (curve.virtual_sol_reserves as u128))
+                            .checked_div(
+                                (curve.virtual_token_reserves as u128)
+                                    .saturating_sub(token_amount as u128),
+                            )
+                            .ok_or(infeasible("No token reserves left to price against"))?
+                            + 1;
+                        let sol_cost = u64::try_from(sol_cost)
+                            .map_err(|_| infeasible("SOL cost overflows"))?;
+                        let fee = ((sol_cost as u128) * (fee_basis_points as u128) / 10000) as u64;
+
+                        curve.virtual_token_reserves -= token_amount;
+                        curve.real_token_reserves -= token_amount;
+                        curve.virtual_sol_reserves = curve
+                            .virtual_sol_reserves
+                            .checked_add(sol_cost)
+                            .ok_or(infeasible("SOL reserves overflow"))?;
+                        curve.real_sol_reserves = curve
+                            .real_sol_reserves
+                            .checked_add(sol_cost)
+                            .ok_or(infeasible("SOL reserves overflow"))?;
+                        if curve.real_token_reserves == 0 {
+                            curve.complete = true;
+                        }
+
+                        TradeFill {
+                            token_amount,
+                            sol_amount: sol_cost,
+                            fee,
+                        }
+                    }
+                }
+                PlannedTrade::Sell { token_amount } => {
+                    // SOL paid out by the curve before fees
+                    let sol_output: u128 = ((token_amount as u128)
+                        * (curve.virtual_sol_reserves as u128))
+                        .checked_div(
+                            (curve.virtual_token_reserves as u128) + (token_amount as u128),
+                        )
+                        .ok_or(infeasible("No token reserves to price against"))?;
+                    let sol_output = sol_output as u64;
+                    if sol_output > curve.real_sol_reserves {
+                        return Err(infeasible("Not enough SOL in the curve"));
+                    }
+                    let fee = ((sol_output as u128) * (fee_basis_points as u128) / 10000) as u64;
+
+                    curve.virtual_token_reserves = curve
+                        .virtual_token_reserves
+                        .checked_add(token_amount)
+                        .ok_or(infeasible("Token reserves overflow"))?;
+                    curve.real_token_reserves = curve
+                        .real_token_reserves
+                        .checked_add(token_amount)
+                        .ok_or(infeasible("Token reserves overflow"))?;
+                    curve.virtual_sol_reserves -= sol_output;
+                    curve.real_sol_reserves -= sol_output;
+
+                    TradeFill {
+                        token_amount,
+                        sol_amount: sol_output,
+                        fee,
+                    }
+                }
+            };
+
+            total_fees = total_fees.saturating_add(fill.fee);
+            fills.push(fill);
+        }
+
+        let final_price = if curve.virtual_token_reserves == 0 {
+            0.0
+        } else {
+            curve.virtual_sol_reserves as f64 / curve.virtual_token_reserves as f64
+        };
+
+        Ok(SequenceOutcome {
+            fills,
+            total_fees,
+            final_curve: curve,
+            final_price,
+        })
+    }
+
+    /// Calculates the spot price the curve would have after a buy executes
+    ///
+    /// The reserves are advanced by the SOL reaching the curve, so fees are not included in
+    /// `sol_in`. Chaining calls on the resulting curves models cascading buys.
+    ///
+    /// # Arguments
+    /// * `sol_in` - Amount of SOL spent in lamports, excluding fees
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - Spot price after the buy in lamports per token base unit
+    /// * `Err(CurveError)` - If the curve is complete or the buy cannot be applied
+    pub fn price_after_buy(&self, sol_in: u64) -> Result<f64, CurveError> {
+        self.price_after(PlannedTrade::Buy { sol_amount: sol_in })
+    }
+
+    /// Calculates the spot price the curve would have after a sell executes
+    ///
+    /// # Arguments
+    /// * `token_in` - Amount of tokens sold in base units
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - Spot price after the sell in lamports per token base unit
+    /// * `Err(CurveError)` - If the curve is complete or cannot pay out the sell
+    pub fn price_after_sell(&self, token_in: u64) -> Result<f64, CurveError> {
+        self.price_after(PlannedTrade::Sell {
+            token_amount: token_in,
+        })
+    }
+
+    /// Splits the SOL spent on a buy into fees, curve input and change
+    ///
+    /// The program charges its fees on top of the SOL that enters the curve, so `sol_in` is
+    /// treated as an all-in budget: the largest curve input whose fees still fit in the budget
+    /// is bought, and each fee is charged on the SOL the tokens actually cost. A complete curve
+    /// takes nothing, leaving all of `sol_in` unspent.
+    ///
+    /// # Arguments
+    /// * `sol_in` - Total SOL to spend in lamports, including fees
+    /// * `global` - Global account holding the fee configuration
+    ///
+    /// # Returns
+    /// Where each lamport of `sol_in` goes and the tokens received
+    pub fn buy_breakdown(&self, sol_in: u64, global: &GlobalAccount) -> BuyBreakdown {
+        self.buy_breakdown_with_fees(
+            sol_in,
+            global.fee_basis_points,
+            global.creator_fee_basis_points(),
+        )
+    }
+
+    /// Splits the SOL spent on a buy into fees, curve input and change, at the given fee rates
+    ///
+    /// Same as [`BondingCurveAccount::buy_breakdown`], for fee rates the global account does
+    /// not hold, such as a creator fee set by a newer program version.
+    ///
+    /// # Arguments
+    /// * `sol_in` - Total SOL to spend in lamports, including fees
+    /// * `fee_basis_points` - Protocol fee in basis points
+    /// * `creator_fee_basis_points` - Creator fee in basis points
+    ///
+    /// # Returns
+    /// Where each lamport of `sol_in` goes and the tokens received
+    pub fn buy_breakdown_with_fees(
+        &self,
+        sol_in: u64,
+        fee_basis_points: u64,
+        creator_fee_basis_points: u64,
+    ) -> BuyBreakdown {
+        let fee = |amount: u64, basis_points: u64| {
+            ((amount as u128) * (basis_points as u128) / 10000) as u64
+        };
+        let fees = |amount: u64| {
+            fee(amount, fee_basis_points).saturating_add(fee(amount, creator_fee_basis_points))
+        };
+        let total_fee_bps = fee_basis_points.saturating_add(creator_fee_basis_points);
+
+        // Start from the budget net of the all-in fee rate, then step down past rounding
+        let mut budget = ((sol_in as u128) * 10000).div_ceil(10000 + total_fee_bps as u128) as u64;
+        while budget > 0 && budget.saturating_add(fees(budget)) > sol_in {
+            budget -= 1;
+        }
+
+        let fill = self
+            .simulate_sequence(&[PlannedTrade::Buy { sol_amount: budget }], 0)
+            .map(|outcome| outcome.fills[0])
+            .unwrap_or(TradeFill {
+                token_amount: 0,
+                sol_amount: 0,
+                fee: 0,
+            });
+        let protocol_fee = fee(fill.sol_amount, fee_basis_points);
+        let creator_fee = fee(fill.sol_amount, creator_fee_basis_points);
+
+        BuyBreakdown {
+            sol_in,
+            protocol_fee,
+            creator_fee,
+            curve_sol: fill.sol_amount,
+            unspent: sol_in.saturating_sub(fill.sol_amount + protocol_fee + creator_fee),
+            token_amount: fill.token_amount,
+        }
+    }
+
+    /// Spot price after a single trade, which fees do not affect
+    fn price_after(&self, trade: PlannedTrade) -> Result<f64, CurveError> {
+        if self.complete {
+            return Err(CurveError::Complete);
+        }
+
+        self.simulate_sequence(&[trade], 0)
+            .map(|outcome| outcome.final_price)
+    }
+
+    /// Samples the curve for a depth chart
+    ///
+    /// Splits `max_amount` into `steps` equal increments and computes, for each cumulative
+    /// amount, what a single trade of that size would receive and the marginal price it would
+    /// leave behind. Buys pay the fee on top of the SOL that reaches the curve; sells pay it out
+    /// of the SOL received. Sampling stops early once the curve's real reserves are exhausted.
+    ///
+    /// # Arguments
+    /// * `side` - Side of the curve to sample
+    /// * `steps` - Number of increments to sample
+    /// * `max_amount` - Total SOL budget for buys, or total tokens for sells
+    /// * `fee_basis_points` - Fee in basis points (1/100th of a percent)
+    ///
+    /// # Returns
+    /// The starting point followed by up to `steps` points in increasing order, or an empty
+    /// vector if the curve is complete or `steps` is zero
+    pub fn depth(
+        &self,
+        side: DepthSide,
+        steps: usize,
+        max_amount: u64,
+        fee_basis_points: u64,
+    ) -> Vec<DepthPoint> {
+        if self.complete || steps == 0 {
+            return Vec::new();
+        }
+
+        let virtual_sol = self.virtual_sol_reserves as u128;
+        let virtual_token = self.virtual_token_reserves as u128;
+        let price = |sol: u128, token: u128| {
+            if token == 0 {
+                0.0
+            } else {
+                sol as f64 / token as f64
+            }
+        };
+
+        let mut points = Vec::with_capacity(steps + 1);
+        points.push(DepthPoint {
+            price: price(virtual_sol, virtual_token),
+            cumulative_in: 0,
+            cumulative_out: 0,
+        });
+
+        for step in 1..=steps {
+            let amount_in = (max_amount as u128) * (step as u128) / (steps as u128);
+
+            let (amount_out, sol_after, token_after, exhausted) = match side {
+                DepthSide::Buy => {
+                    // Only the SOL left after the fee moves the curve
+                    let sol = amount_in * 10000 / (10000 + fee_basis_points as u128);
+                    let tokens = virtual_token
+                        .saturating_sub(virtual_sol * virtual_token / (virtual_sol + sol) + 1);
+                    let exhausted = tokens >= self.real_token_reserves as u128;
+                    let tokens = tokens.min(self.real_token_reserves as u128);
+                    (tokens, virtual_sol + sol, virtual_token - tokens, exhausted)
+                }
+                DepthSide::Sell => {
+                    let sol = amount_in * virtual_sol / (virtual_token + amount_in);
+                    let exhausted = sol >= self.real_sol_reserves as u128;
+                    let sol = sol.min(self.real_sol_reserves as u128);
+                    let fee = sol * (fee_basis_points as u128) / 10000;
+                    (
+                        sol - fee,
+                        virtual_sol - sol,
+                        virtual_token + amount_in,
+                        exhausted,
+                    )
+                }
+            };
+
+            points.push(DepthPoint {
+                price: price(sol_after, token_after),
+                cumulative_in: amount_in as u64,
+                cumulative_out: amount_out as u64,
+            });
+            if exhausted {
+                break;
+            }
+        }
+
+        points
+    }
+}
+
+impl From<cpi::BondingCurve> for BondingCurveAccount {
+    fn from(curve: cpi::BondingCurve) -> Self {
+        Self {
+            discriminator: u64::from_le_bytes(Self::DISCRIMINATOR),
+            virtual_token_reserves: curve.virtual_token_reserves,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            real_token_reserves: curve.real_token_reserves,
+            real_sol_reserves: curve.real_sol_reserves,
+            token_total_supply: curve.token_total_supply,
+            complete: curve.complete,
+        }
+    }
+}
+
+impl From<BondingCurveAccount> for cpi::BondingCurve {
+    fn from(curve: BondingCurveAccount) -> Self {
+        Self {
+            virtual_token_reserves: curve.virtual_token_reserves,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            real_token_reserves: curve.real_token_reserves,
+            real_sol_reserves: curve.real_sol_reserves,
+            token_total_supply: curve.token_total_supply,
+            complete: curve.complete,
+        }
     }
 }
 
@@ -276,12 +941,621 @@ mod tests {
     fn test_buy_out_price() {
         let bonding_curve: BondingCurveAccount = get_bonding_curve();
 
+        // 100 * 1000 / 900 + 1 = 112 lamports, plus a 2 lamport fee
         let buy_out_price = bonding_curve.get_buy_out_price(100, 250);
-        assert!(buy_out_price > 0);
+        assert_eq!(buy_out_price, 114);
 
-        // Test with amount less than real_sol_reserves
-        let small_buy_out = bonding_curve.get_buy_out_price(400, 250);
-        assert!(small_buy_out > 0);
+        // 400 * 1000 / 600 + 1 = 667 lamports, plus a 16 lamport fee
+        let larger_buy_out = bonding_curve.get_buy_out_price(400, 250);
+        assert_eq!(larger_buy_out, 683);
+
+        // Amounts beyond the real token reserves are capped at them
+        assert_eq!(
+            bonding_curve.get_buy_out_price(u64::MAX, 250),
+            bonding_curve.get_buy_out_price(500, 250)
+        );
+    }
+
+    #[test]
+    fn test_sol_to_graduate() {
+        let mut bonding_curve: BondingCurveAccount = get_bonding_curve();
+
+        // Buying with the SOL needed to graduate takes all real token reserves
+        let sol = bonding_curve.sol_to_graduate();
+        assert_eq!(sol, 1001);
+        assert_eq!(
+            bonding_curve.get_buy_price(sol).unwrap(),
+            bonding_curve.real_token_reserves
+        );
+
+        // A complete curve needs nothing more
+        bonding_curve.complete = true;
+        assert_eq!(bonding_curve.sol_to_graduate(), 0);
+    }
+
+    #[test]
+    fn test_invariant() {
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Default::default(),
+            Default::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        );
+        let curve = BondingCurveAccount::new(
+            1,
+            global.initial_virtual_token_reserves,
+            global.initial_virtual_sol_reserves,
+            global.initial_real_token_reserves,
+            0,
+            global.token_total_supply,
+            false,
+        );
+        let initial = curve.invariant_k();
+        assert_eq!(initial, 30_000_000_000u128 * 1_073_000_000_000_000);
+        assert_eq!(curve.check_invariant(&global), Ok(()));
+
+        // Trades only grow k through rounding, and keep the reserve offsets
+        let trades = [
+            PlannedTrade::Buy {
+                sol_amount: 2_000_000_000,
+            },
+            PlannedTrade::Sell {
+                token_amount: 10_000_000_000_000,
+            },
+            PlannedTrade::Buy {
+                sol_amount: 7_777_777,
+            },
+        ];
+        let traded = curve.simulate_sequence(&trades, 100).unwrap().final_curve;
+        assert!(traded.invariant_k() >= initial);
+        assert!(traded.invariant_k() - initial < 3 * initial / 1_000_000_000);
+        assert_eq!(traded.check_invariant(&global), Ok(()));
+
+        // Reserves drained without a matching trade
+        let mut drained = traded.clone();
+        drained.virtual_sol_reserves -= 1_000_000_000;
+        assert!(matches!(
+            drained.check_invariant(&global),
+            Err(CurveError::InvariantBelowInitial { .. })
+        ));
+
+        let mut skewed = traded.clone();
+        skewed.real_sol_reserves += 1;
+        assert_eq!(
+            skewed.check_invariant(&global),
+            Err(CurveError::ReserveOffsetMismatch)
+        );
+    }
+
+    #[test]
+    fn test_simulate_sequence() {
+        let bonding_curve: BondingCurveAccount = get_bonding_curve();
+        let trades = [
+            PlannedTrade::Buy { sol_amount: 100 },
+            PlannedTrade::Buy { sol_amount: 100 },
+            PlannedTrade::Sell { token_amount: 50 },
+        ];
+        let outcome = bonding_curve.simulate_sequence(&trades, 100).unwrap();
+
+        assert_eq!(outcome.fills.len(), 3);
+
+        // The first fill matches the single-shot quote
+        assert_eq!(
+            outcome.fills[0].token_amount,
+            bonding_curve.get_buy_price(100).unwrap()
+        );
+
+        // The second buy gets fewer tokens at the higher price
+        assert!(outcome.fills[1].token_amount < outcome.fills[0].token_amount);
+
+        // Reserves reflect every fill
+        let bought = outcome.fills[0].token_amount + outcome.fills[1].token_amount;
+        let curve = &outcome.final_curve;
+        assert_eq!(
+            curve.virtual_token_reserves,
+            bonding_curve.virtual_token_reserves - bought + 50
+        );
+        assert_eq!(
+            curve.real_sol_reserves,
+            bonding_curve.real_sol_reserves
+                + outcome.fills[0].sol_amount
+                + outcome.fills[1].sol_amount
+                - outcome.fills[2].sol_amount
+        );
+        assert_eq!(
+            outcome.total_fees,
+            outcome.fills.iter().map(|fill| fill.fee).sum::<u64>()
+        );
+        assert_eq!(
+            outcome.final_price,
+            curve.virtual_sol_reserves as f64 / curve.virtual_token_reserves as f64
+        );
+
+        // The original curve is untouched
+        assert_eq!(
+            bonding_curve.virtual_token_reserves,
+            get_bonding_curve().virtual_token_reserves
+        );
+    }
+
+    #[test]
+    fn test_simulate_sequence_infeasible() {
+        let bonding_curve: BondingCurveAccount = get_bonding_curve();
+
+        // Buying out the real reserves completes the curve, so the next trade fails
+        let trades = [
+            PlannedTrade::Buy { sol_amount: 10_000 },
+            PlannedTrade::Buy { sol_amount: 1 },
+        ];
+        let error = bonding_curve.simulate_sequence(&trades, 100).unwrap_err();
+        assert_eq!(
+            error,
+            CurveError::InfeasibleTrade {
+                index: 1,
+                reason: "Curve is complete"
+            }
+        );
+
+        // Selling more than the curve can pay out
+        let trades = [PlannedTrade::Sell {
+            token_amount: 10_000,
+        }];
+        let error = bonding_curve.simulate_sequence(&trades, 100).unwrap_err();
+        assert!(matches!(
+            error,
+            CurveError::InfeasibleTrade { index: 0, .. }
+        ));
+
+        // A curve without token reserves cannot price a trade, rather than dividing by zero
+        let empty = BondingCurveAccount::new(1, 0, 0, 0, 0, 0, false);
+        let error = empty
+            .simulate_sequence(&[PlannedTrade::Sell { token_amount: 0 }], 100)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            CurveError::InfeasibleTrade {
+                index: 0,
+                reason: "No token reserves to price against"
+            }
+        );
+    }
+
+    #[test]
+    fn test_price_after_trades() {
+        // A freshly created curve, then the states expected after buys of 1 and 2 SOL and a
+        // sell of 50M tokens
+        let curve = BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+        let recorded = [
+            (31_000_000_000_u64, 1_038_387_096_774_194_u64),
+            (33_000_000_000, 975_454_545_454_546),
+            (31_390_957_447, 1_025_454_545_454_546),
+        ];
+        let price = |(sol, token): (u64, u64)| sol as f64 / token as f64;
+
+        assert_eq!(
+            curve.price_after_buy(1_000_000_000).unwrap(),
+            price(recorded[0])
+        );
+
+        // Cascading trades chained through the simulated reserves
+        let trades = [
+            PlannedTrade::Buy {
+                sol_amount: 1_000_000_000,
+            },
+            PlannedTrade::Buy {
+                sol_amount: 2_000_000_000,
+            },
+            PlannedTrade::Sell {
+                token_amount: 50_000_000_000_000,
+            },
+        ];
+        let mut current = curve.clone();
+        for (trade, state) in trades.iter().zip(recorded) {
+            let next_price = match *trade {
+                PlannedTrade::Buy { sol_amount } => current.price_after_buy(sol_amount),
+                PlannedTrade::Sell { token_amount } => current.price_after_sell(token_amount),
+            }
+            .unwrap();
+            assert_eq!(next_price, price(state));
+
+            current = current.simulate_sequence(&[*trade], 0).unwrap().final_curve;
+            assert_eq!(
+                (current.virtual_sol_reserves, current.virtual_token_reserves),
+                state
+            );
+        }
+
+        // Buys raise the price and sells lower it
+        let spot = price((curve.virtual_sol_reserves, curve.virtual_token_reserves));
+        assert!(curve.price_after_buy(1).unwrap() >= spot);
+        assert!(current.price_after_sell(1_000_000).unwrap() < price(recorded[2]));
+        assert_eq!(curve.price_after_buy(0).unwrap(), spot);
+
+        // A fresh curve has no SOL to pay out, and a complete curve takes no trades
+        assert!(matches!(
+            curve.price_after_sell(1_000_000),
+            Err(CurveError::InfeasibleTrade { index: 0, .. })
+        ));
+        let mut complete = curve.clone();
+        complete.complete = true;
+        assert_eq!(complete.price_after_buy(1), Err(CurveError::Complete));
+        assert_eq!(complete.price_after_sell(1), Err(CurveError::Complete));
+    }
+
+    /// Synthetic trades with the curve state each executes against, in the shape of the
+    /// program's `TradeEvent`. They were computed from the program's constant-product formula
+    /// rather than captured from chain, so they pin the quote math against hand-checked values
+    /// and not against fills of the deployed program
+    const SYNTHETIC_TRADES: &str = include_str!("fixtures/synthetic_trades.json");
+
+    #[derive(serde::Deserialize)]
+    struct SyntheticCurve {
+        virtual_token_reserves: u64,
+        virtual_sol_reserves: u64,
+        real_token_reserves: u64,
+        real_sol_reserves: u64,
+        token_total_supply: u64,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SyntheticEvent {
+        is_buy: bool,
+        sol_amount: u64,
+        token_amount: u64,
+        virtual_sol_reserves: u64,
+        virtual_token_reserves: u64,
+        real_sol_reserves: u64,
+        real_token_reserves: u64,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SyntheticTrade {
+        fee_basis_points: u64,
+        curve: SyntheticCurve,
+        event: SyntheticEvent,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct SyntheticTrades {
+        trades: Vec<SyntheticTrade>,
+    }
+
+    #[test]
+    fn test_quotes_match_synthetic_fills() {
+        let trades = serde_json::from_str::<SyntheticTrades>(SYNTHETIC_TRADES)
+            .unwrap()
+            .trades;
+        assert!(!trades.is_empty());
+
+        for (index, trade) in trades.iter().enumerate() {
+            let curve = BondingCurveAccount::new(
+                1,
+                trade.curve.virtual_token_reserves,
+                trade.curve.virtual_sol_reserves,
+                trade.curve.real_token_reserves,
+                trade.curve.real_sol_reserves,
+                trade.curve.token_total_supply,
+                false,
+            );
+            let event = &trade.event;
+
+            // `quote_buy` and `quote_sell` apply exactly this math to the fetched curve
+            if event.is_buy {
+                assert_eq!(
+                    curve.get_buy_price(event.sol_amount).unwrap(),
+                    event.token_amount,
+                    "buy {} diverged",
+                    index
+                );
+            } else {
+                let fee = event.sol_amount * trade.fee_basis_points / 10000;
+                assert_eq!(
+                    curve
+                        .get_sell_price(event.token_amount, trade.fee_basis_points)
+                        .unwrap(),
+                    event.sol_amount - fee,
+                    "sell {} diverged",
+                    index
+                );
+            }
+
+            // The simulated reserves land on the state the program reported
+            let trade_plan = if event.is_buy {
+                PlannedTrade::Buy {
+                    sol_amount: event.sol_amount,
+                }
+            } else {
+                PlannedTrade::Sell {
+                    token_amount: event.token_amount,
+                }
+            };
+            let outcome = curve
+                .simulate_sequence(&[trade_plan], trade.fee_basis_points)
+                .unwrap();
+            assert_eq!(outcome.fills[0].token_amount, event.token_amount);
+            assert_eq!(outcome.fills[0].sol_amount, event.sol_amount);
+            let after = &outcome.final_curve;
+            assert_eq!(
+                (
+                    after.virtual_sol_reserves,
+                    after.virtual_token_reserves,
+                    after.real_sol_reserves,
+                    after.real_token_reserves,
+                ),
+                (
+                    event.virtual_sol_reserves,
+                    event.virtual_token_reserves,
+                    event.real_sol_reserves,
+                    event.real_token_reserves,
+                ),
+                "trade {} reserves diverged",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_buy_breakdown_matches_synthetic_fills() {
+        let trades = serde_json::from_str::<SyntheticTrades>(SYNTHETIC_TRADES)
+            .unwrap()
+            .trades;
+
+        for (index, trade) in trades.iter().filter(|trade| trade.event.is_buy).enumerate() {
+            let curve = BondingCurveAccount::new(
+                1,
+                trade.curve.virtual_token_reserves,
+                trade.curve.virtual_sol_reserves,
+                trade.curve.real_token_reserves,
+                trade.curve.real_sol_reserves,
+                trade.curve.token_total_supply,
+                false,
+            );
+            let global = GlobalAccount::new(
+                1,
+                true,
+                Default::default(),
+                Default::default(),
+                1_073_000_000_000_000,
+                30_000_000_000,
+                793_100_000_000_000,
+                1_000_000_000_000_000,
+                trade.fee_basis_points,
+            );
+            let event = &trade.event;
+
+            // The buyer paid the SOL reported by the event plus the fee charged on it
+            let fee = event.sol_amount * trade.fee_basis_points / 10000;
+            let breakdown = curve.buy_breakdown(event.sol_amount + fee, &global);
+            assert_eq!(
+                breakdown,
+                BuyBreakdown {
+                    sol_in: event.sol_amount + fee,
+                    protocol_fee: fee,
+                    creator_fee: 0,
+                    curve_sol: event.sol_amount,
+                    unspent: 0,
+                    token_amount: event.token_amount,
+                },
+                "buy {} diverged",
+                index
+            );
+        }
+    }
+
+    #[test]
+    fn test_buy_breakdown() {
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Default::default(),
+            Default::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        );
+        let curve = BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+
+        // Every lamport is accounted for, with at most rounding left unspent
+        for sol_in in [0, 1, 101, 999_999, 1_010_000_000, 12_345_678_901] {
+            let breakdown = curve.buy_breakdown(sol_in, &global);
+            assert_eq!(
+                breakdown.protocol_fee
+                    + breakdown.creator_fee
+                    + breakdown.curve_sol
+                    + breakdown.unspent,
+                sol_in
+            );
+            assert_eq!(breakdown.protocol_fee, breakdown.curve_sol / 100);
+            assert!(breakdown.unspent <= 2);
+            assert_eq!(
+                breakdown.token_amount,
+                curve.get_buy_price(breakdown.curve_sol).unwrap()
+            );
+        }
+
+        // A budget beyond the remaining supply buys it out and returns the rest
+        let breakdown = curve.buy_breakdown(200_000_000_000, &global);
+        assert_eq!(breakdown.token_amount, curve.real_token_reserves);
+        assert!(breakdown.unspent > 100_000_000_000);
+
+        // A complete curve takes nothing
+        let mut complete = curve.clone();
+        complete.complete = true;
+        let breakdown = complete.buy_breakdown(1_000_000, &global);
+        assert_eq!((breakdown.token_amount, breakdown.unspent), (0, 1_000_000));
+    }
+
+    #[test]
+    fn test_buy_breakdown_with_creator_fee() {
+        let curve = BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+
+        // 1 SOL into the curve, 0.95% to the protocol and 0.3% to the creator on top
+        assert_eq!(
+            curve.buy_breakdown_with_fees(1_012_500_000, 95, 30),
+            BuyBreakdown {
+                sol_in: 1_012_500_000,
+                protocol_fee: 9_500_000,
+                creator_fee: 3_000_000,
+                curve_sol: 1_000_000_000,
+                unspent: 0,
+                token_amount: 34_612_903_225_806,
+            }
+        );
+
+        // Each fee rounds down on its own, leaving the rounding unspent
+        assert_eq!(
+            curve.buy_breakdown_with_fees(2_000_000_000, 95, 30),
+            BuyBreakdown {
+                sol_in: 2_000_000_000,
+                protocol_fee: 18_765_432,
+                creator_fee: 5_925_925,
+                curve_sol: 1_975_308_642,
+                unspent: 1,
+                token_amount: 66_285_714_286_491,
+            }
+        );
+
+        // The global account's rates give the same split as passing them explicitly
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Default::default(),
+            Default::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            95,
+        );
+        assert_eq!(
+            curve.buy_breakdown(2_000_000_000, &global),
+            curve.buy_breakdown_with_fees(2_000_000_000, 95, 0)
+        );
+    }
+
+    #[test]
+    fn test_sol_to_reach_market_cap() {
+        let curve = BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+        let current = curve.get_market_cap_sol();
+
+        // Buy enough to hit 50 SOL market cap, and no more
+        let target = 50_000_000_000;
+        let sol = curve.sol_to_reach_market_cap(target).unwrap();
+        let market_cap_after = |sol_amount| {
+            curve
+                .simulate_sequence(&[PlannedTrade::Buy { sol_amount }], 0)
+                .unwrap()
+                .final_curve
+                .get_market_cap_sol()
+        };
+        assert!(market_cap_after(sol) >= target);
+        assert!(market_cap_after(sol - 1) < target);
+
+        // Matches the closed form sqrt(target * k / supply) - virtual SOL reserves
+        let k = 30_000_000_000f64 * 1_073_000_000_000_000f64;
+        let closed_form = (target as f64 * k / 1_000_000_000_000_000f64).sqrt() - 30_000_000_000f64;
+        assert!((sol as f64 - closed_form).abs() < 1_000.0);
+
+        assert_eq!(curve.sol_to_reach_market_cap(current), Ok(0));
+        assert_eq!(
+            curve.sol_to_reach_market_cap(current - 1),
+            Err(CurveError::TargetBelowCurrent)
+        );
+        assert_eq!(
+            curve.sol_to_reach_market_cap(u64::MAX),
+            Err(CurveError::TargetBeyondGraduation)
+        );
+
+        let mut complete = curve.clone();
+        complete.complete = true;
+        assert_eq!(
+            complete.sol_to_reach_market_cap(target),
+            Err(CurveError::Complete)
+        );
+    }
+
+    #[test]
+    fn test_depth() {
+        let curve = BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+
+        // The last buy point matches a single-shot quote for the whole budget
+        let buy = curve.depth(DepthSide::Buy, 10, 5_000_000_000, 0);
+        assert_eq!(buy.len(), 11);
+        assert_eq!(buy[0].cumulative_out, 0);
+        let quote = curve.get_buy_price(5_000_000_000).unwrap();
+        assert!(buy[10].cumulative_out.abs_diff(quote) <= 1);
+        assert!(buy
+            .windows(2)
+            .all(|w| w[1].price > w[0].price && w[1].cumulative_out > w[0].cumulative_out));
+
+        // Selling walks the price down, and the last point matches a single-shot quote
+        let curve = BondingCurveAccount::new(
+            1,
+            900_000_000_000_000,
+            35_000_000_000,
+            620_100_000_000_000,
+            5_000_000_000,
+            1_000_000_000_000_000,
+            false,
+        );
+        let sell = curve.depth(DepthSide::Sell, 8, 100_000_000_000_000, 100);
+        assert_eq!(sell.len(), 9);
+        let quote = curve.get_sell_price(100_000_000_000_000, 100).unwrap();
+        assert!(sell[8].cumulative_out.abs_diff(quote) <= 1);
+        assert!(sell.windows(2).all(|w| w[1].price < w[0].price));
+
+        // Sampling stops once the real SOL reserves are exhausted
+        let sell = curve.depth(DepthSide::Sell, 8, 800_000_000_000_000, 100);
+        assert!(sell.len() < 9);
+
+        assert!(curve.depth(DepthSide::Buy, 0, 1, 0).is_empty());
     }
 
     #[test]
@@ -323,4 +1597,49 @@ mod tests {
         let buy_out_price = bonding_curve.get_buy_out_price(u64::MAX / 4, 250);
         assert!(buy_out_price > 0);
     }
+
+    #[test]
+    fn test_degenerate_reserves() {
+        // Found by the differential tests: these used to underflow or wrap
+        let empty = BondingCurveAccount::new(1, 0, 0, 0, 0, 0, false);
+        assert_eq!(empty.get_buy_price(1).unwrap(), 0);
+        assert_eq!(empty.get_buy_out_price(1, 250), u64::MAX);
+
+        let sell = get_bonding_curve().get_sell_price(100, 20_000).unwrap();
+        assert_eq!(sell, 0);
+
+        let huge = BondingCurveAccount::new(1, 1, u64::MAX, 0, 0, u64::MAX, false);
+        assert_eq!(huge.get_market_cap_sol(), u64::MAX);
+    }
+
+    #[test]
+    fn test_cpi_round_trip() {
+        use anchor_client::anchor_lang::{AccountDeserialize, AccountSerialize};
+
+        let curve = BondingCurveAccount::new(
+            u64::from_le_bytes(BondingCurveAccount::DISCRIMINATOR),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+        let mut bytes = Vec::new();
+        curve.serialize(&mut bytes).unwrap();
+
+        // The CPI type reads and writes the same bytes
+        let cpi_curve = cpi::BondingCurve::try_deserialize(&mut bytes.as_slice()).unwrap();
+        let mut cpi_bytes = Vec::new();
+        cpi_curve.try_serialize(&mut cpi_bytes).unwrap();
+        assert_eq!(cpi_bytes, bytes);
+
+        // Converting either way preserves every field
+        assert_eq!(BondingCurveAccount::from(cpi_curve), curve);
+        let mut converted_bytes = Vec::new();
+        cpi::BondingCurve::from(curve)
+            .try_serialize(&mut converted_bytes)
+            .unwrap();
+        assert_eq!(converted_bytes, bytes);
+    }
 }
@@ -0,0 +1,212 @@
+//! Decoding of program accounts with diagnostics on failure
+//!
+//! A failed Borsh decode on its own does not say whether the program was upgraded, the RPC
+//! returned truncated data or the wrong account was fetched. [`decode_account`] records enough
+//! context in a [`DecodeFailure`] to tell these apart, and can hand the raw bytes to a
+//! [`DecodeHook`] for capture.
+
+use super::{BondingCurveAccount, GlobalAccount};
+use crate::error::ClientError;
+use anchor_client::anchor_lang::Discriminator;
+use borsh::BorshDeserialize;
+use pumpfun_cpi as cpi;
+use solana_sdk::pubkey::Pubkey;
+use std::sync::Arc;
+
+/// Number of leading account bytes included in a decode failure
+pub const DECODE_FAILURE_HEAD_LEN: usize = 16;
+
+/// Callback receiving the full raw bytes of an account that failed to decode
+pub type DecodeHook = Arc<dyn Fn(&DecodeFailure, &[u8]) + Send + Sync>;
+
+/// An account type owned by the Pump.fun program
+pub trait ProgramAccount: BorshDeserialize {
+    /// Account name in the program IDL
+    const NAME: &'static str;
+    /// Anchor discriminator the account data starts with
+    const DISCRIMINATOR: [u8; 8];
+    /// Layout version the account is decoded with
+    const LAYOUT: &'static str;
+}
+
+impl ProgramAccount for BondingCurveAccount {
+    const NAME: &'static str = "BondingCurve";
+    const DISCRIMINATOR: [u8; 8] = cpi::BondingCurve::DISCRIMINATOR;
+    const LAYOUT: &'static str = "v1 (49 bytes)";
+}
+
+impl ProgramAccount for GlobalAccount {
+    const NAME: &'static str = "Global";
+    const DISCRIMINATOR: [u8; 8] = cpi::Global::DISCRIMINATOR;
+    const LAYOUT: &'static str = "v1 (113 bytes)";
+}
+
+/// Context of an account that failed to decode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeFailure {
+    /// Address of the account
+    pub account: Pubkey,
+    /// Account name in the program IDL
+    pub account_type: &'static str,
+    /// Length of the account data in bytes
+    pub data_len: usize,
+    /// Up to the first 16 bytes of the account data
+    pub head: Vec<u8>,
+    /// Discriminator the data was expected to start with
+    pub expected_discriminator: [u8; 8],
+    /// Each layout version tried, with the reason it was rejected
+    pub attempts: Vec<(&'static str, String)>,
+}
+
+impl DecodeFailure {
+    /// Returns whether the data starts with the expected discriminator
+    pub fn discriminator_matches(&self) -> bool {
+        self.head.starts_with(&self.expected_discriminator)
+    }
+}
+
+impl std::fmt::Display for DecodeFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} account {} ({} bytes, head {}, expected discriminator {}",
+            self.account_type,
+            self.account,
+            self.data_len,
+            to_hex(&self.head),
+            to_hex(&self.expected_discriminator)
+        )?;
+        if !self.discriminator_matches() {
+            write!(f, " [mismatch]")?;
+        }
+        write!(f, "); tried ")?;
+        for (index, (layout, reason)) in self.attempts.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", layout, reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a program account, recording diagnostics on failure
+///
+/// # Arguments
+///
+/// * `account` - Address the data was fetched from
+/// * `data` - Raw account data
+/// * `hook` - Optional callback receiving the raw bytes on failure
+///
+/// # Returns
+///
+/// Returns the decoded account, or a ClientError::AccountDecodeError describing the failure
+pub fn decode_account<T: ProgramAccount>(
+    account: &Pubkey,
+    data: &[u8],
+    hook: Option<&DecodeHook>,
+) -> Result<T, ClientError> {
+    let attempts = match T::try_from_slice(data) {
+        Ok(decoded) => return Ok(decoded),
+        Err(err) => vec![(T::LAYOUT, err.to_string())],
+    };
+
+    let failure = DecodeFailure {
+        account: *account,
+        account_type: T::NAME,
+        data_len: data.len(),
+        head: data[..data.len().min(DECODE_FAILURE_HEAD_LEN)].to_vec(),
+        expected_discriminator: T::DISCRIMINATOR,
+        attempts,
+    };
+    if let Some(hook) = hook {
+        hook(&failure, data);
+    }
+
+    Err(ClientError::AccountDecodeError(Box::new(failure)))
+}
+
+/// Hex-encodes bytes
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+    use std::sync::Mutex;
+
+    fn get_bonding_curve_data() -> Vec<u8> {
+        let mut data = Vec::new();
+        BondingCurveAccount::new(
+            u64::from_le_bytes(BondingCurveAccount::DISCRIMINATOR),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        )
+        .serialize(&mut data)
+        .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_decode_account() {
+        let account = Pubkey::new_unique();
+        let data = get_bonding_curve_data();
+        let decoded: BondingCurveAccount = decode_account(&account, &data, None).unwrap();
+        assert_eq!(decoded.virtual_sol_reserves, 30_000_000_000);
+    }
+
+    #[test]
+    fn test_decode_failure_formatting() {
+        let account = Pubkey::new_from_array([7; 32]);
+
+        // Truncated by the RPC: the discriminator is right but the layout runs out of bytes
+        let data = get_bonding_curve_data();
+        let err = decode_account::<BondingCurveAccount>(&account, &data[..20], None).unwrap_err();
+        let ClientError::AccountDecodeError(failure) = &err else {
+            panic!("unexpected error: {}", err);
+        };
+        assert!(failure.discriminator_matches());
+        assert_eq!(failure.data_len, 20);
+        assert_eq!(failure.head, data[..16]);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Account decode error: BondingCurve account {} (20 bytes, head \
+                 17b7f83760d8ac600010d847e3cf0300, expected discriminator 17b7f83760d8ac60); \
+                 tried v1 (49 bytes): Unexpected length of input",
+                account
+            )
+        );
+
+        // The wrong account type fetched: the discriminator does not match
+        let err = decode_account::<GlobalAccount>(&account, &data, None).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("Global account"));
+        assert!(message.contains("expected discriminator a7e8e8b1c86c727f [mismatch]"));
+    }
+
+    #[test]
+    fn test_decode_hook_receives_raw_bytes() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let sink = captured.clone();
+        let hook: DecodeHook = Arc::new(move |failure, data| {
+            sink.lock().unwrap().push((failure.account, data.to_vec()));
+        });
+
+        let account = Pubkey::new_unique();
+        let data = vec![0xAB; 64];
+        assert!(decode_account::<BondingCurveAccount>(&account, &data, Some(&hook)).is_err());
+
+        // Successful decodes never reach the hook
+        let valid = get_bonding_curve_data();
+        decode_account::<BondingCurveAccount>(&account, &valid, Some(&hook)).unwrap();
+
+        assert_eq!(*captured.lock().unwrap(), vec![(account, data)]);
+    }
+}
@@ -22,9 +22,20 @@
 //!
 //! - `new`: Creates a new global account instance
 //! - `get_initial_buy_price`: Calculates the initial amount of tokens received for a given SOL amount
+//! - `initial_invariant_k`: Calculates the constant-product invariant new curves start with
+//! - `creator_fee_basis_points`: Gets the creator fee charged on trades
+//! - `total_fee_bps`: Gets the all-in fee charged on trades
+//!
+//! # Conversions
+//!
+//! The account converts to and from `pumpfun_cpi::Global` with `From`. The CPI type has no
+//! `discriminator` field: converting to it drops the field, and converting from it sets the
+//! account's Anchor discriminator. Both types serialize to the same bytes.
 
+use super::ProgramAccount;
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use borsh::{BorshDeserialize, BorshSerialize};
+use pumpfun_cpi as cpi;
 
 /// Represents the global configuration account for token pricing and fees
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
@@ -111,6 +122,68 @@ impl GlobalAccount {
             self.initial_real_token_reserves
         }
     }
+
+    /// Calculates the constant-product invariant new bonding curves start with
+    ///
+    /// # Returns
+    /// `k = initial_virtual_sol_reserves * initial_virtual_token_reserves`, which cannot
+    /// overflow a u128
+    pub fn initial_invariant_k(&self) -> u128 {
+        (self.initial_virtual_sol_reserves as u128) * (self.initial_virtual_token_reserves as u128)
+    }
+
+    /// Gets the creator fee charged on trades
+    ///
+    /// This account layout predates creator fees, which are treated as zero.
+    ///
+    /// # Returns
+    /// Creator fee in basis points
+    pub fn creator_fee_basis_points(&self) -> u64 {
+        0
+    }
+
+    /// Gets the all-in fee charged on trades, summing every fee type
+    ///
+    /// Quotes should deduct this rather than individual fee fields, so they stay consistent as
+    /// fee types are added.
+    ///
+    /// # Returns
+    /// Total fee in basis points
+    pub fn total_fee_bps(&self) -> u64 {
+        self.fee_basis_points
+            .saturating_add(self.creator_fee_basis_points())
+    }
+}
+
+impl From<cpi::Global> for GlobalAccount {
+    fn from(global: cpi::Global) -> Self {
+        Self {
+            discriminator: u64::from_le_bytes(Self::DISCRIMINATOR),
+            initialized: global.initialized,
+            authority: global.authority,
+            fee_recipient: global.fee_recipient,
+            initial_virtual_token_reserves: global.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: global.initial_virtual_sol_reserves,
+            initial_real_token_reserves: global.initial_real_token_reserves,
+            token_total_supply: global.token_total_supply,
+            fee_basis_points: global.fee_basis_points,
+        }
+    }
+}
+
+impl From<GlobalAccount> for cpi::Global {
+    fn from(global: GlobalAccount) -> Self {
+        Self {
+            initialized: global.initialized,
+            authority: global.authority,
+            fee_recipient: global.fee_recipient,
+            initial_virtual_token_reserves: global.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: global.initial_virtual_sol_reserves,
+            initial_real_token_reserves: global.initial_real_token_reserves,
+            token_total_supply: global.token_total_supply,
+            fee_basis_points: global.fee_basis_points,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -198,4 +271,51 @@ mod tests {
         assert!(price > 0);
         assert!(price <= global.initial_real_token_reserves);
     }
+
+    #[test]
+    fn test_initial_invariant_k() {
+        assert_eq!(get_global().initial_invariant_k(), 1_000_000);
+        assert_eq!(
+            get_large_global().initial_invariant_k(),
+            (u64::MAX as u128) * (u64::MAX as u128)
+        );
+    }
+
+    #[test]
+    fn test_total_fee_bps() {
+        let global: GlobalAccount = get_global();
+        assert_eq!(global.creator_fee_basis_points(), 0);
+        assert_eq!(global.total_fee_bps(), 250);
+
+        let mut global: GlobalAccount = get_large_global();
+        global.fee_basis_points = u64::MAX;
+        assert_eq!(global.total_fee_bps(), u64::MAX);
+    }
+
+    #[test]
+    fn test_cpi_round_trip() {
+        use anchor_client::anchor_lang::{AccountDeserialize, AccountSerialize};
+
+        let mut global = get_global();
+        global.discriminator = u64::from_le_bytes(GlobalAccount::DISCRIMINATOR);
+        let mut bytes = Vec::new();
+        global.serialize(&mut bytes).unwrap();
+
+        // The CPI type reads and writes the same bytes
+        let cpi_global = cpi::Global::try_deserialize(&mut bytes.as_slice()).unwrap();
+        let mut cpi_bytes = Vec::new();
+        cpi_global.try_serialize(&mut cpi_bytes).unwrap();
+        assert_eq!(cpi_bytes, bytes);
+
+        // Converting either way preserves every field
+        let converted = GlobalAccount::from(cpi_global);
+        let mut converted_bytes = Vec::new();
+        converted.serialize(&mut converted_bytes).unwrap();
+        assert_eq!(converted_bytes, bytes);
+        let mut cpi_bytes = Vec::new();
+        cpi::Global::from(global)
+            .try_serialize(&mut cpi_bytes)
+            .unwrap();
+        assert_eq!(cpi_bytes, bytes);
+    }
 }
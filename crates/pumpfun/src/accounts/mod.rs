@@ -6,9 +6,15 @@
 //!
 //! - `BondingCurve`: Represents a bonding curve account.
 //! - `Global`: Represents the global configuration account.
+//!
+//! Account data is decoded with `decode_account`, which reports diagnostics on failure.
 
 mod bonding_curve;
+mod decode;
 mod global;
+#[cfg(all(test, feature = "differential"))]
+mod reference;
 
 pub use bonding_curve::*;
+pub use decode::*;
 pub use global::*;
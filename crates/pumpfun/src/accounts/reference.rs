@@ -0,0 +1,241 @@
+//! Arbitrary-precision reference implementation of the bonding curve math
+//!
+//! The formulas in [`BondingCurveAccount`] are written against u128 intermediates and casts
+//! back to u64. This module restates them naively with unbounded integers, so nothing can
+//! overflow, wrap or truncate, and differentially tests the two across the full input space:
+//! random reserves of every magnitude, adversarial values such as 0, 1 and `u64::MAX`, and
+//! buys right at the graduation boundary.
+//!
+//! The rounding rule both must follow:
+//!
+//! - Buys leave the curve with the smallest virtual token reserves whose product with the new
+//!   virtual SOL reserves exceeds the old product, so the product never decreases.
+//! - SOL paid out by sells, SOL charged for buys beyond that product, and fees round down.
+//! - Results that do not fit in a u64 saturate at `u64::MAX`, and amounts never go negative.
+//!
+//! Run with `cargo test --features differential`.
+
+use super::BondingCurveAccount;
+use num_bigint::BigUint;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Number of random curves checked per property
+const ITERATIONS: usize = 200_000;
+
+fn big(value: u64) -> BigUint {
+    BigUint::from(value)
+}
+
+/// Converts back to u64, saturating at `u64::MAX`
+fn saturate(value: BigUint) -> u64 {
+    u64::try_from(&value).unwrap_or(u64::MAX)
+}
+
+/// Subtracts, flooring at zero
+fn sub_floor(a: BigUint, b: BigUint) -> BigUint {
+    if a > b {
+        a - b
+    } else {
+        BigUint::default()
+    }
+}
+
+fn ref_buy_tokens(curve: &BondingCurveAccount, sol: u64) -> Option<u64> {
+    if curve.complete {
+        return None;
+    }
+    if sol == 0 {
+        return Some(0);
+    }
+    let product = big(curve.virtual_sol_reserves) * big(curve.virtual_token_reserves);
+    let new_sol = big(curve.virtual_sol_reserves) + big(sol);
+    // Smallest token reserves whose product with the new SOL reserves exceeds the old product
+    let new_tokens = product / new_sol + 1u32;
+    let out = sub_floor(big(curve.virtual_token_reserves), new_tokens);
+    Some(saturate(out).min(curve.real_token_reserves))
+}
+
+fn ref_sell_sol(curve: &BondingCurveAccount, tokens: u64, fee_basis_points: u64) -> Option<u64> {
+    if curve.complete {
+        return None;
+    }
+    if tokens == 0 {
+        return Some(0);
+    }
+    let gross = big(tokens) * big(curve.virtual_sol_reserves)
+        / (big(curve.virtual_token_reserves) + big(tokens));
+    let fee = &gross * big(fee_basis_points) / 10_000u32;
+    Some(saturate(sub_floor(gross, fee)))
+}
+
+fn ref_market_cap(curve: &BondingCurveAccount) -> u64 {
+    if curve.virtual_token_reserves == 0 {
+        return 0;
+    }
+    saturate(
+        big(curve.token_total_supply) * big(curve.virtual_sol_reserves)
+            / big(curve.virtual_token_reserves),
+    )
+}
+
+fn ref_sol_to_graduate(curve: &BondingCurveAccount) -> u64 {
+    if curve.complete || curve.real_token_reserves == 0 {
+        return 0;
+    }
+    let remaining = sub_floor(
+        big(curve.virtual_token_reserves),
+        big(curve.real_token_reserves),
+    );
+    if remaining == BigUint::default() {
+        return u64::MAX;
+    }
+    saturate(big(curve.real_token_reserves) * big(curve.virtual_sol_reserves) / remaining + 1u32)
+}
+
+fn ref_buy_out_price(curve: &BondingCurveAccount, amount: u64, fee_basis_points: u64) -> u64 {
+    // The curve only ever gives out its real token reserves
+    let tokens = big(amount.min(curve.real_token_reserves));
+    let new_tokens = sub_floor(big(curve.virtual_token_reserves), tokens);
+    if new_tokens == BigUint::default() {
+        return u64::MAX;
+    }
+    let product = big(curve.virtual_sol_reserves) * big(curve.virtual_token_reserves);
+    // Smallest SOL reserves whose product with the new token reserves exceeds the old product
+    let new_sol = product / new_tokens + 1u32;
+    let value = sub_floor(new_sol, big(curve.virtual_sol_reserves));
+    let fee = &value * big(fee_basis_points) / 10_000u32;
+    saturate(value + fee)
+}
+
+/// Draws a value biased towards the edges of the u64 range
+fn adversarial(rng: &mut StdRng) -> u64 {
+    match rng.gen_range(0..10) {
+        0 => 0,
+        1 => 1,
+        2 => u64::MAX - rng.gen_range(0..4),
+        3 => 1u64 << rng.gen_range(0..64),
+        4 => (1u64 << rng.gen_range(0..64)).wrapping_sub(1),
+        5 => rng.gen_range(0..1_000),
+        // Realistic magnitudes of mainnet curves
+        6 | 7 => rng.gen_range(1_000_000..2_000_000_000_000_000),
+        _ => rng.gen(),
+    }
+}
+
+fn random_curve(rng: &mut StdRng) -> BondingCurveAccount {
+    let virtual_token_reserves = adversarial(rng);
+    // Real reserves are usually a part of the virtual ones, but not always
+    let real_token_reserves = if rng.gen_bool(0.8) {
+        rng.gen_range(0..=virtual_token_reserves)
+    } else {
+        adversarial(rng)
+    };
+    BondingCurveAccount::new(
+        0,
+        virtual_token_reserves,
+        adversarial(rng),
+        real_token_reserves,
+        adversarial(rng),
+        adversarial(rng),
+        rng.gen_bool(0.02),
+    )
+}
+
+#[test]
+fn test_buy_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(0xb0);
+    for _ in 0..ITERATIONS {
+        let curve = random_curve(&mut rng);
+        let sol = adversarial(&mut rng);
+        assert_eq!(
+            curve.get_buy_price(sol).ok(),
+            ref_buy_tokens(&curve, sol),
+            "buy of {} lamports on {:?}",
+            sol,
+            curve
+        );
+    }
+}
+
+#[test]
+fn test_sell_matches_reference() {
+    let mut rng = StdRng::seed_from_u64(0x5e);
+    for _ in 0..ITERATIONS {
+        let curve = random_curve(&mut rng);
+        let tokens = adversarial(&mut rng);
+        let fee_basis_points = match rng.gen_range(0..4) {
+            0 => adversarial(&mut rng),
+            _ => rng.gen_range(0..=10_000),
+        };
+        assert_eq!(
+            curve.get_sell_price(tokens, fee_basis_points).ok(),
+            ref_sell_sol(&curve, tokens, fee_basis_points),
+            "sell of {} tokens at {} bps on {:?}",
+            tokens,
+            fee_basis_points,
+            curve
+        );
+    }
+}
+
+#[test]
+fn test_valuations_match_reference() {
+    let mut rng = StdRng::seed_from_u64(0xca);
+    for _ in 0..ITERATIONS {
+        let curve = random_curve(&mut rng);
+        assert_eq!(
+            curve.get_market_cap_sol(),
+            ref_market_cap(&curve),
+            "{:?}",
+            curve
+        );
+        assert_eq!(
+            curve.sol_to_graduate(),
+            ref_sol_to_graduate(&curve),
+            "{:?}",
+            curve
+        );
+
+        let amount = adversarial(&mut rng);
+        let fee_basis_points = rng.gen_range(0..=10_000);
+        assert_eq!(
+            curve.get_buy_out_price(amount, fee_basis_points),
+            ref_buy_out_price(&curve, amount, fee_basis_points),
+            "buy out of {} at {} bps on {:?}",
+            amount,
+            fee_basis_points,
+            curve
+        );
+    }
+}
+
+#[test]
+fn test_graduation_boundary_is_exact() {
+    let mut rng = StdRng::seed_from_u64(0x9a);
+    for _ in 0..ITERATIONS {
+        let curve = random_curve(&mut rng);
+        if curve.complete
+            || curve.real_token_reserves == 0
+            || curve.real_token_reserves >= curve.virtual_token_reserves
+        {
+            continue;
+        }
+
+        // The SOL to graduate buys every real token, and one lamport less does not
+        let sol = curve.sol_to_graduate();
+        if sol == u64::MAX {
+            continue;
+        }
+        assert_eq!(
+            ref_buy_tokens(&curve, sol),
+            Some(curve.real_token_reserves),
+            "{:?}",
+            curve
+        );
+        assert!(
+            ref_buy_tokens(&curve, sol - 1).unwrap() < curve.real_token_reserves,
+            "{:?}",
+            curve
+        );
+    }
+}
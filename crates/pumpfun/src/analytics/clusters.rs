@@ -0,0 +1,812 @@
+//! Wallet clustering over trade history and SOL transfers
+//!
+//! Operators split their holdings across wallets, which makes raw holder and trader lists look
+//! more distributed than they are. [`cluster_wallets`] groups the traders of a token into wallets
+//! that are likely controlled together, from three heuristics:
+//!
+//! - Common funder: two traders received SOL from the same source within a window.
+//! - Co-trading: two traders almost always trade the same token within seconds of each other.
+//! - Common destination: two traders sent SOL to the same destination.
+//!
+//! Sources and destinations linked to many traders, such as exchange hot wallets, are skipped, as
+//! they say nothing about who controls the wallets. Evidence between a pair of wallets is
+//! combined into a link, and links above a confidence threshold join wallets into clusters. The
+//! analysis is pure over the given data; [`crate::PumpFun::get_sol_transfers`] collects the
+//! transfers of a wallet, and [`decode_transfers`] those of a single transaction.
+//!
+//! - `Transfer`: A SOL transfer between two wallets.
+//! - `ClusterConfig`: Windows and thresholds of the heuristics.
+//! - `WalletCluster`: Wallets likely controlled together, with the links joining them.
+//! - `WalletLink`: Combined evidence that two wallets are controlled together.
+//! - `LinkEvidence`: A single observation supporting a link, with its confidence.
+//! - `EvidenceKind`: The heuristic behind an observation and what it observed.
+
+use crate::events::TradeEvent;
+use solana_sdk::{
+    pubkey::Pubkey, system_instruction::SystemInstruction, system_program,
+    transaction::VersionedTransaction,
+};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Confidence of a common funder at the end of the funding window, rising towards
+/// [`FUNDER_MAX_CONFIDENCE`] for transfers made at the same time
+const FUNDER_MIN_CONFIDENCE: f64 = 0.5;
+
+/// Confidence of a common funder whose transfers were made at the same time
+const FUNDER_MAX_CONFIDENCE: f64 = 0.8;
+
+/// Confidence of a common consolidation destination
+const DESTINATION_CONFIDENCE: f64 = 0.7;
+
+/// Confidence of co-trading on every trade, before the sample size is accounted for
+const CO_TRADING_MAX_CONFIDENCE: f64 = 0.9;
+
+/// A SOL transfer between two wallets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transfer {
+    /// Wallet sending the SOL
+    pub from: Pubkey,
+    /// Wallet receiving the SOL
+    pub to: Pubkey,
+    /// Amount of SOL in lamports
+    pub lamports: u64,
+    /// Unix timestamp of the transfer
+    pub timestamp: i64,
+}
+
+/// Windows and thresholds of the clustering heuristics
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ClusterConfig {
+    /// Longest time between two transfers from a source for it to count as a common funder, in
+    /// seconds
+    pub funding_window_secs: i64,
+    /// Longest time between two trades of a token for them to count as made together, in
+    /// seconds
+    pub co_trade_window_secs: i64,
+    /// Fewest trades each wallet of a pair must have made together with the other
+    pub min_co_trades: usize,
+    /// Smallest share of each wallet's trades made together with the other
+    pub min_co_trade_ratio: f64,
+    /// Most traders a source or destination may be linked to before it is skipped as a shared
+    /// service, such as an exchange
+    pub max_fanout: usize,
+    /// Smallest combined confidence of a link that joins two wallets into a cluster
+    pub min_confidence: f64,
+}
+
+impl Default for ClusterConfig {
+    fn default() -> Self {
+        Self {
+            funding_window_secs: 3_600,
+            co_trade_window_secs: 2,
+            min_co_trades: 3,
+            min_co_trade_ratio: 0.8,
+            max_fanout: 20,
+            min_confidence: 0.5,
+        }
+    }
+}
+
+impl ClusterConfig {
+    /// Sets the longest time between two transfers from a common funder
+    ///
+    /// # Arguments
+    ///
+    /// * `secs` - Funding window in seconds
+    pub fn with_funding_window(mut self, secs: i64) -> Self {
+        self.funding_window_secs = secs;
+        self
+    }
+
+    /// Sets how close trades must be to count as made together, and how often they must be
+    ///
+    /// # Arguments
+    ///
+    /// * `secs` - Co-trading window in seconds
+    /// * `min_trades` - Fewest trades each wallet must have made together with the other
+    /// * `min_ratio` - Smallest share of each wallet's trades made together with the other
+    pub fn with_co_trading(mut self, secs: i64, min_trades: usize, min_ratio: f64) -> Self {
+        self.co_trade_window_secs = secs;
+        self.min_co_trades = min_trades;
+        self.min_co_trade_ratio = min_ratio;
+        self
+    }
+
+    /// Sets the most traders a source or destination may be linked to
+    ///
+    /// # Arguments
+    ///
+    /// * `max_fanout` - Most linked traders before a source or destination is skipped
+    pub fn with_max_fanout(mut self, max_fanout: usize) -> Self {
+        self.max_fanout = max_fanout;
+        self
+    }
+
+    /// Sets the smallest confidence of a link that joins two wallets into a cluster
+    ///
+    /// # Arguments
+    ///
+    /// * `min_confidence` - Threshold between 0 and 1
+    pub fn with_min_confidence(mut self, min_confidence: f64) -> Self {
+        self.min_confidence = min_confidence;
+        self
+    }
+}
+
+/// The heuristic behind an observation and what it observed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EvidenceKind {
+    /// Both wallets were funded by the same source
+    CommonFunder {
+        /// Wallet that funded both
+        source: Pubkey,
+        /// Shortest time between its transfers to the two wallets, in seconds
+        gap_secs: i64,
+    },
+    /// The wallets traded the same tokens within seconds of each other
+    CoTrading {
+        /// Fewest trades either wallet made together with the other
+        matched: usize,
+        /// Smallest share of either wallet's trades made together with the other
+        ratio: f64,
+    },
+    /// Both wallets sent SOL to the same destination
+    CommonDestination {
+        /// Wallet that received from both
+        destination: Pubkey,
+    },
+}
+
+/// A single observation supporting a link, with its confidence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkEvidence {
+    /// Heuristic and observation
+    pub kind: EvidenceKind,
+    /// Confidence between 0 and 1 that the observation alone links the wallets
+    pub confidence: f64,
+}
+
+/// Combined evidence that two wallets are controlled together
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletLink {
+    /// Lower of the two wallets
+    pub a: Pubkey,
+    /// Higher of the two wallets
+    pub b: Pubkey,
+    /// Combined confidence of the evidence, as if each observation were independent
+    pub confidence: f64,
+    /// Observations supporting the link
+    pub evidence: Vec<LinkEvidence>,
+}
+
+/// Wallets likely controlled together, with the links joining them
+#[derive(Debug, Clone, PartialEq)]
+pub struct WalletCluster {
+    /// Wallets of the cluster, sorted
+    pub wallets: Vec<Pubkey>,
+    /// Confidence of the weakest link needed to join the cluster
+    pub confidence: f64,
+    /// Every link between wallets of the cluster, strongest first
+    pub links: Vec<WalletLink>,
+}
+
+/// Groups the traders of a token into wallets likely controlled together
+///
+/// Uses [`ClusterConfig::default`], see [`cluster_wallets_with`].
+///
+/// # Arguments
+///
+/// * `trades` - Trades to cluster the traders of
+/// * `funding_edges` - SOL transfers to and from the traders
+///
+/// # Returns
+///
+/// Returns the clusters of two or more wallets, most confident first
+pub fn cluster_wallets(trades: &[TradeEvent], funding_edges: &[Transfer]) -> Vec<WalletCluster> {
+    cluster_wallets_with(trades, funding_edges, &ClusterConfig::default())
+}
+
+/// Groups the traders of a token into wallets likely controlled together, with custom windows
+/// and thresholds
+///
+/// Only wallets that traded are clustered; transfers between other wallets are ignored. Links
+/// are added strongest first, so a cluster's confidence is that of the weakest link needed to
+/// connect it, and a chain of strong links outweighs a single weak one.
+///
+/// # Arguments
+///
+/// * `trades` - Trades to cluster the traders of
+/// * `funding_edges` - SOL transfers to and from the traders
+/// * `config` - Windows and thresholds of the heuristics
+///
+/// # Returns
+///
+/// Returns the clusters of two or more wallets, most confident first
+pub fn cluster_wallets_with(
+    trades: &[TradeEvent],
+    funding_edges: &[Transfer],
+    config: &ClusterConfig,
+) -> Vec<WalletCluster> {
+    let traders: BTreeSet<Pubkey> = trades.iter().map(|trade| trade.user).collect();
+    let mut evidence: BTreeMap<(Pubkey, Pubkey), Vec<LinkEvidence>> = BTreeMap::new();
+    let mut observe = |a: Pubkey, b: Pubkey, observation: LinkEvidence| {
+        evidence
+            .entry((a.min(b), a.max(b)))
+            .or_default()
+            .push(observation);
+    };
+
+    for (a, b, observation) in common_funders(&traders, funding_edges, config) {
+        observe(a, b, observation);
+    }
+    for (a, b, observation) in co_trading(trades, config) {
+        observe(a, b, observation);
+    }
+    for (a, b, observation) in common_destinations(&traders, funding_edges, config) {
+        observe(a, b, observation);
+    }
+
+    let mut links: Vec<WalletLink> = evidence
+        .into_iter()
+        .map(|((a, b), evidence)| WalletLink {
+            a,
+            b,
+            confidence: 1.0
+                - evidence
+                    .iter()
+                    .map(|observation| 1.0 - observation.confidence)
+                    .product::<f64>(),
+            evidence,
+        })
+        .filter(|link| link.confidence >= config.min_confidence)
+        .collect();
+    links.sort_by(|x, y| {
+        y.confidence
+            .total_cmp(&x.confidence)
+            .then((x.a, x.b).cmp(&(y.a, y.b)))
+    });
+
+    // Join wallets strongest link first, recording the weakest link each cluster needed
+    let mut parent: HashMap<Pubkey, Pubkey> = HashMap::new();
+    let mut weakest: HashMap<Pubkey, f64> = HashMap::new();
+    fn root(parent: &mut HashMap<Pubkey, Pubkey>, wallet: Pubkey) -> Pubkey {
+        let mut current = wallet;
+        while let Some(&next) = parent.get(&current) {
+            if next == current {
+                break;
+            }
+            current = next;
+        }
+        parent.insert(wallet, current);
+        current
+    }
+    for link in &links {
+        parent.entry(link.a).or_insert(link.a);
+        parent.entry(link.b).or_insert(link.b);
+        let (root_a, root_b) = (root(&mut parent, link.a), root(&mut parent, link.b));
+        if root_a != root_b {
+            parent.insert(root_b, root_a);
+            let confidence = [weakest.remove(&root_b), weakest.get(&root_a).copied()]
+                .into_iter()
+                .flatten()
+                .fold(link.confidence, f64::min);
+            weakest.insert(root_a, confidence);
+        }
+    }
+
+    let mut members: BTreeMap<Pubkey, Vec<Pubkey>> = BTreeMap::new();
+    let wallets: Vec<Pubkey> = parent.keys().copied().collect();
+    for wallet in wallets {
+        let root = root(&mut parent, wallet);
+        members.entry(root).or_default().push(wallet);
+    }
+    let mut clusters: Vec<WalletCluster> = members
+        .into_iter()
+        .map(|(root, mut wallets)| {
+            wallets.sort();
+            let links = links
+                .iter()
+                .filter(|link| wallets.binary_search(&link.a).is_ok())
+                .cloned()
+                .collect();
+            WalletCluster {
+                confidence: weakest[&root],
+                wallets,
+                links,
+            }
+        })
+        .collect();
+    clusters.sort_by(|x, y| {
+        y.confidence
+            .total_cmp(&x.confidence)
+            .then(y.wallets.len().cmp(&x.wallets.len()))
+            .then(x.wallets.cmp(&y.wallets))
+    });
+    clusters
+}
+
+/// Finds pairs of traders funded by the same source within the funding window
+fn common_funders(
+    traders: &BTreeSet<Pubkey>,
+    transfers: &[Transfer],
+    config: &ClusterConfig,
+) -> Vec<(Pubkey, Pubkey, LinkEvidence)> {
+    let mut funded: BTreeMap<Pubkey, Vec<(i64, Pubkey)>> = BTreeMap::new();
+    for transfer in transfers {
+        if transfer.from != transfer.to && traders.contains(&transfer.to) {
+            funded
+                .entry(transfer.from)
+                .or_default()
+                .push((transfer.timestamp, transfer.to));
+        }
+    }
+
+    let mut observations = Vec::new();
+    for (source, mut transfers) in funded {
+        let recipients: BTreeSet<Pubkey> = transfers.iter().map(|(_, to)| *to).collect();
+        if recipients.len() < 2 || recipients.len() > config.max_fanout {
+            continue;
+        }
+        transfers.sort();
+
+        // Shortest gap between transfers to each pair of recipients
+        let mut gaps: BTreeMap<(Pubkey, Pubkey), i64> = BTreeMap::new();
+        for (index, (time, to)) in transfers.iter().enumerate() {
+            for (later_time, later_to) in &transfers[index + 1..] {
+                let gap = later_time - time;
+                if gap > config.funding_window_secs {
+                    break;
+                }
+                if later_to != to {
+                    let gap_secs = gaps
+                        .entry((*to.min(later_to), *to.max(later_to)))
+                        .or_insert(gap);
+                    *gap_secs = (*gap_secs).min(gap);
+                }
+            }
+        }
+
+        let window = config.funding_window_secs.max(1) as f64;
+        for ((a, b), gap_secs) in gaps {
+            let closeness = 1.0 - gap_secs as f64 / window;
+            let confidence =
+                FUNDER_MIN_CONFIDENCE + (FUNDER_MAX_CONFIDENCE - FUNDER_MIN_CONFIDENCE) * closeness;
+            observations.push((
+                a,
+                b,
+                LinkEvidence {
+                    kind: EvidenceKind::CommonFunder { source, gap_secs },
+                    confidence,
+                },
+            ));
+        }
+    }
+    observations
+}
+
+/// Finds pairs of traders that almost always trade the same token within the co-trading window
+fn co_trading(
+    trades: &[TradeEvent],
+    config: &ClusterConfig,
+) -> Vec<(Pubkey, Pubkey, LinkEvidence)> {
+    let mut totals: HashMap<Pubkey, usize> = HashMap::new();
+    let mut by_mint: BTreeMap<Pubkey, Vec<(i64, Pubkey)>> = BTreeMap::new();
+    for trade in trades {
+        *totals.entry(trade.user).or_default() += 1;
+        by_mint
+            .entry(trade.mint)
+            .or_default()
+            .push((trade.timestamp, trade.user));
+    }
+
+    // Number of each wallet's trades made together with each other wallet
+    let mut matched: BTreeMap<(Pubkey, Pubkey), usize> = BTreeMap::new();
+    for (_, mut mint_trades) in by_mint {
+        mint_trades.sort();
+        for (index, (time, user)) in mint_trades.iter().enumerate() {
+            let mut partners = BTreeSet::new();
+            for (other_time, other) in mint_trades[..index].iter().rev() {
+                if time - other_time > config.co_trade_window_secs {
+                    break;
+                }
+                partners.insert(*other);
+            }
+            for (other_time, other) in &mint_trades[index + 1..] {
+                if other_time - time > config.co_trade_window_secs {
+                    break;
+                }
+                partners.insert(*other);
+            }
+            partners.remove(user);
+            for partner in partners {
+                *matched.entry((*user, partner)).or_default() += 1;
+            }
+        }
+    }
+
+    let mut observations = Vec::new();
+    for (&(a, b), &matched_a) in &matched {
+        if a >= b {
+            continue;
+        }
+        let matched_b = matched.get(&(b, a)).copied().unwrap_or_default();
+        let matched = matched_a.min(matched_b);
+        let ratio =
+            (matched_a as f64 / totals[&a] as f64).min(matched_b as f64 / totals[&b] as f64);
+        if matched < config.min_co_trades || ratio < config.min_co_trade_ratio {
+            continue;
+        }
+        // Few trades together are more likely a coincidence
+        let sample = matched as f64 / (matched as f64 + 1.0);
+        observations.push((
+            a,
+            b,
+            LinkEvidence {
+                kind: EvidenceKind::CoTrading { matched, ratio },
+                confidence: CO_TRADING_MAX_CONFIDENCE * ratio * sample,
+            },
+        ));
+    }
+    observations
+}
+
+/// Finds pairs of traders that sent SOL to the same destination
+fn common_destinations(
+    traders: &BTreeSet<Pubkey>,
+    transfers: &[Transfer],
+    config: &ClusterConfig,
+) -> Vec<(Pubkey, Pubkey, LinkEvidence)> {
+    let mut senders: BTreeMap<Pubkey, BTreeSet<Pubkey>> = BTreeMap::new();
+    for transfer in transfers {
+        if transfer.from != transfer.to && traders.contains(&transfer.from) {
+            senders
+                .entry(transfer.to)
+                .or_default()
+                .insert(transfer.from);
+        }
+    }
+
+    let mut observations = Vec::new();
+    for (destination, senders) in senders {
+        if senders.len() < 2 || senders.len() > config.max_fanout {
+            continue;
+        }
+        let senders: Vec<Pubkey> = senders.into_iter().collect();
+        for (index, a) in senders.iter().enumerate() {
+            for b in &senders[index + 1..] {
+                observations.push((
+                    *a,
+                    *b,
+                    LinkEvidence {
+                        kind: EvidenceKind::CommonDestination { destination },
+                        confidence: DESTINATION_CONFIDENCE,
+                    },
+                ));
+            }
+        }
+    }
+    observations
+}
+
+/// Decodes the SOL transfers a transaction makes through top-level System Program instructions
+///
+/// Transfers made from inside other programs, and transfers whose accounts are loaded from
+/// address lookup tables, are not decoded.
+///
+/// # Arguments
+///
+/// * `transaction` - Transaction to decode
+/// * `timestamp` - Unix timestamp of the block the transaction landed in
+///
+/// # Returns
+///
+/// Returns the transfers in instruction order
+pub fn decode_transfers(transaction: &VersionedTransaction, timestamp: i64) -> Vec<Transfer> {
+    let keys = transaction.message.static_account_keys();
+    transaction
+        .message
+        .instructions()
+        .iter()
+        .filter(|instruction| {
+            keys.get(instruction.program_id_index as usize) == Some(&system_program::id())
+        })
+        .filter_map(|instruction| {
+            let SystemInstruction::Transfer { lamports } =
+                bincode::deserialize(&instruction.data).ok()?
+            else {
+                return None;
+            };
+            let account = |position: usize| keys.get(*instruction.accounts.get(position)? as usize);
+            Some(Transfer {
+                from: *account(0)?,
+                to: *account(1)?,
+                lamports,
+                timestamp,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        message::Message, signature::Keypair, signer::Signer, system_instruction,
+        transaction::Transaction,
+    };
+
+    fn trade(mint: Pubkey, user: Pubkey, timestamp: i64) -> TradeEvent {
+        TradeEvent {
+            mint,
+            sol_amount: 1_000_000,
+            token_amount: 1_000_000,
+            is_buy: true,
+            user,
+            timestamp,
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+        }
+    }
+
+    fn transfer(from: Pubkey, to: Pubkey, timestamp: i64) -> Transfer {
+        Transfer {
+            from,
+            to,
+            lamports: 100_000_000,
+            timestamp,
+        }
+    }
+
+    fn wallets(count: usize) -> Vec<Pubkey> {
+        let mut wallets: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+        wallets.sort();
+        wallets
+    }
+
+    #[test]
+    fn test_common_funder_within_window() {
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let w = wallets(3);
+        let trades: Vec<TradeEvent> = w.iter().map(|user| trade(mint, *user, 0)).collect();
+
+        let clusters = cluster_wallets(
+            &trades,
+            &[
+                transfer(source, w[0], 1_000),
+                transfer(source, w[1], 1_000),
+                // Funded by the same source, but long after the others
+                transfer(source, w[2], 10_000),
+            ],
+        );
+        // Trades all at once are only co-trading evidence with enough of them
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].wallets, [w[0], w[1]]);
+        assert_eq!(clusters[0].confidence, FUNDER_MAX_CONFIDENCE);
+        assert_eq!(
+            clusters[0].links[0].evidence,
+            [LinkEvidence {
+                kind: EvidenceKind::CommonFunder {
+                    source,
+                    gap_secs: 0
+                },
+                confidence: FUNDER_MAX_CONFIDENCE,
+            }]
+        );
+
+        // Confidence falls with the gap between the transfers
+        let clusters = cluster_wallets(
+            &trades,
+            &[transfer(source, w[0], 0), transfer(source, w[1], 1_800)],
+        );
+        assert!((clusters[0].confidence - 0.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_shared_services_are_skipped() {
+        let mint = Pubkey::new_unique();
+        let exchange = Pubkey::new_unique();
+        let w = wallets(30);
+        let trades: Vec<TradeEvent> = w.iter().map(|user| trade(mint, *user, 0)).collect();
+        let funding: Vec<Transfer> = w.iter().map(|user| transfer(exchange, *user, 0)).collect();
+        let deposits: Vec<Transfer> = w.iter().map(|user| transfer(*user, exchange, 0)).collect();
+
+        assert!(cluster_wallets(&trades, &funding).is_empty());
+        assert!(cluster_wallets(&trades, &deposits).is_empty());
+        assert_eq!(
+            cluster_wallets_with(
+                &trades,
+                &funding,
+                &ClusterConfig::default().with_max_fanout(30)
+            )[0]
+            .wallets,
+            w
+        );
+    }
+
+    #[test]
+    fn test_transfers_of_non_traders_are_ignored() {
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let w = wallets(3);
+        let trades = [trade(mint, w[0], 0)];
+
+        let clusters = cluster_wallets(
+            &trades,
+            &[transfer(source, w[0], 0), transfer(source, w[1], 0)],
+        );
+        assert!(clusters.is_empty());
+        let clusters =
+            cluster_wallets(&trades, &[transfer(w[0], w[2], 0), transfer(w[1], w[2], 0)]);
+        assert!(clusters.is_empty());
+    }
+
+    #[test]
+    fn test_co_trading() {
+        let w = wallets(3);
+        let mut trades = Vec::new();
+        for round in 0..5 {
+            let mint = Pubkey::new_unique();
+            let time = round * 1_000;
+            trades.push(trade(mint, w[0], time));
+            trades.push(trade(mint, w[1], time + 1));
+            // Only joins in on two of the tokens
+            if round < 2 {
+                trades.push(trade(mint, w[2], time + 1));
+            }
+        }
+        // Close in time, but on another token
+        trades.push(trade(Pubkey::new_unique(), w[2], 4_000));
+
+        let clusters = cluster_wallets(&trades, &[]);
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].wallets, [w[0], w[1]]);
+        let link = &clusters[0].links[0];
+        assert_eq!(
+            link.evidence[0].kind,
+            EvidenceKind::CoTrading {
+                matched: 5,
+                ratio: 1.0
+            }
+        );
+        assert!((link.confidence - 0.75).abs() < 1e-9);
+
+        // Joining in on two of the tokens is enough with lower thresholds
+        let clusters = cluster_wallets_with(
+            &trades,
+            &[],
+            &ClusterConfig::default()
+                .with_co_trading(2, 2, 0.4)
+                .with_min_confidence(0.2),
+        );
+        assert_eq!(clusters[0].wallets, w);
+
+        // Trades a few seconds apart are not made together in a tighter window
+        let clusters = cluster_wallets_with(
+            &trades,
+            &[],
+            &ClusterConfig::default().with_co_trading(0, 3, 0.8),
+        );
+        assert!(clusters.is_empty());
+    }
+
+    #[test]
+    fn test_common_destination() {
+        let mint = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let w = wallets(3);
+        let trades: Vec<TradeEvent> = w.iter().map(|user| trade(mint, *user, 0)).collect();
+
+        let clusters = cluster_wallets(
+            &trades,
+            &[
+                transfer(w[0], destination, 0),
+                transfer(w[1], destination, 86_400),
+                transfer(w[2], Pubkey::new_unique(), 0),
+            ],
+        );
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].wallets, [w[0], w[1]]);
+        assert_eq!(clusters[0].confidence, DESTINATION_CONFIDENCE);
+        assert_eq!(
+            clusters[0].links[0].evidence[0].kind,
+            EvidenceKind::CommonDestination { destination }
+        );
+    }
+
+    #[test]
+    fn test_evidence_combines_and_clusters_chain() {
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let w = wallets(4);
+        let trades: Vec<TradeEvent> = w.iter().map(|user| trade(mint, *user, 0)).collect();
+
+        let clusters = cluster_wallets(
+            &trades,
+            &[
+                // w0 and w1 are funded together and consolidate together
+                transfer(source, w[0], 0),
+                transfer(source, w[1], 0),
+                transfer(w[0], destination, 100),
+                transfer(w[1], destination, 100),
+                // w2 only shares the destination, which chains it into the cluster
+                transfer(w[2], destination, 100),
+            ],
+        );
+        assert_eq!(clusters.len(), 1);
+        assert_eq!(clusters[0].wallets, [w[0], w[1], w[2]]);
+        assert_eq!(clusters[0].confidence, DESTINATION_CONFIDENCE);
+        let strongest = &clusters[0].links[0];
+        assert_eq!((strongest.a, strongest.b), (w[0], w[1]));
+        assert_eq!(strongest.evidence.len(), 2);
+        let combined = 1.0 - (1.0 - FUNDER_MAX_CONFIDENCE) * (1.0 - DESTINATION_CONFIDENCE);
+        assert!((strongest.confidence - combined).abs() < 1e-9);
+        assert_eq!(clusters[0].links.len(), 3);
+
+        // Weak links are dropped
+        let clusters = cluster_wallets_with(
+            &trades,
+            &[
+                transfer(w[0], destination, 0),
+                transfer(w[3], destination, 0),
+            ],
+            &ClusterConfig::default().with_min_confidence(0.75),
+        );
+        assert!(clusters.is_empty());
+    }
+
+    #[test]
+    fn test_clusters_sorted_by_confidence() {
+        let mint = Pubkey::new_unique();
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let w = wallets(4);
+        let trades: Vec<TradeEvent> = w.iter().map(|user| trade(mint, *user, 0)).collect();
+
+        let clusters = cluster_wallets(
+            &trades,
+            &[
+                transfer(w[0], destination, 0),
+                transfer(w[1], destination, 0),
+                transfer(source, w[2], 0),
+                transfer(source, w[3], 0),
+            ],
+        );
+        let wallets: Vec<&[Pubkey]> = clusters.iter().map(|c| c.wallets.as_slice()).collect();
+        assert_eq!(wallets, [&w[2..4], &w[0..2]]);
+    }
+
+    #[test]
+    fn test_decode_transfers() {
+        let payer = Keypair::new();
+        let to = Pubkey::new_unique();
+        let message = Message::new(
+            &[
+                system_instruction::transfer(&payer.pubkey(), &to, 5),
+                crate::instruction::memo("not a transfer"),
+                system_instruction::create_account(
+                    &payer.pubkey(),
+                    &Pubkey::new_unique(),
+                    1,
+                    0,
+                    &system_program::id(),
+                ),
+            ],
+            Some(&payer.pubkey()),
+        );
+        let transaction: VersionedTransaction = Transaction::new_unsigned(message).into();
+        assert_eq!(
+            decode_transfers(&transaction, 42),
+            [Transfer {
+                from: payer.pubkey(),
+                to,
+                lamports: 5,
+                timestamp: 42,
+            }]
+        );
+    }
+}
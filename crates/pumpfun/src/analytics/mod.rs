@@ -0,0 +1,422 @@
+//! Estimates derived from curve state and the trade stream
+//!
+//! [`crate::PumpFun::estimate_time_to_graduate`] walks the curve's transaction history and
+//! extrapolates the plain net inflow of the last five minutes. When a [`NetFlowTracker`] is
+//! already fed by a trade stream, [`graduation_eta`] estimates the same thing without any
+//! request, and weighs recent trades more so the estimate follows a flow that speeds up or
+//! reverses:
+//!
+//! - `EtaEstimate`: When a curve is expected to complete, and how far to trust the estimate.
+//! - `graduation_eta`: Estimates when a curve completes from the weighted net inflow rate.
+//!
+//! A client created with [`crate::PumpFun::with_volume_tracker`] includes the estimate in the
+//! summaries returned by [`crate::PumpFun::get_curve_summary`].
+//!
+//! Statistics over many tokens should not mix curves created under different global parameters.
+//! The constant-product invariant of a curve's virtual reserves stays within a tiny fraction of
+//! the value it was created with, so together with the token supply it identifies the parameter
+//! set:
+//!
+//! - `ParameterEra`: Global parameters new tokens were launched with from a date on.
+//! - `PARAMETER_ERAS`: The known parameter eras of the mainnet program, oldest first.
+//!
+//! Operators split across wallets, so the traders of a token can be fewer people than they look.
+//! [`cluster_wallets`] groups them by common funders, co-trading and common consolidation
+//! destinations, over trades and SOL transfers such as those from
+//! [`crate::PumpFun::get_sol_transfers`]:
+//!
+//! - `WalletCluster`: Wallets likely controlled together, with the links joining them.
+//! - `cluster_wallets`: Clusters the traders of a token with the default `ClusterConfig`.
+
+mod clusters;
+
+pub use clusters::*;
+
+use crate::{
+    accounts::{BondingCurveAccount, GlobalAccount},
+    flow::NetFlowTracker,
+};
+use std::time::Duration;
+
+/// Age at which a trade counts for half as much as one made now in the inflow rate
+pub const ETA_HALF_LIFE: Duration = Duration::from_secs(60);
+
+/// Number of trades in the window at which the sample size halves the confidence
+const CONFIDENCE_SAMPLE_SIZE: f64 = 10.0;
+
+/// Growth of a curve's invariant over its initial value that still matches its era, in basis
+/// points
+///
+/// Rounding grows the invariant by about 1e-10 per trade, so this covers millions of trades
+/// while keeping eras whose invariants differ by more than 0.1% apart.
+pub const ERA_INVARIANT_TOLERANCE_BPS: u128 = 10;
+
+/// Known parameter eras of the mainnet program, oldest first
+///
+/// Only changes to the initial virtual reserves or the supply start a new era here; fee changes
+/// leave the invariant untouched and cannot be told apart from curve state.
+pub const PARAMETER_ERAS: &[ParameterEra] = &[ParameterEra {
+    name: "launch",
+    // 2024-01-19T00:00:00Z
+    effective_from: 1_705_622_400,
+    initial_virtual_token_reserves: 1_073_000_000_000_000,
+    initial_virtual_sol_reserves: 30_000_000_000,
+    token_total_supply: 1_000_000_000_000_000,
+}];
+
+/// Global parameters new tokens were launched with from a date on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParameterEra {
+    /// Short name of the era
+    pub name: &'static str,
+    /// Unix timestamp from which new tokens were launched with these parameters
+    pub effective_from: i64,
+    /// Virtual token reserves a new curve started with
+    pub initial_virtual_token_reserves: u64,
+    /// Virtual SOL reserves a new curve started with, in lamports
+    pub initial_virtual_sol_reserves: u64,
+    /// Total supply of each new token
+    pub token_total_supply: u64,
+}
+
+impl ParameterEra {
+    /// Calculates the constant-product invariant new curves of the era start with
+    pub fn initial_invariant_k(&self) -> u128 {
+        (self.initial_virtual_sol_reserves as u128) * (self.initial_virtual_token_reserves as u128)
+    }
+
+    /// Checks whether an observed invariant and supply belong to the era
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - Invariant of a curve, see [`BondingCurveAccount::invariant_k`]
+    /// * `token_total_supply` - Total supply of the curve's token
+    ///
+    /// # Returns
+    ///
+    /// Returns true if the supply matches and the invariant is at most
+    /// [`ERA_INVARIANT_TOLERANCE_BPS`] above the era's initial invariant
+    pub fn matches(&self, k: u128, token_total_supply: u64) -> bool {
+        let initial = self.initial_invariant_k();
+        token_total_supply == self.token_total_supply
+            && k >= initial
+            && k - initial <= initial / 10_000 * ERA_INVARIANT_TOLERANCE_BPS
+    }
+
+    /// Finds the era an observed invariant and supply belong to
+    ///
+    /// # Arguments
+    ///
+    /// * `k` - Invariant of a curve, see [`BondingCurveAccount::invariant_k`]
+    /// * `token_total_supply` - Total supply of the curve's token
+    ///
+    /// # Returns
+    ///
+    /// Returns the latest era of [`PARAMETER_ERAS`] matching both, or None if the curve was
+    /// created under parameters not listed there
+    pub fn classify(k: u128, token_total_supply: u64) -> Option<&'static ParameterEra> {
+        PARAMETER_ERAS
+            .iter()
+            .rev()
+            .find(|era| era.matches(k, token_total_supply))
+    }
+
+    /// Finds the era a bonding curve was created in
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - State of the bonding curve at any point before it is migrated
+    ///
+    /// # Returns
+    ///
+    /// Returns the era, or None if the curve was created under unknown parameters or its
+    /// reserves have been migrated
+    pub fn of_curve(curve: &BondingCurveAccount) -> Option<&'static ParameterEra> {
+        Self::classify(curve.invariant_k(), curve.token_total_supply)
+    }
+}
+
+/// When a bonding curve is expected to complete
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EtaEstimate {
+    /// SOL still required to flow into the curve for it to complete, in lamports before fees
+    pub sol_remaining: u64,
+    /// Expected time until the curve completes at the current inflow rate
+    pub eta: Duration,
+    /// Trust in the estimate from 0 to 1, higher with more trades and a steadier rate
+    pub confidence: f64,
+}
+
+/// Estimates when a bonding curve completes from the recent net inflow
+///
+/// The inflow rate is the exponentially weighted rate of the tracker, with a half-life of
+/// [`ETA_HALF_LIFE`]. The confidence is the product of two factors: the number of trades
+/// retained, `n / (n + 10)`, and how well the weighted rate agrees with the plain average over
+/// the whole window, the smaller of the two divided by the larger. An accelerating or slowing
+/// flow makes them disagree, so its estimate is trusted less than a steady one.
+///
+/// # Arguments
+///
+/// * `curve` - Current state of the bonding curve
+/// * `global` - Global account the curve was created under
+/// * `recent` - Tracker fed with the curve's trades, restricted to its mint
+///
+/// # Returns
+///
+/// Returns the estimate, a zero ETA if the curve has completed, or None if the net inflow rate
+/// is zero or negative or the reserves are inconsistent with `global`, see
+/// [`BondingCurveAccount::check_invariant`]
+pub fn graduation_eta(
+    curve: &BondingCurveAccount,
+    global: &GlobalAccount,
+    recent: &NetFlowTracker,
+) -> Option<EtaEstimate> {
+    if curve.complete {
+        return Some(EtaEstimate {
+            sol_remaining: 0,
+            eta: Duration::ZERO,
+            confidence: 1.0,
+        });
+    }
+    curve.check_invariant(global).ok()?;
+
+    let rate = recent.weighted_rate(ETA_HALF_LIFE);
+    if rate <= 0.0 {
+        return None;
+    }
+    let sol_remaining = curve.sol_to_graduate();
+    let eta = Duration::try_from_secs_f64(sol_remaining as f64 / rate).ok()?;
+
+    let window = recent.max_window();
+    let average = recent.net_flow(window) as f64 / window.as_secs_f64();
+    let agreement = if average > 0.0 {
+        average.min(rate) / average.max(rate)
+    } else {
+        0.0
+    };
+    let samples = recent.len() as f64;
+
+    Some(EtaEstimate {
+        sol_remaining,
+        eta,
+        confidence: samples / (samples + CONFIDENCE_SAMPLE_SIZE) * agreement,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{accounts::PlannedTrade, events::TradeEvent, utils::clock::MockClock};
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+    use std::sync::Arc;
+
+    /// Timestamp the trade streams of the tests end at
+    const NOW: i64 = 1_700_000_000;
+
+    fn get_global() -> GlobalAccount {
+        GlobalAccount::new(
+            1,
+            true,
+            Pubkey::default(),
+            Pubkey::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        )
+    }
+
+    /// Curve after 20 SOL of buys
+    fn get_curve(global: &GlobalAccount) -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            1,
+            global.initial_virtual_token_reserves,
+            global.initial_virtual_sol_reserves,
+            global.initial_real_token_reserves,
+            0,
+            global.token_total_supply,
+            false,
+        )
+        .simulate_sequence(
+            &[PlannedTrade::Buy {
+                sol_amount: 20_000_000_000,
+            }],
+            global.fee_basis_points,
+        )
+        .unwrap()
+        .final_curve
+    }
+
+    /// Tracker fed with one trade per 10 seconds over the last five minutes
+    fn get_tracker(mint: Pubkey, flow: impl Fn(i64) -> i64) -> NetFlowTracker {
+        let clock = Arc::new(MockClock::at_unix_timestamp(NOW as u64));
+        let mut tracker = NetFlowTracker::new(Duration::from_secs(300))
+            .with_mint(mint)
+            .with_clock(clock);
+        for index in 0..30 {
+            let lamports = flow(index);
+            tracker.record(&TradeEvent {
+                mint,
+                sol_amount: lamports.unsigned_abs(),
+                token_amount: 1,
+                is_buy: lamports > 0,
+                user: Pubkey::new_unique(),
+                timestamp: NOW - 290 + index * 10,
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+            });
+        }
+        tracker
+    }
+
+    #[test]
+    fn test_graduation_eta_steady() {
+        let global = get_global();
+        let curve = get_curve(&global);
+        let mint = Pubkey::new_unique();
+        // 1 SOL every 10 seconds
+        let tracker = get_tracker(mint, |_| 1_000_000_000);
+
+        let estimate = graduation_eta(&curve, &global, &tracker).unwrap();
+        assert_eq!(estimate.sol_remaining, curve.sol_to_graduate());
+        // About 0.1 SOL per second, so about 10 seconds per remaining SOL
+        let expected = estimate.sol_remaining as f64 / 100_000_000.0;
+        let eta = estimate.eta.as_secs_f64();
+        assert!((eta - expected).abs() / expected < 0.1, "{}", eta);
+        assert!(estimate.confidence > 0.6, "{}", estimate.confidence);
+    }
+
+    #[test]
+    fn test_graduation_eta_accelerating() {
+        let global = get_global();
+        let curve = get_curve(&global);
+        let mint = Pubkey::new_unique();
+        let steady = graduation_eta(&curve, &global, &get_tracker(mint, |_| 1_000_000_000));
+        // Same total inflow, growing with every trade
+        let accelerating = get_tracker(mint, |index| (index + 1) * 2_000_000_000 / 31);
+
+        let estimate = graduation_eta(&curve, &global, &accelerating).unwrap();
+        let steady = steady.unwrap();
+        assert!(estimate.eta < steady.eta);
+        assert!(estimate.confidence < steady.confidence);
+        assert!(estimate.confidence > 0.0);
+    }
+
+    #[test]
+    fn test_graduation_eta_reversing() {
+        let global = get_global();
+        let curve = get_curve(&global);
+        let mint = Pubkey::new_unique();
+        // Buys for four minutes, then heavy selling
+        let reversing = get_tracker(mint, |index| {
+            if index < 24 {
+                1_000_000_000
+            } else {
+                -5_000_000_000
+            }
+        });
+        assert!(reversing.net_flow(Duration::from_secs(300)) < 0);
+        assert_eq!(graduation_eta(&curve, &global, &reversing), None);
+
+        // A net positive window still gives no estimate once the recent flow turns negative
+        let turning = get_tracker(mint, |index| {
+            if index < 27 {
+                1_000_000_000
+            } else {
+                -4_000_000_000
+            }
+        });
+        assert!(turning.net_flow(Duration::from_secs(300)) > 0);
+        assert_eq!(graduation_eta(&curve, &global, &turning), None);
+
+        // No trades, no estimate
+        let quiet = NetFlowTracker::new(Duration::from_secs(300));
+        assert_eq!(graduation_eta(&curve, &global, &quiet), None);
+    }
+
+    #[test]
+    fn test_graduation_eta_edge_cases() {
+        let global = get_global();
+        let mint = Pubkey::new_unique();
+        let tracker = get_tracker(mint, |_| 1_000_000_000);
+
+        let mut complete = get_curve(&global);
+        complete.complete = true;
+        let estimate = graduation_eta(&complete, &global, &tracker).unwrap();
+        assert_eq!(estimate.eta, Duration::ZERO);
+        assert_eq!(estimate.sol_remaining, 0);
+
+        // Reserves that do not match the global account give no estimate
+        let mut inconsistent = get_curve(&global);
+        inconsistent.real_sol_reserves += 1;
+        assert_eq!(graduation_eta(&inconsistent, &global, &tracker), None);
+    }
+
+    /// Synthetic trades on launch-era curves, with the curve state each executes against
+    const SYNTHETIC_TRADES: &str = include_str!("../accounts/fixtures/synthetic_trades.json");
+
+    #[test]
+    fn test_synthetic_curves_classify_as_launch_era() {
+        let trades: serde_json::Value = serde_json::from_str(SYNTHETIC_TRADES).unwrap();
+        let trades = trades["trades"].as_array().unwrap();
+        assert!(!trades.is_empty());
+
+        for trade in trades {
+            let reserves = |field: &str| trade["curve"][field].as_u64().unwrap();
+            let curve = BondingCurveAccount::new(
+                1,
+                reserves("virtual_token_reserves"),
+                reserves("virtual_sol_reserves"),
+                reserves("real_token_reserves"),
+                reserves("real_sol_reserves"),
+                reserves("token_total_supply"),
+                false,
+            );
+            assert_eq!(ParameterEra::of_curve(&curve), Some(&PARAMETER_ERAS[0]));
+        }
+        assert_eq!(PARAMETER_ERAS[0].name, "launch");
+        assert_eq!(
+            PARAMETER_ERAS[0].initial_invariant_k(),
+            get_global().initial_invariant_k()
+        );
+    }
+
+    #[test]
+    fn test_parameter_era_classification() {
+        let global = get_global();
+        let k = global.initial_invariant_k();
+        let supply = global.token_total_supply;
+        let launch = Some(&PARAMETER_ERAS[0]);
+
+        // Fresh and heavily traded curves of the era
+        assert_eq!(ParameterEra::classify(k, supply), launch);
+        assert_eq!(ParameterEra::of_curve(&get_curve(&global)), launch);
+        assert_eq!(ParameterEra::classify(k + k / 1_000, supply), launch);
+        assert_eq!(ParameterEra::classify(k + k / 900, supply), None);
+        assert_eq!(ParameterEra::classify(k - 1, supply), None);
+        assert_eq!(ParameterEra::classify(k, supply / 10), None);
+
+        // Curves of a local fork with other parameters, and migrated curves
+        let mut fork = global.clone();
+        fork.initial_virtual_sol_reserves = 40_000_000_000;
+        assert_eq!(ParameterEra::of_curve(&get_curve(&fork)), None);
+        let mut migrated = get_curve(&global);
+        migrated.virtual_sol_reserves = 0;
+        migrated.virtual_token_reserves = 0;
+        assert_eq!(ParameterEra::of_curve(&migrated), None);
+
+        // Eras are told apart by their invariant
+        let era = ParameterEra {
+            name: "fork",
+            effective_from: NOW,
+            initial_virtual_token_reserves: fork.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: fork.initial_virtual_sol_reserves,
+            token_total_supply: fork.token_total_supply,
+        };
+        assert!(era.matches(get_curve(&fork).invariant_k(), supply));
+        assert!(!era.matches(k, supply));
+    }
+}
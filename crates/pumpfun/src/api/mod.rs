@@ -0,0 +1,304 @@
+//! Client for authenticated Pump.fun backend API endpoints
+//!
+//! Some backend endpoints (profile, comments and certain data routes) require a session token
+//! obtained by signing a login message with the wallet. This module implements that
+//! sign-in-with-Solana flow:
+//!
+//! 1. Fetch a single-use nonce for the wallet from [`NONCE_PATH`].
+//! 2. Sign the canonical [`login_message`] for the nonce with the payer keypair.
+//! 3. Exchange the address, nonce and signature for a token at [`LOGIN_PATH`].
+//!
+//! The token is stored on the [`ApiClient`] and refreshed automatically when a request is
+//! rejected with `401 Unauthorized`. Tokens and signatures are never logged, and [`AuthToken`]
+//! redacts its value when debug-printed.
+//!
+//! This module is only available with the `api` feature.
+
+use isahc::{AsyncReadResponseExt, HttpClient, Request};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use solana_sdk::signature::{Keypair, Signer};
+use std::{fmt, sync::Mutex};
+
+/// Base URL of the Pump.fun backend API
+pub const API_URL: &str = "https://frontend-api-v3.pump.fun";
+
+/// Path of the endpoint issuing login nonces
+pub const NONCE_PATH: &str = "/auth/nonce";
+
+/// Path of the endpoint exchanging a signed login message for a token
+pub const LOGIN_PATH: &str = "/auth/login";
+
+/// Errors returned by the backend API client
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ApiError {
+    /// HTTP transport error
+    Http(isahc::Error),
+    /// Request could not be built
+    Request(isahc::http::Error),
+    /// Response could not be read
+    Io(std::io::Error),
+    /// Response body could not be decoded
+    Json(serde_json::Error),
+    /// Backend responded with an unexpected status code
+    Status(u16),
+    /// Backend rejected the credentials, even after re-authenticating
+    Unauthorized,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Http(err) => write!(f, "HTTP error: {}", err),
+            Self::Request(err) => write!(f, "Invalid request: {}", err),
+            Self::Io(err) => write!(f, "Failed to read response: {}", err),
+            Self::Json(err) => write!(f, "Invalid response: {}", err),
+            Self::Status(status) => write!(f, "Unexpected status code: {}", status),
+            Self::Unauthorized => write!(f, "Unauthorized"),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Http(err) => Some(err),
+            Self::Request(err) => Some(err),
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Session token for authenticated backend requests
+///
+/// The token value is redacted from `Debug` output.
+#[derive(Clone, PartialEq, Eq, Deserialize)]
+pub struct AuthToken {
+    /// Bearer token value
+    #[serde(alias = "access_token", alias = "accessToken", alias = "auth_token")]
+    token: String,
+}
+
+impl AuthToken {
+    /// Gets the token value to send as a bearer token
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+}
+
+impl fmt::Debug for AuthToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthToken")
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Nonce issued by the backend for a login
+#[derive(Debug, Deserialize)]
+struct NonceResponse {
+    /// Single-use nonce to sign
+    nonce: String,
+}
+
+/// Signed login request exchanged for a token
+#[derive(Serialize)]
+struct LoginRequest<'b> {
+    /// Wallet address, base58 encoded
+    address: String,
+    /// Nonce that was signed
+    nonce: &'b str,
+    /// Signature of the login message, base58 encoded
+    signature: String,
+}
+
+/// Builds the canonical login message for a nonce
+///
+/// # Arguments
+///
+/// * `nonce` - Nonce issued by the backend
+///
+/// # Returns
+///
+/// The message the wallet signs to log in
+pub fn login_message(nonce: &str) -> String {
+    format!("Sign in to pump.fun: {}", nonce)
+}
+
+/// Client for authenticated Pump.fun backend API endpoints
+pub struct ApiClient<'a> {
+    /// Base URL of the backend API
+    base_url: String,
+    /// Keypair used to sign login messages
+    payer: &'a Keypair,
+    /// HTTP client
+    http: HttpClient,
+    /// Current session token, if logged in
+    token: Mutex<Option<AuthToken>>,
+}
+
+impl<'a> ApiClient<'a> {
+    /// Creates a new API client for the payer
+    ///
+    /// # Arguments
+    ///
+    /// * `payer` - Keypair used to sign login messages
+    ///
+    /// # Returns
+    ///
+    /// Returns a new client pointed at [`API_URL`], or an ApiError if the HTTP client could not
+    /// be created
+    pub fn new(payer: &'a Keypair) -> Result<Self, ApiError> {
+        Ok(Self {
+            base_url: API_URL.to_string(),
+            payer,
+            http: HttpClient::new().map_err(ApiError::Http)?,
+            token: Mutex::new(None),
+        })
+    }
+
+    /// Overrides the base URL of the backend API
+    ///
+    /// # Arguments
+    ///
+    /// * `base_url` - Base URL without a trailing slash
+    ///
+    /// # Returns
+    ///
+    /// Returns the client configured to use the provided base URL
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Gets the current session token, if logged in
+    pub fn token(&self) -> Option<AuthToken> {
+        self.token
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Logs in by signing a fresh nonce with the payer
+    ///
+    /// The token is stored on the client and used by [`ApiClient::get`].
+    ///
+    /// # Returns
+    ///
+    /// Returns the new session token, or an ApiError if the login failed
+    pub async fn authenticate(&self) -> Result<AuthToken, ApiError> {
+        let address = self.payer.pubkey().to_string();
+
+        let request = Request::get(format!(
+            "{}{}?address={}",
+            self.base_url, NONCE_PATH, address
+        ))
+        .body(())
+        .map_err(ApiError::Request)?;
+        let nonce: NonceResponse = self.send(request).await?;
+
+        let signature = self
+            .payer
+            .sign_message(login_message(&nonce.nonce).as_bytes());
+        let body = serde_json::to_vec(&LoginRequest {
+            address,
+            nonce: &nonce.nonce,
+            signature: signature.to_string(),
+        })
+        .map_err(ApiError::Json)?;
+
+        let request = Request::post(format!("{}{}", self.base_url, LOGIN_PATH))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .map_err(ApiError::Request)?;
+        let token: AuthToken = self.send(request).await?;
+
+        *self
+            .token
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(token.clone());
+
+        Ok(token)
+    }
+
+    /// Sends an authenticated GET request to the backend
+    ///
+    /// Logs in first if there is no session token. If the backend responds with
+    /// `401 Unauthorized`, the client logs in again and retries once.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of the endpoint, starting with a slash
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded response body, or an ApiError if the request failed
+    pub async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, ApiError> {
+        let token = match self.token() {
+            Some(token) => token,
+            None => self.authenticate().await?,
+        };
+
+        match self.send(self.authorized_get(path, &token)?).await {
+            Err(ApiError::Unauthorized) => {
+                let token = self.authenticate().await?;
+                self.send(self.authorized_get(path, &token)?).await
+            }
+            result => result,
+        }
+    }
+
+    /// Builds a GET request carrying the session token
+    fn authorized_get(&self, path: &str, token: &AuthToken) -> Result<Request<()>, ApiError> {
+        Request::get(format!("{}{}", self.base_url, path))
+            .header("Authorization", format!("Bearer {}", token.as_str()))
+            .body(())
+            .map_err(ApiError::Request)
+    }
+
+    /// Sends a request and decodes its JSON response
+    async fn send<B, T>(&self, request: Request<B>) -> Result<T, ApiError>
+    where
+        B: Into<isahc::AsyncBody>,
+        T: DeserializeOwned,
+    {
+        let mut response = self
+            .http
+            .send_async(request)
+            .await
+            .map_err(ApiError::Http)?;
+
+        match response.status().as_u16() {
+            200..=299 => {}
+            401 => return Err(ApiError::Unauthorized),
+            status => return Err(ApiError::Status(status)),
+        }
+
+        let text = response.text().await.map_err(ApiError::Io)?;
+        serde_json::from_str(&text).map_err(ApiError::Json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_login_message_signature() {
+        let payer = Keypair::new();
+        let message = login_message("abc123");
+        assert_eq!(message, "Sign in to pump.fun: abc123");
+
+        let signature = payer.sign_message(message.as_bytes());
+        assert!(signature.verify(payer.pubkey().as_ref(), message.as_bytes()));
+    }
+
+    #[test]
+    fn test_auth_token_is_redacted() {
+        let token: AuthToken = serde_json::from_str(r#"{"access_token":"secret"}"#).unwrap();
+        assert_eq!(token.as_str(), "secret");
+        assert!(!format!("{:?}", token).contains("secret"));
+    }
+}
@@ -0,0 +1,41 @@
+//! Regenerates the test vector corpus shared with SDKs in other languages
+//!
+//! Writes the vectors computed by the current code to `testdata/` at the root of the
+//! repository, or to the directory given as the first argument. See [`pumpfun::vectors`].
+//!
+//! ```sh
+//! cargo run --bin gen-vectors
+//! ```
+
+use pumpfun::vectors::Corpus;
+use std::path::PathBuf;
+
+fn main() -> std::io::Result<()> {
+    let dir = std::env::args_os()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../../testdata")));
+
+    let corpus = Corpus::generate();
+    let problems = corpus.check();
+    if !problems.is_empty() {
+        // Generated vectors always agree with the code, unless the math is not deterministic
+        for problem in &problems {
+            eprintln!("{}", problem);
+        }
+        return Err(std::io::Error::other(
+            "Generated vectors are not reproducible",
+        ));
+    }
+
+    corpus.write(&dir)?;
+    println!(
+        "Wrote {} curve, {} slippage, {} PDA and {} event vectors to {}",
+        corpus.curve.len(),
+        corpus.slippage.len(),
+        corpus.pdas.len(),
+        corpus.events.len(),
+        dir.display()
+    );
+    Ok(())
+}
@@ -0,0 +1,233 @@
+//! Command line client for the Pump.fun program
+//!
+//! Runs one command, or an interactive session over one warm client with `repl`:
+//!
+//! ```sh
+//! pumpfun [--url <cluster>] [--keypair <path>] <command> [args...]
+//! pumpfun [--url <cluster>] [--keypair <path>] repl [--journal <path>] [--watch <mint>]... [--script <file>]
+//! ```
+//!
+//! `--url` takes a cluster name (`mainnet`, `devnet`, `localnet`) or an RPC URL and defaults to
+//! devnet. `--keypair` defaults to the Solana CLI keypair at `~/.config/solana/id.json`. The
+//! commands are those of [`pumpfun::repl`]; run `pumpfun help` to list them.
+//!
+//! The keypair is only read by commands that need it, see [`Command::needs_keypair`]. The others
+//! run with a throwaway payer, so they work without a keypair. A session whose keypair cannot
+//! be read stays read-only and refuses the commands that need it. In a session, tab completes
+//! commands and watched mints, see [`Session::complete`].
+
+use anchor_client::solana_sdk::signature::read_keypair_file;
+use pumpfun::{
+    prelude::*,
+    repl::{Command, Session},
+};
+use rustyline::{
+    completion::Completer, error::ReadlineError, highlight::Highlighter, hint::Hinter,
+    history::DefaultHistory, validate::Validator, Context, Editor, Helper,
+};
+use std::{
+    cell::RefCell,
+    env,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Options of the `repl` subcommand
+#[derive(Default)]
+struct ReplOptions {
+    /// Journal shown by `journal tail`
+    journal: Option<PathBuf>,
+    /// Mints watched from the start
+    watch: Vec<Pubkey>,
+    /// Script to run instead of reading commands interactively
+    script: Option<PathBuf>,
+}
+
+fn main() -> ExitCode {
+    match run(env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(mut args: Vec<String>) -> Result<(), Box<dyn std::error::Error>> {
+    let mut cluster = Cluster::Devnet;
+    let mut keypair_path = env::var("HOME")
+        .map(|home| PathBuf::from(home).join(".config/solana/id.json"))
+        .unwrap_or_default();
+    while args.first().is_some_and(|arg| arg.starts_with("--")) {
+        let flag = args.remove(0);
+        let value = take_value(&mut args, &flag)?;
+        match flag.as_str() {
+            "--url" => cluster = value.parse()?,
+            "--keypair" => keypair_path = PathBuf::from(value),
+            _ => return Err(format!("Unknown option {}", flag).into()),
+        }
+    }
+    if args.is_empty() {
+        args.push("help".to_string());
+    }
+
+    // Trades retry on timers, which need a runtime to run on
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()?;
+
+    if args[0] != "repl" {
+        let command = Command::parse(&args.join(" "))?.unwrap_or(Command::Help);
+        if command.is_session_only() {
+            return Err("This command needs a session, run it in `pumpfun repl`".into());
+        }
+        // Commands that never sign or read the payer's holdings can use any payer
+        let payer = if command.needs_keypair() {
+            read_payer(&keypair_path)?
+        } else {
+            Keypair::new()
+        };
+        let client = PumpFun::new(cluster, &payer, Some(CommitmentConfig::confirmed()), None);
+        let output = runtime.block_on(Session::new(&client).execute(&command))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    let options = parse_repl_options(args.split_off(1))?;
+    let (payer, missing_keypair) = match read_payer(&keypair_path) {
+        Ok(payer) => (payer, None),
+        Err(err) => {
+            eprintln!("warning: {}, the session is read-only", err);
+            (Keypair::new(), Some(err))
+        }
+    };
+    // Fails a command that needs the keypair in a read-only session
+    let check_keypair = |command: &Command| match &missing_keypair {
+        Some(err) if command.needs_keypair() => Err(err.clone()),
+        _ => Ok(()),
+    };
+    let client = PumpFun::new(cluster, &payer, Some(CommitmentConfig::confirmed()), None);
+    let mut session = Session::new(&client);
+    if let Some(path) = &options.journal {
+        session = session.with_journal(TradeJournal::open(path)?);
+    }
+    for mint in options.watch {
+        runtime.block_on(session.execute(&Command::WatchAdd(mint)))?;
+    }
+    session.warm_up()?;
+
+    if let Some(path) = &options.script {
+        let script = std::fs::read_to_string(path)?;
+        // Refuse the whole script up front rather than stopping half way through it
+        for (index, line) in script.lines().enumerate() {
+            if let Ok(Some(command)) = Command::parse(line) {
+                check_keypair(&command).map_err(|err| format!("line {}: {}", index + 1, err))?;
+            }
+        }
+        let executed = runtime.block_on(session.run_script(&script, |line, output| {
+            println!("> {}", line);
+            if !output.is_empty() {
+                println!("{}", output);
+            }
+        }))?;
+        eprintln!("{} commands executed", executed);
+        return Ok(());
+    }
+
+    // The editor completes from the session, which commands update between lines
+    let session = RefCell::new(session);
+    let mut editor: Editor<SessionHelper, DefaultHistory> = Editor::new()?;
+    editor.set_helper(Some(SessionHelper { session: &session }));
+    loop {
+        let line = match editor.readline("pumpfun> ") {
+            Ok(line) => line,
+            // Ctrl-C drops the line being typed, Ctrl-D ends the session
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        };
+        editor.add_history_entry(line.as_str())?;
+        let command = match Command::parse(&line) {
+            Ok(Some(Command::Exit)) => break,
+            Ok(Some(command)) => command,
+            Ok(None) => continue,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                continue;
+            }
+        };
+        if let Err(err) = check_keypair(&command) {
+            eprintln!("error: {}", err);
+            continue;
+        }
+        // A failed command is reported and the session carries on
+        match runtime.block_on(session.borrow_mut().execute(&command)) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(err) => eprintln!("error: {}", err),
+        }
+    }
+    Ok(())
+}
+
+/// Line editor helper completing command lines from a session
+struct SessionHelper<'s, 'c, 'a> {
+    /// Session the completions are read from
+    session: &'s RefCell<Session<'c, 'a>>,
+}
+
+impl Completer for SessionHelper<'_, '_, '_> {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        // Only the text before the cursor is completed, replacing its last word
+        let line = &line[..pos];
+        let start = line.rfind(char::is_whitespace).map_or(0, |index| index + 1);
+        Ok((start, self.session.borrow().complete(line)))
+    }
+}
+
+impl Hinter for SessionHelper<'_, '_, '_> {
+    type Hint = String;
+}
+
+impl Highlighter for SessionHelper<'_, '_, '_> {}
+
+impl Validator for SessionHelper<'_, '_, '_> {}
+
+impl Helper for SessionHelper<'_, '_, '_> {}
+
+/// Reads the payer keypair
+fn read_payer(path: &Path) -> Result<Keypair, String> {
+    read_keypair_file(path)
+        .map_err(|err| format!("Failed to read keypair {}: {}", path.display(), err))
+}
+
+/// Removes the value of a flag from the front of the arguments
+fn take_value(args: &mut Vec<String>, flag: &str) -> Result<String, String> {
+    if args.is_empty() {
+        return Err(format!("Missing value for {}", flag));
+    }
+    Ok(args.remove(0))
+}
+
+/// Parses the options following `repl`
+fn parse_repl_options(mut args: Vec<String>) -> Result<ReplOptions, Box<dyn std::error::Error>> {
+    let mut options = ReplOptions::default();
+    while !args.is_empty() {
+        let flag = args.remove(0);
+        let value = take_value(&mut args, &flag)?;
+        match flag.as_str() {
+            "--journal" => options.journal = Some(PathBuf::from(value)),
+            "--watch" => options.watch.push(value.parse()?),
+            "--script" => options.script = Some(PathBuf::from(value)),
+            _ => return Err(format!("Unknown option {}", flag).into()),
+        }
+    }
+    Ok(options)
+}
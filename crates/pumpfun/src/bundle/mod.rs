@@ -0,0 +1,606 @@
+//! Pre-submission checks of transaction bundles
+//!
+//! A bundle only lands if every transaction in it succeeds, so a failure discovered after
+//! submission costs the whole sequence. [`crate::PumpFun::simulate_bundle`] runs each transaction
+//! through `simulateTransaction` first and reports the outcome of each, with the bonding curves
+//! the bundle trades on as they would stand afterwards:
+//!
+//! - `SimulatedTransaction`: Outcome of one transaction, with its logs and curve states.
+//! - `BundleSimulation`: Outcome of every transaction, with the final state of each curve.
+//! - `referenced_curves`: The bonding curves a transaction creates or trades on.
+//! - `replay`: Replays the Pump.fun instructions of a bundle in process, in order.
+//!
+//! The RPC simulates each transaction against the current cluster state, not against the state
+//! left by the transactions before it in the bundle. Transactions that depend on earlier ones,
+//! such as buys following the create of a new token, can fail in simulation even though the
+//! bundle would succeed, and the curve states only reflect the last transaction touching each
+//! curve. Treat a clean simulation as a necessary condition, not a guarantee of atomic success.
+//!
+//! [`crate::PumpFun::replay_bundle`] covers the inter-transaction state instead: it clones the
+//! referenced curves and the global account at the current slot and executes the bundle's
+//! create, buy and sell instructions against them in order, each transaction seeing the curves
+//! left by the ones before it and rolling back as a whole if any of its instructions fails. Only
+//! the Pump.fun curve accounting is executed: signatures, SOL and token balances, and other
+//! programs are not, so run both checks before submitting a bundle that depends on itself.
+
+use crate::{
+    accounts::{BondingCurveAccount, GlobalAccount},
+    constants,
+    error::{ClientError, PumpFunProgramError},
+    mev::{TradeInstruction, BUY_DISCRIMINATOR, SELL_DISCRIMINATOR},
+};
+use anchor_client::anchor_lang::Discriminator;
+use pumpfun_cpi as cpi;
+use solana_sdk::{
+    instruction::InstructionError,
+    pubkey::Pubkey,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+/// Outcome of simulating one transaction of a bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulatedTransaction {
+    /// Position of the transaction in the bundle
+    pub index: usize,
+    /// First signature of the transaction, None if it has no signatures
+    pub signature: Option<Signature>,
+    /// Error the transaction failed with, None if it succeeded
+    pub err: Option<TransactionError>,
+    /// Program logs emitted during the simulation
+    pub logs: Vec<String>,
+    /// Compute units consumed, if reported
+    pub units_consumed: Option<u64>,
+    /// Each bonding curve the transaction references, with its state after the transaction,
+    /// None if the account does not exist or could not be decoded
+    pub curves: Vec<(Pubkey, Option<BondingCurveAccount>)>,
+}
+
+impl SimulatedTransaction {
+    /// Whether the transaction succeeded
+    pub fn succeeded(&self) -> bool {
+        self.err.is_none()
+    }
+}
+
+/// Outcome of simulating every transaction of a bundle
+#[derive(Debug, Clone, PartialEq)]
+pub struct BundleSimulation {
+    /// Outcome of each transaction, in bundle order
+    pub transactions: Vec<SimulatedTransaction>,
+    /// Each bonding curve referenced by the bundle, in order of first reference, with the state
+    /// reported after the last transaction touching it
+    pub final_curves: Vec<(Pubkey, Option<BondingCurveAccount>)>,
+}
+
+impl BundleSimulation {
+    /// Collects the outcomes of a bundle's transactions
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - Outcome of each transaction, in bundle order
+    ///
+    /// # Returns
+    ///
+    /// Returns the simulation with the final state of each referenced curve
+    pub fn new(transactions: Vec<SimulatedTransaction>) -> Self {
+        let mut final_curves: Vec<(Pubkey, Option<BondingCurveAccount>)> = Vec::new();
+        for (curve, state) in transactions.iter().flat_map(|tx| &tx.curves) {
+            match final_curves.iter_mut().find(|(seen, _)| seen == curve) {
+                Some((_, last)) => *last = state.clone(),
+                None => final_curves.push((*curve, state.clone())),
+            }
+        }
+
+        Self {
+            transactions,
+            final_curves,
+        }
+    }
+
+    /// Whether every transaction succeeded
+    pub fn succeeded(&self) -> bool {
+        self.transactions
+            .iter()
+            .all(SimulatedTransaction::succeeded)
+    }
+
+    /// Gets the first transaction that failed, None if all succeeded
+    pub fn first_failure(&self) -> Option<&SimulatedTransaction> {
+        self.transactions.iter().find(|tx| !tx.succeeded())
+    }
+
+    /// Fails if any transaction failed, so a bundle can be aborted before submission
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok if every transaction succeeded, or a SimulationError naming the first failure
+    pub fn ensure_success(&self) -> Result<(), ClientError> {
+        match self.first_failure() {
+            None => Ok(()),
+            Some(tx) => Err(ClientError::SimulationError(format!(
+                "bundle transaction {} failed: {}",
+                tx.index,
+                tx.err.as_ref().map(ToString::to_string).unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// Finds the bonding curves a transaction creates or trades on
+///
+/// Recognises Pump.fun create, buy and sell instructions whose accounts are in the message's
+/// static keys. Curves referenced through address lookup tables are not found.
+///
+/// # Arguments
+///
+/// * `transaction` - Transaction to inspect
+///
+/// # Returns
+///
+/// Returns the bonding curves in order of first reference
+pub fn referenced_curves(transaction: &VersionedTransaction) -> Vec<Pubkey> {
+    let keys = transaction.message.static_account_keys();
+    let mut curves: Vec<Pubkey> = Vec::new();
+
+    for instruction in transaction.message.instructions() {
+        if keys.get(instruction.program_id_index as usize) != Some(&constants::accounts::PUMPFUN) {
+            continue;
+        }
+
+        // Position of the bonding curve in the instruction's accounts
+        let position = match instruction.data.first_chunk::<8>() {
+            Some(discriminator) if *discriminator == cpi::instruction::Create::DISCRIMINATOR => 2,
+            Some(&BUY_DISCRIMINATOR) | Some(&SELL_DISCRIMINATOR) => 3,
+            _ => continue,
+        };
+        let curve = instruction
+            .accounts
+            .get(position)
+            .and_then(|index| keys.get(*index as usize));
+        if let Some(curve) = curve {
+            if !curves.contains(curve) {
+                curves.push(*curve);
+            }
+        }
+    }
+
+    curves
+}
+
+/// Replays the Pump.fun instructions of a bundle against local copies of its curves
+///
+/// Transactions run in bundle order against a working copy of the curves, so each sees the
+/// state left by the ones before it. Creates initialize a curve from the global account's
+/// initial reserves, buys charge the program's cost for the exact token amount and sells pay
+/// out the program's SOL output, each checked against the instruction's slippage bound with the
+/// global's total fee. A failing instruction fails its transaction and rolls back every change the
+/// transaction made, as the runtime would. Only instructions found by [`referenced_curves`] are
+/// executed; the transactions are reported without logs or compute units.
+///
+/// # Arguments
+///
+/// * `transactions` - Transactions of the bundle, in execution order
+/// * `global` - Global account supplying the fee and the initial reserves of new curves
+/// * `curves` - State of each referenced curve before the bundle, None if it does not exist
+///
+/// # Returns
+///
+/// Returns the outcome of each transaction with the final state of each referenced curve
+pub fn replay(
+    transactions: &[VersionedTransaction],
+    global: &GlobalAccount,
+    curves: &[(Pubkey, Option<BondingCurveAccount>)],
+) -> BundleSimulation {
+    let mut state: Vec<(Pubkey, Option<BondingCurveAccount>)> = curves.to_vec();
+    let mut replayed = Vec::with_capacity(transactions.len());
+
+    for (index, transaction) in transactions.iter().enumerate() {
+        let before = state.clone();
+        let err = replay_transaction(transaction, global, &mut state).err();
+        if err.is_some() {
+            state = before;
+        }
+
+        let curves = referenced_curves(transaction)
+            .into_iter()
+            .map(|curve| (curve, curve_state(&state, &curve).clone()))
+            .collect();
+        replayed.push(SimulatedTransaction {
+            index,
+            signature: transaction.signatures.first().copied(),
+            err,
+            logs: Vec::new(),
+            units_consumed: None,
+            curves,
+        });
+    }
+
+    BundleSimulation::new(replayed)
+}
+
+/// Gets the working state of a curve, None if it is unknown or does not exist
+fn curve_state<'s>(
+    state: &'s [(Pubkey, Option<BondingCurveAccount>)],
+    curve: &Pubkey,
+) -> &'s Option<BondingCurveAccount> {
+    state
+        .iter()
+        .find(|(address, _)| address == curve)
+        .map(|(_, state)| state)
+        .unwrap_or(&None)
+}
+
+/// Executes the Pump.fun instructions of one transaction against the working curves
+fn replay_transaction(
+    transaction: &VersionedTransaction,
+    global: &GlobalAccount,
+    state: &mut Vec<(Pubkey, Option<BondingCurveAccount>)>,
+) -> Result<(), TransactionError> {
+    let keys = transaction.message.static_account_keys();
+
+    for (position, instruction) in transaction.message.instructions().iter().enumerate() {
+        if keys.get(instruction.program_id_index as usize) != Some(&constants::accounts::PUMPFUN) {
+            continue;
+        }
+        let fail = |err| TransactionError::InstructionError(position as u8, err);
+        let program_error = |err: PumpFunProgramError| fail(InstructionError::Custom(err.code()));
+
+        // None for a create, which has no slippage bound to check
+        let trade = match instruction.data.first_chunk::<8>() {
+            Some(discriminator) if *discriminator == cpi::instruction::Create::DISCRIMINATOR => {
+                None
+            }
+            Some(&BUY_DISCRIMINATOR) | Some(&SELL_DISCRIMINATOR) => Some(
+                TradeInstruction::decode(&instruction.data)
+                    .ok_or(fail(InstructionError::InvalidInstructionData))?,
+            ),
+            _ => continue,
+        };
+        let account = if trade.is_some() { 3 } else { 2 };
+        let Some(curve) = instruction
+            .accounts
+            .get(account)
+            .and_then(|index| keys.get(*index as usize))
+        else {
+            return Err(fail(InstructionError::NotEnoughAccountKeys));
+        };
+
+        if !state.iter().any(|(address, _)| address == curve) {
+            state.push((*curve, None));
+        }
+        let slot = &mut state
+            .iter_mut()
+            .find(|(address, _)| address == curve)
+            .expect("curve was just inserted")
+            .1;
+
+        let Some(trade) = trade else {
+            if slot.is_some() {
+                return Err(fail(InstructionError::AccountAlreadyInitialized));
+            }
+            *slot = Some(BondingCurveAccount::new(
+                u64::from_le_bytes(cpi::BondingCurve::DISCRIMINATOR),
+                global.initial_virtual_token_reserves,
+                global.initial_virtual_sol_reserves,
+                global.initial_real_token_reserves,
+                0,
+                global.token_total_supply,
+                false,
+            ));
+            continue;
+        };
+
+        let Some(curve) = slot.as_mut() else {
+            return Err(fail(InstructionError::UninitializedAccount));
+        };
+        if curve.complete {
+            return Err(program_error(PumpFunProgramError::BondingCurveComplete));
+        }
+        let virtual_sol = curve.virtual_sol_reserves as u128;
+        let virtual_token = curve.virtual_token_reserves as u128;
+        let fee = |sol: u128| sol * global.total_fee_bps() as u128 / 10_000;
+
+        match trade {
+            TradeInstruction::Buy {
+                amount,
+                max_sol_cost,
+            } => {
+                if amount > curve.real_token_reserves || amount as u128 >= virtual_token {
+                    return Err(fail(InstructionError::InsufficientFunds));
+                }
+                let amount_u128 = amount as u128;
+                let cost = amount_u128 * virtual_sol / (virtual_token - amount_u128) + 1;
+                if cost + fee(cost) > max_sol_cost as u128 {
+                    return Err(program_error(PumpFunProgramError::TooMuchSolRequired));
+                }
+                let cost =
+                    u64::try_from(cost).map_err(|_| fail(InstructionError::ArithmeticOverflow))?;
+                curve.virtual_sol_reserves = curve
+                    .virtual_sol_reserves
+                    .checked_add(cost)
+                    .ok_or(fail(InstructionError::ArithmeticOverflow))?;
+                curve.real_sol_reserves = curve
+                    .real_sol_reserves
+                    .checked_add(cost)
+                    .ok_or(fail(InstructionError::ArithmeticOverflow))?;
+                curve.virtual_token_reserves -= amount;
+                curve.real_token_reserves -= amount;
+                curve.complete = curve.real_token_reserves == 0;
+            }
+            TradeInstruction::Sell {
+                amount,
+                min_sol_output,
+            } => {
+                let output = amount as u128 * virtual_sol / (virtual_token + amount as u128);
+                if output > curve.real_sol_reserves as u128 {
+                    return Err(fail(InstructionError::InsufficientFunds));
+                }
+                if output - fee(output) < min_sol_output as u128 {
+                    return Err(program_error(PumpFunProgramError::TooLittleSolReceived));
+                }
+                let output = output as u64;
+                curve.virtual_token_reserves = curve
+                    .virtual_token_reserves
+                    .checked_add(amount)
+                    .ok_or(fail(InstructionError::ArithmeticOverflow))?;
+                curve.real_token_reserves = curve
+                    .real_token_reserves
+                    .checked_add(amount)
+                    .ok_or(fail(InstructionError::ArithmeticOverflow))?;
+                curve.virtual_sol_reserves -= output;
+                curve.real_sol_reserves -= output;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction, InstructionError},
+        message::{v0, VersionedMessage},
+    };
+
+    fn get_transaction(instructions: &[Instruction], payer: &Pubkey) -> VersionedTransaction {
+        let message = v0::Message::try_compile(payer, instructions, &[], Hash::default()).unwrap();
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::V0(message),
+        }
+    }
+
+    fn get_instruction(discriminator: [u8; 8], accounts: &[Pubkey]) -> Instruction {
+        Instruction::new_with_bytes(
+            constants::accounts::PUMPFUN,
+            &discriminator,
+            accounts
+                .iter()
+                .map(|account| AccountMeta::new(*account, false))
+                .collect(),
+        )
+    }
+
+    fn get_simulated(
+        index: usize,
+        err: Option<TransactionError>,
+        curves: Vec<(Pubkey, Option<BondingCurveAccount>)>,
+    ) -> SimulatedTransaction {
+        SimulatedTransaction {
+            index,
+            signature: Some(Signature::new_unique()),
+            err,
+            logs: Vec::new(),
+            units_consumed: None,
+            curves,
+        }
+    }
+
+    #[test]
+    fn test_referenced_curves() {
+        let payer = Pubkey::new_unique();
+        let accounts: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+
+        let transaction = get_transaction(
+            &[
+                get_instruction(cpi::instruction::Create::DISCRIMINATOR, &accounts),
+                get_instruction(BUY_DISCRIMINATOR, &accounts),
+                get_instruction(SELL_DISCRIMINATOR, &accounts),
+                // Unknown instructions and truncated account lists are skipped
+                get_instruction([0; 8], &accounts),
+                get_instruction(BUY_DISCRIMINATOR, &accounts[..2]),
+            ],
+            &payer,
+        );
+        assert_eq!(
+            referenced_curves(&transaction),
+            vec![accounts[2], accounts[3]]
+        );
+
+        // Other programs are ignored
+        let mut instruction = get_instruction(BUY_DISCRIMINATOR, &accounts);
+        instruction.program_id = Pubkey::new_unique();
+        assert!(referenced_curves(&get_transaction(&[instruction], &payer)).is_empty());
+    }
+
+    #[test]
+    fn test_bundle_simulation() {
+        let curve = Pubkey::new_unique();
+        let before = BondingCurveAccount::new(1, 1000, 1000, 500, 0, 1000, false);
+        let after = BondingCurveAccount::new(1, 900, 1112, 400, 112, 1000, false);
+
+        let simulation = BundleSimulation::new(vec![
+            get_simulated(0, None, vec![(curve, None)]),
+            get_simulated(1, None, vec![(curve, Some(before))]),
+            get_simulated(2, None, vec![(curve, Some(after.clone()))]),
+        ]);
+        assert!(simulation.succeeded());
+        assert!(simulation.ensure_success().is_ok());
+        assert_eq!(simulation.final_curves, vec![(curve, Some(after))]);
+
+        let simulation = BundleSimulation::new(vec![
+            get_simulated(0, None, Vec::new()),
+            get_simulated(
+                1,
+                Some(TransactionError::InstructionError(
+                    0,
+                    InstructionError::Custom(6002),
+                )),
+                Vec::new(),
+            ),
+            get_simulated(2, Some(TransactionError::AccountNotFound), Vec::new()),
+        ]);
+        assert!(!simulation.succeeded());
+        assert_eq!(simulation.first_failure().unwrap().index, 1);
+        assert!(matches!(
+            simulation.ensure_success(),
+            Err(ClientError::SimulationError(message)) if message.contains("transaction 1")
+        ));
+    }
+
+    fn get_trade(discriminator: [u8; 8], amount: u64, limit: u64, curve: &Pubkey) -> Instruction {
+        let mut instruction = get_instruction(
+            discriminator,
+            &[
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                *curve,
+            ],
+        );
+        instruction.data.extend_from_slice(&amount.to_le_bytes());
+        instruction.data.extend_from_slice(&limit.to_le_bytes());
+        instruction
+    }
+
+    #[test]
+    fn test_replay_carries_state_between_transactions() {
+        let payer = Pubkey::new_unique();
+        let curve = Pubkey::new_unique();
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Pubkey::default(),
+            Pubkey::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        );
+        let create = get_instruction(
+            cpi::instruction::Create::DISCRIMINATOR,
+            &[Pubkey::new_unique(), Pubkey::new_unique(), curve],
+        );
+        // These tokens of a new curve cost exactly 1 SOL, plus the 1% fee
+        let buy = get_trade(BUY_DISCRIMINATOR, 34_612_903_225_806, 1_010_000_000, &curve);
+        // Buying the same amount again costs more than the first bound allows
+        let expensive = get_trade(BUY_DISCRIMINATOR, 34_612_903_225_806, 1_010_000_000, &curve);
+        let dust = get_trade(SELL_DISCRIMINATOR, 1_000, 0, &curve);
+        let sell = get_trade(SELL_DISCRIMINATOR, 34_612_903_225_806, 0, &curve);
+
+        let simulation = replay(
+            &[
+                get_transaction(&[create.clone()], &payer),
+                get_transaction(&[buy], &payer),
+                // The first instruction succeeds, but the transaction rolls back as a whole
+                get_transaction(&[dust, expensive], &payer),
+                get_transaction(&[sell], &payer),
+                get_transaction(&[create], &payer),
+            ],
+            &global,
+            &[(curve, None)],
+        );
+
+        let bought = BondingCurveAccount::new(
+            u64::from_le_bytes(cpi::BondingCurve::DISCRIMINATOR),
+            1_038_387_096_774_194,
+            31_000_000_000,
+            758_487_096_774_194,
+            1_000_000_000,
+            1_000_000_000_000_000,
+            false,
+        );
+        assert!(simulation.transactions[0].succeeded());
+        assert_eq!(
+            simulation.transactions[1].curves,
+            vec![(curve, Some(bought.clone()))]
+        );
+        assert_eq!(
+            simulation.transactions[2].err,
+            Some(TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(6002)
+            ))
+        );
+        assert_eq!(
+            simulation.transactions[2].curves,
+            vec![(curve, Some(bought))]
+        );
+        // Selling everything back leaves the rounding in the curve
+        assert!(simulation.transactions[3].succeeded());
+        let sold = simulation.transactions[3].curves[0].1.clone().unwrap();
+        assert_eq!(sold.virtual_token_reserves, 1_073_000_000_000_000);
+        assert_eq!(sold.real_sol_reserves, 1);
+        assert_eq!(
+            simulation.transactions[4].err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::AccountAlreadyInitialized
+            ))
+        );
+        assert_eq!(simulation.final_curves, vec![(curve, Some(sold))]);
+        assert_eq!(simulation.first_failure().unwrap().index, 2);
+    }
+
+    #[test]
+    fn test_replay_rejects_trades_on_missing_or_complete_curves() {
+        let payer = Pubkey::new_unique();
+        let missing = Pubkey::new_unique();
+        let complete = Pubkey::new_unique();
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Pubkey::default(),
+            Pubkey::default(),
+            1000,
+            1000,
+            500,
+            1000,
+            100,
+        );
+
+        let simulation = replay(
+            &[
+                get_transaction(
+                    &[get_trade(BUY_DISCRIMINATOR, 1, u64::MAX, &missing)],
+                    &payer,
+                ),
+                get_transaction(&[get_trade(SELL_DISCRIMINATOR, 1, 0, &complete)], &payer),
+            ],
+            &global,
+            &[(
+                complete,
+                Some(BondingCurveAccount::new(1, 500, 2000, 0, 1000, 1000, true)),
+            )],
+        );
+        assert_eq!(
+            simulation.transactions[0].err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::UninitializedAccount
+            ))
+        );
+        assert_eq!(
+            simulation.transactions[1].err,
+            Some(TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(6005)
+            ))
+        );
+    }
+}
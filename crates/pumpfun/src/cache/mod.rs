@@ -0,0 +1,317 @@
+//! On-disk cache of lookups that never change
+//!
+//! A token's creation data, creator and token program are fixed once the token exists, yet an
+//! in-memory cache loses them on every restart. [`ImmutableCache`] keeps them in a flat file so
+//! a client created with [`crate::PumpFun::with_immutable_cache`] answers them without RPC calls
+//! after a restart.
+//!
+//! Each entry is a line of `namespace`, `key`, JSON value and checksum separated by tabs. The
+//! file is loaded into an in-memory index when opened and new entries are appended. Lines that
+//! are truncated or fail their checksum are skipped, so a corrupted file only costs the
+//! affected lookups, which are refetched and rewritten. The number of entries is bounded; the
+//! oldest are evicted and the file is compacted when the bound is exceeded.
+
+use serde::{de::DeserializeOwned, Serialize};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Default maximum number of entries kept in the cache
+pub const DEFAULT_MAX_ENTRIES: usize = 100_000;
+
+/// Index of the entries in the cache file
+#[derive(Debug, Default)]
+struct CacheState {
+    /// JSON value of each entry, keyed by `namespace\tkey`
+    entries: HashMap<String, String>,
+    /// Entry keys from oldest to newest
+    order: VecDeque<String>,
+    /// Number of lines in the file, including superseded and corrupted ones
+    lines: usize,
+}
+
+/// On-disk cache of lookups that never change
+#[derive(Debug)]
+pub struct ImmutableCache {
+    /// File the entries are stored in
+    path: PathBuf,
+    /// Maximum number of entries kept
+    max_entries: usize,
+    /// In-memory index of the file
+    state: Mutex<CacheState>,
+}
+
+impl ImmutableCache {
+    /// Opens a cache file, creating it on the first insert if it does not exist
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File the entries are stored in
+    ///
+    /// # Returns
+    ///
+    /// Returns the cache, or an I/O error if an existing file cannot be read
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut state = CacheState::default();
+        let contents = match fs::read(&path) {
+            Ok(contents) => String::from_utf8_lossy(&contents).into_owned(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err),
+        };
+
+        let mut corrupted = false;
+        for line in contents.lines() {
+            state.lines += 1;
+            match parse_line(line) {
+                Some((key, value)) => {
+                    if state.entries.insert(key.clone(), value).is_none() {
+                        state.order.push_back(key);
+                    }
+                }
+                None => corrupted = true,
+            }
+        }
+
+        let cache = Self {
+            path,
+            max_entries: DEFAULT_MAX_ENTRIES,
+            state: Mutex::new(state),
+        };
+        if corrupted {
+            // Drop the unreadable lines now rather than skipping them on every open
+            let mut state = cache.lock();
+            if let Err(err) = cache.compact(&mut state) {
+                tracing::warn!(path = %cache.path.display(), "Failed to compact cache: {}", err);
+            }
+        }
+
+        Ok(cache)
+    }
+
+    /// Overrides the maximum number of entries kept
+    ///
+    /// # Arguments
+    ///
+    /// * `max_entries` - Maximum number of entries, at least 1
+    ///
+    /// # Returns
+    ///
+    /// Returns the cache with the new bound, applied on the next insert
+    pub fn with_max_entries(mut self, max_entries: usize) -> Self {
+        self.max_entries = max_entries.max(1);
+        self
+    }
+
+    /// Gets a cached value
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Kind of lookup the value belongs to
+    /// * `key` - Account the value was looked up for
+    ///
+    /// # Returns
+    ///
+    /// Returns the value, or None if it is not cached or cannot be decoded
+    pub fn get<T: DeserializeOwned>(&self, namespace: &str, key: &Pubkey) -> Option<T> {
+        let state = self.lock();
+        let value = state.entries.get(&entry_key(namespace, key))?;
+        serde_json::from_str(value).ok()
+    }
+
+    /// Caches a value, appending it to the file
+    ///
+    /// # Arguments
+    ///
+    /// * `namespace` - Kind of lookup the value belongs to
+    /// * `key` - Account the value was looked up for
+    /// * `value` - Value to cache
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok once the value is written, or an I/O error
+    pub fn insert<T: Serialize>(&self, namespace: &str, key: &Pubkey, value: &T) -> io::Result<()> {
+        let value = serde_json::to_string(value)?;
+        let key = entry_key(namespace, key);
+
+        let mut state = self.lock();
+        match state.entries.insert(key.clone(), value.clone()) {
+            Some(previous) if previous == value => return Ok(()),
+            Some(_) => {}
+            None => state.order.push_back(key.clone()),
+        }
+
+        let mut evicted = false;
+        while state.entries.len() > self.max_entries {
+            let Some(oldest) = state.order.pop_front() else {
+                break;
+            };
+            state.entries.remove(&oldest);
+            evicted = true;
+        }
+
+        // Rewrite the file once superseded lines make up most of it
+        if evicted || state.lines >= 2 * state.entries.len().max(1) {
+            return self.compact(&mut state);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(format_line(&key, &value).as_bytes())?;
+        state.lines += 1;
+        Ok(())
+    }
+
+    /// Returns the number of cached entries
+    pub fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Returns whether the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Locks the index, recovering it if a writer panicked
+    fn lock(&self) -> std::sync::MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Rewrites the file with only the current entries, oldest first
+    fn compact(&self, state: &mut CacheState) -> io::Result<()> {
+        let contents: String = state
+            .order
+            .iter()
+            .filter_map(|key| Some(format_line(key, state.entries.get(key)?)))
+            .collect();
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, contents)?;
+        fs::rename(&tmp, &self.path)?;
+        state.lines = state.entries.len();
+        Ok(())
+    }
+}
+
+/// Index key of an entry
+fn entry_key(namespace: &str, key: &Pubkey) -> String {
+    format!("{}\t{}", namespace, key)
+}
+
+/// Formats an entry as a line of the cache file
+fn format_line(key: &str, value: &str) -> String {
+    let body = format!("{}\t{}", key, value);
+    format!("{}\t{:016x}\n", body, checksum(&body))
+}
+
+/// Parses a line of the cache file, rejecting truncated or altered lines
+fn parse_line(line: &str) -> Option<(String, String)> {
+    let (body, sum) = line.rsplit_once('\t')?;
+    if u64::from_str_radix(sum, 16).ok()? != checksum(body) {
+        return None;
+    }
+    let (key, value) = body.rsplit_once('\t')?;
+    key.contains('\t')
+        .then(|| (key.to_string(), value.to_string()))
+}
+
+/// FNV-1a hash of a line body
+fn checksum(body: &str) -> u64 {
+    body.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "pumpfun-cache-{}-{}.tsv",
+            name,
+            Pubkey::new_unique()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_entries_survive_reopen() {
+        let path = temp_path("reopen");
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+
+        let cache = ImmutableCache::open(&path).unwrap();
+        cache.insert("creator", &mint, &creator).unwrap();
+        cache.insert("token_age", &mint, &42u64).unwrap();
+        drop(cache);
+
+        let cache = ImmutableCache::open(&path).unwrap();
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get::<Pubkey>("creator", &mint), Some(creator));
+        assert_eq!(cache.get::<u64>("token_age", &mint), Some(42));
+        assert_eq!(cache.get::<Pubkey>("token_program", &mint), None);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_corrupted_lines_are_skipped() {
+        let path = temp_path("corrupted");
+        let kept = Pubkey::new_unique();
+        let damaged = Pubkey::new_unique();
+
+        let cache = ImmutableCache::open(&path).unwrap();
+        cache.insert("creator", &kept, &1u64).unwrap();
+        cache.insert("creator", &damaged, &2u64).unwrap();
+        drop(cache);
+
+        // Alter the second entry and append a line cut off mid-write
+        let contents = fs::read_to_string(&path).unwrap();
+        let altered = contents.replacen("\t2\t", "\t3\t", 1) + "creator\tabc";
+        fs::write(&path, altered).unwrap();
+
+        let cache = ImmutableCache::open(&path).unwrap();
+        assert_eq!(cache.get::<u64>("creator", &kept), Some(1));
+        assert_eq!(cache.get::<u64>("creator", &damaged), None);
+
+        // The refetched value is rewritten and the file no longer holds the bad lines
+        cache.insert("creator", &damaged, &2u64).unwrap();
+        drop(cache);
+        let cache = ImmutableCache::open(&path).unwrap();
+        assert_eq!(cache.get::<u64>("creator", &damaged), Some(2));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_oldest_entries_evicted() {
+        let path = temp_path("evicted");
+        let mints: Vec<Pubkey> = (0..5).map(|_| Pubkey::new_unique()).collect();
+
+        let cache = ImmutableCache::open(&path).unwrap().with_max_entries(3);
+        for (index, mint) in mints.iter().enumerate() {
+            cache.insert("token_age", mint, &index).unwrap();
+        }
+        assert_eq!(cache.len(), 3);
+        drop(cache);
+
+        let cache = ImmutableCache::open(&path).unwrap();
+        assert_eq!(cache.get::<usize>("token_age", &mints[1]), None);
+        assert_eq!(cache.get::<usize>("token_age", &mints[2]), Some(2));
+        assert_eq!(cache.get::<usize>("token_age", &mints[4]), Some(4));
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 3);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
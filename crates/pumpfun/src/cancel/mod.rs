@@ -0,0 +1,134 @@
+//! Cooperative cancellation of long-running subscriptions
+//!
+//! A [`CancellationToken`] is shared between a subscription and the code that may want to stop
+//! it. Cancelling wakes every task waiting on the token; subscriptions watching it unsubscribe,
+//! close their websocket connection and return before resolving.
+//!
+//! - `CancellationToken`: A shareable cancellation flag.
+//! - `Cancelled`: Future resolving once a token is cancelled.
+
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+};
+
+/// State shared by every clone of a token
+#[derive(Debug, Default)]
+struct TokenState {
+    /// Whether the token has been cancelled
+    cancelled: AtomicBool,
+    /// Tasks waiting for cancellation
+    wakers: Mutex<Vec<Waker>>,
+}
+
+/// A shareable cancellation flag
+///
+/// Clones share the same flag, so any clone can cancel every subscription watching the token.
+/// Cancellation cannot be undone.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    /// State shared with the other clones
+    state: Arc<TokenState>,
+}
+
+impl CancellationToken {
+    /// Creates a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the token, waking every task waiting on it
+    pub fn cancel(&self) {
+        self.state.cancelled.store(true, Ordering::SeqCst);
+        let wakers = std::mem::take(
+            &mut *self
+                .state
+                .wakers
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner()),
+        );
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Returns whether the token has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.state.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the token to be cancelled
+    ///
+    /// # Returns
+    ///
+    /// Returns a future resolving once the token is cancelled, immediately if it already is
+    pub fn cancelled(&self) -> Cancelled<'_> {
+        Cancelled { token: self }
+    }
+}
+
+/// Future resolving once a token is cancelled
+#[derive(Debug)]
+pub struct Cancelled<'a> {
+    /// Token being waited on
+    token: &'a CancellationToken,
+}
+
+impl Future for Cancelled<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+
+        let mut wakers = self
+            .token
+            .state
+            .wakers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Checked again under the lock, so a concurrent cancel cannot miss this waker
+        if self.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        if !wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+            wakers.push(cx.waker().clone());
+        }
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    #[test]
+    fn test_cancel_wakes_waiters() {
+        let token = CancellationToken::new();
+        assert!(!token.is_cancelled());
+
+        let waiters: Vec<_> = (0..3)
+            .map(|_| {
+                let token = token.clone();
+                thread::spawn(move || futures::executor::block_on(token.cancelled()))
+            })
+            .collect();
+
+        thread::sleep(Duration::from_millis(20));
+        token.cancel();
+        for waiter in waiters {
+            waiter.join().unwrap();
+        }
+        assert!(token.is_cancelled());
+
+        // Waiting on a cancelled token resolves immediately
+        futures::executor::block_on(token.cancelled());
+    }
+}
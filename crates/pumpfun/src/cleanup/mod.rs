@@ -0,0 +1,265 @@
+//! Garbage collection of token accounts left behind by completed curves
+//!
+//! Once a curve completes and the payer has sold out, the payer's associated token account for
+//! the mint is empty but still holds rent. A client created with
+//! [`crate::PumpFun::with_ata_cleanup`] queues that account whenever
+//! [`crate::PumpFun::wait_for_graduation`] sees the curve complete, and
+//! [`crate::PumpFun::process_ata_cleanup`] closes queued accounts in batches with a low priority
+//! fee while recent priority fees are quiet.
+//!
+//! Every account is checked again right before closing, and accounts holding any tokens are
+//! never closed. The queue and the outcome of every close live in the [`TradeJournal`], so the
+//! cleanup can be audited after the fact.
+//!
+//! - `AtaCleanupConfig`: Batching and fee settings of the cleanup.
+//! - `CleanupState`: Where a queued close is in its lifecycle.
+//! - `CleanupEntry`: A queued close, as recorded in the journal.
+
+use crate::{constants, journal::TradeJournal};
+use anchor_spl::token::spl_token::{
+    instruction::close_account,
+    state::{Account as TokenAccount, AccountState},
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    account::Account, instruction::Instruction, program_pack::Pack, pubkey::Pubkey,
+    signature::Signature,
+};
+use std::sync::Arc;
+
+/// Compute units budgeted for each close account instruction
+pub const CLOSE_ACCOUNT_COMPUTE_UNITS: u32 = 5_000;
+
+/// Batching and fee settings of the token account cleanup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtaCleanupConfig {
+    /// Maximum number of accounts closed per transaction
+    pub max_batch: usize,
+    /// Compute unit price of cleanup transactions in micro-lamports
+    pub compute_unit_price: u64,
+    /// Highest median recent priority fee, in micro-lamports per compute unit, at which the
+    /// network is considered quiet enough to clean up
+    pub quiet_fee_threshold: u64,
+}
+
+impl Default for AtaCleanupConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 8,
+            compute_unit_price: 1_000,
+            quiet_fee_threshold: 10_000,
+        }
+    }
+}
+
+/// Journal and settings of an enabled cleanup
+#[derive(Debug, Clone)]
+pub(crate) struct AtaCleanup {
+    /// Journal holding the queue and its results
+    pub(crate) journal: Arc<TradeJournal>,
+    /// Batching and fee settings
+    pub(crate) config: AtaCleanupConfig,
+}
+
+/// Where a queued token account close is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum CleanupState {
+    /// Waiting for a quiet period to be closed
+    Queued,
+    /// Closed, with its rent returned to the payer
+    Closed,
+    /// Left open because it still holds tokens
+    SkippedNonzeroBalance,
+    /// Already closed or never created
+    SkippedMissing,
+    /// Left open because it is not an initialized token account the payer can close
+    SkippedInvalid,
+    /// The close transaction failed
+    Failed,
+}
+
+/// A queued token account close, as recorded in the journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CleanupEntry {
+    /// Mint of the completed curve
+    pub mint: Pubkey,
+    /// Payer's associated token account for the mint
+    pub account: Pubkey,
+    /// Token program owning the account
+    pub token_program: Pubkey,
+    /// Current state of the close
+    pub state: CleanupState,
+    /// Unix timestamp at which the close was queued
+    pub queued_at: i64,
+    /// Unix timestamp at which the close was processed, if it was
+    pub processed_at: Option<i64>,
+    /// Token balance found when a close was skipped for a nonzero balance
+    pub balance: Option<u64>,
+    /// Signature of the close transaction, if one was sent
+    pub signature: Option<Signature>,
+}
+
+/// Checks that a token account can be closed without losing tokens
+///
+/// # Arguments
+///
+/// * `account` - The fetched account, or None if it does not exist
+/// * `owner` - Wallet that must own the token account
+/// * `token_program` - Token program that must own the account
+///
+/// # Returns
+///
+/// Returns Ok if the account is empty and closable, or the state to record for skipping it
+pub fn check_closable(
+    account: Option<&Account>,
+    owner: &Pubkey,
+    token_program: &Pubkey,
+) -> Result<(), CleanupState> {
+    let account = account.ok_or(CleanupState::SkippedMissing)?;
+    if account.owner != *token_program {
+        return Err(CleanupState::SkippedInvalid);
+    }
+    let token_account = account
+        .data
+        .get(..TokenAccount::LEN)
+        .and_then(|data| TokenAccount::unpack_from_slice(data).ok())
+        .ok_or(CleanupState::SkippedInvalid)?;
+    if token_account.amount != 0 {
+        return Err(CleanupState::SkippedNonzeroBalance);
+    }
+    if token_account.owner != *owner || token_account.state != AccountState::Initialized {
+        return Err(CleanupState::SkippedInvalid);
+    }
+    Ok(())
+}
+
+/// Returns whether recent priority fees are low enough to clean up
+///
+/// # Arguments
+///
+/// * `recent_fees` - Recent per-slot priority fees in micro-lamports per compute unit
+/// * `threshold` - Highest median fee considered quiet
+///
+/// # Returns
+///
+/// Returns true if the median fee is at most the threshold, or false if no fees are known
+pub fn is_quiet(recent_fees: &[u64], threshold: u64) -> bool {
+    if recent_fees.is_empty() {
+        return false;
+    }
+    let mut fees = recent_fees.to_vec();
+    fees.sort_unstable();
+    fees[fees.len() / 2] <= threshold
+}
+
+/// Builds the instruction closing an empty token account, returning its rent to the owner
+///
+/// # Arguments
+///
+/// * `account` - Token account to close
+/// * `owner` - Wallet owning the account and receiving its rent
+/// * `token_program` - Token or Token-2022 program owning the account
+///
+/// # Returns
+///
+/// Returns the close account instruction
+pub fn close_instruction(account: &Pubkey, owner: &Pubkey, token_program: &Pubkey) -> Instruction {
+    // Token program ID is always valid, so this cannot fail
+    let mut instruction = close_account(
+        &constants::accounts::TOKEN_PROGRAM,
+        account,
+        owner,
+        owner,
+        &[],
+    )
+    .unwrap();
+    // Token-2022 shares the instruction's layout
+    instruction.program_id = *token_program;
+    instruction
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_account(owner: &Pubkey, amount: u64, state: AccountState) -> Account {
+        let mut data = vec![0; TokenAccount::LEN];
+        TokenAccount {
+            mint: Pubkey::new_unique(),
+            owner: *owner,
+            amount,
+            state,
+            ..TokenAccount::default()
+        }
+        .pack_into_slice(&mut data);
+        Account {
+            lamports: 2_039_280,
+            data,
+            owner: constants::accounts::TOKEN_PROGRAM,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[test]
+    fn test_check_closable() {
+        let owner = Pubkey::new_unique();
+        let token_program = constants::accounts::TOKEN_PROGRAM;
+
+        let empty = get_account(&owner, 0, AccountState::Initialized);
+        assert_eq!(check_closable(Some(&empty), &owner, &token_program), Ok(()));
+
+        // Accounts holding even a single base unit are never closed
+        let dust = get_account(&owner, 1, AccountState::Initialized);
+        assert_eq!(
+            check_closable(Some(&dust), &owner, &token_program),
+            Err(CleanupState::SkippedNonzeroBalance)
+        );
+
+        assert_eq!(
+            check_closable(None, &owner, &token_program),
+            Err(CleanupState::SkippedMissing)
+        );
+        assert_eq!(
+            check_closable(Some(&empty), &Pubkey::new_unique(), &token_program),
+            Err(CleanupState::SkippedInvalid)
+        );
+        let frozen = get_account(&owner, 0, AccountState::Frozen);
+        assert_eq!(
+            check_closable(Some(&frozen), &owner, &token_program),
+            Err(CleanupState::SkippedInvalid)
+        );
+        assert_eq!(
+            check_closable(
+                Some(&empty),
+                &owner,
+                &constants::accounts::TOKEN_2022_PROGRAM
+            ),
+            Err(CleanupState::SkippedInvalid)
+        );
+    }
+
+    #[test]
+    fn test_is_quiet() {
+        assert!(is_quiet(&[0, 0, 5_000, 1_000_000], 10_000));
+        assert!(!is_quiet(&[0, 50_000, 60_000], 10_000));
+        assert!(!is_quiet(&[], 10_000));
+    }
+
+    #[test]
+    fn test_close_instruction_program() {
+        let owner = Pubkey::new_unique();
+        let account = Pubkey::new_unique();
+        let token_2022 =
+            close_instruction(&account, &owner, &constants::accounts::TOKEN_2022_PROGRAM);
+        let token = close_instruction(&account, &owner, &constants::accounts::TOKEN_PROGRAM);
+
+        assert_eq!(
+            token_2022.program_id,
+            constants::accounts::TOKEN_2022_PROGRAM
+        );
+        assert_eq!(token_2022.data, token.data);
+        assert_eq!(token_2022.accounts, token.accounts);
+    }
+}
@@ -21,8 +21,20 @@ pub mod seeds {
     /// Seed for bonding curve PDAs
     pub const BONDING_CURVE_SEED: &[u8] = b"bonding-curve";
 
+    /// Seed for creator vault PDAs
+    pub const CREATOR_VAULT_SEED: &[u8] = b"creator-vault";
+
     /// Seed for metadata PDAs
     pub const METADATA_SEED: &[u8] = b"metadata";
+
+    /// Seed for the global volume accumulator PDA
+    pub const GLOBAL_VOLUME_ACCUMULATOR_SEED: &[u8] = b"global_volume_accumulator";
+
+    /// Seed for user volume accumulator PDAs
+    pub const USER_VOLUME_ACCUMULATOR_SEED: &[u8] = b"user_volume_accumulator";
+
+    /// Seed for the fee config PDA, derived under the fee program
+    pub const FEE_CONFIG_SEED: &[u8] = b"fee_config";
 }
 
 /// Constants related to program accounts and authorities
@@ -44,10 +56,19 @@ pub mod accounts {
     /// Token Program ID
     pub const TOKEN_PROGRAM: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
 
+    /// Token-2022 Program ID
+    pub const TOKEN_2022_PROGRAM: Pubkey = pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
     /// Associated Token Program ID
     pub const ASSOCIATED_TOKEN_PROGRAM: Pubkey =
         pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
 
     /// Rent Sysvar ID
     pub const RENT: Pubkey = pubkey!("SysvarRent111111111111111111111111111111111");
+
+    /// Public key for the Pump.fun fee program
+    pub const FEE_PROGRAM: Pubkey = pubkey!("pfeeUxB6jkeY1Hxd7CsFCAjcbHA9rWtchMGdZ6VojVZ");
+
+    /// SPL Memo Program ID
+    pub const MEMO_PROGRAM: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
 }
@@ -0,0 +1,49 @@
+//! One-call setup of a funded payer on devnet
+//!
+//! Trying the SDK on devnet first takes a funded payer, and the devnet faucet often rate limits
+//! or drops requests. [`crate::PumpFun::devnet_setup`] gets a fresh keypair ready to trade:
+//!
+//! - The airdrop is requested and confirmed, retrying up to [`AIRDROP_ATTEMPTS`] times.
+//! - The payer is checked to be an initialized system account, so it can pay for transactions.
+//! - The Pump.fun and token metadata programs are checked to be deployed on the cluster.
+//!
+//! The setup refuses to run against mainnet, recognised either by the client's cluster or by
+//! the genesis hash the node reports, so a custom URL pointing at mainnet is caught too.
+//!
+//! - `DevnetSetupReport`: What the setup did and the payer's resulting balance.
+
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
+use std::time::Duration;
+
+/// Number of times an airdrop is requested before giving up
+pub const AIRDROP_ATTEMPTS: u32 = 5;
+
+/// Delay before retrying a failed airdrop, multiplied by the number of attempts so far
+pub const AIRDROP_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Genesis hash of mainnet-beta, on which the setup refuses to run
+pub const MAINNET_GENESIS_HASH: &str = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d";
+
+/// Hint returned when the Pump.fun program is missing from the cluster
+pub const PUMPFUN_PROGRAM_HINT: &str =
+    "use devnet, or clone it into the test validator with `solana-test-validator \
+--clone-upgradeable-program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P --url mainnet-beta`";
+
+/// What a devnet setup did
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DevnetSetupReport {
+    /// Public key of the payer that was set up
+    pub payer: Pubkey,
+    /// SOL requested from the faucet in lamports, 0 if no airdrop was requested
+    pub airdrop_lamports: u64,
+    /// Signature of the confirmed airdrop, None if no airdrop was requested
+    pub airdrop_signature: Option<Signature>,
+    /// Number of airdrop requests made, including the successful one
+    pub airdrop_attempts: u32,
+    /// Whether the payer's account did not exist before the setup
+    pub account_created: bool,
+    /// Balance of the payer before the setup in lamports
+    pub balance_before: u64,
+    /// Balance of the payer after the setup in lamports
+    pub balance_after: u64,
+}
@@ -17,10 +17,263 @@
 //! - `InsufficientFunds`: Insufficient funds for a transaction.
 //! - `SimulationError`: Transaction simulation failed.
 //! - `RateLimitExceeded`: Rate limit exceeded.
+//! - `PubsubClientError`: An error occurred while interacting with the Solana websocket client.
+//! - `SubscriptionClosed`: A websocket subscription ended unexpectedly.
+//! - `Timeout`: The operation did not complete in time.
+//! - `TransactionTooLarge`: The transaction exceeds the maximum transaction size.
+//! - `FillBelowExpected`: A confirmed trade filled below the expected minimum.
+//! - `MintAlreadyExists`: The mint for a new token is already in use.
+//! - `JournalError`: The trade journal could not be read or written.
+//! - `AccountDecodeError`: A program account could not be decoded.
+//! - `MissingDependencyProgram`: A program the Pump.fun program depends on is not deployed.
+//! - `Cancelled`: The operation was stopped by a cancellation token.
+//! - `StalePlan`: A trade plan was built from curve state the chain has since moved past.
+//! - `ExportError`: An export could not be written.
+//! - `ProgramError`: The Pump.fun program rejected an instruction with one of its declared errors.
+//! - `MintLocked`: Another order on this client is trading the mint.
+//! - `TradingPaused`: Transactions are paused until a detected program upgrade is acknowledged.
+//! - `PriorityFeeBudgetExceeded`: The priority fees spent in the last hour exceed the budget.
+//! - `GlobalParamsMismatch`: The global account holds other parameters than the ones required.
+//! - `MemoTooLong`: A trade memo does not fit in the space left in its transaction.
+//! - `Shedded`: An optional feature was skipped to protect the trade path under RPC pressure.
+//! - `ImageTooLarge`: A token image is over the size cap of the image policy.
+//! - `InvalidGlobalParams`: Global parameters would leave new bonding curves unusable.
+//! - `BondingCurveComplete`: The bonding curve has completed, so it can no longer be quoted.
+//!
+//! The constraints global parameters violate are listed as `GlobalParamsViolation`s.
+//!
+//! Custom error codes returned by the Pump.fun program are decoded into `PumpFunProgramError`,
+//! which mirrors the errors declared in the program's IDL and must be kept in step with it.
+//!
+//! The enum is `#[non_exhaustive]`: matches outside this crate need a wildcard arm, so adding a
+//! variant is not a breaking change.
+
+use anchor_client::{
+    solana_client,
+    solana_sdk::{
+        instruction::InstructionError, message::VersionedMessage, pubkey::Pubkey,
+        transaction::TransactionError,
+    },
+};
+
+/// A constraint global parameters violate, and why it matters
+///
+/// Checked by `testing::GlobalParams::violations` before parameters are sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum GlobalParamsViolation {
+    /// A parameter was not set on the builder
+    Missing {
+        /// Name of the parameter
+        field: &'static str,
+    },
+    /// The fee recipient is the default address, which nobody can withdraw the fees from
+    DefaultFeeRecipient,
+    /// New curves start without virtual token reserves, so no price can be quoted
+    ZeroVirtualTokenReserves,
+    /// New curves start without virtual SOL reserves, so every token is free
+    ZeroVirtualSolReserves,
+    /// New curves have no tokens to sell
+    ZeroRealTokenReserves,
+    /// New tokens have no supply
+    ZeroTokenTotalSupply,
+    /// The fee takes more than the whole amount traded
+    FeeTooHigh {
+        /// Fee in basis points
+        fee_basis_points: u64,
+    },
+    /// New curves are to sell more tokens than the token's supply
+    SupplyBelowRealReserves {
+        /// Total supply of each new token
+        token_total_supply: u64,
+        /// Tokens a new curve can sell
+        initial_real_token_reserves: u64,
+    },
+    /// Selling every real token would exhaust the virtual token reserves, making the last
+    /// tokens infinitely expensive
+    RealReservesNotBelowVirtual {
+        /// Tokens a new curve can sell
+        initial_real_token_reserves: u64,
+        /// Virtual token reserves a new curve starts with
+        initial_virtual_token_reserves: u64,
+    },
+}
+
+impl std::fmt::Display for GlobalParamsViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { field } => write!(f, "{} is not set", field),
+            Self::DefaultFeeRecipient => write!(
+                f,
+                "fee_recipient is the default address, so the fees could never be withdrawn"
+            ),
+            Self::ZeroVirtualTokenReserves => write!(
+                f,
+                "initial_virtual_token_reserves is zero, so no price can be quoted"
+            ),
+            Self::ZeroVirtualSolReserves => write!(
+                f,
+                "initial_virtual_sol_reserves is zero, so every token would be free"
+            ),
+            Self::ZeroRealTokenReserves => write!(
+                f,
+                "initial_real_token_reserves is zero, so curves have no tokens to sell"
+            ),
+            Self::ZeroTokenTotalSupply => write!(f, "token_total_supply is zero"),
+            Self::FeeTooHigh { fee_basis_points } => write!(
+                f,
+                "fee_basis_points is {}, so the fee would exceed the amount traded",
+                fee_basis_points
+            ),
+            Self::SupplyBelowRealReserves {
+                token_total_supply,
+                initial_real_token_reserves,
+            } => write!(
+                f,
+                "token_total_supply {} is below initial_real_token_reserves {}, so curves \
+                 would sell tokens that do not exist",
+                token_total_supply, initial_real_token_reserves
+            ),
+            Self::RealReservesNotBelowVirtual {
+                initial_real_token_reserves,
+                initial_virtual_token_reserves,
+            } => write!(
+                f,
+                "initial_real_token_reserves {} is not below initial_virtual_token_reserves {}, \
+                 so the last tokens would be infinitely expensive",
+                initial_real_token_reserves, initial_virtual_token_reserves
+            ),
+        }
+    }
+}
+
+/// Errors declared by the Pump.fun program, decoded from custom program error codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PumpFunProgramError {
+    /// The given account is not authorized to execute this instruction
+    NotAuthorized,
+    /// The program is already initialized
+    AlreadyInitialized,
+    /// Too much SOL required to buy the given amount of tokens
+    TooMuchSolRequired,
+    /// Too little SOL received to sell the given amount of tokens
+    TooLittleSolReceived,
+    /// The mint does not match the bonding curve
+    MintDoesNotMatchBondingCurve,
+    /// The bonding curve has completed and liquidity migrated
+    BondingCurveComplete,
+    /// The bonding curve has not completed
+    BondingCurveNotComplete,
+    /// The program is not initialized
+    NotInitialized,
+    /// Withdraw too frequent
+    WithdrawTooFrequent,
+}
+
+impl PumpFunProgramError {
+    /// Every declared error, in code order
+    pub const ALL: [PumpFunProgramError; 9] = [
+        Self::NotAuthorized,
+        Self::AlreadyInitialized,
+        Self::TooMuchSolRequired,
+        Self::TooLittleSolReceived,
+        Self::MintDoesNotMatchBondingCurve,
+        Self::BondingCurveComplete,
+        Self::BondingCurveNotComplete,
+        Self::NotInitialized,
+        Self::WithdrawTooFrequent,
+    ];
+
+    /// Custom error code of the first declared error
+    const FIRST_CODE: u32 = 6000;
+
+    /// Gets the custom error code the program returns for this error
+    pub fn code(&self) -> u32 {
+        Self::FIRST_CODE + *self as u32
+    }
+
+    /// Decodes a custom error code returned by the Pump.fun program
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - Custom program error code, such as 6005 (`0x1775`)
+    ///
+    /// # Returns
+    ///
+    /// Returns the declared error, or None if the code is not one of the program's errors
+    pub fn from_code(code: u32) -> Option<Self> {
+        let index = code.checked_sub(Self::FIRST_CODE)?;
+        Self::ALL.get(index as usize).copied()
+    }
+
+    /// Decodes the Pump.fun program error that failed a transaction
+    ///
+    /// Only custom errors from instructions invoking the Pump.fun program directly are decoded,
+    /// since other programs reuse the same code range for their own errors. Errors raised by the
+    /// program when it is invoked through another program are reported against the outer
+    /// instruction and are not decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - Error the transaction failed with
+    /// * `message` - Message of the failed transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the declared error, or None if the failure was not a Pump.fun program error
+    pub fn from_transaction_error(
+        err: &TransactionError,
+        message: &VersionedMessage,
+    ) -> Option<Self> {
+        let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+            return None;
+        };
+        let instruction = message.instructions().get(*index as usize)?;
+        let program_id = message
+            .static_account_keys()
+            .get(instruction.program_id_index as usize)?;
+        if *program_id != crate::constants::accounts::PUMPFUN {
+            return None;
+        }
+
+        Self::from_code(*code)
+    }
+
+    /// Gets the message the program declares for this error
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::NotAuthorized => {
+                "The given account is not authorized to execute this instruction."
+            }
+            Self::AlreadyInitialized => "The program is already initialized.",
+            Self::TooMuchSolRequired => {
+                "slippage: Too much SOL required to buy the given amount of tokens."
+            }
+            Self::TooLittleSolReceived => {
+                "slippage: Too little SOL received to sell the given amount of tokens."
+            }
+            Self::MintDoesNotMatchBondingCurve => "The mint does not match the bonding curve.",
+            Self::BondingCurveComplete => {
+                "The bonding curve has completed and liquidity migrated to raydium."
+            }
+            Self::BondingCurveNotComplete => "The bonding curve has not completed.",
+            Self::NotInitialized => "The program is not initialized.",
+            Self::WithdrawTooFrequent => "Withdraw too frequent",
+        }
+    }
+}
 
-use anchor_client::solana_client;
+impl std::fmt::Display for PumpFunProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} ({}): {}", self, self.code(), self.message())
+    }
+}
+
+impl std::error::Error for PumpFunProgramError {}
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ClientError {
     /// Bonding curve account was not found
     BondingCurveNotFound,
@@ -42,6 +295,117 @@ pub enum ClientError {
     SimulationError(String),
     /// Rate limit exceeded
     RateLimitExceeded,
+    /// Error from Solana websocket client
+    PubsubClientError(solana_client::nonblocking::pubsub_client::PubsubClientError),
+    /// Websocket subscription ended unexpectedly
+    SubscriptionClosed,
+    /// Operation did not complete in time
+    Timeout,
+    /// Transaction exceeds the maximum transaction size
+    TransactionTooLarge {
+        /// Serialized size of the transaction in bytes
+        size: usize,
+        /// Maximum allowed size in bytes
+        max: usize,
+    },
+    /// Confirmed trade filled below the expected minimum
+    FillBelowExpected {
+        /// Minimum amount expected in base units
+        expected: u64,
+        /// Amount actually filled in base units
+        actual: u64,
+    },
+    /// Mint account already exists or is already being created by this client
+    MintAlreadyExists(Pubkey),
+    /// Error reading or writing the trade journal
+    JournalError(std::io::Error),
+    /// Program account data could not be decoded
+    AccountDecodeError(Box<crate::accounts::DecodeFailure>),
+    /// Program the Pump.fun program depends on is not deployed on the cluster
+    MissingDependencyProgram {
+        /// Program ID of the missing program
+        program: Pubkey,
+        /// How to make the program available
+        hint: &'static str,
+    },
+    /// Operation was stopped by a cancellation token
+    Cancelled,
+    /// Trade plan was built from curve state older than the allowed slot budget
+    StalePlan {
+        /// Slot of the curve state the plan was built from
+        observed_slot: u64,
+        /// Slot of the latest cached curve state
+        current_slot: u64,
+    },
+    /// Error writing an export
+    ExportError(std::io::Error),
+    /// Pump.fun program rejected an instruction with one of its declared errors
+    ProgramError(PumpFunProgramError),
+    /// Mint is locked by another order in progress on this client
+    MintLocked(Pubkey),
+    /// Transactions are paused until a detected program upgrade is acknowledged
+    TradingPaused,
+    /// Priority fees spent in the last hour exceed the configured budget
+    PriorityFeeBudgetExceeded {
+        /// Priority fees spent in the last hour in lamports
+        spent: u64,
+        /// Maximum priority fees per hour in lamports
+        budget: u64,
+    },
+    /// Global account holds other parameters than the ones required
+    GlobalParamsMismatch {
+        /// Names of the parameters that differ
+        fields: Vec<&'static str>,
+    },
+    /// Trade memo does not fit in the space left in its transaction
+    MemoTooLong {
+        /// Length of the memo in bytes
+        len: usize,
+        /// Longest memo in bytes that fits in the transaction
+        max: usize,
+    },
+    /// Optional feature skipped to protect the trade path under RPC pressure
+    Shedded {
+        /// Feature that was skipped
+        feature: crate::shed::Feature,
+        /// Pressure level the feature is shed at
+        pressure: crate::shed::Pressure,
+    },
+    /// Token image over the size cap of the image policy, found before any upload
+    ImageTooLarge {
+        /// Size of the image, or of its smallest encoding if it was resized, in bytes
+        size: usize,
+        /// Size cap of the policy in bytes
+        max: usize,
+    },
+    /// Global parameters that would leave new bonding curves unusable, found before sending
+    InvalidGlobalParams {
+        /// Every constraint the parameters violate
+        violations: Vec<GlobalParamsViolation>,
+    },
+    /// Bonding curve has completed and no longer trades, found before quoting it
+    BondingCurveComplete,
+}
+
+impl ClientError {
+    /// Converts the error of a failed transaction, decoding Pump.fun program errors
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - Error returned for the transaction
+    /// * `message` - Message of the transaction
+    pub(crate) fn from_transaction_failure(
+        err: solana_client::client_error::ClientError,
+        message: &VersionedMessage,
+    ) -> Self {
+        match err
+            .get_transaction_error()
+            .and_then(|tx_err| PumpFunProgramError::from_transaction_error(&tx_err, message))
+        {
+            Some(program_error) => Self::ProgramError(program_error),
+            None => Self::SolanaClientError(err),
+        }
+    }
 }
 
 impl std::fmt::Display for ClientError {
@@ -57,6 +421,69 @@ impl std::fmt::Display for ClientError {
             Self::InsufficientFunds => write!(f, "Insufficient funds for transaction"),
             Self::SimulationError(msg) => write!(f, "Transaction simulation failed: {}", msg),
             Self::RateLimitExceeded => write!(f, "Rate limit exceeded"),
+            Self::PubsubClientError(err) => write!(f, "Solana websocket client error: {}", err),
+            Self::SubscriptionClosed => write!(f, "Subscription closed unexpectedly"),
+            Self::Timeout => write!(f, "Operation timed out"),
+            Self::TransactionTooLarge { size, max } => {
+                write!(f, "Transaction too large: {} bytes (max {})", size, max)
+            }
+            Self::FillBelowExpected { expected, actual } => {
+                write!(
+                    f,
+                    "Fill below expected: expected {}, got {}",
+                    expected, actual
+                )
+            }
+            Self::MintAlreadyExists(mint) => write!(f, "Mint already exists: {}", mint),
+            Self::JournalError(err) => write!(f, "Trade journal error: {}", err),
+            Self::AccountDecodeError(failure) => write!(f, "Account decode error: {}", failure),
+            Self::MissingDependencyProgram { program, hint } => {
+                write!(f, "Missing dependency program {}: {}", program, hint)
+            }
+            Self::Cancelled => write!(f, "Operation cancelled"),
+            Self::StalePlan {
+                observed_slot,
+                current_slot,
+            } => write!(
+                f,
+                "Stale trade plan: built at slot {}, curve updated at slot {}",
+                observed_slot, current_slot
+            ),
+            Self::ExportError(err) => write!(f, "Export error: {}", err),
+            Self::ProgramError(err) => write!(f, "Pump.fun program error: {}", err),
+            Self::MintLocked(mint) => write!(f, "Mint is locked by another order: {}", mint),
+            Self::TradingPaused => write!(
+                f,
+                "Trading is paused until the program upgrade is acknowledged"
+            ),
+            Self::PriorityFeeBudgetExceeded { spent, budget } => write!(
+                f,
+                "Priority fee budget exceeded: spent {} lamports in the last hour (max {})",
+                spent, budget
+            ),
+            Self::GlobalParamsMismatch { fields } => {
+                write!(f, "Global params mismatch: {}", fields.join(", "))
+            }
+            Self::MemoTooLong { len, max } => {
+                write!(f, "Memo too long: {} bytes (max {})", len, max)
+            }
+            Self::Shedded { feature, pressure } => {
+                write!(f, "Shedded {:?} under {:?} RPC pressure", feature, pressure)
+            }
+            Self::ImageTooLarge { size, max } => write!(
+                f,
+                "Image too large: {} bytes, {} over the limit of {}",
+                size,
+                size.saturating_sub(*max),
+                max
+            ),
+            Self::InvalidGlobalParams { violations } => {
+                let violations: Vec<String> = violations.iter().map(ToString::to_string).collect();
+                write!(f, "Invalid global params: {}", violations.join("; "))
+            }
+            Self::BondingCurveComplete => {
+                write!(f, "Bonding curve is complete and no longer trades")
+            }
         }
     }
 }
@@ -68,7 +495,98 @@ impl std::error::Error for ClientError {
             Self::SolanaClientError(err) => Some(err),
             Self::UploadMetadataError(err) => Some(err.as_ref()),
             Self::AnchorClientError(err) => Some(err),
+            Self::PubsubClientError(err) => Some(err),
+            Self::JournalError(err) => Some(err),
+            Self::ExportError(err) => Some(err),
+            Self::ProgramError(err) => Some(err),
             _ => None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::{
+        hash::Hash,
+        instruction::Instruction,
+        message::{v0, VersionedMessage},
+    };
+
+    const IDL: &str = include_str!("../../../pumpfun-cpi/idl.classic.json");
+
+    #[test]
+    fn test_program_errors_match_idl() {
+        let idl: serde_json::Value = serde_json::from_str(IDL).unwrap();
+        let declared = idl["errors"].as_array().unwrap();
+        assert_eq!(declared.len(), PumpFunProgramError::ALL.len());
+
+        for error in declared {
+            let code = error["code"].as_u64().unwrap() as u32;
+            let decoded = PumpFunProgramError::from_code(code).unwrap();
+            assert_eq!(decoded.code(), code);
+            assert_eq!(format!("{:?}", decoded), error["name"].as_str().unwrap());
+            assert_eq!(decoded.message(), error["msg"].as_str().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_program_error_codes() {
+        assert_eq!(
+            PumpFunProgramError::from_code(6002),
+            Some(PumpFunProgramError::TooMuchSolRequired)
+        );
+        assert_eq!(
+            PumpFunProgramError::from_code(0x1775),
+            Some(PumpFunProgramError::BondingCurveComplete)
+        );
+        assert_eq!(PumpFunProgramError::from_code(5999), None);
+        assert_eq!(PumpFunProgramError::from_code(6009), None);
+        assert_eq!(PumpFunProgramError::from_code(0), None);
+    }
+
+    #[test]
+    fn test_program_error_from_transaction_error() {
+        let payer = Pubkey::new_unique();
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &payer,
+                &[
+                    Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]),
+                    Instruction::new_with_bytes(crate::constants::accounts::PUMPFUN, &[], vec![]),
+                ],
+                &[],
+                Hash::default(),
+            )
+            .unwrap(),
+        );
+        let failure =
+            |index, code| TransactionError::InstructionError(index, InstructionError::Custom(code));
+
+        assert_eq!(
+            PumpFunProgramError::from_transaction_error(&failure(1, 6005), &message),
+            Some(PumpFunProgramError::BondingCurveComplete)
+        );
+        // Another program's error in the same code range is left alone
+        assert_eq!(
+            PumpFunProgramError::from_transaction_error(&failure(0, 6005), &message),
+            None
+        );
+        assert_eq!(
+            PumpFunProgramError::from_transaction_error(
+                &TransactionError::AccountNotFound,
+                &message
+            ),
+            None
+        );
+
+        let err = ClientError::from_transaction_failure(failure(1, 6002).into(), &message);
+        assert!(matches!(
+            err,
+            ClientError::ProgramError(PumpFunProgramError::TooMuchSolRequired)
+        ));
+        assert!(err.to_string().contains("TooMuchSolRequired"));
+        let err = ClientError::from_transaction_failure(failure(0, 6002).into(), &message);
+        assert!(matches!(err, ClientError::SolanaClientError(_)));
+    }
+}
@@ -0,0 +1,475 @@
+//! Combinators that narrow a stream of trade events
+//!
+//! Each filter takes any [`Stream`] of trades and returns another, so they stack in any order:
+//!
+//! ```rust
+//! use futures::{stream, StreamExt};
+//! use pumpfun::{
+//!     events::{filters, TradeEvent},
+//!     journal::TradeSide,
+//!     prelude::Pubkey,
+//! };
+//!
+//! # futures::executor::block_on(async {
+//! let mint = Pubkey::new_unique();
+//! let trades = stream::iter(Vec::<TradeEvent>::new());
+//! let criteria = filters::WhaleCriteria::new().with_min_sol(10_000_000_000);
+//! let whales = filters::whale_filter(trades, criteria);
+//! let whale_buys = filters::side_filter(filters::mint_filter(whales, mint), TradeSide::Buy);
+//! assert!(whale_buys.collect::<Vec<_>>().await.is_empty());
+//! # });
+//! ```
+//!
+//! - `WhaleCriteria`: Thresholds at which a trade counts as significant.
+//! - `WhaleReason`: Which threshold a significant trade crossed.
+//! - `WhaleTrade`: A significant trade with the reason it was flagged.
+//! - `whale_filter`: Keeps the significant trades of a stream.
+//! - `mint_filter`: Keeps the trades of a single mint.
+//! - `side_filter`: Keeps the buys or the sells.
+//!
+//! The mint and side filters accept streams of [`TradeEvent`] and of [`WhaleTrade`] alike, so
+//! they can be applied before or after the whale filter.
+
+use crate::{events::TradeEvent, flow::NetFlowTracker, journal::TradeSide};
+use futures::{future, Stream, StreamExt};
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Window of the volume a trade is compared against by [`WhaleCriteria::with_min_volume_pct`]
+pub const WHALE_VOLUME_WINDOW: Duration = Duration::from_secs(300);
+
+/// Thresholds at which a trade counts as significant
+///
+/// A trade is significant when it crosses any of the thresholds set. Criteria without any
+/// threshold match no trade.
+#[derive(Debug, Clone, Default)]
+pub struct WhaleCriteria {
+    /// Minimum SOL amount of the trade, in lamports
+    min_sol: Option<u64>,
+    /// Minimum percentage of the virtual SOL reserves the trade was made against
+    min_reserve_pct: Option<f64>,
+    /// Minimum percentage of the volume over the volume window, and the tracker measuring it
+    min_volume_pct: Option<(f64, Arc<Mutex<NetFlowTracker>>)>,
+}
+
+impl WhaleCriteria {
+    /// Creates criteria without any threshold
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Flags trades of at least an absolute size
+    ///
+    /// # Arguments
+    ///
+    /// * `lamports` - Minimum SOL amount of the trade, excluding fees
+    ///
+    /// # Returns
+    ///
+    /// Returns the criteria also flagging trades of at least `lamports`
+    pub fn with_min_sol(mut self, lamports: u64) -> Self {
+        self.min_sol = Some(lamports);
+        self
+    }
+
+    /// Flags trades that are large relative to the curve they were made on
+    ///
+    /// The trade is compared against the virtual SOL reserves before it, derived from the
+    /// reserves the event reports after it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pct` - Minimum percentage of the virtual SOL reserves
+    ///
+    /// # Returns
+    ///
+    /// Returns the criteria also flagging trades of at least `pct` percent of the reserves
+    pub fn with_min_reserve_pct(mut self, pct: f64) -> Self {
+        self.min_reserve_pct = Some(pct);
+        self
+    }
+
+    /// Flags trades that are large relative to the recent volume
+    ///
+    /// Every trade reaching the filter is recorded in the tracker, so it should not be fed from
+    /// the same stream elsewhere. The trade is compared against the volume over the
+    /// [`WHALE_VOLUME_WINDOW`] ending at it, itself included, so a trade alone in its window is
+    /// all of the volume. Trades of other mints than the one the tracker is restricted to never
+    /// cross this threshold.
+    ///
+    /// # Arguments
+    ///
+    /// * `pct` - Minimum percentage of the volume
+    /// * `tracker` - Tracker retaining at least the volume window, shared with other readers
+    ///
+    /// # Returns
+    ///
+    /// Returns the criteria also flagging trades of at least `pct` percent of the volume
+    pub fn with_min_volume_pct(mut self, pct: f64, tracker: Arc<Mutex<NetFlowTracker>>) -> Self {
+        self.min_volume_pct = Some((pct, tracker));
+        self
+    }
+
+    /// Checks a trade against the criteria
+    ///
+    /// The thresholds are checked in the order absolute size, reserves, volume, and the first
+    /// one crossed is reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Trade to check, recorded in the volume tracker if one is attached
+    ///
+    /// # Returns
+    ///
+    /// Returns the threshold the trade crossed, or None if it is not significant
+    pub fn classify(&self, event: &TradeEvent) -> Option<WhaleReason> {
+        // Record before checking anything, so the volume also counts trades flagged otherwise
+        let volume_pct = self.min_volume_pct.as_ref().and_then(|(_, tracker)| {
+            let mut tracker = tracker
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            tracker.record(event);
+            if tracker.mint().is_some_and(|mint| mint != event.mint) {
+                return None;
+            }
+            share_pct(
+                event.sol_amount,
+                tracker.volume_at(WHALE_VOLUME_WINDOW, event.timestamp),
+            )
+        });
+
+        if self.min_sol.is_some_and(|min| event.sol_amount >= min) {
+            return Some(WhaleReason::SolAmount {
+                lamports: event.sol_amount,
+            });
+        }
+
+        let reserves_before = if event.is_buy {
+            event.virtual_sol_reserves.saturating_sub(event.sol_amount)
+        } else {
+            event.virtual_sol_reserves.saturating_add(event.sol_amount)
+        };
+        if let Some(pct) = self
+            .min_reserve_pct
+            .and_then(|min| share_pct(event.sol_amount, reserves_before).filter(|pct| *pct >= min))
+        {
+            return Some(WhaleReason::ReserveShare { pct });
+        }
+
+        let min = self.min_volume_pct.as_ref().map(|(min, _)| *min)?;
+        volume_pct
+            .filter(|pct| *pct >= min)
+            .map(|pct| WhaleReason::VolumeShare { pct })
+    }
+}
+
+/// Threshold of [`WhaleCriteria`] a significant trade crossed
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum WhaleReason {
+    /// The trade's SOL amount reached the minimum size
+    SolAmount {
+        /// SOL amount of the trade, in lamports
+        lamports: u64,
+    },
+    /// The trade reached the minimum share of the virtual SOL reserves
+    ReserveShare {
+        /// Percentage of the virtual SOL reserves before the trade
+        pct: f64,
+    },
+    /// The trade reached the minimum share of the recent volume
+    VolumeShare {
+        /// Percentage of the volume over the volume window, the trade included
+        pct: f64,
+    },
+}
+
+/// A significant trade and the reason it was flagged
+#[derive(Debug, Clone, PartialEq)]
+pub struct WhaleTrade {
+    /// Trade as emitted by the program
+    pub event: TradeEvent,
+    /// Threshold the trade crossed
+    pub reason: WhaleReason,
+}
+
+impl AsRef<TradeEvent> for WhaleTrade {
+    fn as_ref(&self) -> &TradeEvent {
+        &self.event
+    }
+}
+
+impl AsRef<TradeEvent> for TradeEvent {
+    fn as_ref(&self) -> &TradeEvent {
+        self
+    }
+}
+
+/// Keeps the significant trades of a stream
+///
+/// # Arguments
+///
+/// * `stream` - Stream of trade events, such as one decoded from a logs subscription
+/// * `criteria` - Thresholds at which a trade counts as significant
+///
+/// # Returns
+///
+/// Returns a stream of the trades crossing a threshold, annotated with the one they crossed
+pub fn whale_filter<S>(stream: S, criteria: WhaleCriteria) -> impl Stream<Item = WhaleTrade>
+where
+    S: Stream<Item = TradeEvent>,
+{
+    stream.filter_map(move |event| {
+        future::ready(
+            criteria
+                .classify(&event)
+                .map(|reason| WhaleTrade { event, reason }),
+        )
+    })
+}
+
+/// Keeps the trades of a single mint
+///
+/// # Arguments
+///
+/// * `stream` - Stream of trade events or of whale trades
+/// * `mint` - Public key of the token mint to keep
+///
+/// # Returns
+///
+/// Returns a stream of the items whose trade is on `mint`
+pub fn mint_filter<S, T>(stream: S, mint: Pubkey) -> impl Stream<Item = T>
+where
+    S: Stream<Item = T>,
+    T: AsRef<TradeEvent>,
+{
+    stream.filter(move |item| future::ready(item.as_ref().mint == mint))
+}
+
+/// Keeps the buys or the sells of a stream
+///
+/// # Arguments
+///
+/// * `stream` - Stream of trade events or of whale trades
+/// * `side` - Direction of the trades to keep
+///
+/// # Returns
+///
+/// Returns a stream of the items whose trade is on `side`
+pub fn side_filter<S, T>(stream: S, side: TradeSide) -> impl Stream<Item = T>
+where
+    S: Stream<Item = T>,
+    T: AsRef<TradeEvent>,
+{
+    stream.filter(move |item| future::ready(item.as_ref().is_buy == (side == TradeSide::Buy)))
+}
+
+/// Gets an amount as a percentage of a total, or None if the total is zero
+fn share_pct(amount: u64, total: u64) -> Option<f64> {
+    (total > 0).then(|| amount as f64 * 100.0 / total as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{executor::block_on, stream};
+
+    const SOL: u64 = 1_000_000_000;
+
+    /// Gets a trade made against the given virtual SOL reserves
+    fn get_event(
+        mint: Pubkey,
+        is_buy: bool,
+        sol_amount: u64,
+        reserves_before: u64,
+        timestamp: i64,
+    ) -> TradeEvent {
+        TradeEvent {
+            mint,
+            sol_amount,
+            token_amount: 1_000,
+            is_buy,
+            user: Pubkey::new_unique(),
+            timestamp,
+            virtual_sol_reserves: if is_buy {
+                reserves_before + sol_amount
+            } else {
+                reserves_before - sol_amount
+            },
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+        }
+    }
+
+    /// Trades on two mints, the last one after a quiet period
+    fn get_events(first: Pubkey, second: Pubkey) -> Vec<TradeEvent> {
+        vec![
+            get_event(first, true, SOL / 2, 30 * SOL, 1_000),
+            get_event(first, true, 2 * SOL, 100 * SOL, 1_010),
+            get_event(second, false, SOL / 5, 2 * SOL, 1_020),
+            get_event(second, true, 5 * SOL, 40 * SOL, 1_030),
+            get_event(first, false, 3 * SOL, 100 * SOL, 1_040),
+            get_event(first, true, SOL / 10, 30 * SOL, 1_400),
+        ]
+    }
+
+    fn collect<S: Stream>(stream: S) -> Vec<S::Item> {
+        block_on(stream.collect())
+    }
+
+    #[test]
+    fn test_whale_filter_criteria() {
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let events = get_events(first, second);
+
+        let criteria = WhaleCriteria::new().with_min_sol(SOL);
+        let whales = collect(whale_filter(stream::iter(events.clone()), criteria));
+        let expected: Vec<_> = [1, 3, 4]
+            .into_iter()
+            .map(|index| WhaleTrade {
+                event: events[index].clone(),
+                reason: WhaleReason::SolAmount {
+                    lamports: events[index].sol_amount,
+                },
+            })
+            .collect();
+        assert_eq!(whales, expected);
+
+        let criteria = WhaleCriteria::new().with_min_reserve_pct(5.0);
+        let whales = collect(whale_filter(stream::iter(events.clone()), criteria));
+        assert_eq!(
+            whales,
+            vec![
+                WhaleTrade {
+                    event: events[2].clone(),
+                    reason: WhaleReason::ReserveShare { pct: 10.0 },
+                },
+                WhaleTrade {
+                    event: events[3].clone(),
+                    reason: WhaleReason::ReserveShare { pct: 12.5 },
+                },
+            ]
+        );
+
+        let tracker = Arc::new(Mutex::new(
+            NetFlowTracker::new(WHALE_VOLUME_WINDOW).with_mint(first),
+        ));
+        let criteria = WhaleCriteria::new().with_min_volume_pct(50.0, tracker.clone());
+        let whales = collect(whale_filter(stream::iter(events.clone()), criteria));
+        // 100% and 80% of the volume, then 3 of 5.5 SOL, then alone after the quiet period
+        let flagged: Vec<_> = whales.iter().map(|whale| whale.event.clone()).collect();
+        assert_eq!(
+            flagged,
+            vec![
+                events[0].clone(),
+                events[1].clone(),
+                events[4].clone(),
+                events[5].clone()
+            ]
+        );
+        assert!(whales.iter().all(|whale| matches!(
+            whale.reason,
+            WhaleReason::VolumeShare { pct } if pct >= 50.0
+        )));
+        // Older trades were evicted as the stream went on, and other mints never recorded
+        assert_eq!(tracker.lock().unwrap().len(), 1);
+
+        // No threshold, no whale
+        let whales = collect(whale_filter(stream::iter(events), WhaleCriteria::new()));
+        assert!(whales.is_empty());
+    }
+
+    #[test]
+    fn test_whale_filter_combined_criteria() {
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let events = get_events(first, second);
+
+        // A trade crossing several thresholds reports the first one
+        let criteria = WhaleCriteria::new()
+            .with_min_sol(SOL)
+            .with_min_reserve_pct(5.0);
+        let reasons: Vec<_> = collect(whale_filter(stream::iter(events.clone()), criteria))
+            .into_iter()
+            .map(|whale| whale.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                WhaleReason::SolAmount { lamports: 2 * SOL },
+                WhaleReason::ReserveShare { pct: 10.0 },
+                WhaleReason::SolAmount { lamports: 5 * SOL },
+                WhaleReason::SolAmount { lamports: 3 * SOL },
+            ]
+        );
+
+        // Trades flagged by size still count towards the volume
+        let tracker = Arc::new(Mutex::new(
+            NetFlowTracker::new(Duration::from_secs(3_600)).with_mint(first),
+        ));
+        let criteria = WhaleCriteria::new()
+            .with_min_sol(SOL)
+            .with_min_volume_pct(50.0, tracker.clone());
+        let reasons: Vec<_> = collect(whale_filter(stream::iter(events), criteria))
+            .into_iter()
+            .map(|whale| whale.reason)
+            .collect();
+        assert_eq!(
+            reasons,
+            vec![
+                WhaleReason::VolumeShare { pct: 100.0 },
+                WhaleReason::SolAmount { lamports: 2 * SOL },
+                WhaleReason::SolAmount { lamports: 5 * SOL },
+                WhaleReason::SolAmount { lamports: 3 * SOL },
+                WhaleReason::VolumeShare { pct: 100.0 },
+            ]
+        );
+        assert_eq!(
+            tracker
+                .lock()
+                .unwrap()
+                .volume_at(WHALE_VOLUME_WINDOW, 1_040),
+            11 * SOL / 2
+        );
+    }
+
+    #[test]
+    fn test_stacked_filters() {
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        let events = get_events(first, second);
+        let criteria = WhaleCriteria::new()
+            .with_min_sol(SOL)
+            .with_min_reserve_pct(5.0);
+
+        // Whale buys of the second mint
+        let whales = whale_filter(stream::iter(events.clone()), criteria.clone());
+        let buys = collect(side_filter(mint_filter(whales, second), TradeSide::Buy));
+        assert_eq!(buys.len(), 1);
+        assert_eq!(buys[0].event, events[3]);
+
+        // Sells of the first mint, then the whales among them
+        let sells = side_filter(
+            mint_filter(stream::iter(events.clone()), first),
+            TradeSide::Sell,
+        );
+        let whales = collect(whale_filter(sells, criteria));
+        assert_eq!(whales.len(), 1);
+        assert_eq!(whales[0].event, events[4]);
+
+        // The plain filters keep trades of either side and mint
+        assert_eq!(
+            collect(mint_filter(stream::iter(events.clone()), first)).len(),
+            4
+        );
+        assert_eq!(
+            collect(side_filter(stream::iter(events), TradeSide::Sell)).len(),
+            2
+        );
+    }
+}
@@ -0,0 +1,633 @@
+//! Events emitted by the Pump.fun Solana Program
+//!
+//! This module contains the definitions for the events emitted by the Pump.fun program and
+//! helpers for decoding them from transaction logs.
+//!
+//! Anchor events are written to the transaction logs as `Program data: <base64>` lines, where the
+//! decoded payload is an 8-byte discriminator followed by the Borsh-serialized event.
+//!
+//! # Events
+//!
+//! - `CreateEvent`: Emitted when a token and its bonding curve are created.
+//! - `TradeEvent`: Emitted for every buy and sell on a bonding curve.
+//! - `CompleteEvent`: Emitted when a bonding curve completes and liquidity is migrated.
+//! - `SetParamsEvent`: Emitted when the authority changes the global parameters.
+//! - `PumpFunEvent`: Any of the above, as decoded by [`crate::PumpFun::subscribe_events`].
+//!
+//! # Functions
+//!
+//! - `decode_event`: Decodes a single event from its raw payload
+//! - `parse_logs`: Decodes all events of a given type from transaction logs
+//! - `parse_all_logs`: Decodes the events of every type from transaction logs
+//!
+//! Consumers parsing many transactions should keep a [`LogParser`] instead of calling
+//! `parse_logs`: it reuses its decode buffer across calls, and its payload prefix and filter skip
+//! irrelevant events before they are deserialized. A payload prefix, such as the mint a trade
+//! starts with, is checked after decoding only the leading characters of the line. Either way,
+//! lines of other events are rejected from their first base64 characters, without decoding them.
+//!
+//! Streams of decoded trades can be narrowed to whale trades, a single mint or a single side
+//! with the combinators of [`filters`].
+
+pub mod filters;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::{BorshDeserialize, BorshSerialize};
+use solana_sdk::pubkey::Pubkey;
+use std::marker::PhantomData;
+
+/// Prefix of the log lines that carry event payloads
+pub const PROGRAM_DATA_PREFIX: &str = "Program data: ";
+
+/// An event emitted by the Pump.fun program
+pub trait Event: BorshDeserialize {
+    /// 8-byte discriminator prefixed to the serialized event
+    const DISCRIMINATOR: [u8; 8];
+}
+
+/// Emitted when a token and its bonding curve are created
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CreateEvent {
+    /// Name of the token
+    pub name: String,
+    /// Symbol of the token
+    pub symbol: String,
+    /// URI of the token's metadata
+    pub uri: String,
+    /// Token mint that was created
+    pub mint: Pubkey,
+    /// Bonding curve account of the token
+    pub bonding_curve: Pubkey,
+    /// User who created the token
+    pub user: Pubkey,
+}
+
+impl Event for CreateEvent {
+    const DISCRIMINATOR: [u8; 8] = [27, 114, 169, 77, 222, 235, 99, 118];
+}
+
+impl CreateEvent {
+    /// Gets the name cleaned up for display with the default policy, see
+    /// [`crate::utils::SanitizePolicy`]
+    pub fn name_sanitized(&self) -> String {
+        crate::utils::sanitize(&self.name)
+    }
+
+    /// Gets the symbol cleaned up for display with the default policy, see
+    /// [`crate::utils::SanitizePolicy`]
+    pub fn symbol_sanitized(&self) -> String {
+        crate::utils::sanitize(&self.symbol)
+    }
+}
+
+/// Emitted for every buy and sell on a bonding curve
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct TradeEvent {
+    /// Token mint that was traded
+    pub mint: Pubkey,
+    /// Amount of SOL in lamports paid (buy) or received (sell), excluding fees
+    pub sol_amount: u64,
+    /// Amount of tokens in base units bought or sold
+    pub token_amount: u64,
+    /// Whether the trade was a buy
+    pub is_buy: bool,
+    /// User who made the trade
+    pub user: Pubkey,
+    /// Unix timestamp of the trade
+    pub timestamp: i64,
+    /// Virtual SOL reserves after the trade
+    pub virtual_sol_reserves: u64,
+    /// Virtual token reserves after the trade
+    pub virtual_token_reserves: u64,
+    /// Real SOL reserves after the trade
+    pub real_sol_reserves: u64,
+    /// Real token reserves after the trade
+    pub real_token_reserves: u64,
+}
+
+impl Event for TradeEvent {
+    const DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+}
+
+/// Emitted when a bonding curve completes and its liquidity is ready for migration
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct CompleteEvent {
+    /// User whose trade completed the curve
+    pub user: Pubkey,
+    /// Token mint of the completed curve
+    pub mint: Pubkey,
+    /// Bonding curve account that completed
+    pub bonding_curve: Pubkey,
+    /// Unix timestamp at which the curve completed
+    pub timestamp: i64,
+}
+
+impl Event for CompleteEvent {
+    const DISCRIMINATOR: [u8; 8] = [95, 114, 97, 156, 212, 46, 152, 8];
+}
+
+/// Emitted when the authority changes the global parameters
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct SetParamsEvent {
+    /// Account that receives fees
+    pub fee_recipient: Pubkey,
+    /// Initial virtual token reserves of new bonding curves
+    pub initial_virtual_token_reserves: u64,
+    /// Initial virtual SOL reserves of new bonding curves
+    pub initial_virtual_sol_reserves: u64,
+    /// Initial real token reserves of new bonding curves
+    pub initial_real_token_reserves: u64,
+    /// Total supply of new tokens
+    pub token_total_supply: u64,
+    /// Fee in basis points (1/100th of a percent)
+    pub fee_basis_points: u64,
+}
+
+impl Event for SetParamsEvent {
+    const DISCRIMINATOR: [u8; 8] = [223, 195, 159, 246, 62, 48, 143, 131];
+}
+
+/// Any event emitted by the Pump.fun program
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PumpFunEvent {
+    /// A token was created
+    Create(CreateEvent),
+    /// A token was bought or sold
+    Trade(TradeEvent),
+    /// A bonding curve completed
+    Complete(CompleteEvent),
+    /// The global parameters changed
+    SetParams(SetParamsEvent),
+}
+
+impl PumpFunEvent {
+    /// Decodes an event of any type from its raw payload
+    ///
+    /// # Arguments
+    /// * `data` - Discriminator followed by the Borsh-serialized event
+    ///
+    /// # Returns
+    /// The decoded event, or None if the discriminator is unknown or the payload is malformed
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let discriminator: [u8; 8] = data.get(..8)?.try_into().ok()?;
+        match discriminator {
+            CreateEvent::DISCRIMINATOR => decode_event(data).map(Self::Create),
+            TradeEvent::DISCRIMINATOR => decode_event(data).map(Self::Trade),
+            CompleteEvent::DISCRIMINATOR => decode_event(data).map(Self::Complete),
+            SetParamsEvent::DISCRIMINATOR => decode_event(data).map(Self::SetParams),
+            _ => None,
+        }
+    }
+
+    /// Gets the token mint the event is about
+    ///
+    /// # Returns
+    /// The mint, or None for events not tied to a token
+    pub fn mint(&self) -> Option<&Pubkey> {
+        match self {
+            Self::Create(event) => Some(&event.mint),
+            Self::Trade(event) => Some(&event.mint),
+            Self::Complete(event) => Some(&event.mint),
+            Self::SetParams(_) => None,
+        }
+    }
+}
+
+/// Decodes an event from its raw payload
+///
+/// # Arguments
+/// * `data` - Discriminator followed by the Borsh-serialized event
+///
+/// # Returns
+/// The decoded event, or None if the discriminator does not match or the payload is malformed
+pub fn decode_event<T: Event>(data: &[u8]) -> Option<T> {
+    let payload = data.strip_prefix(&T::DISCRIMINATOR)?;
+    T::deserialize(&mut &payload[..]).ok()
+}
+
+/// Decodes all events of a given type from transaction logs
+///
+/// # Arguments
+/// * `logs` - Log lines of a transaction
+///
+/// # Returns
+/// The decoded events in log order. Lines that are not event payloads, or payloads of other
+/// events, are skipped.
+pub fn parse_logs<T: Event>(logs: &[impl AsRef<str>]) -> Vec<T> {
+    LogParser::new().parse(logs)
+}
+
+/// Decodes the events of every type from transaction logs
+///
+/// # Arguments
+/// * `logs` - Log lines of a transaction
+///
+/// # Returns
+/// The decoded events in log order. Lines that are not event payloads, or payloads of unknown
+/// events, are skipped.
+pub fn parse_all_logs(logs: &[impl AsRef<str>]) -> Vec<PumpFunEvent> {
+    let mut buffer = Vec::new();
+    logs.iter()
+        .filter_map(|log| log.as_ref().strip_prefix(PROGRAM_DATA_PREFIX))
+        .filter_map(|data| {
+            buffer.clear();
+            STANDARD.decode_vec(data, &mut buffer).ok()?;
+            PumpFunEvent::decode(&buffer)
+        })
+        .collect()
+}
+
+/// Number of base64 characters encoding the first 6 bytes of a payload
+///
+/// Base64 encodes every 3 bytes as 4 characters on their own, so these characters only depend
+/// on the first 6 bytes of the discriminator and can be compared without decoding the line.
+const DISCRIMINATOR_PREFIX_LEN: usize = 8;
+
+/// Payload filter of a [`LogParser`]
+type PayloadFilter = Box<dyn Fn(&[u8]) -> bool + Send + Sync>;
+
+/// Reusable decoder of one type of event from transaction logs
+///
+/// Decodes the same events as [`parse_logs`], but keeps its decode buffer between calls, so
+/// a consumer parsing every transaction of a busy stream doesn't allocate one per log line.
+///
+/// # Example
+///
+/// ```rust
+/// use pumpfun::{events::{LogParser, TradeEvent}, prelude::Pubkey};
+///
+/// let mint = Pubkey::new_unique();
+/// // A trade event payload starts with its mint
+/// let mut parser = LogParser::<TradeEvent>::new().with_payload_prefix(mint);
+/// let logs = vec!["Program log: Instruction: Buy".to_string()];
+/// assert!(parser.parse(&logs).is_empty());
+/// ```
+pub struct LogParser<T: Event> {
+    /// Base64 encoding of the start of the event's discriminator
+    prefix: [u8; DISCRIMINATOR_PREFIX_LEN],
+    /// Buffer the payload of each line is decoded into
+    buffer: Vec<u8>,
+    /// Bytes the Borsh payload after the discriminator must start with
+    payload_prefix: Vec<u8>,
+    /// Predicate on the Borsh payload after the discriminator, checked before deserializing
+    filter: Option<PayloadFilter>,
+    _event: PhantomData<fn() -> T>,
+}
+
+impl<T: Event> LogParser<T> {
+    /// Creates a parser without a filter
+    pub fn new() -> Self {
+        let mut prefix = [0; DISCRIMINATOR_PREFIX_LEN];
+        STANDARD
+            .encode_slice(&T::DISCRIMINATOR[..6], &mut prefix)
+            .expect("6 bytes encode to 8 base64 characters");
+
+        Self {
+            prefix,
+            buffer: Vec::new(),
+            payload_prefix: Vec::new(),
+            filter: None,
+            _event: PhantomData,
+        }
+    }
+
+    /// Skips events whose payload doesn't match a predicate, without deserializing them
+    ///
+    /// # Arguments
+    ///
+    /// * `filter` - Predicate on the Borsh-serialized event after its discriminator, e.g. on the
+    ///   leading mint of a [`TradeEvent`]
+    ///
+    /// # Returns
+    ///
+    /// Returns the parser, only decoding the events whose payload matches
+    pub fn with_filter(mut self, filter: impl Fn(&[u8]) -> bool + Send + Sync + 'static) -> Self {
+        self.filter = Some(Box::new(filter));
+        self
+    }
+
+    /// Skips events whose payload doesn't start with the given bytes
+    ///
+    /// Unlike [`Self::with_filter`], the prefix is compared after decoding only the leading base64
+    /// characters of a line, so the lines it rejects are never decoded in full.
+    ///
+    /// # Arguments
+    ///
+    /// * `prefix` - Leading bytes of the Borsh-serialized event after its discriminator, e.g. the
+    ///   mint a [`TradeEvent`] starts with
+    ///
+    /// # Returns
+    ///
+    /// Returns the parser, only decoding the events whose payload starts with the prefix
+    pub fn with_payload_prefix(mut self, prefix: impl AsRef<[u8]>) -> Self {
+        self.payload_prefix = prefix.as_ref().to_vec();
+        self
+    }
+
+    /// Decodes the events of a transaction's logs
+    ///
+    /// # Arguments
+    ///
+    /// * `logs` - Log lines of a transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded events in log order, skipping the lines [`parse_logs`] skips and the
+    /// events the payload prefix or filter rejects
+    pub fn parse(&mut self, logs: &[impl AsRef<str>]) -> Vec<T> {
+        let mut events = Vec::new();
+        for log in logs {
+            if let Some(event) = self.parse_line(log.as_ref()) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Decodes the event of a single log line, if it carries one of the parser's type
+    fn parse_line(&mut self, log: &str) -> Option<T> {
+        let data = log.strip_prefix(PROGRAM_DATA_PREFIX)?;
+        if !data.as_bytes().starts_with(&self.prefix) {
+            return None;
+        }
+
+        // Decode just the base64 groups covering the discriminator and payload prefix first, so
+        // lines of other mints are rejected without decoding the rest
+        if !self.payload_prefix.is_empty() {
+            let len = T::DISCRIMINATOR.len() + self.payload_prefix.len();
+            let head = data.as_bytes().get(..len.div_ceil(3) * 4)?;
+            self.buffer.clear();
+            STANDARD.decode_vec(head, &mut self.buffer).ok()?;
+            if self.buffer.get(T::DISCRIMINATOR.len()..len)? != self.payload_prefix.as_slice() {
+                return None;
+            }
+        }
+
+        self.buffer.clear();
+        STANDARD.decode_vec(data, &mut self.buffer).ok()?;
+        let payload = self.buffer.strip_prefix(&T::DISCRIMINATOR)?;
+        if let Some(filter) = &self.filter {
+            if !filter(payload) {
+                return None;
+            }
+        }
+        T::deserialize(&mut &payload[..]).ok()
+    }
+}
+
+impl<T: Event> Default for LogParser<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Event> std::fmt::Debug for LogParser<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogParser")
+            .field("capacity", &self.buffer.capacity())
+            .field("payload_prefix", &self.payload_prefix)
+            .field("filtered", &self.filter.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discriminators_match_idl() {
+        let idl: serde_json::Value =
+            serde_json::from_str(include_str!("../../../pumpfun-cpi/idl.json")).unwrap();
+        let decoded = [
+            ("CreateEvent", CreateEvent::DISCRIMINATOR),
+            ("TradeEvent", TradeEvent::DISCRIMINATOR),
+            ("CompleteEvent", CompleteEvent::DISCRIMINATOR),
+            ("SetParamsEvent", SetParamsEvent::DISCRIMINATOR),
+        ];
+
+        // Every event in the IDL has a decoder, under the same name and discriminator
+        let events = idl["events"].as_array().unwrap();
+        for event in events {
+            let name = event["name"].as_str().unwrap();
+            let discriminator: [u8; 8] =
+                serde_json::from_value(event["discriminator"].clone()).unwrap();
+            assert_eq!(
+                decoded.iter().find(|(decoded, _)| *decoded == name),
+                Some(&(name, discriminator)),
+                "event `{}`",
+                name
+            );
+        }
+        assert_eq!(events.len(), decoded.len());
+    }
+
+    fn get_complete_event() -> CompleteEvent {
+        CompleteEvent {
+            user: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            timestamp: 1_735_689_600,
+        }
+    }
+
+    fn to_log<T: Event + BorshSerialize>(event: &T) -> String {
+        let mut data = T::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+        format!("{}{}", PROGRAM_DATA_PREFIX, STANDARD.encode(data))
+    }
+
+    fn get_trade_event() -> TradeEvent {
+        TradeEvent {
+            mint: Pubkey::new_unique(),
+            sol_amount: 1_000_000_000,
+            token_amount: 34_612_903_225_806,
+            is_buy: true,
+            user: Pubkey::new_unique(),
+            timestamp: 1_735_689_600,
+            virtual_sol_reserves: 31_000_000_000,
+            virtual_token_reserves: 1_038_387_096_774_194,
+            real_sol_reserves: 1_000_000_000,
+            real_token_reserves: 758_487_096_774_194,
+        }
+    }
+
+    #[test]
+    fn test_decode_event() {
+        let event = get_complete_event();
+        let mut data = CompleteEvent::DISCRIMINATOR.to_vec();
+        event.serialize(&mut data).unwrap();
+
+        assert_eq!(decode_event::<CompleteEvent>(&data), Some(event));
+
+        // Wrong discriminator or truncated payload
+        assert_eq!(decode_event::<CompleteEvent>(&data[1..]), None);
+        assert_eq!(decode_event::<CompleteEvent>(&data[..40]), None);
+    }
+
+    #[test]
+    fn test_parse_logs() {
+        let event = get_complete_event();
+        let logs = vec![
+            "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P invoke [1]".to_string(),
+            "Program log: Instruction: Buy".to_string(),
+            format!("{}not base64!", PROGRAM_DATA_PREFIX),
+            to_log(&event),
+            "Program 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P success".to_string(),
+        ];
+
+        assert_eq!(parse_logs::<CompleteEvent>(&logs), vec![event]);
+        assert!(parse_logs::<CompleteEvent>(&logs[..3]).is_empty());
+    }
+
+    #[test]
+    fn test_log_parser() {
+        let trade = get_trade_event();
+        let other = TradeEvent {
+            mint: Pubkey::new_unique(),
+            ..get_trade_event()
+        };
+        let complete = get_complete_event();
+        let logs = vec![
+            to_log(&trade),
+            "Program log: Instruction: Sell".to_string(),
+            to_log(&complete),
+            to_log(&other),
+        ];
+
+        // Borrowed lines decode like owned ones, and the buffer is reused across calls
+        let mut parser = LogParser::<TradeEvent>::new();
+        let borrowed: Vec<&str> = logs.iter().map(String::as_str).collect();
+        assert_eq!(parser.parse(&borrowed), vec![trade.clone(), other.clone()]);
+        assert_eq!(parser.parse(&logs), parse_logs::<TradeEvent>(&logs));
+
+        // The filter sees the payload after the discriminator
+        let mint = trade.mint;
+        let mut parser = LogParser::<TradeEvent>::new()
+            .with_filter(move |payload| payload.starts_with(mint.as_ref()));
+        assert_eq!(parser.parse(&logs), vec![trade.clone()]);
+
+        // The payload prefix skips other mints from the start of their line, and still rejects
+        // lines of other events and lines too short to hold it
+        let mut parser = LogParser::<TradeEvent>::new().with_payload_prefix(mint);
+        assert_eq!(parser.parse(&logs), vec![trade.clone()]);
+        let mut truncated = to_log(&trade);
+        truncated.truncate(PROGRAM_DATA_PREFIX.len() + 40);
+        assert!(parser.parse(&[truncated.as_str()]).is_empty());
+        let mut parser = LogParser::<CompleteEvent>::new().with_payload_prefix(trade.user);
+        assert!(parser.parse(&logs).is_empty());
+        let mut parser = LogParser::<CompleteEvent>::new().with_payload_prefix(complete.user);
+        assert_eq!(parser.parse(&logs), vec![complete.clone()]);
+
+        // Malformed lines sharing the prefix fail the full decode
+        let mut forged = to_log(&trade);
+        forged.push('!');
+        let mut parser = LogParser::<TradeEvent>::new().with_payload_prefix(mint);
+        assert!(parser.parse(&[forged.as_str()]).is_empty());
+
+        // Lines of other events are rejected from their prefix, even when not valid base64
+        let mut forged = to_log(&complete);
+        forged.push('!');
+        assert!(LogParser::<TradeEvent>::new()
+            .parse(&[forged.as_str()])
+            .is_empty());
+        assert!(LogParser::<CompleteEvent>::new()
+            .parse(&[forged.as_str()])
+            .is_empty());
+    }
+
+    fn get_create_event() -> CreateEvent {
+        CreateEvent {
+            name: "Pump Token".to_string(),
+            symbol: "PUMP".to_string(),
+            uri: "https://ipfs.io/ipfs/QmPumpToken".to_string(),
+            mint: Pubkey::new_unique(),
+            bonding_curve: Pubkey::new_unique(),
+            user: Pubkey::new_unique(),
+        }
+    }
+
+    #[test]
+    fn test_decode_create_event() {
+        // A fixed creation log line, independent of the Borsh derive
+        let log = "Program data: G3KpTd7rY3YKAAAAUHVtcCBUb2tlbgQAAABQVU1QIAAAAGh0dHBzOi8vaXBmcy5pby9pcGZzL1FtUHVtcFRva2VuAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQUFBQQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE";
+        let event = parse_logs::<CreateEvent>(&[log]).pop().unwrap();
+        assert_eq!(
+            event,
+            CreateEvent {
+                name: "Pump Token".to_string(),
+                symbol: "PUMP".to_string(),
+                uri: "https://ipfs.io/ipfs/QmPumpToken".to_string(),
+                mint: Pubkey::new_from_array([1; 32]),
+                bonding_curve: Pubkey::new_from_array([5; 32]),
+                user: Pubkey::new_from_array([4; 32]),
+            }
+        );
+
+        // Names are logged as the creator chose them, and sanitized on request
+        let hostile = CreateEvent {
+            name: "\u{1b}[31mPump\u{202e}Token\0".to_string(),
+            symbol: "PUMP\u{7}".to_string(),
+            ..get_create_event()
+        };
+        let decoded = parse_logs::<CreateEvent>(&[to_log(&hostile)])
+            .pop()
+            .unwrap();
+        assert_eq!(decoded, hostile);
+        assert_eq!(decoded.name_sanitized(), "PumpToken");
+        assert_eq!(decoded.symbol_sanitized(), "PUMP");
+
+        // Round trips, and only decodes as a creation
+        let event = get_create_event();
+        let logs = vec![to_log(&event)];
+        assert_eq!(parse_logs::<CreateEvent>(&logs), vec![event]);
+        assert!(parse_logs::<TradeEvent>(&logs).is_empty());
+    }
+
+    #[test]
+    fn test_parse_all_logs() {
+        let create = get_create_event();
+        let trade = TradeEvent {
+            mint: create.mint,
+            ..get_trade_event()
+        };
+        let complete = get_complete_event();
+        let logs = vec![
+            "Program log: Instruction: Create".to_string(),
+            to_log(&create),
+            format!("{}not base64!", PROGRAM_DATA_PREFIX),
+            format!("{}{}", PROGRAM_DATA_PREFIX, STANDARD.encode([0; 16])),
+            to_log(&trade),
+            to_log(&complete),
+        ];
+
+        let events = parse_all_logs(&logs);
+        assert_eq!(
+            events,
+            vec![
+                PumpFunEvent::Create(create.clone()),
+                PumpFunEvent::Trade(trade),
+                PumpFunEvent::Complete(complete.clone()),
+            ]
+        );
+        assert_eq!(events[0].mint(), Some(&create.mint));
+        assert_eq!(events[2].mint(), Some(&complete.mint));
+
+        // Truncated payloads are skipped rather than misread
+        let mut data = CreateEvent::DISCRIMINATOR.to_vec();
+        get_create_event().serialize(&mut data).unwrap();
+        assert_eq!(PumpFunEvent::decode(&data[..data.len() - 1]), None);
+        assert_eq!(PumpFunEvent::decode(&data[..4]), None);
+    }
+
+    #[test]
+    fn test_parse_logs_by_event_type() {
+        let trade = get_trade_event();
+        let complete = get_complete_event();
+        let logs = vec![to_log(&trade), to_log(&complete)];
+
+        // Each event type only picks up its own payloads
+        assert_eq!(parse_logs::<TradeEvent>(&logs), vec![trade]);
+        assert_eq!(parse_logs::<CompleteEvent>(&logs), vec![complete]);
+    }
+}
@@ -0,0 +1,698 @@
+//! Export of tokens in the Solana token list format
+//!
+//! Wallets, explorers and aggregators such as Jupiter ingest token lists in the shape of the
+//! Solana token-list schema. This module converts tokens into that shape:
+//!
+//! - `TokenInfo`: Mint and metadata of a token, as read from its accounts.
+//! - `TokenListJson`: A token list, serializing to the published schema.
+//! - `TokenListEntry`: A single token of a list.
+//!
+//! Names and symbols are chosen by token creators and may hold control characters or
+//! bidirectional overrides, so they are sanitized when read, see [`SanitizePolicy`]. The raw
+//! values are kept in separate fields.
+//!
+//! Optional fields that are unknown or empty, such as a logo for a token whose metadata has no
+//! image, are omitted from the JSON rather than emitted as empty strings, which most consumers
+//! reject or render as broken images.
+
+use crate::{
+    error::ClientError,
+    screening::decode_mint,
+    utils::{sanitize, SanitizePolicy},
+};
+use anchor_client::{solana_sdk::pubkey::Pubkey, Cluster};
+use isahc::{config::Configurable, AsyncReadResponseExt};
+use mpl_token_metadata::accounts::Metadata;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Chain ID of mainnet-beta in token lists
+pub const MAINNET_CHAIN_ID: u32 = 101;
+
+/// Chain ID of testnet in token lists
+pub const TESTNET_CHAIN_ID: u32 = 102;
+
+/// Chain ID of devnet in token lists
+pub const DEVNET_CHAIN_ID: u32 = 103;
+
+/// Tag given to every Pump.fun token in exported lists
+pub const PUMPFUN_TAG: &str = "pump-fun";
+
+/// Time allowed to fetch a token's off-chain metadata JSON, see [`fetch_logo_uri`]
+pub const LOGO_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Mint and metadata of a token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenInfo {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Number of decimals of the mint
+    pub decimals: u8,
+    /// Name of the token, sanitized for display
+    pub name: String,
+    /// Token symbol, sanitized for display
+    pub symbol: String,
+    /// Name of the token as stored in its metadata, without the padding
+    pub raw_name: String,
+    /// Token symbol as stored in its metadata, without the padding
+    pub raw_symbol: String,
+    /// URI of the off-chain metadata JSON, if set
+    pub uri: Option<String>,
+    /// URL of the token's image, if known
+    pub logo_uri: Option<String>,
+    /// Tags to list the token under
+    pub tags: Vec<String>,
+}
+
+impl TokenInfo {
+    /// Reads a token from its mint and metadata account data
+    ///
+    /// The fixed-size name, symbol and URI fields of the metadata are stripped of their
+    /// padding, and an empty URI is treated as unset. The name and symbol are sanitized with the
+    /// default policy, keeping the raw values alongside. The logo is left unknown, since it
+    /// lives in the off-chain metadata.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `mint_data` - Data of the mint account
+    /// * `metadata_data` - Data of the token's metadata account
+    ///
+    /// # Returns
+    ///
+    /// Returns the token tagged as a Pump.fun token, or a ClientError if either account cannot
+    /// be decoded
+    pub fn from_accounts(
+        mint: Pubkey,
+        mint_data: &[u8],
+        metadata_data: &[u8],
+    ) -> Result<Self, ClientError> {
+        let mint_account = decode_mint(mint_data)?;
+        let metadata =
+            Metadata::safe_deserialize(metadata_data).map_err(ClientError::BorshError)?;
+
+        let raw_name = metadata.name.trim_end_matches('\0').to_string();
+        let raw_symbol = metadata.symbol.trim_end_matches('\0').to_string();
+
+        Ok(Self {
+            mint,
+            decimals: mint_account.decimals,
+            name: sanitize(&raw_name),
+            symbol: sanitize(&raw_symbol),
+            raw_name,
+            raw_symbol,
+            uri: non_empty(trim_padding(&metadata.uri)),
+            logo_uri: None,
+            tags: vec![PUMPFUN_TAG.to_string()],
+        })
+    }
+
+    /// Re-sanitizes the name and symbol from their raw values with another policy
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Policy to apply instead of the default one
+    ///
+    /// # Returns
+    ///
+    /// Returns the token with its name and symbol sanitized by `policy`
+    pub fn with_sanitize_policy(mut self, policy: &SanitizePolicy) -> Self {
+        self.name = policy.sanitize(&self.raw_name);
+        self.symbol = policy.sanitize(&self.raw_symbol);
+        self
+    }
+}
+
+/// Version of a token list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenListVersion {
+    /// Incremented when tokens are removed or changed incompatibly
+    pub major: u32,
+    /// Incremented when tokens are added
+    pub minor: u32,
+    /// Incremented for other changes
+    pub patch: u32,
+}
+
+/// Definition of a tag used by the tokens of a list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TagDefinition {
+    /// Display name of the tag
+    pub name: String,
+    /// What the tag means
+    pub description: String,
+}
+
+/// A single token of a token list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenListEntry {
+    /// Chain the token lives on, see [`MAINNET_CHAIN_ID`]
+    pub chain_id: u32,
+    /// Base58 address of the token mint
+    pub address: String,
+    /// Number of decimals of the mint
+    pub decimals: u8,
+    /// Name of the token
+    pub name: String,
+    /// Token symbol
+    pub symbol: String,
+    /// URL of the token's image, omitted if unknown
+    #[serde(rename = "logoURI", default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// Tags the token is listed under, omitted if none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// A token list in the Solana token-list format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenListJson {
+    /// Name of the list
+    pub name: String,
+    /// URL of the list's image, omitted if unset
+    #[serde(rename = "logoURI", default, skip_serializing_if = "Option::is_none")]
+    pub logo_uri: Option<String>,
+    /// Keywords describing the list
+    pub keywords: Vec<String>,
+    /// Definitions of the tags used by the tokens
+    pub tags: BTreeMap<String, TagDefinition>,
+    /// When the list was generated, as an ISO 8601 UTC timestamp
+    pub timestamp: String,
+    /// Tokens of the list
+    pub tokens: Vec<TokenListEntry>,
+    /// Version of the list
+    pub version: TokenListVersion,
+}
+
+/// Converts tokens into a mainnet token list generated now
+///
+/// # Arguments
+///
+/// * `entries` - Tokens to list, in order
+///
+/// # Returns
+///
+/// Returns the token list
+pub fn to_token_list(entries: &[TokenInfo]) -> TokenListJson {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or_default();
+    to_token_list_at(entries, MAINNET_CHAIN_ID, now)
+}
+
+/// Converts tokens into a token list for a chain, generated at a given time
+///
+/// # Arguments
+///
+/// * `entries` - Tokens to list, in order
+/// * `chain_id` - Chain the tokens live on, see [`chain_id`]
+/// * `unix_timestamp` - When the list is generated, in seconds
+///
+/// # Returns
+///
+/// Returns the token list, with definitions for the tags this crate assigns
+pub fn to_token_list_at(
+    entries: &[TokenInfo],
+    chain_id: u32,
+    unix_timestamp: i64,
+) -> TokenListJson {
+    let tokens: Vec<TokenListEntry> = entries
+        .iter()
+        .map(|entry| TokenListEntry {
+            chain_id,
+            address: entry.mint.to_string(),
+            decimals: entry.decimals,
+            name: entry.name.clone(),
+            symbol: entry.symbol.clone(),
+            logo_uri: entry.logo_uri.clone().and_then(non_empty),
+            tags: entry.tags.clone(),
+        })
+        .collect();
+
+    let mut tags = BTreeMap::new();
+    if tokens
+        .iter()
+        .any(|token| token.tags.iter().any(|tag| tag == PUMPFUN_TAG))
+    {
+        tags.insert(
+            PUMPFUN_TAG.to_string(),
+            TagDefinition {
+                name: "Pump.fun".to_string(),
+                description: "Token launched on a Pump.fun bonding curve".to_string(),
+            },
+        );
+    }
+
+    TokenListJson {
+        name: "Pump.fun".to_string(),
+        logo_uri: None,
+        keywords: vec!["pump.fun".to_string(), "solana".to_string()],
+        tags,
+        timestamp: iso8601(unix_timestamp),
+        tokens,
+        version: TokenListVersion::default(),
+    }
+}
+
+/// Gets the token list chain ID of a cluster
+///
+/// Custom clusters are assumed to be mainnet RPC providers; localnet and debug clusters are
+/// listed as devnet.
+///
+/// # Arguments
+///
+/// * `cluster` - Cluster the tokens live on
+pub fn chain_id(cluster: &Cluster) -> u32 {
+    match cluster {
+        Cluster::Mainnet | Cluster::Custom(..) => MAINNET_CHAIN_ID,
+        Cluster::Testnet => TESTNET_CHAIN_ID,
+        _ => DEVNET_CHAIN_ID,
+    }
+}
+
+/// Fetches the image URL from a token's off-chain metadata JSON
+///
+/// The request is abandoned after [`LOGO_FETCH_TIMEOUT`], so an unresponsive metadata host
+/// cannot stall an export.
+///
+/// # Arguments
+///
+/// * `uri` - URI of the off-chain metadata JSON
+///
+/// # Returns
+///
+/// Returns the non-empty `image` field of the metadata, or None if it is missing or the
+/// metadata cannot be fetched in time or parsed
+pub async fn fetch_logo_uri(uri: &str) -> Option<String> {
+    let request = isahc::Request::get(uri)
+        .timeout(LOGO_FETCH_TIMEOUT)
+        .body(())
+        .ok()?;
+    let mut response = isahc::send_async(request).await.ok()?;
+    let metadata: serde_json::Value = serde_json::from_str(&response.text().await.ok()?).ok()?;
+    metadata
+        .get("image")
+        .and_then(serde_json::Value::as_str)
+        .map(|image| image.trim().to_string())
+        .and_then(non_empty)
+}
+
+/// Strips the null padding and surrounding whitespace of a fixed-size metadata field
+fn trim_padding(value: &str) -> String {
+    value.trim_end_matches('\0').trim().to_string()
+}
+
+/// Treats an empty string as unset
+fn non_empty(value: String) -> Option<String> {
+    (!value.is_empty()).then_some(value)
+}
+
+/// Formats a Unix timestamp as an ISO 8601 UTC timestamp
+fn iso8601(unix_timestamp: i64) -> String {
+    let secs = unix_timestamp.max(0);
+    let (days, time) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since the epoch, see Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.000Z",
+        year,
+        month,
+        day,
+        time / 3_600,
+        time % 3_600 / 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_info(logo_uri: Option<&str>) -> TokenInfo {
+        TokenInfo {
+            mint: Pubkey::new_unique(),
+            decimals: 6,
+            name: "Pump Token".to_string(),
+            symbol: "PUMP".to_string(),
+            raw_name: "Pump Token".to_string(),
+            raw_symbol: "PUMP".to_string(),
+            uri: None,
+            logo_uri: logo_uri.map(str::to_string),
+            tags: vec![PUMPFUN_TAG.to_string()],
+        }
+    }
+
+    #[test]
+    fn test_token_list_schema() {
+        let entries = [get_info(Some("https://ipfs.io/ipfs/logo")), get_info(None)];
+        let list = to_token_list_at(&entries, MAINNET_CHAIN_ID, 1_700_000_000);
+        let json = serde_json::to_value(&list).unwrap();
+
+        assert_eq!(json["name"], "Pump.fun");
+        assert_eq!(json["timestamp"], "2023-11-14T22:13:20.000Z");
+        assert_eq!(
+            json["version"],
+            serde_json::json!({ "major": 0, "minor": 0, "patch": 0 })
+        );
+        assert_eq!(json["tags"][PUMPFUN_TAG]["name"], "Pump.fun");
+        assert!(json.get("logoURI").is_none());
+
+        let token = &json["tokens"][0];
+        assert_eq!(token["chainId"], 101);
+        assert_eq!(token["address"], entries[0].mint.to_string());
+        assert_eq!(token["decimals"], 6);
+        assert_eq!(token["symbol"], "PUMP");
+        assert_eq!(token["logoURI"], "https://ipfs.io/ipfs/logo");
+        assert_eq!(token["tags"], serde_json::json!([PUMPFUN_TAG]));
+
+        // Missing logos are omitted rather than empty
+        assert!(json["tokens"][1].get("logoURI").is_none());
+
+        let round_trip: TokenListJson = serde_json::from_value(json).unwrap();
+        assert_eq!(round_trip, list);
+    }
+
+    /// Checks a token list against the Solana token-list format, returning every violation
+    ///
+    /// Lists hold exactly the known fields with their JSON types. Strings that are required
+    /// are non-empty, addresses are base58 public keys, timestamps are ISO 8601 UTC, versions
+    /// are non-negative integers, and every tag a token uses is defined by the list.
+    fn schema_violations(json: &serde_json::Value) -> Vec<String> {
+        use serde_json::Value;
+
+        fn check_keys(
+            object: &serde_json::Map<String, Value>,
+            path: &str,
+            required: &[&str],
+            optional: &[&str],
+            violations: &mut Vec<String>,
+        ) {
+            for key in required {
+                if !object.contains_key(*key) {
+                    violations.push(format!("{path}: missing {key}"));
+                }
+            }
+            for key in object.keys() {
+                if !required.contains(&key.as_str()) && !optional.contains(&key.as_str()) {
+                    violations.push(format!("{path}: unknown {key}"));
+                }
+            }
+        }
+        fn non_empty_str(value: &Value, path: String, violations: &mut Vec<String>) {
+            if !value.as_str().is_some_and(|value| !value.is_empty()) {
+                violations.push(format!("{path}: expected a non-empty string"));
+            }
+        }
+
+        let mut violations = Vec::new();
+        let Some(list) = json.as_object() else {
+            return vec!["list: expected an object".to_string()];
+        };
+        check_keys(
+            list,
+            "list",
+            &["name", "keywords", "tags", "timestamp", "tokens", "version"],
+            &["logoURI"],
+            &mut violations,
+        );
+        non_empty_str(&json["name"], "list.name".to_string(), &mut violations);
+        if let Some(logo) = list.get("logoURI") {
+            non_empty_str(logo, "list.logoURI".to_string(), &mut violations);
+        }
+        match json["keywords"].as_array() {
+            Some(keywords) => keywords.iter().enumerate().for_each(|(i, keyword)| {
+                non_empty_str(keyword, format!("list.keywords[{i}]"), &mut violations)
+            }),
+            None => violations.push("list.keywords: expected an array".to_string()),
+        }
+        let defined = match json["tags"].as_object() {
+            Some(tags) => {
+                for (id, tag) in tags {
+                    match tag.as_object() {
+                        Some(object) => {
+                            let path = format!("list.tags.{id}");
+                            check_keys(
+                                object,
+                                &path,
+                                &["name", "description"],
+                                &[],
+                                &mut violations,
+                            );
+                            non_empty_str(&tag["name"], format!("{path}.name"), &mut violations);
+                            non_empty_str(
+                                &tag["description"],
+                                format!("{path}.description"),
+                                &mut violations,
+                            );
+                        }
+                        None => violations.push(format!("list.tags.{id}: expected an object")),
+                    }
+                }
+                tags.keys().cloned().collect()
+            }
+            None => {
+                violations.push("list.tags: expected an object".to_string());
+                Vec::new()
+            }
+        };
+        let timestamp = json["timestamp"].as_str().unwrap_or_default();
+        let is_iso8601 = timestamp.len() == 24
+            && timestamp.bytes().enumerate().all(|(i, byte)| match i {
+                4 | 7 => byte == b'-',
+                10 => byte == b'T',
+                13 | 16 => byte == b':',
+                19 => byte == b'.',
+                23 => byte == b'Z',
+                _ => byte.is_ascii_digit(),
+            });
+        if !is_iso8601 {
+            violations.push(format!("list.timestamp: {timestamp:?} is not ISO 8601 UTC"));
+        }
+        match json["version"].as_object() {
+            Some(version) => {
+                check_keys(
+                    version,
+                    "list.version",
+                    &["major", "minor", "patch"],
+                    &[],
+                    &mut violations,
+                );
+                for (key, value) in version {
+                    if !value.is_u64() {
+                        violations.push(format!("list.version.{key}: expected an integer >= 0"));
+                    }
+                }
+            }
+            None => violations.push("list.version: expected an object".to_string()),
+        }
+
+        let Some(tokens) = json["tokens"].as_array() else {
+            violations.push("list.tokens: expected an array".to_string());
+            return violations;
+        };
+        for (i, token) in tokens.iter().enumerate() {
+            let path = format!("list.tokens[{i}]");
+            let Some(object) = token.as_object() else {
+                violations.push(format!("{path}: expected an object"));
+                continue;
+            };
+            check_keys(
+                object,
+                &path,
+                &["chainId", "address", "decimals", "name", "symbol"],
+                &["logoURI", "tags", "extensions"],
+                &mut violations,
+            );
+            if !token["chainId"].as_u64().is_some_and(|id| id >= 1) {
+                violations.push(format!("{path}.chainId: expected an integer >= 1"));
+            }
+            if token["address"]
+                .as_str()
+                .and_then(|address| address.parse::<Pubkey>().ok())
+                .is_none()
+            {
+                violations.push(format!("{path}.address: expected a base58 public key"));
+            }
+            if !token["decimals"]
+                .as_u64()
+                .is_some_and(|decimals| decimals <= 255)
+            {
+                violations.push(format!("{path}.decimals: expected an integer in 0..=255"));
+            }
+            non_empty_str(&token["name"], format!("{path}.name"), &mut violations);
+            non_empty_str(&token["symbol"], format!("{path}.symbol"), &mut violations);
+            if let Some(logo) = token.get("logoURI") {
+                non_empty_str(logo, format!("{path}.logoURI"), &mut violations);
+            }
+            if let Some(tags) = token.get("tags") {
+                match tags.as_array() {
+                    Some(tags) => {
+                        for tag in tags {
+                            if !tag
+                                .as_str()
+                                .is_some_and(|tag| defined.iter().any(|id| id == tag))
+                            {
+                                violations.push(format!("{path}.tags: {tag} is not defined"));
+                            }
+                        }
+                    }
+                    None => violations.push(format!("{path}.tags: expected an array")),
+                }
+            }
+        }
+        violations
+    }
+
+    #[test]
+    fn test_token_list_validates_against_schema() {
+        let mut no_tags = get_info(None);
+        no_tags.tags.clear();
+        let entries = [
+            get_info(Some("https://ipfs.io/ipfs/logo")),
+            get_info(Some("")),
+            no_tags,
+        ];
+        for (chain_id, timestamp) in [(MAINNET_CHAIN_ID, 1_700_000_000), (DEVNET_CHAIN_ID, 0)] {
+            let list = to_token_list_at(&entries, chain_id, timestamp);
+            let json = serde_json::to_value(&list).unwrap();
+            assert_eq!(schema_violations(&json), Vec::<String>::new());
+        }
+        let empty = serde_json::to_value(to_token_list_at(&[], MAINNET_CHAIN_ID, 0)).unwrap();
+        assert_eq!(schema_violations(&empty), Vec::<String>::new());
+
+        // The checks catch the mistakes consumers reject
+        let list = to_token_list_at(&entries, MAINNET_CHAIN_ID, 0);
+        let mut json = serde_json::to_value(&list).unwrap();
+        json["tokens"][0]["logoURI"] = "".into();
+        json["tokens"][1]["address"] = "not-a-key".into();
+        json["tokens"][2]["tags"] = serde_json::json!(["undefined-tag"]);
+        json["tokens"][2]["decimals"] = 256.into();
+        json["timestamp"] = "1700000000".into();
+        json["version"]["patch"] = (-1).into();
+        json.as_object_mut().unwrap().remove("keywords");
+        assert_eq!(
+            schema_violations(&json),
+            [
+                "list: missing keywords",
+                "list.keywords: expected an array",
+                "list.timestamp: \"1700000000\" is not ISO 8601 UTC",
+                "list.version.patch: expected an integer >= 0",
+                "list.tokens[0].logoURI: expected a non-empty string",
+                "list.tokens[1].address: expected a base58 public key",
+                "list.tokens[2].decimals: expected an integer in 0..=255",
+                "list.tokens[2].tags: \"undefined-tag\" is not defined",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_fields_omitted() {
+        let mut info = get_info(Some(""));
+        info.tags.clear();
+        let list = to_token_list_at(&[info], DEVNET_CHAIN_ID, 0);
+        let json = serde_json::to_value(&list).unwrap();
+
+        let token = &json["tokens"][0];
+        assert_eq!(token["chainId"], 103);
+        assert!(token.get("logoURI").is_none());
+        assert!(token.get("tags").is_none());
+        assert!(list.tags.is_empty());
+        assert_eq!(list.timestamp, "1970-01-01T00:00:00.000Z");
+    }
+
+    /// Builds a metadata account holding the given fields, padded like the program pads them
+    fn metadata_account(mint: &Pubkey, name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+        fn padded(data: &mut Vec<u8>, value: &str, len: usize) {
+            let mut bytes = value.as_bytes().to_vec();
+            bytes.resize(bytes.len().max(len), 0);
+            data.extend((bytes.len() as u32).to_le_bytes());
+            data.extend(bytes);
+        }
+
+        // Key::MetadataV1, then the update authority and mint
+        let mut data = vec![4];
+        data.extend(Pubkey::new_unique().to_bytes());
+        data.extend(mint.to_bytes());
+        padded(&mut data, name, 32);
+        padded(&mut data, symbol, 10);
+        padded(&mut data, uri, 200);
+        // Seller fee, no creators, not sold, mutable, then every optional field unset
+        data.extend([0, 0, 0, 0, 1]);
+        data.extend([0; 6]);
+        data
+    }
+
+    fn mint_account() -> Vec<u8> {
+        use anchor_client::solana_sdk::program_option::COption;
+        use anchor_spl::token::spl_token::{solana_program::program_pack::Pack, state::Mint};
+
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::None,
+                supply: 1_000_000_000_000_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut data,
+        )
+        .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_hostile_metadata_sanitized() {
+        let mint = Pubkey::new_unique();
+        let name = "\u{1b}[2J\u{1b}[31mPump\0Token\u{202e}gnp.exe\n";
+        let symbol = "\u{ff30}UMP\u{200f}";
+        let metadata = metadata_account(&mint, name, symbol, "https://ipfs.io/ipfs/meta");
+
+        let info = TokenInfo::from_accounts(mint, &mint_account(), &metadata).unwrap();
+        assert_eq!(info.name, "PumpTokengnp.exe");
+        assert_eq!(info.symbol, "PUMP");
+        assert_eq!(info.raw_name, name);
+        assert_eq!(info.raw_symbol, symbol);
+        assert_eq!(info.uri.as_deref(), Some("https://ipfs.io/ipfs/meta"));
+
+        // Exports only carry the sanitized values
+        let list = to_token_list_at(&[info.clone()], MAINNET_CHAIN_ID, 0);
+        assert_eq!(list.tokens[0].name, "PumpTokengnp.exe");
+        assert_eq!(list.tokens[0].symbol, "PUMP");
+        let json = serde_json::to_string(&list).unwrap();
+        assert!(!json.contains("\\u001b") && !json.contains('\u{202e}'));
+
+        let info = info.with_sanitize_policy(&SanitizePolicy::new().with_max_chars(4));
+        assert_eq!(info.name, "Pump");
+    }
+
+    #[test]
+    fn test_metadata_padding_trimmed() {
+        assert_eq!(trim_padding("PUMP\0\0\0\0"), "PUMP");
+        assert_eq!(non_empty(trim_padding("\0\0\0")), None);
+        assert_eq!(iso8601(951_782_400), "2000-02-29T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_chain_ids() {
+        assert_eq!(chain_id(&Cluster::Mainnet), MAINNET_CHAIN_ID);
+        assert_eq!(chain_id(&Cluster::Testnet), TESTNET_CHAIN_ID);
+        assert_eq!(chain_id(&Cluster::Devnet), DEVNET_CHAIN_ID);
+        assert_eq!(chain_id(&Cluster::Localnet), DEVNET_CHAIN_ID);
+    }
+}
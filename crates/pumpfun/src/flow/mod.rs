@@ -0,0 +1,423 @@
+//! Rolling net SOL flow of the trade stream
+//!
+//! Net SOL inflow, buys minus sells, over a recent window is a common momentum signal. A
+//! [`NetFlowTracker`] is fed the trade events of a stream, such as a logs subscription, and
+//! answers [`NetFlowTracker::net_flow`] for any window up to the one it retains.
+//!
+//! Trades are kept in a ring buffer ordered by event timestamp. Trades older than the retained
+//! window are evicted as new ones arrive, and a running total answers the full retained window
+//! without summing it. After a reconnection, events may have been missed or may be
+//! replayed, so call [`NetFlowTracker::reset`] before feeding the new stream.
+//!
+//! - `NetFlowTracker`: Aggregates net SOL inflow over a rolling window.
+//!
+//! [`NetFlowTracker::volume`] answers the SOL traded in both directions over the same windows.
+//! [`NetFlowTracker::weighted_rate`] turns the retained trades into a net inflow rate that
+//! weighs recent trades more, so it follows a flow that speeds up or reverses sooner than the
+//! plain window total does.
+
+use crate::{
+    events::{self, TradeEvent},
+    utils::clock::{Clock, SystemClock},
+};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::VecDeque, sync::Arc, time::Duration};
+
+/// A trade's signed SOL flow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Flow {
+    /// Unix timestamp of the trade
+    timestamp: i64,
+    /// Lamports paid into the curve, negative for sells
+    lamports: i64,
+}
+
+/// Aggregates net SOL inflow over a rolling window of trades
+#[derive(Debug, Clone)]
+pub struct NetFlowTracker {
+    /// Longest window answered, and how long trades are retained
+    max_window: Duration,
+    /// Only trades of this mint are counted, if set
+    mint: Option<Pubkey>,
+    /// Retained trades, oldest first
+    flows: VecDeque<Flow>,
+    /// Sum of the retained trades' flows
+    total: i64,
+    /// Source of the time windows end at
+    clock: Arc<dyn Clock>,
+}
+
+impl NetFlowTracker {
+    /// Creates a tracker counting trades of every mint
+    ///
+    /// # Arguments
+    ///
+    /// * `max_window` - Longest window that will be queried; older trades are evicted
+    pub fn new(max_window: Duration) -> Self {
+        Self {
+            max_window,
+            mint: None,
+            flows: VecDeque::new(),
+            total: 0,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Restricts the tracker to the trades of a single mint
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to count
+    ///
+    /// # Returns
+    ///
+    /// Returns the tracker ignoring trades of other mints
+    pub fn with_mint(mut self, mint: Pubkey) -> Self {
+        self.mint = Some(mint);
+        self
+    }
+
+    /// Overrides the clock that [`NetFlowTracker::net_flow`] reads the current time from
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Clock to read the current time from
+    ///
+    /// # Returns
+    ///
+    /// Returns the tracker reading time from the provided clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Adds a trade to the window
+    ///
+    /// Trades of other mints are ignored if the tracker is restricted to one. Trades arriving
+    /// out of order are placed by their timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Trade event from the stream
+    pub fn record(&mut self, event: &TradeEvent) {
+        if self.mint.is_some_and(|mint| mint != event.mint) {
+            return;
+        }
+
+        let magnitude = i64::try_from(event.sol_amount).unwrap_or(i64::MAX);
+        let flow = Flow {
+            timestamp: event.timestamp,
+            lamports: if event.is_buy { magnitude } else { -magnitude },
+        };
+        let position = self
+            .flows
+            .iter()
+            .rposition(|retained| retained.timestamp <= flow.timestamp)
+            .map_or(0, |index| index + 1);
+        self.flows.insert(position, flow);
+        self.total = self.total.saturating_add(flow.lamports);
+
+        let newest = self
+            .flows
+            .back()
+            .map_or(flow.timestamp, |flow| flow.timestamp);
+        self.evict_before(newest.saturating_sub(self.max_window.as_secs() as i64));
+    }
+
+    /// Adds every trade found in a transaction's logs
+    ///
+    /// # Arguments
+    ///
+    /// * `logs` - Log messages of a successful transaction
+    pub fn record_logs(&mut self, logs: &[String]) {
+        for event in events::parse_logs::<TradeEvent>(logs) {
+            self.record(&event);
+        }
+    }
+
+    /// Gets the net SOL inflow over the most recent window
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window ending now; capped at the tracker's maximum window
+    ///
+    /// # Returns
+    ///
+    /// Returns the lamports bought minus the lamports sold over the window
+    pub fn net_flow(&self, window: Duration) -> i64 {
+        self.net_flow_at(window, self.clock.unix_timestamp())
+    }
+
+    /// Gets the net SOL inflow over a window ending at a given time
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window ending at `now`; capped at the tracker's maximum window
+    /// * `now` - Unix timestamp the window ends at
+    ///
+    /// # Returns
+    ///
+    /// Returns the lamports bought minus the lamports sold in `(now - window, now]`
+    pub fn net_flow_at(&self, window: Duration, now: i64) -> i64 {
+        let window = window.min(self.max_window);
+        // The retained trades are exactly the maximum window ending at the newest trade
+        let newest = self.flows.back().map_or(now, |flow| flow.timestamp);
+        if window == self.max_window && newest == now {
+            return self.total;
+        }
+
+        let start = now.saturating_sub(window.as_secs() as i64);
+        self.flows
+            .iter()
+            .rev()
+            .take_while(|flow| flow.timestamp > start)
+            .filter(|flow| flow.timestamp <= now)
+            .map(|flow| flow.lamports)
+            .sum()
+    }
+
+    /// Gets the SOL traded in both directions over the most recent window
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window ending now; capped at the tracker's maximum window
+    ///
+    /// # Returns
+    ///
+    /// Returns the lamports bought plus the lamports sold over the window
+    pub fn volume(&self, window: Duration) -> u64 {
+        self.volume_at(window, self.clock.unix_timestamp())
+    }
+
+    /// Gets the SOL traded in both directions over a window ending at a given time
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - Window ending at `now`; capped at the tracker's maximum window
+    /// * `now` - Unix timestamp the window ends at
+    ///
+    /// # Returns
+    ///
+    /// Returns the lamports bought plus the lamports sold in `(now - window, now]`
+    pub fn volume_at(&self, window: Duration, now: i64) -> u64 {
+        let start = now.saturating_sub(window.min(self.max_window).as_secs() as i64);
+        self.flows
+            .iter()
+            .rev()
+            .take_while(|flow| flow.timestamp > start)
+            .filter(|flow| flow.timestamp <= now)
+            .fold(0u64, |volume, flow| {
+                volume.saturating_add(flow.lamports.unsigned_abs())
+            })
+    }
+
+    /// Gets the exponentially weighted net SOL inflow rate over the retained window
+    ///
+    /// # Arguments
+    ///
+    /// * `half_life` - Age at which a trade counts for half as much as one made now
+    ///
+    /// # Returns
+    ///
+    /// Returns the net inflow rate in lamports per second, negative while sells dominate
+    pub fn weighted_rate(&self, half_life: Duration) -> f64 {
+        self.weighted_rate_at(half_life, self.clock.unix_timestamp())
+    }
+
+    /// Gets the exponentially weighted net SOL inflow rate over the window ending at a given time
+    ///
+    /// Each trade's flow is weighted by `exp(-age / tau)`, with `tau = half_life / ln 2`, and
+    /// the weighted sum divided by the integral of the weights over the retained window, so a
+    /// steady flow of `r` lamports per second yields `r` whatever the half-life.
+    ///
+    /// # Arguments
+    ///
+    /// * `half_life` - Age at which a trade counts for half as much as one made at `now`
+    /// * `now` - Unix timestamp the window ends at
+    ///
+    /// # Returns
+    ///
+    /// Returns the net inflow rate in lamports per second, or 0 if the half-life or the
+    /// retained window is zero
+    pub fn weighted_rate_at(&self, half_life: Duration, now: i64) -> f64 {
+        if half_life.is_zero() || self.max_window.is_zero() {
+            return 0.0;
+        }
+        let tau = half_life.as_secs_f64() / std::f64::consts::LN_2;
+        let start = now.saturating_sub(self.max_window.as_secs() as i64);
+
+        let weighted: f64 = self
+            .flows
+            .iter()
+            .rev()
+            .take_while(|flow| flow.timestamp > start)
+            .filter(|flow| flow.timestamp <= now)
+            .map(|flow| flow.lamports as f64 * (-((now - flow.timestamp) as f64) / tau).exp())
+            .sum();
+        let weights = tau * (1.0 - (-self.max_window.as_secs_f64() / tau).exp());
+        weighted / weights
+    }
+
+    /// Returns the mint the tracker is restricted to, if any
+    pub fn mint(&self) -> Option<Pubkey> {
+        self.mint
+    }
+
+    /// Returns the longest window answered
+    pub fn max_window(&self) -> Duration {
+        self.max_window
+    }
+
+    /// Returns the number of retained trades
+    pub fn len(&self) -> usize {
+        self.flows.len()
+    }
+
+    /// Returns whether no trades are retained
+    pub fn is_empty(&self) -> bool {
+        self.flows.is_empty()
+    }
+
+    /// Clears every retained trade
+    ///
+    /// Call this after the stream reconnects, since trades may have been missed or may be
+    /// delivered again.
+    pub fn reset(&mut self) {
+        self.flows.clear();
+        self.total = 0;
+    }
+
+    /// Evicts trades at or before a timestamp
+    fn evict_before(&mut self, cutoff: i64) {
+        while let Some(flow) = self.flows.front() {
+            if flow.timestamp > cutoff {
+                break;
+            }
+            self.total = self.total.saturating_sub(flow.lamports);
+            self.flows.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::clock::MockClock;
+
+    fn get_event(mint: Pubkey, is_buy: bool, sol_amount: u64, timestamp: i64) -> TradeEvent {
+        TradeEvent {
+            mint,
+            sol_amount,
+            token_amount: 1_000,
+            is_buy,
+            user: Pubkey::new_unique(),
+            timestamp,
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+        }
+    }
+
+    #[test]
+    fn test_net_flow_over_windows() {
+        let mint = Pubkey::new_unique();
+        let mut tracker = NetFlowTracker::new(Duration::from_secs(300)).with_mint(mint);
+
+        tracker.record(&get_event(mint, true, 5_000, 1_000));
+        tracker.record(&get_event(mint, false, 2_000, 1_100));
+        tracker.record(&get_event(mint, true, 1_000, 1_200));
+        // Out-of-order and foreign trades
+        tracker.record(&get_event(mint, true, 500, 1_150));
+        tracker.record(&get_event(Pubkey::new_unique(), true, 9_999, 1_200));
+
+        assert_eq!(tracker.len(), 4);
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(300), 1_200), 4_500);
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(60), 1_200), 1_500);
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(120), 1_200), -500);
+        // Windows beyond the retained one are capped
+        assert_eq!(
+            tracker.net_flow_at(Duration::from_secs(3_600), 1_200),
+            4_500
+        );
+        // Trades after the end of the window are not counted
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(300), 1_100), 3_000);
+
+        // Volume counts sells as well as buys
+        assert_eq!(tracker.volume_at(Duration::from_secs(300), 1_200), 8_500);
+        assert_eq!(tracker.volume_at(Duration::from_secs(60), 1_200), 1_500);
+        assert_eq!(tracker.volume_at(Duration::from_secs(300), 1_100), 7_000);
+    }
+
+    #[test]
+    fn test_old_trades_evicted() {
+        let mint = Pubkey::new_unique();
+        let mut tracker = NetFlowTracker::new(Duration::from_secs(60));
+
+        tracker.record(&get_event(mint, true, 5_000, 1_000));
+        tracker.record(&get_event(mint, true, 1_000, 1_030));
+        tracker.record(&get_event(mint, false, 3_000, 1_070));
+
+        assert_eq!(tracker.len(), 2);
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(60), 1_070), -2_000);
+        // Nothing in the window once the stream goes quiet
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(60), 2_000), 0);
+    }
+
+    #[test]
+    fn test_net_flow_follows_clock() {
+        let mint = Pubkey::new_unique();
+        let clock = Arc::new(MockClock::at_unix_timestamp(1_000));
+        let mut tracker = NetFlowTracker::new(Duration::from_secs(60)).with_clock(clock.clone());
+
+        tracker.record(&get_event(mint, true, 5_000, 990));
+        assert_eq!(tracker.net_flow(Duration::from_secs(60)), 5_000);
+
+        // The trade leaves the window as time passes without new trades
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(tracker.net_flow(Duration::from_secs(60)), 0);
+    }
+
+    #[test]
+    fn test_weighted_rate() {
+        let mint = Pubkey::new_unique();
+        let window = Duration::from_secs(300);
+        let half_life = Duration::from_secs(60);
+
+        // A steady 1_000 lamports per second is measured as such
+        let mut steady = NetFlowTracker::new(window);
+        for timestamp in 1_001..=1_300 {
+            steady.record(&get_event(mint, true, 1_000, timestamp));
+        }
+        let rate = steady.weighted_rate_at(half_life, 1_300);
+        assert!((rate - 1_000.0).abs() < 10.0, "{}", rate);
+
+        // The same total arriving late weighs more than when it arrived early
+        let mut late = NetFlowTracker::new(window);
+        late.record(&get_event(mint, true, 300_000, 1_290));
+        let mut early = NetFlowTracker::new(window);
+        early.record(&get_event(mint, true, 300_000, 1_010));
+        assert!(late.weighted_rate_at(half_life, 1_300) > rate);
+        assert!(early.weighted_rate_at(half_life, 1_300) < rate);
+
+        // Sells make the rate negative, and no window gives no rate
+        late.record(&get_event(mint, false, 600_000, 1_300));
+        assert!(late.weighted_rate_at(half_life, 1_300) < 0.0);
+        assert_eq!(late.weighted_rate_at(Duration::ZERO, 1_300), 0.0);
+        assert_eq!(late.max_window(), window);
+    }
+
+    #[test]
+    fn test_reset_clears_window() {
+        let mint = Pubkey::new_unique();
+        let mut tracker = NetFlowTracker::new(Duration::from_secs(60));
+        tracker.record(&get_event(mint, true, 5_000, 1_000));
+
+        tracker.reset();
+        assert!(tracker.is_empty());
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(60), 1_000), 0);
+
+        tracker.record(&get_event(mint, true, 1_000, 1_010));
+        assert_eq!(tracker.net_flow_at(Duration::from_secs(60), 1_010), 1_000);
+    }
+}
@@ -9,10 +9,21 @@
 //! - `create`: Instruction to create a new token with an associated bonding curve.
 //! - `buy`: Instruction to buy tokens from a bonding curve by providing SOL.
 //! - `sell`: Instruction to sell tokens back to the bonding curve in exchange for SOL.
+//! - `initialize`: Instruction to create the global account, for local forks of the program.
+//! - `set_params`: Instruction for the authority to set the global parameters, crate-private so
+//!   they can only be sent validated, through `testing::set_params`.
+//! - `memo`: SPL Memo instruction attaching a UTF-8 note to a transaction.
+//!
+//! Each builder has a `*_with_seeds` variant that derives the program's PDAs from a custom
+//! [`SeedConfig`] for derivation-compatible forks of the program, and a `*_with_token_program`
+//! variant that also takes the token program owning the mint.
+//!
+//! The builders produce the account set of the original program, which later upgrades extended.
+//! [`with_program_version`] converts a buy or sell to the account set of a [`ProgramVersion`].
 
-use crate::{constants, PumpFun};
+use crate::{constants, error::ClientError, mev::TradeInstruction, PumpFun, SeedConfig};
 use anchor_client::anchor_lang::InstructionData;
-use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
 use pumpfun_cpi as cpi;
 use solana_sdk::{
     instruction::{AccountMeta, Instruction},
@@ -21,6 +32,207 @@ use solana_sdk::{
     signer::Signer,
 };
 
+/// Derives a user's associated token account for a mint owned by the given token program
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the token account owner
+/// * `mint` - Public key of the token mint
+/// * `token_program` - Token program that owns the mint
+///
+/// # Returns
+///
+/// Returns the address of the owner's associated token account
+pub(crate) fn derive_user_ata(owner: &Pubkey, mint: &Pubkey, token_program: &Pubkey) -> Pubkey {
+    get_associated_token_address_with_program_id(owner, mint, token_program)
+}
+
+/// Derives a bonding curve's associated token account for a mint owned by the given token program
+///
+/// # Arguments
+///
+/// * `bonding_curve` - Public key of the bonding curve PDA
+/// * `mint` - Public key of the token mint
+/// * `token_program` - Token program that owns the mint
+///
+/// # Returns
+///
+/// Returns the address of the bonding curve's associated token account
+pub(crate) fn derive_curve_ata(
+    bonding_curve: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    get_associated_token_address_with_program_id(bonding_curve, mint, token_program)
+}
+
+/// Version of the Pump.fun program, which determines the accounts of buy and sell instructions
+///
+/// Each version keeps the accounts of the one before it, changing them as follows:
+///
+/// - `V1`: buy takes global, fee recipient, mint, bonding curve, curve token account, user token
+///   account, user, system program, token program, rent, event authority and program. Sell
+///   takes the associated token program in place of rent, before the token program.
+/// - `V2`: the creator vault replaces rent in buy and the associated token program in sell,
+///   keeping their positions.
+/// - `V3`: buy appends the global and the user's volume accumulators.
+/// - `V4`: buy and sell append the fee config and the fee program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum ProgramVersion {
+    /// Detected by the client from the deployed program's accounts
+    #[default]
+    Auto,
+    /// Original program, matching the IDL in the cpi crate
+    V1,
+    /// Creator fees, paid into a vault of the token's creator
+    V2,
+    /// Trading volume tracked per user
+    V3,
+    /// Fees configured by the fee program
+    V4,
+}
+
+impl ProgramVersion {
+    /// Whether buys and sells pay the creator vault
+    pub fn has_creator_vault(&self) -> bool {
+        matches!(self, Self::V2 | Self::V3 | Self::V4)
+    }
+
+    /// Whether buys update the volume accumulators
+    pub fn has_volume_accumulators(&self) -> bool {
+        matches!(self, Self::V3 | Self::V4)
+    }
+
+    /// Whether buys and sells read the fee config
+    pub fn has_fee_config(&self) -> bool {
+        matches!(self, Self::V4)
+    }
+}
+
+/// Derives the global volume accumulator PDA
+pub fn get_global_volume_accumulator_pda() -> Pubkey {
+    Pubkey::find_program_address(
+        &[constants::seeds::GLOBAL_VOLUME_ACCUMULATOR_SEED],
+        &constants::accounts::PUMPFUN,
+    )
+    .0
+}
+
+/// Derives a user's volume accumulator PDA
+///
+/// # Arguments
+///
+/// * `user` - Public key of the trading wallet
+pub fn get_user_volume_accumulator_pda(user: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            constants::seeds::USER_VOLUME_ACCUMULATOR_SEED,
+            user.as_ref(),
+        ],
+        &constants::accounts::PUMPFUN,
+    )
+    .0
+}
+
+/// Derives the fee config PDA of the Pump.fun program under the fee program
+pub fn get_fee_config_pda() -> Pubkey {
+    Pubkey::find_program_address(
+        &[
+            constants::seeds::FEE_CONFIG_SEED,
+            constants::accounts::PUMPFUN.as_ref(),
+        ],
+        &constants::accounts::FEE_PROGRAM,
+    )
+    .0
+}
+
+/// Creates an instruction to create the program's global account
+///
+/// The global account can only be created once, so the instruction fails on any cluster where
+/// the program is already initialized. The signer becomes the authority allowed to set the
+/// global parameters.
+///
+/// # Arguments
+///
+/// * `authority` - Keypair that pays for the global account and becomes its authority
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will create the global account
+pub fn initialize(authority: &Keypair) -> Instruction {
+    initialize_with_seeds(&SeedConfig::default(), authority)
+}
+
+/// Creates an instruction to create the program's global account using custom PDA seeds
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `authority` - Keypair that pays for the global account and becomes its authority
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will create the global account
+pub fn initialize_with_seeds(seeds: &SeedConfig, authority: &Keypair) -> Instruction {
+    Instruction::new_with_bytes(
+        constants::accounts::PUMPFUN,
+        &cpi::instruction::Initialize {}.data(),
+        vec![
+            AccountMeta::new(PumpFun::get_global_pda_with_seeds(seeds), false),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(constants::accounts::SYSTEM_PROGRAM, false),
+        ],
+    )
+}
+
+/// Creates an instruction to set the global parameters new bonding curves are created with
+///
+/// # Arguments
+///
+/// * `authority` - Keypair of the global account's authority
+/// * `args` - Set params instruction data containing the fee recipient, initial reserves, total
+///   supply and fee
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will update the global account
+#[cfg(test)]
+pub(crate) fn set_params(authority: &Keypair, args: cpi::instruction::SetParams) -> Instruction {
+    set_params_with_seeds(&SeedConfig::default(), authority, args)
+}
+
+/// Creates an instruction to set the global parameters using custom PDA seeds
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `authority` - Keypair of the global account's authority
+/// * `args` - Set params instruction data containing the fee recipient, initial reserves, total
+///   supply and fee
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will update the global account
+#[cfg(any(test, feature = "test-utils"))]
+pub(crate) fn set_params_with_seeds(
+    seeds: &SeedConfig,
+    authority: &Keypair,
+    args: cpi::instruction::SetParams,
+) -> Instruction {
+    Instruction::new_with_bytes(
+        constants::accounts::PUMPFUN,
+        &args.data(),
+        vec![
+            AccountMeta::new(PumpFun::get_global_pda_with_seeds(seeds), false),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(constants::accounts::SYSTEM_PROGRAM, false),
+            AccountMeta::new_readonly(constants::accounts::EVENT_AUTHORITY, false),
+            AccountMeta::new_readonly(constants::accounts::PUMPFUN, false),
+        ],
+    )
+}
+
 /// Creates an instruction to create a new token with bonding curve
 ///
 /// Creates a new SPL token with an associated bonding curve that determines its price.
@@ -35,24 +247,78 @@ use solana_sdk::{
 ///
 /// Returns a Solana instruction that when executed will create the token and its accounts
 pub fn create(payer: &Keypair, mint: &Keypair, args: cpi::instruction::Create) -> Instruction {
-    let bonding_curve: Pubkey = PumpFun::get_bonding_curve_pda(&mint.pubkey()).unwrap();
+    create_with_seeds(&SeedConfig::default(), payer, mint, args)
+}
+
+/// Creates an instruction to create a new token with bonding curve using custom PDA seeds
+///
+/// The mint is assumed to be owned by the classic token program, see
+/// [`create_with_token_program`] for Token-2022 mints.
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `payer` - Keypair that will pay for account creation and transaction fees
+/// * `mint` - Keypair for the new token mint account that will be created
+/// * `args` - Create instruction data containing token name, symbol and metadata URI
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will create the token and its accounts
+pub fn create_with_seeds(
+    seeds: &SeedConfig,
+    payer: &Keypair,
+    mint: &Keypair,
+    args: cpi::instruction::Create,
+) -> Instruction {
+    create_with_token_program(
+        seeds,
+        &constants::accounts::TOKEN_PROGRAM,
+        payer,
+        mint,
+        args,
+    )
+}
+
+/// Creates an instruction to create a new token with bonding curve for a mint of any token program
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `token_program` - Token program that owns the mint
+/// * `payer` - Keypair that will pay for account creation and transaction fees
+/// * `mint` - Keypair for the new token mint account that will be created
+/// * `args` - Create instruction data containing token name, symbol and metadata URI
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will create the token and its accounts
+pub fn create_with_token_program(
+    seeds: &SeedConfig,
+    token_program: &Pubkey,
+    payer: &Keypair,
+    mint: &Keypair,
+    args: cpi::instruction::Create,
+) -> Instruction {
+    let bonding_curve: Pubkey =
+        PumpFun::get_bonding_curve_pda_with_seeds(seeds, &mint.pubkey()).unwrap();
     Instruction::new_with_bytes(
         constants::accounts::PUMPFUN,
         &args.data(),
         vec![
             AccountMeta::new(mint.pubkey(), true),
-            AccountMeta::new(PumpFun::get_mint_authority_pda(), false),
+            AccountMeta::new(PumpFun::get_mint_authority_pda_with_seeds(seeds), false),
             AccountMeta::new(bonding_curve, false),
             AccountMeta::new(
-                get_associated_token_address(&bonding_curve, &mint.pubkey()),
+                derive_curve_ata(&bonding_curve, &mint.pubkey(), token_program),
                 false,
             ),
-            AccountMeta::new_readonly(PumpFun::get_global_pda(), false),
+            AccountMeta::new_readonly(PumpFun::get_global_pda_with_seeds(seeds), false),
             AccountMeta::new_readonly(constants::accounts::MPL_TOKEN_METADATA, false),
             AccountMeta::new(PumpFun::get_metadata_pda(&mint.pubkey()), false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(constants::accounts::SYSTEM_PROGRAM, false),
-            AccountMeta::new_readonly(constants::accounts::TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(*token_program, false),
             AccountMeta::new_readonly(constants::accounts::ASSOCIATED_TOKEN_PROGRAM, false),
             AccountMeta::new_readonly(constants::accounts::RENT, false),
             AccountMeta::new_readonly(constants::accounts::EVENT_AUTHORITY, false),
@@ -83,20 +349,78 @@ pub fn buy(
     fee_recipient: &Pubkey,
     args: cpi::instruction::Buy,
 ) -> Instruction {
-    let bonding_curve: Pubkey = PumpFun::get_bonding_curve_pda(mint).unwrap();
+    buy_with_seeds(&SeedConfig::default(), payer, mint, fee_recipient, args)
+}
+
+/// Creates an instruction to buy tokens from a bonding curve using custom PDA seeds
+///
+/// The mint is assumed to be owned by the classic token program, see
+/// [`buy_with_token_program`] for Token-2022 mints.
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `payer` - Keypair that will provide the SOL to buy tokens
+/// * `mint` - Public key of the token mint to buy
+/// * `fee_recipient` - Public key of the account that will receive the transaction fee
+/// * `args` - Buy instruction data containing the SOL amount and maximum acceptable token price
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will buy tokens from the bonding curve
+pub fn buy_with_seeds(
+    seeds: &SeedConfig,
+    payer: &Keypair,
+    mint: &Pubkey,
+    fee_recipient: &Pubkey,
+    args: cpi::instruction::Buy,
+) -> Instruction {
+    buy_with_token_program(
+        seeds,
+        &constants::accounts::TOKEN_PROGRAM,
+        payer,
+        mint,
+        fee_recipient,
+        args,
+    )
+}
+
+/// Creates an instruction to buy tokens from a bonding curve for a mint of any token program
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `token_program` - Token program that owns the mint
+/// * `payer` - Keypair that will provide the SOL to buy tokens
+/// * `mint` - Public key of the token mint to buy
+/// * `fee_recipient` - Public key of the account that will receive the transaction fee
+/// * `args` - Buy instruction data containing the SOL amount and maximum acceptable token price
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will buy tokens from the bonding curve
+pub fn buy_with_token_program(
+    seeds: &SeedConfig,
+    token_program: &Pubkey,
+    payer: &Keypair,
+    mint: &Pubkey,
+    fee_recipient: &Pubkey,
+    args: cpi::instruction::Buy,
+) -> Instruction {
+    let bonding_curve: Pubkey = PumpFun::get_bonding_curve_pda_with_seeds(seeds, mint).unwrap();
     Instruction::new_with_bytes(
         constants::accounts::PUMPFUN,
         &args.data(),
         vec![
-            AccountMeta::new_readonly(PumpFun::get_global_pda(), false),
+            AccountMeta::new_readonly(PumpFun::get_global_pda_with_seeds(seeds), false),
             AccountMeta::new(*fee_recipient, false),
             AccountMeta::new_readonly(*mint, false),
             AccountMeta::new(bonding_curve, false),
-            AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
-            AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+            AccountMeta::new(derive_curve_ata(&bonding_curve, mint, token_program), false),
+            AccountMeta::new(derive_user_ata(&payer.pubkey(), mint, token_program), false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(constants::accounts::SYSTEM_PROGRAM, false),
-            AccountMeta::new_readonly(constants::accounts::TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(*token_program, false),
             AccountMeta::new_readonly(constants::accounts::RENT, false),
             AccountMeta::new_readonly(constants::accounts::EVENT_AUTHORITY, false),
             AccountMeta::new_readonly(constants::accounts::PUMPFUN, false),
@@ -126,23 +450,777 @@ pub fn sell(
     fee_recipient: &Pubkey,
     args: cpi::instruction::Sell,
 ) -> Instruction {
-    let bonding_curve: Pubkey = PumpFun::get_bonding_curve_pda(mint).unwrap();
+    sell_with_seeds(&SeedConfig::default(), payer, mint, fee_recipient, args)
+}
+
+/// Creates an instruction to sell tokens back to a bonding curve using custom PDA seeds
+///
+/// The mint is assumed to be owned by the classic token program, see
+/// [`sell_with_token_program`] for Token-2022 mints.
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `payer` - Keypair that owns the tokens to sell
+/// * `mint` - Public key of the token mint to sell
+/// * `fee_recipient` - Public key of the account that will receive the transaction fee
+/// * `args` - Sell instruction data containing token amount and minimum acceptable SOL output
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will sell tokens to the bonding curve
+pub fn sell_with_seeds(
+    seeds: &SeedConfig,
+    payer: &Keypair,
+    mint: &Pubkey,
+    fee_recipient: &Pubkey,
+    args: cpi::instruction::Sell,
+) -> Instruction {
+    sell_with_token_program(
+        seeds,
+        &constants::accounts::TOKEN_PROGRAM,
+        payer,
+        mint,
+        fee_recipient,
+        args,
+    )
+}
+
+/// Creates an instruction to sell tokens back to a bonding curve for a mint of any token program
+///
+/// # Arguments
+///
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `token_program` - Token program that owns the mint
+/// * `payer` - Keypair that owns the tokens to sell
+/// * `mint` - Public key of the token mint to sell
+/// * `fee_recipient` - Public key of the account that will receive the transaction fee
+/// * `args` - Sell instruction data containing token amount and minimum acceptable SOL output
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed will sell tokens to the bonding curve
+pub fn sell_with_token_program(
+    seeds: &SeedConfig,
+    token_program: &Pubkey,
+    payer: &Keypair,
+    mint: &Pubkey,
+    fee_recipient: &Pubkey,
+    args: cpi::instruction::Sell,
+) -> Instruction {
+    let bonding_curve: Pubkey = PumpFun::get_bonding_curve_pda_with_seeds(seeds, mint).unwrap();
     Instruction::new_with_bytes(
         constants::accounts::PUMPFUN,
         &args.data(),
         vec![
-            AccountMeta::new_readonly(PumpFun::get_global_pda(), false),
+            AccountMeta::new_readonly(PumpFun::get_global_pda_with_seeds(seeds), false),
             AccountMeta::new(*fee_recipient, false),
             AccountMeta::new_readonly(*mint, false),
             AccountMeta::new(bonding_curve, false),
-            AccountMeta::new(get_associated_token_address(&bonding_curve, mint), false),
-            AccountMeta::new(get_associated_token_address(&payer.pubkey(), mint), false),
+            AccountMeta::new(derive_curve_ata(&bonding_curve, mint, token_program), false),
+            AccountMeta::new(derive_user_ata(&payer.pubkey(), mint, token_program), false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(constants::accounts::SYSTEM_PROGRAM, false),
             AccountMeta::new_readonly(constants::accounts::ASSOCIATED_TOKEN_PROGRAM, false),
-            AccountMeta::new_readonly(constants::accounts::TOKEN_PROGRAM, false),
+            AccountMeta::new_readonly(*token_program, false),
             AccountMeta::new_readonly(constants::accounts::EVENT_AUTHORITY, false),
             AccountMeta::new_readonly(constants::accounts::PUMPFUN, false),
         ],
     )
 }
+
+/// Converts a buy or sell instruction to the account set of a program version
+///
+/// Instructions from [`buy_with_token_program`] and [`sell_with_token_program`] have the `V1`
+/// account set.
+/// `Auto` must be resolved by the caller, see [`PumpFun::get_program_version`], and like `V1`
+/// leaves the instruction unchanged, as it does any instruction that is not a buy or sell.
+///
+/// # Arguments
+///
+/// * `instruction` - Buy or sell instruction with the `V1` account set
+/// * `version` - Program version to convert to
+/// * `seeds` - Seed configuration used to derive the program's PDAs
+/// * `creator` - Creator of the token, whose vault receives the creator fee
+///
+/// # Returns
+///
+/// Returns the instruction with the accounts of the given version, or a
+/// ClientError::InvalidInput if a buy or sell lacks the accounts of the `V1` set
+pub fn with_program_version(
+    mut instruction: Instruction,
+    version: ProgramVersion,
+    seeds: &SeedConfig,
+    creator: &Pubkey,
+) -> Result<Instruction, ClientError> {
+    const MISSING_ACCOUNTS: ClientError =
+        ClientError::InvalidInput("Trade instruction lacks the accounts of the original program");
+
+    let Some(trade) = TradeInstruction::decode(&instruction.data) else {
+        return Ok(instruction);
+    };
+    let user = instruction.accounts.get(6).ok_or(MISSING_ACCOUNTS)?.pubkey;
+
+    if version.has_creator_vault() {
+        let creator_vault =
+            PumpFun::get_creator_vault_pda_with_seeds(seeds, creator).unwrap_or_default();
+        // Rent in buy, after the token program, and the associated token program in sell,
+        // before it
+        let replaced = if trade.is_buy() { 9 } else { 8 };
+        *instruction
+            .accounts
+            .get_mut(replaced)
+            .ok_or(MISSING_ACCOUNTS)? = AccountMeta::new(creator_vault, false);
+    }
+    if version.has_volume_accumulators() && trade.is_buy() {
+        instruction.accounts.extend([
+            AccountMeta::new(get_global_volume_accumulator_pda(), false),
+            AccountMeta::new(get_user_volume_accumulator_pda(&user), false),
+        ]);
+    }
+    if version.has_fee_config() {
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(get_fee_config_pda(), false),
+            AccountMeta::new_readonly(constants::accounts::FEE_PROGRAM, false),
+        ]);
+    }
+
+    Ok(instruction)
+}
+
+/// Creates an SPL Memo instruction carrying a UTF-8 note
+///
+/// The memo takes no signers, so it only records the text in the transaction. Decoders find it
+/// again with [`crate::utils::extract_memos`].
+///
+/// # Arguments
+///
+/// * `text` - Note to attach to the transaction
+///
+/// # Returns
+///
+/// Returns a Solana instruction that when executed logs the note
+pub fn memo(text: &str) -> Instruction {
+    Instruction::new_with_bytes(constants::accounts::MEMO_PROGRAM, text.as_bytes(), vec![])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::associated_token::{
+        get_associated_token_address, spl_associated_token_account,
+    };
+    use borsh::BorshSerialize;
+    use serde_json::Value;
+    use std::{collections::BTreeSet, str::FromStr};
+
+    /// Program IDL checked into the cpi crate
+    const IDL: &str = include_str!("../../../pumpfun-cpi/idl.json");
+
+    /// Legacy IDL the cpi crate's encoders are generated from
+    const CLASSIC_IDL: &str = include_str!("../../../pumpfun-cpi/idl.classic.json");
+
+    /// Representative argument value for IDL-driven encoding
+    enum Arg {
+        U64(u64),
+        String(String),
+        Pubkey(Pubkey),
+    }
+
+    fn snake_case(name: &str) -> String {
+        let mut out = String::new();
+        for c in name.chars() {
+            if c.is_ascii_uppercase() {
+                out.push('_');
+                out.push(c.to_ascii_lowercase());
+            } else {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    fn idl() -> Value {
+        serde_json::from_str(IDL).unwrap()
+    }
+
+    fn idl_instruction<'a>(idl: &'a Value, name: &str) -> &'a Value {
+        idl["instructions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|ix| ix["name"] == name)
+            .unwrap_or_else(|| panic!("instruction `{}` missing from IDL", name))
+    }
+
+    /// Encodes instruction data from the IDL's discriminator and argument types
+    fn encode_from_idl(idl: &Value, name: &str, args: &[(&str, Arg)]) -> Vec<u8> {
+        let ix = idl_instruction(idl, name);
+        let mut data: Vec<u8> = ix["discriminator"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|byte| byte.as_u64().unwrap() as u8)
+            .collect();
+
+        let idl_args = ix["args"].as_array().unwrap();
+        assert_eq!(idl_args.len(), args.len(), "argument count for `{}`", name);
+        for (idl_arg, (arg_name, value)) in idl_args.iter().zip(args) {
+            assert_eq!(idl_arg["name"], *arg_name, "argument order for `{}`", name);
+            match (idl_arg["type"].as_str().unwrap(), value) {
+                ("u64", Arg::U64(v)) => v.serialize(&mut data).unwrap(),
+                ("string", Arg::String(v)) => v.serialize(&mut data).unwrap(),
+                ("pubkey", Arg::Pubkey(v)) => data.extend_from_slice(v.as_ref()),
+                (ty, _) => panic!("unexpected type `{}` for `{}`", ty, arg_name),
+            }
+        }
+
+        data
+    }
+
+    /// Resolves the expected account list of an instruction from the IDL
+    ///
+    /// Fixed addresses and PDAs are derived from the IDL itself; the remaining accounts are
+    /// looked up by name in `named`.
+    fn accounts_from_idl(
+        idl: &Value,
+        name: &str,
+        named: &[(&str, Pubkey)],
+    ) -> Vec<(String, Pubkey, bool, bool)> {
+        let lookup = |account: &str| -> Pubkey {
+            named
+                .iter()
+                .find(|(n, _)| *n == account)
+                .unwrap_or_else(|| panic!("no pubkey for account `{}`", account))
+                .1
+        };
+
+        idl_instruction(idl, name)["accounts"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|account| {
+                let account_name = snake_case(account["name"].as_str().unwrap());
+                let pubkey = if let Some(address) = account["address"].as_str() {
+                    Pubkey::from_str(address).unwrap()
+                } else if let Some(seeds) = account["pda"]["seeds"].as_array() {
+                    let seeds: Vec<Vec<u8>> = seeds
+                        .iter()
+                        .map(|seed| match seed["kind"].as_str().unwrap() {
+                            "const" => seed["value"]
+                                .as_array()
+                                .unwrap()
+                                .iter()
+                                .map(|byte| byte.as_u64().unwrap() as u8)
+                                .collect(),
+                            "account" => lookup(seed["path"].as_str().unwrap()).to_bytes().to_vec(),
+                            kind => panic!("unexpected seed kind `{}`", kind),
+                        })
+                        .collect();
+                    let seeds: Vec<&[u8]> = seeds.iter().map(Vec::as_slice).collect();
+                    Pubkey::find_program_address(&seeds, &cpi::ID).0
+                } else {
+                    lookup(&account_name)
+                };
+                let writable = account["writable"].as_bool().unwrap_or(false)
+                    || account["isMut"].as_bool().unwrap_or(false);
+                let signer = account["signer"].as_bool().unwrap_or(false)
+                    || account["isSigner"].as_bool().unwrap_or(false);
+                (account_name, pubkey, writable, signer)
+            })
+            .collect()
+    }
+
+    fn assert_accounts_match(
+        name: &str,
+        ix: &Instruction,
+        expected: &[(String, Pubkey, bool, bool)],
+    ) {
+        assert_eq!(
+            ix.accounts.len(),
+            expected.len(),
+            "account count for `{}`",
+            name
+        );
+        for (meta, (account, pubkey, writable, signer)) in ix.accounts.iter().zip(expected) {
+            assert_eq!(meta.pubkey, *pubkey, "`{}` account `{}`", name, account);
+            assert_eq!(
+                meta.is_signer, *signer,
+                "`{}` signer flag of `{}`",
+                name, account
+            );
+            assert!(
+                meta.is_writable || !writable,
+                "`{}` account `{}` must be writable",
+                name,
+                account
+            );
+        }
+    }
+
+    /// Instructions whose discriminator in the vendored IDL is known to be wrong, with the
+    /// preimage it was hashed from
+    ///
+    /// The IDL is kept as it was vendored. Its `setParams` discriminator hashes the camelCase
+    /// name, while Anchor dispatches on the hash of the snake_case name, which the encoders use.
+    /// `test_idl_errata_still_apply` fails once a refreshed IDL no longer has the error, so the
+    /// entry is dropped with it.
+    const IDL_ERRATA: &[(&str, &str)] = &[("setParams", "global:setParams")];
+
+    /// Anchor discriminator of an instruction, hashed from the preimage
+    fn sighash(preimage: &str) -> [u8; 8] {
+        solana_sdk::hash::hash(preimage.as_bytes()).to_bytes()[..8]
+            .try_into()
+            .unwrap()
+    }
+
+    /// Encodes an instruction with the crate's encoder and representative arguments
+    ///
+    /// # Returns
+    /// The encoded data and the arguments it was encoded with, named as in the IDL, or None if
+    /// the crate has no encoder for the instruction
+    fn encode_sample(name: &str) -> Option<(Vec<u8>, Vec<(&'static str, Arg)>)> {
+        let fee_recipient = Pubkey::new_unique();
+        let sample = match name {
+            "initialize" => (cpi::instruction::Initialize {}.data(), vec![]),
+            "set_params" => (
+                cpi::instruction::SetParams {
+                    _fee_recipient: fee_recipient,
+                    _initial_virtual_token_reserves: 1_073_000_000_000_000,
+                    _initial_virtual_sol_reserves: 30_000_000_000,
+                    _initial_real_token_reserves: 793_100_000_000_000,
+                    _token_total_supply: 1_000_000_000_000_000,
+                    _fee_basis_points: 100,
+                }
+                .data(),
+                vec![
+                    ("feeRecipient", Arg::Pubkey(fee_recipient)),
+                    (
+                        "initialVirtualTokenReserves",
+                        Arg::U64(1_073_000_000_000_000),
+                    ),
+                    ("initialVirtualSolReserves", Arg::U64(30_000_000_000)),
+                    ("initialRealTokenReserves", Arg::U64(793_100_000_000_000)),
+                    ("tokenTotalSupply", Arg::U64(1_000_000_000_000_000)),
+                    ("feeBasisPoints", Arg::U64(100)),
+                ],
+            ),
+            "create" => (
+                cpi::instruction::Create {
+                    _name: "Lorem ipsum".to_string(),
+                    _symbol: "LIP".to_string(),
+                    _uri: "https://example.com/metadata.json".to_string(),
+                }
+                .data(),
+                vec![
+                    ("name", Arg::String("Lorem ipsum".to_string())),
+                    ("symbol", Arg::String("LIP".to_string())),
+                    (
+                        "uri",
+                        Arg::String("https://example.com/metadata.json".to_string()),
+                    ),
+                ],
+            ),
+            "buy" => (
+                cpi::instruction::Buy {
+                    _amount: 1_000_000,
+                    _max_sol_cost: 1_050_000_000,
+                }
+                .data(),
+                vec![
+                    ("amount", Arg::U64(1_000_000)),
+                    ("maxSolCost", Arg::U64(1_050_000_000)),
+                ],
+            ),
+            "sell" => (
+                cpi::instruction::Sell {
+                    _amount: 1_000_000,
+                    _min_sol_output: 950_000_000,
+                }
+                .data(),
+                vec![
+                    ("amount", Arg::U64(1_000_000)),
+                    ("minSolOutput", Arg::U64(950_000_000)),
+                ],
+            ),
+            "withdraw" => (cpi::instruction::Withdraw {}.data(), vec![]),
+            _ => return None,
+        };
+        Some(sample)
+    }
+
+    #[test]
+    fn test_instruction_set_matches_idl() {
+        let names = |idl: &Value| -> BTreeSet<String> {
+            idl["instructions"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|ix| snake_case(ix["name"].as_str().unwrap()))
+                .collect()
+        };
+
+        // The encoders are generated from the classic IDL, so an instruction added on either
+        // side shows up as a difference between the two
+        let names_in_idl = names(&idl());
+        assert_eq!(
+            names(&serde_json::from_str(CLASSIC_IDL).unwrap()),
+            names_in_idl
+        );
+        for name in &names_in_idl {
+            assert!(
+                encode_sample(name).is_some(),
+                "instruction `{}` has no encoder",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_instruction_data_matches_idl() {
+        let idl = idl();
+
+        for ix in idl["instructions"].as_array().unwrap() {
+            let name = ix["name"].as_str().unwrap();
+            let (data, args) = encode_sample(&snake_case(name))
+                .unwrap_or_else(|| panic!("instruction `{}` has no encoder", name));
+            let mut expected = encode_from_idl(&idl, name, &args);
+            if IDL_ERRATA.iter().any(|(erratum, _)| *erratum == name) {
+                expected[..8].copy_from_slice(&sighash(&format!("global:{}", snake_case(name))));
+            }
+            assert_eq!(data, expected, "data of `{}`", name);
+        }
+    }
+
+    #[test]
+    fn test_idl_errata_still_apply() {
+        let idl = idl();
+
+        for (name, preimage) in IDL_ERRATA {
+            let discriminator: [u8; 8] =
+                serde_json::from_value(idl_instruction(&idl, name)["discriminator"].clone())
+                    .unwrap();
+            assert_eq!(discriminator, sighash(preimage), "erratum of `{}`", name);
+            assert_ne!(
+                discriminator,
+                sighash(&format!("global:{}", snake_case(name))),
+                "`{}` is fixed in the IDL, drop its erratum",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_instruction_accounts_match_idl() {
+        let idl = idl();
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        let fee_recipient = Pubkey::new_unique();
+        let bonding_curve = PumpFun::get_bonding_curve_pda(&mint.pubkey()).unwrap();
+        let named = [
+            ("mint", mint.pubkey()),
+            ("user", payer.pubkey()),
+            ("fee_recipient", fee_recipient),
+            (
+                "associated_bonding_curve",
+                get_associated_token_address(&bonding_curve, &mint.pubkey()),
+            ),
+            (
+                "associated_user",
+                get_associated_token_address(&payer.pubkey(), &mint.pubkey()),
+            ),
+            ("metadata", PumpFun::get_metadata_pda(&mint.pubkey())),
+        ];
+
+        let ix = create(
+            &payer,
+            &mint,
+            cpi::instruction::Create {
+                _name: String::new(),
+                _symbol: String::new(),
+                _uri: String::new(),
+            },
+        );
+        assert_accounts_match("create", &ix, &accounts_from_idl(&idl, "create", &named));
+
+        let ix = buy(
+            &payer,
+            &mint.pubkey(),
+            &fee_recipient,
+            cpi::instruction::Buy {
+                _amount: 0,
+                _max_sol_cost: 0,
+            },
+        );
+        assert_accounts_match("buy", &ix, &accounts_from_idl(&idl, "buy", &named));
+
+        let ix = sell(
+            &payer,
+            &mint.pubkey(),
+            &fee_recipient,
+            cpi::instruction::Sell {
+                _amount: 0,
+                _min_sol_output: 0,
+            },
+        );
+        assert_accounts_match("sell", &ix, &accounts_from_idl(&idl, "sell", &named));
+
+        let ix = initialize(&payer);
+        assert_accounts_match(
+            "initialize",
+            &ix,
+            &accounts_from_idl(&idl, "initialize", &named),
+        );
+
+        let ix = set_params(
+            &payer,
+            cpi::instruction::SetParams {
+                _fee_recipient: fee_recipient,
+                _initial_virtual_token_reserves: 0,
+                _initial_virtual_sol_reserves: 0,
+                _initial_real_token_reserves: 0,
+                _token_total_supply: 0,
+                _fee_basis_points: 0,
+            },
+        );
+        assert_accounts_match(
+            "set_params",
+            &ix,
+            &accounts_from_idl(&idl, "setParams", &named),
+        );
+    }
+
+    #[test]
+    fn test_derive_atas_for_both_token_programs() {
+        let owner = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let bonding_curve = PumpFun::get_bonding_curve_pda(&mint).unwrap();
+
+        for token_program in [
+            constants::accounts::TOKEN_PROGRAM,
+            constants::accounts::TOKEN_2022_PROGRAM,
+        ] {
+            // Reference derivation from the associated token account program's seeds
+            let expected = |wallet: &Pubkey| {
+                Pubkey::find_program_address(
+                    &[wallet.as_ref(), token_program.as_ref(), mint.as_ref()],
+                    &constants::accounts::ASSOCIATED_TOKEN_PROGRAM,
+                )
+                .0
+            };
+
+            assert_eq!(
+                derive_user_ata(&owner, &mint, &token_program),
+                spl_associated_token_account::get_associated_token_address_with_program_id(
+                    &owner,
+                    &mint,
+                    &token_program
+                )
+            );
+            assert_eq!(
+                derive_user_ata(&owner, &mint, &token_program),
+                expected(&owner)
+            );
+            assert_eq!(
+                derive_curve_ata(&bonding_curve, &mint, &token_program),
+                expected(&bonding_curve)
+            );
+        }
+
+        // The classic program matches the address derived without a program
+        assert_eq!(
+            derive_user_ata(&owner, &mint, &constants::accounts::TOKEN_PROGRAM),
+            get_associated_token_address(&owner, &mint)
+        );
+        assert_ne!(
+            derive_user_ata(&owner, &mint, &constants::accounts::TOKEN_2022_PROGRAM),
+            get_associated_token_address(&owner, &mint)
+        );
+    }
+
+    #[test]
+    fn test_builders_use_token_program() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let fee_recipient = Pubkey::new_unique();
+        let token_program = constants::accounts::TOKEN_2022_PROGRAM;
+        let bonding_curve = PumpFun::get_bonding_curve_pda(&mint).unwrap();
+
+        let ix = buy_with_token_program(
+            &SeedConfig::default(),
+            &token_program,
+            &payer,
+            &mint,
+            &fee_recipient,
+            cpi::instruction::Buy {
+                _amount: 0,
+                _max_sol_cost: 0,
+            },
+        );
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&derive_curve_ata(&bonding_curve, &mint, &token_program)));
+        assert!(keys.contains(&derive_user_ata(&payer.pubkey(), &mint, &token_program)));
+        assert!(keys.contains(&token_program));
+        assert!(!keys.contains(&constants::accounts::TOKEN_PROGRAM));
+
+        let ix = sell_with_token_program(
+            &SeedConfig::default(),
+            &token_program,
+            &payer,
+            &mint,
+            &fee_recipient,
+            cpi::instruction::Sell {
+                _amount: 0,
+                _min_sol_output: 0,
+            },
+        );
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&derive_user_ata(&payer.pubkey(), &mint, &token_program)));
+        assert!(keys.contains(&token_program));
+        assert!(!keys.contains(&constants::accounts::TOKEN_PROGRAM));
+
+        let mint = Keypair::new();
+        let create_args = || cpi::instruction::Create {
+            _name: "Lorem ipsum".to_string(),
+            _symbol: "LIP".to_string(),
+            _uri: "https://example.com/metadata.json".to_string(),
+        };
+        let ix = create_with_token_program(
+            &SeedConfig::default(),
+            &token_program,
+            &payer,
+            &mint,
+            create_args(),
+        );
+        let bonding_curve = PumpFun::get_bonding_curve_pda(&mint.pubkey()).unwrap();
+        let keys: Vec<Pubkey> = ix.accounts.iter().map(|meta| meta.pubkey).collect();
+        assert!(keys.contains(&derive_curve_ata(
+            &bonding_curve,
+            &mint.pubkey(),
+            &token_program
+        )));
+        assert!(keys.contains(&token_program));
+        assert!(!keys.contains(&constants::accounts::TOKEN_PROGRAM));
+
+        // The seeded builders keep their signature and build for the classic program
+        let seeds = SeedConfig::default();
+        let classic = constants::accounts::TOKEN_PROGRAM;
+        assert_eq!(
+            create_with_seeds(&seeds, &payer, &mint, create_args()),
+            create_with_token_program(&seeds, &classic, &payer, &mint, create_args())
+        );
+        let mint = mint.pubkey();
+        let buy_args = || cpi::instruction::Buy {
+            _amount: 1,
+            _max_sol_cost: 2,
+        };
+        assert_eq!(
+            buy_with_seeds(&seeds, &payer, &mint, &fee_recipient, buy_args()),
+            buy_with_token_program(&seeds, &classic, &payer, &mint, &fee_recipient, buy_args())
+        );
+        let sell_args = || cpi::instruction::Sell {
+            _amount: 1,
+            _min_sol_output: 2,
+        };
+        assert_eq!(
+            sell_with_seeds(&seeds, &payer, &mint, &fee_recipient, sell_args()),
+            sell_with_token_program(&seeds, &classic, &payer, &mint, &fee_recipient, sell_args())
+        );
+    }
+
+    #[test]
+    fn test_with_program_version() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let seeds = SeedConfig::default();
+        let creator_vault = PumpFun::get_creator_vault_pda(&creator).unwrap();
+        let keys = |ix: &Instruction| -> Vec<Pubkey> {
+            ix.accounts.iter().map(|meta| meta.pubkey).collect()
+        };
+        let buy_ix = buy(
+            &payer,
+            &mint,
+            &Pubkey::new_unique(),
+            cpi::instruction::Buy {
+                _amount: 0,
+                _max_sol_cost: 0,
+            },
+        );
+        let sell_ix = sell(
+            &payer,
+            &mint,
+            &Pubkey::new_unique(),
+            cpi::instruction::Sell {
+                _amount: 0,
+                _min_sol_output: 0,
+            },
+        );
+
+        for version in [ProgramVersion::Auto, ProgramVersion::V1] {
+            assert_eq!(
+                with_program_version(buy_ix.clone(), version, &seeds, &creator).unwrap(),
+                buy_ix
+            );
+        }
+
+        let v2 =
+            with_program_version(buy_ix.clone(), ProgramVersion::V2, &seeds, &creator).unwrap();
+        assert_eq!(v2.accounts.len(), 12);
+        assert_eq!(v2.accounts[8].pubkey, constants::accounts::TOKEN_PROGRAM);
+        assert_eq!(v2.accounts[9], AccountMeta::new(creator_vault, false));
+        let v2 =
+            with_program_version(sell_ix.clone(), ProgramVersion::V2, &seeds, &creator).unwrap();
+        assert_eq!(v2.accounts[7].pubkey, constants::accounts::SYSTEM_PROGRAM);
+        assert_eq!(v2.accounts[8], AccountMeta::new(creator_vault, false));
+        assert_eq!(v2.accounts[9].pubkey, constants::accounts::TOKEN_PROGRAM);
+        assert!(!keys(&v2).contains(&constants::accounts::ASSOCIATED_TOKEN_PROGRAM));
+
+        let v3 =
+            with_program_version(buy_ix.clone(), ProgramVersion::V3, &seeds, &creator).unwrap();
+        assert_eq!(
+            keys(&v3)[12..],
+            [
+                get_global_volume_accumulator_pda(),
+                get_user_volume_accumulator_pda(&payer.pubkey())
+            ]
+        );
+        let v3 =
+            with_program_version(sell_ix.clone(), ProgramVersion::V3, &seeds, &creator).unwrap();
+        assert_eq!(v3.accounts.len(), 12);
+
+        let v4 =
+            with_program_version(buy_ix.clone(), ProgramVersion::V4, &seeds, &creator).unwrap();
+        assert_eq!(v4.accounts.len(), 16);
+        assert_eq!(
+            keys(&v4)[14..],
+            [get_fee_config_pda(), constants::accounts::FEE_PROGRAM]
+        );
+        let v4 =
+            with_program_version(sell_ix.clone(), ProgramVersion::V4, &seeds, &creator).unwrap();
+        assert_eq!(v4.data, sell_ix.data);
+        assert_eq!(
+            keys(&v4)[12..],
+            [get_fee_config_pda(), constants::accounts::FEE_PROGRAM]
+        );
+        assert!(v4.accounts[12..].iter().all(|meta| !meta.is_writable));
+
+        // A truncated trade is rejected instead of indexing past its accounts
+        let mut truncated = buy_ix.clone();
+        truncated.accounts.truncate(6);
+        assert!(matches!(
+            with_program_version(truncated, ProgramVersion::V2, &seeds, &creator),
+            Err(ClientError::InvalidInput(_))
+        ));
+        let mut truncated = sell_ix.clone();
+        truncated.accounts.truncate(8);
+        assert!(matches!(
+            with_program_version(truncated.clone(), ProgramVersion::V2, &seeds, &creator),
+            Err(ClientError::InvalidInput(_))
+        ));
+        assert_eq!(
+            with_program_version(truncated.clone(), ProgramVersion::V1, &seeds, &creator).unwrap(),
+            truncated
+        );
+    }
+}
@@ -0,0 +1,667 @@
+//! Persistent journal of submitted trades for crash recovery
+//!
+//! A bot that crashes between submitting a trade and recording its confirmation cannot tell on
+//! restart whether the trade landed. Record an entry with [`TradeJournal::record`] before sending
+//! and call [`TradeJournal::reconcile`] on startup to resolve every entry that never reached a
+//! terminal state.
+//!
+//! The journal also holds the queue of the token account cleanup, see [`crate::cleanup`], so
+//! every queued and processed close is on record.
+//!
+//! - `TradeSide`: Whether a journaled trade was a buy or a sell.
+//! - `EntryState`: Where a journaled trade is in its lifecycle.
+//! - `JournalEntry`: A submitted trade with the context needed to reconcile it.
+//! - `TradeJournal`: The journal, persisted as JSON to a file.
+//! - `ReconcileReport`: Entries resolved by a reconciliation pass.
+
+use crate::{
+    cleanup::{CleanupEntry, CleanupState},
+    error::ClientError,
+    events::{parse_logs, TradeEvent},
+    trade_id::TradeId,
+    utils::clock::Clock,
+    PumpFun, TradeOutcome,
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{pubkey::Pubkey, signature::Signature, signer::Signer};
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Maximum number of signatures per `getSignatureStatuses` request
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+/// Whether a journaled trade was a buy or a sell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TradeSide {
+    /// Tokens bought with SOL
+    Buy,
+    /// Tokens sold for SOL
+    Sell,
+}
+
+/// Where a journaled trade is in its lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum EntryState {
+    /// Submitted, outcome not yet known
+    Pending,
+    /// Landed successfully, either as submitted or as an equivalent fill
+    Confirmed,
+    /// Landed with an error
+    Failed,
+    /// Blockhash expired without the trade or an equivalent fill landing
+    Expired,
+    /// Outcome could not be determined and needs manual review
+    Unknown,
+}
+
+impl EntryState {
+    /// Returns whether the state is final and needs no further reconciliation
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Confirmed | Self::Failed | Self::Expired)
+    }
+}
+
+/// A submitted trade with the context needed to reconcile it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// Client-generated identifier of the logical trade
+    pub trade_id: TradeId,
+    /// Signature of the submitted transaction
+    pub signature: Signature,
+    /// Token mint that was traded
+    pub mint: Pubkey,
+    /// Whether the trade was a buy or a sell
+    pub side: TradeSide,
+    /// Amount of tokens in base units bought or sold
+    pub token_amount: u64,
+    /// Maximum SOL cost for buys, or minimum SOL output for sells, in lamports
+    pub sol_limit: u64,
+    /// Last block height at which the transaction's blockhash is valid
+    pub last_valid_block_height: u64,
+    /// Unix timestamp at which the trade was submitted
+    pub submitted_at: i64,
+    /// Current state of the trade
+    pub state: EntryState,
+    /// Signature of the equivalent fill, if the trade landed under a different signature
+    pub fill_signature: Option<Signature>,
+    /// Memo sent with the trade, such as the strategy that placed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+impl JournalEntry {
+    /// Creates a pending entry for a trade about to be submitted
+    ///
+    /// # Arguments
+    ///
+    /// * `trade_id` - Identifier of the logical trade
+    /// * `signature` - Signature of the signed transaction
+    /// * `mint` - Token mint being traded
+    /// * `side` - Whether the trade is a buy or a sell
+    /// * `token_amount` - Amount of tokens in base units to buy or sell
+    /// * `sol_limit` - Maximum SOL cost for buys, or minimum SOL output for sells
+    /// * `last_valid_block_height` - Last block height at which the blockhash is valid
+    /// * `clock` - Clock the submission time is read from, usually [`PumpFun::clock`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn pending(
+        trade_id: TradeId,
+        signature: Signature,
+        mint: Pubkey,
+        side: TradeSide,
+        token_amount: u64,
+        sol_limit: u64,
+        last_valid_block_height: u64,
+        clock: &dyn Clock,
+    ) -> Self {
+        let submitted_at = clock.unix_timestamp();
+
+        Self {
+            trade_id,
+            signature,
+            mint,
+            side,
+            token_amount,
+            sol_limit,
+            last_valid_block_height,
+            submitted_at,
+            state: EntryState::Pending,
+            fill_signature: None,
+            memo: None,
+        }
+    }
+
+    /// Creates a confirmed entry from the outcome of a trade
+    ///
+    /// The amounts are the ones filled, as reported by the trade event, and the memo is the one
+    /// of the confirmed transaction. The entry is terminal, so its blockhash window is not
+    /// needed and left at zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `outcome` - Outcome of the confirmed trade, see [`crate::PumpFun::buy_with_outcome`]
+    ///
+    /// # Returns
+    ///
+    /// Returns the entry, or None if the outcome has no trade event
+    pub fn from_outcome(outcome: &TradeOutcome) -> Option<Self> {
+        let event = outcome.trade_event.as_ref()?;
+
+        Some(Self {
+            trade_id: outcome.trade_id,
+            signature: outcome.signature,
+            mint: event.mint,
+            side: if event.is_buy {
+                TradeSide::Buy
+            } else {
+                TradeSide::Sell
+            },
+            token_amount: event.token_amount,
+            sol_limit: event.sol_amount,
+            last_valid_block_height: 0,
+            submitted_at: event.timestamp,
+            state: EntryState::Confirmed,
+            fill_signature: None,
+            memo: outcome.memo.clone(),
+        })
+    }
+
+    /// Sets the memo sent with the trade
+    ///
+    /// # Arguments
+    ///
+    /// * `memo` - Memo of the trade's transaction, see [`crate::params::BuyParams::with_memo`]
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Returns whether a trade event is an equivalent fill of this entry
+    ///
+    /// A fill is equivalent when the same user traded the same amount of the same mint in the
+    /// same direction, within the entry's SOL limit, no earlier than the entry was submitted.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - Trade event to compare
+    /// * `user` - Wallet that submitted the entry
+    pub fn matches_fill(&self, event: &TradeEvent, user: &Pubkey) -> bool {
+        let within_limit = match self.side {
+            TradeSide::Buy => event.is_buy && event.sol_amount <= self.sol_limit,
+            TradeSide::Sell => !event.is_buy && event.sol_amount >= self.sol_limit,
+        };
+
+        within_limit
+            && event.user == *user
+            && event.mint == self.mint
+            && event.token_amount == self.token_amount
+            && event.timestamp >= self.submitted_at
+    }
+}
+
+/// Entries resolved by a reconciliation pass
+#[derive(Debug, Clone, Default)]
+pub struct ReconcileReport {
+    /// Entries that moved to a terminal state
+    pub resolved: Vec<JournalEntry>,
+    /// Entries whose outcome could not be determined and need manual review
+    pub unknown: Vec<JournalEntry>,
+}
+
+/// Contents of a journal file
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum JournalFile {
+    /// Trades only, as written by journals that never queued a cleanup
+    Entries(Vec<JournalEntry>),
+    /// Trades and cleanup queue
+    Full {
+        entries: Vec<JournalEntry>,
+        #[serde(default)]
+        cleanups: Vec<CleanupEntry>,
+    },
+}
+
+/// Journal of submitted trades, persisted as JSON to a file
+#[derive(Debug)]
+pub struct TradeJournal {
+    /// File the journal is persisted to
+    path: PathBuf,
+    /// Journaled entries in submission order
+    entries: Mutex<Vec<JournalEntry>>,
+    /// Token account closes in the order they were queued
+    cleanups: Mutex<Vec<CleanupEntry>>,
+}
+
+impl TradeJournal {
+    /// Opens a journal, loading its entries if the file exists
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - File the journal is persisted to
+    ///
+    /// # Returns
+    ///
+    /// Returns the journal, or a ClientError if the file exists but cannot be read
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        let path = path.as_ref().to_path_buf();
+        let file = if path.exists() {
+            let data = fs::read(&path).map_err(ClientError::JournalError)?;
+            serde_json::from_slice(&data).map_err(|err| ClientError::JournalError(err.into()))?
+        } else {
+            JournalFile::Entries(Vec::new())
+        };
+        let (entries, cleanups) = match file {
+            JournalFile::Entries(entries) => (entries, Vec::new()),
+            JournalFile::Full { entries, cleanups } => (entries, cleanups),
+        };
+
+        Ok(Self {
+            path,
+            entries: Mutex::new(entries),
+            cleanups: Mutex::new(cleanups),
+        })
+    }
+
+    /// Records an entry and persists the journal
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Entry to record, typically created with [`JournalEntry::pending`]
+    pub fn record(&self, entry: JournalEntry) -> Result<(), ClientError> {
+        let mut entries = self.entries.lock().unwrap();
+        entries.push(entry);
+        self.persist(&entries)
+    }
+
+    /// Gets a snapshot of every journaled entry
+    pub fn entries(&self) -> Vec<JournalEntry> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Queues a token account close and persists the journal
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Close to queue, in the `Queued` state
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the close was queued, false if the account is already queued, or a
+    /// ClientError if the journal cannot be persisted
+    pub fn queue_cleanup(&self, entry: CleanupEntry) -> Result<bool, ClientError> {
+        let entries = self.entries.lock().unwrap();
+        let mut cleanups = self.cleanups.lock().unwrap();
+        if cleanups
+            .iter()
+            .any(|queued| queued.state == CleanupState::Queued && queued.account == entry.account)
+        {
+            return Ok(false);
+        }
+        cleanups.push(entry);
+        self.persist_all(&entries, &cleanups)?;
+        Ok(true)
+    }
+
+    /// Records the outcome of a queued token account close and persists the journal
+    ///
+    /// # Arguments
+    ///
+    /// * `entry` - Processed close, replacing the queued entry for the same account
+    pub fn update_cleanup(&self, entry: CleanupEntry) -> Result<(), ClientError> {
+        let entries = self.entries.lock().unwrap();
+        let mut cleanups = self.cleanups.lock().unwrap();
+        match cleanups
+            .iter_mut()
+            .find(|queued| queued.state == CleanupState::Queued && queued.account == entry.account)
+        {
+            Some(queued) => *queued = entry,
+            None => cleanups.push(entry),
+        }
+        self.persist_all(&entries, &cleanups)
+    }
+
+    /// Gets a snapshot of every queued and processed token account close
+    pub fn cleanups(&self) -> Vec<CleanupEntry> {
+        self.cleanups.lock().unwrap().clone()
+    }
+
+    /// Gets the token account closes still waiting to be processed, oldest first
+    pub fn queued_cleanups(&self) -> Vec<CleanupEntry> {
+        self.cleanups
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.state == CleanupState::Queued)
+            .cloned()
+            .collect()
+    }
+
+    /// Resolves every entry lacking a terminal state
+    ///
+    /// Each entry's signature status is queried first. If the signature is unknown to the
+    /// cluster and its blockhash has expired, the payer's recent trades are searched for an
+    /// equivalent fill before the entry is marked expired. Entries still within their blockhash
+    /// window stay pending; entries whose outcome cannot be determined are marked unknown,
+    /// logged as warnings and returned for review.
+    ///
+    /// The cluster is queried without holding the journal, so trades can be recorded meanwhile,
+    /// and the outcomes are applied in one pass at the end.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client for the wallet that submitted the entries
+    ///
+    /// # Returns
+    ///
+    /// Returns the entries resolved by this pass, or a ClientError if the journal cannot be
+    /// persisted
+    pub fn reconcile(&self, client: &PumpFun<'_>) -> Result<ReconcileReport, ClientError> {
+        let mut open: Vec<(usize, JournalEntry)> = self
+            .entries
+            .lock()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.state.is_terminal())
+            .map(|(index, entry)| (index, entry.clone()))
+            .collect();
+        let mut report = ReconcileReport::default();
+        if open.is_empty() {
+            return Ok(report);
+        }
+
+        let block_height = client.rpc.get_block_height().ok();
+        let mut statuses = Vec::with_capacity(open.len());
+        for chunk in open.chunks(MAX_SIGNATURE_STATUSES) {
+            let signatures: Vec<Signature> =
+                chunk.iter().map(|(_, entry)| entry.signature).collect();
+            match client.rpc.get_signature_statuses_with_history(&signatures) {
+                Ok(response) => statuses.extend(response.value.into_iter().map(Some)),
+                Err(_) => statuses.extend(chunk.iter().map(|_| None)),
+            }
+        }
+
+        // Only entries that expired without landing need the payer's history
+        let mints: Vec<Pubkey> = open
+            .iter()
+            .zip(&statuses)
+            .filter(|((_, entry), status)| {
+                matches!(status, Some(None))
+                    && block_height.is_some_and(|height| height > entry.last_valid_block_height)
+            })
+            .map(|((_, entry), _)| entry.mint)
+            .collect();
+        let fills = if mints.is_empty() {
+            None
+        } else {
+            Self::get_recent_fills(client, &mints)
+        };
+        let payer = client.payer.pubkey();
+
+        for ((_, entry), status) in open.iter_mut().zip(statuses) {
+            entry.state = match status {
+                Some(Some(status)) if status.err.is_some() => EntryState::Failed,
+                Some(Some(status)) if status.satisfies_commitment(client.rpc.commitment()) => {
+                    EntryState::Confirmed
+                }
+                // Landed but not yet at the client's commitment
+                Some(Some(_)) => EntryState::Pending,
+                Some(None) => match block_height {
+                    Some(height) if height <= entry.last_valid_block_height => EntryState::Pending,
+                    Some(_) => match fills.as_ref().map(|fills| {
+                        fills
+                            .iter()
+                            .find(|(_, event)| entry.matches_fill(event, &payer))
+                    }) {
+                        Some(Some((signature, _))) => {
+                            entry.fill_signature = Some(*signature);
+                            EntryState::Confirmed
+                        }
+                        Some(None) => EntryState::Expired,
+                        None => EntryState::Unknown,
+                    },
+                    None => EntryState::Unknown,
+                },
+                None => EntryState::Unknown,
+            };
+        }
+
+        let mut entries = self.entries.lock().unwrap();
+        for (index, resolved) in open {
+            // Entries are only ever appended, and one resolved by a concurrent pass is kept
+            let entry = &mut entries[index];
+            if entry.signature != resolved.signature || entry.state.is_terminal() {
+                continue;
+            }
+            entry.state = resolved.state;
+            entry.fill_signature = resolved.fill_signature;
+
+            if entry.state == EntryState::Unknown {
+                tracing::warn!(
+                    trade_id = %entry.trade_id,
+                    signature = %entry.signature,
+                    mint = %client.redaction_policy().mint(&entry.mint),
+                    "Could not determine the outcome of a journaled trade"
+                );
+                report.unknown.push(entry.clone());
+            } else if entry.state.is_terminal() {
+                report.resolved.push(entry.clone());
+            }
+        }
+
+        self.persist(&entries)?;
+        Ok(report)
+    }
+
+    /// Gets the payer's recent successful trade events for the given mints
+    ///
+    /// Each transaction in the history is fetched once and searched for trades of every mint.
+    /// Returns None if the payer's history cannot be read, or if any successful transaction in
+    /// it cannot be fetched, since a fill could then be missed.
+    fn get_recent_fills(
+        client: &PumpFun<'_>,
+        mints: &[Pubkey],
+    ) -> Option<Vec<(Signature, TradeEvent)>> {
+        let payer = client.payer.pubkey();
+        let signatures = client.rpc.get_signatures_for_address(&payer).ok()?;
+
+        let mut fetched = HashSet::new();
+        let mut fills = Vec::new();
+        for signature in signatures
+            .iter()
+            .filter(|status| status.err.is_none())
+            .filter_map(|status| status.signature.parse::<Signature>().ok())
+            .filter(|signature| fetched.insert(*signature))
+        {
+            let transaction = client.get_confirmed_transaction(&signature).ok()?;
+            let logs: Vec<String> = transaction
+                .transaction
+                .meta
+                .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+                .unwrap_or_default();
+            fills.extend(
+                parse_logs::<TradeEvent>(&logs)
+                    .into_iter()
+                    .filter(|event| event.user == payer && mints.contains(&event.mint))
+                    .map(|event| (signature, event)),
+            );
+        }
+
+        Some(fills)
+    }
+
+    /// Writes the entries to the journal file, replacing it atomically
+    fn persist(&self, entries: &[JournalEntry]) -> Result<(), ClientError> {
+        let cleanups = self.cleanups.lock().unwrap();
+        self.persist_all(entries, &cleanups)
+    }
+
+    /// Writes the entries and cleanup queue to the journal file, replacing it atomically
+    fn persist_all(
+        &self,
+        entries: &[JournalEntry],
+        cleanups: &[CleanupEntry],
+    ) -> Result<(), ClientError> {
+        // Journals without cleanups keep the plain list of entries older versions read
+        let data = if cleanups.is_empty() {
+            serde_json::to_vec_pretty(entries)
+        } else {
+            serde_json::to_vec_pretty(&JournalFile::Full {
+                entries: entries.to_vec(),
+                cleanups: cleanups.to_vec(),
+            })
+        }
+        .map_err(|err| ClientError::JournalError(err.into()))?;
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, data).map_err(ClientError::JournalError)?;
+        fs::rename(&tmp, &self.path).map_err(ClientError::JournalError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_entry(side: TradeSide) -> JournalEntry {
+        JournalEntry {
+            trade_id: TradeId {
+                session: 1,
+                sequence: 0,
+            },
+            signature: Signature::new_unique(),
+            mint: Pubkey::new_unique(),
+            side,
+            token_amount: 1_000_000,
+            sol_limit: 50_000_000,
+            last_valid_block_height: 100,
+            submitted_at: 1_700_000_000,
+            state: EntryState::Pending,
+            fill_signature: None,
+            memo: None,
+        }
+    }
+
+    fn get_event(entry: &JournalEntry, user: Pubkey, sol_amount: u64) -> TradeEvent {
+        TradeEvent {
+            mint: entry.mint,
+            sol_amount,
+            token_amount: entry.token_amount,
+            is_buy: entry.side == TradeSide::Buy,
+            user,
+            timestamp: entry.submitted_at + 5,
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+        }
+    }
+
+    #[test]
+    fn test_matches_fill() {
+        let user = Pubkey::new_unique();
+
+        let buy = get_entry(TradeSide::Buy);
+        assert!(buy.matches_fill(&get_event(&buy, user, 49_000_000), &user));
+        // Paid more than the maximum cost
+        assert!(!buy.matches_fill(&get_event(&buy, user, 51_000_000), &user));
+        // Someone else's trade
+        assert!(!buy.matches_fill(&get_event(&buy, user, 49_000_000), &Pubkey::new_unique()));
+        // A fill from before the entry was submitted
+        let mut event = get_event(&buy, user, 49_000_000);
+        event.timestamp = buy.submitted_at - 60;
+        assert!(!buy.matches_fill(&event, &user));
+
+        let sell = get_entry(TradeSide::Sell);
+        assert!(sell.matches_fill(&get_event(&sell, user, 51_000_000), &user));
+        // Received less than the minimum output
+        assert!(!sell.matches_fill(&get_event(&sell, user, 49_000_000), &user));
+        // Opposite direction
+        assert!(!buy.matches_fill(&get_event(&sell, user, 49_000_000), &user));
+    }
+
+    #[test]
+    fn test_entry_states() {
+        assert!(!EntryState::Pending.is_terminal());
+        assert!(!EntryState::Unknown.is_terminal());
+        assert!(EntryState::Confirmed.is_terminal());
+        assert!(EntryState::Failed.is_terminal());
+        assert!(EntryState::Expired.is_terminal());
+    }
+
+    #[test]
+    fn test_journal_persists_entries() {
+        let path = std::env::temp_dir().join(format!("journal-{}.json", Pubkey::new_unique()));
+        let entry = get_entry(TradeSide::Buy);
+
+        let journal = TradeJournal::open(&path).unwrap();
+        assert!(journal.entries().is_empty());
+        journal.record(entry.clone()).unwrap();
+
+        // A restarted bot sees the pending entry
+        let reopened = TradeJournal::open(&path).unwrap();
+        assert_eq!(reopened.entries(), vec![entry]);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_entry_memo() {
+        let entry = get_entry(TradeSide::Buy);
+        let json = serde_json::to_value(&entry).unwrap();
+        // Entries without a memo serialize as before, and older entries load without one
+        assert!(json.get("memo").is_none());
+        assert_eq!(serde_json::from_value::<JournalEntry>(json).unwrap(), entry);
+
+        let tagged = entry.with_memo("strategy-v1");
+        let json = serde_json::to_string(&tagged).unwrap();
+        let loaded: JournalEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.memo.as_deref(), Some("strategy-v1"));
+        assert_eq!(loaded, tagged);
+    }
+
+    #[test]
+    fn test_journal_persists_cleanups() {
+        let path = std::env::temp_dir().join(format!("journal-{}.json", Pubkey::new_unique()));
+        let entry = get_entry(TradeSide::Sell);
+        let queued = CleanupEntry {
+            mint: entry.mint,
+            account: Pubkey::new_unique(),
+            token_program: Pubkey::new_unique(),
+            state: CleanupState::Queued,
+            queued_at: 1_700_000_000,
+            processed_at: None,
+            balance: None,
+            signature: None,
+        };
+
+        let journal = TradeJournal::open(&path).unwrap();
+        journal.record(entry.clone()).unwrap();
+        // Journals without cleanups stay readable by older versions
+        let legacy: Vec<JournalEntry> = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(legacy, vec![entry.clone()]);
+
+        assert!(journal.queue_cleanup(queued.clone()).unwrap());
+        assert!(!journal.queue_cleanup(queued.clone()).unwrap());
+        assert_eq!(journal.queued_cleanups(), vec![queued.clone()]);
+
+        let closed = CleanupEntry {
+            state: CleanupState::Closed,
+            processed_at: Some(1_700_000_100),
+            signature: Some(Signature::new_unique()),
+            ..queued.clone()
+        };
+        journal.update_cleanup(closed.clone()).unwrap();
+
+        let reopened = TradeJournal::open(&path).unwrap();
+        assert_eq!(reopened.entries(), vec![entry]);
+        assert_eq!(reopened.cleanups(), vec![closed]);
+        assert!(reopened.queued_cleanups().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+}
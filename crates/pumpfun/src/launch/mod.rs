@@ -0,0 +1,361 @@
+//! Offline projections of a token launch for creators
+//!
+//! [`Simulator`] models a launch on the pure bonding curve math: the creator's dev buy executes
+//! first, followed by a scenario of follower buys, until the scenario runs out or the curve
+//! graduates. Each step of the resulting [`Projection`] records the price, the supply split
+//! between the creator, followers and the curve, the creator fees accrued so far and what the
+//! creator's allocation could be sold for at that point.
+//!
+//! - `FollowerBuys`: The follower buy sizes, as a fixed list or a seeded distribution.
+//! - `Scenario`: The dev buy, follower buys and fee parameters of a launch.
+//! - `Simulator`: Runs scenarios against a starting curve.
+//! - `Projection` and `LaunchStep`: The projected outcome, step by step.
+//!
+//! No client or network access is needed.
+
+use crate::accounts::{BondingCurveAccount, CurveError, GlobalAccount, PlannedTrade};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Follower buy sizes in a launch scenario
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FollowerBuys {
+    /// Buys of the given SOL amounts in lamports, in order
+    List(Vec<u64>),
+    /// Buys of amounts drawn uniformly from `min..=max` lamports, reproducible from the seed
+    Uniform {
+        /// Number of buys
+        count: usize,
+        /// Smallest buy in lamports
+        min: u64,
+        /// Largest buy in lamports
+        max: u64,
+        /// Seed of the random number generator
+        seed: u64,
+    },
+}
+
+impl FollowerBuys {
+    /// Expands the follower buys into SOL amounts in lamports, in order
+    pub fn amounts(&self) -> Vec<u64> {
+        match self {
+            Self::List(amounts) => amounts.clone(),
+            Self::Uniform {
+                count,
+                min,
+                max,
+                seed,
+            } => {
+                let mut rng = StdRng::seed_from_u64(*seed);
+                (0..*count)
+                    .map(|_| rng.gen_range(*min.min(max)..=*max.max(min)))
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Description of a launch to simulate
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scenario {
+    /// SOL spent by the creator in the first buy in lamports, excluding fees
+    pub dev_buy: u64,
+    /// SOL spent by followers after the dev buy, excluding fees
+    pub followers: FollowerBuys,
+    /// Protocol fee charged on each buy in basis points
+    pub fee_basis_points: u64,
+    /// Creator fee charged on each buy in basis points
+    pub creator_fee_basis_points: u64,
+}
+
+/// Who made a buy in a launch projection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Buyer {
+    /// The creator's dev buy
+    Dev,
+    /// A follower buy, by position in the scenario
+    Follower(usize),
+}
+
+/// State of a launch after a single buy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LaunchStep {
+    /// Who made the buy
+    pub buyer: Buyer,
+    /// SOL that entered the curve in lamports, excluding fees
+    pub sol_amount: u64,
+    /// Tokens received in base units
+    pub token_amount: u64,
+    /// Spot price after the buy in lamports per token base unit
+    pub price: f64,
+    /// Market cap after the buy in lamports
+    pub market_cap_sol: u64,
+    /// Tokens held by the creator in base units
+    pub dev_tokens: u64,
+    /// Tokens held by followers in base units
+    pub follower_tokens: u64,
+    /// Tokens still held by the curve in base units
+    pub curve_tokens: u64,
+    /// Creator fees accrued up to and including this buy in lamports
+    pub creator_fees: u64,
+    /// SOL the creator would receive for selling their whole allocation now, after fees
+    pub dev_allocation_value: u64,
+}
+
+/// Projected outcome of a launch scenario
+#[derive(Debug, Clone)]
+pub struct Projection {
+    /// Every buy that executed, in order
+    pub steps: Vec<LaunchStep>,
+    /// Whether the curve graduated during the scenario
+    pub graduated: bool,
+    /// Total protocol fees charged in lamports
+    pub protocol_fees: u64,
+    /// Total creator fees accrued in lamports
+    pub creator_fees: u64,
+    /// State of the curve after the last buy
+    pub final_curve: BondingCurveAccount,
+}
+
+/// Runs launch scenarios against a starting curve
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    /// Curve the launch starts from
+    curve: BondingCurveAccount,
+}
+
+impl Simulator {
+    /// Creates a simulator starting from the given curve
+    ///
+    /// # Arguments
+    ///
+    /// * `curve` - Curve the launch starts from
+    pub fn new(curve: BondingCurveAccount) -> Self {
+        Self { curve }
+    }
+
+    /// Creates a simulator starting from a freshly created curve
+    ///
+    /// # Arguments
+    ///
+    /// * `global` - Global account holding the initial curve parameters
+    pub fn from_global(global: &GlobalAccount) -> Self {
+        Self::new(BondingCurveAccount::new(
+            0,
+            global.initial_virtual_token_reserves,
+            global.initial_virtual_sol_reserves,
+            global.initial_real_token_reserves,
+            0,
+            global.token_total_supply,
+            false,
+        ))
+    }
+
+    /// Projects a launch scenario
+    ///
+    /// Buys execute in order until the scenario runs out or the curve graduates; buys after
+    /// graduation are dropped. Zero-sized buys are recorded with no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `scenario` - Launch to simulate
+    ///
+    /// # Returns
+    ///
+    /// Returns the projection, or a CurveError if the starting curve is complete or a buy
+    /// cannot be applied
+    pub fn run(&self, scenario: &Scenario) -> Result<Projection, CurveError> {
+        if self.curve.complete {
+            return Err(CurveError::Complete);
+        }
+
+        let buys = std::iter::once((Buyer::Dev, scenario.dev_buy)).chain(
+            scenario
+                .followers
+                .amounts()
+                .into_iter()
+                .enumerate()
+                .map(|(index, amount)| (Buyer::Follower(index), amount)),
+        );
+
+        let mut curve = self.curve.clone();
+        let mut steps = Vec::new();
+        let mut dev_tokens: u64 = 0;
+        let mut follower_tokens: u64 = 0;
+        let mut protocol_fees: u64 = 0;
+        let mut creator_fees: u64 = 0;
+
+        for (buyer, sol_amount) in buys {
+            if curve.complete {
+                break;
+            }
+
+            let outcome = curve.simulate_sequence(
+                &[PlannedTrade::Buy { sol_amount }],
+                scenario.fee_basis_points,
+            )?;
+            let fill = outcome.fills[0];
+            curve = outcome.final_curve;
+
+            match buyer {
+                Buyer::Dev => dev_tokens += fill.token_amount,
+                Buyer::Follower(_) => follower_tokens += fill.token_amount,
+            }
+            protocol_fees = protocol_fees.saturating_add(fill.fee);
+            creator_fees = creator_fees.saturating_add(
+                ((fill.sol_amount as u128) * (scenario.creator_fee_basis_points as u128) / 10000)
+                    as u64,
+            );
+
+            // A graduated curve no longer trades, so value the allocation at the final price
+            let dev_allocation_value = if curve.complete {
+                (dev_tokens as f64 * outcome.final_price) as u64
+            } else {
+                curve
+                    .get_sell_price(
+                        dev_tokens,
+                        scenario.fee_basis_points + scenario.creator_fee_basis_points,
+                    )
+                    .unwrap_or_default()
+            };
+
+            steps.push(LaunchStep {
+                buyer,
+                sol_amount: fill.sol_amount,
+                token_amount: fill.token_amount,
+                price: outcome.final_price,
+                market_cap_sol: curve.get_market_cap_sol(),
+                dev_tokens,
+                follower_tokens,
+                curve_tokens: curve.real_token_reserves,
+                creator_fees,
+                dev_allocation_value,
+            });
+        }
+
+        Ok(Projection {
+            steps,
+            graduated: curve.complete,
+            protocol_fees,
+            creator_fees,
+            final_curve: curve,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::pubkey::Pubkey;
+
+    fn get_simulator() -> Simulator {
+        Simulator::from_global(&GlobalAccount::new(
+            0,
+            true,
+            Pubkey::default(),
+            Pubkey::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        ))
+    }
+
+    #[test]
+    fn test_fixed_scenario() {
+        let scenario = Scenario {
+            dev_buy: 1_000_000_000,
+            followers: FollowerBuys::List(vec![2_000_000_000, 500_000_000]),
+            fee_basis_points: 100,
+            creator_fee_basis_points: 50,
+        };
+        let projection = get_simulator().run(&scenario).unwrap();
+
+        assert_eq!(projection.steps.len(), 3);
+        assert!(!projection.graduated);
+
+        let dev = projection.steps[0];
+        assert_eq!(dev.buyer, Buyer::Dev);
+        assert_eq!(dev.sol_amount, 1_000_000_000);
+        assert_eq!(dev.token_amount, 34_612_903_225_806);
+        assert_eq!(dev.creator_fees, 5_000_000);
+        assert_eq!(dev.price, 31_000_000_000.0 / 1_038_387_096_774_194.0);
+
+        // Supply is conserved between the creator, followers and the curve
+        let last = projection.steps[2];
+        assert_eq!(last.dev_tokens, dev.token_amount);
+        assert_eq!(
+            last.dev_tokens + last.follower_tokens + last.curve_tokens,
+            793_100_000_000_000
+        );
+        assert_eq!(projection.creator_fees, last.creator_fees);
+        assert_eq!(projection.creator_fees, 17_500_000);
+        assert_eq!(projection.protocol_fees, 35_000_000);
+
+        // Follower buys push the price up and lift the value of the dev allocation
+        assert!(projection.steps.windows(2).all(|w| w[1].price > w[0].price));
+        assert!(last.dev_allocation_value > dev.dev_allocation_value);
+    }
+
+    #[test]
+    fn test_scenario_stops_at_graduation() {
+        let scenario = Scenario {
+            dev_buy: 5_000_000_000,
+            followers: FollowerBuys::List(vec![50_000_000_000, 50_000_000_000, 1_000_000_000]),
+            fee_basis_points: 100,
+            creator_fee_basis_points: 0,
+        };
+        let projection = get_simulator().run(&scenario).unwrap();
+
+        // The second follower buy takes the rest of the curve; the last buy never happens
+        assert!(projection.graduated);
+        assert_eq!(projection.steps.len(), 3);
+        let last = projection.steps.last().unwrap();
+        assert_eq!(last.curve_tokens, 0);
+        assert_eq!(last.dev_tokens + last.follower_tokens, 793_100_000_000_000);
+        assert_eq!(projection.creator_fees, 0);
+    }
+
+    #[test]
+    fn test_seeded_distribution_is_reproducible() {
+        let followers = FollowerBuys::Uniform {
+            count: 50,
+            min: 100_000_000,
+            max: 1_000_000_000,
+            seed: 7,
+        };
+        let amounts = followers.amounts();
+        assert_eq!(amounts.len(), 50);
+        assert!(amounts
+            .iter()
+            .all(|amount| (100_000_000..=1_000_000_000).contains(amount)));
+        assert_eq!(amounts, followers.amounts());
+
+        let scenario = Scenario {
+            dev_buy: 1_000_000_000,
+            followers,
+            fee_basis_points: 100,
+            creator_fee_basis_points: 50,
+        };
+        let simulator = get_simulator();
+        let first = simulator.run(&scenario).unwrap();
+        let second = simulator.run(&scenario).unwrap();
+        assert_eq!(first.steps, second.steps);
+    }
+
+    #[test]
+    fn test_complete_curve_rejected() {
+        let mut curve = get_simulator().curve;
+        curve.complete = true;
+        let scenario = Scenario {
+            dev_buy: 1,
+            followers: FollowerBuys::List(Vec::new()),
+            fee_basis_points: 0,
+            creator_fee_basis_points: 0,
+        };
+        assert_eq!(
+            Simulator::new(curve).run(&scenario).unwrap_err(),
+            CurveError::Complete
+        );
+    }
+}
@@ -1,29 +1,127 @@
 #![doc = include_str!("../RUSTDOC.md")]
 
 pub mod accounts;
+pub mod analytics;
+#[cfg(feature = "api")]
+pub mod api;
+pub mod bundle;
+#[cfg(feature = "immutable-cache")]
+pub mod cache;
+pub mod cancel;
+pub mod cleanup;
 pub mod constants;
+pub mod devnet;
 pub mod error;
+pub mod events;
+pub mod export;
+pub mod flow;
 pub mod instruction;
+pub mod journal;
+pub mod launch;
+pub mod mev;
+pub mod params;
+pub mod plan;
+pub mod portfolio;
+pub mod prelude;
+pub mod quote;
+pub mod redact;
+pub mod refresh;
+pub mod repl;
+pub mod screening;
+pub mod shed;
+pub mod signers;
+pub mod spend;
+pub mod strategy;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod stress;
+pub mod submit;
+pub mod summary;
+pub mod swap;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
+pub mod timings;
+pub mod trade_id;
+pub mod upgrade;
+pub mod usage;
 pub mod utils;
+pub mod vectors;
+pub mod wallets;
 
 use anchor_client::{
-    solana_client::rpc_client::RpcClient,
+    solana_client::{
+        nonblocking::pubsub_client::PubsubClient,
+        rpc_client::{GetConfirmedSignaturesForAddress2Config, RpcClient, RpcClientConfig},
+        rpc_config::{
+            RpcAccountInfoConfig, RpcBlockConfig, RpcSimulateTransactionAccountsConfig,
+            RpcSimulateTransactionConfig, RpcTransactionConfig, RpcTransactionLogsConfig,
+            RpcTransactionLogsFilter,
+        },
+        rpc_response::RpcConfirmedTransactionStatusWithSignature,
+        rpc_sender::RpcSender,
+    },
     solana_sdk::{
+        account::Account,
         commitment_config::CommitmentConfig,
+        instruction::Instruction,
+        message::VersionedMessage,
         pubkey::Pubkey,
+        rent::Rent,
         signature::{Keypair, Signature},
         signer::Signer,
+        transaction::{Transaction, VersionedTransaction},
     },
-    Client, Cluster, Program,
+    Client, Cluster, Program, RequestBuilder,
 };
-use anchor_spl::associated_token::{
-    get_associated_token_address,
-    spl_associated_token_account::instruction::create_associated_token_account,
+use anchor_spl::{
+    associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    token::spl_token::state::{Account as TokenAccount, Mint},
 };
-use borsh::BorshDeserialize;
+use futures::{StreamExt, TryStreamExt};
 pub use pumpfun_cpi as cpi;
-use solana_sdk::compute_budget::ComputeBudgetInstruction;
-use std::rc::Rc;
+use solana_account_decoder::{parse_token::UiTokenAmount, UiAccountEncoding, UiDataSliceConfig};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::{
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    compute_budget::ComputeBudgetInstruction,
+    program_pack::Pack,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionDetails, UiConfirmedBlock,
+    UiTransactionEncoding, UiTransactionStatusMeta,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    rc::Rc,
+    str::FromStr,
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Namespaces of lookups written to the on-disk cache, see [`PumpFun::with_immutable_cache`]
+const CACHE_TOKEN_AGE: &str = "token_age";
+const CACHE_CREATOR: &str = "creator";
+const CACHE_TOKEN_PROGRAM: &str = "token_program";
+const CACHE_METADATA_UPLOAD: &str = "metadata_upload";
+
+/// Hint returned when the Metaplex token metadata program is missing from the cluster
+pub const METADATA_PROGRAM_HINT: &str =
+    "clone it into the test validator with `solana-test-validator \
+--clone metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s --url mainnet-beta`";
+
+/// Window of recent trades used to measure a curve's SOL inflow velocity
+pub const GRADUATION_VELOCITY_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// Default maximum number of RPC requests a batch operation keeps in flight
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// Maximum number of accounts per `getMultipleAccounts` request
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Number of signatures per page when walking the global authority's history
+pub const PARAM_HISTORY_PAGE: usize = 100;
+
+/// Size of the global account before the creator fee upgrade
+const GLOBAL_V1_LEN: usize = 113;
 
 /// Configuration for priority fee compute unit parameters
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,16 +132,265 @@ pub struct PriorityFee {
     pub price: Option<u64>,
 }
 
+/// Outcome of a confirmed trade
+#[derive(Debug, Clone)]
+pub struct TradeOutcome {
+    /// Client-generated identifier of the trade
+    pub trade_id: trade_id::TradeId,
+    /// Signature of the confirmed transaction
+    pub signature: Signature,
+    /// Trade event emitted by the program, if it could be found in the transaction logs
+    pub trade_event: Option<events::TradeEvent>,
+    /// Time spent in each stage of the trade, from the start of the quote to confirmation
+    pub timings: timings::StageTimings,
+    /// The signed transaction exactly as it was sent, if [`PumpFun::return_transaction`] is set
+    pub transaction: Option<Transaction>,
+    /// How submission errors were handled before the transaction was accepted
+    pub submit_path: submit::SubmitPath,
+    /// Memo of the confirmed transaction, if it carried one
+    pub memo: Option<String>,
+}
+
+impl TradeOutcome {
+    /// Encodes the sent transaction as base64 wire bytes, for logs and archives
+    ///
+    /// # Returns
+    ///
+    /// Returns the encoded transaction, or None if it was not captured
+    pub fn transaction_base64(&self) -> Option<String> {
+        use base64::Engine;
+        let transaction = self.transaction.as_ref()?;
+        let bytes = bincode::serialize(transaction).ok()?;
+        Some(base64::engine::general_purpose::STANDARD.encode(bytes))
+    }
+}
+
+/// Decoded state needed to trade a token, fetched in a single round-trip
+#[derive(Debug, Clone)]
+pub struct TokenContext {
+    /// The token's bonding curve account
+    pub bonding_curve: accounts::BondingCurveAccount,
+    /// The token's mint account, including its decimals and supply
+    pub mint: Mint,
+}
+
+/// A change to the program's global parameters
+#[derive(Debug, Clone)]
+pub struct GlobalParamChange {
+    /// Slot of the transaction that changed the parameters
+    pub slot: u64,
+    /// Unix timestamp of the change, if the node reported one
+    pub block_time: Option<i64>,
+    /// Signature of the transaction that changed the parameters
+    pub signature: Signature,
+    /// Global account state after the change
+    pub global: accounts::GlobalAccount,
+}
+
+/// When a token was created
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TokenAge {
+    /// Slot of the token's first transaction
+    pub creation_slot: u64,
+    /// Unix timestamp of the token's first transaction, if the node reported one
+    pub creation_block_time: Option<i64>,
+    /// Signature of the token's first transaction
+    pub creation_signature: Signature,
+}
+
+impl TokenAge {
+    /// Gets how long ago the token was created
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Clock to read the current time from, usually [`PumpFun::clock`]
+    ///
+    /// # Returns
+    ///
+    /// Returns the time since creation, or None if the creation time is unknown
+    pub fn age(&self, clock: &dyn utils::clock::Clock) -> Option<Duration> {
+        let created = self.creation_block_time?;
+        let now = clock.unix_timestamp();
+        Some(Duration::from_secs(
+            now.saturating_sub(created).max(0) as u64
+        ))
+    }
+}
+
+/// An inconsistency between a bonding curve and its associated token account
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CurveAtaIssue {
+    /// No account exists at the derived address
+    Missing,
+    /// The account is owned by a different program than the mint's token program
+    WrongProgram {
+        /// Program that owns the account
+        actual: Pubkey,
+    },
+    /// The account data is not a token account
+    InvalidData,
+    /// The token account holds a different mint
+    WrongMint {
+        /// Mint held by the account
+        actual: Pubkey,
+    },
+    /// The token account's authority is not the bonding curve
+    WrongAuthority {
+        /// Authority of the account
+        actual: Pubkey,
+    },
+    /// The token account holds fewer tokens than the curve's real reserves
+    BalanceBelowReserves {
+        /// Balance of the token account in base units
+        balance: u64,
+        /// Real token reserves of the curve in base units
+        reserves: u64,
+    },
+}
+
+/// Result of checking a bonding curve's associated token account against the curve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CurveAtaReport {
+    /// Address of the bonding curve
+    pub bonding_curve: Pubkey,
+    /// Associated token account address derived for the curve
+    pub expected_ata: Pubkey,
+    /// Balance of the token account in base units, if it could be read
+    pub balance: Option<u64>,
+    /// Real token reserves recorded by the curve in base units
+    pub reserves: u64,
+    /// Every inconsistency found
+    pub issues: Vec<CurveAtaIssue>,
+}
+
+impl CurveAtaReport {
+    /// Returns whether the token account matches the curve
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Seed strings used to derive the program's Program Derived Addresses (PDAs)
+///
+/// Defaults to the values in [`constants::seeds`]. Forks of the program that changed their
+/// seed strings while keeping the instruction layouts can supply their own values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeedConfig {
+    /// Seed for the global state PDA
+    pub global: &'static [u8],
+    /// Seed for the mint authority PDA
+    pub mint_authority: &'static [u8],
+    /// Seed for bonding curve PDAs
+    pub bonding_curve: &'static [u8],
+    /// Seed for creator vault PDAs
+    pub creator_vault: &'static [u8],
+}
+
+impl Default for SeedConfig {
+    fn default() -> Self {
+        Self {
+            global: constants::seeds::GLOBAL_SEED,
+            mint_authority: constants::seeds::MINT_AUTHORITY_SEED,
+            bonding_curve: constants::seeds::BONDING_CURVE_SEED,
+            creator_vault: constants::seeds::CREATOR_VAULT_SEED,
+        }
+    }
+}
+
 /// Main client for interacting with the Pump.fun program
 pub struct PumpFun<'a> {
+    /// Solana cluster the client is connected to
+    pub cluster: Cluster,
     /// RPC client for Solana network requests
     pub rpc: RpcClient,
-    /// Keypair used to sign transactions
+    /// Keypair used to sign transactions, unless a signer provider is set
     pub payer: &'a Keypair,
     /// Anchor client instance
     pub client: Client<Rc<&'a Keypair>>,
     /// Anchor program instance
     pub program: Program<Rc<&'a Keypair>>,
+    /// Seeds used for PDA derivation
+    pub seeds: SeedConfig,
+    /// Commitment used for account reads in quotes, if different from the client commitment
+    pub quote_commitment: Option<CommitmentConfig>,
+    /// Maximum number of RPC requests a batch operation keeps in flight
+    pub max_concurrent_requests: usize,
+    /// Whether trade outcomes include the signed transaction that was sent
+    pub return_transaction: bool,
+    /// Number of slots cached curve state may move past a trade plan before it is refused
+    pub plan_slot_budget: u64,
+    /// Program version buy and sell instructions are built for
+    pub program_version: instruction::ProgramVersion,
+    /// Mints with a create transaction currently in flight from this client
+    pending_mints: Mutex<HashSet<Pubkey>>,
+    /// Mints locked by an order in progress on this client
+    locked_mints: Mutex<HashSet<Pubkey>>,
+    /// Creation data of tokens looked up so far, which never changes
+    token_ages: Mutex<HashMap<Pubkey, TokenAge>>,
+    /// Token program owning each mint looked up so far, which never changes
+    token_programs: Mutex<HashMap<Pubkey, Pubkey>>,
+    /// Metadata uploaded to IPFS so far, keyed by content hash
+    metadata_uploads: Mutex<HashMap<solana_sdk::hash::Hash, utils::TokenMetadataResponse>>,
+    /// Limits token images are checked against before upload, if any
+    image_policy: Option<utils::ImagePolicy>,
+    /// Programs confirmed to be deployed on the cluster, which stay deployed
+    deployed_programs: Mutex<HashSet<Pubkey>>,
+    /// Program version detected on the cluster, if detection has run
+    detected_version: Mutex<Option<instruction::ProgramVersion>>,
+    /// Cached cluster time and slot duration readings
+    clock_cache: utils::clock::ClockCache,
+    /// Source of the local time
+    clock: std::sync::Arc<dyn utils::clock::Clock>,
+    /// Identifiers assigned to trades started by this client
+    trade_ids: trade_id::TradeIdGenerator,
+    /// Callback receiving the raw bytes of accounts that fail to decode
+    decode_hook: Option<accounts::DecodeHook>,
+    /// Source of creation data for curve summaries
+    summary_enricher: Option<std::sync::Arc<dyn summary::SummaryEnricher>>,
+    /// On-disk cache of lookups that never change
+    #[cfg(feature = "immutable-cache")]
+    immutable_cache: Option<std::sync::Arc<cache::ImmutableCache>>,
+    /// Cleanup of empty token accounts after curve completion, if enabled
+    ata_cleanup: Option<cleanup::AtaCleanup>,
+    /// Requests sent through the RPC client, per operation
+    rpc_usage: std::sync::Arc<usage::RpcUsage>,
+    /// Credits charged per RPC request, for usage estimates
+    rpc_costs: usage::CostTable,
+    /// Deployment slot of the program, event subscribers and pause state
+    upgrade_watch: upgrade::UpgradeWatch,
+    /// Which sensitive values are redacted in log output
+    redaction: redact::RedactionPolicy,
+    /// Priority fees spent and the budget applied to new trades, if tracking is enabled
+    fee_spend: Option<spend::FeeSpendTracker>,
+    /// Priority fee of transactions sent without one
+    default_priority_fee: Option<PriorityFee>,
+    /// Fee recipient of trades that don't name one, in place of the global account's
+    fee_recipient: Option<Pubkey>,
+    /// Provider of the keypair each trade signs with, in place of the payer, if set
+    signer_provider: Option<std::sync::Arc<dyn signers::SignerProvider>>,
+    /// Trackers of the trade stream of single mints, used for graduation estimates
+    volume_trackers: Vec<std::sync::Arc<Mutex<flow::NetFlowTracker>>>,
+    /// Rate of transient failures among recent RPC requests
+    rpc_pressure: std::sync::Arc<shed::RpcPressure>,
+    /// Priorities of the optional features shed under RPC pressure, if shedding is enabled
+    load_shedder: Option<shed::LoadShedder>,
+}
+
+/// Marks a mint as being created until dropped
+struct MintReservation<'b> {
+    /// Set of mints the reservation was taken from
+    pending_mints: &'b Mutex<HashSet<Pubkey>>,
+    /// Reserved mint
+    mint: Pubkey,
+}
+
+impl Drop for MintReservation<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut pending_mints) = self.pending_mints.lock() {
+            pending_mints.remove(&self.mint);
+        }
+    }
 }
 
 impl<'a> PumpFun<'a> {
@@ -66,12 +413,35 @@ impl<'a> PumpFun<'a> {
         ws: Option<bool>,
     ) -> Self {
         // Create Solana RPC Client with either WS or HTTP endpoint
-        let rpc: RpcClient = RpcClient::new(if ws.unwrap_or(false) {
+        let sender = HttpSender::new(if ws.unwrap_or(false) {
             cluster.ws_url()
         } else {
             cluster.url()
         });
 
+        Self::new_with_sender(cluster, payer, options, sender)
+    }
+
+    /// Creates a client sending its RPC requests through the provided transport
+    ///
+    /// Requests are counted towards [`Self::rpc_usage`] before being passed to the transport,
+    /// and their outcomes are recorded for load shedding, see [`Self::with_load_shedder`].
+    fn new_with_sender(
+        cluster: Cluster,
+        payer: &'a Keypair,
+        options: Option<CommitmentConfig>,
+        sender: impl RpcSender + Send + Sync + 'static,
+    ) -> Self {
+        let rpc_usage = std::sync::Arc::new(usage::RpcUsage::new());
+        let rpc_pressure = std::sync::Arc::new(shed::RpcPressure::new());
+        let rpc: RpcClient = RpcClient::new_sender(
+            usage::UsageSender::new(
+                shed::PressureSender::new(sender, rpc_pressure.clone()),
+                rpc_usage.clone(),
+            ),
+            RpcClientConfig::with_commitment(CommitmentConfig::default()),
+        );
+
         // Create Anchor Client with optional commitment config
         let client: Client<Rc<&Keypair>> = if let Some(options) = options {
             Client::new_with_options(cluster.clone(), Rc::new(payer), options)
@@ -84,456 +454,8755 @@ impl<'a> PumpFun<'a> {
 
         // Return configured PumpFun client
         Self {
+            cluster,
             rpc,
             payer,
             client,
             program,
+            seeds: SeedConfig::default(),
+            quote_commitment: None,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            return_transaction: false,
+            plan_slot_budget: 0,
+            program_version: instruction::ProgramVersion::Auto,
+            pending_mints: Mutex::new(HashSet::new()),
+            locked_mints: Mutex::new(HashSet::new()),
+            token_ages: Mutex::new(HashMap::new()),
+            token_programs: Mutex::new(HashMap::new()),
+            metadata_uploads: Mutex::new(HashMap::new()),
+            image_policy: None,
+            deployed_programs: Mutex::new(HashSet::new()),
+            detected_version: Mutex::new(None),
+            clock_cache: utils::clock::ClockCache::default(),
+            clock: std::sync::Arc::new(utils::clock::SystemClock),
+            trade_ids: trade_id::TradeIdGenerator::new(),
+            decode_hook: None,
+            summary_enricher: None,
+            #[cfg(feature = "immutable-cache")]
+            immutable_cache: None,
+            ata_cleanup: None,
+            rpc_usage,
+            rpc_costs: usage::CostTable::default(),
+            upgrade_watch: upgrade::UpgradeWatch::default(),
+            redaction: redact::RedactionPolicy::default(),
+            fee_spend: None,
+            default_priority_fee: None,
+            fee_recipient: None,
+            signer_provider: None,
+            volume_trackers: Vec::new(),
+            rpc_pressure,
+            load_shedder: None,
         }
     }
 
-    /// Creates a new token with metadata by uploading metadata to IPFS and initializing on-chain accounts
+    /// Overrides the seeds used for PDA derivation
     ///
     /// # Arguments
     ///
-    /// * `mint` - Keypair for the new token mint account that will be created
-    /// * `metadata` - Token metadata including name, symbol, description and image file
-    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `seeds` - Seed strings used by a derivation-compatible fork of the program
     ///
     /// # Returns
     ///
-    /// Returns the transaction signature if successful, or a ClientError if the operation fails
-    pub async fn create(
-        &self,
-        mint: &Keypair,
-        metadata: utils::CreateTokenMetadata,
-        priority_fee: Option<PriorityFee>,
-    ) -> Result<Signature, error::ClientError> {
-        // First upload metadata and image to IPFS
-        // let ipfs: utils::TokenMetadataResponse = utils::create_token_metadata(metadata)
-        //     .await
-        //     .map_err(error::ClientError::UploadMetadataError)?;
-
-        let mut request = self.program.request();
-
-        // Add priority fee if provided
-        if let Some(fee) = priority_fee {
-            if let Some(limit) = fee.limit {
-                let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
-                request = request.instruction(limit_ix);
-            }
-
-            if let Some(price) = fee.price {
-                let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
-                request = request.instruction(price_ix);
-            }
-        }
-
-        // // Add create token instruction
-        // request = request.instruction(instruction::create(
-        //     self.payer,
-        //     mint,
-        //     cpi::instruction::Create {
-        //         _name: ipfs.metadata.name,
-        //         _symbol: ipfs.metadata.symbol,
-        //         _uri: ipfs.metadata.image,
-        //     },
-        // ));
-
-        request = request.instruction(instruction::create(
-            self.payer,
-            mint,
-            cpi::instruction::Create {
-                _name: metadata.name,
-                _symbol: metadata.symbol,
-                _uri: metadata.file,
-            },
-        ));
-
-        // Add signers
-        request = request.signer(&self.payer).signer(mint);
-
-        // Send transaction
-        let signature: Signature = request
-            .send()
-            .await
-            .map_err(error::ClientError::AnchorClientError)?;
-
-        Ok(signature)
+    /// Returns the client configured to derive the global, mint authority, bonding curve and
+    /// creator vault PDAs from the provided seeds
+    pub fn with_seeds(mut self, seeds: SeedConfig) -> Self {
+        self.seeds = seeds;
+        self
     }
 
-    /// Creates a new token and immediately buys an initial amount in a single atomic transaction
+    /// Overrides the commitment used for account reads in quotes
+    ///
+    /// Quotes read the bonding curve at this commitment instead of the client commitment, while
+    /// trades keep reading at the client commitment. `processed` gives the freshest state and is
+    /// suited to rapidly refreshing quotes, but may reflect a fork that is later dropped.
+    /// `finalized` can never be rolled back, but lags the tip by about 30 slots, so quotes can be
+    /// noticeably stale on an active curve. `confirmed` is a middle ground.
     ///
     /// # Arguments
     ///
-    /// * `mint` - Keypair for the new token mint
-    /// * `metadata` - Token metadata to upload to IPFS
-    /// * `amount_sol` - Amount of SOL to spend on initial buy in lamports
-    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
-    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `commitment` - Commitment level to read quote accounts at
     ///
     /// # Returns
     ///
-    /// Returns the transaction signature if successful, or a ClientError if the operation fails
-    pub async fn create_and_buy(
-        &self,
-        mint: &Keypair,
-        metadata: utils::CreateTokenMetadata,
-        amount_sol: u64,
-        slippage_basis_points: Option<u64>,
-        priority_fee: Option<PriorityFee>,
-    ) -> Result<Signature, error::ClientError> {
-        // // Upload metadata to IPFS first
-        // let ipfs: utils::TokenMetadataResponse = utils::create_token_metadata(metadata)
-        //     .await
-        //     .map_err(error::ClientError::UploadMetadataError)?;
-
-        // Get accounts and calculate buy amounts
-        let global_account = self.get_global_account()?;
-        let buy_amount = global_account.get_initial_buy_price(amount_sol);
-        let buy_amount_with_slippage =
-            utils::calculate_with_slippage_buy(amount_sol, slippage_basis_points.unwrap_or(500));
-
-        let mut request = self.program.request();
-
-        // Add priority fee if provided
-        if let Some(fee) = priority_fee {
-            if let Some(limit) = fee.limit {
-                let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
-                request = request.instruction(limit_ix);
-            }
-
-            if let Some(price) = fee.price {
-                let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
-                request = request.instruction(price_ix);
-            }
-        }
-
-        // Add create token instruction
-        // request = request.instruction(instruction::create(
-        //     self.payer,
-        //     mint,
-        //     cpi::instruction::Create {
-        //         _name: ipfs.metadata.name,
-        //         _symbol: ipfs.metadata.symbol,
-        //         _uri: ipfs.metadata.image,
-        //     },
-        // ));
-
-        request = request.instruction(instruction::create(
-            self.payer,
-            mint,
-            cpi::instruction::Create {
-                _name: metadata.name,
-                _symbol: metadata.symbol,
-                _uri: metadata.file,
-            },
-        ));
-
-        // Create Associated Token Account if needed
-        let ata: Pubkey = get_associated_token_address(&self.payer.pubkey(), &mint.pubkey());
-        if self.rpc.get_account(&ata).is_err() {
-            request = request.instruction(create_associated_token_account(
-                &self.payer.pubkey(),
-                &self.payer.pubkey(),
-                &mint.pubkey(),
-                &constants::accounts::TOKEN_PROGRAM,
-            ));
-        }
-
-        // Add buy instruction
-        request = request.instruction(instruction::buy(
-            self.payer,
-            &mint.pubkey(),
-            &global_account.fee_recipient,
-            cpi::instruction::Buy {
-                _amount: buy_amount,
-                _max_sol_cost: buy_amount_with_slippage,
-            },
-        ));
-
-        // Add signers and send transaction
-        let signature: Signature = request
-            .signer(&self.payer)
-            .signer(mint)
-            .send()
-            .await
-            .map_err(error::ClientError::AnchorClientError)?;
-
-        Ok(signature)
+    /// Returns the client configured to read quote accounts at the provided commitment
+    pub fn with_quote_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.quote_commitment = Some(commitment);
+        self
     }
 
-    /// Buys tokens from a bonding curve by spending SOL
+    /// Sets whether trade outcomes include the signed transaction that was sent
+    ///
+    /// The transaction is captured right before it is sent, so it is exactly what the cluster
+    /// received. Archiving it helps diagnose failed or front-run trades after the fact.
     ///
     /// # Arguments
     ///
-    /// * `mint` - Public key of the token mint to buy
-    /// * `amount_sol` - Amount of SOL to spend in lamports
-    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
-    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `return_transaction` - Whether to include the transaction in trade outcomes
     ///
     /// # Returns
     ///
-    /// Returns the transaction signature if successful, or a ClientError if the operation fails
-    pub async fn buy(
-        &self,
-        mint: &Pubkey,
-        amount_sol: u64,
-        slippage_basis_points: Option<u64>,
-        priority_fee: Option<PriorityFee>,
-    ) -> Result<Signature, error::ClientError> {
-        // Get accounts and calculate buy amounts
-        let global_account = self.get_global_account()?;
-        let bonding_curve_account = self.get_bonding_curve_account(mint)?;
-        let buy_amount = bonding_curve_account
-            .get_buy_price(amount_sol)
-            .map_err(error::ClientError::BondingCurveError)?;
-        let buy_amount_with_slippage =
-            utils::calculate_with_slippage_buy(amount_sol, slippage_basis_points.unwrap_or(500));
-
-        let mut request = self.program.request();
-
-        // Add priority fee if provided
-        if let Some(fee) = priority_fee {
-            if let Some(limit) = fee.limit {
-                let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
-                request = request.instruction(limit_ix);
-            }
-
-            if let Some(price) = fee.price {
-                let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
-                request = request.instruction(price_ix);
-            }
-        }
-
-        // Create Associated Token Account if needed
-        let ata: Pubkey = get_associated_token_address(&self.payer.pubkey(), mint);
-        if self.rpc.get_account(&ata).is_err() {
-            request = request.instruction(create_associated_token_account(
-                &self.payer.pubkey(),
-                &self.payer.pubkey(),
-                mint,
-                &constants::accounts::TOKEN_PROGRAM,
-            ));
-        }
-
-        // Add buy instruction
-        request = request.instruction(instruction::buy(
-            self.payer,
-            mint,
-            &global_account.fee_recipient,
-            cpi::instruction::Buy {
-                _amount: buy_amount,
-                _max_sol_cost: buy_amount_with_slippage,
-            },
-        ));
-
-        // Add signer
-        request = request.signer(&self.payer);
-
-        // Send transaction
-        let signature: Signature = request
-            .send()
-            .await
-            .map_err(error::ClientError::AnchorClientError)?;
-
-        Ok(signature)
+    /// Returns the client with the option set
+    pub fn with_return_transaction(mut self, return_transaction: bool) -> Self {
+        self.return_transaction = return_transaction;
+        self
     }
 
-    /// Sells tokens back to the bonding curve in exchange for SOL
+    /// Overrides the clock the client reads the local time from
+    ///
+    /// Cached cluster readings, time windows, fee spend windows and the RPC pressure behind load
+    /// shedding all use this clock, so tests can swap in a [`utils::clock::MockClock`] and
+    /// advance time deterministically. Token ages and journal entries take the clock they are
+    /// measured with, see [`PumpFun::clock`]. Defaults to [`utils::clock::SystemClock`].
     ///
     /// # Arguments
     ///
-    /// * `mint` - Public key of the token mint to sell
-    /// * `amount_token` - Optional amount of tokens to sell in base units. If None, sells entire balance
-    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
-    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `clock` - Clock to read the local time from
     ///
     /// # Returns
     ///
-    /// Returns the transaction signature if successful, or a ClientError if the operation fails
-    pub async fn sell(
-        &self,
-        mint: &Pubkey,
-        amount_token: Option<u64>,
-        slippage_basis_points: Option<u64>,
-        priority_fee: Option<PriorityFee>,
-    ) -> Result<Signature, error::ClientError> {
-        // Get accounts and calculate sell amounts
-        let ata: Pubkey = get_associated_token_address(&self.payer.pubkey(), mint);
-        let balance = self.rpc.get_token_account_balance(&ata).unwrap();
-        let balance_u64: u64 = balance.amount.parse::<u64>().unwrap();
-        let _amount = amount_token.unwrap_or(balance_u64);
-        let global_account = self.get_global_account()?;
-        let bonding_curve_account = self.get_bonding_curve_account(mint)?;
-        let min_sol_output = bonding_curve_account
-            .get_sell_price(_amount, global_account.fee_basis_points)
-            .map_err(error::ClientError::BondingCurveError)?;
-        let _min_sol_output = utils::calculate_with_slippage_sell(
-            min_sol_output,
-            slippage_basis_points.unwrap_or(500),
-        );
-
-        let mut request = self.program.request();
-
-        // Add priority fee if provided
-        if let Some(fee) = priority_fee {
-            if let Some(limit) = fee.limit {
-                let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
-                request = request.instruction(limit_ix);
-            }
-
-            if let Some(price) = fee.price {
-                let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
-                request = request.instruction(price_ix);
-            }
-        }
-
-        // Add sell instruction
-        request = request.instruction(instruction::sell(
-            self.payer,
-            mint,
-            &global_account.fee_recipient,
-            cpi::instruction::Sell {
-                _amount,
-                _min_sol_output,
-            },
-        ));
-
-        // Add signer
-        request = request.signer(&self.payer);
-
-        // Send transaction
-        let signature: Signature = request
-            .send()
-            .await
-            .map_err(error::ClientError::AnchorClientError)?;
+    /// Returns the client reading time from the provided clock
+    pub fn with_clock(mut self, clock: std::sync::Arc<dyn utils::clock::Clock>) -> Self {
+        self.rpc_pressure.set_clock(clock.clone());
+        self.clock = clock;
+        self
+    }
 
-        Ok(signature)
+    /// Gets the clock the client reads the local time from
+    pub fn clock(&self) -> &dyn utils::clock::Clock {
+        &*self.clock
     }
 
-    /// Gets the Program Derived Address (PDA) for the global state account
+    /// Overrides the number of slots a trade plan may fall behind the cached curve state
+    ///
+    /// [`PumpFun::execute`] refuses plans priced against state that the cache has since
+    /// replaced with an observation more than this many slots newer. The default of 0 refuses
+    /// any plan built before the latest observation of its curve.
+    ///
+    /// # Arguments
+    ///
+    /// * `slots` - Number of slots the cached state may move past a plan
     ///
     /// # Returns
     ///
-    /// Returns the PDA public key derived from the GLOBAL_SEED
-    pub fn get_global_pda() -> Pubkey {
-        let seeds: &[&[u8]; 1] = &[constants::seeds::GLOBAL_SEED];
-        let program_id: &Pubkey = &cpi::ID;
-        Pubkey::find_program_address(seeds, program_id).0
+    /// Returns the client with the slot budget applied
+    pub fn with_plan_slot_budget(mut self, slots: u64) -> Self {
+        self.plan_slot_budget = slots;
+        self
     }
 
-    /// Gets the Program Derived Address (PDA) for the mint authority
+    /// Overrides the program version buy and sell instructions are built for
+    ///
+    /// By default the version is detected from the cluster the first time a trade is built.
+    /// Setting it skips detection, for clusters where it guesses wrong or to pin the account
+    /// set across an upgrade. See [`instruction::ProgramVersion`] for each version's accounts.
+    ///
+    /// # Arguments
+    ///
+    /// * `version` - Program version deployed on the cluster
     ///
     /// # Returns
     ///
-    /// Returns the PDA public key derived from the MINT_AUTHORITY_SEED
-    pub fn get_mint_authority_pda() -> Pubkey {
-        let seeds: &[&[u8]; 1] = &[constants::seeds::MINT_AUTHORITY_SEED];
-        let program_id: &Pubkey = &cpi::ID;
-        Pubkey::find_program_address(seeds, program_id).0
+    /// Returns the client building instructions for the given version
+    pub fn with_program_version(mut self, version: instruction::ProgramVersion) -> Self {
+        self.program_version = version;
+        self
     }
 
-    /// Gets the Program Derived Address (PDA) for a token's bonding curve account
+    /// Overrides the maximum number of RPC requests a batch operation keeps in flight
     ///
     /// # Arguments
     ///
-    /// * `mint` - Public key of the token mint
+    /// * `limit` - Maximum number of concurrent requests, at least 1
     ///
     /// # Returns
     ///
-    /// Returns Some(PDA) if derivation succeeds, or None if it fails
-    pub fn get_bonding_curve_pda(mint: &Pubkey) -> Option<Pubkey> {
-        let seeds: &[&[u8]; 2] = &[constants::seeds::BONDING_CURVE_SEED, mint.as_ref()];
-        let program_id: &Pubkey = &cpi::ID;
-        let pda: Option<(Pubkey, u8)> = Pubkey::try_find_program_address(seeds, program_id);
-        pda.map(|pubkey| pubkey.0)
+    /// Returns the client with the concurrency limit applied
+    pub fn with_max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = limit.max(1);
+        self
     }
 
-    /// Gets the Program Derived Address (PDA) for a token's metadata account
+    /// Assigns the identifier for a new logical trade
+    ///
+    /// Every trade method calls this once at its start and tags its tracing events with the
+    /// result. Call it yourself to tag journal entries or other records for a trade you are about
+    /// to start.
+    ///
+    /// # Returns
+    ///
+    /// Returns an identifier unique to this client instance and larger than any assigned before
+    pub fn next_trade_id(&self) -> trade_id::TradeId {
+        self.trade_ids.next_id()
+    }
+
+    /// Sets a callback receiving the full raw bytes of accounts that fail to decode
+    ///
+    /// Decode errors already carry the account, data length and leading bytes. The hook lets
+    /// the complete data be captured for offline diagnosis, for example after a program upgrade.
     ///
     /// # Arguments
     ///
-    /// * `mint` - Public key of the token mint
+    /// * `hook` - Callback invoked with the failure and the raw account data
     ///
     /// # Returns
     ///
-    /// Returns the PDA public key for the token's metadata account
-    pub fn get_metadata_pda(mint: &Pubkey) -> Pubkey {
-        let seeds: &[&[u8]; 3] = &[
-            constants::seeds::METADATA_SEED,
-            constants::accounts::MPL_TOKEN_METADATA.as_ref(),
-            mint.as_ref(),
-        ];
-        let program_id: &Pubkey = &constants::accounts::MPL_TOKEN_METADATA;
-        Pubkey::find_program_address(seeds, program_id).0
+    /// Returns the client with the hook installed
+    pub fn with_decode_hook(mut self, hook: accounts::DecodeHook) -> Self {
+        self.decode_hook = Some(hook);
+        self
     }
 
-    /// Gets the global state account data containing program-wide configuration
+    /// Sets the source of creation data for curve summaries
+    ///
+    /// # Arguments
+    ///
+    /// * `enricher` - Event store or index answering creation lookups, see
+    ///   [`summary::SummaryEnricher`]
     ///
     /// # Returns
     ///
-    /// Returns the deserialized GlobalAccount if successful, or a ClientError if the operation fails
-    pub fn get_global_account(&self) -> Result<accounts::GlobalAccount, error::ClientError> {
-        let global: Pubkey = Self::get_global_pda();
+    /// Returns the client with the enricher attached
+    pub fn with_summary_enricher(
+        mut self,
+        enricher: std::sync::Arc<dyn summary::SummaryEnricher>,
+    ) -> Self {
+        self.summary_enricher = Some(enricher);
+        self
+    }
 
-        let account = self
-            .rpc
-            .get_account(&global)
-            .map_err(error::ClientError::SolanaClientError)?;
+    /// Attaches an on-disk cache for lookups that never change
+    ///
+    /// Token ages, creators and token programs are read from the cache before making RPC
+    /// calls, and written to it after. Metadata uploads are remembered in it as well, so a
+    /// launch retried after a crash reuses the URI already pinned. See [`cache::ImmutableCache`].
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - Cache to read and write lookups through
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the cache attached
+    #[cfg(feature = "immutable-cache")]
+    pub fn with_immutable_cache(mut self, cache: std::sync::Arc<cache::ImmutableCache>) -> Self {
+        self.immutable_cache = Some(cache);
+        self
+    }
 
-        accounts::GlobalAccount::try_from_slice(&account.data)
-            .map_err(error::ClientError::BorshError)
+    /// Sets the limits token images are checked against before upload
+    ///
+    /// Images over the size cap are rejected before any request, or resized to meet the policy
+    /// if it allows, see [`utils::prepare_image`]. Images are uploaded as they are by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Size cap and maximum dimension of the images, and whether to resize them
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the policy applied
+    pub fn with_image_policy(mut self, policy: utils::ImagePolicy) -> Self {
+        self.image_policy = Some(policy);
+        self
     }
 
-    /// Gets a token's bonding curve account data containing pricing parameters
+    /// Enables the cleanup of empty token accounts after curve completion
+    ///
+    /// Once enabled, every completion seen by [`PumpFun::wait_for_graduation`] queues the
+    /// payer's token account for the mint in the journal, to be closed by
+    /// [`PumpFun::process_ata_cleanup`]. See the [`cleanup`] module. Cleanup is disabled unless
+    /// this is called.
     ///
     /// # Arguments
     ///
-    /// * `mint` - Public key of the token mint
+    /// * `journal` - Journal recording the queue and the outcome of every close
+    /// * `config` - Batching and fee settings
     ///
     /// # Returns
     ///
-    /// Returns the deserialized BondingCurveAccount if successful, or a ClientError if the operation fails
-    pub fn get_bonding_curve_account(
+    /// Returns the client with the cleanup enabled
+    pub fn with_ata_cleanup(
+        mut self,
+        journal: std::sync::Arc<journal::TradeJournal>,
+        config: cleanup::AtaCleanupConfig,
+    ) -> Self {
+        self.ata_cleanup = Some(cleanup::AtaCleanup { journal, config });
+        self
+    }
+
+    /// Sets the credits charged per RPC request, used to estimate the cost of usage
+    ///
+    /// # Arguments
+    ///
+    /// * `costs` - Credits per request of each method, matching the provider's pricing
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the cost table applied
+    pub fn with_rpc_cost_table(mut self, costs: usage::CostTable) -> Self {
+        self.rpc_costs = costs;
+        self
+    }
+
+    /// Sets whether a detected program upgrade pauses sending transactions
+    ///
+    /// While paused, every transaction sent through the client fails with
+    /// ClientError::TradingPaused until [`PumpFun::acknowledge_program_upgrade`] is called. See
+    /// the [`upgrade`] module.
+    ///
+    /// # Arguments
+    ///
+    /// * `pause` - Whether to pause on upgrade
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the pause behavior applied
+    pub fn with_pause_on_upgrade(mut self, pause: bool) -> Self {
+        self.upgrade_watch.pause_on_upgrade = pause;
+        self
+    }
+
+    /// Sets which sensitive values are redacted in the client's log output
+    ///
+    /// See the [`redact`] module for how each kind of value is rendered.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Values to redact
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the redaction policy applied
+    pub fn with_redaction(mut self, policy: redact::RedactionPolicy) -> Self {
+        self.redaction = policy;
+        self
+    }
+
+    /// Gets the policy applied to sensitive values in the client's log output
+    pub fn redaction_policy(&self) -> redact::RedactionPolicy {
+        self.redaction
+    }
+
+    /// Sets the priority fee of transactions sent without one
+    ///
+    /// Creates, trades and swaps called without a priority fee get compute budget instructions
+    /// setting this unit price and limit ahead of their other instructions. A priority fee
+    /// passed to a call replaces it entirely. Without this setting, transactions carry no
+    /// priority fee unless one is passed. Instruction builders such as [`PumpFun::buy_ix`] only
+    /// use the fee they are given.
+    ///
+    /// # Arguments
+    ///
+    /// * `micro_lamports` - Price in micro-lamports per compute unit
+    /// * `unit_limit` - Optional maximum compute units of each transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the default priority fee set
+    pub fn with_priority_fee(mut self, micro_lamports: u64, unit_limit: Option<u32>) -> Self {
+        self.default_priority_fee = Some(PriorityFee {
+            limit: unit_limit,
+            price: Some(micro_lamports),
+        });
+        self
+    }
+
+    /// Sets the fee recipient of trades that don't name one
+    ///
+    /// Buys, sells, planned trades and the buy of [`PumpFun::create_and_buy`] pay their
+    /// protocol fee to this account instead of the fee recipient of the global account. A
+    /// recipient set on [`params::BuyParams`] or [`params::SellParams`] still takes precedence.
+    /// The program only accepts recipients it lists, so trades fail if this account is not one.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Fee recipient account to use
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the fee recipient set
+    pub fn with_fee_recipient(mut self, fee_recipient: Pubkey) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Sets the provider of the keypair trades sign with
+    ///
+    /// The provider is resolved when each trade starts, and the trade keeps that keypair until
+    /// it finishes, including when its transaction is rebuilt and signed again. Instruction
+    /// builders such as [`PumpFun::buy_ix`] resolve it once per call. The keypair the client
+    /// was created with is no longer used to sign, see [`signers`] for rotating hot wallets.
+    ///
+    /// # Arguments
+    ///
+    /// * `provider` - Provider of the active keypair
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with the signer provider set
+    pub fn with_signer_provider(
+        mut self,
+        provider: std::sync::Arc<dyn signers::SignerProvider>,
+    ) -> Self {
+        self.signer_provider = Some(provider);
+        self
+    }
+
+    /// Gets the keypair trades are signed with
+    ///
+    /// Without a signer provider, this is [`PumpFun::payer`]. With one, it is the keypair the
+    /// trade in progress resolved, or outside of a trade the provider's current keypair.
+    pub fn signer(&self) -> signers::ActiveSigner<'a> {
+        match &self.signer_provider {
+            Some(provider) => signers::ActiveSigner::Provided(
+                signers::pinned(provider).unwrap_or_else(|| provider.current()),
+            ),
+            None => signers::ActiveSigner::Payer(self.payer),
+        }
+    }
+
+    /// Attributes a trade's requests to an operation and resolves the keypair it signs with
+    pub(crate) fn trade_scoped<F: std::future::Future>(
         &self,
-        mint: &Pubkey,
-    ) -> Result<accounts::BondingCurveAccount, error::ClientError> {
-        let bonding_curve_pda =
-            Self::get_bonding_curve_pda(mint).ok_or(error::ClientError::BondingCurveNotFound)?;
+        operation: usage::Operation,
+        future: F,
+    ) -> impl std::future::Future<Output = F::Output> {
+        usage::scoped(
+            operation,
+            signers::scoped(self.signer_provider.as_ref(), future),
+        )
+    }
 
-        let account = self
-            .rpc
-            .get_account(&bonding_curve_pda)
-            .map_err(error::ClientError::SolanaClientError)?;
+    /// Picks the fee recipient of a trade, from the trade, the client or the global account
+    fn fee_recipient(
+        &self,
+        requested: Option<Pubkey>,
+        global_account: &accounts::GlobalAccount,
+    ) -> Pubkey {
+        requested
+            .or(self.fee_recipient)
+            .unwrap_or(global_account.fee_recipient)
+    }
 
-        accounts::BondingCurveAccount::try_from_slice(&account.data)
-            .map_err(error::ClientError::BorshError)
+    /// Enables tracking of the priority fees spent by trades, with an optional hourly budget
+    ///
+    /// Each confirmed trade sent with a unit price is charged for the compute units it
+    /// consumed. Once the fees spent over the last hour reach the budget, new trades are sent
+    /// at a lower unit price or refused with ClientError::PriorityFeeBudgetExceeded, depending
+    /// on its action. See the [`spend`] module.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - Optional budget applied to new trades
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with fee tracking enabled
+    pub fn with_fee_spend_tracking(mut self, budget: Option<spend::FeeBudget>) -> Self {
+        self.fee_spend = Some(spend::FeeSpendTracker::new(budget));
+        self
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use anchor_client::solana_sdk::signer::keypair::Keypair;
+    /// Reports the priority fees spent by trades since tracking was enabled
+    ///
+    /// # Returns
+    ///
+    /// Returns the fees spent per mint, over the session and over the last hour, or an empty
+    /// report if tracking is not enabled with [`PumpFun::with_fee_spend_tracking`]
+    pub fn fee_spend_report(&self) -> spend::FeeSpendReport {
+        self.fee_spend
+            .as_ref()
+            .map(|tracker| tracker.report(self.clock.now()))
+            .unwrap_or_default()
+    }
 
-    #[test]
-    fn test_new_client() {
-        let payer = Keypair::new();
-        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
-        assert_eq!(client.payer.pubkey(), payer.pubkey());
+    /// Attaches a tracker of a mint's trade stream, for graduation estimates in summaries
+    ///
+    /// [`PumpFun::get_curve_summary`] estimates when the curve of the tracker's mint completes
+    /// from the trades it holds, see [`analytics::graduation_eta`]. The tracker must be
+    /// restricted to one mint with [`flow::NetFlowTracker::with_mint`], and is fed by the
+    /// caller's trade stream. Attach one tracker per mint to estimate several.
+    ///
+    /// # Arguments
+    ///
+    /// * `tracker` - Tracker restricted to a mint, shared with the code feeding it
+    ///
+    /// # Returns
+    ///
+    /// Returns the client including graduation estimates for the tracker's mint
+    pub fn with_volume_tracker(
+        mut self,
+        tracker: std::sync::Arc<Mutex<flow::NetFlowTracker>>,
+    ) -> Self {
+        self.volume_trackers.push(tracker);
+        self
     }
 
-    #[test]
-    fn test_get_pdas() {
-        let mint = Keypair::new();
-        let global_pda = PumpFun::get_global_pda();
-        let mint_authority_pda = PumpFun::get_mint_authority_pda();
-        let bonding_curve_pda = PumpFun::get_bonding_curve_pda(&mint.pubkey());
-        let metadata_pda = PumpFun::get_metadata_pda(&mint.pubkey());
+    /// Enables shedding of optional features while the RPC node is under pressure
+    ///
+    /// The outcome of every request the client sends is recorded, and once transient failures
+    /// reach the shedder's thresholds, token snapshots, holder counts, logo fetches and
+    /// background refreshes are skipped by priority, failing with ClientError::Shedded without
+    /// making a request. Trades, quotes and account reads are never shed. See the [`shed`]
+    /// module. Shedding is disabled unless this is called.
+    ///
+    /// # Arguments
+    ///
+    /// * `shedder` - Priorities of the optional features and the pressure thresholds
+    ///
+    /// # Returns
+    ///
+    /// Returns the client with load shedding enabled
+    pub fn with_load_shedder(mut self, shedder: shed::LoadShedder) -> Self {
+        self.load_shedder = Some(shedder);
+        self
+    }
 
-        assert!(global_pda != Pubkey::default());
-        assert!(mint_authority_pda != Pubkey::default());
-        assert!(bonding_curve_pda.is_some());
-        assert!(metadata_pda != Pubkey::default());
+    /// Gets the current RPC pressure level
+    ///
+    /// # Returns
+    ///
+    /// Returns the level from the recent failure rate, always normal if load shedding is not
+    /// enabled with [`PumpFun::with_load_shedder`]
+    pub fn load_pressure(&self) -> shed::Pressure {
+        self.load_shedder
+            .as_ref()
+            .map_or(shed::Pressure::Normal, |shedder| {
+                shedder.evaluate(self.rpc_pressure.failure_rate(self.clock.now()))
+            })
+    }
+
+    /// Records that a rate limiter in front of the client is saturated
+    ///
+    /// Counts as a failed request towards the RPC pressure, so a limiter that queues requests
+    /// sheds optional features before the node starts failing them.
+    pub fn record_rate_limited(&self) {
+        self.rpc_pressure.record(self.clock.now(), true);
+    }
+
+    /// Checks whether an optional feature may run at the current RPC pressure
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok if the feature may run or shedding is not enabled, or a
+    /// ClientError::Shedded if it is shed
+    pub(crate) fn check_shed(&self, feature: shed::Feature) -> Result<(), error::ClientError> {
+        match &self.load_shedder {
+            Some(shedder) => {
+                shedder.check(feature, self.rpc_pressure.failure_rate(self.clock.now()))
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Reports the RPC requests sent by this client since creation or the last reset
+    ///
+    /// Requests are attributed to the operation that initiated them, see the [`usage`] module.
+    /// Transactions are signed with blockhashes fetched through the client, so those requests
+    /// are counted too. Subscriptions are not counted.
+    ///
+    /// # Returns
+    ///
+    /// Returns the request counts per operation and method, with estimated credits
+    pub fn rpc_usage(&self) -> usage::UsageReport {
+        self.rpc_usage.report(&self.rpc_costs)
+    }
+
+    /// Resets the RPC usage counters to zero
+    pub fn reset_rpc_usage(&self) {
+        self.rpc_usage.reset();
+    }
+
+    /// Gets a lookup from the on-disk cache, if one is attached
+    #[allow(unused_variables)]
+    fn cached_lookup<T: serde::de::DeserializeOwned>(
+        &self,
+        namespace: &str,
+        key: &Pubkey,
+    ) -> Option<T> {
+        #[cfg(feature = "immutable-cache")]
+        if let Some(cache) = &self.immutable_cache {
+            return cache.get(namespace, key);
+        }
+        None
+    }
+
+    /// Writes a lookup to the on-disk cache, if one is attached
+    ///
+    /// Failures are logged rather than returned, since the lookup itself succeeded.
+    #[allow(unused_variables)]
+    fn store_lookup<T: serde::Serialize>(&self, namespace: &str, key: &Pubkey, value: &T) {
+        #[cfg(feature = "immutable-cache")]
+        if let Some(cache) = &self.immutable_cache {
+            if let Err(err) = cache.insert(namespace, key, value) {
+                tracing::warn!(namespace, %key, "Failed to write immutable cache: {}", err);
+            }
+        }
+    }
+
+    /// Creates a nonblocking RPC client for the same endpoint and commitment as [`Self::rpc`]
+    ///
+    /// Its requests are counted towards [`Self::rpc_usage`] like those of the blocking client.
+    fn nonblocking_rpc(&self) -> anchor_client::solana_client::nonblocking::rpc_client::RpcClient {
+        anchor_client::solana_client::nonblocking::rpc_client::RpcClient::new_sender(
+            usage::UsageSender::new(HttpSender::new(self.rpc.url()), self.rpc_usage.clone()),
+            RpcClientConfig::with_commitment(self.rpc.commitment()),
+        )
+    }
+
+    /// Gets the nonblocking client underlying [`Self::rpc`]
+    ///
+    /// Unlike [`Self::nonblocking_rpc`] it shares the sender of the blocking client, so its
+    /// requests go through the same transport, load shedding and usage accounting.
+    fn async_rpc(&self) -> &anchor_client::solana_client::nonblocking::rpc_client::RpcClient {
+        self.rpc.get_inner_client()
+    }
+
+    /// Decodes program account data, passing failures to the decode hook
+    fn decode_account<T: accounts::ProgramAccount>(
+        &self,
+        account: &Pubkey,
+        data: &[u8],
+    ) -> Result<T, error::ClientError> {
+        accounts::decode_account(account, data, self.decode_hook.as_ref())
+    }
+
+    /// Gets the commitment used for account reads in quotes
+    ///
+    /// # Returns
+    ///
+    /// Returns the configured quote commitment, or the client commitment if none was set
+    pub fn get_quote_commitment(&self) -> CommitmentConfig {
+        self.quote_commitment.unwrap_or(self.rpc.commitment())
+    }
+
+    /// Quotes the amount of tokens received for buying with an amount of SOL
+    ///
+    /// Reads the bonding curve at the quote commitment, see [`PumpFun::with_quote_commitment`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `amount_sol` - Amount of SOL to spend in lamports
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount of tokens in base units, a ClientError::BondingCurveComplete if the
+    /// curve has completed, or a ClientError if the operation fails
+    pub fn quote_buy(&self, mint: &Pubkey, amount_sol: u64) -> Result<u64, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Quote);
+        let (global_account, bonding_curve_account) = self.get_quote_accounts(mint)?;
+        quote::BuyQuote::new(&global_account, &bonding_curve_account, amount_sol, 0)
+            .map(|quote| quote.token_amount)
+    }
+
+    /// Quotes the amount of SOL received for selling an amount of tokens
+    ///
+    /// Reads the global and bonding curve accounts at the quote commitment, see
+    /// [`PumpFun::with_quote_commitment`]. The quote is net of all fees, see
+    /// [`accounts::GlobalAccount::total_fee_bps`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `amount_token` - Amount of tokens to sell in base units
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount of SOL in lamports, a ClientError::BondingCurveComplete if the curve
+    /// has completed, or a ClientError if the operation fails
+    pub fn quote_sell(&self, mint: &Pubkey, amount_token: u64) -> Result<u64, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Quote);
+        let (global_account, bonding_curve_account) = self.get_quote_accounts(mint)?;
+        quote::SellQuote::new(&global_account, &bonding_curve_account, amount_token, 0)
+            .map(|quote| quote.sol_amount)
+    }
+
+    /// Quotes a buy with the most it may cost after slippage and fees, without sending it
+    ///
+    /// Reads the global and bonding curve accounts at the quote commitment, see
+    /// [`PumpFun::with_quote_commitment`]. The quote includes the fees paid on top of the SOL
+    /// spent, and nothing is signed, so it can be shown to a user before they commit to the
+    /// trade.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `amount_sol` - Amount of SOL to spend in lamports
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    ///
+    /// # Returns
+    ///
+    /// Returns the tokens received and the most SOL the buy may cost, a
+    /// ClientError::BondingCurveComplete if the curve has completed, or a ClientError if the
+    /// operation fails
+    pub fn get_buy_quote(
+        &self,
+        mint: &Pubkey,
+        amount_sol: u64,
+        slippage_basis_points: Option<u64>,
+    ) -> Result<quote::BuyQuote, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Quote);
+        let (global_account, bonding_curve_account) = self.get_quote_accounts(mint)?;
+        quote::BuyQuote::new(
+            &global_account,
+            &bonding_curve_account,
+            amount_sol,
+            slippage_basis_points.unwrap_or(quote::DEFAULT_SLIPPAGE_BASIS_POINTS),
+        )
+    }
+
+    /// Quotes a sell with the slippage bound it would be sent with, without sending it
+    ///
+    /// Reads the global and bonding curve accounts at the quote commitment, see
+    /// [`PumpFun::with_quote_commitment`]. The quote is net of all fees, and nothing is signed.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `amount_token` - Amount of tokens to sell in base units
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    ///
+    /// # Returns
+    ///
+    /// Returns the SOL received and the least SOL the sell may return, a
+    /// ClientError::BondingCurveComplete if the curve has completed, or a ClientError if the
+    /// operation fails
+    pub fn get_sell_quote(
+        &self,
+        mint: &Pubkey,
+        amount_token: u64,
+        slippage_basis_points: Option<u64>,
+    ) -> Result<quote::SellQuote, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Quote);
+        let (global_account, bonding_curve_account) = self.get_quote_accounts(mint)?;
+        quote::SellQuote::new(
+            &global_account,
+            &bonding_curve_account,
+            amount_token,
+            slippage_basis_points.unwrap_or(quote::DEFAULT_SLIPPAGE_BASIS_POINTS),
+        )
+    }
+
+    /// Summarizes a token's bonding curve for dashboards
+    ///
+    /// Creation data is taken from the attached summary enricher, then from creation data the
+    /// client has already cached (see [`PumpFun::get_token_age`]). It is left as None rather
+    /// than looked up, which would take a walk of the curve's history.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the summary, or a ClientError if the operation fails
+    pub fn get_curve_summary(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<summary::CurveSummary, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Quote);
+        let (global_account, bonding_curve_account) = self.get_quote_accounts(mint)?;
+        let mut curve_summary = summary::CurveSummary::new(
+            *mint,
+            &bonding_curve_account,
+            global_account.initial_real_token_reserves,
+        );
+        self.enrich_summary(&mut curve_summary);
+        curve_summary.graduation_eta =
+            self.estimate_graduation(mint, &bonding_curve_account, &global_account);
+        Ok(curve_summary)
+    }
+
+    /// Estimates when a curve completes from the attached tracker of its mint, if any
+    fn estimate_graduation(
+        &self,
+        mint: &Pubkey,
+        curve: &accounts::BondingCurveAccount,
+        global: &accounts::GlobalAccount,
+    ) -> Option<analytics::EtaEstimate> {
+        self.volume_trackers
+            .iter()
+            .map(|tracker| {
+                tracker
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+            })
+            .find(|tracker| tracker.mint() == Some(*mint))
+            .and_then(|tracker| analytics::graduation_eta(curve, global, &tracker))
+    }
+
+    /// Fills in creation data for a summary from the enricher and the client's caches
+    fn enrich_summary(&self, curve_summary: &mut summary::CurveSummary) {
+        if let Some(info) = self
+            .summary_enricher
+            .as_ref()
+            .and_then(|enricher| enricher.creation_info(&curve_summary.mint))
+        {
+            curve_summary.enrich(info);
+        }
+        if let Some(age) = self.get_cached_token_ages().get(&curve_summary.mint) {
+            curve_summary.enrich((*age).into());
+        }
+    }
+
+    /// Gets the global and bonding curve accounts at the quote commitment in one call
+    fn get_quote_accounts(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<(accounts::GlobalAccount, accounts::BondingCurveAccount), error::ClientError> {
+        let global_pda = Self::get_global_pda_with_seeds(&self.seeds);
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let accounts = self
+            .rpc
+            .get_multiple_accounts_with_commitment(
+                &[global_pda, bonding_curve_pda],
+                self.get_quote_commitment(),
+            )
+            .map_err(error::ClientError::SolanaClientError)?
+            .value;
+
+        let global_account = accounts[0]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Global account not found"))?;
+        let bonding_curve_account = accounts[1]
+            .as_ref()
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        Ok((
+            self.decode_account(&global_pda, &global_account.data)?,
+            self.decode_account(&bonding_curve_pda, &bonding_curve_account.data)?,
+        ))
+    }
+
+    /// Uploads token metadata and its image to IPFS, unless identical content was uploaded before
+    ///
+    /// Uploads are keyed by [`utils::metadata_content_hash`], so a retried launch reuses the URI
+    /// pinned by the first attempt instead of uploading again, which is slow and may pin the
+    /// same content at a different URI. Uploads are remembered for the lifetime of the client,
+    /// and across restarts when an immutable cache is attached, see
+    /// [`PumpFun::with_immutable_cache`]. The image is checked against the client's image
+    /// policy before uploading, see [`PumpFun::with_image_policy`].
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - Token metadata including name, symbol, description and image file
+    ///
+    /// # Returns
+    ///
+    /// Returns the uploaded metadata, flagged as cached if no upload was made, a
+    /// ClientError::ImageTooLarge if the image does not meet the client's image policy, or a
+    /// ClientError::UploadMetadataError if the image cannot be read or the upload fails
+    pub async fn upload_metadata(
+        &self,
+        metadata: utils::CreateTokenMetadata,
+    ) -> Result<utils::UploadedMetadata, error::ClientError> {
+        self.upload_metadata_with(metadata, utils::upload_token_metadata)
+            .await
+    }
+
+    /// Uploads token metadata through the given uploader, unless identical content was uploaded before
+    async fn upload_metadata_with<F, Fut>(
+        &self,
+        metadata: utils::CreateTokenMetadata,
+        upload: F,
+    ) -> Result<utils::UploadedMetadata, error::ClientError>
+    where
+        F: FnOnce(utils::CreateTokenMetadata, Vec<u8>) -> Fut,
+        Fut: std::future::Future<
+            Output = Result<utils::TokenMetadataResponse, Box<dyn std::error::Error>>,
+        >,
+    {
+        let content_hash = utils::metadata_content_hash(&metadata)
+            .map_err(|err| error::ClientError::UploadMetadataError(err.into()))?;
+        // The on-disk cache is keyed by account, and a hash is as unique as one
+        let key = Pubkey::new_from_array(content_hash.to_bytes());
+
+        let cached = self
+            .metadata_uploads
+            .lock()
+            .unwrap()
+            .get(&content_hash)
+            .cloned()
+            .or_else(|| self.cached_lookup(CACHE_METADATA_UPLOAD, &key));
+        if let Some(response) = cached {
+            tracing::debug!(%content_hash, uri = %response.metadata_uri, "Reusing uploaded metadata");
+            self.metadata_uploads
+                .lock()
+                .unwrap()
+                .insert(content_hash, response.clone());
+            return Ok(utils::UploadedMetadata {
+                response,
+                content_hash,
+                from_cache: true,
+            });
+        }
+
+        // Apply the image policy before anything is sent
+        let image = std::fs::read(&metadata.file)
+            .map_err(|err| error::ClientError::UploadMetadataError(err.into()))?;
+        let image = match &self.image_policy {
+            Some(policy) => utils::prepare_image(image, policy)?,
+            None => image,
+        };
+
+        let response = upload(metadata, image)
+            .await
+            .map_err(error::ClientError::UploadMetadataError)?;
+        self.metadata_uploads
+            .lock()
+            .unwrap()
+            .insert(content_hash, response.clone());
+        self.store_lookup(CACHE_METADATA_UPLOAD, &key, &response);
+        Ok(utils::UploadedMetadata {
+            response,
+            content_hash,
+            from_cache: false,
+        })
+    }
+
+    /// Creates a new token with metadata by uploading metadata to IPFS and initializing on-chain accounts
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint account that will be created
+    /// * `metadata` - Token metadata including name, symbol, description and image file
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn create(
+        &self,
+        mint: &Keypair,
+        metadata: utils::CreateTokenMetadata,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Create, async move {
+            self.create_with_instructions(mint, metadata, priority_fee, Vec::new())
+                .await
+        })
+        .await
+    }
+
+    /// Creates a new token, prefixing the launch transaction with caller-provided instructions
+    ///
+    /// The transaction is assembled as: priority fee instructions (if any), then
+    /// `extra_instructions` in the given order, then the create instruction. Compute budget
+    /// instructions should always come first; when supplying your own in `extra_instructions`,
+    /// put them at the front and pass `None` for `priority_fee`, as a transaction may only
+    /// contain one of each.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint account that will be created
+    /// * `metadata` - Token metadata to upload to IPFS
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `extra_instructions` - Instructions to execute atomically before the create instruction
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, a ClientError::UploadMetadataError if the
+    /// metadata upload fails, in which case nothing is sent, a ClientError::TransactionTooLarge if
+    /// the instructions don't fit in a single transaction, or a ClientError if the operation fails
+    pub async fn create_with_instructions(
+        &self,
+        mint: &Keypair,
+        metadata: utils::CreateTokenMetadata,
+        priority_fee: Option<PriorityFee>,
+        extra_instructions: Vec<Instruction>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Create, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(priority_fee)?;
+            // Make sure the mint is not already in use, here or on chain
+            let _reservation = self.reserve_mint(&mint.pubkey()).await?;
+
+            let instructions = self
+                .create_instructions(mint, metadata, priority_fee, extra_instructions)
+                .await?;
+
+            // Make sure everything fits in a single transaction
+            utils::validate_transaction_size(&instructions, &self.signer().pubkey())?;
+
+            let mut request = self.program.request();
+            for ix in instructions {
+                request = request.instruction(ix);
+            }
+
+            // Add the mint as a signer
+            request = request.signer(mint);
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[mint]).await?;
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, "Create confirmed");
+            self.track_fee_spend(&signature, Some(&mint.pubkey()), priority_fee)
+                .await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Builds the instructions creating a new token without signing or sending them
+    ///
+    /// Nothing is uploaded: the metadata URI in `args` is used as it is, so upload the metadata
+    /// first with [`PumpFun::upload_metadata`]. The instructions are the ones
+    /// [`PumpFun::create`] sends: priority fee instructions (if any), then the create
+    /// instruction. Both the payer and the mint keypair must sign the transaction they are sent
+    /// in. Unlike [`PumpFun::create`], the mint is not reserved, so nothing stops it from being
+    /// used by another launch in the meantime.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint account that will be created
+    /// * `args` - Name, symbol and metadata URI of the token
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the instructions, a ClientError::MissingDependencyProgram if the token metadata
+    /// program is not deployed, or a ClientError if the operation fails
+    pub fn create_ix(
+        &self,
+        mint: &Keypair,
+        args: cpi::instruction::Create,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        self.create_instructions_with_args(mint, args, priority_fee, Vec::new())
+    }
+
+    /// Uploads the metadata of a new token and builds the instructions creating it
+    async fn create_instructions(
+        &self,
+        mint: &Keypair,
+        metadata: utils::CreateTokenMetadata,
+        priority_fee: Option<PriorityFee>,
+        extra_instructions: Vec<Instruction>,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        // Upload metadata and image to IPFS first, so a failed upload leaves nothing on chain
+        let uploaded = self.upload_metadata(metadata).await?;
+        self.create_instructions_with_args(
+            mint,
+            cpi::instruction::Create {
+                _name: uploaded.response.metadata.name,
+                _symbol: uploaded.response.metadata.symbol,
+                _uri: uploaded.response.metadata_uri,
+            },
+            priority_fee,
+            extra_instructions,
+        )
+    }
+
+    /// Builds the instructions creating a new token from metadata that is already uploaded
+    fn create_instructions_with_args(
+        &self,
+        mint: &Keypair,
+        args: cpi::instruction::Create,
+        priority_fee: Option<PriorityFee>,
+        extra_instructions: Vec<Instruction>,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        self.ensure_program_deployed(
+            &constants::accounts::MPL_TOKEN_METADATA,
+            METADATA_PROGRAM_HINT,
+        )?;
+
+        let mut instructions = Vec::new();
+
+        // Add priority fee if provided
+        if let Some(fee) = priority_fee {
+            if let Some(limit) = fee.limit {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+            }
+
+            if let Some(price) = fee.price {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+            }
+        }
+
+        // Add caller-provided instructions in order
+        instructions.extend(extra_instructions);
+
+        // Add create token instruction
+        instructions.push(self.create_instruction(mint, args));
+
+        Ok(instructions)
+    }
+
+    /// Creates a new token and immediately buys an initial amount in a single atomic transaction
+    ///
+    /// Buying in the launch transaction leaves no window for others to buy before the creator.
+    /// The metadata is uploaded before anything is sent, and the bonding curve does not exist yet,
+    /// so the buy is quoted from the global account's initial reserves.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint
+    /// * `metadata` - Token metadata to upload to IPFS
+    /// * `amount_sol` - Amount of SOL to spend on initial buy in lamports
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn create_and_buy(
+        &self,
+        mint: &Keypair,
+        metadata: utils::CreateTokenMetadata,
+        amount_sol: u64,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Buy, async move {
+            self.create_and_buy_with_instructions(
+                mint,
+                metadata,
+                amount_sol,
+                slippage_basis_points,
+                priority_fee,
+                Vec::new(),
+            )
+            .await
+        })
+        .await
+    }
+
+    /// Creates a new token and buys an initial amount, prefixing the launch transaction with
+    /// caller-provided instructions
+    ///
+    /// The transaction is assembled as: priority fee instructions (if any), then
+    /// `extra_instructions` in the given order, then the create, associated token account and buy
+    /// instructions. Compute budget instructions should always come first; when supplying your
+    /// own in `extra_instructions`, put them at the front and pass `None` for `priority_fee`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint
+    /// * `metadata` - Token metadata to upload to IPFS
+    /// * `amount_sol` - Amount of SOL to spend on initial buy in lamports
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `extra_instructions` - Instructions to execute atomically before the create instruction
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, a ClientError::UploadMetadataError if the
+    /// metadata upload fails, in which case nothing is sent, a ClientError::TransactionTooLarge if
+    /// the instructions don't fit in a single transaction, or a ClientError if the operation fails
+    pub async fn create_and_buy_with_instructions(
+        &self,
+        mint: &Keypair,
+        metadata: utils::CreateTokenMetadata,
+        amount_sol: u64,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+        extra_instructions: Vec<Instruction>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Buy, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(priority_fee)?;
+            // Make sure the mint is not already in use, here or on chain
+            let _reservation = self.reserve_mint(&mint.pubkey()).await?;
+
+            // Upload metadata and image to IPFS first, so a failed upload leaves nothing on chain
+            let uploaded = self.upload_metadata(metadata).await?;
+            self.ensure_program_deployed(
+                &constants::accounts::MPL_TOKEN_METADATA,
+                METADATA_PROGRAM_HINT,
+            )?;
+
+            // The curve does not exist yet, so the buy is sized from the initial reserves
+            let global_account = self.get_global_account_async().await?;
+            let mut request = self.program.request();
+            for ix in self.create_and_buy_instructions(
+                mint,
+                &global_account,
+                cpi::instruction::Create {
+                    _name: uploaded.response.metadata.name,
+                    _symbol: uploaded.response.metadata.symbol,
+                    _uri: uploaded.response.metadata_uri,
+                },
+                amount_sol,
+                slippage_basis_points,
+                priority_fee,
+                extra_instructions,
+            )? {
+                request = request.instruction(ix);
+            }
+
+            // Add the mint as a signer
+            request = request.signer(mint);
+
+            // Make sure everything fits in a single transaction
+            let instructions = request
+                .instructions()
+                .map_err(error::ClientError::AnchorClientError)?;
+            utils::validate_transaction_size(&instructions, &self.signer().pubkey())?;
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[mint]).await?;
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, "Create and buy confirmed");
+            self.track_fee_spend(&signature, Some(&mint.pubkey()), priority_fee)
+                .await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Assembles the instructions of a launch transaction that buys from the new curve
+    ///
+    /// The bonding curve does not exist until the create instruction runs, so the buy is sized
+    /// with [`accounts::GlobalAccount::get_initial_buy_price`], which applies the curve math to
+    /// the initial reserves every new curve starts with.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint
+    /// * `global` - Global account the curve is created under
+    /// * `create` - Name, symbol and metadata URI of the new token
+    /// * `amount_sol` - Amount of SOL to spend on initial buy in lamports
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points. Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `extra_instructions` - Instructions to execute before the create instruction
+    ///
+    /// # Returns
+    ///
+    /// Returns the priority fee instructions, `extra_instructions`, then the create, associated
+    /// token account and buy instructions, or a ClientError if the program version cannot be
+    /// detected
+    #[allow(clippy::too_many_arguments)]
+    fn create_and_buy_instructions(
+        &self,
+        mint: &Keypair,
+        global: &accounts::GlobalAccount,
+        create: cpi::instruction::Create,
+        amount_sol: u64,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+        extra_instructions: Vec<Instruction>,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        let buy_amount = global.get_initial_buy_price(amount_sol);
+        let buy_amount_with_slippage = utils::calculate_with_slippage_buy(
+            amount_sol,
+            slippage_basis_points.unwrap_or(quote::DEFAULT_SLIPPAGE_BASIS_POINTS),
+        );
+
+        let mut instructions = Vec::new();
+
+        // Add priority fee if provided
+        if let Some(fee) = priority_fee {
+            if let Some(limit) = fee.limit {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+            }
+
+            if let Some(price) = fee.price {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+            }
+        }
+
+        // Add caller-provided instructions in order
+        instructions.extend(extra_instructions);
+
+        // Add create token instruction
+        instructions.push(self.create_instruction(mint, create));
+
+        // Create Associated Token Account for the new mint
+        let payer = self.signer().pubkey();
+        instructions.push(create_associated_token_account_idempotent(
+            &payer,
+            &payer,
+            &mint.pubkey(),
+            &constants::accounts::TOKEN_PROGRAM,
+        ));
+
+        // Add buy instruction, with the payer as the new token's creator
+        let accounts =
+            params::TradeAccounts::new(self.fee_recipient(None, global)).with_creator(payer);
+        instructions.push(self.buy_instruction(
+            &mint.pubkey(),
+            &accounts,
+            cpi::instruction::Buy {
+                _amount: buy_amount,
+                _max_sol_cost: buy_amount_with_slippage,
+            },
+        )?);
+
+        Ok(instructions)
+    }
+
+    /// Buys tokens from a bonding curve by spending SOL
+    ///
+    /// Shorthand for [`PumpFun::buy_with`] with the options most buys set.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to buy
+    /// * `amount_sol` - Amount of SOL to spend in lamports
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn buy(
+        &self,
+        mint: &Pubkey,
+        amount_sol: u64,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        self.buy_with_slippage_mode(
+            mint,
+            amount_sol,
+            slippage_basis_points.map(utils::SlippageMode::from),
+            priority_fee,
+        )
+        .await
+    }
+
+    /// Buys tokens from a bonding curve by spending SOL, bounding the cost with a slippage mode
+    ///
+    /// Same as [`PumpFun::buy`], with the slippage bound given as a [`utils::SlippageMode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to buy
+    /// * `amount_sol` - Amount of SOL to spend in lamports
+    /// * `slippage` - Optional slippage mode bounding the SOL cost, either in basis points over the
+    ///   quote or as a maximum multiple of it. Defaults to 500 basis points
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn buy_with_slippage_mode(
+        &self,
+        mint: &Pubkey,
+        amount_sol: u64,
+        slippage: Option<utils::SlippageMode>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        let mut params = params::BuyParams::new(*mint, amount_sol);
+        params.slippage = slippage;
+        params.priority_fee = priority_fee;
+        self.buy_with(params).await
+    }
+
+    /// Buys tokens from a bonding curve by spending SOL, with the options set in `params`
+    ///
+    /// The transaction is assembled as: priority fee instructions (if any), then the extra
+    /// instructions in the given order, then the associated token account (if needed) and buy
+    /// instructions, and last the memo (if any). Without confirmation, the signature is returned once the node accepts the
+    /// transaction, and its priority fee is not tracked.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, SOL amount and options of the buy
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, a ClientError::TransactionTooLarge if the
+    /// extra instructions don't fit in a single transaction, a ClientError::MemoTooLong if the
+    /// memo doesn't fit alongside them, or a ClientError if the operation fails
+    pub async fn buy_with(
+        &self,
+        mut params: params::BuyParams,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Buy, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(params.priority_fee)?;
+            params.priority_fee = priority_fee;
+            let mint = &params.mint;
+            let request = self.buy_request(&params).await?;
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[]).await?;
+            if !params.confirm {
+                let (signature, _, _) = self.submit_request(&request, transaction).await?;
+                tracing::debug!(%trade_id, %signature, "Buy sent");
+                return Ok(signature);
+            }
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, "Buy confirmed");
+            self.track_fee_spend(&signature, Some(mint), priority_fee)
+                .await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Builds the instructions of a buy without signing or sending them
+    ///
+    /// These are the instructions [`PumpFun::buy_with`] sends, with the same amounts and
+    /// slippage bound: priority fee instructions (if any), then the extra instructions in the
+    /// given order, then the associated token account (if needed) and buy instructions, and last
+    /// the memo (if any). The payer must sign the transaction they are sent in.
+    ///
+    /// To send several trades in one atomic transaction, set the priority fee on only one of
+    /// them, as a transaction may only contain one of each compute budget instruction. The fee
+    /// budget is not applied and the spend of the transaction is not tracked, see
+    /// [`PumpFun::with_fee_spend_tracking`].
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, SOL amount and options of the buy
+    ///
+    /// # Returns
+    ///
+    /// Returns the instructions, a ClientError::MemoTooLong if the memo doesn't fit alongside
+    /// them, or a ClientError if the accounts cannot be fetched or the curve is complete
+    pub fn buy_ix(
+        &self,
+        params: &params::BuyParams,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        // Resolve the signer once, so every instruction is built for the same keypair
+        signers::sync_scoped(self.signer_provider.as_ref(), || {
+            let mint = &params.mint;
+            let global_account = self.get_global_account()?;
+            let bonding_curve_account = self.get_bonding_curve_account(mint)?;
+            let token_program = self.get_token_program(mint)?;
+            let ata: Pubkey =
+                instruction::derive_user_ata(&self.signer().pubkey(), mint, &token_program);
+            let ata_exists = self.rpc.get_account(&ata).is_ok();
+
+            self.buy_instructions(
+                params,
+                &global_account,
+                &bonding_curve_account,
+                token_program,
+                ata_exists,
+            )
+        })
+    }
+
+    /// Builds the instructions of a buy from the accounts it is quoted against
+    fn buy_instructions(
+        &self,
+        params: &params::BuyParams,
+        global_account: &accounts::GlobalAccount,
+        bonding_curve_account: &accounts::BondingCurveAccount,
+        token_program: Pubkey,
+        ata_exists: bool,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        let mint = &params.mint;
+        let payer = self.signer().pubkey();
+        // Calculate buy amounts
+        let buy_amount = bonding_curve_account
+            .get_buy_price(params.sol_amount)
+            .map_err(error::ClientError::BondingCurveError)?;
+        let buy_amount_with_slippage = params
+            .slippage
+            .unwrap_or_default()
+            .max_sol_cost(params.sol_amount);
+
+        let mut instructions = Vec::new();
+
+        // Add priority fee if provided
+        if let Some(fee) = params.priority_fee {
+            if let Some(limit) = fee.limit {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+            }
+
+            if let Some(price) = fee.price {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+            }
+        }
+
+        // Add caller-provided instructions in order
+        instructions.extend(params.extra_instructions.iter().cloned());
+
+        // Create Associated Token Account if needed, tolerating a concurrent create
+        if !ata_exists {
+            instructions.push(create_associated_token_account_idempotent(
+                &payer,
+                &payer,
+                mint,
+                &token_program,
+            ));
+        }
+
+        // Add buy instruction
+        instructions.push(
+            self.buy_instruction(
+                mint,
+                &params::TradeAccounts::new(
+                    self.fee_recipient(params.fee_recipient, global_account),
+                )
+                .with_token_program(token_program),
+                cpi::instruction::Buy {
+                    _amount: buy_amount,
+                    _max_sol_cost: buy_amount_with_slippage,
+                },
+            )?,
+        );
+
+        // Add memo last, once the space it may take is known
+        if let Some(memo) = &params.memo {
+            utils::validate_memo(&instructions, memo, &payer)?;
+            instructions.push(instruction::memo(memo));
+        }
+
+        Ok(instructions)
+    }
+
+    /// Sells tokens back to the bonding curve in exchange for SOL
+    ///
+    /// Shorthand for [`PumpFun::sell_with`] with the options most sells set.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to sell
+    /// * `amount_token` - Optional amount of tokens to sell in base units. If None, sells entire balance
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn sell(
+        &self,
+        mint: &Pubkey,
+        amount_token: Option<u64>,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        let mut params = params::SellParams::new(*mint);
+        params.token_amount = amount_token;
+        params.slippage_basis_points = slippage_basis_points;
+        params.priority_fee = priority_fee;
+        self.sell_with(params).await
+    }
+
+    /// Sells tokens back to the bonding curve in exchange for SOL, with the options set in
+    /// `params`
+    ///
+    /// The transaction is assembled as: priority fee instructions (if any), then the extra
+    /// instructions in the given order, then the sell instruction, and last the memo (if any).
+    /// Without confirmation, the signature is returned once the node accepts the transaction,
+    /// and its priority fee is not tracked.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, amount and options of the sell
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, a ClientError::TransactionTooLarge if the
+    /// extra instructions don't fit in a single transaction, a ClientError::MemoTooLong if the
+    /// memo doesn't fit alongside them, or a ClientError if the operation fails
+    pub async fn sell_with(
+        &self,
+        mut params: params::SellParams,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Sell, async move {
+            let trade_id = self.next_trade_id();
+            params.priority_fee = self.apply_fee_budget(params.priority_fee)?;
+            let (request, _) = self.sell_request(&params, None).await?;
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[]).await?;
+            if !params.confirm {
+                let (signature, _, _) = self.submit_request(&request, transaction).await?;
+                tracing::debug!(%trade_id, %signature, "Sell sent");
+                return Ok(signature);
+            }
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, "Sell confirmed");
+            self.track_fee_spend(&signature, Some(&params.mint), params.priority_fee)
+                .await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Sells tokens back to the bonding curve and returns the confirmed fill
+    ///
+    /// Behaves like [`PumpFun::sell`], then fetches the confirmed transaction and decodes its
+    /// `TradeEvent`. The time spent in each stage of the trade is recorded in the outcome. When
+    /// `verify_min_sol_output` is set, the SOL amount reported by the event is compared against
+    /// the minimum sent with the instruction, as a defensive check against decoding or program
+    /// version mismatches; the on-chain program enforces the same bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to sell
+    /// * `amount_token` - Optional amount of tokens to sell in base units. If None, sells entire balance
+    /// * `slippage_basis_points` - Optional maximum acceptable slippage in basis points (1 bp = 0.01%). Defaults to 500
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    /// * `verify_min_sol_output` - Whether to check the fill against the minimum SOL output
+    ///
+    /// # Returns
+    ///
+    /// Returns the trade outcome if successful, a ClientError::FillBelowExpected if the check is
+    /// enabled and the fill was below the minimum, or a ClientError if the operation fails
+    pub async fn sell_with_result(
+        &self,
+        mint: &Pubkey,
+        amount_token: Option<u64>,
+        slippage_basis_points: Option<u64>,
+        priority_fee: Option<PriorityFee>,
+        verify_min_sol_output: bool,
+    ) -> Result<TradeOutcome, error::ClientError> {
+        let mut params = params::SellParams::new(*mint);
+        params.token_amount = amount_token;
+        params.slippage_basis_points = slippage_basis_points;
+        params.priority_fee = priority_fee;
+        self.sell_with_outcome(params, verify_min_sol_output).await
+    }
+
+    /// Sells tokens back to the bonding curve with the options set in `params`, and returns the
+    /// confirmed fill
+    ///
+    /// Same as [`PumpFun::sell_with_result`], with every option of [`PumpFun::sell_with`]. The
+    /// trade is always confirmed, whatever `params.confirm` is set to. The memo of the
+    /// confirmed transaction is returned in the outcome.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, amount and options of the sell
+    /// * `verify_min_sol_output` - Whether to check the fill against the minimum SOL output
+    ///
+    /// # Returns
+    ///
+    /// Returns the trade outcome if successful, a ClientError::FillBelowExpected if the check is
+    /// enabled and the fill was below the minimum, a ClientError::MemoTooLong if the memo doesn't
+    /// fit alongside the other instructions, or a ClientError if the operation fails
+    pub async fn sell_with_outcome(
+        &self,
+        mut params: params::SellParams,
+        verify_min_sol_output: bool,
+    ) -> Result<TradeOutcome, error::ClientError> {
+        self.trade_scoped(usage::Operation::Sell, async move {
+            let trade_id = self.next_trade_id();
+            params.priority_fee = self.apply_fee_budget(params.priority_fee)?;
+            let mut timer = timings::StageTimer::start();
+            let (request, min_sol_output) = self.sell_request(&params, Some(&mut timer)).await?;
+            let outcome = self
+                .confirm_outcome(trade_id, &request, timer, &params.mint, params.priority_fee)
+                .await?;
+
+            if verify_min_sol_output {
+                let actual = outcome
+                    .trade_event
+                    .as_ref()
+                    .map(|event| event.sol_amount)
+                    .ok_or(error::ClientError::InvalidInput("Trade event not found"))?;
+                if actual < min_sol_output {
+                    return Err(error::ClientError::FillBelowExpected {
+                        expected: min_sol_output,
+                        actual,
+                    });
+                }
+            }
+
+            tracing::debug!(%trade_id, signature = %outcome.signature, "Sell confirmed");
+            Ok(outcome)
+        })
+        .await
+    }
+
+    /// Buys tokens from a bonding curve with the options set in `params`, and returns the
+    /// confirmed fill
+    ///
+    /// Behaves like [`PumpFun::buy_with`], then fetches the confirmed transaction and decodes its
+    /// `TradeEvent`. The trade is always confirmed, whatever `params.confirm` is set to. The time
+    /// spent in each stage of the trade and the memo of the confirmed transaction are returned in
+    /// the outcome.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, SOL amount and options of the buy
+    ///
+    /// # Returns
+    ///
+    /// Returns the trade outcome if successful, a ClientError::MemoTooLong if the memo doesn't
+    /// fit alongside the other instructions, or a ClientError if the operation fails
+    pub async fn buy_with_outcome(
+        &self,
+        mut params: params::BuyParams,
+    ) -> Result<TradeOutcome, error::ClientError> {
+        self.trade_scoped(usage::Operation::Buy, async move {
+            let trade_id = self.next_trade_id();
+            params.priority_fee = self.apply_fee_budget(params.priority_fee)?;
+            let mut timer = timings::StageTimer::start();
+            let request = self.buy_request(&params).await?;
+            timer.record(timings::Stage::Built);
+            let outcome = self
+                .confirm_outcome(trade_id, &request, timer, &params.mint, params.priority_fee)
+                .await?;
+
+            tracing::debug!(%trade_id, signature = %outcome.signature, "Buy confirmed");
+            Ok(outcome)
+        })
+        .await
+    }
+
+    /// Signs, sends and confirms a trade request, then reads its fill and memo back from the
+    /// confirmed transaction
+    ///
+    /// Sign, send and confirm are separate so each stage can be timed.
+    #[allow(clippy::redundant_allocation)]
+    async fn confirm_outcome(
+        &self,
+        trade_id: trade_id::TradeId,
+        request: &RequestBuilder<'_, Rc<&'a Keypair>>,
+        mut timer: timings::StageTimer,
+        mint: &Pubkey,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<TradeOutcome, error::ClientError> {
+        let transaction = self.sign_request(request, &[]).await?;
+        timer.record(timings::Stage::Signed);
+
+        timer.record(timings::Stage::Sent);
+        let (signature, transaction, submit_path) =
+            self.submit_request(request, transaction).await?;
+        timer.record(timings::Stage::Acknowledged);
+
+        let rpc = self.async_rpc();
+        rpc.poll_for_signature_with_commitment(&signature, rpc.commitment())
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+        if let Some(Err(err)) = rpc
+            .get_signature_status_with_commitment(&signature, rpc.commitment())
+            .await
+            .map_err(error::ClientError::SolanaClientError)?
+        {
+            return Err(error::ClientError::from_transaction_failure(
+                err.into(),
+                &VersionedMessage::Legacy(transaction.message),
+            ));
+        }
+        timer.record(timings::Stage::Confirmed);
+        let sent_transaction = self.return_transaction.then_some(transaction);
+
+        let (trade_event, memo) = self.read_fill(&signature, mint, priority_fee).await?;
+
+        Ok(TradeOutcome {
+            trade_id,
+            signature,
+            trade_event,
+            timings: timer.timings(),
+            transaction: sent_transaction,
+            submit_path,
+            memo,
+        })
+    }
+
+    /// Reads the payer's fill and the memo of a confirmed trade, and records its fee spend
+    ///
+    /// The confirmed transaction has both the fill and the compute units consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the confirmed transaction
+    /// * `mint` - Public key of the traded token mint
+    /// * `priority_fee` - Priority fee the trade was sent with
+    ///
+    /// # Returns
+    ///
+    /// Returns the trade event, if found, and the first memo of the transaction, if any, or a
+    /// ClientError if the transaction could not be fetched
+    async fn read_fill(
+        &self,
+        signature: &Signature,
+        mint: &Pubkey,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<(Option<events::TradeEvent>, Option<String>), error::ClientError> {
+        let confirmed = self.get_confirmed_transaction_async(signature).await?;
+        let meta = confirmed.transaction.meta.as_ref();
+        self.record_fee_spend(Some(mint), priority_fee, meta);
+        let trade_event = self.find_trade_event(meta, mint);
+        let memo = confirmed
+            .transaction
+            .transaction
+            .decode()
+            .and_then(|decoded| utils::extract_memos(&decoded.message).into_iter().next());
+
+        Ok((trade_event, memo))
+    }
+
+    /// Executes a trade plan, unless the cached curve has moved past it
+    ///
+    /// The plan is first checked against the cache it was built from, see
+    /// [`plan::TradePlan::check_fresh`], then sent with the amounts and slippage bound it fixed.
+    /// Buys create the payer's token account if needed.
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - Plan to execute
+    /// * `cache` - Cache holding the latest state of the plan's curve
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, a ClientError::StalePlan if the cached
+    /// curve moved more than the slot budget past the plan, or a ClientError if the operation
+    /// fails
+    pub async fn execute(
+        &self,
+        plan: &plan::TradePlan,
+        cache: &refresh::CurveCache,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        let operation = match plan.side {
+            journal::TradeSide::Buy => usage::Operation::Buy,
+            journal::TradeSide::Sell => usage::Operation::Sell,
+        };
+        self.trade_scoped(operation, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(priority_fee)?;
+            plan.check_fresh(cache, self.plan_slot_budget)?;
+
+            let (global_account, token_program) = futures::try_join!(
+                self.get_global_account_async(),
+                self.get_token_program_async(&plan.mint),
+            )?;
+            let accounts = params::TradeAccounts::new(self.fee_recipient(None, &global_account))
+                .with_token_program(token_program);
+            let mut request = self.program.request();
+
+            // Add priority fee if provided
+            if let Some(fee) = priority_fee {
+                if let Some(limit) = fee.limit {
+                    let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
+                    request = request.instruction(limit_ix);
+                }
+
+                if let Some(price) = fee.price {
+                    let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
+                    request = request.instruction(price_ix);
+                }
+            }
+
+            request = match plan.side {
+                journal::TradeSide::Buy => {
+                    // Create Associated Token Account if needed, tolerating a concurrent create
+                    let payer = self.signer().pubkey();
+                    let ata: Pubkey =
+                        instruction::derive_user_ata(&payer, &plan.mint, &token_program);
+                    if self.async_rpc().get_account(&ata).await.is_err() {
+                        request = request.instruction(create_associated_token_account_idempotent(
+                            &payer,
+                            &payer,
+                            &plan.mint,
+                            &token_program,
+                        ));
+                    }
+
+                    request.instruction(self.buy_instruction(
+                        &plan.mint,
+                        &accounts,
+                        cpi::instruction::Buy {
+                            _amount: plan.expected_out,
+                            _max_sol_cost: plan.limit,
+                        },
+                    )?)
+                }
+                journal::TradeSide::Sell => request.instruction(self.sell_instruction(
+                    &plan.mint,
+                    &accounts,
+                    cpi::instruction::Sell {
+                        _amount: plan.amount_in,
+                        _min_sol_output: plan.limit,
+                    },
+                )?),
+            };
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[]).await?;
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, side = ?plan.side, "Plan executed");
+            self.track_fee_spend(&signature, Some(&plan.mint), priority_fee)
+                .await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Signs the instructions of a request with the client's signer and any extra signers
+    ///
+    /// The blockhash is fetched through the client's own RPC client, rather than the one the
+    /// Anchor program was created with, so the request is counted in [`PumpFun::rpc_usage`] and
+    /// goes through the client's transport like every other one.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Request holding the instructions to sign
+    /// * `signers` - Signers required besides the payer, such as a new mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the signed transaction, or a ClientError if the blockhash cannot be fetched
+    #[allow(clippy::redundant_allocation)]
+    async fn sign_request(
+        &self,
+        request: &RequestBuilder<'_, Rc<&'a Keypair>>,
+        signers: &[&dyn Signer],
+    ) -> Result<Transaction, error::ClientError> {
+        let instructions = request
+            .instructions()
+            .map_err(error::ClientError::AnchorClientError)?;
+        let blockhash = self
+            .async_rpc()
+            .get_latest_blockhash()
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+        let payer = self.signer();
+        let mut all_signers: Vec<&dyn Signer> = vec![&*payer];
+        all_signers.extend_from_slice(signers);
+
+        Ok(Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &all_signers,
+            blockhash,
+        ))
+    }
+
+    /// Reads whether a transaction that failed to send or confirm was processed anyway
+    ///
+    /// The status is read at processed commitment and searched in the transaction history, so a
+    /// transaction that landed before the error was reported is found. An error reading the
+    /// status counts as not found.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the outcome of the transaction if it was processed, or None if it was not found
+    async fn processed_status(
+        &self,
+        signature: &Signature,
+    ) -> Option<Result<(), solana_sdk::transaction::TransactionError>> {
+        self.async_rpc()
+            .get_signature_status_with_commitment_and_history(
+                signature,
+                CommitmentConfig::processed(),
+                true,
+            )
+            .await
+            .ok()
+            .flatten()
+    }
+
+    /// Sends a signed request, reacting to each class of submission error
+    ///
+    /// Expired blockhashes are handled by re-signing the request, which fetches a fresh one, and
+    /// transient node or connection errors by resending the same transaction after a short
+    /// delay. A transaction the node reports as already processed landed on an earlier attempt
+    /// and counts as sent. Before re-signing, the status of the transaction sent so far is read,
+    /// since an attempt that failed transiently may have landed anyway, and re-signing it would
+    /// trade twice. See [`submit::SubmitErrorKind`].
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Request the transaction was signed from
+    /// * `transaction` - Signed transaction to send first
+    ///
+    /// # Returns
+    ///
+    /// Returns the signature and the transaction that was accepted with the handling it needed,
+    /// or a ClientError if the transaction was rejected or every attempt failed
+    #[allow(clippy::redundant_allocation)]
+    async fn submit_request(
+        &self,
+        request: &RequestBuilder<'_, Rc<&'a Keypair>>,
+        mut transaction: Transaction,
+    ) -> Result<(Signature, Transaction, submit::SubmitPath), error::ClientError> {
+        self.ensure_trading_allowed()?;
+        let mut path = submit::SubmitPath::default();
+        loop {
+            path.attempts += 1;
+            let err = match self.async_rpc().send_transaction(&transaction).await {
+                Ok(signature) => return Ok((signature, transaction, path)),
+                Err(err) => err,
+            };
+
+            let kind = submit::SubmitErrorKind::classify(&err);
+            tracing::debug!(?kind, attempt = path.attempts, "Submission failed: {}", err);
+            match kind {
+                submit::SubmitErrorKind::AlreadyProcessed => {
+                    path.already_processed = true;
+                    return Ok((transaction.signatures[0], transaction, path));
+                }
+                _ if path.attempts >= submit::MAX_SUBMIT_ATTEMPTS => {
+                    return Err(error::ClientError::from_transaction_failure(
+                        err,
+                        &VersionedMessage::Legacy(transaction.message),
+                    ))
+                }
+                submit::SubmitErrorKind::BlockhashNotFound => {
+                    match self.processed_status(&transaction.signatures[0]).await {
+                        Some(Ok(())) => {
+                            path.already_processed = true;
+                            return Ok((transaction.signatures[0], transaction, path));
+                        }
+                        Some(Err(tx_err)) => {
+                            return Err(error::ClientError::from_transaction_failure(
+                                tx_err.into(),
+                                &VersionedMessage::Legacy(transaction.message),
+                            ))
+                        }
+                        None => {}
+                    }
+                    path.rebuilds += 1;
+                    transaction = self.sign_request(request, &[]).await?;
+                }
+                submit::SubmitErrorKind::Transient => {
+                    path.resends += 1;
+                    tokio::time::sleep(submit::SUBMIT_RETRY_DELAY * path.attempts).await;
+                }
+                submit::SubmitErrorKind::Fatal => {
+                    return Err(error::ClientError::from_transaction_failure(
+                        err,
+                        &VersionedMessage::Legacy(transaction.message),
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Builds a buy instruction from amounts and accounts the caller already has
+    ///
+    /// Unlike [`PumpFun::buy_ix`], nothing is quoted or fetched: the accounts of the instruction
+    /// are derived from the client's seeds and signer, and converted to the client's program
+    /// version. Only an undetected program version, or a missing creator on versions with
+    /// creator vaults, costs a request. No associated token account is created, see
+    /// [`PumpFun::buy_ix`] for the full set of instructions.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to buy
+    /// * `accounts` - Fee recipient, token program and creator of the trade
+    /// * `args` - Token amount and maximum SOL cost of the buy
+    ///
+    /// # Returns
+    ///
+    /// Returns the buy instruction, or a ClientError if the program version or the creator
+    /// cannot be fetched
+    pub fn buy_instruction(
+        &self,
+        mint: &Pubkey,
+        accounts: &params::TradeAccounts,
+        args: cpi::instruction::Buy,
+    ) -> Result<Instruction, error::ClientError> {
+        self.apply_program_version(
+            instruction::buy_with_token_program(
+                &self.seeds,
+                &accounts.token_program,
+                &self.signer(),
+                mint,
+                &accounts.fee_recipient,
+                args,
+            ),
+            mint,
+            accounts.creator,
+        )
+    }
+
+    /// Builds a sell instruction from amounts and accounts the caller already has
+    ///
+    /// Unlike [`PumpFun::sell_ix`], nothing is quoted or fetched: the accounts of the instruction
+    /// are derived from the client's seeds and signer, and converted to the client's program
+    /// version. Only an undetected program version, or a missing creator on versions with
+    /// creator vaults, costs a request.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to sell
+    /// * `accounts` - Fee recipient, token program and creator of the trade
+    /// * `args` - Token amount and minimum SOL output of the sell
+    ///
+    /// # Returns
+    ///
+    /// Returns the sell instruction, or a ClientError if the program version or the creator
+    /// cannot be fetched
+    pub fn sell_instruction(
+        &self,
+        mint: &Pubkey,
+        accounts: &params::TradeAccounts,
+        args: cpi::instruction::Sell,
+    ) -> Result<Instruction, error::ClientError> {
+        self.apply_program_version(
+            instruction::sell_with_token_program(
+                &self.seeds,
+                &accounts.token_program,
+                &self.signer(),
+                mint,
+                &accounts.fee_recipient,
+                args,
+            ),
+            mint,
+            accounts.creator,
+        )
+    }
+
+    /// Builds a create instruction from metadata that is already uploaded
+    ///
+    /// Nothing is uploaded: the URI in `args` is used as it is. Unlike [`PumpFun::create_ix`],
+    /// no priority fee instructions are added. Both the payer and the mint keypair must sign
+    /// the transaction it is sent in. The mint is created under the classic token program, as
+    /// the program's `create` instruction expects; forks creating mints of another token
+    /// program can build the instruction with [`instruction::create_with_token_program`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Keypair for the new token mint account that will be created
+    /// * `args` - Name, symbol and metadata URI of the token
+    ///
+    /// # Returns
+    ///
+    /// Returns the create instruction
+    pub fn create_instruction(
+        &self,
+        mint: &Keypair,
+        args: cpi::instruction::Create,
+    ) -> Instruction {
+        instruction::create_with_seeds(&self.seeds, &self.signer(), mint, args)
+    }
+
+    /// Builds the instructions of a sell without signing or sending them
+    ///
+    /// These are the instructions [`PumpFun::sell_with`] sends, with the same amounts and
+    /// slippage bound: priority fee instructions (if any), then the extra instructions in the
+    /// given order, then the sell instruction, and last the memo (if any). The payer must sign
+    /// the transaction they are sent in.
+    ///
+    /// To send several trades in one atomic transaction, set the priority fee on only one of
+    /// them, as a transaction may only contain one of each compute budget instruction. Without
+    /// a token amount the payer's current balance is sold, so set the amount when selling
+    /// tokens bought earlier in the same transaction. The fee budget is not applied and the
+    /// spend of the transaction is not tracked, see [`PumpFun::with_fee_spend_tracking`].
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Token, amount and options of the sell
+    ///
+    /// # Returns
+    ///
+    /// Returns the instructions, a ClientError::MemoTooLong if the memo doesn't fit alongside
+    /// them, or a ClientError if the accounts cannot be fetched or the curve is complete
+    pub fn sell_ix(
+        &self,
+        params: &params::SellParams,
+    ) -> Result<Vec<Instruction>, error::ClientError> {
+        // Resolve the signer once, so every instruction is built for the same keypair
+        signers::sync_scoped(self.signer_provider.as_ref(), || {
+            let mint = &params.mint;
+            let token_program = self.get_token_program(mint)?;
+            let ata: Pubkey =
+                instruction::derive_user_ata(&self.signer().pubkey(), mint, &token_program);
+            let balance = self
+                .rpc
+                .get_token_account_balance(&ata)
+                .map_err(error::ClientError::SolanaClientError)?;
+            let global_account = self.get_global_account()?;
+            let bonding_curve_account = self.get_bonding_curve_account(mint)?;
+
+            let (instructions, _) = self.sell_instructions(
+                params,
+                &global_account,
+                &bonding_curve_account,
+                token_program,
+                &balance,
+            )?;
+            Ok(instructions)
+        })
+    }
+
+    /// Builds the instructions of a sell and the minimum SOL output it enforces
+    fn sell_instructions(
+        &self,
+        params: &params::SellParams,
+        global_account: &accounts::GlobalAccount,
+        bonding_curve_account: &accounts::BondingCurveAccount,
+        token_program: Pubkey,
+        balance: &UiTokenAmount,
+    ) -> Result<(Vec<Instruction>, u64), error::ClientError> {
+        let mint = &params.mint;
+        // Calculate sell amounts
+        let balance_u64: u64 = balance
+            .amount
+            .parse::<u64>()
+            .map_err(|_| error::ClientError::InvalidInput("Invalid token account balance"))?;
+        let _amount = params.token_amount.unwrap_or(balance_u64);
+        let min_sol_output = bonding_curve_account
+            .get_sell_price(_amount, global_account.total_fee_bps())
+            .map_err(error::ClientError::BondingCurveError)?;
+        let _min_sol_output = utils::calculate_with_slippage_sell(
+            min_sol_output,
+            params
+                .slippage_basis_points
+                .unwrap_or(quote::DEFAULT_SLIPPAGE_BASIS_POINTS),
+        );
+
+        let mut instructions = Vec::new();
+
+        // Add priority fee if provided
+        if let Some(fee) = params.priority_fee {
+            if let Some(limit) = fee.limit {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+            }
+
+            if let Some(price) = fee.price {
+                instructions.push(ComputeBudgetInstruction::set_compute_unit_price(price));
+            }
+        }
+
+        // Add caller-provided instructions in order
+        instructions.extend(params.extra_instructions.iter().cloned());
+
+        // Add sell instruction
+        instructions.push(
+            self.sell_instruction(
+                mint,
+                &params::TradeAccounts::new(
+                    self.fee_recipient(params.fee_recipient, global_account),
+                )
+                .with_token_program(token_program),
+                cpi::instruction::Sell {
+                    _amount,
+                    _min_sol_output,
+                },
+            )?,
+        );
+
+        // Add memo last, once the space it may take is known
+        if let Some(memo) = &params.memo {
+            utils::validate_memo(&instructions, memo, &self.signer().pubkey())?;
+            instructions.push(instruction::memo(memo));
+        }
+
+        Ok((instructions, _min_sol_output))
+    }
+
+    /// Builds the request for a buy, fetching the accounts it is quoted against concurrently
+    #[allow(clippy::redundant_allocation)]
+    async fn buy_request(
+        &self,
+        params: &params::BuyParams,
+    ) -> Result<RequestBuilder<'_, Rc<&'a Keypair>>, error::ClientError> {
+        let mint = &params.mint;
+        let (global_account, bonding_curve_account, token_program) = futures::try_join!(
+            self.get_global_account_async(),
+            self.get_bonding_curve_account_async(mint),
+            self.get_token_program_async(mint),
+        )?;
+        let ata: Pubkey =
+            instruction::derive_user_ata(&self.signer().pubkey(), mint, &token_program);
+        let ata_exists = self.async_rpc().get_account(&ata).await.is_ok();
+        let instructions = self.buy_instructions(
+            params,
+            &global_account,
+            &bonding_curve_account,
+            token_program,
+            ata_exists,
+        )?;
+
+        // Make sure everything fits in a single transaction
+        if !params.extra_instructions.is_empty() || params.memo.is_some() {
+            utils::validate_transaction_size(&instructions, &self.signer().pubkey())?;
+        }
+
+        let mut request = self.program.request();
+        for ix in instructions {
+            request = request.instruction(ix);
+        }
+
+        Ok(request)
+    }
+
+    /// Builds the request for a sell and the minimum SOL output it enforces, fetching the
+    /// accounts it is quoted against concurrently
+    #[allow(clippy::redundant_allocation)]
+    async fn sell_request(
+        &self,
+        params: &params::SellParams,
+        mut timer: Option<&mut timings::StageTimer>,
+    ) -> Result<(RequestBuilder<'_, Rc<&'a Keypair>>, u64), error::ClientError> {
+        let mint = &params.mint;
+        let token_program = self.get_token_program_async(mint).await?;
+        let ata: Pubkey =
+            instruction::derive_user_ata(&self.signer().pubkey(), mint, &token_program);
+        let (balance, global_account, bonding_curve_account) = futures::try_join!(
+            async {
+                self.async_rpc()
+                    .get_token_account_balance(&ata)
+                    .await
+                    .map_err(error::ClientError::SolanaClientError)
+            },
+            self.get_global_account_async(),
+            self.get_bonding_curve_account_async(mint),
+        )?;
+        if let Some(timer) = timer.as_mut() {
+            timer.record(timings::Stage::AccountsFetched);
+        }
+        let (instructions, min_sol_output) = self.sell_instructions(
+            params,
+            &global_account,
+            &bonding_curve_account,
+            token_program,
+            &balance,
+        )?;
+
+        // Make sure everything fits in a single transaction
+        if !params.extra_instructions.is_empty() || params.memo.is_some() {
+            utils::validate_transaction_size(&instructions, &self.signer().pubkey())?;
+        }
+
+        let mut request = self.program.request();
+        for ix in instructions {
+            request = request.instruction(ix);
+        }
+
+        if let Some(timer) = timer.as_mut() {
+            timer.record(timings::Stage::Built);
+        }
+
+        Ok((request, min_sol_output))
+    }
+
+    /// Gets the payer's trade event for a mint from a confirmed transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the confirmed transaction
+    /// * `mint` - Public key of the traded token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(event) if the transaction emitted a trade event for the payer and mint, None
+    /// if it did not, or a ClientError if the transaction could not be fetched
+    fn get_trade_event(
+        &self,
+        signature: &Signature,
+        mint: &Pubkey,
+    ) -> Result<Option<events::TradeEvent>, error::ClientError> {
+        let transaction = self.get_confirmed_transaction(signature)?;
+        Ok(self.find_trade_event(transaction.transaction.meta.as_ref(), mint))
+    }
+
+    /// Fetches a confirmed transaction with its status metadata
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the confirmed transaction
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction, or a ClientError if it could not be fetched
+    fn get_confirmed_transaction(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, error::ClientError> {
+        self.rpc
+            .get_transaction_with_config(signature, self.confirmed_transaction_config())
+            .map_err(error::ClientError::SolanaClientError)
+    }
+
+    /// Fetches a confirmed transaction with its status metadata without blocking the executor
+    ///
+    /// Same as [`PumpFun::get_confirmed_transaction`], for use from the trade flows.
+    async fn get_confirmed_transaction_async(
+        &self,
+        signature: &Signature,
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, error::ClientError> {
+        self.async_rpc()
+            .get_transaction_with_config(signature, self.confirmed_transaction_config())
+            .await
+            .map_err(error::ClientError::SolanaClientError)
+    }
+
+    /// Gets the configuration confirmed transactions are fetched with
+    fn confirmed_transaction_config(&self) -> RpcTransactionConfig {
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.get_history_commitment()),
+            max_supported_transaction_version: Some(0),
+        }
+    }
+
+    /// Finds the payer's trade event for a mint in the logs of a confirmed transaction
+    fn find_trade_event(
+        &self,
+        meta: Option<&UiTransactionStatusMeta>,
+        mint: &Pubkey,
+    ) -> Option<events::TradeEvent> {
+        let logs: Vec<String> = meta
+            .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages.clone()))
+            .unwrap_or_default();
+
+        events::parse_logs::<events::TradeEvent>(&logs)
+            .into_iter()
+            .find(|event| event.mint == *mint && event.user == self.signer().pubkey())
+    }
+
+    /// Applies the default priority fee and the priority fee budget, if any, to a new trade
+    ///
+    /// # Arguments
+    ///
+    /// * `priority_fee` - Priority fee requested for the trade
+    ///
+    /// # Returns
+    ///
+    /// Returns the priority fee to send, or a ClientError::PriorityFeeBudgetExceeded if the
+    /// budget is spent and set to block trades
+    fn apply_fee_budget(
+        &self,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Option<PriorityFee>, error::ClientError> {
+        let priority_fee = priority_fee.or(self.default_priority_fee);
+        match &self.fee_spend {
+            Some(tracker) => tracker.apply_budget(self.clock.now(), priority_fee),
+            None => Ok(priority_fee),
+        }
+    }
+
+    /// Records the priority fee of a confirmed trade, fetching the transaction to read the
+    /// compute units it consumed
+    ///
+    /// Nothing is fetched unless tracking is enabled and the trade was sent with a unit price.
+    /// The trade has already landed, so a failed fetch is logged rather than returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the confirmed transaction
+    /// * `mint` - Token mint traded, None for swaps outside Pump.fun
+    /// * `priority_fee` - Priority fee the transaction was sent with
+    async fn track_fee_spend(
+        &self,
+        signature: &Signature,
+        mint: Option<&Pubkey>,
+        priority_fee: Option<PriorityFee>,
+    ) {
+        if self.fee_spend.is_none() || priority_fee.and_then(|fee| fee.price).is_none() {
+            return;
+        }
+        match self.get_confirmed_transaction_async(signature).await {
+            Ok(confirmed) => {
+                self.record_fee_spend(mint, priority_fee, confirmed.transaction.meta.as_ref())
+            }
+            Err(err) => {
+                tracing::warn!(%signature, %err, "Failed to fetch transaction for fee tracking")
+            }
+        }
+    }
+
+    /// Records the priority fee of a confirmed trade from its status metadata
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Token mint traded, None for swaps outside Pump.fun
+    /// * `priority_fee` - Priority fee the transaction was sent with
+    /// * `meta` - Status metadata of the confirmed transaction
+    fn record_fee_spend(
+        &self,
+        mint: Option<&Pubkey>,
+        priority_fee: Option<PriorityFee>,
+        meta: Option<&UiTransactionStatusMeta>,
+    ) {
+        let (Some(tracker), Some(unit_price)) =
+            (&self.fee_spend, priority_fee.and_then(|fee| fee.price))
+        else {
+            return;
+        };
+        let Some(units_consumed) =
+            meta.and_then(|meta| Option::<u64>::from(meta.compute_units_consumed.clone()))
+        else {
+            tracing::warn!("Confirmed transaction does not report the compute units consumed");
+            return;
+        };
+        tracker.record(
+            self.clock.now(),
+            mint.copied(),
+            spend::priority_fee_lamports(unit_price, units_consumed),
+        );
+    }
+
+    /// Requests an airdrop to the payer and waits for it to confirm
+    ///
+    /// Only available on clusters with a faucet, such as devnet and localnet. The airdrop is
+    /// confirmed at `commitment`, defaulting to `confirmed`. Use `confirmed` in test flows: a
+    /// `processed` airdrop can be reported before the balance is visible to the next
+    /// transaction's preflight checks, which is a common source of flaky `create` calls.
+    /// `finalized` is only needed when the funds must never be rolled back, and waits about 13
+    /// seconds longer.
+    ///
+    /// # Arguments
+    ///
+    /// * `lamports` - Amount of SOL to request in lamports
+    /// * `commitment` - Optional commitment to wait for. Defaults to confirmed
+    ///
+    /// # Returns
+    ///
+    /// Returns the airdrop signature once it reaches the commitment, or a ClientError if the
+    /// request or the airdrop transaction fails
+    pub async fn request_airdrop(
+        &self,
+        lamports: u64,
+        commitment: Option<CommitmentConfig>,
+    ) -> Result<Signature, error::ClientError> {
+        let commitment = commitment.unwrap_or(CommitmentConfig::confirmed());
+
+        let signature = self
+            .rpc
+            .request_airdrop(&self.signer().pubkey(), lamports)
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        self.rpc
+            .poll_for_signature_with_commitment(&signature, commitment)
+            .map_err(error::ClientError::SolanaClientError)?;
+        if let Some(Err(err)) = self
+            .rpc
+            .get_signature_status_with_commitment(&signature, commitment)
+            .map_err(error::ClientError::SolanaClientError)?
+        {
+            return Err(error::ClientError::SolanaClientError(err.into()));
+        }
+
+        Ok(signature)
+    }
+
+    /// Funds the payer from the faucet and checks the cluster is ready to trade on
+    ///
+    /// Requests an airdrop of `airdrop_sol` and waits for it to confirm, retrying when the
+    /// faucet fails, then checks that the payer is an initialized system account and that the
+    /// Pump.fun and token metadata programs are deployed. Pass 0 to only run the checks on an
+    /// already funded payer. Refuses to run against mainnet. See the [`devnet`] module.
+    ///
+    /// The devnet faucet caps each airdrop, currently at a few SOL; larger requests fail on
+    /// every attempt.
+    ///
+    /// # Arguments
+    ///
+    /// * `airdrop_sol` - Amount of SOL to request from the faucet
+    ///
+    /// # Returns
+    ///
+    /// Returns a report of what was done, a ClientError::InvalidInput if the cluster is mainnet
+    /// or the payer cannot pay for transactions, a ClientError::MissingDependencyProgram if a
+    /// program is not deployed, or a ClientError if the airdrop still fails after
+    /// [`devnet::AIRDROP_ATTEMPTS`] attempts
+    pub async fn devnet_setup(
+        &self,
+        airdrop_sol: f64,
+    ) -> Result<devnet::DevnetSetupReport, error::ClientError> {
+        if !airdrop_sol.is_finite() || airdrop_sol < 0.0 {
+            return Err(error::ClientError::InvalidInput(
+                "Airdrop amount must be a non-negative number of SOL",
+            ));
+        }
+        let airdrop_lamports = solana_sdk::native_token::sol_to_lamports(airdrop_sol);
+
+        // Refuse mainnet by its cluster or, for custom URLs, by its genesis hash
+        if matches!(self.cluster, Cluster::Mainnet)
+            || self
+                .rpc
+                .get_genesis_hash()
+                .map_err(error::ClientError::SolanaClientError)?
+                .to_string()
+                == devnet::MAINNET_GENESIS_HASH
+        {
+            return Err(error::ClientError::InvalidInput(
+                "Devnet setup refuses to run against mainnet",
+            ));
+        }
+
+        let payer = self.signer().pubkey();
+        let before = self
+            .rpc
+            .get_account_with_commitment(&payer, CommitmentConfig::confirmed())
+            .map_err(error::ClientError::SolanaClientError)?
+            .value;
+
+        // Retry the airdrop, as the devnet faucet often rate limits or drops requests
+        let mut airdrop_signature = None;
+        let mut airdrop_attempts = 0;
+        if airdrop_lamports > 0 {
+            loop {
+                airdrop_attempts += 1;
+                match self.request_airdrop(airdrop_lamports, None).await {
+                    Ok(signature) => {
+                        airdrop_signature = Some(signature);
+                        break;
+                    }
+                    Err(err) if airdrop_attempts < devnet::AIRDROP_ATTEMPTS => {
+                        tracing::warn!(attempt = airdrop_attempts, %err, "Airdrop failed, retrying");
+                        tokio::time::sleep(devnet::AIRDROP_RETRY_DELAY * airdrop_attempts).await;
+                    }
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+
+        // The airdrop is confirmed, not finalized, so read the payer at the same commitment
+        let account = self
+            .rpc
+            .get_account_with_commitment(&payer, CommitmentConfig::confirmed())
+            .map_err(error::ClientError::SolanaClientError)?
+            .value
+            .ok_or(error::ClientError::InvalidInput(
+                "Payer account does not exist, request an airdrop to create it",
+            ))?;
+        if account.owner != solana_sdk::system_program::id() {
+            return Err(error::ClientError::InvalidInput(
+                "Payer account is not owned by the system program",
+            ));
+        }
+
+        self.ensure_program_deployed(&cpi::ID, devnet::PUMPFUN_PROGRAM_HINT)?;
+        self.ensure_program_deployed(
+            &constants::accounts::MPL_TOKEN_METADATA,
+            METADATA_PROGRAM_HINT,
+        )?;
+
+        let report = devnet::DevnetSetupReport {
+            payer,
+            airdrop_lamports,
+            airdrop_signature,
+            airdrop_attempts,
+            account_created: before.is_none(),
+            balance_before: before.map_or(0, |account| account.lamports),
+            balance_after: account.lamports,
+        };
+        tracing::info!(
+            payer = %self.redaction.wallet(&payer),
+            balance = %self.redaction.amount(report.balance_after),
+            attempts = airdrop_attempts,
+            "Devnet setup complete"
+        );
+        Ok(report)
+    }
+
+    /// Sends a caller-assembled transaction and waits for confirmation
+    ///
+    /// This is the lowest-level send primitive: the transaction is broadcast as-is through the
+    /// client's RPC connection and confirmed at the client's commitment level. No Pump.fun-specific
+    /// validation is applied, so the caller is responsible for the instructions, signatures and
+    /// recent blockhash.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Fully signed transaction to send
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> Result<Signature, error::ClientError> {
+        self.ensure_trading_allowed()?;
+        self.async_rpc()
+            .send_and_confirm_transaction(transaction)
+            .await
+            .map_err(|err| {
+                error::ClientError::from_transaction_failure(
+                    err,
+                    &VersionedMessage::Legacy(transaction.message.clone()),
+                )
+            })
+    }
+
+    /// Sends a caller-assembled versioned transaction and waits for confirmation
+    ///
+    /// Behaves like [`PumpFun::send_transaction`] for transactions using address lookup tables
+    /// or other versioned message features. No Pump.fun-specific validation is applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `transaction` - Fully signed versioned transaction to send
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> Result<Signature, error::ClientError> {
+        self.ensure_trading_allowed()?;
+        self.async_rpc()
+            .send_and_confirm_transaction(transaction)
+            .await
+            .map_err(|err| error::ClientError::from_transaction_failure(err, &transaction.message))
+    }
+
+    /// Runs AMM swap instructions paid for and settled in native SOL
+    ///
+    /// Wraps `amount_sol` into the payer's WSOL account and runs the swap instructions, all in
+    /// one transaction. If the WSOL account is created by this call, it is closed afterwards to
+    /// unwrap the proceeds and reclaim its rent. An existing WSOL account is left open, so the
+    /// WSOL the payer already held is never unwrapped, and the proceeds stay wrapped in it; use
+    /// [`PumpFun::swap_with_wsol_account`] to receive them in native SOL. See the [`swap`] module
+    /// for details.
+    ///
+    /// # Arguments
+    ///
+    /// * `amount_sol` - Amount of SOL to wrap for the swap in lamports, 0 for sells
+    /// * `swap_instructions` - Instructions that swap against the payer's WSOL account
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn swap_with_wsol(
+        &self,
+        amount_sol: u64,
+        swap_instructions: Vec<Instruction>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Swap, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(priority_fee)?;
+            let owner = self.signer().pubkey();
+            let rpc = self.async_rpc();
+            let ata_exists = rpc
+                .get_account_with_commitment(&swap::get_wsol_account(&owner), rpc.commitment())
+                .await
+                .map_err(error::ClientError::SolanaClientError)?
+                .value
+                .is_some();
+            let mut request = self.program.request();
+
+            // Add priority fee if provided
+            if let Some(fee) = priority_fee {
+                if let Some(limit) = fee.limit {
+                    let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
+                    request = request.instruction(limit_ix);
+                }
+
+                if let Some(price) = fee.price {
+                    let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
+                    request = request.instruction(price_ix);
+                }
+            }
+
+            // Add wrap, swap and, if the account is created here, unwrap instructions
+            for ix in swap::with_wsol_preserving(&owner, amount_sol, swap_instructions, ata_exists)
+            {
+                request = request.instruction(ix);
+            }
+
+            // Make sure everything fits in a single transaction
+            let instructions = request
+                .instructions()
+                .map_err(error::ClientError::AnchorClientError)?;
+            utils::validate_transaction_size(&instructions, &owner)?;
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[]).await?;
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, unwrapped = !ata_exists, "Swap confirmed");
+            self.track_fee_spend(&signature, None, priority_fee).await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Runs AMM swap instructions settled in native SOL, leaving existing WSOL untouched
+    ///
+    /// Behaves like [`PumpFun::swap_with_wsol`], except when the payer already has a WSOL
+    /// account: the swap then runs through a temporary WSOL account that is closed afterwards,
+    /// so the existing account stays open and its balance is neither unwrapped nor mixed with
+    /// the swap's proceeds. See [`swap::WsolSession`].
+    ///
+    /// # Arguments
+    ///
+    /// * `amount_sol` - Amount of SOL to wrap for the swap in lamports, 0 for sells
+    /// * `build_swap` - Builds the swap instructions against the WSOL account it is given
+    /// * `priority_fee` - Optional priority fee configuration for compute units
+    ///
+    /// # Returns
+    ///
+    /// Returns the transaction signature if successful, or a ClientError if the operation fails
+    pub async fn swap_with_wsol_account(
+        &self,
+        amount_sol: u64,
+        build_swap: impl FnOnce(&Pubkey) -> Vec<Instruction>,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Signature, error::ClientError> {
+        self.trade_scoped(usage::Operation::Swap, async move {
+            let trade_id = self.next_trade_id();
+            let priority_fee = self.apply_fee_budget(priority_fee)?;
+            let owner = self.signer().pubkey();
+            let rpc = self.async_rpc();
+            let ata_exists = rpc
+                .get_account_with_commitment(&swap::get_wsol_account(&owner), rpc.commitment())
+                .await
+                .map_err(error::ClientError::SolanaClientError)?
+                .value
+                .is_some();
+            let seed = format!("wsol-{:08x}-{:x}", trade_id.session, trade_id.sequence);
+            let session = swap::WsolSession::new(&owner, amount_sol, ata_exists, &seed);
+
+            let mut request = self.program.request();
+
+            // Add priority fee if provided
+            if let Some(fee) = priority_fee {
+                if let Some(limit) = fee.limit {
+                    let limit_ix = ComputeBudgetInstruction::set_compute_unit_limit(limit);
+                    request = request.instruction(limit_ix);
+                }
+
+                if let Some(price) = fee.price {
+                    let price_ix = ComputeBudgetInstruction::set_compute_unit_price(price);
+                    request = request.instruction(price_ix);
+                }
+            }
+
+            // Add setup, swap and cleanup instructions
+            let swap_instructions = build_swap(&session.account);
+            for ix in session.wrap(swap_instructions) {
+                request = request.instruction(ix);
+            }
+
+            // Make sure everything fits in a single transaction
+            let instructions = request
+                .instructions()
+                .map_err(error::ClientError::AnchorClientError)?;
+            utils::validate_transaction_size(&instructions, &owner)?;
+
+            // Sign and send transaction
+            let transaction = self.sign_request(&request, &[]).await?;
+            let signature: Signature = self.send_transaction(&transaction).await?;
+            tracing::debug!(%trade_id, %signature, temporary_wsol = ata_exists, "Swap confirmed");
+            self.track_fee_spend(&signature, None, priority_fee).await;
+
+            Ok(signature)
+        })
+        .await
+    }
+
+    /// Gets the program version buy and sell instructions are built for
+    ///
+    /// Returns the configured version, or detects it from the cluster when it is
+    /// [`instruction::ProgramVersion::Auto`]. A global account of the original size means the
+    /// original program. Otherwise the newest version whose accounts exist is assumed: the fee
+    /// config for `V4`, the global volume accumulator for `V3`, and `V2` if neither does. The
+    /// detected version is cached for the lifetime of the client.
+    ///
+    /// # Returns
+    ///
+    /// Returns the program version, or a ClientError if detection fails
+    pub fn get_program_version(&self) -> Result<instruction::ProgramVersion, error::ClientError> {
+        use instruction::ProgramVersion;
+
+        if self.program_version != ProgramVersion::Auto {
+            return Ok(self.program_version);
+        }
+        let mut detected = self
+            .detected_version
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(version) = *detected {
+            return Ok(version);
+        }
+
+        let accounts = self
+            .rpc
+            .get_multiple_accounts(&[
+                Self::get_global_pda_with_seeds(&self.seeds),
+                instruction::get_global_volume_accumulator_pda(),
+                instruction::get_fee_config_pda(),
+            ])
+            .map_err(error::ClientError::SolanaClientError)?;
+        let global = accounts[0]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Global account not found"))?;
+
+        let version = if global.data.len() <= GLOBAL_V1_LEN {
+            ProgramVersion::V1
+        } else if accounts[2].is_some() {
+            ProgramVersion::V4
+        } else if accounts[1].is_some() {
+            ProgramVersion::V3
+        } else {
+            ProgramVersion::V2
+        };
+        *detected = Some(version);
+
+        Ok(version)
+    }
+
+    /// Converts a buy or sell instruction to the accounts of the program version in use
+    ///
+    /// # Arguments
+    ///
+    /// * `instruction` - Buy or sell instruction with the original account set
+    /// * `mint` - Public key of the traded token mint
+    /// * `creator` - Creator of the token, read from its bonding curve if None and the version
+    ///   pays the creator vault
+    fn apply_program_version(
+        &self,
+        instruction: Instruction,
+        mint: &Pubkey,
+        creator: Option<Pubkey>,
+    ) -> Result<Instruction, error::ClientError> {
+        let version = self.get_program_version()?;
+        let creator = match creator {
+            Some(creator) => creator,
+            None if version.has_creator_vault() => self.get_curve_creator(mint)?,
+            None => Pubkey::default(),
+        };
+
+        instruction::with_program_version(instruction, version, &self.seeds, &creator)
+    }
+
+    /// Reads the creator a bonding curve stores for its creator vault
+    ///
+    /// Costs one account read, unlike [`PumpFun::get_creator`] which walks the curve's history.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the creator's public key, a ClientError::InvalidInput if the curve predates
+    /// creator vaults, or a ClientError if the operation fails
+    fn get_curve_creator(&self, mint: &Pubkey) -> Result<Pubkey, error::ClientError> {
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let account = self
+            .rpc
+            .get_account(&bonding_curve_pda)
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        accounts::BondingCurveAccount::creator_from_data(&account.data).ok_or(
+            error::ClientError::InvalidInput("Bonding curve does not store its creator"),
+        )
+    }
+
+    /// Checks that a program the Pump.fun program depends on is deployed on the cluster
+    ///
+    /// Without the check, a missing dependency only surfaces as an opaque failure deep inside
+    /// the transaction. Deployments are cached, so the check costs one request per program.
+    ///
+    /// # Arguments
+    ///
+    /// * `program` - Program ID of the dependency
+    /// * `hint` - How to make the program available, included in the error
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok if the program is deployed, a ClientError::MissingDependencyProgram if it is
+    /// not, or a ClientError if the operation fails
+    pub fn ensure_program_deployed(
+        &self,
+        program: &Pubkey,
+        hint: &'static str,
+    ) -> Result<(), error::ClientError> {
+        if self
+            .deployed_programs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .contains(program)
+        {
+            return Ok(());
+        }
+
+        let account = self
+            .rpc
+            .get_account_with_commitment(program, self.rpc.commitment())
+            .map_err(error::ClientError::SolanaClientError)?
+            .value;
+        if !account.is_some_and(|account| account.executable) {
+            return Err(error::ClientError::MissingDependencyProgram {
+                program: *program,
+                hint,
+            });
+        }
+
+        self.deployed_programs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(*program);
+        Ok(())
+    }
+
+    /// Creates a receiver of the client's notifications, such as detected program upgrades
+    ///
+    /// # Returns
+    ///
+    /// Returns a receiver of every event sent from now on; dropping it unsubscribes
+    pub fn subscribe_client_events(
+        &self,
+    ) -> futures::channel::mpsc::UnboundedReceiver<upgrade::ClientEvent> {
+        self.upgrade_watch.subscribe()
+    }
+
+    /// Checks whether the Pump.fun program was redeployed since the last check
+    ///
+    /// Reads the deployment slot from the program's program data account. The first check
+    /// records it as the baseline. When a later check reads a different slot, cached program
+    /// state is dropped, trading is paused if enabled with [`PumpFun::with_pause_on_upgrade`],
+    /// and a ClientEvent::ProgramUpgraded is sent to every subscriber.
+    ///
+    /// # Returns
+    ///
+    /// Returns the new deployment slot if an upgrade was detected, None otherwise, or a
+    /// ClientError if the program data account could not be read
+    pub fn check_program_upgrade(&self) -> Result<Option<u64>, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        let program_data =
+            Pubkey::find_program_address(&[cpi::ID.as_ref()], &bpf_loader_upgradeable::id()).0;
+        let account = self
+            .rpc
+            .get_account_with_config(
+                &program_data,
+                RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    data_slice: Some(UiDataSliceConfig {
+                        offset: 0,
+                        length: UpgradeableLoaderState::size_of_programdata_metadata(),
+                    }),
+                    commitment: Some(self.rpc.commitment()),
+                    min_context_slot: None,
+                },
+            )
+            .map_err(error::ClientError::SolanaClientError)?
+            .value
+            .ok_or(error::ClientError::InvalidInput(
+                "Program data account not found",
+            ))?;
+        let slot = upgrade::decode_deployed_slot(&account.data)?;
+
+        if !self.upgrade_watch.observe(slot) {
+            return Ok(None);
+        }
+        tracing::warn!(slot, "Pump.fun program upgrade detected");
+        self.invalidate_program_caches();
+        if self.upgrade_watch.pause_on_upgrade {
+            self.upgrade_watch.set_paused(true);
+        }
+        self.upgrade_watch
+            .notify(upgrade::ClientEvent::ProgramUpgraded { slot });
+        Ok(Some(slot))
+    }
+
+    /// Checks for program upgrades on every interval until the token is cancelled
+    ///
+    /// Failed checks are skipped and retried on the next interval. See
+    /// [`PumpFun::check_program_upgrade`].
+    ///
+    /// # Arguments
+    ///
+    /// * `interval` - Time between checks
+    /// * `cancel` - Token that stops the checks when cancelled
+    pub async fn watch_program_upgrades_until_cancelled(
+        &self,
+        interval: Duration,
+        cancel: &cancel::CancellationToken,
+    ) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut cancelled = std::pin::pin!(cancel.cancelled());
+        loop {
+            let tick = std::pin::pin!(interval.tick());
+            if let futures::future::Either::Right(_) =
+                futures::future::select(tick, cancelled.as_mut()).await
+            {
+                return;
+            }
+            if let Err(err) = self.check_program_upgrade() {
+                tracing::debug!("Program upgrade check failed: {}", err);
+            }
+        }
+    }
+
+    /// Gets the slot the Pump.fun program was deployed at when last checked
+    ///
+    /// # Returns
+    ///
+    /// Returns the deployment slot, or None if [`PumpFun::check_program_upgrade`] has not
+    /// succeeded yet
+    pub fn last_deployed_slot(&self) -> Option<u64> {
+        self.upgrade_watch.deployed_slot()
+    }
+
+    /// Whether transactions are paused until a program upgrade is acknowledged
+    pub fn is_trading_paused(&self) -> bool {
+        self.upgrade_watch.is_paused()
+    }
+
+    /// Resumes sending transactions after a program upgrade paused them
+    ///
+    /// Call this once the client has been checked against the upgraded program.
+    pub fn acknowledge_program_upgrade(&self) {
+        self.upgrade_watch.set_paused(false);
+    }
+
+    /// Fails while transactions are paused by a detected program upgrade
+    fn ensure_trading_allowed(&self) -> Result<(), error::ClientError> {
+        if self.upgrade_watch.is_paused() {
+            return Err(error::ClientError::TradingPaused);
+        }
+        Ok(())
+    }
+
+    /// Drops cached state that depends on the deployed program
+    fn invalidate_program_caches(&self) {
+        *self
+            .detected_version
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+        self.deployed_programs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    /// Reserves a mint for a create transaction from this client
+    ///
+    /// Fails if another create for the same mint is already in flight from this client, or if
+    /// the mint account already exists on chain (the keypair has been used before).
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the new token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns a reservation that releases the mint when dropped, a
+    /// ClientError::MintAlreadyExists if the mint is taken, or a ClientError if the operation fails
+    async fn reserve_mint(&self, mint: &Pubkey) -> Result<MintReservation<'_>, error::ClientError> {
+        let reservation = {
+            let mut pending_mints = self
+                .pending_mints
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if !pending_mints.insert(*mint) {
+                return Err(error::ClientError::MintAlreadyExists(*mint));
+            }
+            MintReservation {
+                pending_mints: &self.pending_mints,
+                mint: *mint,
+            }
+        };
+
+        let rpc = self.async_rpc();
+        let account = rpc
+            .get_account_with_commitment(mint, rpc.commitment())
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+        if account.value.is_some() {
+            return Err(error::ClientError::MintAlreadyExists(*mint));
+        }
+
+        Ok(reservation)
+    }
+
+    /// Locks a mint for an order made of several trades
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns a reservation that unlocks the mint when dropped, or a ClientError::MintLocked if
+    /// another order holds it
+    pub(crate) fn lock_mint(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<MintReservation<'_>, error::ClientError> {
+        let mut locked_mints = self
+            .locked_mints
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !locked_mints.insert(*mint) {
+            return Err(error::ClientError::MintLocked(*mint));
+        }
+
+        Ok(MintReservation {
+            pending_mints: &self.locked_mints,
+            mint: *mint,
+        })
+    }
+
+    /// Gets the Program Derived Address (PDA) for the global state account
+    ///
+    /// # Returns
+    ///
+    /// Returns the PDA public key derived from the GLOBAL_SEED
+    pub fn get_global_pda() -> Pubkey {
+        Self::get_global_pda_with_seeds(&SeedConfig::default())
+    }
+
+    /// Gets the Program Derived Address (PDA) for the global state account using custom seeds
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - Seed configuration to derive from
+    ///
+    /// # Returns
+    ///
+    /// Returns the PDA public key derived from the configured global seed
+    pub fn get_global_pda_with_seeds(seeds: &SeedConfig) -> Pubkey {
+        let seeds: &[&[u8]; 1] = &[seeds.global];
+        let program_id: &Pubkey = &cpi::ID;
+        Pubkey::find_program_address(seeds, program_id).0
+    }
+
+    /// Gets the Program Derived Address (PDA) for the mint authority
+    ///
+    /// # Returns
+    ///
+    /// Returns the PDA public key derived from the MINT_AUTHORITY_SEED
+    pub fn get_mint_authority_pda() -> Pubkey {
+        Self::get_mint_authority_pda_with_seeds(&SeedConfig::default())
+    }
+
+    /// Gets the Program Derived Address (PDA) for the mint authority using custom seeds
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - Seed configuration to derive from
+    ///
+    /// # Returns
+    ///
+    /// Returns the PDA public key derived from the configured mint authority seed
+    pub fn get_mint_authority_pda_with_seeds(seeds: &SeedConfig) -> Pubkey {
+        let seeds: &[&[u8]; 1] = &[seeds.mint_authority];
+        let program_id: &Pubkey = &cpi::ID;
+        Pubkey::find_program_address(seeds, program_id).0
+    }
+
+    /// Gets the Program Derived Address (PDA) for a token's bonding curve account
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(PDA) if derivation succeeds, or None if it fails
+    pub fn get_bonding_curve_pda(mint: &Pubkey) -> Option<Pubkey> {
+        Self::get_bonding_curve_pda_with_seeds(&SeedConfig::default(), mint)
+    }
+
+    /// Gets the Program Derived Address (PDA) for a token's bonding curve account using custom seeds
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - Seed configuration to derive from
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(PDA) if derivation succeeds, or None if it fails
+    pub fn get_bonding_curve_pda_with_seeds(seeds: &SeedConfig, mint: &Pubkey) -> Option<Pubkey> {
+        let seeds: &[&[u8]; 2] = &[seeds.bonding_curve, mint.as_ref()];
+        let program_id: &Pubkey = &cpi::ID;
+        let pda: Option<(Pubkey, u8)> = Pubkey::try_find_program_address(seeds, program_id);
+        pda.map(|pubkey| pubkey.0)
+    }
+
+    /// Gets the Program Derived Address (PDA) for a creator's fee vault
+    ///
+    /// # Arguments
+    ///
+    /// * `creator` - Public key of the token creator
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(PDA) if derivation succeeds, or None if it fails
+    pub fn get_creator_vault_pda(creator: &Pubkey) -> Option<Pubkey> {
+        Self::get_creator_vault_pda_with_seeds(&SeedConfig::default(), creator)
+    }
+
+    /// Gets the Program Derived Address (PDA) for a creator's fee vault using custom seeds
+    ///
+    /// # Arguments
+    ///
+    /// * `seeds` - Seed configuration to derive from
+    /// * `creator` - Public key of the token creator
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(PDA) if derivation succeeds, or None if it fails
+    pub fn get_creator_vault_pda_with_seeds(
+        seeds: &SeedConfig,
+        creator: &Pubkey,
+    ) -> Option<Pubkey> {
+        let seeds: &[&[u8]; 2] = &[seeds.creator_vault, creator.as_ref()];
+        let program_id: &Pubkey = &cpi::ID;
+        let pda: Option<(Pubkey, u8)> = Pubkey::try_find_program_address(seeds, program_id);
+        pda.map(|pubkey| pubkey.0)
+    }
+
+    /// Gets the Program Derived Address (PDA) for a token's metadata account
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the PDA public key for the token's metadata account
+    pub fn get_metadata_pda(mint: &Pubkey) -> Pubkey {
+        let seeds: &[&[u8]; 3] = &[
+            constants::seeds::METADATA_SEED,
+            constants::accounts::MPL_TOKEN_METADATA.as_ref(),
+            mint.as_ref(),
+        ];
+        let program_id: &Pubkey = &constants::accounts::MPL_TOKEN_METADATA;
+        Pubkey::find_program_address(seeds, program_id).0
+    }
+
+    /// Gets the associated token account of any owner for a token
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Public key of the token account owner
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the address of the owner's associated token account for the mint
+    pub fn get_user_ata(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        Self::get_user_ata_with_token_program(owner, mint, &constants::accounts::TOKEN_PROGRAM)
+    }
+
+    /// Gets the associated token account of any owner for a token of any token program
+    ///
+    /// [`PumpFun::get_user_ata`] assumes the classic token program; Token-2022 mints have their
+    /// accounts at other addresses. See [`PumpFun::get_token_program`] to look up the program.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Public key of the token account owner
+    /// * `mint` - Public key of the token mint
+    /// * `token_program` - Token program that owns the mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the address of the owner's associated token account for the mint
+    pub fn get_user_ata_with_token_program(
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+    ) -> Pubkey {
+        instruction::derive_user_ata(owner, mint, token_program)
+    }
+
+    /// Gets the token program that owns a mint
+    ///
+    /// The owner is looked up once per mint and cached, since it never changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the Token or Token-2022 program ID, or a ClientError if the mint account cannot
+    /// be fetched or is not owned by a token program
+    pub fn get_token_program(&self, mint: &Pubkey) -> Result<Pubkey, error::ClientError> {
+        if let Some(token_program) = self.token_programs.lock().unwrap().get(mint) {
+            return Ok(*token_program);
+        }
+        if let Some(token_program) = self.cached_lookup::<Pubkey>(CACHE_TOKEN_PROGRAM, mint) {
+            self.cache_token_program(mint, &token_program)?;
+            return Ok(token_program);
+        }
+
+        let account = self
+            .rpc
+            .get_account(mint)
+            .map_err(error::ClientError::SolanaClientError)?;
+        self.cache_token_program(mint, &account.owner)?;
+
+        Ok(account.owner)
+    }
+
+    /// Gets the token program that owns a mint without blocking the executor
+    ///
+    /// Shares the cache of [`PumpFun::get_token_program`].
+    async fn get_token_program_async(&self, mint: &Pubkey) -> Result<Pubkey, error::ClientError> {
+        if let Some(token_program) = self.token_programs.lock().unwrap().get(mint) {
+            return Ok(*token_program);
+        }
+        if let Some(token_program) = self.cached_lookup::<Pubkey>(CACHE_TOKEN_PROGRAM, mint) {
+            self.cache_token_program(mint, &token_program)?;
+            return Ok(token_program);
+        }
+
+        let account = self
+            .async_rpc()
+            .get_account(mint)
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+        self.cache_token_program(mint, &account.owner)?;
+
+        Ok(account.owner)
+    }
+
+    /// Records the token program owning a mint, rejecting accounts no token program owns
+    fn cache_token_program(&self, mint: &Pubkey, owner: &Pubkey) -> Result<(), error::ClientError> {
+        if *owner != constants::accounts::TOKEN_PROGRAM
+            && *owner != constants::accounts::TOKEN_2022_PROGRAM
+        {
+            return Err(error::ClientError::InvalidInput(
+                "Mint is not owned by a token program",
+            ));
+        }
+
+        let newly_cached = self
+            .token_programs
+            .lock()
+            .unwrap()
+            .insert(*mint, *owner)
+            .is_none();
+        if newly_cached {
+            self.store_lookup(CACHE_TOKEN_PROGRAM, mint, owner);
+        }
+        Ok(())
+    }
+
+    /// Creates a client for authenticated Pump.fun backend API endpoints, signing in as the payer
+    ///
+    /// # Returns
+    ///
+    /// Returns a new API client, or an ApiError if the HTTP client could not be created
+    #[cfg(feature = "api")]
+    pub fn api_client(&self) -> Result<api::ApiClient<'a>, api::ApiError> {
+        api::ApiClient::new(self.payer)
+    }
+
+    /// Gets the global state account data containing program-wide configuration
+    ///
+    /// # Returns
+    ///
+    /// Returns the deserialized GlobalAccount if successful, or a ClientError if the operation fails
+    pub fn get_global_account(&self) -> Result<accounts::GlobalAccount, error::ClientError> {
+        let global: Pubkey = Self::get_global_pda_with_seeds(&self.seeds);
+
+        let account = self
+            .rpc
+            .get_account(&global)
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        self.decode_account(&global, &account.data)
+    }
+
+    /// Gets a token's bonding curve account data containing pricing parameters
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the deserialized BondingCurveAccount if successful, or a ClientError if the operation fails
+    pub fn get_bonding_curve_account(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<accounts::BondingCurveAccount, error::ClientError> {
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let account = self
+            .rpc
+            .get_account(&bonding_curve_pda)
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        self.decode_account(&bonding_curve_pda, &account.data)
+    }
+
+    /// Gets the global state account data without blocking the executor
+    ///
+    /// Same as [`PumpFun::get_global_account`], for use from async code such as the trade flows.
+    ///
+    /// # Returns
+    ///
+    /// Returns the deserialized GlobalAccount if successful, or a ClientError if the operation fails
+    pub async fn get_global_account_async(
+        &self,
+    ) -> Result<accounts::GlobalAccount, error::ClientError> {
+        let global: Pubkey = Self::get_global_pda_with_seeds(&self.seeds);
+
+        let account = self
+            .async_rpc()
+            .get_account(&global)
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        self.decode_account(&global, &account.data)
+    }
+
+    /// Gets a token's bonding curve account data without blocking the executor
+    ///
+    /// Same as [`PumpFun::get_bonding_curve_account`], for use from async code such as the trade
+    /// flows.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the deserialized BondingCurveAccount if successful, or a ClientError if the operation fails
+    pub async fn get_bonding_curve_account_async(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<accounts::BondingCurveAccount, error::ClientError> {
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let account = self
+            .async_rpc()
+            .get_account(&bonding_curve_pda)
+            .await
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        self.decode_account(&bonding_curve_pda, &account.data)
+    }
+
+    /// Gets the bonding curve accounts of many tokens
+    ///
+    /// Collects the results of [`PumpFun::get_bonding_curve_accounts_stream`] in the order the
+    /// mints were given.
+    ///
+    /// # Arguments
+    ///
+    /// * `mints` - Public keys of the token mints
+    ///
+    /// # Returns
+    ///
+    /// Returns each mint with its bonding curve account, None if the account does not exist, or a
+    /// ClientError if it could not be fetched or decoded
+    pub async fn get_bonding_curve_accounts(
+        &self,
+        mints: &[Pubkey],
+    ) -> Vec<(
+        Pubkey,
+        Result<Option<accounts::BondingCurveAccount>, error::ClientError>,
+    )> {
+        usage::scoped(usage::Operation::Query, async move {
+            let positions: HashMap<Pubkey, usize> = mints
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(position, mint)| (*mint, position))
+                .collect();
+
+            let mut results: Vec<_> = self
+                .get_bonding_curve_accounts_stream(mints)
+                .collect()
+                .await;
+            results.sort_by_key(|(mint, _)| positions[mint]);
+            results
+        })
+        .await
+    }
+
+    /// Streams the bonding curve accounts of many tokens as they arrive
+    ///
+    /// Accounts are requested in chunks of up to 100 with `getMultipleAccounts`, keeping at most
+    /// [`PumpFun::max_concurrent_requests`] requests in flight. Each chunk's results are yielded
+    /// as soon as it completes, so chunks may arrive out of order. If a chunk fails, its accounts
+    /// are fetched one by one so each gets its own result.
+    ///
+    /// # Arguments
+    ///
+    /// * `mints` - Public keys of the token mints
+    ///
+    /// # Returns
+    ///
+    /// Returns a stream of each mint with its bonding curve account, None if the account does not
+    /// exist, or a ClientError if it could not be fetched or decoded
+    pub fn get_bonding_curve_accounts_stream(
+        &self,
+        mints: &[Pubkey],
+    ) -> impl futures::Stream<
+        Item = (
+            Pubkey,
+            Result<Option<accounts::BondingCurveAccount>, error::ClientError>,
+        ),
+    > {
+        let commitment = self.rpc.commitment();
+        let decode_hook = self.decode_hook.clone();
+        let rpc = std::sync::Arc::new(self.nonblocking_rpc());
+        let chunks: Vec<Vec<(Pubkey, Pubkey)>> = mints
+            .chunks(MAX_MULTIPLE_ACCOUNTS)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|mint| {
+                        let pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+                            .unwrap_or_default();
+                        (*mint, pda)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        futures::stream::iter(chunks)
+            .map(move |chunk| {
+                let rpc = rpc.clone();
+                let decode_hook = decode_hook.clone();
+                async move {
+                    let pdas: Vec<Pubkey> = chunk.iter().map(|(_, pda)| *pda).collect();
+                    let fetched: Vec<Result<Option<Account>, error::ClientError>> =
+                        match rpc.get_multiple_accounts(&pdas).await {
+                            Ok(accounts) => accounts.into_iter().map(Ok).collect(),
+                            Err(_) => {
+                                let mut fetched = Vec::with_capacity(pdas.len());
+                                for pda in &pdas {
+                                    fetched.push(
+                                        rpc.get_account_with_commitment(pda, commitment)
+                                            .await
+                                            .map(|response| response.value)
+                                            .map_err(error::ClientError::SolanaClientError),
+                                    );
+                                }
+                                fetched
+                            }
+                        };
+
+                    chunk
+                        .into_iter()
+                        .zip(fetched)
+                        .map(|((mint, pda), account)| {
+                            let account = account.and_then(|account| {
+                                account
+                                    .map(|account| {
+                                        accounts::decode_account(
+                                            &pda,
+                                            &account.data,
+                                            decode_hook.as_ref(),
+                                        )
+                                    })
+                                    .transpose()
+                            });
+                            (mint, account)
+                        })
+                        .collect::<Vec<_>>()
+                }
+            })
+            .buffer_unordered(self.max_concurrent_requests.max(1))
+            .flat_map(futures::stream::iter)
+    }
+
+    /// Filters a list of mints down to the pump.fun tokens among them
+    ///
+    /// Bonding curve addresses are checked in chunks of up to 100 with `getMultipleAccounts`,
+    /// which makes this suited to separating pump.fun tokens from other SPL tokens in a wallet.
+    /// A mint is kept if its bonding curve account exists and is owned by the Pump.fun program,
+    /// whether or not the curve has completed.
+    ///
+    /// # Arguments
+    ///
+    /// * `mints` - Public keys of the token mints
+    ///
+    /// # Returns
+    ///
+    /// Returns the mints that have a bonding curve, in the order they were given, or a
+    /// ClientError if the accounts could not be fetched
+    pub fn filter_pumpfun_mints(
+        &self,
+        mints: &[Pubkey],
+    ) -> Result<Vec<Pubkey>, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        let mut filtered = Vec::new();
+        for chunk in mints.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            let pdas: Vec<Pubkey> = chunk
+                .iter()
+                .map(|mint| {
+                    Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint).unwrap_or_default()
+                })
+                .collect();
+            let accounts = self
+                .rpc
+                .get_multiple_accounts(&pdas)
+                .map_err(error::ClientError::SolanaClientError)?;
+
+            filtered.extend(
+                chunk
+                    .iter()
+                    .zip(accounts)
+                    .filter(|(_, account)| {
+                        account
+                            .as_ref()
+                            .is_some_and(|account| account.owner == cpi::ID)
+                    })
+                    .map(|(mint, _)| *mint),
+            );
+        }
+
+        Ok(filtered)
+    }
+
+    /// Gets an address's signature history by fetching windows of it concurrently
+    ///
+    /// Serial pagination needs one round trip per page of signatures, which dominates the time
+    /// taken to reconstruct a long history. Splitting the history at known signatures lets each
+    /// window be paginated on its own, with at most [`PumpFun::max_concurrent_requests`] windows
+    /// in flight. Any confirmed transaction signature can serve as a boundary, such as one saved
+    /// by a previous run or taken from a block at a chosen slot.
+    ///
+    /// `before` and `until` both exclude the boundary itself, so each boundary is probed
+    /// separately and only kept if it belongs to the address's history.
+    ///
+    /// # Arguments
+    ///
+    /// * `address` - Address whose history to fetch
+    /// * `boundaries` - Signatures splitting the history into windows, newest first
+    ///
+    /// # Returns
+    ///
+    /// Returns the signatures newest first without duplicates, or a ClientError if any window
+    /// fails
+    pub async fn get_signatures_in_windows(
+        &self,
+        address: &Pubkey,
+        boundaries: &[Signature],
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, error::ClientError> {
+        usage::scoped(usage::Operation::Backfill, async move {
+            let commitment = self.rpc.commitment();
+            let rpc = std::sync::Arc::new(self.nonblocking_rpc());
+            let parse = |signature: &str| {
+                Signature::from_str(signature)
+                    .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))
+            };
+
+            // Window i lies between boundary i - 1 and boundary i
+            let windows: Vec<(Option<Signature>, Option<Signature>)> = (0..=boundaries.len())
+                .map(|index| {
+                    (
+                        index.checked_sub(1).map(|previous| boundaries[previous]),
+                        boundaries.get(index).copied(),
+                    )
+                })
+                .collect();
+
+            let fetched: Vec<Vec<RpcConfirmedTransactionStatusWithSignature>> =
+                futures::stream::iter(windows.clone())
+                    .map(|(before, until)| {
+                        let rpc = rpc.clone();
+                        async move {
+                            let mut window = Vec::new();
+                            let mut page_before = before;
+                            loop {
+                                let page = rpc
+                                    .get_signatures_for_address_with_config(
+                                        address,
+                                        GetConfirmedSignaturesForAddress2Config {
+                                            before: page_before,
+                                            until,
+                                            limit: None,
+                                            commitment: Some(commitment),
+                                        },
+                                    )
+                                    .await
+                                    .map_err(error::ClientError::SolanaClientError)?;
+                                let Some(last) = page.last() else {
+                                    break;
+                                };
+                                page_before = Some(parse(&last.signature)?);
+                                window.extend(page);
+                            }
+                            Ok::<_, error::ClientError>(window)
+                        }
+                    })
+                    .buffered(self.max_concurrent_requests.max(1))
+                    .try_collect()
+                    .await?;
+
+            // The signature right after a window's oldest one is its boundary, if the boundary
+            // belongs to the history
+            let probes: Vec<(Signature, Option<Signature>)> = boundaries
+                .iter()
+                .enumerate()
+                .map(|(index, boundary)| {
+                    let probe_before = match fetched[index].last() {
+                        Some(oldest) => Some(parse(&oldest.signature)?),
+                        None => windows[index].0,
+                    };
+                    Ok((*boundary, probe_before))
+                })
+                .collect::<Result<_, error::ClientError>>()?;
+
+            let boundary_statuses: Vec<Option<RpcConfirmedTransactionStatusWithSignature>> =
+                futures::stream::iter(probes)
+                    .map(|(boundary, before)| {
+                        let rpc = rpc.clone();
+                        async move {
+                            let page = rpc
+                                .get_signatures_for_address_with_config(
+                                    address,
+                                    GetConfirmedSignaturesForAddress2Config {
+                                        before,
+                                        until: None,
+                                        limit: Some(1),
+                                        commitment: Some(commitment),
+                                    },
+                                )
+                                .await
+                                .map_err(error::ClientError::SolanaClientError)?;
+                            Ok::<_, error::ClientError>(
+                                page.into_iter()
+                                    .next()
+                                    .filter(|status| status.signature == boundary.to_string()),
+                            )
+                        }
+                    })
+                    .buffered(self.max_concurrent_requests.max(1))
+                    .try_collect()
+                    .await?;
+
+            Ok(utils::merge_signature_windows(fetched, boundary_statuses))
+        })
+        .await
+    }
+
+    /// Gets the most recent changes to the program's global parameters
+    ///
+    /// Every trade references the global account, so its own signature history is dominated by
+    /// trades. Parameter changes can only be signed by the global authority, so the authority's
+    /// history is walked instead, decoding the `SetParamsEvent` of each successful transaction.
+    /// Changes made under a previous authority are not found.
+    ///
+    /// The history is requested in pages of [`PARAM_HISTORY_PAGE`] signatures, and the
+    /// transactions of each page are fetched with at most
+    /// [`PumpFun::max_concurrent_requests`] requests in flight. The walk stops once `limit`
+    /// changes are found, at `until`, or at the start of the authority's history, so pass the
+    /// newest signature of a previous call as `until` to only fetch what changed since.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - Maximum number of changes to return
+    /// * `until` - Signature to stop at, exclusive, None to walk the whole history
+    ///
+    /// # Returns
+    ///
+    /// Returns the global account state after each change, newest first, or a ClientError if the
+    /// operation fails
+    pub async fn get_global_param_changes(
+        &self,
+        limit: usize,
+        until: Option<Signature>,
+    ) -> Result<Vec<GlobalParamChange>, error::ClientError> {
+        usage::scoped(usage::Operation::Backfill, async move {
+            let current = self.get_global_account_async().await?;
+            let current = &current;
+            let rpc = self.async_rpc();
+            let commitment = self.rpc.commitment();
+            let parse = |signature: &str| {
+                Signature::from_str(signature)
+                    .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))
+            };
+
+            let mut changes = Vec::new();
+            let mut before: Option<Signature> = None;
+            while changes.len() < limit {
+                let signatures = rpc
+                    .get_signatures_for_address_with_config(
+                        &current.authority,
+                        GetConfirmedSignaturesForAddress2Config {
+                            before,
+                            until,
+                            limit: Some(PARAM_HISTORY_PAGE),
+                            commitment: Some(commitment),
+                        },
+                    )
+                    .await
+                    .map_err(error::ClientError::SolanaClientError)?;
+
+                let Some(last) = signatures.last() else {
+                    break;
+                };
+                before = Some(parse(&last.signature)?);
+
+                let page: Vec<Option<GlobalParamChange>> = futures::stream::iter(
+                    signatures.into_iter().filter(|status| status.err.is_none()),
+                )
+                .map(|status| async move {
+                    let signature = parse(&status.signature)?;
+                    let transaction = rpc
+                        .get_transaction_with_config(
+                            &signature,
+                            RpcTransactionConfig {
+                                encoding: Some(UiTransactionEncoding::Base64),
+                                commitment: Some(commitment),
+                                max_supported_transaction_version: Some(0),
+                            },
+                        )
+                        .await
+                        .map_err(error::ClientError::SolanaClientError)?;
+
+                    let logs: Vec<String> = transaction
+                        .transaction
+                        .meta
+                        .and_then(|meta| Option::<Vec<String>>::from(meta.log_messages))
+                        .unwrap_or_default();
+
+                    // A transaction may set parameters more than once; the last one wins
+                    let change = events::parse_logs::<events::SetParamsEvent>(&logs)
+                        .pop()
+                        .map(|event| GlobalParamChange {
+                            slot: status.slot,
+                            block_time: status.block_time,
+                            signature,
+                            global: accounts::GlobalAccount {
+                                fee_recipient: event.fee_recipient,
+                                initial_virtual_token_reserves: event
+                                    .initial_virtual_token_reserves,
+                                initial_virtual_sol_reserves: event.initial_virtual_sol_reserves,
+                                initial_real_token_reserves: event.initial_real_token_reserves,
+                                token_total_supply: event.token_total_supply,
+                                fee_basis_points: event.fee_basis_points,
+                                ..current.clone()
+                            },
+                        });
+                    Ok::<_, error::ClientError>(change)
+                })
+                .buffered(self.max_concurrent_requests.max(1))
+                .try_collect()
+                .await?;
+
+                changes.extend(page.into_iter().flatten());
+            }
+
+            changes.truncate(limit);
+            Ok(changes)
+        })
+        .await
+    }
+
+    /// Checks whether a confirmed trade was likely sandwiched
+    ///
+    /// Fetches the block the trade landed in, decodes the trade events of every successful
+    /// transaction, and looks for a wallet that bought the same token right before the trade
+    /// and sold it right after. See [`mev::find_sandwich`].
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the confirmed trade
+    /// * `mint` - Public key of the traded token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(report) if a likely sandwich was found, None if not, or a ClientError if the
+    /// operation fails
+    pub fn detect_sandwich(
+        &self,
+        signature: &Signature,
+        mint: &Pubkey,
+    ) -> Result<Option<mev::SandwichReport>, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Backfill);
+        let slot = self
+            .rpc
+            .get_transaction_with_config(
+                signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(self.get_history_commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .map_err(error::ClientError::SolanaClientError)?
+            .slot;
+
+        let block = self.get_full_block(slot)?;
+
+        let mut trades = Vec::new();
+        for (index, transaction) in block
+            .transactions
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            let Some(meta) = transaction.meta else {
+                continue;
+            };
+            if meta.err.is_some() {
+                continue;
+            }
+            let Some(signature) = transaction
+                .transaction
+                .decode()
+                .and_then(|transaction| transaction.signatures.first().copied())
+            else {
+                continue;
+            };
+
+            let logs: Vec<String> = Option::from(meta.log_messages).unwrap_or_default();
+            trades.extend(
+                events::parse_logs::<events::TradeEvent>(&logs)
+                    .into_iter()
+                    .filter(|event| event.mint == *mint)
+                    .map(|event| mev::BlockTrade {
+                        index,
+                        signature,
+                        event,
+                    }),
+            );
+        }
+
+        Ok(mev::find_sandwich(&trades, signature))
+    }
+
+    /// Reports every transaction that touched a token's bonding curve in a slot
+    ///
+    /// Fetches the block at the slot and decodes each transaction that references the curve,
+    /// failed ones included: its fee payer, the buy or sell it sent, the trade event it emitted
+    /// and its compute budget settings, in the order the transactions landed. Comparing a lost
+    /// trade's priority fee and position with those ahead of it shows what it would have taken
+    /// to win. See [`mev::CompetingTransaction::decode`] for what is recognised.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `slot` - Slot of the block to inspect
+    ///
+    /// # Returns
+    ///
+    /// Returns the report, or a ClientError if the block could not be fetched
+    pub fn slot_competition_report(
+        &self,
+        mint: &Pubkey,
+        slot: u64,
+    ) -> Result<mev::CompetitionReport, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Backfill);
+        let bonding_curve = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let block = self.get_full_block(slot)?;
+
+        let mut transactions = Vec::new();
+        for (index, transaction) in block
+            .transactions
+            .unwrap_or_default()
+            .into_iter()
+            .enumerate()
+        {
+            let Some(decoded) = transaction.transaction.decode() else {
+                continue;
+            };
+            let (succeeded, logs) = match transaction.meta {
+                Some(meta) => (
+                    meta.err.is_none(),
+                    Option::<Vec<String>>::from(meta.log_messages).unwrap_or_default(),
+                ),
+                None => (false, Vec::new()),
+            };
+
+            transactions.extend(mev::CompetingTransaction::decode(
+                index,
+                &decoded,
+                succeeded,
+                &logs,
+                mint,
+                &bonding_curve,
+            ));
+        }
+
+        Ok(mev::CompetitionReport {
+            mint: *mint,
+            slot,
+            transactions,
+        })
+    }
+
+    /// Gets the commitment used to fetch transactions and blocks
+    ///
+    /// Transactions and blocks cannot be fetched at processed commitment, so confirmed is used
+    /// in its place.
+    fn get_history_commitment(&self) -> CommitmentConfig {
+        if self.rpc.commitment() == CommitmentConfig::processed() {
+            CommitmentConfig::confirmed()
+        } else {
+            self.rpc.commitment()
+        }
+    }
+
+    /// Fetches a block with its full transactions and their metadata
+    fn get_full_block(&self, slot: u64) -> Result<UiConfirmedBlock, error::ClientError> {
+        self.rpc
+            .get_block_with_config(
+                slot,
+                RpcBlockConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    transaction_details: Some(TransactionDetails::Full),
+                    rewards: Some(false),
+                    commitment: Some(self.get_history_commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .map_err(error::ClientError::SolanaClientError)
+    }
+
+    /// Simulates the transactions of a bundle before it is submitted
+    ///
+    /// Each transaction is passed to `simulateTransaction` in bundle order, requesting the
+    /// post-simulation state of the bonding curves it creates or trades on. Signatures are not
+    /// verified and blockhashes are not replaced, so an expired blockhash shows up as a failure.
+    ///
+    /// Each simulation runs against the current cluster state rather than the state left by
+    /// the transactions before it, so it cannot prove the bundle executes atomically. See the
+    /// [`bundle`] module for what this means for transactions that depend on each other, and
+    /// [`PumpFun::replay_bundle`] for a check that carries curve state between transactions.
+    /// Call [`bundle::BundleSimulation::ensure_success`] on the result to abort before
+    /// submission.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - Signed transactions of the bundle, in execution order
+    ///
+    /// # Returns
+    ///
+    /// Returns the outcome of each transaction with the final state of each referenced curve,
+    /// or a ClientError if a simulation request fails
+    pub fn simulate_bundle(
+        &self,
+        transactions: &[VersionedTransaction],
+    ) -> Result<bundle::BundleSimulation, error::ClientError> {
+        let mut simulated = Vec::with_capacity(transactions.len());
+        for (index, transaction) in transactions.iter().enumerate() {
+            let curves = bundle::referenced_curves(transaction);
+            let result = self
+                .rpc
+                .simulate_transaction_with_config(
+                    transaction,
+                    RpcSimulateTransactionConfig {
+                        commitment: Some(self.rpc.commitment()),
+                        accounts: Some(RpcSimulateTransactionAccountsConfig {
+                            encoding: None,
+                            addresses: curves.iter().map(ToString::to_string).collect(),
+                        }),
+                        ..RpcSimulateTransactionConfig::default()
+                    },
+                )
+                .map_err(error::ClientError::SolanaClientError)?
+                .value;
+
+            let states = result.accounts.unwrap_or_default();
+            let curves = curves
+                .into_iter()
+                .enumerate()
+                .map(|(position, curve)| {
+                    let state = states
+                        .get(position)
+                        .cloned()
+                        .flatten()
+                        .and_then(|account| account.decode::<Account>())
+                        .and_then(|account| self.decode_account(&curve, &account.data).ok());
+                    (curve, state)
+                })
+                .collect();
+
+            simulated.push(bundle::SimulatedTransaction {
+                index,
+                signature: transaction.signatures.first().copied(),
+                err: result.err,
+                logs: result.logs.unwrap_or_default(),
+                units_consumed: result.units_consumed,
+                curves,
+            });
+        }
+
+        Ok(bundle::BundleSimulation::new(simulated))
+    }
+
+    /// Replays the Pump.fun instructions of a bundle in process before it is submitted
+    ///
+    /// Clones the global account and every bonding curve the bundle references at the current
+    /// slot, then executes the bundle's creates, buys and sells against them in order with
+    /// [`bundle::replay`]. Unlike [`PumpFun::simulate_bundle`], each transaction sees the curves
+    /// left by the ones before it, so a buy following the create of a new token is checked
+    /// against the new curve. Signatures, balances and other programs are not executed.
+    ///
+    /// # Arguments
+    ///
+    /// * `transactions` - Transactions of the bundle, in execution order
+    ///
+    /// # Returns
+    ///
+    /// Returns the outcome of each transaction with the final state of each referenced curve,
+    /// or a ClientError if the accounts could not be fetched or decoded
+    pub fn replay_bundle(
+        &self,
+        transactions: &[VersionedTransaction],
+    ) -> Result<bundle::BundleSimulation, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        let global = self.get_global_account()?;
+
+        let mut curves: Vec<Pubkey> = Vec::new();
+        for curve in transactions.iter().flat_map(bundle::referenced_curves) {
+            if !curves.contains(&curve) {
+                curves.push(curve);
+            }
+        }
+        let states: Vec<(Pubkey, Option<accounts::BondingCurveAccount>)> = self
+            .get_multiple_accounts_isolated(&curves)
+            .into_iter()
+            .zip(&curves)
+            .map(|(account, curve)| {
+                let state = match account? {
+                    Some(account) => Some(self.decode_account(curve, &account.data)?),
+                    None => None,
+                };
+                Ok((*curve, state))
+            })
+            .collect::<Result<_, error::ClientError>>()?;
+
+        Ok(bundle::replay(transactions, &global, &states))
+    }
+
+    /// Gets the claimable balance of every fee recipient declared in the global account
+    ///
+    /// This version of the program declares a single fee recipient. Balances are fetched with
+    /// one `getMultipleAccounts` call and exclude the lamports needed to keep each account
+    /// rent-exempt.
+    ///
+    /// # Returns
+    ///
+    /// Returns each fee recipient with its claimable balance in lamports, or a ClientError if the
+    /// operation fails
+    pub fn get_all_fee_recipient_balances(&self) -> Result<Vec<(Pubkey, u64)>, error::ClientError> {
+        let global_account = self.get_global_account()?;
+        let mut recipients = vec![global_account.fee_recipient];
+        recipients.dedup();
+
+        let accounts = self
+            .rpc
+            .get_multiple_accounts(&recipients)
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        let rent = Rent::default();
+        Ok(recipients
+            .into_iter()
+            .zip(accounts)
+            .map(|(recipient, account)| {
+                let balance = account.map_or(0, |account| {
+                    account
+                        .lamports
+                        .saturating_sub(rent.minimum_balance(account.data.len()))
+                });
+                (recipient, balance)
+            })
+            .collect())
+    }
+
+    /// Gets a token's bonding curve and mint accounts in one batched call
+    ///
+    /// Both accounts are fetched with a single `getMultipleAccounts` request, avoiding two serial
+    /// round-trips when looking up a token.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded token context if successful, a ClientError::BondingCurveNotFound if the
+    /// token has no bonding curve, or a ClientError if the operation fails
+    pub fn get_token_context(&self, mint: &Pubkey) -> Result<TokenContext, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let accounts = self
+            .rpc
+            .get_multiple_accounts(&[bonding_curve_pda, *mint])
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        let bonding_curve_account = accounts[0]
+            .as_ref()
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let mint_account = accounts[1]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Mint account not found"))?;
+
+        let bonding_curve = self.decode_account(&bonding_curve_pda, &bonding_curve_account.data)?;
+
+        let mint = screening::decode_mint(&mint_account.data)?;
+
+        Ok(TokenContext {
+            bonding_curve,
+            mint,
+        })
+    }
+
+    /// Gets everything about a token in one batched call, as of a single slot
+    ///
+    /// The global, bonding curve, mint and metadata accounts are fetched with a single
+    /// `getMultipleAccounts` request, so every field of the snapshot reflects the same slot. A
+    /// token without a metadata account still gets a snapshot, with its name, symbol and URI
+    /// left as None.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the snapshot if successful, a ClientError::BondingCurveNotFound if the token has
+    /// no bonding curve, a ClientError::Shedded if metadata enrichment is shed under RPC
+    /// pressure, or a ClientError if the operation fails
+    pub fn get_token_snapshot(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<summary::TokenSnapshot, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        self.check_shed(shed::Feature::MetadataEnrichment)?;
+        let global_pda = Self::get_global_pda_with_seeds(&self.seeds);
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let response = self
+            .rpc
+            .get_multiple_accounts_with_commitment(
+                &[
+                    global_pda,
+                    bonding_curve_pda,
+                    *mint,
+                    Self::get_metadata_pda(mint),
+                ],
+                self.rpc.commitment(),
+            )
+            .map_err(error::ClientError::SolanaClientError)?;
+        let accounts = response.value;
+
+        let global_account = accounts[0]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Global account not found"))?;
+        let bonding_curve_account = accounts[1]
+            .as_ref()
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let mint_account = accounts[2]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Mint account not found"))?;
+
+        summary::TokenSnapshot::from_accounts(
+            response.context.slot,
+            *mint,
+            &self.decode_account(&global_pda, &global_account.data)?,
+            self.decode_account(&bonding_curve_pda, &bonding_curve_account.data)?,
+            &mint_account.data,
+            accounts[3].as_ref().map(|account| account.data.as_slice()),
+        )
+    }
+
+    /// Values token holdings from bonding curves read at a single slot
+    ///
+    /// The global and bonding curve accounts are fetched in batched `getMultipleAccounts`
+    /// requests, each carrying the latest slot seen so far as its minimum context slot, and
+    /// batches served at an earlier slot are fetched again, so every holding is priced from the
+    /// same state. The valuation reports the slot it was taken at and whether all batches
+    /// agreed on it, see [`portfolio`] for how disagreements are handled.
+    ///
+    /// # Arguments
+    ///
+    /// * `holdings` - Holdings to value
+    ///
+    /// # Returns
+    ///
+    /// Returns the valuation of each holding in the order they were given, with the totals, or
+    /// a ClientError if the accounts could not be fetched or decoded
+    pub fn value_portfolio(
+        &self,
+        holdings: &[portfolio::Holding],
+    ) -> Result<portfolio::PortfolioValuation, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        let global_pda = Self::get_global_pda_with_seeds(&self.seeds);
+        let addresses: Vec<Pubkey> = std::iter::once(global_pda)
+            .chain(holdings.iter().map(|holding| {
+                Self::get_bonding_curve_pda_with_seeds(&self.seeds, &holding.mint)
+                    .unwrap_or_default()
+            }))
+            .collect();
+        let batches: Vec<&[Pubkey]> = addresses.chunks(MAX_MULTIPLE_ACCOUNTS).collect();
+
+        let (accounts, snapshot_slot, consistent) = portfolio::fetch_at_single_slot(
+            batches.len(),
+            portfolio::SNAPSHOT_RETRIES,
+            |batch, min_context_slot| {
+                let response = self
+                    .rpc
+                    .get_multiple_accounts_with_config(
+                        batches[batch],
+                        RpcAccountInfoConfig {
+                            encoding: Some(UiAccountEncoding::Base64Zstd),
+                            commitment: Some(self.rpc.commitment()),
+                            min_context_slot,
+                            ..RpcAccountInfoConfig::default()
+                        },
+                    )
+                    .map_err(error::ClientError::SolanaClientError)?;
+                Ok((response.context.slot, response.value))
+            },
+        )?;
+
+        let global_account = accounts[0]
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput("Global account not found"))?;
+        let global: accounts::GlobalAccount =
+            self.decode_account(&global_pda, &global_account.data)?;
+
+        let curves = addresses[1..]
+            .iter()
+            .zip(&accounts[1..])
+            .map(|(address, account)| match account {
+                Some(account) if account.owner == cpi::ID => {
+                    self.decode_account(address, &account.data).map(Some)
+                }
+                _ => Ok(None),
+            })
+            .collect::<Result<Vec<Option<accounts::BondingCurveAccount>>, _>>()?;
+
+        Ok(portfolio::PortfolioValuation::from_accounts(
+            snapshot_slot,
+            consistent,
+            &global,
+            holdings,
+            &curves,
+        ))
+    }
+
+    /// Screens many tokens for common safety red flags with as few RPC calls as possible
+    ///
+    /// Cheap checks read the mint, metadata and bonding curve accounts of every token through
+    /// batched `getMultipleAccounts` requests, and the global account is fetched once for the
+    /// whole batch. Expensive checks then run only for tokens that passed every selected cheap
+    /// check. Failures are reported per token: a missing account or failed lookup for one token
+    /// does not affect the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `mints` - Public keys of the token mints to screen
+    /// * `checks` - Checks to run
+    ///
+    /// # Returns
+    ///
+    /// Returns one result per mint, in the same order as `mints`
+    pub fn screen_tokens(
+        &self,
+        mints: &[Pubkey],
+        checks: screening::ScreenChecks,
+    ) -> Vec<Result<screening::SafetyReport, error::ClientError>> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Query);
+        use screening::ScreenChecks;
+
+        // Shared across the batch; the supply comparison is skipped if it cannot be fetched
+        let global = if checks.contains(ScreenChecks::BONDING_CURVE) {
+            self.get_global_account().ok()
+        } else {
+            None
+        };
+
+        // Mint, metadata and bonding curve addresses of every token, in that order
+        let addresses: Vec<Pubkey> = mints
+            .iter()
+            .flat_map(|mint| {
+                [
+                    *mint,
+                    Self::get_metadata_pda(mint),
+                    Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint).unwrap_or_default(),
+                ]
+            })
+            .collect();
+        let mut fetched = self.get_multiple_accounts_isolated(&addresses).into_iter();
+
+        mints
+            .iter()
+            .map(|mint| {
+                let mint_account = fetched.next().unwrap()?;
+                let metadata_account = fetched.next().unwrap()?;
+                let bonding_curve_account = fetched.next().unwrap()?;
+                if let Some(account) = &mint_account {
+                    self.cache_token_program(mint, &account.owner)?;
+                }
+
+                let mut report = screening::SafetyReport::from_accounts(
+                    *mint,
+                    checks,
+                    mint_account.as_ref().map(|account| account.data.as_slice()),
+                    metadata_account
+                        .as_ref()
+                        .map(|account| account.data.as_slice()),
+                    bonding_curve_account
+                        .as_ref()
+                        .map(|account| account.data.as_slice()),
+                    global.as_ref(),
+                )?;
+                if !report.passes_cheap_checks() {
+                    return Ok(report);
+                }
+
+                if checks.contains(ScreenChecks::HOLDERS) {
+                    report.top_holders_pct = Some(self.get_top_holders_pct(mint)?);
+                }
+                if checks.contains(ScreenChecks::CREATOR_HISTORY) {
+                    let creator = self.get_creator(mint)?;
+                    let signatures = self
+                        .rpc
+                        .get_signatures_for_address(&creator)
+                        .map_err(error::ClientError::SolanaClientError)?;
+                    report.creator = Some(creator);
+                    report.creator_transaction_count = Some(signatures.len());
+                }
+
+                Ok(report)
+            })
+            .collect()
+    }
+
+    /// Writes a watchlist of tokens to a file in the Solana token list format
+    ///
+    /// Mint and metadata accounts are fetched in batches, and the logo of each token is read
+    /// from its off-chain metadata, with up to the concurrency limit of fetches in flight.
+    /// Tokens whose accounts cannot be fetched or decoded are left out of the list, and logos
+    /// that cannot be resolved, or whose fetch is shed under RPC pressure, are omitted. See the
+    /// [`export`] module.
+    ///
+    /// # Arguments
+    ///
+    /// * `mints` - Public keys of the token mints to list, in order
+    /// * `path` - File to write the list to, replacing any existing file
+    ///
+    /// # Returns
+    ///
+    /// Returns the list that was written, or a ClientError::ExportError if the file could not
+    /// be written
+    pub async fn export_watchlist_token_list(
+        &self,
+        mints: &[Pubkey],
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<export::TokenListJson, error::ClientError> {
+        usage::scoped(usage::Operation::Query, async move {
+            let addresses: Vec<Pubkey> = mints
+                .iter()
+                .flat_map(|mint| [*mint, Self::get_metadata_pda(mint)])
+                .collect();
+            let mut fetched = self.get_multiple_accounts_isolated(&addresses).into_iter();
+
+            let infos: Vec<export::TokenInfo> = mints
+                .iter()
+                .filter_map(|mint| {
+                    let mint_account = fetched.next().unwrap();
+                    let metadata_account = fetched.next().unwrap();
+                    let info = match (mint_account, metadata_account) {
+                        (Ok(Some(mint_account)), Ok(Some(metadata_account))) => {
+                            export::TokenInfo::from_accounts(
+                                *mint,
+                                &mint_account.data,
+                                &metadata_account.data,
+                            )
+                        }
+                        (Err(err), _) | (_, Err(err)) => Err(err),
+                        _ => Err(error::ClientError::InvalidInput("Token account not found")),
+                    };
+                    match info {
+                        Ok(info) => Some(info),
+                        Err(err) => {
+                            tracing::warn!(
+                                mint = %self.redaction.mint(mint),
+                                "Leaving token out of list: {}",
+                                err
+                            );
+                            None
+                        }
+                    }
+                })
+                .collect();
+
+            let infos: Vec<export::TokenInfo> = futures::stream::iter(infos)
+                .map(|mut info| async move {
+                    // Logos are optional, so a shed fetch leaves the logo out like a failed one
+                    let fetch = self.check_shed(shed::Feature::OffchainMetadata);
+                    if let (Some(uri), Ok(())) = (&info.uri, fetch) {
+                        info.logo_uri = export::fetch_logo_uri(uri).await;
+                    }
+                    info
+                })
+                .buffered(self.max_concurrent_requests.max(1))
+                .collect()
+                .await;
+
+            let list = export::to_token_list_at(
+                &infos,
+                export::chain_id(&self.cluster),
+                self.clock.unix_timestamp(),
+            );
+            let file = std::fs::File::create(path).map_err(error::ClientError::ExportError)?;
+            serde_json::to_writer_pretty(file, &list)
+                .map_err(|err| error::ClientError::ExportError(err.into()))?;
+
+            Ok(list)
+        })
+        .await
+    }
+
+    /// Fetches many accounts in batches, isolating failures to the affected accounts
+    ///
+    /// Accounts are requested in chunks of up to 100 with `getMultipleAccounts`. If a chunk
+    /// fails, its accounts are fetched one by one so each gets its own result.
+    fn get_multiple_accounts_isolated(
+        &self,
+        addresses: &[Pubkey],
+    ) -> Vec<Result<Option<Account>, error::ClientError>> {
+        let mut results = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            match self.rpc.get_multiple_accounts(chunk) {
+                Ok(accounts) => results.extend(accounts.into_iter().map(Ok)),
+                Err(_) => results.extend(chunk.iter().map(|address| {
+                    self.rpc
+                        .get_account_with_commitment(address, self.rpc.commitment())
+                        .map(|response| response.value)
+                        .map_err(error::ClientError::SolanaClientError)
+                })),
+            }
+        }
+
+        results
+    }
+
+    /// Checks that a token's bonding curve token account matches what the program expects
+    ///
+    /// The account at the derived associated token address must be owned by the mint's token
+    /// program, hold the mint, have the bonding curve as its authority and hold at least the
+    /// curve's real token reserves. Anyone can send tokens to the account, so a larger balance
+    /// is not an issue. A mismatch points to a program version or seed configuration this
+    /// client does not match, or to corrupted state, and trading should not proceed.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns a report listing any inconsistencies, a ClientError::BondingCurveNotFound if the
+    /// curve does not exist, or a ClientError if the operation fails
+    pub fn verify_curve_ata(&self, mint: &Pubkey) -> Result<CurveAtaReport, error::ClientError> {
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let token_program = self.get_token_program(mint)?;
+        let expected_ata = instruction::derive_curve_ata(&bonding_curve_pda, mint, &token_program);
+
+        let mut accounts = self
+            .get_multiple_accounts_isolated(&[bonding_curve_pda, expected_ata])
+            .into_iter();
+        let curve_account = accounts
+            .next()
+            .unwrap_or(Ok(None))?
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let ata_account = accounts.next().unwrap_or(Ok(None))?;
+        let curve: accounts::BondingCurveAccount =
+            self.decode_account(&bonding_curve_pda, &curve_account.data)?;
+
+        Ok(inspect_curve_ata(
+            mint,
+            &bonding_curve_pda,
+            &expected_ata,
+            &token_program,
+            &curve,
+            ata_account.as_ref(),
+        ))
+    }
+
+    /// Gets the percentage of a token's supply held by its largest holders
+    ///
+    /// The bonding curve's token account is excluded, since it holds the unsold supply.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the percentage of the supply held by up to 20 of the largest holders, a
+    /// ClientError::Shedded if holder counts are shed under RPC pressure, or a ClientError if
+    /// the operation fails
+    pub fn get_top_holders_pct(&self, mint: &Pubkey) -> Result<f64, error::ClientError> {
+        self.check_shed(shed::Feature::HolderCounts)?;
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let token_program = self.get_token_program(mint)?;
+        let bonding_curve_ata =
+            instruction::derive_curve_ata(&bonding_curve_pda, mint, &token_program).to_string();
+
+        let supply = self
+            .rpc
+            .get_token_supply(mint)
+            .map_err(error::ClientError::SolanaClientError)?;
+        let supply: u64 = supply
+            .amount
+            .parse()
+            .map_err(|_| error::ClientError::InvalidInput("Invalid token supply"))?;
+        if supply == 0 {
+            return Ok(0.0);
+        }
+
+        let held: u64 = self
+            .rpc
+            .get_token_largest_accounts(mint)
+            .map_err(error::ClientError::SolanaClientError)?
+            .iter()
+            .filter(|holder| holder.address != bonding_curve_ata)
+            .filter_map(|holder| holder.amount.amount.parse::<u64>().ok())
+            .sum();
+
+        Ok(held as f64 / supply as f64 * 100.0)
+    }
+
+    /// Gets who can still mint more of a token
+    ///
+    /// Pump.fun tokens have their mint authority revoked at creation. An authority that is
+    /// still the program's mint authority PDA, or any other account, indicates a non-standard
+    /// or malicious token. See [`screening::MintAuthority`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the classified mint authority, or a ClientError if the mint cannot be fetched or
+    /// decoded
+    pub fn get_mint_authority(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<screening::MintAuthority, error::ClientError> {
+        let account = self
+            .rpc
+            .get_account(mint)
+            .map_err(error::ClientError::SolanaClientError)?;
+        screening::MintAuthority::from_mint_data(
+            &account.data,
+            &Self::get_mint_authority_pda_with_seeds(&self.seeds),
+        )
+    }
+
+    /// Checks whether a token's mint authority has been revoked
+    ///
+    /// Use [`PumpFun::get_mint_authority`] to inspect the authority when it has not.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns whether no account can mint more of the token, or a ClientError if the mint
+    /// cannot be fetched or decoded
+    pub fn is_mint_authority_renounced(&self, mint: &Pubkey) -> Result<bool, error::ClientError> {
+        Ok(self.get_mint_authority(mint)?.is_renounced())
+    }
+
+    /// Gets the percentage of a token's supply held by its creator
+    ///
+    /// The creator is found with [`PumpFun::get_creator`] and their balance read from their
+    /// associated token account, so tokens moved to other wallets are not counted. The
+    /// denominator is the curve's total supply, including tokens still held by the curve: those
+    /// are unsold rather than held by anyone, and counting them keeps the figure comparable
+    /// across the life of the curve. A creator who has sold everything, or never bought, holds
+    /// 0%.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the creator's share of the total supply as a percentage, a ClientError::Shedded
+    /// if holder counts are shed under RPC pressure, or a ClientError if the operation fails
+    pub fn get_creator_holdings_pct(&self, mint: &Pubkey) -> Result<f64, error::ClientError> {
+        self.check_shed(shed::Feature::HolderCounts)?;
+        let creator = self.get_creator(mint)?;
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let token_program = self.get_token_program(mint)?;
+        let creator_ata = instruction::derive_user_ata(&creator, mint, &token_program);
+
+        let mut accounts = self
+            .get_multiple_accounts_isolated(&[bonding_curve_pda, creator_ata])
+            .into_iter();
+        let curve_account = accounts
+            .next()
+            .unwrap_or(Ok(None))?
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let curve: accounts::BondingCurveAccount =
+            self.decode_account(&bonding_curve_pda, &curve_account.data)?;
+        if curve.token_total_supply == 0 {
+            return Ok(0.0);
+        }
+
+        // A closed account means the creator sold everything and reclaimed the rent
+        let balance = accounts
+            .next()
+            .unwrap_or(Ok(None))?
+            .and_then(|account| {
+                account
+                    .data
+                    .get(..TokenAccount::LEN)
+                    .and_then(|data| TokenAccount::unpack_from_slice(data).ok())
+            })
+            .map_or(0, |token_account| token_account.amount);
+
+        Ok(balance as f64 / curve.token_total_supply as f64 * 100.0)
+    }
+
+    /// Gets the wallet that created a token
+    ///
+    /// Finds the bonding curve's first transaction and returns that transaction's fee payer.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the creator's public key, or a ClientError if the operation fails
+    pub fn get_creator(&self, mint: &Pubkey) -> Result<Pubkey, error::ClientError> {
+        if let Some(creator) = self.cached_lookup(CACHE_CREATOR, mint) {
+            return Ok(creator);
+        }
+
+        let signature = self.get_token_age(mint)?.creation_signature;
+        let transaction = self
+            .rpc
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(self.rpc.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        let creator = transaction
+            .transaction
+            .transaction
+            .decode()
+            .and_then(|transaction| transaction.message.static_account_keys().first().copied())
+            .ok_or(error::ClientError::InvalidInput(
+                "Invalid creation transaction",
+            ))?;
+        self.store_lookup(CACHE_CREATOR, mint, &creator);
+
+        Ok(creator)
+    }
+
+    /// Gets when a token was created
+    ///
+    /// Walks the bonding curve's signature history back to its first transaction. Creation data
+    /// never changes, so results are cached permanently on the client; see
+    /// [`PumpFun::get_cached_token_ages`].
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the token's creation slot, time and signature, a ClientError::BondingCurveNotFound
+    /// if the bonding curve has no history, or a ClientError if the operation fails
+    pub fn get_token_age(&self, mint: &Pubkey) -> Result<TokenAge, error::ClientError> {
+        if let Some(age) = self.get_cached_token_ages().get(mint) {
+            return Ok(*age);
+        }
+        if let Some(age) = self.cached_lookup::<TokenAge>(CACHE_TOKEN_AGE, mint) {
+            self.token_ages
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .insert(*mint, age);
+            return Ok(age);
+        }
+
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+
+        let mut before: Option<Signature> = None;
+        let mut first: Option<TokenAge> = None;
+        loop {
+            let signatures = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &bonding_curve_pda,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: None,
+                        commitment: Some(self.rpc.commitment()),
+                    },
+                )
+                .map_err(error::ClientError::SolanaClientError)?;
+
+            let Some(last) = signatures.last() else {
+                break;
+            };
+            let signature = Signature::from_str(&last.signature)
+                .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))?;
+            before = Some(signature);
+            first = Some(TokenAge {
+                creation_slot: last.slot,
+                creation_block_time: last.block_time,
+                creation_signature: signature,
+            });
+        }
+
+        let age = first.ok_or(error::ClientError::BondingCurveNotFound)?;
+        self.token_ages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(*mint, age);
+        self.store_lookup(CACHE_TOKEN_AGE, mint, &age);
+
+        Ok(age)
+    }
+
+    /// Gets the SOL transfers to and from a wallet in its recent transaction history
+    ///
+    /// Collects the funding edges [`analytics::cluster_wallets`] works on. Only top-level System
+    /// Program transfers of successful transactions are found, see
+    /// [`analytics::decode_transfers`]. Each transaction costs a request, so keep `limit` small
+    /// when scanning many wallets.
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - Wallet whose transfers to get
+    /// * `limit` - Most recent transactions to scan, at most 1000
+    ///
+    /// # Returns
+    ///
+    /// Returns the transfers newest first, or a ClientError if the operation fails
+    pub fn get_sol_transfers(
+        &self,
+        wallet: &Pubkey,
+        limit: usize,
+    ) -> Result<Vec<analytics::Transfer>, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Backfill);
+        let signatures = self
+            .rpc
+            .get_signatures_for_address_with_config(
+                wallet,
+                GetConfirmedSignaturesForAddress2Config {
+                    before: None,
+                    until: None,
+                    limit: Some(limit),
+                    commitment: Some(self.rpc.commitment()),
+                },
+            )
+            .map_err(error::ClientError::SolanaClientError)?;
+
+        let mut transfers = Vec::new();
+        for status in signatures.iter().filter(|status| status.err.is_none()) {
+            let signature = Signature::from_str(&status.signature)
+                .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))?;
+            let transaction = self
+                .rpc
+                .get_transaction_with_config(
+                    &signature,
+                    RpcTransactionConfig {
+                        encoding: Some(UiTransactionEncoding::Base64),
+                        commitment: Some(self.rpc.commitment()),
+                        max_supported_transaction_version: Some(0),
+                    },
+                )
+                .map_err(error::ClientError::SolanaClientError)?;
+            let Some(decoded) = transaction.transaction.transaction.decode() else {
+                continue;
+            };
+            let timestamp = transaction
+                .block_time
+                .or(status.block_time)
+                .unwrap_or_default();
+            transfers.extend(
+                analytics::decode_transfers(&decoded, timestamp)
+                    .into_iter()
+                    .filter(|transfer| transfer.from == *wallet || transfer.to == *wallet),
+            );
+        }
+
+        Ok(transfers)
+    }
+
+    /// Gets the creation data of every token looked up so far
+    ///
+    /// # Returns
+    ///
+    /// Returns a snapshot of the cached token ages, keyed by mint
+    pub fn get_cached_token_ages(&self) -> HashMap<Pubkey, TokenAge> {
+        self.token_ages
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Gets the current time on the cluster
+    ///
+    /// Reads the block time of the current slot and caches it for
+    /// [`utils::clock::CLOCK_CACHE_TTL`]; in between, the cached reading is advanced by the local
+    /// monotonic time elapsed. Use this instead of the local clock when comparing against
+    /// on-chain timestamps.
+    ///
+    /// # Returns
+    ///
+    /// Returns the estimated Unix timestamp on the cluster, or a ClientError if the operation fails
+    pub fn get_cluster_time(&self) -> Result<i64, error::ClientError> {
+        let mut cached = self
+            .clock_cache
+            .cluster_time
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let now = self.clock.now();
+        if let Some(time) = cached.filter(|time| {
+            now.saturating_duration_since(time.fetched_at) < utils::clock::CLOCK_CACHE_TTL
+        }) {
+            return Ok(time.estimate_at(now));
+        }
+
+        let slot = self
+            .rpc
+            .get_slot()
+            .map_err(error::ClientError::SolanaClientError)?;
+        let unix_timestamp = self
+            .rpc
+            .get_block_time(slot)
+            .map_err(error::ClientError::SolanaClientError)?;
+        let now = self.clock.now();
+        let time = utils::clock::ClusterTime {
+            slot,
+            unix_timestamp,
+            fetched_at: now,
+        };
+        *cached = Some(time);
+
+        Ok(time.estimate_at(now))
+    }
+
+    /// Gets the average slot duration measured over recent performance samples
+    ///
+    /// The measurement is cached for [`utils::clock::CLOCK_CACHE_TTL`]. Falls back to
+    /// [`utils::clock::NOMINAL_SLOT_DURATION`] if the node reports no samples.
+    ///
+    /// # Returns
+    ///
+    /// Returns the measured slot duration, or a ClientError if the operation fails
+    pub fn get_slot_duration(&self) -> Result<Duration, error::ClientError> {
+        let mut cached = self
+            .clock_cache
+            .slot_duration
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some((duration, measured_at)) = *cached {
+            if self.clock.now().saturating_duration_since(measured_at)
+                < utils::clock::CLOCK_CACHE_TTL
+            {
+                return Ok(duration);
+            }
+        }
+
+        let samples = self
+            .rpc
+            .get_recent_performance_samples(Some(utils::clock::SLOT_DURATION_SAMPLES))
+            .map_err(error::ClientError::SolanaClientError)?;
+        let duration = utils::clock::measure_slot_duration(&samples)
+            .unwrap_or(utils::clock::NOMINAL_SLOT_DURATION);
+        *cached = Some((duration, self.clock.now()));
+
+        Ok(duration)
+    }
+
+    /// Converts a number of slots to a duration using the measured slot duration
+    ///
+    /// # Arguments
+    ///
+    /// * `slots` - Number of slots
+    ///
+    /// # Returns
+    ///
+    /// Returns the time it currently takes for that many slots to pass, or a ClientError if the
+    /// slot duration could not be measured
+    pub fn slots_to_duration(&self, slots: u64) -> Result<Duration, error::ClientError> {
+        Ok(utils::clock::slots_to_duration(
+            slots,
+            self.get_slot_duration()?,
+        ))
+    }
+
+    /// Converts a duration to a number of slots using the measured slot duration
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - Duration to convert
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of slots that currently pass in that duration, rounded up, or a
+    /// ClientError if the slot duration could not be measured
+    pub fn duration_to_slots(&self, duration: Duration) -> Result<u64, error::ClientError> {
+        Ok(utils::clock::duration_to_slots(
+            duration,
+            self.get_slot_duration()?,
+        ))
+    }
+
+    /// Waits until the cluster reaches a slot
+    ///
+    /// Sleeps for the estimated time until the slot, then confirms with `getSlot`, repeating with
+    /// shorter sleeps as the slot approaches so a slow cluster does not cause an early return.
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - Slot to wait for
+    ///
+    /// # Returns
+    ///
+    /// Returns the slot the cluster was at when the wait ended, or a ClientError if the operation fails
+    pub async fn await_slot(&self, slot: u64) -> Result<u64, error::ClientError> {
+        const MIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+        loop {
+            let current = self
+                .rpc
+                .get_slot()
+                .map_err(error::ClientError::SolanaClientError)?;
+            if current >= slot {
+                return Ok(current);
+            }
+
+            let remaining = self.slots_to_duration(slot - current)?;
+            tokio::time::sleep(remaining.max(MIN_POLL_INTERVAL)).await;
+        }
+    }
+
+    /// Gets the net SOL that flowed into a token's bonding curve over a recent window
+    ///
+    /// Walks the bonding curve's signature history back to the start of the window and sums
+    /// the lamport balance change of the bonding curve account in each successful transaction.
+    /// Buys add to the total and sells subtract from it.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `window` - How far back from now to look
+    ///
+    /// # Returns
+    ///
+    /// Returns the net inflow in lamports (negative when sells dominate), or a ClientError if the operation fails
+    pub fn get_net_sol_inflow(
+        &self,
+        mint: &Pubkey,
+        window: Duration,
+    ) -> Result<i64, error::ClientError> {
+        let _usage = usage::OperationScope::enter(usage::Operation::Backfill);
+        let bonding_curve_pda = Self::get_bonding_curve_pda_with_seeds(&self.seeds, mint)
+            .ok_or(error::ClientError::BondingCurveNotFound)?;
+        let cutoff = self.clock.unix_timestamp() - window.as_secs() as i64;
+
+        let mut net_inflow: i64 = 0;
+        let mut before: Option<Signature> = None;
+        loop {
+            let signatures = self
+                .rpc
+                .get_signatures_for_address_with_config(
+                    &bonding_curve_pda,
+                    GetConfirmedSignaturesForAddress2Config {
+                        before,
+                        until: None,
+                        limit: None,
+                        commitment: Some(self.rpc.commitment()),
+                    },
+                )
+                .map_err(error::ClientError::SolanaClientError)?;
+
+            let Some(last) = signatures.last() else {
+                break;
+            };
+            before = Some(
+                Signature::from_str(&last.signature)
+                    .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))?,
+            );
+
+            let mut reached_cutoff = false;
+            for status in &signatures {
+                if status.block_time.is_none_or(|time| time < cutoff) {
+                    reached_cutoff = true;
+                    break;
+                }
+                if status.err.is_some() {
+                    continue;
+                }
+
+                let signature = Signature::from_str(&status.signature)
+                    .map_err(|_| error::ClientError::InvalidInput("Invalid signature"))?;
+                let transaction = self
+                    .rpc
+                    .get_transaction_with_config(
+                        &signature,
+                        RpcTransactionConfig {
+                            encoding: Some(UiTransactionEncoding::Base64),
+                            commitment: Some(self.rpc.commitment()),
+                            max_supported_transaction_version: Some(0),
+                        },
+                    )
+                    .map_err(error::ClientError::SolanaClientError)?;
+
+                net_inflow +=
+                    utils::get_lamport_change(&transaction, &bonding_curve_pda).unwrap_or(0);
+            }
+
+            if reached_cutoff {
+                break;
+            }
+        }
+
+        Ok(net_inflow)
+    }
+
+    /// Estimates how long until a token's bonding curve completes and migrates
+    ///
+    /// Measures the net SOL inflow into the bonding curve over the last
+    /// [`GRADUATION_VELOCITY_WINDOW`] (five minutes) and extrapolates how long it would take to
+    /// bring in the SOL still required for the curve to complete at that rate.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns Some(duration) with the estimated time to graduation (zero if already complete),
+    /// None if the curve is stalling (zero or negative inflow), or a ClientError if the operation fails
+    pub fn estimate_time_to_graduate(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<Option<Duration>, error::ClientError> {
+        let bonding_curve_account = self.get_bonding_curve_account(mint)?;
+        if bonding_curve_account.complete {
+            return Ok(Some(Duration::ZERO));
+        }
+
+        let net_inflow = self.get_net_sol_inflow(mint, GRADUATION_VELOCITY_WINDOW)?;
+
+        Ok(utils::extrapolate_time_to_graduate(
+            bonding_curve_account.sol_to_graduate(),
+            net_inflow,
+            GRADUATION_VELOCITY_WINDOW,
+        ))
+    }
+
+    /// Waits for a token's bonding curve to complete
+    ///
+    /// Subscribes to the program logs of transactions mentioning the mint and resolves with the
+    /// `CompleteEvent` emitted for it, which marks the point at which liquidity can be migrated.
+    /// The websocket subscription is closed before returning, whether the event fired or not.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `timeout` - Maximum time to wait for the event
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded CompleteEvent, a ClientError::Timeout if it did not fire in time, or a
+    /// ClientError if the curve has already completed or the subscription fails
+    pub async fn wait_for_graduation(
+        &self,
+        mint: &Pubkey,
+        timeout: Duration,
+    ) -> Result<events::CompleteEvent, error::ClientError> {
+        self.wait_for_graduation_with_cancel(mint, timeout, &cancel::CancellationToken::new())
+            .await
+    }
+
+    /// Waits for a token's bonding curve to complete until cancelled
+    ///
+    /// Behaves like [`PumpFun::wait_for_graduation`], but also stops when the token is
+    /// cancelled. However the wait ends, the logs subscription is unsubscribed and the websocket
+    /// connection closed before returning, so no task or connection outlives the call.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `timeout` - Maximum time to wait for the event
+    /// * `cancel` - Token that stops the wait when cancelled
+    ///
+    /// # Returns
+    ///
+    /// Returns the decoded CompleteEvent, a ClientError::Cancelled if the token was cancelled, a
+    /// ClientError::Timeout if the event did not fire in time, or a ClientError if the curve has
+    /// already completed or the subscription fails
+    pub async fn wait_for_graduation_with_cancel(
+        &self,
+        mint: &Pubkey,
+        timeout: Duration,
+        cancel: &cancel::CancellationToken,
+    ) -> Result<events::CompleteEvent, error::ClientError> {
+        usage::scoped(usage::Operation::Subscription, async move {
+            if cancel.is_cancelled() {
+                return Err(error::ClientError::Cancelled);
+            }
+
+            let pubsub = PubsubClient::new(self.cluster.ws_url())
+                .await
+                .map_err(error::ClientError::PubsubClientError)?;
+
+            let result = tokio::time::timeout(timeout, async {
+                let (mut stream, unsubscribe) = pubsub
+                    .logs_subscribe(
+                        RpcTransactionLogsFilter::Mentions(vec![mint.to_string()]),
+                        RpcTransactionLogsConfig {
+                            commitment: Some(self.rpc.commitment()),
+                        },
+                    )
+                    .await
+                    .map_err(error::ClientError::PubsubClientError)?;
+
+                // The event will never fire for a curve that completed before we subscribed
+                let bonding_curve_account = self.get_bonding_curve_account(mint)?;
+                if bonding_curve_account.complete {
+                    unsubscribe().await;
+                    return Err(error::ClientError::BondingCurveError("Curve is complete"));
+                }
+
+                let mut outcome = Err(error::ClientError::SubscriptionClosed);
+                let mut cancelled = std::pin::pin!(cancel.cancelled());
+                loop {
+                    let response =
+                        match futures::future::select(stream.next(), cancelled.as_mut()).await {
+                            futures::future::Either::Left((Some(response), _)) => response,
+                            futures::future::Either::Left((None, _)) => break,
+                            futures::future::Either::Right(_) => {
+                                outcome = Err(error::ClientError::Cancelled);
+                                break;
+                            }
+                        };
+                    if response.value.err.is_some() {
+                        continue;
+                    }
+
+                    if let Some(event) =
+                        events::parse_logs::<events::CompleteEvent>(&response.value.logs)
+                            .into_iter()
+                            .find(|event| event.mint == *mint)
+                    {
+                        outcome = Ok(event);
+                        break;
+                    }
+                }
+
+                drop(stream);
+                unsubscribe().await;
+                outcome
+            })
+            .await;
+
+            // Dropping the connection ends any subscription left open by a timeout
+            let _ = pubsub.shutdown().await;
+
+            let event = result.map_err(|_| error::ClientError::Timeout)??;
+            if self.ata_cleanup.is_some() {
+                if let Err(err) = self.queue_ata_cleanup(mint) {
+                    tracing::warn!(
+                        mint = %self.redaction.mint(mint),
+                        "Failed to queue token account cleanup: {}",
+                        err
+                    );
+                }
+            }
+
+            Ok(event)
+        })
+        .await
+    }
+
+    /// Calls back with every event the Pump.fun program emits
+    ///
+    /// Subscribes to the logs of transactions mentioning the program over the cluster
+    /// websocket, and decodes the events of every transaction that succeeded, see
+    /// [`events::parse_all_logs`]. The callback is called in log order on the task awaiting
+    /// this call, so slow work should be handed off rather than done in it.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Called with each decoded event
+    ///
+    /// # Returns
+    ///
+    /// Returns a ClientError::SubscriptionClosed once the subscription ends, or a ClientError if
+    /// it fails
+    pub async fn subscribe_events(
+        &self,
+        callback: impl FnMut(events::PumpFunEvent),
+    ) -> Result<(), error::ClientError> {
+        self.subscribe_events_with_cancel(callback, &cancel::CancellationToken::new())
+            .await
+    }
+
+    /// Calls back with every event the Pump.fun program emits until cancelled
+    ///
+    /// Behaves like [`PumpFun::subscribe_events`], but also stops when the token is cancelled.
+    /// However the subscription ends, it is unsubscribed and the websocket connection closed
+    /// before returning.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback` - Called with each decoded event
+    /// * `cancel` - Token that stops the subscription when cancelled
+    ///
+    /// # Returns
+    ///
+    /// Returns once cancelled, a ClientError::SubscriptionClosed if the subscription ends
+    /// before, or a ClientError if it fails
+    pub async fn subscribe_events_with_cancel(
+        &self,
+        mut callback: impl FnMut(events::PumpFunEvent),
+        cancel: &cancel::CancellationToken,
+    ) -> Result<(), error::ClientError> {
+        usage::scoped(usage::Operation::Subscription, async move {
+            if cancel.is_cancelled() {
+                return Ok(());
+            }
+
+            let pubsub = PubsubClient::new(self.cluster.ws_url())
+                .await
+                .map_err(error::ClientError::PubsubClientError)?;
+
+            let result = async {
+                let (mut stream, unsubscribe) = pubsub
+                    .logs_subscribe(
+                        RpcTransactionLogsFilter::Mentions(vec![cpi::ID.to_string()]),
+                        RpcTransactionLogsConfig {
+                            commitment: Some(self.rpc.commitment()),
+                        },
+                    )
+                    .await
+                    .map_err(error::ClientError::PubsubClientError)?;
+
+                let mut outcome = Err(error::ClientError::SubscriptionClosed);
+                let mut cancelled = std::pin::pin!(cancel.cancelled());
+                loop {
+                    let response =
+                        match futures::future::select(stream.next(), cancelled.as_mut()).await {
+                            futures::future::Either::Left((Some(response), _)) => response,
+                            futures::future::Either::Left((None, _)) => break,
+                            futures::future::Either::Right(_) => {
+                                outcome = Ok(());
+                                break;
+                            }
+                        };
+                    // Failed transactions roll back, so their events never happened
+                    if response.value.err.is_some() {
+                        continue;
+                    }
+
+                    for event in events::parse_all_logs(&response.value.logs) {
+                        callback(event);
+                    }
+                }
+
+                drop(stream);
+                unsubscribe().await;
+                outcome
+            }
+            .await;
+
+            let _ = pubsub.shutdown().await;
+            result
+        })
+        .await
+    }
+
+    /// Queues the payer's token account for a mint to be closed by the cleanup
+    ///
+    /// Called automatically when [`PumpFun::wait_for_graduation`] sees a curve complete. The
+    /// account's balance is only checked when the queue is processed, so tokens still held
+    /// now may be sold in the meantime.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the account was queued, false if it already was, or a ClientError if
+    /// cleanup is not enabled or the journal cannot be persisted
+    pub fn queue_ata_cleanup(&self, mint: &Pubkey) -> Result<bool, error::ClientError> {
+        let cleanup = self
+            .ata_cleanup
+            .as_ref()
+            .ok_or(error::ClientError::InvalidInput(
+                "Token account cleanup is not enabled",
+            ))?;
+        let token_program = self.get_token_program(mint)?;
+        let account = instruction::derive_user_ata(&self.signer().pubkey(), mint, &token_program);
+
+        let queued = cleanup.journal.queue_cleanup(cleanup::CleanupEntry {
+            mint: *mint,
+            account,
+            token_program,
+            state: cleanup::CleanupState::Queued,
+            queued_at: self.clock.unix_timestamp(),
+            processed_at: None,
+            balance: None,
+            signature: None,
+        })?;
+        if queued {
+            tracing::debug!(
+                mint = %self.redaction.mint(mint),
+                account = %self.redaction.wallet(&account),
+                "Queued token account cleanup"
+            );
+        }
+        Ok(queued)
+    }
+
+    /// Closes a batch of queued token accounts if the network is quiet
+    ///
+    /// The network is quiet when the median recent priority fee is at most the configured
+    /// threshold; otherwise, or if the fees cannot be read, nothing is processed and the queue
+    /// is left for a later call. Each queued account is fetched again, and only empty token
+    /// accounts owned by the payer are closed, in a single transaction with the configured
+    /// compute unit price. Every processed entry is updated in the journal.
+    ///
+    /// # Returns
+    ///
+    /// Returns the entries processed by this call, empty if cleanup is disabled, the queue is
+    /// empty or the network is busy, or a ClientError if the journal cannot be persisted
+    pub async fn process_ata_cleanup(
+        &self,
+    ) -> Result<Vec<cleanup::CleanupEntry>, error::ClientError> {
+        self.trade_scoped(usage::Operation::Cleanup, async move {
+            let Some(cleanup) = &self.ata_cleanup else {
+                return Ok(Vec::new());
+            };
+            let mut batch = cleanup.journal.queued_cleanups();
+            batch.truncate(cleanup.config.max_batch.max(1));
+            if batch.is_empty() {
+                return Ok(batch);
+            }
+
+            let quiet = self.rpc.get_recent_prioritization_fees(&[]).map(|fees| {
+                let fees: Vec<u64> = fees.iter().map(|fee| fee.prioritization_fee).collect();
+                cleanup::is_quiet(&fees, cleanup.config.quiet_fee_threshold)
+            });
+            if !matches!(quiet, Ok(true)) {
+                tracing::debug!(queued = batch.len(), "Deferring token account cleanup");
+                return Ok(Vec::new());
+            }
+
+            let payer = self.signer().pubkey();
+            let addresses: Vec<Pubkey> = batch.iter().map(|entry| entry.account).collect();
+            let accounts = self.get_multiple_accounts_isolated(&addresses);
+            let mut closable = Vec::new();
+            let mut processed = Vec::new();
+            for (mut entry, account) in batch.into_iter().zip(accounts) {
+                // Accounts that cannot be fetched stay queued
+                let Ok(account) = account else {
+                    continue;
+                };
+                match cleanup::check_closable(account.as_ref(), &payer, &entry.token_program) {
+                    Ok(()) => closable.push(entry),
+                    Err(state) => {
+                        entry.balance = (state == cleanup::CleanupState::SkippedNonzeroBalance)
+                            .then(|| account.as_ref().and_then(token_account_amount))
+                            .flatten();
+                        entry.state = state;
+                        entry.processed_at = Some(self.clock.unix_timestamp());
+                        processed.push(entry);
+                    }
+                }
+            }
+
+            if !closable.is_empty() {
+                let result = self.close_token_accounts(&closable, &cleanup.config).await;
+                for mut entry in closable {
+                    entry.processed_at = Some(self.clock.unix_timestamp());
+                    match &result {
+                        Ok(signature) => {
+                            entry.state = cleanup::CleanupState::Closed;
+                            entry.signature = Some(*signature);
+                        }
+                        Err(err) => {
+                            tracing::warn!(
+                                mint = %self.redaction.mint(&entry.mint),
+                                account = %self.redaction.wallet(&entry.account),
+                                "Failed to close token account: {}",
+                                err
+                            );
+                            entry.state = cleanup::CleanupState::Failed;
+                        }
+                    }
+                    processed.push(entry);
+                }
+            }
+
+            for entry in &processed {
+                cleanup.journal.update_cleanup(entry.clone())?;
+            }
+            Ok(processed)
+        })
+        .await
+    }
+
+    /// Closes empty token accounts of the payer in a single transaction
+    async fn close_token_accounts(
+        &self,
+        entries: &[cleanup::CleanupEntry],
+        config: &cleanup::AtaCleanupConfig,
+    ) -> Result<Signature, error::ClientError> {
+        let payer = self.signer().pubkey();
+        let mut request = self
+            .program
+            .request()
+            .instruction(ComputeBudgetInstruction::set_compute_unit_limit(
+                cleanup::CLOSE_ACCOUNT_COMPUTE_UNITS * entries.len() as u32,
+            ))
+            .instruction(ComputeBudgetInstruction::set_compute_unit_price(
+                config.compute_unit_price,
+            ));
+        for entry in entries {
+            request = request.instruction(cleanup::close_instruction(
+                &entry.account,
+                &payer,
+                &entry.token_program,
+            ));
+        }
+
+        let transaction = self.sign_request(&request, &[]).await?;
+        self.send_transaction(&transaction).await
+    }
+}
+
+/// Balance of a token account, if the account data decodes
+fn token_account_amount(account: &Account) -> Option<u64> {
+    account
+        .data
+        .get(..TokenAccount::LEN)
+        .and_then(|data| TokenAccount::unpack_from_slice(data).ok())
+        .map(|token_account| token_account.amount)
+}
+
+/// Compares a bonding curve's token account with the curve
+fn inspect_curve_ata(
+    mint: &Pubkey,
+    bonding_curve: &Pubkey,
+    expected_ata: &Pubkey,
+    token_program: &Pubkey,
+    curve: &accounts::BondingCurveAccount,
+    ata_account: Option<&Account>,
+) -> CurveAtaReport {
+    let mut report = CurveAtaReport {
+        bonding_curve: *bonding_curve,
+        expected_ata: *expected_ata,
+        balance: None,
+        reserves: curve.real_token_reserves,
+        issues: Vec::new(),
+    };
+
+    let Some(account) = ata_account else {
+        report.issues.push(CurveAtaIssue::Missing);
+        return report;
+    };
+    if account.owner != *token_program {
+        report.issues.push(CurveAtaIssue::WrongProgram {
+            actual: account.owner,
+        });
+    }
+
+    // Token-2022 accounts share the base layout, followed by extensions
+    let Some(token_account) = account
+        .data
+        .get(..TokenAccount::LEN)
+        .and_then(|data| TokenAccount::unpack_from_slice(data).ok())
+    else {
+        report.issues.push(CurveAtaIssue::InvalidData);
+        return report;
+    };
+    if token_account.mint != *mint {
+        report.issues.push(CurveAtaIssue::WrongMint {
+            actual: token_account.mint,
+        });
+    }
+    if token_account.owner != *bonding_curve {
+        report.issues.push(CurveAtaIssue::WrongAuthority {
+            actual: token_account.owner,
+        });
+    }
+    if token_account.amount < curve.real_token_reserves {
+        report.issues.push(CurveAtaIssue::BalanceBelowReserves {
+            balance: token_account.amount,
+            reserves: curve.real_token_reserves,
+        });
+    }
+    report.balance = Some(token_account.amount);
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::signer::keypair::Keypair;
+
+    #[test]
+    fn test_new_client() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        assert_eq!(client.payer.pubkey(), payer.pubkey());
+    }
+
+    #[test]
+    fn test_get_token_age_cached() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        let mint = Pubkey::new_unique();
+        let age = TokenAge {
+            creation_slot: 300_000_000,
+            creation_block_time: None,
+            creation_signature: Signature::new_unique(),
+        };
+
+        // Cached ages are served without touching the network
+        client.token_ages.lock().unwrap().insert(mint, age);
+        assert_eq!(client.get_token_age(&mint).unwrap(), age);
+        assert_eq!(client.get_cached_token_ages().get(&mint), Some(&age));
+        assert_eq!(age.age(client.clock()), None);
+    }
+
+    #[test]
+    fn test_quote_commitment() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(
+            Cluster::Devnet,
+            &payer,
+            Some(CommitmentConfig::confirmed()),
+            None,
+        );
+        assert_eq!(client.get_quote_commitment(), client.rpc.commitment());
+
+        let client = client.with_quote_commitment(CommitmentConfig::processed());
+        assert_eq!(client.get_quote_commitment(), CommitmentConfig::processed());
+    }
+
+    #[test]
+    fn test_program_version_override() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        assert_eq!(client.program_version, instruction::ProgramVersion::Auto);
+
+        // A configured version is used without querying the cluster
+        let client = client.with_program_version(instruction::ProgramVersion::V1);
+        assert_eq!(
+            client.get_program_version().unwrap(),
+            instruction::ProgramVersion::V1
+        );
+        let mint = Pubkey::new_unique();
+        let ix = instruction::buy(
+            &payer,
+            &mint,
+            &Pubkey::new_unique(),
+            cpi::instruction::Buy {
+                _amount: 0,
+                _max_sol_cost: 0,
+            },
+        );
+        assert_eq!(
+            client
+                .apply_program_version(ix.clone(), &mint, None)
+                .unwrap(),
+            ix
+        );
+    }
+
+    #[test]
+    fn test_creator_read_from_bonding_curve() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let curve = get_fresh_curve();
+        // Versions with creator vaults store the creator after the original fields
+        let mut data = borsh::to_vec(&curve).unwrap();
+        data.extend_from_slice(creator.as_ref());
+        let sender = AccountsSender {
+            accounts: HashMap::from([(
+                PumpFun::get_bonding_curve_pda(&mint).unwrap(),
+                (constants::accounts::PUMPFUN, data),
+            )]),
+            balance: 0,
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender)
+            .with_program_version(instruction::ProgramVersion::V2);
+        let ix = instruction::buy(
+            &payer,
+            &mint,
+            &Pubkey::new_unique(),
+            cpi::instruction::Buy {
+                _amount: 0,
+                _max_sol_cost: 0,
+            },
+        );
+
+        // One account read finds the creator, without walking the curve's history
+        assert_eq!(
+            client
+                .apply_program_version(ix.clone(), &mint, None)
+                .unwrap(),
+            instruction::with_program_version(
+                ix.clone(),
+                instruction::ProgramVersion::V2,
+                &client.seeds,
+                &creator,
+            )
+            .unwrap()
+        );
+        // Besides the node version the client asks for before its first read
+        assert_eq!(
+            client
+                .rpc_usage()
+                .operation(usage::Operation::Untagged)
+                .unwrap()
+                .requests,
+            vec![
+                (usage::RpcMethod::GetAccountInfo, 1),
+                (usage::RpcMethod::Other, 1)
+            ]
+        );
+
+        // A missing curve fails the read, and one of the original layout stores no creator
+        let other = Pubkey::new_unique();
+        assert!(matches!(
+            client.apply_program_version(ix, &other, None),
+            Err(error::ClientError::SolanaClientError(_))
+        ));
+        assert_eq!(
+            accounts::BondingCurveAccount::creator_from_data(&borsh::to_vec(&curve).unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_max_concurrent_requests() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        assert_eq!(
+            client.max_concurrent_requests,
+            DEFAULT_MAX_CONCURRENT_REQUESTS
+        );
+
+        // At least one request must be allowed in flight
+        let client = client.with_max_concurrent_requests(0);
+        assert_eq!(client.max_concurrent_requests, 1);
+
+        // An empty batch completes without touching the network
+        let results = futures::executor::block_on(client.get_bonding_curve_accounts(&[]));
+        assert!(results.is_empty());
+    }
+
+    #[test]
+    fn test_reserve_mint_rejects_in_flight_mint() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        let mint = Pubkey::new_unique();
+
+        // A mint already being created is rejected without touching the network
+        let reservation = MintReservation {
+            pending_mints: &client.pending_mints,
+            mint,
+        };
+        client.pending_mints.lock().unwrap().insert(mint);
+        assert!(matches!(
+            futures::executor::block_on(client.reserve_mint(&mint)),
+            Err(error::ClientError::MintAlreadyExists(m)) if m == mint
+        ));
+
+        // Dropping the reservation releases the mint
+        drop(reservation);
+        assert!(client.pending_mints.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lock_mint() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        let mint = Pubkey::new_unique();
+
+        let lock = client.lock_mint(&mint).unwrap();
+        assert!(matches!(
+            client.lock_mint(&mint),
+            Err(error::ClientError::MintLocked(m)) if m == mint
+        ));
+        assert!(client.lock_mint(&Pubkey::new_unique()).is_ok());
+
+        // Dropping the lock releases the mint
+        drop(lock);
+        assert!(client.lock_mint(&mint).is_ok());
+    }
+
+    #[test]
+    fn test_get_pdas() {
+        let mint = Keypair::new();
+        let global_pda = PumpFun::get_global_pda();
+        let mint_authority_pda = PumpFun::get_mint_authority_pda();
+        let bonding_curve_pda = PumpFun::get_bonding_curve_pda(&mint.pubkey());
+        let metadata_pda = PumpFun::get_metadata_pda(&mint.pubkey());
+        let owner = Pubkey::new_unique();
+        let user_ata = PumpFun::get_user_ata(&owner, &mint.pubkey());
+
+        assert!(global_pda != Pubkey::default());
+        assert!(mint_authority_pda != Pubkey::default());
+        assert!(bonding_curve_pda.is_some());
+        assert!(metadata_pda != Pubkey::default());
+        assert_eq!(
+            user_ata,
+            Pubkey::find_program_address(
+                &[
+                    owner.as_ref(),
+                    constants::accounts::TOKEN_PROGRAM.as_ref(),
+                    mint.pubkey().as_ref(),
+                ],
+                &constants::accounts::ASSOCIATED_TOKEN_PROGRAM,
+            )
+            .0
+        );
+    }
+
+    #[test]
+    fn test_get_pdas_with_custom_seeds() {
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        let creator = Keypair::new();
+        let seeds = SeedConfig {
+            global: b"fork-global",
+            mint_authority: b"fork-mint-authority",
+            bonding_curve: b"fork-bonding-curve",
+            creator_vault: b"fork-creator-vault",
+        };
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None).with_seeds(seeds);
+
+        assert_eq!(
+            PumpFun::get_global_pda_with_seeds(&client.seeds),
+            Pubkey::find_program_address(&[b"fork-global"], &cpi::ID).0
+        );
+        assert_eq!(
+            PumpFun::get_mint_authority_pda_with_seeds(&client.seeds),
+            Pubkey::find_program_address(&[b"fork-mint-authority"], &cpi::ID).0
+        );
+        assert_eq!(
+            PumpFun::get_bonding_curve_pda_with_seeds(&client.seeds, &mint.pubkey()),
+            Some(
+                Pubkey::find_program_address(
+                    &[b"fork-bonding-curve", mint.pubkey().as_ref()],
+                    &cpi::ID
+                )
+                .0
+            )
+        );
+        assert_eq!(
+            PumpFun::get_creator_vault_pda_with_seeds(&client.seeds, &creator.pubkey()),
+            Some(
+                Pubkey::find_program_address(
+                    &[b"fork-creator-vault", creator.pubkey().as_ref()],
+                    &cpi::ID
+                )
+                .0
+            )
+        );
+
+        // Default seeds are untouched
+        assert_ne!(
+            PumpFun::get_global_pda_with_seeds(&client.seeds),
+            PumpFun::get_global_pda()
+        );
+        assert_eq!(
+            PumpFun::get_global_pda_with_seeds(&SeedConfig::default()),
+            PumpFun::get_global_pda()
+        );
+    }
+
+    #[test]
+    fn test_inspect_curve_ata() {
+        let mint = Pubkey::new_unique();
+        let bonding_curve = Pubkey::new_unique();
+        let token_program = constants::accounts::TOKEN_PROGRAM;
+        let expected_ata = instruction::derive_curve_ata(&bonding_curve, &mint, &token_program);
+        let curve = accounts::BondingCurveAccount::new(
+            0,
+            1_000_000_000_000_000,
+            31_000_000_000,
+            720_000_000_000_000,
+            1_000_000_000,
+            1_000_000_000_000_000,
+            false,
+        );
+        let ata_account = |owner: Pubkey, authority: Pubkey, amount: u64| {
+            let mut data = vec![0; TokenAccount::LEN];
+            TokenAccount {
+                mint,
+                owner: authority,
+                amount,
+                state: anchor_spl::token::spl_token::state::AccountState::Initialized,
+                ..TokenAccount::default()
+            }
+            .pack_into_slice(&mut data);
+            Account {
+                lamports: 2_039_280,
+                data,
+                owner,
+                executable: false,
+                rent_epoch: 0,
+            }
+        };
+        let inspect = |account: Option<&Account>| {
+            inspect_curve_ata(
+                &mint,
+                &bonding_curve,
+                &expected_ata,
+                &token_program,
+                &curve,
+                account,
+            )
+        };
+
+        // Donations above the reserves are fine
+        let report = inspect(Some(&ata_account(
+            token_program,
+            bonding_curve,
+            720_000_000_000_001,
+        )));
+        assert!(report.is_consistent());
+        assert_eq!(report.balance, Some(720_000_000_000_001));
+
+        let report = inspect(None);
+        assert_eq!(report.issues, vec![CurveAtaIssue::Missing]);
+
+        let other = Pubkey::new_unique();
+        let report = inspect(Some(&ata_account(other, other, 1)));
+        assert_eq!(
+            report.issues,
+            vec![
+                CurveAtaIssue::WrongProgram { actual: other },
+                CurveAtaIssue::WrongAuthority { actual: other },
+                CurveAtaIssue::BalanceBelowReserves {
+                    balance: 1,
+                    reserves: 720_000_000_000_000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enrich_summary() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let curve = accounts::BondingCurveAccount::new(
+            0,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        );
+
+        // Nothing attached or cached: no creation data
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        let mut curve_summary = summary::CurveSummary::new(mint, &curve, 793_100_000_000_000);
+        client.enrich_summary(&mut curve_summary);
+        assert_eq!(curve_summary.creator, None);
+        assert_eq!(curve_summary.created_at, None);
+
+        // The enricher supplies the creator, the cached age the rest
+        let index: HashMap<Pubkey, summary::CreationInfo> = HashMap::from([(
+            mint,
+            summary::CreationInfo {
+                creator: Some(creator),
+                ..Default::default()
+            },
+        )]);
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_summary_enricher(std::sync::Arc::new(index));
+        let age = TokenAge {
+            creation_slot: 300_000_000,
+            creation_block_time: Some(1_700_000_000),
+            creation_signature: Signature::new_unique(),
+        };
+        client.token_ages.lock().unwrap().insert(mint, age);
+        client.enrich_summary(&mut curve_summary);
+        assert_eq!(curve_summary.creator, Some(creator));
+        assert_eq!(curve_summary.created_at, Some(1_700_000_000));
+        assert_eq!(
+            curve_summary.creation_signature,
+            Some(age.creation_signature)
+        );
+    }
+
+    #[test]
+    fn test_summary_graduation_estimate() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let global = get_global();
+        let curve = get_fresh_curve();
+        let clock = std::sync::Arc::new(utils::clock::MockClock::at_unix_timestamp(1_000));
+        let mut tracker = flow::NetFlowTracker::new(Duration::from_secs(300))
+            .with_mint(mint)
+            .with_clock(clock);
+        tracker.record(&events::TradeEvent {
+            mint,
+            sol_amount: 1_000_000_000,
+            token_amount: 1,
+            is_buy: true,
+            user: Pubkey::new_unique(),
+            timestamp: 1_000,
+            virtual_sol_reserves: 0,
+            virtual_token_reserves: 0,
+            real_sol_reserves: 0,
+            real_token_reserves: 0,
+        });
+        let tracker = std::sync::Arc::new(Mutex::new(tracker));
+
+        // Without a tracker for the mint, summaries carry no estimate
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        assert_eq!(client.estimate_graduation(&mint, &curve, &global), None);
+        let client = client.with_volume_tracker(tracker.clone());
+        assert_eq!(
+            client.estimate_graduation(&Pubkey::new_unique(), &curve, &global),
+            None
+        );
+
+        let estimate = client.estimate_graduation(&mint, &curve, &global).unwrap();
+        assert_eq!(estimate.sol_remaining, curve.sol_to_graduate());
+        assert_eq!(
+            Some(estimate),
+            analytics::graduation_eta(&curve, &global, &tracker.lock().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_trade_outcome_transaction_base64() {
+        use base64::Engine;
+
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[ComputeBudgetInstruction::set_compute_unit_limit(200_000)],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let mut outcome = TradeOutcome {
+            trade_id: trade_id::TradeIdGenerator::with_session(1).next_id(),
+            signature: transaction.signatures[0],
+            trade_event: None,
+            timings: timings::StageTimer::start().timings(),
+            transaction: None,
+            submit_path: submit::SubmitPath::default(),
+            memo: None,
+        };
+        assert_eq!(outcome.transaction_base64(), None);
+
+        outcome.transaction = Some(transaction.clone());
+        let encoded = outcome.transaction_base64().unwrap();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .unwrap();
+        let decoded: Transaction = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(decoded, transaction);
+    }
+
+    #[cfg(feature = "immutable-cache")]
+    #[test]
+    fn test_immutable_cache_survives_restart() {
+        let path =
+            std::env::temp_dir().join(format!("pumpfun-client-{}.tsv", Pubkey::new_unique()));
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let age = TokenAge {
+            creation_slot: 300_000_000,
+            creation_block_time: Some(1_700_000_000),
+            creation_signature: Signature::new_unique(),
+        };
+
+        // Nothing listens on this port, so any lookup that reaches RPC fails
+        let offline = Cluster::Custom(
+            "http://127.0.0.1:1".to_string(),
+            "ws://127.0.0.1:1".to_string(),
+        );
+
+        {
+            let cache = std::sync::Arc::new(cache::ImmutableCache::open(&path).unwrap());
+            let client = PumpFun::new(offline.clone(), &payer, None, None)
+                .with_immutable_cache(cache.clone());
+            client.token_ages.lock().unwrap().insert(mint, age);
+            client.store_lookup(CACHE_TOKEN_AGE, &mint, &age);
+            client.store_lookup(CACHE_CREATOR, &mint, &creator);
+            client
+                .cache_token_program(&mint, &constants::accounts::TOKEN_2022_PROGRAM)
+                .unwrap();
+        }
+
+        let cache = std::sync::Arc::new(cache::ImmutableCache::open(&path).unwrap());
+        let client = PumpFun::new(offline, &payer, None, None).with_immutable_cache(cache);
+        assert_eq!(client.get_token_age(&mint).unwrap(), age);
+        assert_eq!(client.get_creator(&mint).unwrap(), creator);
+        assert_eq!(
+            client.get_token_program(&mint).unwrap(),
+            constants::accounts::TOKEN_2022_PROGRAM
+        );
+        assert!(client.get_creator(&Pubkey::new_unique()).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_upload_metadata_deduplicates() {
+        let path = std::env::temp_dir().join(format!("pumpfun-image-{}.png", Pubkey::new_unique()));
+        std::fs::write(&path, b"fake image data").unwrap();
+        let metadata = utils::CreateTokenMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: "Test Description".to_string(),
+            file: path.to_str().unwrap().to_string(),
+            twitter: None,
+            telegram: None,
+            website: None,
+        };
+        let uploads = std::cell::Cell::new(0);
+        let upload = |metadata: utils::CreateTokenMetadata, _image: Vec<u8>| {
+            uploads.set(uploads.get() + 1);
+            let response = utils::TokenMetadataResponse {
+                metadata: utils::TokenMetadata {
+                    name: metadata.name,
+                    symbol: metadata.symbol,
+                    description: metadata.description,
+                    image: "https://ipfs.io/ipfs/image".to_string(),
+                    show_name: true,
+                    created_on: "https://pump.fun".to_string(),
+                    twitter: metadata.twitter,
+                    telegram: metadata.telegram,
+                    website: metadata.website,
+                },
+                metadata_uri: format!("https://ipfs.io/ipfs/{}", uploads.get()),
+            };
+            async move { Ok::<_, Box<dyn std::error::Error>>(response) }
+        };
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+
+        let first =
+            futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload))
+                .unwrap();
+        assert!(!first.from_cache);
+        assert_eq!(first.response.metadata_uri, "https://ipfs.io/ipfs/1");
+
+        // A retry reuses the pinned URI without uploading again
+        let retry =
+            futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload))
+                .unwrap();
+        assert!(retry.from_cache);
+        assert_eq!(retry.response, first.response);
+        assert_eq!(retry.content_hash, first.content_hash);
+        assert_eq!(uploads.get(), 1);
+
+        // Different content is uploaded
+        let renamed = utils::CreateTokenMetadata {
+            name: "Other Token".to_string(),
+            ..metadata.clone()
+        };
+        let other =
+            futures::executor::block_on(client.upload_metadata_with(renamed, upload)).unwrap();
+        assert!(!other.from_cache);
+        assert_eq!(other.response.metadata_uri, "https://ipfs.io/ipfs/2");
+
+        // A failed upload is not remembered
+        let failing = |_: utils::CreateTokenMetadata, _: Vec<u8>| async {
+            Err::<utils::TokenMetadataResponse, Box<dyn std::error::Error>>("upload failed".into())
+        };
+        let described = utils::CreateTokenMetadata {
+            description: "Other Description".to_string(),
+            ..metadata.clone()
+        };
+        assert!(matches!(
+            futures::executor::block_on(client.upload_metadata_with(described.clone(), failing)),
+            Err(error::ClientError::UploadMetadataError(_))
+        ));
+        let described =
+            futures::executor::block_on(client.upload_metadata_with(described, upload)).unwrap();
+        assert!(!described.from_cache);
+        assert_eq!(uploads.get(), 3);
+
+        // Uploads survive a restart through the immutable cache
+        #[cfg(feature = "immutable-cache")]
+        {
+            let cache_path =
+                std::env::temp_dir().join(format!("pumpfun-uploads-{}.tsv", Pubkey::new_unique()));
+            let cache = std::sync::Arc::new(cache::ImmutableCache::open(&cache_path).unwrap());
+            let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+                .with_immutable_cache(cache.clone());
+            futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload))
+                .unwrap();
+
+            let cache = std::sync::Arc::new(cache::ImmutableCache::open(&cache_path).unwrap());
+            let client =
+                PumpFun::new(Cluster::Devnet, &payer, None, None).with_immutable_cache(cache);
+            let restarted =
+                futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload))
+                    .unwrap();
+            assert!(restarted.from_cache);
+            assert_eq!(restarted.response.metadata_uri, "https://ipfs.io/ipfs/4");
+            assert_eq!(uploads.get(), 4);
+            std::fs::remove_file(&cache_path).unwrap();
+        }
+
+        // The image cannot be read once removed
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            futures::executor::block_on(client.upload_metadata(metadata)),
+            Err(error::ClientError::UploadMetadataError(_))
+        ));
+    }
+
+    #[test]
+    fn test_upload_metadata_image_policy() {
+        let image = include_bytes!("utils/fixtures/noise.png");
+        let path = std::env::temp_dir().join(format!("pumpfun-image-{}.png", Pubkey::new_unique()));
+        std::fs::write(&path, image).unwrap();
+        let metadata = utils::CreateTokenMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: "Test Description".to_string(),
+            file: path.to_str().unwrap().to_string(),
+            twitter: None,
+            telegram: None,
+            website: None,
+        };
+        let uploaded = std::cell::RefCell::new(Vec::new());
+        let upload = |metadata: utils::CreateTokenMetadata, image: Vec<u8>| {
+            uploaded.borrow_mut().push(image);
+            let response = utils::TokenMetadataResponse {
+                metadata: utils::TokenMetadata {
+                    name: metadata.name,
+                    symbol: metadata.symbol,
+                    description: metadata.description,
+                    image: "https://ipfs.io/ipfs/image".to_string(),
+                    show_name: true,
+                    created_on: "https://pump.fun".to_string(),
+                    twitter: None,
+                    telegram: None,
+                    website: None,
+                },
+                metadata_uri: "https://ipfs.io/ipfs/metadata".to_string(),
+            };
+            async move { Ok::<_, Box<dyn std::error::Error>>(response) }
+        };
+        let payer = Keypair::new();
+
+        // Rejected before the upload
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_image_policy(utils::ImagePolicy::new(24_000, 512));
+        let result =
+            futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload));
+        assert!(matches!(
+            result,
+            Err(error::ClientError::ImageTooLarge { size, max: 24_000 }) if size == image.len()
+        ));
+        assert!(uploaded.borrow().is_empty());
+
+        // Images meeting the policy are uploaded as they are
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_image_policy(utils::ImagePolicy::new(image.len(), 512));
+        futures::executor::block_on(client.upload_metadata_with(metadata.clone(), upload)).unwrap();
+        assert_eq!(uploaded.borrow().as_slice(), [image.to_vec()]);
+
+        // Resized images are uploaded in place of the file
+        #[cfg(feature = "image")]
+        {
+            let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+                .with_image_policy(utils::ImagePolicy::new(24_000, 512).with_resize(true));
+            futures::executor::block_on(client.upload_metadata_with(metadata, upload)).unwrap();
+            assert!(uploaded.borrow()[1].len() <= 24_000);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_create_and_buy_instructions() {
+        use anchor_client::anchor_lang::AnchorDeserialize;
+
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_program_version(instruction::ProgramVersion::V1);
+        let global = get_global();
+        let create = || cpi::instruction::Create {
+            _name: "Test Token".to_string(),
+            _symbol: "TEST".to_string(),
+            _uri: "https://ipfs.io/ipfs/metadata".to_string(),
+        };
+        let extra = instruction::memo("launch");
+        let fee = PriorityFee {
+            limit: Some(200_000),
+            price: Some(1_000),
+        };
+
+        let instructions = client
+            .create_and_buy_instructions(
+                &mint,
+                &global,
+                create(),
+                1_000_000_000,
+                Some(100),
+                Some(fee),
+                vec![extra.clone()],
+            )
+            .unwrap();
+        let programs: Vec<Pubkey> = instructions.iter().map(|ix| ix.program_id).collect();
+        assert_eq!(
+            programs,
+            [
+                solana_sdk::compute_budget::id(),
+                solana_sdk::compute_budget::id(),
+                constants::accounts::MEMO_PROGRAM,
+                constants::accounts::PUMPFUN,
+                constants::accounts::ASSOCIATED_TOKEN_PROGRAM,
+                constants::accounts::PUMPFUN,
+            ]
+        );
+        assert_eq!(
+            instructions[0],
+            ComputeBudgetInstruction::set_compute_unit_limit(200_000)
+        );
+        assert_eq!(
+            instructions[1],
+            ComputeBudgetInstruction::set_compute_unit_price(1_000)
+        );
+        assert_eq!(instructions[2], extra);
+        assert_eq!(
+            instructions[3],
+            instruction::create_with_token_program(
+                &client.seeds,
+                &constants::accounts::TOKEN_PROGRAM,
+                &payer,
+                &mint,
+                create(),
+            )
+        );
+
+        // The buy is quoted from a fresh curve with the initial reserves
+        let buy = cpi::instruction::Buy::try_from_slice(&instructions[5].data[8..]).unwrap();
+        let fresh = accounts::BondingCurveAccount::new(
+            1,
+            global.initial_virtual_token_reserves,
+            global.initial_virtual_sol_reserves,
+            global.initial_real_token_reserves,
+            0,
+            global.token_total_supply,
+            false,
+        );
+        assert_eq!(buy._amount, fresh.get_buy_price(1_000_000_000).unwrap());
+        assert_eq!(buy._amount, 34_612_903_225_806);
+        assert_eq!(buy._max_sol_cost, 1_010_000_000);
+
+        // Without a priority fee or extra instructions the create comes first
+        let instructions = client
+            .create_and_buy_instructions(
+                &mint,
+                &global,
+                create(),
+                1_000_000_000,
+                None,
+                None,
+                vec![],
+            )
+            .unwrap();
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0].program_id, constants::accounts::PUMPFUN);
+        let buy = cpi::instruction::Buy::try_from_slice(&instructions[2].data[8..]).unwrap();
+        assert_eq!(buy._max_sol_cost, 1_050_000_000);
+    }
+
+    #[test]
+    fn test_initial_buy_matches_fresh_curve() {
+        let global = get_global();
+        let fresh = accounts::BondingCurveAccount::new(
+            1,
+            global.initial_virtual_token_reserves,
+            global.initial_virtual_sol_reserves,
+            global.initial_real_token_reserves,
+            0,
+            global.token_total_supply,
+            false,
+        );
+        for amount_sol in [
+            0,
+            1,
+            1_000_000_000,
+            10_000_000_000,
+            85_000_000_000,
+            u64::MAX,
+        ] {
+            assert_eq!(
+                global.get_initial_buy_price(amount_sol),
+                fresh.get_buy_price(amount_sol).unwrap(),
+                "{}",
+                amount_sol
+            );
+        }
+        // Buying past graduation is capped at the tokens for sale
+        assert_eq!(
+            global.get_initial_buy_price(u64::MAX),
+            global.initial_real_token_reserves
+        );
+    }
+
+    #[test]
+    fn test_create_and_buy_fails_before_sending_on_upload_error() {
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, MissingAccountsSender);
+        let metadata = utils::CreateTokenMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: "Test Description".to_string(),
+            file: std::env::temp_dir()
+                .join(format!("pumpfun-missing-{}.png", Pubkey::new_unique()))
+                .to_str()
+                .unwrap()
+                .to_string(),
+            twitter: None,
+            telegram: None,
+            website: None,
+        };
+
+        for _ in 0..2 {
+            assert!(matches!(
+                futures::executor::block_on(client.create_and_buy(
+                    &mint,
+                    metadata.clone(),
+                    1_000_000_000,
+                    None,
+                    None
+                )),
+                Err(error::ClientError::UploadMetadataError(_))
+            ));
+        }
+        // Only the mint was looked up, after the node version on the first attempt, nothing
+        // was sent, and the reservation of the mint was released
+        let usage = client.rpc_usage();
+        assert_eq!(
+            usage.operation(usage::Operation::Buy).unwrap().requests,
+            vec![
+                (usage::RpcMethod::GetAccountInfo, 2),
+                (usage::RpcMethod::Other, 1)
+            ]
+        );
+        assert_eq!(usage.total_requests, 3);
+        assert!(client.pending_mints.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_create_ix_uses_prepared_uri() {
+        let payer = Keypair::new();
+        let mint = Keypair::new();
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, MissingAccountsSender)
+            .with_program_version(instruction::ProgramVersion::V1);
+        let create = || cpi::instruction::Create {
+            _name: "Test Token".to_string(),
+            _symbol: "TEST".to_string(),
+            _uri: "https://ipfs.io/ipfs/metadata".to_string(),
+        };
+        let fee = PriorityFee {
+            limit: Some(200_000),
+            price: Some(1_000),
+        };
+
+        // Nothing is built while the token metadata program is missing
+        assert!(matches!(
+            client.create_ix(&mint, create(), Some(fee)),
+            Err(error::ClientError::MissingDependencyProgram { .. })
+        ));
+
+        client
+            .deployed_programs
+            .lock()
+            .unwrap()
+            .insert(constants::accounts::MPL_TOKEN_METADATA);
+        let instructions = client.create_ix(&mint, create(), Some(fee)).unwrap();
+        assert_eq!(
+            instructions,
+            [
+                ComputeBudgetInstruction::set_compute_unit_limit(200_000),
+                ComputeBudgetInstruction::set_compute_unit_price(1_000),
+                client.create_instruction(&mint, create()),
+            ]
+        );
+
+        // Only the node version and the metadata program were looked up, the URI is used as it is
+        assert_eq!(
+            client
+                .rpc_usage()
+                .operation(usage::Operation::Untagged)
+                .unwrap()
+                .requests,
+            vec![
+                (usage::RpcMethod::GetAccountInfo, 1),
+                (usage::RpcMethod::Other, 1)
+            ]
+        );
+        assert_eq!(
+            client.create_ix(&mint, create(), None).unwrap(),
+            [client.create_instruction(&mint, create())]
+        );
+    }
+
+    /// Transport answering every account lookup with missing accounts
+    struct MissingAccountsSender;
+
+    #[async_trait::async_trait]
+    impl RpcSender for MissingAccountsSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+
+            // The client asks for the node version before mapping commitments
+            if request == RpcRequest::GetVersion {
+                return Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                }));
+            }
+
+            // A list of addresses for getMultipleAccounts, a single one otherwise
+            let value = match params[0].as_array() {
+                Some(addresses) => {
+                    serde_json::json!(vec![serde_json::Value::Null; addresses.len()])
+                }
+                None => serde_json::Value::Null,
+            };
+            Ok(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": value,
+            }))
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "missing-accounts".to_string()
+        }
+    }
+
+    #[test]
+    fn test_rpc_usage_attribution() {
+        let payer = Keypair::new();
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, MissingAccountsSender)
+            .with_rpc_cost_table(
+                usage::CostTable::default().with_cost(usage::RpcMethod::GetMultipleAccounts, 10),
+            );
+        let mints: Vec<Pubkey> = (0..150).map(|_| Pubkey::new_unique()).collect();
+
+        // Both chunks of the lookup, and the node version asked for once before the first of
+        // them, are attributed to the query
+        assert!(client.filter_pumpfun_mints(&mints).unwrap().is_empty());
+        let report = client.rpc_usage();
+        let query = report.operation(usage::Operation::Query).unwrap();
+        assert_eq!(
+            query.requests,
+            vec![
+                (usage::RpcMethod::GetMultipleAccounts, 2),
+                (usage::RpcMethod::Other, 1)
+            ]
+        );
+        assert_eq!(report.total_requests, 3);
+        assert_eq!(report.total_credits, 21);
+
+        // A workflow scoped by the caller takes the requests of the methods it calls
+        {
+            let _workflow = usage::OperationScope::enter(usage::Operation::Backfill);
+            client.filter_pumpfun_mints(&mints).unwrap();
+        }
+        client.filter_pumpfun_mints(&mints[..1]).unwrap();
+        let report = client.rpc_usage();
+        assert_eq!(
+            report
+                .operation(usage::Operation::Backfill)
+                .unwrap()
+                .total_requests,
+            2
+        );
+        assert_eq!(
+            report
+                .operation(usage::Operation::Query)
+                .unwrap()
+                .total_requests,
+            4
+        );
+
+        client.reset_rpc_usage();
+        assert_eq!(client.rpc_usage().total_requests, 0);
+    }
+
+    /// Transport serving the program data account with a deployment slot the test can change
+    struct ProgramDataSender(std::sync::Arc<std::sync::atomic::AtomicU64>);
+
+    #[async_trait::async_trait]
+    impl RpcSender for ProgramDataSender {
+        async fn send(
+            &self,
+            _request: anchor_client::solana_client::rpc_request::RpcRequest,
+            _params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use base64::Engine;
+
+            let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+                slot: self.0.load(std::sync::atomic::Ordering::SeqCst),
+                upgrade_authority_address: None,
+            })
+            .unwrap();
+            Ok(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                    "executable": false,
+                    "lamports": 1,
+                    "owner": bpf_loader_upgradeable::id().to_string(),
+                    "rentEpoch": 0,
+                },
+            }))
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "program-data".to_string()
+        }
+    }
+
+    #[test]
+    fn test_program_upgrade_detection() {
+        let payer = Keypair::new();
+        let deployed_slot = std::sync::Arc::new(std::sync::atomic::AtomicU64::new(100));
+        let client = PumpFun::new_with_sender(
+            Cluster::Devnet,
+            &payer,
+            None,
+            ProgramDataSender(deployed_slot.clone()),
+        )
+        .with_pause_on_upgrade(true);
+        let mut events = client.subscribe_client_events();
+
+        // The first check records the baseline without reporting an upgrade
+        assert_eq!(client.check_program_upgrade().unwrap(), None);
+        assert_eq!(client.last_deployed_slot(), Some(100));
+        assert_eq!(client.check_program_upgrade().unwrap(), None);
+        assert!(events.try_next().is_err());
+
+        *client.detected_version.lock().unwrap() = Some(instruction::ProgramVersion::V2);
+        client
+            .deployed_programs
+            .lock()
+            .unwrap()
+            .insert(constants::accounts::MPL_TOKEN_METADATA);
+
+        // A redeploy flushes the caches, notifies subscribers and pauses trading
+        deployed_slot.store(200, std::sync::atomic::Ordering::SeqCst);
+        assert_eq!(client.check_program_upgrade().unwrap(), Some(200));
+        assert_eq!(client.last_deployed_slot(), Some(200));
+        assert_eq!(
+            events.try_next().unwrap(),
+            Some(upgrade::ClientEvent::ProgramUpgraded { slot: 200 })
+        );
+        assert_eq!(*client.detected_version.lock().unwrap(), None);
+        assert!(client.deployed_programs.lock().unwrap().is_empty());
+
+        assert!(client.is_trading_paused());
+        let transaction = Transaction::new_with_payer(&[], Some(&payer.pubkey()));
+        assert!(matches!(
+            futures::executor::block_on(client.send_transaction(&transaction)),
+            Err(error::ClientError::TradingPaused)
+        ));
+
+        client.acknowledge_program_upgrade();
+        assert!(!client.is_trading_paused());
+        assert_eq!(client.check_program_upgrade().unwrap(), None);
+    }
+
+    #[test]
+    fn test_fee_spend_budget() {
+        let payer = Keypair::new();
+        let clock = std::sync::Arc::new(utils::clock::MockClock::new());
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_clock(clock.clone())
+            .with_fee_spend_tracking(Some(spend::FeeBudget::new(
+                50_000,
+                spend::BudgetAction::Block,
+            )));
+        let mint = Pubkey::new_unique();
+        let fee = Some(PriorityFee {
+            limit: Some(200_000),
+            price: Some(1_000_000),
+        });
+        let meta: UiTransactionStatusMeta = serde_json::from_value(serde_json::json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5_000,
+            "preBalances": [],
+            "postBalances": [],
+            "computeUnitsConsumed": 50_000,
+        }))
+        .unwrap();
+
+        // The fee is charged on the units consumed, not the requested limit
+        client.record_fee_spend(Some(&mint), fee, Some(&meta));
+        let report = client.fee_spend_report();
+        assert_eq!(report.session_lamports, 50_000);
+        assert_eq!(report.per_mint[&mint], 50_000);
+
+        // Trades are refused before anything is sent once the budget is spent
+        assert!(matches!(
+            futures::executor::block_on(client.buy(&mint, 1_000, None, fee)),
+            Err(error::ClientError::PriorityFeeBudgetExceeded {
+                spent: 50_000,
+                budget: 50_000
+            })
+        ));
+        assert_eq!(client.fee_spend_report().blocked, 1);
+
+        clock.advance(spend::SPEND_WINDOW);
+        assert_eq!(client.fee_spend_report().last_hour_lamports, 0);
+        assert_eq!(client.apply_fee_budget(fee).unwrap(), fee);
+
+        // Without tracking nothing is recorded
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        client.record_fee_spend(Some(&mint), fee, Some(&meta));
+        assert_eq!(client.fee_spend_report(), spend::FeeSpendReport::default());
+    }
+
+    #[test]
+    fn test_default_priority_fee() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let default = PriorityFee {
+            limit: Some(200_000),
+            price: Some(1_000_000),
+        };
+        let explicit = Some(PriorityFee {
+            limit: None,
+            price: Some(5),
+        });
+
+        // No fee unless one is set
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None);
+        assert_eq!(client.apply_fee_budget(None).unwrap(), None);
+
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_priority_fee(1_000_000, Some(200_000))
+            .with_fee_spend_tracking(Some(spend::FeeBudget::new(
+                50_000,
+                spend::BudgetAction::Block,
+            )));
+        assert_eq!(client.apply_fee_budget(None).unwrap(), Some(default));
+        assert_eq!(client.apply_fee_budget(explicit).unwrap(), explicit);
+
+        // Trades sent without a fee carry the default, which the budget then applies to
+        let meta: UiTransactionStatusMeta = serde_json::from_value(serde_json::json!({
+            "err": null,
+            "status": { "Ok": null },
+            "fee": 5_000,
+            "preBalances": [],
+            "postBalances": [],
+            "computeUnitsConsumed": 50_000,
+        }))
+        .unwrap();
+        client.record_fee_spend(Some(&mint), Some(default), Some(&meta));
+        assert!(matches!(
+            futures::executor::block_on(client.buy(&mint, 1_000, None, None)),
+            Err(error::ClientError::PriorityFeeBudgetExceeded { .. })
+        ));
+    }
+
+    /// Transport rejecting every transaction for an unknown blockhash, and reporting the status
+    /// of any signature as the given one
+    struct LandedSender {
+        /// Status of every signature, None if not found
+        status: Option<serde_json::Value>,
+        /// Number of transactions sent
+        sends: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for LandedSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            _params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+
+            match request {
+                RpcRequest::SendTransaction => {
+                    self.sends.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    Err(solana_sdk::transaction::TransactionError::BlockhashNotFound.into())
+                }
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetSignatureStatuses => Ok(serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": [self.status.clone().map(|err| serde_json::json!({
+                        "slot": 1,
+                        "confirmations": null,
+                        "err": err,
+                        "status": if err.is_null() {
+                            serde_json::json!({ "Ok": null })
+                        } else {
+                            serde_json::json!({ "Err": err })
+                        },
+                        "confirmationStatus": "processed",
+                    }))],
+                })),
+                _ => unreachable!("unexpected request {}", request),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "landed".to_string()
+        }
+    }
+
+    #[test]
+    fn test_submit_does_not_rebuild_landed_transaction() {
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::memo("landed")],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let submit = |status: Option<serde_json::Value>| {
+            let sends = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+            let sender = LandedSender {
+                status,
+                sends: sends.clone(),
+            };
+            let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+            let request = client.program.request();
+            let result =
+                futures::executor::block_on(client.submit_request(&request, transaction.clone()))
+                    .map(|(signature, _, path)| (signature, path));
+            (result, sends.load(std::sync::atomic::Ordering::SeqCst))
+        };
+
+        // A transaction an earlier attempt landed is reported as sent, not signed again
+        let (result, sends) = submit(Some(serde_json::Value::Null));
+        let (signature, path) = result.unwrap();
+        assert_eq!(signature, transaction.signatures[0]);
+        assert!(path.already_processed);
+        assert_eq!((path.attempts, path.rebuilds), (1, 0));
+        assert_eq!(sends, 1);
+
+        // One that landed and failed is reported as failed
+        let (result, _) = submit(Some(serde_json::json!("AccountInUse")));
+        assert!(matches!(
+            result,
+            Err(error::ClientError::SolanaClientError(_))
+        ));
+    }
+
+    /// Transport accepting every transaction and reporting it confirmed
+    struct AcceptingSender {
+        /// Signature returned for every transaction sent
+        signature: Signature,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for AcceptingSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            _params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+
+            match request {
+                RpcRequest::SendTransaction => Ok(serde_json::json!(self.signature.to_string())),
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetSignatureStatuses => Ok(serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": [{
+                        "slot": 1,
+                        "confirmations": null,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": "confirmed",
+                    }],
+                })),
+                request => panic!("unexpected request {request}"),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "accepting".to_string()
+        }
+    }
+
+    #[test]
+    fn test_send_transaction_on_current_thread_runtime() {
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::memo("sent")],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let signature = transaction.signatures[0];
+        let client =
+            PumpFun::new_with_sender(Cluster::Devnet, &payer, None, AcceptingSender { signature });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // Blocking on the only thread of the runtime would panic, so both sends must be awaited
+        assert_eq!(
+            runtime
+                .block_on(client.send_transaction(&transaction))
+                .unwrap(),
+            signature
+        );
+        assert_eq!(
+            runtime
+                .block_on(
+                    client.send_versioned_transaction(&VersionedTransaction::from(
+                        transaction.clone()
+                    ))
+                )
+                .unwrap(),
+            signature
+        );
+    }
+
+    #[test]
+    fn test_submit_on_current_thread_runtime() {
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::memo("submitted")],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let signature = transaction.signatures[0];
+        let client =
+            PumpFun::new_with_sender(Cluster::Devnet, &payer, None, AcceptingSender { signature });
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        // The trade flows submit and read statuses without blocking the only thread
+        let request = client.program.request();
+        let (sent, _, path) = runtime
+            .block_on(client.submit_request(&request, transaction))
+            .unwrap();
+        assert_eq!(sent, signature);
+        assert_eq!(path.attempts, 1);
+        assert_eq!(
+            runtime.block_on(client.processed_status(&signature)),
+            Some(Ok(()))
+        );
+    }
+
+    /// Transport serving a faucet cluster with the given genesis hash and a deployed program set
+    struct FaucetSender {
+        /// Genesis hash reported by the node
+        genesis_hash: &'static str,
+        /// Payer funded by the airdrop
+        payer: Pubkey,
+        /// Whether an airdrop has been requested
+        funded: std::sync::atomic::AtomicBool,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for FaucetSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use std::sync::atomic::Ordering;
+
+            let account = |lamports: u64, owner: Pubkey, executable: bool| {
+                serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": {
+                        "data": ["", "base64"],
+                        "executable": executable,
+                        "lamports": lamports,
+                        "owner": owner.to_string(),
+                        "rentEpoch": 0,
+                    },
+                })
+            };
+            Ok(match request {
+                RpcRequest::GetGenesisHash => serde_json::json!(self.genesis_hash),
+                // The client asks for the node version before mapping commitments
+                RpcRequest::GetVersion => serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                }),
+                RpcRequest::RequestAirdrop => {
+                    self.funded.store(true, Ordering::SeqCst);
+                    serde_json::json!(Signature::default().to_string())
+                }
+                RpcRequest::GetSignatureStatuses => serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": [{
+                        "slot": 1,
+                        "confirmations": null,
+                        "err": null,
+                        "status": { "Ok": null },
+                        "confirmationStatus": "confirmed",
+                    }],
+                }),
+                RpcRequest::GetAccountInfo if params[0] == self.payer.to_string() => {
+                    if self.funded.load(Ordering::SeqCst) {
+                        account(2_000_000_000, solana_sdk::system_program::id(), false)
+                    } else {
+                        serde_json::json!({ "context": { "slot": 1 }, "value": null })
+                    }
+                }
+                RpcRequest::GetAccountInfo => account(1, bpf_loader_upgradeable::id(), true),
+                // Anything else fails the setup as a node without the method would
+                request => {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::Unsupported,
+                        format!("unexpected request {request}"),
+                    )
+                    .into())
+                }
+            })
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "faucet".to_string()
+        }
+    }
+
+    #[test]
+    fn test_devnet_setup() {
+        let payer = Keypair::new();
+        let sender = |genesis_hash| FaucetSender {
+            genesis_hash,
+            payer: payer.pubkey(),
+            funded: std::sync::atomic::AtomicBool::new(false),
+        };
+        let devnet_hash = "EtWTRABZaYq6iMfeYKouRu166VU2xqa1wcaWoxPkrZBG";
+
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender(devnet_hash));
+        let report = futures::executor::block_on(client.devnet_setup(2.0)).unwrap();
+        assert_eq!(report.payer, payer.pubkey());
+        assert_eq!(report.airdrop_lamports, 2_000_000_000);
+        assert_eq!(report.airdrop_signature, Some(Signature::default()));
+        assert_eq!(report.airdrop_attempts, 1);
+        assert!(report.account_created);
+        assert_eq!(report.balance_before, 0);
+        assert_eq!(report.balance_after, 2_000_000_000);
+
+        // Without an airdrop, an unfunded payer cannot pay for transactions
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender(devnet_hash));
+        assert!(matches!(
+            futures::executor::block_on(client.devnet_setup(0.0)),
+            Err(error::ClientError::InvalidInput(_))
+        ));
+        assert!(futures::executor::block_on(client.devnet_setup(-1.0)).is_err());
+
+        // Mainnet is refused by cluster, and by genesis hash behind a custom URL
+        let mainnet = devnet::MAINNET_GENESIS_HASH;
+        let client = PumpFun::new_with_sender(Cluster::Mainnet, &payer, None, sender(devnet_hash));
+        assert!(futures::executor::block_on(client.devnet_setup(1.0)).is_err());
+        assert_eq!(client.rpc_usage().total_requests, 0);
+
+        let custom = Cluster::Custom("http://rpc".to_string(), "ws://rpc".to_string());
+        let client = PumpFun::new_with_sender(custom, &payer, None, sender(mainnet));
+        assert!(futures::executor::block_on(client.devnet_setup(1.0)).is_err());
+        assert_eq!(client.rpc_usage().total_requests, 1);
+    }
+
+    /// Transport serving the global account and a bonding curve, or failing every request with
+    /// a connection error while the node is down
+    struct FlakySender {
+        /// Whether requests fail
+        failing: std::sync::Arc<std::sync::atomic::AtomicBool>,
+        /// Address of the global account
+        global: Pubkey,
+        /// Borsh data of the global and bonding curve accounts
+        data: [Vec<u8>; 2],
+        /// Methods of the requests received, in order
+        requests: std::sync::Arc<Mutex<Vec<anchor_client::solana_client::rpc_request::RpcRequest>>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for FlakySender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use base64::Engine;
+
+            self.requests.lock().unwrap().push(request);
+            if self.failing.load(std::sync::atomic::Ordering::SeqCst) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionReset,
+                    "connection reset",
+                )
+                .into());
+            }
+            // The client asks for the node version before mapping commitments
+            if request == RpcRequest::GetVersion {
+                return Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                }));
+            }
+            assert_eq!(request, RpcRequest::GetMultipleAccounts);
+            let accounts: Vec<serde_json::Value> = params[0]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|address| {
+                    let data = if *address == self.global.to_string() {
+                        &self.data[0]
+                    } else {
+                        &self.data[1]
+                    };
+                    serde_json::json!({
+                        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                        "executable": false,
+                        "lamports": 1,
+                        "owner": constants::accounts::PUMPFUN.to_string(),
+                        "rentEpoch": 0,
+                    })
+                })
+                .collect();
+            Ok(serde_json::json!({ "context": { "slot": 1 }, "value": accounts }))
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "flaky".to_string()
+        }
+    }
+
+    #[test]
+    fn test_load_shedding_under_rpc_pressure() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let global = get_global();
+        let curve = get_fresh_curve();
+        let failing = std::sync::Arc::new(AtomicBool::new(false));
+        let sender = FlakySender {
+            failing: failing.clone(),
+            global: PumpFun::get_global_pda(),
+            data: [
+                borsh::to_vec(&global).unwrap(),
+                borsh::to_vec(&curve).unwrap(),
+            ],
+            requests: Default::default(),
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender)
+            .with_load_shedder(shed::LoadShedder::new());
+        let quote = client.quote_buy(&mint, 1_000_000_000).unwrap();
+        let rate = || {
+            client
+                .rpc_pressure
+                .failure_rate(client.clock().now())
+                .unwrap()
+        };
+
+        // The node goes down for a while
+        failing.store(true, Ordering::SeqCst);
+        for _ in 0..10 {
+            assert!(matches!(
+                client.quote_buy(&mint, 1_000_000_000),
+                Err(error::ClientError::SolanaClientError(_))
+            ));
+        }
+        failing.store(false, Ordering::SeqCst);
+        assert_eq!(client.load_pressure(), shed::Pressure::Severe);
+
+        // Trade path reads still reach the node, optional features fail without a request
+        assert_eq!(client.quote_buy(&mint, 1_000_000_000).unwrap(), quote);
+        let requests = client.rpc_usage().total_requests;
+        assert!(matches!(
+            client.get_token_snapshot(&mint),
+            Err(error::ClientError::Shedded {
+                feature: shed::Feature::MetadataEnrichment,
+                pressure: shed::Pressure::Severe,
+            })
+        ));
+        assert!(matches!(
+            client.get_top_holders_pct(&mint),
+            Err(error::ClientError::Shedded {
+                feature: shed::Feature::HolderCounts,
+                ..
+            })
+        ));
+        let scheduler = refresh::RefreshScheduler::new(
+            std::sync::Arc::new(refresh::CurveCache::new()),
+            Duration::from_secs(1),
+        );
+        scheduler.register(mint, Duration::ZERO);
+        assert!(matches!(
+            scheduler.tick(&client),
+            Err(error::ClientError::Shedded {
+                feature: shed::Feature::BackgroundRefresh,
+                ..
+            })
+        ));
+        assert_eq!(client.rpc_usage().total_requests, requests);
+
+        // Pressure stays severe after the failure rate drops below its entry threshold
+        while rate() >= 0.5 {
+            client.quote_buy(&mint, 1_000_000_000).unwrap();
+        }
+        assert_eq!(client.load_pressure(), shed::Pressure::Severe);
+
+        // Then eases a level at a time, resuming background refreshes first
+        while rate() > 0.3 {
+            client.quote_buy(&mint, 1_000_000_000).unwrap();
+        }
+        assert_eq!(client.load_pressure(), shed::Pressure::Elevated);
+        assert_eq!(scheduler.tick(&client).unwrap(), 1);
+        assert!(scheduler.cache().get(&mint).unwrap().account.is_some());
+        assert!(client.get_token_snapshot(&mint).is_err());
+
+        while rate() > 0.1 {
+            client.quote_buy(&mint, 1_000_000_000).unwrap();
+        }
+        assert_eq!(client.load_pressure(), shed::Pressure::Normal);
+    }
+
+    #[test]
+    fn test_rpc_pressure_follows_client_clock() {
+        use std::sync::atomic::AtomicBool;
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let failing = std::sync::Arc::new(AtomicBool::new(true));
+        let sender = FlakySender {
+            failing: failing.clone(),
+            global: PumpFun::get_global_pda(),
+            data: [
+                borsh::to_vec(&get_global()).unwrap(),
+                borsh::to_vec(&get_fresh_curve()).unwrap(),
+            ],
+            requests: Default::default(),
+        };
+        let clock = std::sync::Arc::new(utils::clock::MockClock::new());
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender)
+            .with_load_shedder(shed::LoadShedder::new())
+            .with_clock(clock.clone());
+
+        for _ in 0..10 {
+            assert!(client.quote_buy(&mint, 1_000_000_000).is_err());
+        }
+        assert_eq!(client.load_pressure(), shed::Pressure::Severe);
+
+        // Failures recorded by the transport age on the client's clock, without waiting
+        clock.advance(shed::PRESSURE_HALF_LIFE * 10);
+        assert_eq!(client.rpc_pressure.failure_rate(client.clock().now()), None);
+        assert_eq!(client.load_pressure(), shed::Pressure::Normal);
+    }
+
+    #[test]
+    fn test_buy_and_sell_quotes() {
+        use anchor_client::solana_client::rpc_request::RpcRequest;
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let global = get_global();
+        let curve = get_fresh_curve();
+        let sender = FlakySender {
+            failing: Default::default(),
+            global: PumpFun::get_global_pda(),
+            data: [
+                borsh::to_vec(&global).unwrap(),
+                borsh::to_vec(&curve).unwrap(),
+            ],
+            requests: Default::default(),
+        };
+        let requests = sender.requests.clone();
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+
+        // Quotes match the plain quotes and default to 500 basis points of slippage over the
+        // cost with fees
+        let buy = client.get_buy_quote(&mint, 1_000_000_000, None).unwrap();
+        assert_eq!(
+            buy.token_amount,
+            client.quote_buy(&mint, 1_000_000_000).unwrap()
+        );
+        assert_eq!(buy.max_sol_cost, 1_060_500_000);
+        assert_eq!(buy.fee_amount, 10_000_000);
+        assert_eq!(buy.virtual_sol_reserves, curve.virtual_sol_reserves);
+        let sell = client
+            .get_sell_quote(&mint, 1_000_000_000, Some(100))
+            .unwrap();
+        assert_eq!(
+            sell.sol_amount,
+            client.quote_sell(&mint, 1_000_000_000).unwrap()
+        );
+        assert_eq!(
+            sell.min_sol_output,
+            utils::calculate_with_slippage_sell(sell.sol_amount, 100)
+        );
+
+        // Each quote is a single account read, after the node version asked for once
+        assert_eq!(
+            *requests.lock().unwrap(),
+            [
+                RpcRequest::GetVersion,
+                RpcRequest::GetMultipleAccounts,
+                RpcRequest::GetMultipleAccounts,
+                RpcRequest::GetMultipleAccounts,
+                RpcRequest::GetMultipleAccounts,
+            ]
+        );
+    }
+
+    /// Global account of a fresh deployment with a 1% fee, paid to a new fee recipient
+    fn get_global() -> accounts::GlobalAccount {
+        accounts::GlobalAccount::new(
+            1,
+            true,
+            Pubkey::default(),
+            Pubkey::new_unique(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        )
+    }
+
+    /// Bonding curve of a token nobody has traded yet, with the reserves of [`get_global`]
+    fn get_fresh_curve() -> accounts::BondingCurveAccount {
+        accounts::BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            false,
+        )
+    }
+
+    /// Client on the original program version whose transport serves [`get_global`], a fresh
+    /// curve for the mint, the mint under the token program and a balance in every token account
+    ///
+    /// Returns the client with the global account it serves.
+    fn mock_client_with_curve<'a>(
+        payer: &'a Keypair,
+        mint: &Pubkey,
+    ) -> (PumpFun<'a>, accounts::GlobalAccount) {
+        let global = get_global();
+        let sender = AccountsSender {
+            accounts: HashMap::from([
+                (
+                    PumpFun::get_global_pda(),
+                    (
+                        constants::accounts::PUMPFUN,
+                        borsh::to_vec(&global).unwrap(),
+                    ),
+                ),
+                (
+                    PumpFun::get_bonding_curve_pda(mint).unwrap(),
+                    (
+                        constants::accounts::PUMPFUN,
+                        borsh::to_vec(&get_fresh_curve()).unwrap(),
+                    ),
+                ),
+                (*mint, (constants::accounts::TOKEN_PROGRAM, Vec::new())),
+            ]),
+            balance: 1_000_000,
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, payer, None, sender)
+            .with_program_version(instruction::ProgramVersion::V1);
+        (client, global)
+    }
+
+    /// Transport serving fixed accounts by address, with every other account missing
+    struct AccountsSender {
+        /// Owner and data of each served account
+        accounts: HashMap<Pubkey, (Pubkey, Vec<u8>)>,
+        /// Raw token balance of every token account
+        balance: u64,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for AccountsSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use base64::Engine;
+
+            let account = |address: &serde_json::Value| {
+                let address: Pubkey = address.as_str().unwrap().parse().unwrap();
+                match self.accounts.get(&address) {
+                    Some((owner, data)) => serde_json::json!({
+                        "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                        "executable": false,
+                        "lamports": 1,
+                        "owner": owner.to_string(),
+                        "rentEpoch": 0,
+                    }),
+                    None => serde_json::Value::Null,
+                }
+            };
+            let value = match request {
+                // The client asks for the node version before mapping commitments
+                RpcRequest::GetVersion => {
+                    return Ok(serde_json::json!({
+                        "solana-core": "1.18.26",
+                        "feature-set": 0,
+                    }))
+                }
+                RpcRequest::GetAccountInfo => account(&params[0]),
+                RpcRequest::GetMultipleAccounts => {
+                    params[0].as_array().unwrap().iter().map(account).collect()
+                }
+                RpcRequest::GetTokenAccountBalance => serde_json::json!({
+                    "amount": self.balance.to_string(),
+                    "decimals": 6,
+                    "uiAmount": null,
+                    "uiAmountString": "",
+                }),
+                request => panic!("unexpected request {request}"),
+            };
+            Ok(serde_json::json!({ "context": { "slot": 1 }, "value": value }))
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "accounts".to_string()
+        }
+    }
+
+    #[test]
+    fn test_buy_and_sell_ix() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let (client, global) = mock_client_with_curve(&payer, &mint);
+        let fee_recipient = global.fee_recipient;
+        let curve = get_fresh_curve();
+        let fee = PriorityFee {
+            limit: Some(200_000),
+            price: Some(1_000),
+        };
+        let programs = |instructions: &[Instruction]| -> Vec<Pubkey> {
+            instructions.iter().map(|ix| ix.program_id).collect()
+        };
+
+        // Buys create the missing token account and carry the memo last
+        let mut params = params::BuyParams::new(mint, 1_000_000_000);
+        params.priority_fee = Some(fee);
+        params.memo = Some("buy".to_string());
+        let instructions = client.buy_ix(&params).unwrap();
+        assert_eq!(
+            programs(&instructions),
+            [
+                solana_sdk::compute_budget::id(),
+                solana_sdk::compute_budget::id(),
+                constants::accounts::ASSOCIATED_TOKEN_PROGRAM,
+                constants::accounts::PUMPFUN,
+                constants::accounts::MEMO_PROGRAM,
+            ]
+        );
+        assert_eq!(
+            instructions[3],
+            instruction::buy_with_token_program(
+                &client.seeds,
+                &constants::accounts::TOKEN_PROGRAM,
+                &payer,
+                &mint,
+                &fee_recipient,
+                cpi::instruction::Buy {
+                    _amount: curve.get_buy_price(1_000_000_000).unwrap(),
+                    _max_sol_cost: utils::SlippageMode::default().max_sol_cost(1_000_000_000),
+                },
+            )
+        );
+        assert_eq!(instructions[4], instruction::memo("buy"));
+
+        // Sells default to the whole balance
+        let mut params = params::SellParams::new(mint);
+        params.extra_instructions = vec![instruction::memo("first")];
+        let instructions = client.sell_ix(&params).unwrap();
+        let min_sol_output = utils::calculate_with_slippage_sell(
+            curve
+                .get_sell_price(1_000_000, global.total_fee_bps())
+                .unwrap(),
+            500,
+        );
+        assert_eq!(
+            instructions,
+            [
+                instruction::memo("first"),
+                instruction::sell_with_token_program(
+                    &client.seeds,
+                    &constants::accounts::TOKEN_PROGRAM,
+                    &payer,
+                    &mint,
+                    &fee_recipient,
+                    cpi::instruction::Sell {
+                        _amount: 1_000_000,
+                        _min_sol_output: min_sol_output,
+                    },
+                ),
+            ]
+        );
+
+        // An overridden fee recipient replaces the one in the global account
+        let rotated = Pubkey::new_unique();
+        let instructions = client
+            .sell_ix(&params::SellParams::new(mint).with_fee_recipient(rotated))
+            .unwrap();
+        assert_eq!(
+            instructions,
+            [instruction::sell_with_token_program(
+                &client.seeds,
+                &constants::accounts::TOKEN_PROGRAM,
+                &payer,
+                &mint,
+                &rotated,
+                cpi::instruction::Sell {
+                    _amount: 1_000_000,
+                    _min_sol_output: min_sol_output,
+                },
+            )]
+        );
+        let instructions = client
+            .buy_ix(&params::BuyParams::new(mint, 1_000_000_000).with_fee_recipient(rotated))
+            .unwrap();
+        assert_eq!(
+            instructions.last(),
+            Some(&instruction::buy_with_token_program(
+                &client.seeds,
+                &constants::accounts::TOKEN_PROGRAM,
+                &payer,
+                &mint,
+                &rotated,
+                cpi::instruction::Buy {
+                    _amount: curve.get_buy_price(1_000_000_000).unwrap(),
+                    _max_sol_cost: utils::SlippageMode::default().max_sol_cost(1_000_000_000),
+                },
+            ))
+        );
+    }
+
+    #[test]
+    fn test_client_fee_recipient() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let (client, global) = mock_client_with_curve(&payer, &mint);
+        let rotated = Pubkey::new_unique();
+        let client = client.with_fee_recipient(rotated);
+        // Fee recipient account of the last instruction, a buy or sell
+        let fee_recipient = |instructions: Vec<Instruction>| {
+            let trade = instructions.last().unwrap();
+            assert_eq!(trade.program_id, constants::accounts::PUMPFUN);
+            trade.accounts[1].pubkey
+        };
+
+        // Trades without a fee recipient use the client's
+        let buy = params::BuyParams::new(mint, 1_000_000_000);
+        assert_eq!(fee_recipient(client.buy_ix(&buy).unwrap()), rotated);
+        let sell = params::SellParams::new(mint);
+        assert_eq!(fee_recipient(client.sell_ix(&sell).unwrap()), rotated);
+        let instructions = client
+            .create_and_buy_instructions(
+                &Keypair::new(),
+                &global,
+                cpi::instruction::Create {
+                    _name: "Test Token".to_string(),
+                    _symbol: "TEST".to_string(),
+                    _uri: "https://ipfs.io/ipfs/metadata".to_string(),
+                },
+                1_000_000_000,
+                None,
+                None,
+                Vec::new(),
+            )
+            .unwrap();
+        assert_eq!(fee_recipient(instructions), rotated);
+
+        // A fee recipient set on the trade takes precedence
+        let other = Pubkey::new_unique();
+        let buy = buy.with_fee_recipient(other);
+        assert_eq!(fee_recipient(client.buy_ix(&buy).unwrap()), other);
+        let sell = sell.with_fee_recipient(other);
+        assert_eq!(fee_recipient(client.sell_ix(&sell).unwrap()), other);
+        assert_ne!(global.fee_recipient, rotated);
+    }
+
+    /// Signer provider whose keypair is swapped on demand, counting how often it is resolved
+    struct SwappableSigner {
+        /// Currently active keypair
+        keypair: Mutex<std::sync::Arc<Keypair>>,
+        /// Number of times the keypair was resolved
+        resolved: std::sync::atomic::AtomicUsize,
+    }
+
+    impl SwappableSigner {
+        fn rotate(&self, keypair: Keypair) {
+            *self.keypair.lock().unwrap() = std::sync::Arc::new(keypair);
+        }
+    }
+
+    impl signers::SignerProvider for SwappableSigner {
+        fn current(&self) -> std::sync::Arc<Keypair> {
+            self.resolved
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            self.keypair.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_signer_resolved_per_trade() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let first = Keypair::new();
+        let first_pubkey = first.pubkey();
+        let provider = std::sync::Arc::new(SwappableSigner {
+            keypair: Mutex::new(std::sync::Arc::new(first)),
+            resolved: std::sync::atomic::AtomicUsize::new(0),
+        });
+        let (client, _) = mock_client_with_curve(&payer, &mint);
+        let client = client.with_signer_provider(provider.clone());
+        let params = params::BuyParams::new(mint, 1_000_000_000);
+        // Signing user of the buy instruction, the last one
+        let user = |instructions: Vec<Instruction>| {
+            let buy = instructions.last().unwrap();
+            assert_eq!(buy.program_id, constants::accounts::PUMPFUN);
+            assert!(buy.accounts[6].is_signer);
+            buy.accounts[6].pubkey
+        };
+
+        // Each call resolves the provider once, and the payer is never used
+        assert_eq!(user(client.buy_ix(&params).unwrap()), first_pubkey);
+        assert_eq!(
+            provider.resolved.load(std::sync::atomic::Ordering::SeqCst),
+            1
+        );
+
+        // Rotating the keypair mid-sequence changes the signer of the next trade
+        let second = Keypair::new();
+        let second_pubkey = second.pubkey();
+        provider.rotate(second);
+        assert_eq!(user(client.buy_ix(&params).unwrap()), second_pubkey);
+        assert_ne!(second_pubkey, payer.pubkey());
+
+        // A trade in flight keeps the keypair it started with
+        let third = Keypair::new();
+        let third_pubkey = third.pubkey();
+        futures::executor::block_on(client.trade_scoped(usage::Operation::Buy, async {
+            assert_eq!(client.signer().pubkey(), second_pubkey);
+            provider.rotate(third);
+            assert_eq!(client.signer().pubkey(), second_pubkey);
+            assert_eq!(user(client.buy_ix(&params).unwrap()), second_pubkey);
+        }));
+        assert_eq!(client.signer().pubkey(), third_pubkey);
+    }
+
+    #[test]
+    fn test_priority_fee_in_trade_requests() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        // Compute budget instructions at the start of the buy and sell requests, as sent
+        let budget_instructions = |client: &PumpFun, fee: Option<PriorityFee>| {
+            let fee = client.apply_fee_budget(fee).unwrap();
+            let mut buy = params::BuyParams::new(mint, 1_000_000_000);
+            buy.priority_fee = fee;
+            let mut sell = params::SellParams::new(mint);
+            sell.priority_fee = fee;
+            [
+                futures::executor::block_on(client.buy_request(&buy))
+                    .unwrap()
+                    .instructions()
+                    .unwrap(),
+                futures::executor::block_on(client.sell_request(&sell, None))
+                    .unwrap()
+                    .0
+                    .instructions()
+                    .unwrap(),
+            ]
+            .map(|instructions| {
+                let pumpfun = instructions
+                    .iter()
+                    .position(|ix| ix.program_id == constants::accounts::PUMPFUN)
+                    .unwrap();
+                instructions[..pumpfun]
+                    .iter()
+                    .filter(|ix| ix.program_id == solana_sdk::compute_budget::id())
+                    .cloned()
+                    .collect::<Vec<Instruction>>()
+            })
+        };
+        let explicit = PriorityFee {
+            limit: None,
+            price: Some(7),
+        };
+
+        // Nothing is added without a fee
+        let (client, _) = mock_client_with_curve(&payer, &mint);
+        assert_eq!(budget_instructions(&client, None), [Vec::new(), Vec::new()]);
+        let explicit_instructions = vec![ComputeBudgetInstruction::set_compute_unit_price(7)];
+        assert_eq!(
+            budget_instructions(&client, Some(explicit)),
+            [explicit_instructions.clone(), explicit_instructions.clone()]
+        );
+
+        // The client default is prepended, and a fee passed to the call replaces it
+        let (client, _) = mock_client_with_curve(&payer, &mint);
+        let client = client.with_priority_fee(50_000, Some(120_000));
+        let default_instructions = vec![
+            ComputeBudgetInstruction::set_compute_unit_limit(120_000),
+            ComputeBudgetInstruction::set_compute_unit_price(50_000),
+        ];
+        assert_eq!(
+            budget_instructions(&client, None),
+            [default_instructions.clone(), default_instructions]
+        );
+        assert_eq!(
+            budget_instructions(&client, Some(explicit)),
+            [explicit_instructions.clone(), explicit_instructions]
+        );
+    }
+
+    #[test]
+    fn test_async_account_getters() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let (client, _) = mock_client_with_curve(&payer, &mint);
+
+        // The async getters go through the client's sender and decode like the blocking ones
+        assert_eq!(
+            borsh::to_vec(&futures::executor::block_on(client.get_global_account_async()).unwrap())
+                .unwrap(),
+            borsh::to_vec(&client.get_global_account().unwrap()).unwrap()
+        );
+        assert_eq!(
+            futures::executor::block_on(client.get_bonding_curve_account_async(&mint)).unwrap(),
+            client.get_bonding_curve_account(&mint).unwrap()
+        );
+        // Two lookups each way, after the node version asked for once by the shared client
+        assert_eq!(client.rpc_usage().total_requests, 5);
+
+        // Missing accounts surface as errors rather than panics
+        let unknown = Pubkey::new_unique();
+        assert!(
+            futures::executor::block_on(client.get_bonding_curve_account_async(&unknown)).is_err()
+        );
+
+        // The token program is cached across the blocking and async lookups
+        assert_eq!(
+            futures::executor::block_on(client.get_token_program_async(&mint)).unwrap(),
+            constants::accounts::TOKEN_PROGRAM
+        );
+        assert_eq!(
+            client.get_token_program(&mint).unwrap(),
+            constants::accounts::TOKEN_PROGRAM
+        );
+        assert_eq!(client.rpc_usage().total_requests, 7);
+
+        // Requests are sent with the fetched accounts
+        let params = params::BuyParams::new(mint, 1_000_000_000);
+        let request = futures::executor::block_on(client.buy_request(&params)).unwrap();
+        assert_eq!(
+            request.instructions().unwrap(),
+            client.buy_ix(&params).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_trade_instructions_without_fetching() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let fee_recipient = Pubkey::new_unique();
+        let client = PumpFun::new(Cluster::Devnet, &payer, None, None)
+            .with_program_version(instruction::ProgramVersion::V4);
+        let accounts = params::TradeAccounts::new(fee_recipient)
+            .with_token_program(constants::accounts::TOKEN_2022_PROGRAM)
+            .with_creator(creator);
+        let buy = || cpi::instruction::Buy {
+            _amount: 1_000,
+            _max_sol_cost: 2_000,
+        };
+        let sell = || cpi::instruction::Sell {
+            _amount: 1_000,
+            _min_sol_output: 500,
+        };
+
+        assert_eq!(
+            client.buy_instruction(&mint, &accounts, buy()).unwrap(),
+            instruction::with_program_version(
+                instruction::buy_with_token_program(
+                    &client.seeds,
+                    &constants::accounts::TOKEN_2022_PROGRAM,
+                    &payer,
+                    &mint,
+                    &fee_recipient,
+                    buy(),
+                ),
+                instruction::ProgramVersion::V4,
+                &client.seeds,
+                &creator,
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            client.sell_instruction(&mint, &accounts, sell()).unwrap(),
+            instruction::with_program_version(
+                instruction::sell_with_token_program(
+                    &client.seeds,
+                    &constants::accounts::TOKEN_2022_PROGRAM,
+                    &payer,
+                    &mint,
+                    &fee_recipient,
+                    sell(),
+                ),
+                instruction::ProgramVersion::V4,
+                &client.seeds,
+                &creator,
+            )
+            .unwrap()
+        );
+        assert_eq!(client.rpc_usage().total_requests, 0);
+    }
+
+    /// Transport serving a wallet's signature history and the transactions behind it
+    struct HistorySender {
+        /// Transactions newest first, with whether each failed
+        transactions: Vec<(VersionedTransaction, bool)>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for HistorySender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use base64::Engine;
+
+            match request {
+                // The client asks for the node version before mapping commitments
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetSignaturesForAddress => Ok(self
+                    .transactions
+                    .iter()
+                    .map(|(transaction, failed)| {
+                        serde_json::json!({
+                            "signature": transaction.signatures[0].to_string(),
+                            "slot": 1,
+                            "err": if *failed { serde_json::json!({ "InsufficientFundsForFee": null }) } else { serde_json::Value::Null },
+                            "memo": null,
+                            "blockTime": 100,
+                        })
+                    })
+                    .collect()),
+                RpcRequest::GetTransaction => {
+                    let (transaction, _) = self
+                        .transactions
+                        .iter()
+                        .find(|(transaction, _)| {
+                            transaction.signatures[0].to_string() == params[0].as_str().unwrap()
+                        })
+                        .unwrap();
+                    let bytes = bincode::serialize(transaction).unwrap();
+                    Ok(serde_json::json!({
+                        "slot": 1,
+                        "blockTime": 200,
+                        "transaction": [base64::engine::general_purpose::STANDARD.encode(bytes), "base64"],
+                        "meta": null,
+                    }))
+                }
+                request => panic!("unexpected request {request}"),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "history".to_string()
+        }
+    }
+
+    #[test]
+    fn test_get_sol_transfers() {
+        let payer = Keypair::new();
+        let wallet = Keypair::new();
+        let funder = Keypair::new();
+        let transaction = |signer: &Keypair, instructions: &[Instruction]| {
+            let mut transaction = Transaction::new_with_payer(instructions, Some(&signer.pubkey()));
+            transaction.sign(&[signer], solana_sdk::hash::Hash::new_unique());
+            VersionedTransaction::from(transaction)
+        };
+        let other = Pubkey::new_unique();
+        let sender = HistorySender {
+            transactions: vec![
+                (
+                    transaction(
+                        &wallet,
+                        &[solana_sdk::system_instruction::transfer(
+                            &wallet.pubkey(),
+                            &other,
+                            3,
+                        )],
+                    ),
+                    true,
+                ),
+                (
+                    transaction(
+                        &funder,
+                        &[
+                            solana_sdk::system_instruction::transfer(&funder.pubkey(), &other, 1),
+                            solana_sdk::system_instruction::transfer(
+                                &funder.pubkey(),
+                                &wallet.pubkey(),
+                                2,
+                            ),
+                        ],
+                    ),
+                    false,
+                ),
+            ],
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+
+        // Failed transactions and transfers between other wallets are skipped
+        assert_eq!(
+            client.get_sol_transfers(&wallet.pubkey(), 10).unwrap(),
+            [analytics::Transfer {
+                from: funder.pubkey(),
+                to: wallet.pubkey(),
+                lamports: 2,
+                timestamp: 200,
+            }]
+        );
+        // One page of signatures, one transaction fetch for the successful one, and the node
+        // version asked for before the first of them
+        let usage = client.rpc_usage();
+        assert_eq!(
+            usage
+                .operation(usage::Operation::Backfill)
+                .unwrap()
+                .requests,
+            vec![
+                (usage::RpcMethod::GetSignaturesForAddress, 1),
+                (usage::RpcMethod::GetTransaction, 1),
+                (usage::RpcMethod::Other, 1)
+            ]
+        );
+        assert_eq!(usage.total_requests, 3);
+    }
+
+    /// Transport serving the payer's history to a journal reconciliation
+    struct JournalSender {
+        /// Successful transactions of the payer, newest first, with their logs
+        history: Vec<(Signature, Vec<String>)>,
+        /// Journal being reconciled, read while transactions are fetched
+        journal: std::sync::Arc<journal::TradeJournal>,
+        /// Number of transactions fetched
+        fetched: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for JournalSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+
+            match request {
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetBlockHeight => Ok(serde_json::json!(1_000)),
+                // No journaled signature landed
+                RpcRequest::GetSignatureStatuses => {
+                    let statuses =
+                        vec![serde_json::Value::Null; params[0].as_array().unwrap().len()];
+                    Ok(serde_json::json!({ "context": { "slot": 1 }, "value": statuses }))
+                }
+                RpcRequest::GetSignaturesForAddress => Ok(self
+                    .history
+                    .iter()
+                    .map(|(signature, _)| {
+                        serde_json::json!({
+                            "signature": signature.to_string(),
+                            "slot": 1,
+                            "err": null,
+                            "memo": null,
+                            "blockTime": 100,
+                        })
+                    })
+                    .collect()),
+                RpcRequest::GetTransaction => {
+                    // Deadlocks if the journal is held while its trades are looked up
+                    assert_eq!(self.journal.entries().len(), 2);
+                    self.fetched
+                        .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    let (_, logs) = self
+                        .history
+                        .iter()
+                        .find(|(signature, _)| signature.to_string() == params[0].as_str().unwrap())
+                        .unwrap();
+                    Ok(serde_json::json!({
+                        "slot": 1,
+                        "blockTime": 200,
+                        "transaction": ["AA==", "base64"],
+                        "meta": {
+                            "err": null,
+                            "status": { "Ok": null },
+                            "fee": 5_000,
+                            "preBalances": [],
+                            "postBalances": [],
+                            "logMessages": logs,
+                        },
+                    }))
+                }
+                request => panic!("unexpected request {request}"),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "journal".to_string()
+        }
+    }
+
+    /// Transport serving the global account and a paged signature history of its authority
+    struct ParamHistorySender {
+        /// Data of the global account
+        global: Vec<u8>,
+        /// Successful transactions of the authority, newest first, with their logs
+        history: Vec<(Signature, Vec<String>)>,
+        /// Number of signature pages requested
+        pages: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for ParamHistorySender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use base64::Engine;
+
+            let position = |signature: &serde_json::Value| {
+                signature.as_str().map(|signature| {
+                    self.history
+                        .iter()
+                        .position(|(known, _)| known.to_string() == signature)
+                        .unwrap()
+                })
+            };
+            match request {
+                // The client asks for the node version before mapping commitments
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetAccountInfo => Ok(serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": {
+                        "data": [base64::engine::general_purpose::STANDARD.encode(&self.global), "base64"],
+                        "executable": false,
+                        "lamports": 1,
+                        "owner": constants::accounts::PUMPFUN.to_string(),
+                        "rentEpoch": 0,
+                    },
+                })),
+                RpcRequest::GetSignaturesForAddress => {
+                    self.pages.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                    let config = &params[1];
+                    let start = position(&config["before"]).map_or(0, |before| before + 1);
+                    let end = position(&config["until"]).unwrap_or(self.history.len());
+                    let limit = config["limit"].as_u64().unwrap() as usize;
+                    Ok(self.history[start..end.max(start)]
+                        .iter()
+                        .take(limit)
+                        .map(|(signature, _)| {
+                            serde_json::json!({
+                                "signature": signature.to_string(),
+                                "slot": 1,
+                                "err": null,
+                                "memo": null,
+                                "blockTime": 100,
+                            })
+                        })
+                        .collect())
+                }
+                RpcRequest::GetTransaction => {
+                    let (_, logs) = &self.history[position(&params[0]).unwrap()];
+                    Ok(serde_json::json!({
+                        "slot": 1,
+                        "blockTime": 200,
+                        "transaction": ["AA==", "base64"],
+                        "meta": {
+                            "err": null,
+                            "status": { "Ok": null },
+                            "fee": 5_000,
+                            "preBalances": [],
+                            "postBalances": [],
+                            "logMessages": logs,
+                        },
+                    }))
+                }
+                request => panic!("unexpected request {request}"),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "param-history".to_string()
+        }
+    }
+
+    #[test]
+    fn test_get_global_param_changes_pages_history() {
+        use base64::Engine;
+
+        let payer = Keypair::new();
+        let global = accounts::GlobalAccount::new(
+            1,
+            true,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        );
+        let set_params = |fee_basis_points| {
+            let event = events::SetParamsEvent {
+                fee_recipient: global.fee_recipient,
+                initial_virtual_token_reserves: global.initial_virtual_token_reserves,
+                initial_virtual_sol_reserves: global.initial_virtual_sol_reserves,
+                initial_real_token_reserves: global.initial_real_token_reserves,
+                token_total_supply: global.token_total_supply,
+                fee_basis_points,
+            };
+            let mut data = <events::SetParamsEvent as events::Event>::DISCRIMINATOR.to_vec();
+            data.extend(borsh::to_vec(&event).unwrap());
+            format!(
+                "{}{}",
+                events::PROGRAM_DATA_PREFIX,
+                base64::engine::general_purpose::STANDARD.encode(data)
+            )
+        };
+        // Two and a half pages of history, with a change every 50 transactions
+        let history: Vec<(Signature, Vec<String>)> = (0..PARAM_HISTORY_PAGE * 5 / 2)
+            .map(|index| {
+                let logs = if index % 50 == 0 {
+                    vec![set_params(index as u64)]
+                } else {
+                    Vec::new()
+                };
+                (Signature::new_unique(), logs)
+            })
+            .collect();
+        let pages = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let client = PumpFun::new_with_sender(
+            Cluster::Devnet,
+            &payer,
+            None,
+            ParamHistorySender {
+                global: borsh::to_vec(&global).unwrap(),
+                history: history.clone(),
+                pages: pages.clone(),
+            },
+        );
+        let fees = |changes: &[GlobalParamChange]| -> Vec<u64> {
+            changes
+                .iter()
+                .map(|change| change.global.fee_basis_points)
+                .collect()
+        };
+
+        // The whole history is walked page by page until it runs out
+        let changes =
+            futures::executor::block_on(client.get_global_param_changes(10, None)).unwrap();
+        assert_eq!(fees(&changes), vec![0, 50, 100, 150, 200]);
+        assert_eq!(changes[1].signature, history[50].0);
+        assert_eq!(changes[1].global.authority, global.authority);
+        assert_eq!(pages.swap(0, std::sync::atomic::Ordering::SeqCst), 4);
+
+        // The walk stops at the limit without requesting further pages
+        let changes =
+            futures::executor::block_on(client.get_global_param_changes(2, None)).unwrap();
+        assert_eq!(fees(&changes), vec![0, 50]);
+        assert_eq!(pages.swap(0, std::sync::atomic::Ordering::SeqCst), 1);
+
+        // And at the until cursor, which is excluded
+        let changes =
+            futures::executor::block_on(client.get_global_param_changes(10, Some(history[150].0)))
+                .unwrap();
+        assert_eq!(fees(&changes), vec![0, 50, 100]);
+        assert_eq!(pages.swap(0, std::sync::atomic::Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_reconcile_fetches_each_transaction_once() {
+        use base64::Engine;
+
+        let payer = Keypair::new();
+        let path = std::env::temp_dir().join(format!("journal-{}.json", Pubkey::new_unique()));
+        let journal = std::sync::Arc::new(journal::TradeJournal::open(&path).unwrap());
+        let clock = utils::clock::MockClock::at_unix_timestamp(1_700_000_000);
+        let entry = |side, mint| {
+            journal::JournalEntry::pending(
+                trade_id::TradeId {
+                    session: 1,
+                    sequence: 0,
+                },
+                Signature::new_unique(),
+                mint,
+                side,
+                1_000_000,
+                50_000_000,
+                100,
+                &clock,
+            )
+        };
+        let bought = entry(journal::TradeSide::Buy, Pubkey::new_unique());
+        let sold = entry(journal::TradeSide::Sell, Pubkey::new_unique());
+        journal.record(bought.clone()).unwrap();
+        journal.record(sold.clone()).unwrap();
+
+        // The buy landed under another signature, alongside someone else's sell of the other mint
+        let log = |mint, is_buy, user| {
+            let event = events::TradeEvent {
+                mint,
+                sol_amount: 40_000_000,
+                token_amount: 1_000_000,
+                is_buy,
+                user,
+                timestamp: 1_700_000_005,
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+            };
+            let mut data = <events::TradeEvent as events::Event>::DISCRIMINATOR.to_vec();
+            data.extend(borsh::to_vec(&event).unwrap());
+            format!(
+                "{}{}",
+                events::PROGRAM_DATA_PREFIX,
+                base64::engine::general_purpose::STANDARD.encode(data)
+            )
+        };
+        let fill = Signature::new_unique();
+        let fetched = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let sender = JournalSender {
+            history: vec![
+                (
+                    fill,
+                    vec![
+                        log(bought.mint, true, payer.pubkey()),
+                        log(sold.mint, false, Pubkey::new_unique()),
+                    ],
+                ),
+                (Signature::new_unique(), vec![]),
+            ],
+            journal: journal.clone(),
+            fetched: fetched.clone(),
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+
+        let report = journal.reconcile(&client).unwrap();
+        assert!(report.unknown.is_empty());
+        assert_eq!(
+            report.resolved,
+            [
+                journal::JournalEntry {
+                    state: journal::EntryState::Confirmed,
+                    fill_signature: Some(fill),
+                    ..bought
+                },
+                journal::JournalEntry {
+                    state: journal::EntryState::Expired,
+                    ..sold
+                },
+            ]
+        );
+        assert_eq!(journal.entries(), report.resolved);
+
+        // One fetch per transaction in the history, however many mints are searched for
+        assert_eq!(fetched.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    /// Transport serving one confirmed transaction with the given logs, and its blockhash as the
+    /// latest one
+    struct ConfirmedSender {
+        /// Transaction returned for any signature
+        transaction: Transaction,
+        /// Log messages of the transaction
+        logs: Vec<String>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcSender for ConfirmedSender {
+        async fn send(
+            &self,
+            request: anchor_client::solana_client::rpc_request::RpcRequest,
+            _params: serde_json::Value,
+        ) -> anchor_client::solana_client::client_error::Result<serde_json::Value> {
+            use anchor_client::solana_client::rpc_request::RpcRequest;
+            use base64::Engine;
+
+            match request {
+                RpcRequest::GetVersion => Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                })),
+                RpcRequest::GetLatestBlockhash => Ok(serde_json::json!({
+                    "context": { "slot": 1 },
+                    "value": {
+                        "blockhash": self.transaction.message.recent_blockhash.to_string(),
+                        "lastValidBlockHeight": 100,
+                    },
+                })),
+                RpcRequest::GetTransaction => Ok(serde_json::json!({
+                    "slot": 1,
+                    "blockTime": 200,
+                    "transaction": [
+                        base64::engine::general_purpose::STANDARD
+                            .encode(bincode::serialize(&self.transaction).unwrap()),
+                        "base64",
+                    ],
+                    "meta": {
+                        "err": null,
+                        "status": { "Ok": null },
+                        "fee": 5_000,
+                        "preBalances": [],
+                        "postBalances": [],
+                        "logMessages": self.logs,
+                    },
+                })),
+                request => panic!("unexpected request {request}"),
+            }
+        }
+
+        fn get_transport_stats(
+            &self,
+        ) -> anchor_client::solana_client::rpc_sender::RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "confirmed".to_string()
+        }
+    }
+
+    #[test]
+    fn test_trade_memo_reaches_outcome_and_journal() {
+        use base64::Engine;
+
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let (client, _) = mock_client_with_curve(&payer, &mint);
+
+        // The requests sent by buy_with_outcome and sell_with_outcome, carrying the memo
+        let buy = params::BuyParams::new(mint, 1_000_000_000).with_memo("strategy-buy");
+        let sell = params::SellParams::new(mint).with_memo("strategy-sell");
+        let requests = [
+            (
+                futures::executor::block_on(client.buy_request(&buy))
+                    .unwrap()
+                    .instructions()
+                    .unwrap(),
+                true,
+                "strategy-buy",
+            ),
+            (
+                futures::executor::block_on(client.sell_request(&sell, None))
+                    .unwrap()
+                    .0
+                    .instructions()
+                    .unwrap(),
+                false,
+                "strategy-sell",
+            ),
+        ];
+
+        for (instructions, is_buy, memo) in requests {
+            let transaction = Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer.pubkey()),
+                &[&payer],
+                solana_sdk::hash::Hash::new_unique(),
+            );
+            let event = events::TradeEvent {
+                mint,
+                sol_amount: 40_000_000,
+                token_amount: 1_000_000,
+                is_buy,
+                user: payer.pubkey(),
+                timestamp: 1_700_000_005,
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+            };
+            let mut data = <events::TradeEvent as events::Event>::DISCRIMINATOR.to_vec();
+            data.extend(borsh::to_vec(&event).unwrap());
+            let sender = ConfirmedSender {
+                transaction: transaction.clone(),
+                logs: vec![format!(
+                    "{}{}",
+                    events::PROGRAM_DATA_PREFIX,
+                    base64::engine::general_purpose::STANDARD.encode(data)
+                )],
+            };
+            let confirmed = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+
+            // The memo is read back from the confirmed transaction along with the fill
+            let (trade_event, read_memo) = futures::executor::block_on(confirmed.read_fill(
+                &transaction.signatures[0],
+                &mint,
+                None,
+            ))
+            .unwrap();
+            assert_eq!(trade_event, Some(event));
+            assert_eq!(read_memo.as_deref(), Some(memo));
+
+            // And recorded in the journal entry of the outcome
+            let outcome = TradeOutcome {
+                trade_id: client.next_trade_id(),
+                signature: transaction.signatures[0],
+                trade_event,
+                timings: timings::StageTimer::start().timings(),
+                transaction: None,
+                submit_path: submit::SubmitPath::default(),
+                memo: read_memo,
+            };
+            let entry = journal::JournalEntry::from_outcome(&outcome).unwrap();
+            assert_eq!(entry.memo.as_deref(), Some(memo));
+            assert_eq!(
+                entry.side,
+                if is_buy {
+                    journal::TradeSide::Buy
+                } else {
+                    journal::TradeSide::Sell
+                }
+            );
+            assert_eq!(entry.state, journal::EntryState::Confirmed);
+        }
+    }
+
+    #[test]
+    fn test_concurrent_signing_is_attributed_per_operation() {
+        let payer = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction::memo("signed")],
+            Some(&payer.pubkey()),
+            &[&payer],
+            solana_sdk::hash::Hash::new_unique(),
+        );
+        let sender = ConfirmedSender {
+            transaction: transaction.clone(),
+            logs: Vec::new(),
+        };
+        let client = PumpFun::new_with_sender(Cluster::Devnet, &payer, None, sender);
+        let sign = |operation| {
+            let client = &client;
+            usage::scoped(operation, async move {
+                let request = client
+                    .program
+                    .request()
+                    .instruction(instruction::memo("signed"));
+                // Let the other signings start before this one fetches its blockhash
+                let mut yielded = false;
+                futures::future::poll_fn(|cx| {
+                    if yielded {
+                        return std::task::Poll::Ready(());
+                    }
+                    yielded = true;
+                    cx.waker().wake_by_ref();
+                    std::task::Poll::Pending
+                })
+                .await;
+                client.sign_request(&request, &[]).await
+            })
+        };
+
+        // The blockhash is fetched through the client, once per signing and for its own
+        // operation, however the signings interleave
+        let untagged = client
+            .program
+            .request()
+            .instruction(instruction::memo("signed"));
+        let signed = futures::executor::block_on(async {
+            futures::join!(
+                sign(usage::Operation::Buy),
+                sign(usage::Operation::Sell),
+                client.sign_request(&untagged, &[]),
+            )
+        });
+        assert_eq!(signed.0.unwrap(), transaction);
+        assert_eq!(signed.1.unwrap(), transaction);
+        assert_eq!(signed.2.unwrap(), transaction);
+
+        for operation in [
+            usage::Operation::Buy,
+            usage::Operation::Sell,
+            usage::Operation::Untagged,
+        ] {
+            assert_eq!(
+                client
+                    .rpc_usage
+                    .count(operation, usage::RpcMethod::GetLatestBlockhash),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn test_replay_bundle_carries_curve_state() {
+        let payer = Keypair::new();
+        let mint = Pubkey::new_unique();
+        let (client, global) = mock_client_with_curve(&payer, &mint);
+        let bonding_curve = PumpFun::get_bonding_curve_pda(&mint).unwrap();
+
+        // Exactly 1 SOL plus the 1% fee, so the same buy right after it exceeds its bound
+        let buy = instruction::buy_with_token_program(
+            &client.seeds,
+            &constants::accounts::TOKEN_PROGRAM,
+            &payer,
+            &mint,
+            &global.fee_recipient,
+            cpi::instruction::Buy {
+                _amount: 34_612_903_225_806,
+                _max_sol_cost: 1_010_000_000,
+            },
+        );
+        let transaction =
+            VersionedTransaction::from(Transaction::new_with_payer(&[buy], Some(&payer.pubkey())));
+
+        let simulation = client
+            .replay_bundle(&[transaction.clone(), transaction])
+            .unwrap();
+        assert!(simulation.transactions[0].succeeded());
+        assert_eq!(
+            simulation.transactions[1].err,
+            Some(solana_sdk::transaction::TransactionError::InstructionError(
+                0,
+                solana_sdk::instruction::InstructionError::Custom(6002)
+            ))
+        );
+        assert_eq!(
+            simulation.final_curves,
+            vec![(
+                bonding_curve,
+                Some(accounts::BondingCurveAccount::new(
+                    1,
+                    1_038_387_096_774_194,
+                    31_000_000_000,
+                    758_487_096_774_194,
+                    1_000_000_000,
+                    1_000_000_000_000_000,
+                    false,
+                ))
+            )]
+        );
+        assert!(simulation.ensure_success().is_err());
     }
 }
@@ -0,0 +1,565 @@
+//! Detection of MEV around Pump.fun trades
+//!
+//! This module inspects the trades that landed in the same block as a user's trade to flag a
+//! likely sandwich: a buy by some wallet right before the user's trade on the same bonding curve,
+//! followed by a sell by that same wallet right after it.
+//!
+//! - `BlockTrade`: A trade decoded from a block, with its position in the block.
+//! - `SandwichReport`: The suspected attacker and the value they extracted.
+//! - `find_sandwich`: Looks for a sandwich around a trade in a block's trades.
+//!
+//! It also reconstructs the competition for a bonding curve within a slot, for post-mortems of
+//! trades that lost a race, including transactions that failed:
+//!
+//! - `TradeInstruction`: The arguments of a Pump.fun buy or sell instruction.
+//! - `CompetingTransaction`: A transaction touching a bonding curve, with its fee settings.
+//! - `CompetitionReport`: Every transaction touching a bonding curve in a slot, in block order.
+
+use crate::{
+    constants,
+    events::{self, TradeEvent},
+    utils,
+};
+use solana_sdk::{
+    compute_budget, pubkey::Pubkey, signature::Signature, transaction::VersionedTransaction,
+};
+
+/// Discriminator of the Pump.fun buy instruction
+pub const BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+
+/// Discriminator of the Pump.fun sell instruction
+pub const SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Tag of the compute budget instruction setting the compute unit limit
+const SET_COMPUTE_UNIT_LIMIT_TAG: u8 = 2;
+
+/// Tag of the compute budget instruction setting the compute unit price
+const SET_COMPUTE_UNIT_PRICE_TAG: u8 = 3;
+
+/// A trade decoded from a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockTrade {
+    /// Position of the trade's transaction in the block
+    pub index: usize,
+    /// Signature of the trade's transaction
+    pub signature: Signature,
+    /// Trade event emitted by the program
+    pub event: TradeEvent,
+}
+
+/// A suspected sandwich around a trade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SandwichReport {
+    /// Wallet suspected of sandwiching the trade
+    pub attacker: Pubkey,
+    /// Signature of the buy placed before the trade
+    pub front_run: Signature,
+    /// Signature of the sell placed after the trade
+    pub back_run: Signature,
+    /// Tokens bought in the front run and not sold back in the back run
+    pub tokens_retained: u64,
+    /// Estimated value extracted in lamports: SOL received in the back run minus SOL paid in
+    /// the front run, before fees
+    pub extracted_lamports: i64,
+}
+
+/// Looks for a sandwich around a trade in a block's trades
+///
+/// Walks the buys before the victim's trade on the same mint, nearest first, and reports the
+/// first wallet that also sold the mint after the victim's trade.
+///
+/// # Arguments
+///
+/// * `trades` - Trades in the block, in block order
+/// * `victim` - Signature of the trade to check
+///
+/// # Returns
+///
+/// Returns the suspected sandwich, or None if the trade is not in `trades` or no wallet bought
+/// before and sold after it
+pub fn find_sandwich(trades: &[BlockTrade], victim: &Signature) -> Option<SandwichReport> {
+    let position = trades.iter().position(|trade| trade.signature == *victim)?;
+    let victim = &trades[position];
+    let mint = victim.event.mint;
+
+    trades[..position]
+        .iter()
+        .rev()
+        .filter(|trade| {
+            trade.event.mint == mint && trade.event.is_buy && trade.event.user != victim.event.user
+        })
+        .find_map(|front_run| {
+            let back_run = trades[position + 1..].iter().find(|trade| {
+                trade.event.mint == mint
+                    && !trade.event.is_buy
+                    && trade.event.user == front_run.event.user
+            })?;
+
+            Some(SandwichReport {
+                attacker: front_run.event.user,
+                front_run: front_run.signature,
+                back_run: back_run.signature,
+                tokens_retained: front_run
+                    .event
+                    .token_amount
+                    .saturating_sub(back_run.event.token_amount),
+                extracted_lamports: back_run.event.sol_amount as i64
+                    - front_run.event.sol_amount as i64,
+            })
+        })
+}
+
+/// Arguments of a Pump.fun buy or sell instruction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeInstruction {
+    /// Buy an exact amount of tokens for at most the given SOL
+    Buy {
+        /// Amount of tokens to buy in base units
+        amount: u64,
+        /// Maximum SOL cost in lamports
+        max_sol_cost: u64,
+    },
+    /// Sell an exact amount of tokens for at least the given SOL
+    Sell {
+        /// Amount of tokens to sell in base units
+        amount: u64,
+        /// Minimum SOL output in lamports
+        min_sol_output: u64,
+    },
+}
+
+impl TradeInstruction {
+    /// Decodes the data of a Pump.fun instruction
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Discriminator followed by the Borsh-serialized arguments
+    ///
+    /// # Returns
+    ///
+    /// The decoded arguments, or None if the data is not a buy or sell instruction
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let (discriminator, args) = data.split_first_chunk::<8>()?;
+        let (amount, args) = args.split_first_chunk::<8>()?;
+        let (limit, _) = args.split_first_chunk::<8>()?;
+        let amount = u64::from_le_bytes(*amount);
+        let limit = u64::from_le_bytes(*limit);
+
+        match *discriminator {
+            BUY_DISCRIMINATOR => Some(Self::Buy {
+                amount,
+                max_sol_cost: limit,
+            }),
+            SELL_DISCRIMINATOR => Some(Self::Sell {
+                amount,
+                min_sol_output: limit,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the instruction buys tokens
+    pub fn is_buy(&self) -> bool {
+        matches!(self, Self::Buy { .. })
+    }
+}
+
+/// A transaction touching a bonding curve, as it landed in a block
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompetingTransaction {
+    /// Position of the transaction in the block
+    pub index: usize,
+    /// Signature of the transaction
+    pub signature: Signature,
+    /// Account that paid the transaction's fees
+    pub fee_payer: Pubkey,
+    /// Whether the transaction succeeded
+    pub succeeded: bool,
+    /// First buy or sell sent directly to the Pump.fun program on the curve, None if the curve was
+    /// only reached through another program
+    pub instruction: Option<TradeInstruction>,
+    /// Trade event emitted for the mint, None if the transaction failed or did not trade it
+    pub event: Option<TradeEvent>,
+    /// Compute unit price in micro-lamports, None if not set
+    pub compute_unit_price: Option<u64>,
+    /// Compute unit limit, None if not set
+    pub compute_unit_limit: Option<u32>,
+    /// First memo of the transaction, such as the strategy that sent it, None if it has none
+    pub memo: Option<String>,
+}
+
+impl CompetingTransaction {
+    /// Decodes a transaction from a block if it touches a bonding curve
+    ///
+    /// Only the transaction's static account keys are searched for the curve, so a transaction
+    /// that loads it from an address lookup table is not recognised.
+    ///
+    /// # Arguments
+    ///
+    /// * `index` - Position of the transaction in the block
+    /// * `transaction` - Decoded transaction
+    /// * `succeeded` - Whether the transaction succeeded
+    /// * `logs` - Log messages of the transaction
+    /// * `mint` - Public key of the token mint
+    /// * `bonding_curve` - Address of the mint's bonding curve
+    ///
+    /// # Returns
+    ///
+    /// The decoded transaction, or None if it does not touch the curve
+    pub fn decode(
+        index: usize,
+        transaction: &VersionedTransaction,
+        succeeded: bool,
+        logs: &[String],
+        mint: &Pubkey,
+        bonding_curve: &Pubkey,
+    ) -> Option<Self> {
+        let keys = transaction.message.static_account_keys();
+        if !keys.contains(bonding_curve) {
+            return None;
+        }
+
+        let mut instruction = None;
+        let mut compute_unit_price = None;
+        let mut compute_unit_limit = None;
+        for compiled in transaction.message.instructions() {
+            let Some(program) = keys.get(compiled.program_id_index as usize) else {
+                continue;
+            };
+            let data = compiled.data.as_slice();
+
+            if *program == compute_budget::id() {
+                match data.split_first() {
+                    Some((&SET_COMPUTE_UNIT_PRICE_TAG, price)) => {
+                        compute_unit_price = price.try_into().ok().map(u64::from_le_bytes);
+                    }
+                    Some((&SET_COMPUTE_UNIT_LIMIT_TAG, limit)) => {
+                        compute_unit_limit = limit.try_into().ok().map(u32::from_le_bytes);
+                    }
+                    _ => {}
+                }
+            } else if *program == constants::accounts::PUMPFUN && instruction.is_none() {
+                let on_curve = compiled
+                    .accounts
+                    .iter()
+                    .any(|account| keys.get(*account as usize) == Some(bonding_curve));
+                if on_curve {
+                    instruction = TradeInstruction::decode(data);
+                }
+            }
+        }
+
+        let event = if succeeded {
+            events::parse_logs::<TradeEvent>(logs)
+                .into_iter()
+                .find(|event| event.mint == *mint)
+        } else {
+            None
+        };
+
+        Some(Self {
+            index,
+            signature: transaction.signatures.first().copied().unwrap_or_default(),
+            fee_payer: keys.first().copied().unwrap_or_default(),
+            succeeded,
+            instruction,
+            event,
+            compute_unit_price,
+            compute_unit_limit,
+            memo: utils::extract_memos(&transaction.message)
+                .into_iter()
+                .next(),
+        })
+    }
+
+    /// Whether the transaction bought tokens, from its event or else its instruction
+    pub fn is_buy(&self) -> Option<bool> {
+        self.event
+            .as_ref()
+            .map(|event| event.is_buy)
+            .or(self.instruction.map(|instruction| instruction.is_buy()))
+    }
+
+    /// Calculates the priority fee the transaction offered in lamports
+    ///
+    /// # Returns
+    ///
+    /// The compute unit price times the compute unit limit, or None if either is not set
+    pub fn priority_fee_lamports(&self) -> Option<u64> {
+        let price = self.compute_unit_price? as u128;
+        let limit = self.compute_unit_limit? as u128;
+        Some((price * limit).div_ceil(1_000_000).min(u64::MAX as u128) as u64)
+    }
+}
+
+/// Every transaction touching a bonding curve in a slot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompetitionReport {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Slot of the block
+    pub slot: u64,
+    /// Transactions touching the curve, in block order
+    pub transactions: Vec<CompetingTransaction>,
+}
+
+impl CompetitionReport {
+    /// Finds the position of a transaction among the competing transactions
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the transaction
+    ///
+    /// # Returns
+    ///
+    /// The number of competing transactions that landed before it, or None if it is not in
+    /// the report
+    pub fn position(&self, signature: &Signature) -> Option<usize> {
+        self.transactions
+            .iter()
+            .position(|transaction| transaction.signature == *signature)
+    }
+
+    /// Gets the successful buys that landed before a transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `signature` - Signature of the transaction
+    ///
+    /// # Returns
+    ///
+    /// The buys ahead of the transaction in block order, empty if it is not in the report
+    pub fn buys_before(&self, signature: &Signature) -> Vec<&CompetingTransaction> {
+        let Some(position) = self.position(signature) else {
+            return Vec::new();
+        };
+
+        self.transactions[..position]
+            .iter()
+            .filter(|transaction| transaction.succeeded && transaction.is_buy() == Some(true))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::{
+        compute_budget::ComputeBudgetInstruction,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, VersionedMessage},
+    };
+
+    fn get_trade(
+        index: usize,
+        mint: Pubkey,
+        user: Pubkey,
+        is_buy: bool,
+        sol_amount: u64,
+    ) -> BlockTrade {
+        BlockTrade {
+            index,
+            signature: Signature::new_unique(),
+            event: TradeEvent {
+                mint,
+                sol_amount,
+                token_amount: 1_000_000,
+                is_buy,
+                user,
+                timestamp: 0,
+                virtual_sol_reserves: 0,
+                virtual_token_reserves: 0,
+                real_sol_reserves: 0,
+                real_token_reserves: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_find_sandwich() {
+        let mint = Pubkey::new_unique();
+        let attacker = Pubkey::new_unique();
+        let victim = Pubkey::new_unique();
+        let trades = vec![
+            get_trade(0, mint, attacker, true, 2_000_000_000),
+            get_trade(1, Pubkey::new_unique(), Pubkey::new_unique(), true, 1),
+            get_trade(2, mint, victim, true, 1_000_000_000),
+            get_trade(3, mint, attacker, false, 2_100_000_000),
+        ];
+
+        let report = find_sandwich(&trades, &trades[2].signature).unwrap();
+        assert_eq!(report.attacker, attacker);
+        assert_eq!(report.front_run, trades[0].signature);
+        assert_eq!(report.back_run, trades[3].signature);
+        assert_eq!(report.tokens_retained, 0);
+        assert_eq!(report.extracted_lamports, 100_000_000);
+    }
+
+    #[test]
+    fn test_find_sandwich_none() {
+        let mint = Pubkey::new_unique();
+        let victim = Pubkey::new_unique();
+        let trades = vec![
+            get_trade(0, mint, Pubkey::new_unique(), true, 1),
+            get_trade(1, mint, victim, true, 1),
+            get_trade(2, mint, Pubkey::new_unique(), false, 1),
+        ];
+
+        // Different wallets before and after is not a sandwich
+        assert_eq!(find_sandwich(&trades, &trades[1].signature), None);
+        assert_eq!(find_sandwich(&trades, &Signature::new_unique()), None);
+    }
+
+    fn get_transaction(instructions: &[Instruction], payer: &Pubkey) -> VersionedTransaction {
+        let message = v0::Message::try_compile(payer, instructions, &[], Hash::default()).unwrap();
+        VersionedTransaction {
+            signatures: vec![Signature::new_unique()],
+            message: VersionedMessage::V0(message),
+        }
+    }
+
+    fn get_trade_instruction(
+        discriminator: [u8; 8],
+        bonding_curve: &Pubkey,
+        payer: &Pubkey,
+    ) -> Instruction {
+        let mut data = discriminator.to_vec();
+        data.extend(1_000_000u64.to_le_bytes());
+        data.extend(2_000_000u64.to_le_bytes());
+        Instruction::new_with_bytes(
+            constants::accounts::PUMPFUN,
+            &data,
+            vec![
+                AccountMeta::new(*bonding_curve, false),
+                AccountMeta::new(*payer, true),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_decode_trade_instruction() {
+        let mut data = BUY_DISCRIMINATOR.to_vec();
+        data.extend(1u64.to_le_bytes());
+        data.extend(2u64.to_le_bytes());
+        assert_eq!(
+            TradeInstruction::decode(&data),
+            Some(TradeInstruction::Buy {
+                amount: 1,
+                max_sol_cost: 2
+            })
+        );
+
+        data[..8].copy_from_slice(&SELL_DISCRIMINATOR);
+        assert_eq!(
+            TradeInstruction::decode(&data),
+            Some(TradeInstruction::Sell {
+                amount: 1,
+                min_sol_output: 2
+            })
+        );
+
+        assert_eq!(TradeInstruction::decode(&data[..20]), None);
+        data[0] = 0;
+        assert_eq!(TradeInstruction::decode(&data), None);
+    }
+
+    #[test]
+    fn test_decode_competing_transaction() {
+        let mint = Pubkey::new_unique();
+        let bonding_curve = Pubkey::new_unique();
+        let payer = Pubkey::new_unique();
+
+        let transaction = get_transaction(
+            &[
+                ComputeBudgetInstruction::set_compute_unit_limit(100_000),
+                ComputeBudgetInstruction::set_compute_unit_price(250_000),
+                get_trade_instruction(BUY_DISCRIMINATOR, &bonding_curve, &payer),
+            ],
+            &payer,
+        );
+        let competing =
+            CompetingTransaction::decode(3, &transaction, false, &[], &mint, &bonding_curve)
+                .unwrap();
+        assert_eq!(competing.index, 3);
+        assert_eq!(competing.signature, transaction.signatures[0]);
+        assert_eq!(competing.fee_payer, payer);
+        assert!(!competing.succeeded);
+        assert_eq!(
+            competing.instruction,
+            Some(TradeInstruction::Buy {
+                amount: 1_000_000,
+                max_sol_cost: 2_000_000
+            })
+        );
+        assert_eq!(competing.event, None);
+        assert_eq!(competing.compute_unit_price, Some(250_000));
+        assert_eq!(competing.compute_unit_limit, Some(100_000));
+        assert_eq!(competing.priority_fee_lamports(), Some(25_000));
+        assert_eq!(competing.is_buy(), Some(true));
+        assert_eq!(competing.memo, None);
+
+        // Memos attribute the transaction to the strategy that sent it
+        let tagged = get_transaction(
+            &[
+                get_trade_instruction(BUY_DISCRIMINATOR, &bonding_curve, &payer),
+                crate::instruction::memo("strategy-v1"),
+            ],
+            &payer,
+        );
+        let competing =
+            CompetingTransaction::decode(0, &tagged, true, &[], &mint, &bonding_curve).unwrap();
+        assert_eq!(competing.memo.as_deref(), Some("strategy-v1"));
+
+        // Transactions that do not touch the curve are left out
+        let other = get_transaction(
+            &[get_trade_instruction(
+                SELL_DISCRIMINATOR,
+                &Pubkey::new_unique(),
+                &payer,
+            )],
+            &payer,
+        );
+        assert_eq!(
+            CompetingTransaction::decode(0, &other, true, &[], &mint, &bonding_curve),
+            None
+        );
+    }
+
+    #[test]
+    fn test_competition_report_order() {
+        let mint = Pubkey::new_unique();
+        let bonding_curve = Pubkey::new_unique();
+        let transactions: Vec<CompetingTransaction> = [
+            (BUY_DISCRIMINATOR, true),
+            (SELL_DISCRIMINATOR, true),
+            (BUY_DISCRIMINATOR, false),
+            (BUY_DISCRIMINATOR, true),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(index, (discriminator, succeeded))| {
+            let payer = Pubkey::new_unique();
+            let transaction = get_transaction(
+                &[get_trade_instruction(discriminator, &bonding_curve, &payer)],
+                &payer,
+            );
+            CompetingTransaction::decode(index, &transaction, succeeded, &[], &mint, &bonding_curve)
+                .unwrap()
+        })
+        .collect();
+        let report = CompetitionReport {
+            mint,
+            slot: 1,
+            transactions,
+        };
+
+        let mine = report.transactions[3].signature;
+        assert_eq!(report.position(&mine), Some(3));
+        // The failed buy and the sell did not get ahead
+        let ahead = report.buys_before(&mine);
+        assert_eq!(ahead.len(), 1);
+        assert_eq!(ahead[0].index, 0);
+        assert!(report.buys_before(&Signature::new_unique()).is_empty());
+    }
+}
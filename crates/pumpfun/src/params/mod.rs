@@ -0,0 +1,399 @@
+//! Typed arguments for buys and sells
+//!
+//! [`crate::PumpFun::buy`] and [`crate::PumpFun::sell`] take their options positionally, so
+//! every new option would change their signature. [`crate::PumpFun::buy_with`] and
+//! [`crate::PumpFun::sell_with`] take them as a struct built from the required values, with
+//! each option set by a `with_*` method:
+//!
+//! ```rust
+//! use pumpfun::{params::BuyParams, PriorityFee};
+//! use anchor_client::solana_sdk::pubkey::Pubkey;
+//!
+//! let params = BuyParams::new(Pubkey::new_unique(), 100_000_000)
+//!     .with_slippage_bps(300)
+//!     .with_priority_fee(PriorityFee {
+//!         limit: Some(100_000),
+//!         price: Some(50_000),
+//!     })
+//!     .with_confirmation(true);
+//! assert_eq!(params.sol_amount, 100_000_000);
+//! ```
+//!
+//! The structs are `#[non_exhaustive]`, so new options can be added without breaking callers.
+//! The positional methods remain as shorthands delegating to them.
+//!
+//! - `BuyParams`: Token and SOL amount of a buy, with its options.
+//! - `SellParams`: Token and amount of a sell, with its options.
+//! - `TradeAccounts`: Accounts a buy or sell instruction needs, resolved by the caller.
+
+use crate::{utils::SlippageMode, PriorityFee};
+use anchor_client::solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+/// Token and SOL amount of a buy, with its options
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct BuyParams {
+    /// Public key of the token mint to buy
+    pub mint: Pubkey,
+    /// Amount of SOL to spend in lamports
+    pub sol_amount: u64,
+    /// Slippage bound on the SOL cost, defaults to 500 basis points over the quote
+    pub slippage: Option<SlippageMode>,
+    /// Priority fee configuration for compute units
+    pub priority_fee: Option<PriorityFee>,
+    /// Instructions to execute atomically before the buy, after any priority fee instructions
+    pub extra_instructions: Vec<Instruction>,
+    /// Whether to wait for the transaction to confirm, defaults to true
+    pub confirm: bool,
+    /// Note appended to the transaction as an SPL Memo instruction, after the buy
+    pub memo: Option<String>,
+    /// Fee recipient to pay instead of the one in the global account
+    pub fee_recipient: Option<Pubkey>,
+}
+
+impl BuyParams {
+    /// Creates a confirmed buy with the default slippage and no priority fee
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to buy
+    /// * `sol_amount` - Amount of SOL to spend in lamports
+    pub fn new(mint: Pubkey, sol_amount: u64) -> Self {
+        Self {
+            mint,
+            sol_amount,
+            slippage: None,
+            priority_fee: None,
+            extra_instructions: Vec::new(),
+            confirm: true,
+            memo: None,
+            fee_recipient: None,
+        }
+    }
+
+    /// Sets the slippage bound on the SOL cost
+    ///
+    /// # Arguments
+    ///
+    /// * `slippage` - Slippage mode, in basis points over the quote or as a maximum multiple
+    pub fn with_slippage(mut self, slippage: SlippageMode) -> Self {
+        self.slippage = Some(slippage);
+        self
+    }
+
+    /// Sets the slippage bound on the SOL cost in basis points over the quote
+    ///
+    /// # Arguments
+    ///
+    /// * `basis_points` - Maximum slippage in basis points (1 bp = 0.01%)
+    pub fn with_slippage_bps(self, basis_points: u64) -> Self {
+        self.with_slippage(SlippageMode::BasisPoints(basis_points))
+    }
+
+    /// Sets the priority fee
+    ///
+    /// # Arguments
+    ///
+    /// * `priority_fee` - Priority fee configuration for compute units
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = Some(priority_fee);
+        self
+    }
+
+    /// Sets instructions to execute atomically before the buy
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Instructions in execution order. Compute budget instructions must be
+    ///   left to the priority fee, as a transaction may only contain one of each
+    pub fn with_instructions(mut self, instructions: Vec<Instruction>) -> Self {
+        self.extra_instructions = instructions;
+        self
+    }
+
+    /// Sets whether to wait for the transaction to confirm
+    ///
+    /// # Arguments
+    ///
+    /// * `confirm` - Whether to wait for confirmation, or return once the node accepted the
+    ///   transaction
+    pub fn with_confirmation(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Sets a memo identifying the buy, such as the strategy that placed it
+    ///
+    /// The memo is checked against the space left in the transaction once all other
+    /// instructions are in place, and the buy fails with a ClientError::MemoTooLong if it does
+    /// not fit.
+    ///
+    /// # Arguments
+    ///
+    /// * `memo` - UTF-8 note to record on chain with the buy
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Sets the fee recipient to pay instead of the one in the global account
+    ///
+    /// The program only accepts the recipients it currently allows, so this is for when the
+    /// global account read lags behind a rotation of recipients.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Fee recipient known to be valid
+    pub fn with_fee_recipient(mut self, fee_recipient: Pubkey) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+}
+
+/// Token and amount of a sell, with its options
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct SellParams {
+    /// Public key of the token mint to sell
+    pub mint: Pubkey,
+    /// Amount of tokens to sell in base units, None to sell the entire balance
+    pub token_amount: Option<u64>,
+    /// Maximum slippage on the SOL output in basis points, defaults to 500
+    pub slippage_basis_points: Option<u64>,
+    /// Priority fee configuration for compute units
+    pub priority_fee: Option<PriorityFee>,
+    /// Instructions to execute atomically before the sell, after any priority fee instructions
+    pub extra_instructions: Vec<Instruction>,
+    /// Whether to wait for the transaction to confirm, defaults to true
+    pub confirm: bool,
+    /// Note appended to the transaction as an SPL Memo instruction, after the sell
+    pub memo: Option<String>,
+    /// Fee recipient to pay instead of the one in the global account
+    pub fee_recipient: Option<Pubkey>,
+}
+
+impl SellParams {
+    /// Creates a confirmed sell of the entire balance with the default slippage and no
+    /// priority fee
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint to sell
+    pub fn new(mint: Pubkey) -> Self {
+        Self {
+            mint,
+            token_amount: None,
+            slippage_basis_points: None,
+            priority_fee: None,
+            extra_instructions: Vec::new(),
+            confirm: true,
+            memo: None,
+            fee_recipient: None,
+        }
+    }
+
+    /// Sets the amount of tokens to sell instead of the entire balance
+    ///
+    /// # Arguments
+    ///
+    /// * `token_amount` - Amount of tokens to sell in base units
+    pub fn with_amount(mut self, token_amount: u64) -> Self {
+        self.token_amount = Some(token_amount);
+        self
+    }
+
+    /// Sets the slippage bound on the SOL output
+    ///
+    /// # Arguments
+    ///
+    /// * `basis_points` - Maximum slippage in basis points (1 bp = 0.01%)
+    pub fn with_slippage_bps(mut self, basis_points: u64) -> Self {
+        self.slippage_basis_points = Some(basis_points);
+        self
+    }
+
+    /// Sets the priority fee
+    ///
+    /// # Arguments
+    ///
+    /// * `priority_fee` - Priority fee configuration for compute units
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = Some(priority_fee);
+        self
+    }
+
+    /// Sets instructions to execute atomically before the sell
+    ///
+    /// # Arguments
+    ///
+    /// * `instructions` - Instructions in execution order. Compute budget instructions must be
+    ///   left to the priority fee, as a transaction may only contain one of each
+    pub fn with_instructions(mut self, instructions: Vec<Instruction>) -> Self {
+        self.extra_instructions = instructions;
+        self
+    }
+
+    /// Sets whether to wait for the transaction to confirm
+    ///
+    /// # Arguments
+    ///
+    /// * `confirm` - Whether to wait for confirmation, or return once the node accepted the
+    ///   transaction
+    pub fn with_confirmation(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Sets a memo identifying the sell, such as the strategy that placed it
+    ///
+    /// The memo is checked against the space left in the transaction once all other
+    /// instructions are in place, and the sell fails with a ClientError::MemoTooLong if it does
+    /// not fit.
+    ///
+    /// # Arguments
+    ///
+    /// * `memo` - UTF-8 note to record on chain with the sell
+    pub fn with_memo(mut self, memo: impl Into<String>) -> Self {
+        self.memo = Some(memo.into());
+        self
+    }
+
+    /// Sets the fee recipient to pay instead of the one in the global account
+    ///
+    /// The program only accepts the recipients it currently allows, so this is for when the
+    /// global account read lags behind a rotation of recipients.
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Fee recipient known to be valid
+    pub fn with_fee_recipient(mut self, fee_recipient: Pubkey) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+}
+
+/// Accounts a buy or sell instruction needs beyond its token, resolved by the caller
+///
+/// Passed to [`crate::PumpFun::buy_instruction`] and [`crate::PumpFun::sell_instruction`], which
+/// then build the instruction without fetching the global account or the mint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TradeAccounts {
+    /// Fee recipient from the global account
+    pub fee_recipient: Pubkey,
+    /// Token program that owns the mint
+    pub token_program: Pubkey,
+    /// Creator of the token, looked up when the program version needs it if not set
+    pub creator: Option<Pubkey>,
+}
+
+impl TradeAccounts {
+    /// Creates the accounts of a trade on a mint owned by the token program
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Fee recipient from the global account
+    pub fn new(fee_recipient: Pubkey) -> Self {
+        Self {
+            fee_recipient,
+            token_program: crate::constants::accounts::TOKEN_PROGRAM,
+            creator: None,
+        }
+    }
+
+    /// Sets the token program that owns the mint
+    ///
+    /// # Arguments
+    ///
+    /// * `token_program` - Token program that owns the mint
+    pub fn with_token_program(mut self, token_program: Pubkey) -> Self {
+        self.token_program = token_program;
+        self
+    }
+
+    /// Sets the creator of the token, sparing its lookup on program versions with creator vaults
+    ///
+    /// # Arguments
+    ///
+    /// * `creator` - Creator of the token
+    pub fn with_creator(mut self, creator: Pubkey) -> Self {
+        self.creator = Some(creator);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_builders() {
+        let mint = Pubkey::new_unique();
+        let fee = PriorityFee {
+            limit: Some(100_000),
+            price: Some(1),
+        };
+
+        let buy = BuyParams::new(mint, 1_000);
+        assert_eq!(buy.slippage, None);
+        assert!(buy.confirm);
+        let buy = buy
+            .with_slippage_bps(300)
+            .with_priority_fee(fee)
+            .with_instructions(vec![Instruction::new_with_bytes(mint, &[], Vec::new())])
+            .with_confirmation(false);
+        assert_eq!(buy.slippage, Some(SlippageMode::BasisPoints(300)));
+        assert_eq!(buy.priority_fee, Some(fee));
+        assert_eq!(buy.extra_instructions.len(), 1);
+        assert!(!buy.confirm);
+        let buy = buy.with_slippage(SlippageMode::MaxMultiplier(1.5));
+        assert_eq!(buy.slippage, Some(SlippageMode::MaxMultiplier(1.5)));
+        assert_eq!(buy.memo, None);
+        assert_eq!(buy.fee_recipient, None);
+        assert_eq!(
+            buy.clone().with_fee_recipient(mint).fee_recipient,
+            Some(mint)
+        );
+        assert_eq!(
+            buy.with_memo("strategy-v1").memo.as_deref(),
+            Some("strategy-v1")
+        );
+
+        let sell = SellParams::new(mint);
+        assert_eq!(sell.token_amount, None);
+        assert!(sell.confirm);
+        let sell = sell
+            .with_amount(5)
+            .with_slippage_bps(100)
+            .with_priority_fee(fee);
+        assert_eq!(sell.token_amount, Some(5));
+        assert_eq!(sell.slippage_basis_points, Some(100));
+        assert_eq!(sell.priority_fee, Some(fee));
+        assert_eq!(sell.fee_recipient, None);
+        assert_eq!(
+            sell.clone().with_fee_recipient(mint).fee_recipient,
+            Some(mint)
+        );
+        assert_eq!(
+            sell.with_memo(String::from("v2")).memo.as_deref(),
+            Some("v2")
+        );
+
+        let recipient = Pubkey::new_unique();
+        let accounts = TradeAccounts::new(recipient);
+        assert_eq!(
+            accounts.token_program,
+            crate::constants::accounts::TOKEN_PROGRAM
+        );
+        assert_eq!(accounts.creator, None);
+        let accounts = accounts
+            .with_token_program(crate::constants::accounts::TOKEN_2022_PROGRAM)
+            .with_creator(mint);
+        assert_eq!(accounts.fee_recipient, recipient);
+        assert_eq!(
+            accounts.token_program,
+            crate::constants::accounts::TOKEN_2022_PROGRAM
+        );
+        assert_eq!(accounts.creator, Some(mint));
+    }
+}
@@ -0,0 +1,230 @@
+//! Trade plans built from cached curve state
+//!
+//! A plan fixes the amounts and slippage bound of a trade from the state of a [`CurveCache`],
+//! without a round trip to the cluster, and is sent later with [`crate::PumpFun::execute`]:
+//!
+//! - `TradePlan`: The amounts of a planned buy or sell and the slot of the state it was built from.
+//!
+//! A plan is only as good as the reserves it was priced against. When the cache is fed by a
+//! websocket stream (see [`crate::refresh::RefreshScheduler::stream_until_cancelled`]), every
+//! trade on the curve moves the cached slot forward, and executing refuses plans that have
+//! fallen more than the client's slot budget behind it, see
+//! [`crate::PumpFun::with_plan_slot_budget`].
+
+use crate::{
+    error::ClientError,
+    journal::TradeSide,
+    refresh::{CachedCurve, CurveCache},
+    utils::{self, SlippageMode},
+};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+/// A planned buy or sell, priced against cached curve state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TradePlan {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Whether the plan buys or sells tokens
+    pub side: TradeSide,
+    /// Amount spent, lamports for a buy and tokens in base units for a sell
+    pub amount_in: u64,
+    /// Amount quoted in return, tokens in base units for a buy and lamports for a sell
+    pub expected_out: u64,
+    /// Slippage bound, the maximum SOL cost of a buy or the minimum SOL output of a sell
+    pub limit: u64,
+    /// Slot of the curve state the plan was priced against, None if unknown
+    pub observed_slot: Option<u64>,
+}
+
+impl TradePlan {
+    /// Plans a buy against a cached curve
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `curve` - Cached curve of the mint
+    /// * `amount_sol` - Amount of SOL to spend in lamports
+    /// * `slippage` - Slippage mode bounding the SOL cost
+    ///
+    /// # Returns
+    ///
+    /// Returns the plan, or a ClientError if the curve does not exist or has completed
+    pub fn buy(
+        mint: Pubkey,
+        curve: &CachedCurve,
+        amount_sol: u64,
+        slippage: SlippageMode,
+    ) -> Result<Self, ClientError> {
+        let account = curve
+            .account
+            .as_ref()
+            .ok_or(ClientError::BondingCurveNotFound)?;
+        let expected_out = account
+            .get_buy_price(amount_sol)
+            .map_err(ClientError::BondingCurveError)?;
+
+        Ok(Self {
+            mint,
+            side: TradeSide::Buy,
+            amount_in: amount_sol,
+            expected_out,
+            limit: slippage.max_sol_cost(amount_sol),
+            observed_slot: curve.slot,
+        })
+    }
+
+    /// Plans a sell against a cached curve
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `curve` - Cached curve of the mint
+    /// * `amount_token` - Amount of tokens to sell in base units
+    /// * `fee_basis_points` - Total fee in basis points, see
+    ///   [`crate::accounts::GlobalAccount::total_fee_bps`]
+    /// * `slippage_basis_points` - Slippage tolerance in basis points (1 bp = 0.01%)
+    ///
+    /// # Returns
+    ///
+    /// Returns the plan, or a ClientError if the curve does not exist or has completed
+    pub fn sell(
+        mint: Pubkey,
+        curve: &CachedCurve,
+        amount_token: u64,
+        fee_basis_points: u64,
+        slippage_basis_points: u64,
+    ) -> Result<Self, ClientError> {
+        let account = curve
+            .account
+            .as_ref()
+            .ok_or(ClientError::BondingCurveNotFound)?;
+        let expected_out = account
+            .get_sell_price(amount_token, fee_basis_points)
+            .map_err(ClientError::BondingCurveError)?;
+
+        Ok(Self {
+            mint,
+            side: TradeSide::Sell,
+            amount_in: amount_token,
+            expected_out,
+            limit: utils::calculate_with_slippage_sell(expected_out, slippage_basis_points),
+            observed_slot: curve.slot,
+        })
+    }
+
+    /// Checks the plan against the latest state in a cache
+    ///
+    /// Plans without an observed slot, or whose mint has no slot in the cache, cannot be
+    /// compared and pass.
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - Cache holding the latest state of the mint
+    /// * `slot_budget` - Number of slots the cached state may have moved past the plan
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok if the plan is fresh enough, or a ClientError::StalePlan otherwise
+    pub fn check_fresh(&self, cache: &CurveCache, slot_budget: u64) -> Result<(), ClientError> {
+        let (Some(observed_slot), Some(current_slot)) =
+            (self.observed_slot, cache.slot(&self.mint))
+        else {
+            return Ok(());
+        };
+
+        if current_slot.saturating_sub(observed_slot) > slot_budget {
+            return Err(ClientError::StalePlan {
+                observed_slot,
+                current_slot,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::BondingCurveAccount;
+    use std::time::Instant;
+
+    fn get_curve(virtual_sol_reserves: u64) -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            virtual_sol_reserves,
+            793_100_000_000_000,
+            virtual_sol_reserves - 30_000_000_000,
+            1_000_000_000_000_000,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_plans_price_cached_state() {
+        let cache = CurveCache::new();
+        let mint = Pubkey::new_unique();
+        cache.insert_at_slot(mint, Some(get_curve(30_000_000_000)), 100, Instant::now());
+        let cached = cache.get(&mint).unwrap();
+
+        let buy =
+            TradePlan::buy(mint, &cached, 1_000_000_000, SlippageMode::BasisPoints(100)).unwrap();
+        assert_eq!(buy.side, TradeSide::Buy);
+        assert_eq!(
+            buy.expected_out,
+            get_curve(30_000_000_000)
+                .get_buy_price(1_000_000_000)
+                .unwrap()
+        );
+        assert_eq!(buy.limit, 1_010_000_000);
+        assert_eq!(buy.observed_slot, Some(100));
+
+        let sell = TradePlan::sell(mint, &cached, 1_000_000_000, 100, 500).unwrap();
+        assert_eq!(sell.side, TradeSide::Sell);
+        assert!(sell.limit < sell.expected_out);
+
+        let missing = CachedCurve {
+            account: None,
+            fetched_at: Instant::now(),
+            slot: Some(100),
+        };
+        assert!(matches!(
+            TradePlan::buy(mint, &missing, 1, SlippageMode::default()),
+            Err(ClientError::BondingCurveNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_notifications_invalidate_plans() {
+        let cache = CurveCache::new();
+        let mint = Pubkey::new_unique();
+        cache.insert_at_slot(mint, Some(get_curve(30_000_000_000)), 100, Instant::now());
+        let plan = TradePlan::buy(
+            mint,
+            &cache.get(&mint).unwrap(),
+            1_000_000_000,
+            SlippageMode::default(),
+        )
+        .unwrap();
+        assert!(plan.check_fresh(&cache, 0).is_ok());
+
+        // A whale buy lands two slots later
+        cache.insert_at_slot(mint, Some(get_curve(80_000_000_000)), 102, Instant::now());
+        assert!(matches!(
+            plan.check_fresh(&cache, 0),
+            Err(ClientError::StalePlan {
+                observed_slot: 100,
+                current_slot: 102
+            })
+        ));
+        assert!(plan.check_fresh(&cache, 2).is_ok());
+
+        // Without slots there is nothing to compare
+        let unslotted = TradePlan {
+            observed_slot: None,
+            ..plan
+        };
+        assert!(unslotted.check_fresh(&cache, 0).is_ok());
+    }
+}
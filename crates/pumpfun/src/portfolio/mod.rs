@@ -0,0 +1,324 @@
+//! Valuation of token holdings at a single slot
+//!
+//! Pricing many holdings takes several `getMultipleAccounts` requests, and each may be served
+//! at a different slot. Totals summed over curves read at different slots describe no state
+//! the chain was ever in. [`crate::PumpFun::value_portfolio`] reads every curve at the same
+//! slot where it can, and says whether it managed to:
+//!
+//! - `Holding`: An amount of a token, with what was paid for it if known.
+//! - `HoldingValuation`: Value and profit of one holding at the snapshot slot.
+//! - `PortfolioValuation`: Value and profit of every holding, stamped with the snapshot slot.
+//!
+//! RPC nodes cannot serve reads at a past slot, only at or after a minimum one. Batches served
+//! at an earlier slot than the latest one seen are fetched again with that slot as their
+//! minimum, up to [`SNAPSHOT_RETRIES`] times. If the batches still disagree, the valuation is
+//! returned with `consistent` set to false rather than failing.
+
+use crate::{
+    accounts::{BondingCurveAccount, GlobalAccount},
+    error::ClientError,
+    summary::CurveSummary,
+};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+
+/// Number of times batches served at an earlier slot are fetched again
+pub const SNAPSHOT_RETRIES: usize = 3;
+
+/// An amount of a token held
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Holding {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Amount of tokens held in base units
+    pub amount: u64,
+    /// SOL paid for the holding in lamports, including fees, if known
+    pub cost_basis_sol: Option<u64>,
+}
+
+impl Holding {
+    /// Creates a holding with an unknown cost basis
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `amount` - Amount of tokens held in base units
+    pub fn new(mint: Pubkey, amount: u64) -> Self {
+        Self {
+            mint,
+            amount,
+            cost_basis_sol: None,
+        }
+    }
+
+    /// Sets the SOL paid for the holding, so its profit can be reported
+    ///
+    /// # Arguments
+    ///
+    /// * `cost_basis_sol` - SOL paid in lamports, including fees
+    pub fn with_cost_basis(mut self, cost_basis_sol: u64) -> Self {
+        self.cost_basis_sol = Some(cost_basis_sol);
+        self
+    }
+}
+
+/// Value of one holding at the snapshot slot
+#[derive(Debug, Clone, PartialEq)]
+pub struct HoldingValuation {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Amount of tokens held in base units
+    pub amount: u64,
+    /// Spot price in lamports per token base unit, None if the token has no bonding curve
+    pub price: Option<f64>,
+    /// SOL the holding would sell for into the curve in lamports, net of fees, None if the token
+    /// has no bonding curve or its curve has completed
+    pub value_sol: Option<u64>,
+    /// Value minus cost basis in lamports, None if either is unknown
+    pub pnl_sol: Option<i64>,
+}
+
+/// Value of every holding, read from accounts at one slot where possible
+#[derive(Debug, Clone, PartialEq)]
+pub struct PortfolioValuation {
+    /// Latest slot at which the accounts were read
+    pub snapshot_slot: u64,
+    /// Whether every account was read at `snapshot_slot`, so the totals are coherent
+    pub consistent: bool,
+    /// Valuation of each holding, in the order they were given
+    pub holdings: Vec<HoldingValuation>,
+    /// Sum of the values of the holdings that could be valued, in lamports
+    pub total_value_sol: u64,
+    /// Sum of the profits of the holdings with a known value and cost basis, in lamports, None
+    /// if there are none
+    pub total_pnl_sol: Option<i64>,
+}
+
+impl PortfolioValuation {
+    /// Values holdings against their bonding curves
+    ///
+    /// # Arguments
+    ///
+    /// * `snapshot_slot` - Latest slot at which the accounts were read
+    /// * `consistent` - Whether every account was read at `snapshot_slot`
+    /// * `global` - Global account holding the fee configuration
+    /// * `holdings` - Holdings to value
+    /// * `curves` - Bonding curve of each holding, None if the token has none
+    ///
+    /// # Returns
+    ///
+    /// Returns the valuation of each holding and the totals
+    pub fn from_accounts(
+        snapshot_slot: u64,
+        consistent: bool,
+        global: &GlobalAccount,
+        holdings: &[Holding],
+        curves: &[Option<BondingCurveAccount>],
+    ) -> Self {
+        let holdings: Vec<HoldingValuation> = holdings
+            .iter()
+            .zip(curves)
+            .map(|(holding, curve)| {
+                let price = curve.as_ref().map(|curve| {
+                    CurveSummary::new(holding.mint, curve, global.initial_real_token_reserves).price
+                });
+                let value_sol = curve.as_ref().and_then(|curve| {
+                    curve
+                        .get_sell_price(holding.amount, global.total_fee_bps())
+                        .ok()
+                });
+                let pnl_sol = value_sol
+                    .zip(holding.cost_basis_sol)
+                    .map(|(value, cost)| value as i64 - cost as i64);
+
+                HoldingValuation {
+                    mint: holding.mint,
+                    amount: holding.amount,
+                    price,
+                    value_sol,
+                    pnl_sol,
+                }
+            })
+            .collect();
+
+        let total_value_sol = holdings
+            .iter()
+            .filter_map(|holding| holding.value_sol)
+            .fold(0u64, u64::saturating_add);
+        let total_pnl_sol = holdings
+            .iter()
+            .filter_map(|holding| holding.pnl_sol)
+            .reduce(i64::saturating_add);
+
+        Self {
+            snapshot_slot,
+            consistent,
+            holdings,
+            total_value_sol,
+            total_pnl_sol,
+        }
+    }
+}
+
+/// Fetches batches of values so that every batch is read at the same slot where possible
+///
+/// The first batch is fetched without a minimum slot and every later one with the latest slot
+/// seen so far as its minimum. Batches read at an earlier slot than the latest are then fetched
+/// again with it as their minimum, up to `retries` rounds.
+///
+/// # Arguments
+///
+/// * `batches` - Number of batches to fetch
+/// * `retries` - Maximum number of rounds of fetching stale batches again
+/// * `fetch` - Fetches a batch given its index and minimum slot, returning the slot it was read
+///   at and its values
+///
+/// # Returns
+///
+/// Returns the values of every batch in order, the latest slot read, and whether every batch
+/// was read at that slot, or the first ClientError returned by `fetch`
+pub(crate) fn fetch_at_single_slot<T>(
+    batches: usize,
+    retries: usize,
+    mut fetch: impl FnMut(usize, Option<u64>) -> Result<(u64, Vec<T>), ClientError>,
+) -> Result<(Vec<T>, u64, bool), ClientError> {
+    let mut fetched: Vec<(u64, Vec<T>)> = Vec::with_capacity(batches);
+    let mut snapshot_slot: Option<u64> = None;
+    for batch in 0..batches {
+        let (slot, values) = fetch(batch, snapshot_slot)?;
+        snapshot_slot = Some(snapshot_slot.map_or(slot, |latest| latest.max(slot)));
+        fetched.push((slot, values));
+    }
+
+    let mut snapshot_slot = snapshot_slot.unwrap_or_default();
+    for _ in 0..retries {
+        let stale: Vec<usize> = (0..fetched.len())
+            .filter(|batch| fetched[*batch].0 != snapshot_slot)
+            .collect();
+        if stale.is_empty() {
+            break;
+        }
+
+        for batch in stale {
+            fetched[batch] = fetch(batch, Some(snapshot_slot))?;
+        }
+        snapshot_slot = fetched
+            .iter()
+            .map(|(slot, _)| *slot)
+            .max()
+            .unwrap_or(snapshot_slot);
+    }
+
+    let consistent = fetched.iter().all(|(slot, _)| *slot == snapshot_slot);
+    let values = fetched.into_iter().flat_map(|(_, values)| values).collect();
+    Ok((values, snapshot_slot, consistent))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_global() -> GlobalAccount {
+        GlobalAccount::new(
+            1,
+            true,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        )
+    }
+
+    fn get_curve(complete: bool) -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            1,
+            873_000_000_000_000,
+            36_872_852_233,
+            593_100_000_000_000,
+            6_872_852_233,
+            1_000_000_000_000_000,
+            complete,
+        )
+    }
+
+    #[test]
+    fn test_valuation() {
+        let global = get_global();
+        let holdings = [
+            Holding::new(Pubkey::new_unique(), 10_000_000_000).with_cost_basis(300_000_000),
+            Holding::new(Pubkey::new_unique(), 10_000_000_000),
+            Holding::new(Pubkey::new_unique(), 10_000_000_000).with_cost_basis(1),
+            Holding::new(Pubkey::new_unique(), 10_000_000_000).with_cost_basis(1),
+        ];
+        let curves = [
+            Some(get_curve(false)),
+            Some(get_curve(false)),
+            Some(get_curve(true)),
+            None,
+        ];
+
+        let valuation = PortfolioValuation::from_accounts(7, true, &global, &holdings, &curves);
+        let value = get_curve(false)
+            .get_sell_price(10_000_000_000, global.total_fee_bps())
+            .unwrap();
+        assert_eq!(valuation.snapshot_slot, 7);
+        assert_eq!(valuation.holdings[0].value_sol, Some(value));
+        assert_eq!(
+            valuation.holdings[0].pnl_sol,
+            Some(value as i64 - 300_000_000)
+        );
+        assert_eq!(valuation.holdings[1].pnl_sol, None);
+        assert!(valuation.holdings[2].price.is_some());
+        assert_eq!(valuation.holdings[2].value_sol, None);
+        assert_eq!(valuation.holdings[3].price, None);
+        assert_eq!(valuation.total_value_sol, 2 * value);
+        assert_eq!(valuation.total_pnl_sol, valuation.holdings[0].pnl_sol);
+
+        let valuation =
+            PortfolioValuation::from_accounts(7, true, &global, &holdings[1..2], &[None]);
+        assert_eq!(valuation.total_value_sol, 0);
+        assert_eq!(valuation.total_pnl_sol, None);
+    }
+
+    #[test]
+    fn test_fetch_at_single_slot() {
+        // Batches behind the latest slot are fetched again with it as their minimum
+        let mut requests = Vec::new();
+        let (values, slot, consistent) = fetch_at_single_slot(3, SNAPSHOT_RETRIES, |batch, min| {
+            requests.push((batch, min));
+            let slot = match (batch, min) {
+                (0, None) => 10,
+                (1, _) => 12,
+                (2, _) => 12,
+                (0, Some(min)) => min,
+                _ => unreachable!(),
+            };
+            Ok((slot, vec![batch]))
+        })
+        .unwrap();
+        assert_eq!(values, vec![0, 1, 2]);
+        assert_eq!(slot, 12);
+        assert!(consistent);
+        assert_eq!(
+            requests,
+            vec![(0, None), (1, Some(10)), (2, Some(12)), (0, Some(12))]
+        );
+
+        // A node that keeps advancing leaves the snapshot inconsistent once retries run out
+        let mut next_slot = 0;
+        let (_, slot, consistent) = fetch_at_single_slot(2, 2, |_, _| {
+            next_slot += 1;
+            Ok((next_slot, vec![()]))
+        })
+        .unwrap();
+        assert_eq!(slot, 4);
+        assert!(!consistent);
+
+        // A single batch is consistent by construction, and failures are passed on
+        let (_, _, consistent) = fetch_at_single_slot(1, 0, |_, _| Ok((5, vec![()]))).unwrap();
+        assert!(consistent);
+        assert!(fetch_at_single_slot::<()>(1, 0, |_, _| Err(ClientError::Timeout)).is_err());
+    }
+}
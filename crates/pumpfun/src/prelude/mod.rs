@@ -0,0 +1,63 @@
+//! Commonly used types, re-exported for downstream crates
+//!
+//! The external types are re-exported from the exact crate versions this SDK compiles against,
+//! so consumers can name them without depending on `anchor-client` or `solana-sdk` directly and
+//! without running into trait mismatches between versions.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use pumpfun::prelude::*;
+//!
+//! fn bonding_curve(client: &PumpFun, mint: &Pubkey) -> Result<BondingCurveAccount, ClientError> {
+//!     client.get_bonding_curve_account(mint)
+//! }
+//!
+//! let payer = Keypair::new();
+//! let client = PumpFun::new(Cluster::Devnet, &payer, Some(CommitmentConfig::confirmed()), None);
+//! let signer: Pubkey = client.payer.pubkey();
+//! let _ = bonding_curve(&client, &signer);
+//! let _: Option<Signature> = None;
+//! ```
+
+pub use anchor_client::{
+    solana_sdk::{
+        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature},
+        signer::Signer,
+    },
+    Cluster,
+};
+
+pub use crate::{
+    accounts::{
+        BondingCurveAccount, BuyBreakdown, CurveError, DepthPoint, DepthSide, GlobalAccount,
+        PlannedTrade, SequenceOutcome, TradeFill,
+    },
+    analytics::{EtaEstimate, ParameterEra, WalletCluster},
+    cancel::CancellationToken,
+    cleanup::{AtaCleanupConfig, CleanupEntry, CleanupState},
+    error::{ClientError, GlobalParamsViolation, PumpFunProgramError},
+    events::{
+        filters::{WhaleCriteria, WhaleReason, WhaleTrade},
+        CompleteEvent, CreateEvent, PumpFunEvent, SetParamsEvent, TradeEvent,
+    },
+    export::{TokenInfo, TokenListJson},
+    flow::NetFlowTracker,
+    instruction::ProgramVersion,
+    journal::{EntryState, JournalEntry, TradeJournal, TradeSide},
+    params::{BuyParams, SellParams, TradeAccounts},
+    plan::TradePlan,
+    portfolio::{Holding, HoldingValuation, PortfolioValuation},
+    quote::{BuyQuote, SellQuote},
+    redact::RedactionPolicy,
+    screening::{MintAuthority, SafetyReport, ScreenChecks},
+    shed::{LoadShedder, Pressure},
+    spend::{BudgetAction, FeeBudget, FeeSpendReport},
+    summary::{CreationInfo, CurveSummary, SummaryEnricher, TokenSnapshot},
+    timings::StageTimings,
+    trade_id::TradeId,
+    utils::{CreateTokenMetadata, ImagePolicy, SanitizePolicy, SlippageMode},
+    GlobalParamChange, PriorityFee, PumpFun, SeedConfig, TokenAge, TokenContext, TradeOutcome,
+};
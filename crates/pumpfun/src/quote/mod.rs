@@ -0,0 +1,260 @@
+//! Slippage-aware trade quotes
+//!
+//! This module contains the types returned by [`crate::PumpFun::get_buy_quote`] and
+//! [`crate::PumpFun::get_sell_quote`]:
+//!
+//! - `BuyQuote`: Tokens expected for an amount of SOL, and the most SOL the buy may cost.
+//! - `SellQuote`: SOL expected for an amount of tokens, and the least SOL the sell may return.
+//!
+//! Quotes only read the curve, nothing is signed or sent. A buy's bound is its expected cost
+//! with fees plus the slippage, so it never falls below what the buy is expected to cost, even
+//! with a slippage under the fee rate. A sell's bound is the one [`crate::PumpFun::sell_with`]
+//! enforces for the same slippage.
+//!
+//! Each quote also carries the fee it pays, its average price and the virtual reserves it was
+//! taken at, so a UI can show the price and market cap next to it without reading the curve
+//! again. Completed curves are not quoted, see [`ClientError::BondingCurveComplete`].
+
+use crate::{
+    accounts::{BondingCurveAccount, GlobalAccount},
+    error::ClientError,
+    utils,
+};
+
+/// Slippage tolerance of quotes that don't set one, in basis points
+pub const DEFAULT_SLIPPAGE_BASIS_POINTS: u64 = 500;
+
+/// Quote of a buy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BuyQuote {
+    /// Amount of SOL to spend in lamports
+    pub sol_amount: u64,
+    /// Amount of tokens received in base units
+    pub token_amount: u64,
+    /// Fees paid on top of the SOL spent in lamports
+    pub fee_amount: u64,
+    /// Average price paid in lamports per base unit of token, fees included
+    pub price_per_token: f64,
+    /// Most SOL the buy may cost in lamports after slippage, fees included
+    pub max_sol_cost: u64,
+    /// Slippage tolerance the bound was derived with, in basis points
+    pub slippage_basis_points: u64,
+    /// Virtual SOL reserves of the curve the quote was taken at
+    pub virtual_sol_reserves: u64,
+    /// Virtual token reserves of the curve the quote was taken at
+    pub virtual_token_reserves: u64,
+    /// Market cap of the curve the quote was taken at in lamports
+    pub market_cap_sol: u64,
+}
+
+impl BuyQuote {
+    /// Quotes a buy against a bonding curve
+    ///
+    /// # Arguments
+    ///
+    /// * `global` - Global account holding the fees, see [`GlobalAccount::total_fee_bps`]
+    /// * `curve` - Bonding curve of the token
+    /// * `sol_amount` - Amount of SOL to spend in lamports
+    /// * `slippage_basis_points` - Slippage tolerance in basis points (1 bp = 0.01%)
+    ///
+    /// # Returns
+    ///
+    /// Returns the quote, or a ClientError::BondingCurveComplete if the curve has completed
+    pub fn new(
+        global: &GlobalAccount,
+        curve: &BondingCurveAccount,
+        sol_amount: u64,
+        slippage_basis_points: u64,
+    ) -> Result<Self, ClientError> {
+        if curve.complete {
+            return Err(ClientError::BondingCurveComplete);
+        }
+        let token_amount = curve
+            .get_buy_price(sol_amount)
+            .map_err(ClientError::BondingCurveError)?;
+        let fee_amount = ((sol_amount as u128) * (global.total_fee_bps() as u128) / 10000) as u64;
+
+        Ok(Self {
+            sol_amount,
+            token_amount,
+            fee_amount,
+            price_per_token: price(sol_amount.saturating_add(fee_amount), token_amount),
+            max_sol_cost: utils::calculate_with_slippage_buy(
+                sol_amount.saturating_add(fee_amount),
+                slippage_basis_points,
+            ),
+            slippage_basis_points,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            market_cap_sol: curve.get_market_cap_sol(),
+        })
+    }
+}
+
+/// Quote of a sell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SellQuote {
+    /// Amount of tokens to sell in base units
+    pub token_amount: u64,
+    /// Amount of SOL received in lamports, net of fees
+    pub sol_amount: u64,
+    /// Fees deducted from the SOL the curve pays out in lamports
+    pub fee_amount: u64,
+    /// Average price received in lamports per base unit of token, net of fees
+    pub price_per_token: f64,
+    /// Least SOL the sell may return in lamports after slippage
+    pub min_sol_output: u64,
+    /// Slippage tolerance the bound was derived with, in basis points
+    pub slippage_basis_points: u64,
+    /// Virtual SOL reserves of the curve the quote was taken at
+    pub virtual_sol_reserves: u64,
+    /// Virtual token reserves of the curve the quote was taken at
+    pub virtual_token_reserves: u64,
+    /// Market cap of the curve the quote was taken at in lamports
+    pub market_cap_sol: u64,
+}
+
+impl SellQuote {
+    /// Quotes a sell against a bonding curve
+    ///
+    /// # Arguments
+    ///
+    /// * `global` - Global account holding the fees, see [`GlobalAccount::total_fee_bps`]
+    /// * `curve` - Bonding curve of the token
+    /// * `token_amount` - Amount of tokens to sell in base units
+    /// * `slippage_basis_points` - Slippage tolerance in basis points (1 bp = 0.01%)
+    ///
+    /// # Returns
+    ///
+    /// Returns the quote, or a ClientError::BondingCurveComplete if the curve has completed
+    pub fn new(
+        global: &GlobalAccount,
+        curve: &BondingCurveAccount,
+        token_amount: u64,
+        slippage_basis_points: u64,
+    ) -> Result<Self, ClientError> {
+        if curve.complete {
+            return Err(ClientError::BondingCurveComplete);
+        }
+        let gross_sol_amount = curve
+            .get_sell_price(token_amount, 0)
+            .map_err(ClientError::BondingCurveError)?;
+        let sol_amount = curve
+            .get_sell_price(token_amount, global.total_fee_bps())
+            .map_err(ClientError::BondingCurveError)?;
+
+        Ok(Self {
+            token_amount,
+            sol_amount,
+            fee_amount: gross_sol_amount - sol_amount,
+            price_per_token: price(sol_amount, token_amount),
+            min_sol_output: utils::calculate_with_slippage_sell(sol_amount, slippage_basis_points),
+            slippage_basis_points,
+            virtual_sol_reserves: curve.virtual_sol_reserves,
+            virtual_token_reserves: curve.virtual_token_reserves,
+            market_cap_sol: curve.get_market_cap_sol(),
+        })
+    }
+}
+
+/// Average price of a trade in lamports per base unit, 0 if no tokens change hands
+fn price(sol_amount: u64, token_amount: u64) -> f64 {
+    if token_amount == 0 {
+        return 0.0;
+    }
+    sol_amount as f64 / token_amount as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+
+    fn curve(complete: bool) -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            complete,
+        )
+    }
+
+    fn global() -> GlobalAccount {
+        GlobalAccount::new(
+            1,
+            true,
+            Pubkey::default(),
+            Pubkey::default(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        )
+    }
+
+    #[test]
+    fn test_buy_quote() {
+        let quote = BuyQuote::new(&global(), &curve(false), 1_000_000_000, 500).unwrap();
+        let token_amount = curve(false).get_buy_price(1_000_000_000).unwrap();
+        assert_eq!(quote.sol_amount, 1_000_000_000);
+        assert_eq!(quote.token_amount, token_amount);
+        assert_eq!(quote.fee_amount, 10_000_000);
+        assert_eq!(quote.price_per_token, 1_010_000_000.0 / token_amount as f64);
+        assert_eq!(quote.max_sol_cost, 1_060_500_000);
+        assert_eq!(quote.slippage_basis_points, 500);
+        assert_eq!(quote.virtual_sol_reserves, 30_000_000_000);
+        assert_eq!(quote.virtual_token_reserves, 1_073_000_000_000_000);
+        assert_eq!(quote.market_cap_sol, curve(false).get_market_cap_sol());
+
+        // Without slippage the bound is the expected cost itself
+        assert_eq!(
+            BuyQuote::new(&global(), &curve(false), 1_000_000_000, 0)
+                .unwrap()
+                .max_sol_cost,
+            1_010_000_000
+        );
+
+        // A slippage below the fee rate still covers the fee
+        let quote = BuyQuote::new(&global(), &curve(false), 1_000_000_000, 50).unwrap();
+        assert!(quote.max_sol_cost >= quote.sol_amount + quote.fee_amount);
+        assert_eq!(quote.max_sol_cost, 1_015_050_000);
+
+        // Nothing bought, nothing paid
+        let quote = BuyQuote::new(&global(), &curve(false), 0, 500).unwrap();
+        assert_eq!((quote.token_amount, quote.fee_amount), (0, 0));
+        assert_eq!(quote.price_per_token, 0.0);
+
+        assert!(matches!(
+            BuyQuote::new(&global(), &curve(true), 1_000_000_000, 500),
+            Err(ClientError::BondingCurveComplete)
+        ));
+    }
+
+    #[test]
+    fn test_sell_quote() {
+        let quote = SellQuote::new(&global(), &curve(false), 1_000_000_000, 100).unwrap();
+        let sol_amount = curve(false)
+            .get_sell_price(1_000_000_000, global().total_fee_bps())
+            .unwrap();
+        let gross_sol_amount = curve(false).get_sell_price(1_000_000_000, 0).unwrap();
+        assert_eq!(quote.token_amount, 1_000_000_000);
+        assert_eq!(quote.sol_amount, sol_amount);
+        assert_eq!(quote.fee_amount, gross_sol_amount / 100);
+        assert_eq!(quote.sol_amount + quote.fee_amount, gross_sol_amount);
+        assert_eq!(quote.price_per_token, sol_amount as f64 / 1_000_000_000.0);
+        assert_eq!(quote.min_sol_output, sol_amount - sol_amount / 100);
+        assert!(quote.min_sol_output <= quote.sol_amount);
+        assert_eq!(quote.virtual_sol_reserves, 30_000_000_000);
+        assert_eq!(quote.virtual_token_reserves, 1_073_000_000_000_000);
+        assert_eq!(quote.market_cap_sol, curve(false).get_market_cap_sol());
+
+        assert!(matches!(
+            SellQuote::new(&global(), &curve(true), 1_000_000_000, 100),
+            Err(ClientError::BondingCurveComplete)
+        ));
+    }
+}
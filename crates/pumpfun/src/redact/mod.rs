@@ -0,0 +1,225 @@
+//! Redaction of sensitive values in log output
+//!
+//! Tracing fields emitted by the crate can end up in shared log pipelines. A
+//! [`RedactionPolicy`] set with [`crate::PumpFun::with_redaction`] controls how wallets, mints
+//! and amounts are rendered in them. Redacted values are shortened or hashed rather than
+//! dropped, so events about the same wallet or token can still be correlated:
+//!
+//! - Wallets, including token accounts derived from them, keep their first and last four
+//!   characters, for example `7xKX..AsU3`.
+//! - Mints are replaced by a short hash, for example `mint#3f1a9c02b7d4`, stable across runs.
+//! - Amounts are rounded down to their order of magnitude, for example `~1e9`.
+//!
+//! Only log output is redacted. Values persisted by the trade journal are needed in full to
+//! reconcile trades and are never altered.
+
+use anchor_client::solana_sdk::{hash::hash, pubkey::Pubkey};
+
+/// Which sensitive values are redacted in log output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedactionPolicy {
+    /// Whether SOL and token amounts are rounded to their order of magnitude
+    pub redact_amounts: bool,
+    /// Whether wallets and their token accounts are truncated
+    pub redact_wallets: bool,
+    /// Whether mints are replaced by a short hash
+    pub hash_mints: bool,
+}
+
+impl RedactionPolicy {
+    /// Policy redacting every kind of sensitive value
+    pub const ALL: Self = Self {
+        redact_amounts: true,
+        redact_wallets: true,
+        hash_mints: true,
+    };
+
+    /// Renders a wallet or token account for logging
+    ///
+    /// # Arguments
+    ///
+    /// * `wallet` - Public key of the wallet or token account
+    ///
+    /// # Returns
+    ///
+    /// Returns the address, truncated if wallets are redacted
+    pub fn wallet(&self, wallet: &Pubkey) -> String {
+        let address = wallet.to_string();
+        if !self.redact_wallets || address.len() <= 8 {
+            return address;
+        }
+        format!("{}..{}", &address[..4], &address[address.len() - 4..])
+    }
+
+    /// Renders a token mint for logging
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the address, or a short hash of it if mints are hashed
+    pub fn mint(&self, mint: &Pubkey) -> String {
+        if !self.hash_mints {
+            return mint.to_string();
+        }
+        let digest = hash(mint.as_ref()).to_bytes();
+        let hex: String = digest[..6]
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        format!("mint#{}", hex)
+    }
+
+    /// Renders a SOL or token amount for logging
+    ///
+    /// # Arguments
+    ///
+    /// * `amount` - Amount in lamports or token base units
+    ///
+    /// # Returns
+    ///
+    /// Returns the amount, or its order of magnitude if amounts are redacted
+    pub fn amount(&self, amount: u64) -> String {
+        if !self.redact_amounts || amount == 0 {
+            return amount.to_string();
+        }
+        format!("~1e{}", amount.ilog10())
+    }
+}
+
+/// Collects the events emitted while a closure runs, for asserting on log output in tests
+#[cfg(test)]
+pub(crate) mod capture {
+    use std::sync::{Arc, Mutex};
+    use tracing::{
+        field::{Field, Visit},
+        span, Event, Metadata, Subscriber,
+    };
+
+    /// Subscriber recording every event as its formatted fields
+    #[derive(Default)]
+    struct CaptureSubscriber {
+        events: Arc<Mutex<Vec<String>>>,
+    }
+
+    /// Formats the fields of an event as `name=value` pairs
+    struct FieldWriter<'a>(&'a mut String);
+
+    impl Visit for FieldWriter<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            if !self.0.is_empty() {
+                self.0.push(' ');
+            }
+            self.0.push_str(&format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    impl Subscriber for CaptureSubscriber {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _span: &span::Attributes<'_>) -> span::Id {
+            span::Id::from_u64(1)
+        }
+
+        fn record(&self, _span: &span::Id, _values: &span::Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &span::Id, _follows: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut line = String::new();
+            event.record(&mut FieldWriter(&mut line));
+            self.events.lock().unwrap().push(line);
+        }
+
+        fn enter(&self, _span: &span::Id) {}
+
+        fn exit(&self, _span: &span::Id) {}
+    }
+
+    /// Runs a closure and returns the events it emitted on the current thread
+    pub(crate) fn capture_events(f: impl FnOnce()) -> Vec<String> {
+        let subscriber = CaptureSubscriber::default();
+        let events = subscriber.events.clone();
+        tracing::subscriber::with_default(subscriber, f);
+        let events = events.lock().unwrap().clone();
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn emit(policy: &RedactionPolicy, wallet: &Pubkey, mint: &Pubkey, amount: u64) -> String {
+        capture::capture_events(|| {
+            tracing::info!(
+                wallet = %policy.wallet(wallet),
+                mint = %policy.mint(mint),
+                amount = %policy.amount(amount),
+                "Trade"
+            );
+        })
+        .remove(0)
+    }
+
+    #[test]
+    fn test_redaction_policies() {
+        let wallet = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let address = wallet.to_string();
+
+        // Nothing is redacted by default
+        let line = emit(&RedactionPolicy::default(), &wallet, &mint, 1_234_567_890);
+        assert!(line.contains(&format!("wallet={}", address)));
+        assert!(line.contains(&format!("mint={}", mint)));
+        assert!(line.contains("amount=1234567890"));
+
+        // Each setting only affects its own kind of value
+        let policy = RedactionPolicy {
+            redact_wallets: true,
+            ..RedactionPolicy::default()
+        };
+        let line = emit(&policy, &wallet, &mint, 1_234_567_890);
+        assert!(line.contains(&format!(
+            "wallet={}..{}",
+            &address[..4],
+            &address[address.len() - 4..]
+        )));
+        assert!(!line.contains(&address));
+        assert!(line.contains(&format!("mint={}", mint)));
+
+        let policy = RedactionPolicy {
+            hash_mints: true,
+            ..RedactionPolicy::default()
+        };
+        let line = emit(&policy, &wallet, &mint, 1_234_567_890);
+        assert!(line.contains("mint=mint#"));
+        assert!(!line.contains(&mint.to_string()));
+        assert!(line.contains("amount=1234567890"));
+
+        let line = emit(&RedactionPolicy::ALL, &wallet, &mint, 1_234_567_890);
+        assert!(line.contains("amount=~1e9"));
+        assert!(!line.contains(&address));
+        assert!(!line.contains(&mint.to_string()));
+    }
+
+    #[test]
+    fn test_redacted_values_correlate() {
+        let policy = RedactionPolicy::ALL;
+        let mint = Pubkey::new_unique();
+
+        // The same mint always hashes the same, and different mints differ
+        assert_eq!(policy.mint(&mint), policy.mint(&mint));
+        assert_ne!(policy.mint(&mint), policy.mint(&Pubkey::new_unique()));
+        assert_eq!(policy.mint(&mint).len(), "mint#".len() + 12);
+
+        assert_eq!(policy.amount(0), "0");
+        assert_eq!(policy.amount(9), "~1e0");
+        assert_eq!(policy.amount(10), "~1e1");
+        assert_eq!(policy.amount(u64::MAX), "~1e19");
+    }
+}
@@ -0,0 +1,742 @@
+//! Coalesced refreshing of many watched bonding curves
+//!
+//! Polling each watched mint on its own timer makes RPC load arrive in bursts. A single
+//! [`RefreshScheduler`] instead collects every mint's staleness budget and, on each tick,
+//! refreshes the mints closest to going stale with one `getMultipleAccounts` call:
+//!
+//! - `CurveCache`: Latest bonding curve state of each watched mint, shared with readers.
+//! - `CachedCurve`: A cached bonding curve, when it was fetched and the slot it was observed at.
+//! - `RefreshScheduler`: Decides which mints to refresh, fetches them and notifies subscribers.
+//!
+//! Mints due within the next tick are pulled forward so work is spread evenly across ticks
+//! instead of piling up when many mints share a staleness budget. One-off priority refreshes
+//! jump the queue but may only fill half of each batch, so regular refreshes never starve.
+//!
+//! Polling alone leaves the cache up to a staleness budget behind the chain. For mints that
+//! need more, [`RefreshScheduler::stream_until_cancelled`] also writes every websocket account
+//! notification into the cache the moment it arrives. Entries carry the slot they were observed
+//! at, and older observations never overwrite newer ones, so a trade plan built from the cache
+//! can tell when the curve has moved since (see [`crate::plan::TradePlan`]).
+
+use crate::{
+    accounts::BondingCurveAccount, cancel::CancellationToken, error::ClientError, shed::Feature,
+    PumpFun,
+};
+use anchor_client::solana_client::{
+    nonblocking::pubsub_client::PubsubClient, rpc_config::RpcAccountInfoConfig,
+};
+use borsh::BorshDeserialize;
+use futures::{
+    channel::mpsc,
+    future::{select, Either},
+    StreamExt,
+};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Maximum number of accounts per `getMultipleAccounts` request
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// A cached bonding curve, when it was fetched and the slot it was observed at
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedCurve {
+    /// Bonding curve state, or None if the account does not exist
+    pub account: Option<BondingCurveAccount>,
+    /// When the state was fetched
+    pub fetched_at: Instant,
+    /// Slot the state was observed at, None if unknown
+    pub slot: Option<u64>,
+}
+
+/// Latest bonding curve state of each watched mint
+#[derive(Debug, Default)]
+pub struct CurveCache {
+    /// Cached curves by mint
+    entries: RwLock<HashMap<Pubkey, CachedCurve>>,
+}
+
+impl CurveCache {
+    /// Creates an empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Gets the cached curve of a mint
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    pub fn get(&self, mint: &Pubkey) -> Option<CachedCurve> {
+        self.entries.read().unwrap().get(mint).cloned()
+    }
+
+    /// Gets the slot the cached curve of a mint was observed at
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the slot, or None if the mint is not cached or its slot is unknown
+    pub fn slot(&self, mint: &Pubkey) -> Option<u64> {
+        self.entries
+            .read()
+            .unwrap()
+            .get(mint)
+            .and_then(|cached| cached.slot)
+    }
+
+    /// Stores the curve of a mint, observed at an unknown slot
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `account` - Fetched bonding curve state, None if the account does not exist
+    /// * `fetched_at` - When the state was fetched
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the state differs from the previously cached state
+    pub fn insert(
+        &self,
+        mint: Pubkey,
+        account: Option<BondingCurveAccount>,
+        fetched_at: Instant,
+    ) -> bool {
+        self.store(mint, account, None, fetched_at)
+    }
+
+    /// Stores the curve of a mint observed at a slot, unless a later observation is cached
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `account` - Observed bonding curve state, None if the account does not exist
+    /// * `slot` - Slot the state was observed at
+    /// * `fetched_at` - When the state was fetched
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the state was stored and differs from the previously cached state
+    pub fn insert_at_slot(
+        &self,
+        mint: Pubkey,
+        account: Option<BondingCurveAccount>,
+        slot: u64,
+        fetched_at: Instant,
+    ) -> bool {
+        self.store(mint, account, Some(slot), fetched_at)
+    }
+
+    /// Stores a curve, leaving the entry untouched if it was observed at a later slot
+    fn store(
+        &self,
+        mint: Pubkey,
+        account: Option<BondingCurveAccount>,
+        slot: Option<u64>,
+        fetched_at: Instant,
+    ) -> bool {
+        let mut entries = self.entries.write().unwrap();
+        let cached = entries.get(&mint);
+        if let (Some(slot), Some(cached_slot)) = (slot, cached.and_then(|cached| cached.slot)) {
+            if slot < cached_slot {
+                return false;
+            }
+        }
+
+        let changed = cached.is_none_or(|cached| cached.account != account);
+        entries.insert(
+            mint,
+            CachedCurve {
+                account,
+                fetched_at,
+                slot,
+            },
+        );
+        changed
+    }
+}
+
+/// A watched mint's refresh budget and subscribers
+#[derive(Debug)]
+struct Registration {
+    /// Maximum age of the cached state before it must be refreshed
+    max_staleness: Duration,
+    /// When the mint was last refreshed, None if never
+    last_refreshed: Option<Instant>,
+    /// Receivers of the mint's state when it changes
+    subscribers: Vec<mpsc::UnboundedSender<Option<BondingCurveAccount>>>,
+}
+
+impl Registration {
+    /// When the cached state goes stale, None if it has never been fetched
+    fn deadline(&self) -> Option<Instant> {
+        self.last_refreshed
+            .map(|refreshed| refreshed + self.max_staleness)
+    }
+}
+
+/// Mutable scheduler state
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// Watched mints
+    registrations: HashMap<Pubkey, Registration>,
+    /// Mints with a pending priority refresh, in request order
+    priority: VecDeque<Pubkey>,
+}
+
+/// Refreshes many watched bonding curves in coalesced batches
+#[derive(Debug)]
+pub struct RefreshScheduler {
+    /// Cache the refreshed curves are written to
+    cache: Arc<CurveCache>,
+    /// Time between ticks, used to pull upcoming refreshes forward
+    tick_interval: Duration,
+    /// Maximum number of mints refreshed per tick
+    batch_size: usize,
+    /// Watched mints and pending priority refreshes
+    state: Mutex<SchedulerState>,
+}
+
+impl RefreshScheduler {
+    /// Creates a scheduler writing into the given cache
+    ///
+    /// # Arguments
+    ///
+    /// * `cache` - Cache the refreshed curves are written to
+    /// * `tick_interval` - Time between ticks
+    pub fn new(cache: Arc<CurveCache>, tick_interval: Duration) -> Self {
+        Self {
+            cache,
+            tick_interval,
+            batch_size: MAX_BATCH_SIZE,
+            state: Mutex::new(SchedulerState::default()),
+        }
+    }
+
+    /// Overrides the maximum number of mints refreshed per tick
+    ///
+    /// # Arguments
+    ///
+    /// * `batch_size` - Mints per tick, between 1 and [`MAX_BATCH_SIZE`]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.clamp(1, MAX_BATCH_SIZE);
+        self
+    }
+
+    /// Gets the cache the refreshed curves are written to
+    pub fn cache(&self) -> &Arc<CurveCache> {
+        &self.cache
+    }
+
+    /// Watches a mint, or updates its staleness budget if already watched
+    ///
+    /// A newly watched mint is refreshed on the next tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `max_staleness` - Maximum age of the cached state before it must be refreshed
+    pub fn register(&self, mint: Pubkey, max_staleness: Duration) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .registrations
+            .entry(mint)
+            .and_modify(|registration| registration.max_staleness = max_staleness)
+            .or_insert(Registration {
+                max_staleness,
+                last_refreshed: None,
+                subscribers: Vec::new(),
+            });
+    }
+
+    /// Stops watching a mint, closing its subscriptions
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    pub fn unregister(&self, mint: &Pubkey) {
+        let mut state = self.state.lock().unwrap();
+        state.registrations.remove(mint);
+        state.priority.retain(|queued| queued != mint);
+    }
+
+    /// Subscribes to changes of a watched mint's state
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of a registered token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns a receiver of the mint's state each time a refresh finds it changed, or a
+    /// ClientError if the mint is not registered
+    pub fn subscribe(
+        &self,
+        mint: &Pubkey,
+    ) -> Result<mpsc::UnboundedReceiver<Option<BondingCurveAccount>>, ClientError> {
+        let mut state = self.state.lock().unwrap();
+        let registration = state
+            .registrations
+            .get_mut(mint)
+            .ok_or(ClientError::InvalidInput("Mint is not registered"))?;
+
+        let (sender, receiver) = mpsc::unbounded();
+        registration.subscribers.push(sender);
+        Ok(receiver)
+    }
+
+    /// Requests a one-off refresh of a watched mint ahead of its schedule
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of a registered token mint
+    pub fn request_priority_refresh(&self, mint: &Pubkey) {
+        let mut state = self.state.lock().unwrap();
+        if state.registrations.contains_key(mint) && !state.priority.contains(mint) {
+            state.priority.push_back(*mint);
+        }
+    }
+
+    /// Selects the mints to refresh on a tick
+    ///
+    /// Pending priority refreshes take up to half the batch. The rest is filled with mints that
+    /// have never been fetched, then mints ordered by when they go stale, including those going
+    /// stale before the next tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Time of the tick
+    ///
+    /// # Returns
+    ///
+    /// Returns up to the batch size of distinct mints to refresh
+    pub fn next_batch(&self, now: Instant) -> Vec<Pubkey> {
+        let mut state = self.state.lock().unwrap();
+        let priority_slots = self.batch_size.div_ceil(2);
+
+        let mut batch = Vec::with_capacity(self.batch_size);
+        let mut selected = HashSet::new();
+        while batch.len() < priority_slots {
+            let Some(mint) = state.priority.pop_front() else {
+                break;
+            };
+            if selected.insert(mint) {
+                batch.push(mint);
+            }
+        }
+
+        let horizon = now + self.tick_interval;
+        let mut due: Vec<(Option<Instant>, Pubkey)> = state
+            .registrations
+            .iter()
+            .filter(|(mint, _)| !selected.contains(*mint))
+            .map(|(mint, registration)| (registration.deadline(), *mint))
+            .filter(|(deadline, _)| deadline.is_none_or(|deadline| deadline <= horizon))
+            .collect();
+        // Never-fetched mints (None) sort first, then the stalest
+        due.sort();
+
+        batch.extend(
+            due.into_iter()
+                .take(self.batch_size - batch.len())
+                .map(|(_, mint)| mint),
+        );
+        batch
+    }
+
+    /// Records fetched curves, writing them to the cache and notifying subscribers on change
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - Each refreshed mint with its curve, None if the account does not exist
+    /// * `fetched_at` - When the curves were fetched
+    pub fn apply(
+        &self,
+        results: impl IntoIterator<Item = (Pubkey, Option<BondingCurveAccount>)>,
+        fetched_at: Instant,
+    ) {
+        self.record(results, None, fetched_at)
+    }
+
+    /// Records curves observed at a slot, notifying subscribers on change
+    ///
+    /// Curves older than the cached state are dropped, but still count as a refresh.
+    ///
+    /// # Arguments
+    ///
+    /// * `results` - Each observed mint with its curve, None if the account does not exist
+    /// * `slot` - Slot the curves were observed at
+    /// * `fetched_at` - When the curves were fetched
+    pub fn apply_at_slot(
+        &self,
+        results: impl IntoIterator<Item = (Pubkey, Option<BondingCurveAccount>)>,
+        slot: u64,
+        fetched_at: Instant,
+    ) {
+        self.record(results, Some(slot), fetched_at)
+    }
+
+    /// Writes curves to the cache and notifies subscribers of those that changed
+    fn record(
+        &self,
+        results: impl IntoIterator<Item = (Pubkey, Option<BondingCurveAccount>)>,
+        slot: Option<u64>,
+        fetched_at: Instant,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        for (mint, account) in results {
+            let Some(registration) = state.registrations.get_mut(&mint) else {
+                continue;
+            };
+            registration.last_refreshed = Some(fetched_at);
+
+            if self.cache.store(mint, account.clone(), slot, fetched_at) {
+                registration
+                    .subscribers
+                    .retain(|subscriber| subscriber.unbounded_send(account.clone()).is_ok());
+            }
+        }
+    }
+
+    /// Refreshes the next batch of mints
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to fetch the bonding curves
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of mints refreshed, or a ClientError if the batch could not be
+    /// fetched or background refreshes are shed under RPC pressure, in which case the mints
+    /// stay due for the next tick
+    pub fn tick(&self, client: &PumpFun<'_>) -> Result<usize, ClientError> {
+        client.check_shed(Feature::BackgroundRefresh)?;
+        let batch = self.next_batch(client.clock.now());
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        let pdas: Vec<Pubkey> = batch
+            .iter()
+            .map(|mint| {
+                PumpFun::get_bonding_curve_pda_with_seeds(&client.seeds, mint).unwrap_or_default()
+            })
+            .collect();
+        let response = client
+            .rpc
+            .get_multiple_accounts_with_commitment(&pdas, client.rpc.commitment())
+            .map_err(ClientError::SolanaClientError)?;
+        let fetched_at = client.clock.now();
+
+        let results = batch.iter().zip(response.value).map(|(mint, account)| {
+            let curve =
+                account.and_then(|account| BondingCurveAccount::try_from_slice(&account.data).ok());
+            (*mint, curve)
+        });
+        self.apply_at_slot(results, response.context.slot, fetched_at);
+
+        Ok(batch.len())
+    }
+
+    /// Streams a watched mint's account notifications into the cache until cancelled
+    ///
+    /// Every notification is written to the cache with its slot as soon as it arrives and
+    /// notifies subscribers like a refresh, so quotes built from the cache are invalidated
+    /// without waiting for the staleness budget. Polling continues alongside as a fallback.
+    /// However the stream ends, the subscription is unsubscribed and the websocket connection
+    /// closed before returning.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client whose cluster and commitment the subscription uses
+    /// * `mint` - Public key of a registered token mint
+    /// * `cancel` - Token that stops the stream when cancelled
+    ///
+    /// # Returns
+    ///
+    /// Returns once cancelled, or a ClientError if the mint is not registered, the subscription
+    /// fails or it ends unexpectedly
+    pub async fn stream_until_cancelled(
+        &self,
+        client: &PumpFun<'_>,
+        mint: &Pubkey,
+        cancel: &CancellationToken,
+    ) -> Result<(), ClientError> {
+        if !self.state.lock().unwrap().registrations.contains_key(mint) {
+            return Err(ClientError::InvalidInput("Mint is not registered"));
+        }
+        if cancel.is_cancelled() {
+            return Ok(());
+        }
+
+        let bonding_curve_pda = PumpFun::get_bonding_curve_pda_with_seeds(&client.seeds, mint)
+            .ok_or(ClientError::BondingCurveNotFound)?;
+        let pubsub = PubsubClient::new(client.cluster.ws_url())
+            .await
+            .map_err(ClientError::PubsubClientError)?;
+
+        let result = async {
+            let (mut stream, unsubscribe) = pubsub
+                .account_subscribe(
+                    &bonding_curve_pda,
+                    Some(RpcAccountInfoConfig {
+                        commitment: Some(client.rpc.commitment()),
+                        ..RpcAccountInfoConfig::default()
+                    }),
+                )
+                .await
+                .map_err(ClientError::PubsubClientError)?;
+
+            let mut outcome = Err(ClientError::SubscriptionClosed);
+            let mut cancelled = std::pin::pin!(cancel.cancelled());
+            loop {
+                let response = match select(stream.next(), cancelled.as_mut()).await {
+                    Either::Left((Some(response), _)) => response,
+                    Either::Left((None, _)) => break,
+                    Either::Right(_) => {
+                        outcome = Ok(());
+                        break;
+                    }
+                };
+
+                let curve = response
+                    .value
+                    .decode::<Account>()
+                    .and_then(|account| BondingCurveAccount::try_from_slice(&account.data).ok());
+                self.apply_at_slot([(*mint, curve)], response.context.slot, client.clock.now());
+            }
+
+            drop(stream);
+            unsubscribe().await;
+            outcome
+        }
+        .await;
+
+        let _ = pubsub.shutdown().await;
+        result
+    }
+
+    /// Refreshes batches on every tick until the future is dropped
+    ///
+    /// Failed ticks are skipped; the affected mints stay due and are retried on the next tick.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to fetch the bonding curves
+    pub async fn run(&self, client: &PumpFun<'_>) {
+        self.run_until_cancelled(client, &CancellationToken::new())
+            .await
+    }
+
+    /// Refreshes batches on every tick until the token is cancelled
+    ///
+    /// Returns between ticks once cancelled; a tick already in progress completes first.
+    /// Subscribers keep their receivers, which yield no further updates, and can stop listening
+    /// at any time by dropping them.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to fetch the bonding curves
+    /// * `cancel` - Token that stops the refreshes when cancelled
+    pub async fn run_until_cancelled(&self, client: &PumpFun<'_>, cancel: &CancellationToken) {
+        let mut interval = tokio::time::interval(self.tick_interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut cancelled = std::pin::pin!(cancel.cancelled());
+        loop {
+            let tick = std::pin::pin!(interval.tick());
+            if let Either::Right(_) = select(tick, cancelled.as_mut()).await {
+                return;
+            }
+            let _ = self.tick(client);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_curve(virtual_sol_reserves: u64) -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            virtual_sol_reserves,
+            793_100_000_000_000,
+            virtual_sol_reserves - 30_000_000_000,
+            1_000_000_000_000_000,
+            false,
+        )
+    }
+
+    fn get_scheduler(tick_interval: Duration) -> RefreshScheduler {
+        RefreshScheduler::new(Arc::new(CurveCache::new()), tick_interval)
+    }
+
+    #[test]
+    fn test_batches_coalesce_due_mints() {
+        let scheduler = get_scheduler(Duration::from_millis(100));
+        let mints: Vec<Pubkey> = (0..250).map(|_| Pubkey::new_unique()).collect();
+        for mint in &mints {
+            scheduler.register(*mint, Duration::from_secs(5));
+        }
+
+        // New mints are fetched in full batches of distinct keys
+        let start = Instant::now();
+        let mut fetched = HashSet::new();
+        for expected in [100, 100, 50] {
+            let batch = scheduler.next_batch(start);
+            assert_eq!(batch.len(), expected);
+            assert_eq!(batch.iter().collect::<HashSet<_>>().len(), expected);
+            fetched.extend(batch.iter().copied());
+            scheduler.apply(batch.into_iter().map(|mint| (mint, None)), start);
+        }
+        assert_eq!(fetched.len(), 250);
+
+        // Nothing is due until the staleness budget is almost spent
+        assert!(scheduler
+            .next_batch(start + Duration::from_secs(1))
+            .is_empty());
+        let almost = start + Duration::from_secs(5) - Duration::from_millis(50);
+        assert_eq!(scheduler.next_batch(almost).len(), 100);
+    }
+
+    #[test]
+    fn test_no_mint_starves_under_priority_load() {
+        let scheduler = get_scheduler(Duration::from_millis(100)).with_batch_size(10);
+        let mints: Vec<Pubkey> = (0..100).map(|_| Pubkey::new_unique()).collect();
+        for mint in &mints {
+            scheduler.register(*mint, Duration::from_millis(500));
+        }
+
+        let start = Instant::now();
+        let mut last_refreshed: HashMap<Pubkey, Instant> = HashMap::new();
+        for tick in 0..200u32 {
+            let now = start + Duration::from_millis(100) * tick;
+            // A hot subset keeps asking for priority refreshes
+            for mint in &mints[..20] {
+                scheduler.request_priority_refresh(mint);
+            }
+
+            let batch = scheduler.next_batch(now);
+            assert!(batch.len() <= 10);
+            for mint in &batch {
+                last_refreshed.insert(*mint, now);
+            }
+            scheduler.apply(batch.into_iter().map(|mint| (mint, None)), now);
+        }
+
+        // Every mint keeps being refreshed despite the priority traffic
+        let end = start + Duration::from_millis(100) * 199;
+        for mint in &mints {
+            let refreshed = last_refreshed[mint];
+            assert!(
+                end.duration_since(refreshed) <= Duration::from_secs(3),
+                "mint starved"
+            );
+        }
+    }
+
+    #[test]
+    fn test_subscribers_notified_only_on_change() {
+        let scheduler = get_scheduler(Duration::from_millis(100));
+        let mint = Pubkey::new_unique();
+        assert!(scheduler.subscribe(&mint).is_err());
+
+        scheduler.register(mint, Duration::from_secs(1));
+        let mut receiver = scheduler.subscribe(&mint).unwrap();
+
+        let now = Instant::now();
+        scheduler.apply([(mint, Some(get_curve(30_000_000_000)))], now);
+        scheduler.apply([(mint, Some(get_curve(30_000_000_000)))], now);
+        scheduler.apply([(mint, Some(get_curve(31_000_000_000)))], now);
+        drop(scheduler);
+
+        let updates: Vec<_> = futures::executor::block_on(receiver.by_ref().collect());
+        assert_eq!(
+            updates,
+            vec![
+                Some(get_curve(30_000_000_000)),
+                Some(get_curve(31_000_000_000))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_older_slots_never_overwrite_newer() {
+        let scheduler = get_scheduler(Duration::from_millis(100));
+        let mint = Pubkey::new_unique();
+        scheduler.register(mint, Duration::from_secs(1));
+        let mut receiver = scheduler.subscribe(&mint).unwrap();
+
+        // A notification lands before a slower poll that read an earlier slot
+        let now = Instant::now();
+        scheduler.apply_at_slot([(mint, Some(get_curve(31_000_000_000)))], 120, now);
+        scheduler.apply_at_slot([(mint, Some(get_curve(30_000_000_000)))], 118, now);
+
+        let cached = scheduler.cache().get(&mint).unwrap();
+        assert_eq!(cached.account, Some(get_curve(31_000_000_000)));
+        assert_eq!(scheduler.cache().slot(&mint), Some(120));
+
+        scheduler.apply_at_slot([(mint, Some(get_curve(32_000_000_000)))], 121, now);
+        assert_eq!(scheduler.cache().slot(&mint), Some(121));
+        drop(scheduler);
+
+        let updates: Vec<_> = futures::executor::block_on(receiver.by_ref().collect());
+        assert_eq!(
+            updates,
+            vec![
+                Some(get_curve(31_000_000_000)),
+                Some(get_curve(32_000_000_000))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_priority_refresh_jumps_queue() {
+        let scheduler = get_scheduler(Duration::from_millis(100)).with_batch_size(4);
+        let mints: Vec<Pubkey> = (0..8).map(|_| Pubkey::new_unique()).collect();
+        let now = Instant::now();
+        for mint in &mints {
+            scheduler.register(*mint, Duration::from_secs(60));
+        }
+        scheduler.apply(mints.iter().map(|mint| (*mint, None)), now);
+
+        // Nothing is due, but the priority request is served on the next tick
+        scheduler.request_priority_refresh(&mints[3]);
+        scheduler.request_priority_refresh(&mints[3]);
+        assert_eq!(scheduler.next_batch(now), vec![mints[3]]);
+        assert!(scheduler.next_batch(now).is_empty());
+
+        // Unregistered mints are ignored
+        scheduler.request_priority_refresh(&Pubkey::new_unique());
+        assert!(scheduler.next_batch(now).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_run_stops_when_cancelled() {
+        let payer = solana_sdk::signature::Keypair::new();
+        let client = PumpFun::new(anchor_client::Cluster::Devnet, &payer, None, None);
+        let scheduler = get_scheduler(Duration::from_millis(10));
+        let cancel = CancellationToken::new();
+
+        let canceller = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.cancel();
+        });
+
+        // No mints are registered, so ticks never reach the network
+        tokio::time::timeout(
+            Duration::from_secs(5),
+            scheduler.run_until_cancelled(&client, &cancel),
+        )
+        .await
+        .unwrap();
+    }
+}
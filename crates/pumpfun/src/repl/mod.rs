@@ -0,0 +1,756 @@
+//! Interactive command session over one client
+//!
+//! Each invocation of a one-shot command pays for connecting and reading the global account
+//! again. A [`Session`] keeps one client and its caches warm between commands, and adds commands
+//! that only make sense over a session: a watch list of mints, the payer's positions in them and
+//! the tail of a trade journal. The `pumpfun` binary runs it interactively with
+//! `pumpfun repl`, or runs a file of commands with `pumpfun repl --script <file>`.
+//!
+//! The session only uses the public API of the crate, as any downstream crate would.
+//!
+//! - `Command`: A parsed command line.
+//! - `Session`: A client with its watch list and journal, executing commands.
+//! - `ScriptError`: A script command that failed, with its line number.
+//!
+//! Amounts are integers in base units: lamports for SOL and raw token units for tokens.
+
+use crate::{
+    accounts::GlobalAccount,
+    error::ClientError,
+    journal::{JournalEntry, TradeJournal},
+    params::{BuyParams, SellParams},
+    portfolio::Holding,
+    PumpFun,
+};
+use anchor_client::solana_sdk::{program_pack::Pack, pubkey::Pubkey, signer::Signer};
+use anchor_spl::{
+    associated_token::get_associated_token_address_with_program_id,
+    token::spl_token::state::Account as TokenAccount,
+};
+use std::{collections::BTreeSet, fmt};
+
+/// Number of journal entries shown by `journal tail` without a count
+pub const DEFAULT_JOURNAL_TAIL: usize = 10;
+
+/// Verbs accepted at the start of a command line
+pub const VERBS: [&str; 12] = [
+    "buy",
+    "curve",
+    "exit",
+    "global",
+    "help",
+    "journal",
+    "positions",
+    "quit",
+    "quote",
+    "sell",
+    "usage",
+    "watch",
+];
+
+/// Usage of every command, shown by `help`
+pub const HELP: &str = "\
+buy <mint> <lamports> [slippage_bps]     Buy tokens with SOL
+sell <mint> [amount|all] [slippage_bps]  Sell tokens, the whole balance by default
+quote buy <mint> <lamports>              Quote the tokens received for SOL
+quote sell <mint> <amount>               Quote the SOL received for tokens
+curve <mint>                             Summarize a bonding curve
+global                                   Refresh and show the global account
+usage                                    Show the RPC requests sent so far
+watch add|remove <mint>                  Add or remove a mint from the watch list (session)
+watch list                               List the watched mints (session)
+positions                                Value the payer's holdings of watched mints (session)
+journal tail [count]                     Show the last journal entries (session)
+help                                     Show this help
+exit, quit                               End the session";
+
+/// A parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Shows the usage of every command
+    Help,
+    /// Ends the session
+    Exit,
+    /// Refreshes and shows the global account
+    Global,
+    /// Summarizes a bonding curve
+    Curve(Pubkey),
+    /// Quotes the tokens received for an amount of SOL
+    QuoteBuy {
+        /// Public key of the token mint
+        mint: Pubkey,
+        /// Amount of SOL to spend in lamports
+        sol_amount: u64,
+    },
+    /// Quotes the SOL received for an amount of tokens
+    QuoteSell {
+        /// Public key of the token mint
+        mint: Pubkey,
+        /// Amount of tokens to sell in base units
+        token_amount: u64,
+    },
+    /// Buys tokens with SOL
+    Buy {
+        /// Public key of the token mint
+        mint: Pubkey,
+        /// Amount of SOL to spend in lamports
+        sol_amount: u64,
+        /// Maximum slippage in basis points, None for the default
+        slippage_basis_points: Option<u64>,
+    },
+    /// Sells tokens for SOL
+    Sell {
+        /// Public key of the token mint
+        mint: Pubkey,
+        /// Amount of tokens to sell in base units, None to sell the entire balance
+        token_amount: Option<u64>,
+        /// Maximum slippage in basis points, None for the default
+        slippage_basis_points: Option<u64>,
+    },
+    /// Shows the RPC requests sent so far
+    Usage,
+    /// Adds a mint to the watch list
+    WatchAdd(Pubkey),
+    /// Removes a mint from the watch list
+    WatchRemove(Pubkey),
+    /// Lists the watched mints
+    WatchList,
+    /// Values the payer's holdings of the watched mints
+    Positions,
+    /// Shows the last entries of the journal
+    JournalTail(usize),
+}
+
+impl Command {
+    /// Parses a command line
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Command line, with words separated by whitespace
+    ///
+    /// # Returns
+    ///
+    /// Returns the command, None if the line is blank or a `#` comment, or a
+    /// ClientError::InvalidInput describing what is wrong with the line
+    pub fn parse(line: &str) -> Result<Option<Self>, ClientError> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let command = match words.as_slice() {
+            ["help"] => Self::Help,
+            ["exit"] | ["quit"] => Self::Exit,
+            ["global"] => Self::Global,
+            ["usage"] => Self::Usage,
+            ["curve", mint] => Self::Curve(parse_mint(mint)?),
+            ["quote", "buy", mint, sol_amount] => Self::QuoteBuy {
+                mint: parse_mint(mint)?,
+                sol_amount: parse_amount(sol_amount)?,
+            },
+            ["quote", "sell", mint, token_amount] => Self::QuoteSell {
+                mint: parse_mint(mint)?,
+                token_amount: parse_amount(token_amount)?,
+            },
+            ["buy", mint, sol_amount, rest @ ..] if rest.len() <= 1 => Self::Buy {
+                mint: parse_mint(mint)?,
+                sol_amount: parse_amount(sol_amount)?,
+                slippage_basis_points: rest.first().map(|bps| parse_amount(bps)).transpose()?,
+            },
+            ["sell", mint, rest @ ..] if rest.len() <= 2 => Self::Sell {
+                mint: parse_mint(mint)?,
+                token_amount: match rest.first() {
+                    None | Some(&"all") => None,
+                    Some(amount) => Some(parse_amount(amount)?),
+                },
+                slippage_basis_points: rest.get(1).map(|bps| parse_amount(bps)).transpose()?,
+            },
+            ["watch", "add", mint] => Self::WatchAdd(parse_mint(mint)?),
+            ["watch", "remove", mint] => Self::WatchRemove(parse_mint(mint)?),
+            ["watch", "list"] => Self::WatchList,
+            ["positions"] => Self::Positions,
+            ["journal", "tail"] => Self::JournalTail(DEFAULT_JOURNAL_TAIL),
+            ["journal", "tail", count] => Self::JournalTail(
+                count
+                    .parse()
+                    .map_err(|_| ClientError::InvalidInput("Invalid count"))?,
+            ),
+            [verb, ..] if VERBS.contains(verb) => {
+                return Err(ClientError::InvalidInput(
+                    "Invalid arguments, see `help` for usage",
+                ))
+            }
+            _ => return Err(ClientError::InvalidInput("Unknown command, see `help`")),
+        };
+        Ok(Some(command))
+    }
+
+    /// Returns whether the command relies on the state of a session, such as the watch list,
+    /// and is pointless as a one-shot command
+    pub fn is_session_only(&self) -> bool {
+        matches!(
+            self,
+            Self::WatchAdd(_)
+                | Self::WatchRemove(_)
+                | Self::WatchList
+                | Self::Positions
+                | Self::JournalTail(_)
+        )
+    }
+
+    /// Returns whether the command signs with or reads the holdings of the payer, and so needs
+    /// the payer's keypair rather than a throwaway one
+    pub fn needs_keypair(&self) -> bool {
+        matches!(self, Self::Buy { .. } | Self::Sell { .. } | Self::Positions)
+    }
+}
+
+/// Parses a mint public key argument
+fn parse_mint(word: &str) -> Result<Pubkey, ClientError> {
+    word.parse()
+        .map_err(|_| ClientError::InvalidInput("Invalid mint public key"))
+}
+
+/// Parses an integer amount argument
+fn parse_amount(word: &str) -> Result<u64, ClientError> {
+    word.replace('_', "")
+        .parse()
+        .map_err(|_| ClientError::InvalidInput("Invalid amount, expected an integer"))
+}
+
+/// A script command that failed
+#[derive(Debug)]
+pub struct ScriptError {
+    /// Line of the script the command is on, starting at 1
+    pub line: usize,
+    /// Command line as written in the script
+    pub command: String,
+    /// Error the command failed with
+    pub error: ClientError,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {} `{}`: {}", self.line, self.command, self.error)
+    }
+}
+
+impl std::error::Error for ScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A client with its watch list and journal, executing commands
+pub struct Session<'c, 'a> {
+    /// Client every command runs against
+    client: &'c PumpFun<'a>,
+    /// Mints added to the watch list, in order of their public keys
+    watched: BTreeSet<Pubkey>,
+    /// Journal shown by `journal tail`, if one was opened
+    journal: Option<TradeJournal>,
+    /// Global account as of the last warm up or `global` command
+    global: Option<GlobalAccount>,
+}
+
+impl<'c, 'a> Session<'c, 'a> {
+    /// Creates a session with an empty watch list and no journal
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client every command runs against
+    pub fn new(client: &'c PumpFun<'a>) -> Self {
+        Self {
+            client,
+            watched: BTreeSet::new(),
+            journal: None,
+            global: None,
+        }
+    }
+
+    /// Sets the journal shown by `journal tail`
+    ///
+    /// # Arguments
+    ///
+    /// * `journal` - Journal to show, usually the one a bot records its trades in
+    pub fn with_journal(mut self, journal: TradeJournal) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Returns the client commands run against
+    pub fn client(&self) -> &'c PumpFun<'a> {
+        self.client
+    }
+
+    /// Returns the watched mints, in order of their public keys
+    pub fn watched(&self) -> &BTreeSet<Pubkey> {
+        &self.watched
+    }
+
+    /// Returns the global account as of the last warm up or `global` command
+    pub fn global(&self) -> Option<&GlobalAccount> {
+        self.global.as_ref()
+    }
+
+    /// Fills the client's caches ahead of the first command
+    ///
+    /// Reads the global account, detects the program version and looks up the token program of
+    /// every watched mint, all of which the client caches for later trades.
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok once the caches are filled, or a ClientError if a lookup fails
+    pub fn warm_up(&mut self) -> Result<(), ClientError> {
+        self.global = Some(self.client.get_global_account()?);
+        self.client.get_program_version()?;
+        for mint in &self.watched {
+            self.client.get_token_program(mint)?;
+        }
+        Ok(())
+    }
+
+    /// Completes the last word of a command line
+    ///
+    /// Verbs and subcommands are completed from the commands the session knows, and mint
+    /// arguments from the watch list.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - Command line typed so far
+    ///
+    /// # Returns
+    ///
+    /// Returns every word the last word of the line may complete to, in alphabetical order
+    pub fn complete(&self, line: &str) -> Vec<String> {
+        let mut words: Vec<&str> = line.split_whitespace().collect();
+        let partial = if line.ends_with(char::is_whitespace) || words.is_empty() {
+            ""
+        } else {
+            words.pop().unwrap_or_default()
+        };
+
+        let watched = || -> Vec<String> { self.watched.iter().map(Pubkey::to_string).collect() };
+        let candidates: Vec<String> = match words.as_slice() {
+            [] => VERBS.iter().map(|verb| verb.to_string()).collect(),
+            ["quote"] => vec!["buy".to_string(), "sell".to_string()],
+            ["watch"] => vec!["add".to_string(), "list".to_string(), "remove".to_string()],
+            ["journal"] => vec!["tail".to_string()],
+            ["buy"] | ["sell"] | ["curve"] | ["quote", "buy" | "sell"] => watched(),
+            ["watch", "remove"] => watched(),
+            ["sell", _] => vec!["all".to_string()],
+            _ => Vec::new(),
+        };
+
+        let mut completions: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| candidate.starts_with(partial))
+            .collect();
+        completions.sort();
+        completions
+    }
+
+    /// Executes a command
+    ///
+    /// # Arguments
+    ///
+    /// * `command` - Command to execute
+    ///
+    /// # Returns
+    ///
+    /// Returns the output of the command, or a ClientError if it fails. `exit` returns an empty
+    /// output, ending the session is left to the caller
+    pub async fn execute(&mut self, command: &Command) -> Result<String, ClientError> {
+        let client = self.client;
+        let output = match command {
+            Command::Help => HELP.to_string(),
+            Command::Exit => String::new(),
+            Command::Global => {
+                let global = client.get_global_account()?;
+                let output = format!(
+                    "authority: {}\nfee recipient: {}\nfee: {} bps\ninitial virtual reserves: \
+                     {} tokens, {} lamports\ninitial real token reserves: {}\ntoken supply: {}",
+                    global.authority,
+                    global.fee_recipient,
+                    global.total_fee_bps(),
+                    global.initial_virtual_token_reserves,
+                    global.initial_virtual_sol_reserves,
+                    global.initial_real_token_reserves,
+                    global.token_total_supply
+                );
+                self.global = Some(global);
+                output
+            }
+            Command::Curve(mint) => {
+                let summary = client.get_curve_summary(mint)?;
+                let mut output = format!(
+                    "mint: {}\nprice: {} lamports per token unit\nmarket cap: {} lamports\n\
+                     real SOL reserves: {} lamports\nprogress: {:.2}%\ncomplete: {}",
+                    summary.mint,
+                    summary.price,
+                    summary.market_cap_sol,
+                    summary.real_sol_reserves,
+                    summary.progress_pct,
+                    summary.complete
+                );
+                if let Some(estimate) = summary.graduation_eta {
+                    output.push_str(&format!(
+                        "\ngraduation in {}s, {} lamports to go, confidence {:.2}",
+                        estimate.eta.as_secs(),
+                        estimate.sol_remaining,
+                        estimate.confidence
+                    ));
+                }
+                output
+            }
+            Command::QuoteBuy { mint, sol_amount } => format!(
+                "{} lamports buy {} tokens",
+                sol_amount,
+                client.quote_buy(mint, *sol_amount)?
+            ),
+            Command::QuoteSell { mint, token_amount } => format!(
+                "{} tokens sell for {} lamports",
+                token_amount,
+                client.quote_sell(mint, *token_amount)?
+            ),
+            Command::Buy {
+                mint,
+                sol_amount,
+                slippage_basis_points,
+            } => {
+                let mut params = BuyParams::new(*mint, *sol_amount);
+                if let Some(basis_points) = slippage_basis_points {
+                    params = params.with_slippage_bps(*basis_points);
+                }
+                format!("Buy confirmed: {}", client.buy_with(params).await?)
+            }
+            Command::Sell {
+                mint,
+                token_amount,
+                slippage_basis_points,
+            } => {
+                let mut params = SellParams::new(*mint);
+                if let Some(token_amount) = token_amount {
+                    params = params.with_amount(*token_amount);
+                }
+                if let Some(basis_points) = slippage_basis_points {
+                    params = params.with_slippage_bps(*basis_points);
+                }
+                format!("Sell confirmed: {}", client.sell_with(params).await?)
+            }
+            Command::Usage => {
+                let report = client.rpc_usage();
+                let mut output = format!(
+                    "{} requests, {} credits",
+                    report.total_requests, report.total_credits
+                );
+                for operation in &report.operations {
+                    output.push_str(&format!(
+                        "\n  {:?}: {} requests, {} credits",
+                        operation.operation, operation.total_requests, operation.credits
+                    ));
+                }
+                output
+            }
+            Command::WatchAdd(mint) => {
+                // Looking up the token program now saves the lookup on the first trade
+                client.get_token_program(mint)?;
+                if self.watched.insert(*mint) {
+                    format!("Watching {}", mint)
+                } else {
+                    format!("Already watching {}", mint)
+                }
+            }
+            Command::WatchRemove(mint) => {
+                if self.watched.remove(mint) {
+                    format!("Stopped watching {}", mint)
+                } else {
+                    format!("Not watching {}", mint)
+                }
+            }
+            Command::WatchList => self
+                .watched
+                .iter()
+                .map(Pubkey::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Command::Positions => self.positions()?,
+            Command::JournalTail(count) => {
+                let journal = self.journal.as_ref().ok_or(ClientError::InvalidInput(
+                    "No journal opened for this session",
+                ))?;
+                let entries = journal.entries();
+                entries[entries.len().saturating_sub(*count)..]
+                    .iter()
+                    .map(format_entry)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        };
+        Ok(output)
+    }
+
+    /// Values the payer's holdings of the watched mints
+    fn positions(&self) -> Result<String, ClientError> {
+        if self.watched.is_empty() {
+            return Ok("No watched mints, add one with `watch add <mint>`".to_string());
+        }
+
+        let owner = self.client.signer().pubkey();
+        let mints: Vec<Pubkey> = self.watched.iter().copied().collect();
+        let atas = mints
+            .iter()
+            .map(|mint| {
+                let token_program = self.client.get_token_program(mint)?;
+                Ok(get_associated_token_address_with_program_id(
+                    &owner,
+                    mint,
+                    &token_program,
+                ))
+            })
+            .collect::<Result<Vec<Pubkey>, ClientError>>()?;
+        let accounts = self
+            .client
+            .rpc
+            .get_multiple_accounts(&atas)
+            .map_err(ClientError::SolanaClientError)?;
+
+        // Token-2022 accounts share the base layout, followed by extensions
+        let holdings: Vec<Holding> = mints
+            .iter()
+            .zip(&accounts)
+            .map(|(mint, account)| {
+                let amount = account
+                    .as_ref()
+                    .and_then(|account| account.data.get(..TokenAccount::LEN))
+                    .and_then(|data| TokenAccount::unpack_from_slice(data).ok())
+                    .map_or(0, |token_account| token_account.amount);
+                Holding::new(*mint, amount)
+            })
+            .collect();
+
+        let valuation = self.client.value_portfolio(&holdings)?;
+        let mut output = format!(
+            "slot {}{}",
+            valuation.snapshot_slot,
+            if valuation.consistent {
+                ""
+            } else {
+                " (accounts read at different slots)"
+            }
+        );
+        for holding in &valuation.holdings {
+            output.push_str(&format!(
+                "\n{}: {} tokens, {}",
+                holding.mint,
+                holding.amount,
+                holding
+                    .value_sol
+                    .map_or("no curve value".to_string(), |value| {
+                        format!("{} lamports", value)
+                    })
+            ));
+        }
+        output.push_str(&format!("\ntotal: {} lamports", valuation.total_value_sol));
+        Ok(output)
+    }
+
+    /// Runs a script of commands, one per line
+    ///
+    /// Blank lines and `#` comments are skipped, and `exit` ends the script early. The script
+    /// stops at the first command that fails, so a sequence never continues from an unexpected
+    /// state.
+    ///
+    /// # Arguments
+    ///
+    /// * `script` - Commands, one per line
+    /// * `on_output` - Called with each command line and its output, as they complete
+    ///
+    /// # Returns
+    ///
+    /// Returns the number of commands executed, or a ScriptError for the first command that
+    /// failed to parse or execute
+    pub async fn run_script(
+        &mut self,
+        script: &str,
+        mut on_output: impl FnMut(&str, &str),
+    ) -> Result<usize, ScriptError> {
+        let mut executed = 0;
+        for (index, line) in script.lines().enumerate() {
+            let failed = |error: ClientError| ScriptError {
+                line: index + 1,
+                command: line.trim().to_string(),
+                error,
+            };
+            let Some(command) = Command::parse(line).map_err(failed)? else {
+                continue;
+            };
+            if command == Command::Exit {
+                break;
+            }
+            let output = self.execute(&command).await.map_err(failed)?;
+            on_output(line.trim(), &output);
+            executed += 1;
+        }
+        Ok(executed)
+    }
+}
+
+/// Formats a journal entry on one line
+fn format_entry(entry: &JournalEntry) -> String {
+    format!(
+        "{} {:?} {} {} tokens, limit {} lamports, {:?}, {}",
+        entry.trade_id,
+        entry.side,
+        entry.mint,
+        entry.token_amount,
+        entry.sol_limit,
+        entry.state,
+        entry.signature
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::{solana_sdk::signature::Keypair, Cluster};
+
+    #[test]
+    fn test_parse_commands() {
+        let mint = Pubkey::new_unique();
+        let parse = |line: String| Command::parse(&line).unwrap();
+
+        assert_eq!(Command::parse("").unwrap(), None);
+        assert_eq!(Command::parse("  # comment").unwrap(), None);
+        assert_eq!(Command::parse("quit").unwrap(), Some(Command::Exit));
+        assert_eq!(
+            parse(format!("buy {} 1_000_000", mint)),
+            Some(Command::Buy {
+                mint,
+                sol_amount: 1_000_000,
+                slippage_basis_points: None
+            })
+        );
+        assert_eq!(
+            parse(format!("sell {} all 300", mint)),
+            Some(Command::Sell {
+                mint,
+                token_amount: None,
+                slippage_basis_points: Some(300)
+            })
+        );
+        assert_eq!(
+            parse(format!("sell {} 5", mint)),
+            Some(Command::Sell {
+                mint,
+                token_amount: Some(5),
+                slippage_basis_points: None
+            })
+        );
+        assert_eq!(
+            parse(format!("quote sell {} 7", mint)),
+            Some(Command::QuoteSell {
+                mint,
+                token_amount: 7
+            })
+        );
+        assert_eq!(
+            Command::parse("journal tail").unwrap(),
+            Some(Command::JournalTail(DEFAULT_JOURNAL_TAIL))
+        );
+        assert!(parse(format!("watch add {}", mint))
+            .unwrap()
+            .is_session_only());
+        assert!(!parse(format!("curve {}", mint)).unwrap().is_session_only());
+        assert!(parse(format!("sell {}", mint)).unwrap().needs_keypair());
+        assert!(parse("positions".to_string()).unwrap().needs_keypair());
+        assert!(!parse(format!("quote buy {} 1", mint))
+            .unwrap()
+            .needs_keypair());
+
+        assert!(matches!(
+            Command::parse("buy not-a-mint 1"),
+            Err(ClientError::InvalidInput("Invalid mint public key"))
+        ));
+        assert!(matches!(
+            Command::parse(&format!("buy {} 1.5", mint)),
+            Err(ClientError::InvalidInput(
+                "Invalid amount, expected an integer"
+            ))
+        ));
+        assert!(matches!(
+            Command::parse("watch"),
+            Err(ClientError::InvalidInput(
+                "Invalid arguments, see `help` for usage"
+            ))
+        ));
+        assert!(matches!(
+            Command::parse("launch"),
+            Err(ClientError::InvalidInput("Unknown command, see `help`"))
+        ));
+    }
+
+    #[test]
+    fn test_complete() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Localnet, &payer, None, None);
+        let mut session = Session::new(&client);
+        let first = Pubkey::new_from_array([1; 32]);
+        let second = Pubkey::new_from_array([2; 32]);
+        session.watched.extend([first, second]);
+
+        assert_eq!(session.complete(""), VERBS.to_vec());
+        assert_eq!(session.complete("qu"), vec!["quit", "quote"]);
+        assert_eq!(session.complete("watch "), vec!["add", "list", "remove"]);
+        assert_eq!(session.complete("watch r"), vec!["remove"]);
+        assert_eq!(
+            session.complete("quote buy "),
+            vec![first.to_string(), second.to_string()]
+        );
+        let prefix = &first.to_string()[..3];
+        assert_eq!(
+            session.complete(&format!("sell {}", prefix)),
+            vec![first.to_string()]
+        );
+        assert_eq!(session.complete(&format!("sell {} a", first)), vec!["all"]);
+        assert!(session.complete("global ").is_empty());
+    }
+
+    #[test]
+    fn test_run_script() {
+        let payer = Keypair::new();
+        let client = PumpFun::new(Cluster::Localnet, &payer, None, None);
+        let mut session = Session::new(&client);
+        let mint = Pubkey::new_unique();
+        let mut outputs = Vec::new();
+
+        let script = format!(
+            "# reproducible sequence\n\nwatch remove {}\nwatch list\nhelp\nbogus\nhelp\n",
+            mint
+        );
+        let err = futures::executor::block_on(session.run_script(&script, |line, output| {
+            outputs.push((line.to_string(), output.to_string()))
+        }))
+        .unwrap_err();
+        assert_eq!(err.line, 6);
+        assert_eq!(err.command, "bogus");
+        assert_eq!(outputs.len(), 3);
+        assert_eq!(outputs[0].1, format!("Not watching {}", mint));
+        assert_eq!(outputs[1].1, "");
+        assert_eq!(outputs[2].1, HELP);
+
+        // Commands after exit are not run
+        let executed =
+            futures::executor::block_on(session.run_script("help\nexit\nbogus\n", |_, _| {}))
+                .unwrap();
+        assert_eq!(executed, 1);
+
+        assert!(matches!(
+            futures::executor::block_on(session.execute(&Command::JournalTail(1))),
+            Err(ClientError::InvalidInput(
+                "No journal opened for this session"
+            ))
+        ));
+    }
+}
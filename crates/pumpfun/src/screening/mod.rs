@@ -0,0 +1,377 @@
+//! Bulk safety screening of Pump.fun tokens
+//!
+//! This module contains the types used by [`crate::PumpFun::screen_tokens`] to screen many
+//! tokens at once:
+//!
+//! - `ScreenChecks`: Selects which checks to run.
+//! - `SafetyReport`: The results of the selected checks for a single token.
+//! - `MintAuthority`: Who can still mint a token, see [`crate::PumpFun::get_mint_authority`].
+//!
+//! Checks are split into two tiers. Cheap checks only need the mint, metadata and bonding curve
+//! accounts, which are fetched for the whole batch with `getMultipleAccounts`. Expensive checks
+//! need extra RPC calls per token and only run for tokens that pass every selected cheap check.
+
+use crate::{accounts, error::ClientError};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_spl::token::spl_token::{solana_program::program_pack::Pack, state::Mint};
+use borsh::BorshDeserialize;
+use mpl_token_metadata::accounts::Metadata;
+use std::ops::{BitOr, BitOrAssign};
+
+/// Set of checks to run when screening tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScreenChecks(u8);
+
+impl ScreenChecks {
+    /// Whether the mint authority has been renounced (cheap)
+    pub const MINT_AUTHORITY: Self = Self(1 << 0);
+    /// Whether the freeze authority has been renounced (cheap)
+    pub const FREEZE_AUTHORITY: Self = Self(1 << 1);
+    /// Whether the token metadata is immutable (cheap)
+    pub const METADATA: Self = Self(1 << 2);
+    /// Whether the bonding curve is active and was created with the program's supply (cheap)
+    pub const BONDING_CURVE: Self = Self(1 << 3);
+    /// Share of the supply held by the largest holders outside the bonding curve (expensive)
+    pub const HOLDERS: Self = Self(1 << 4);
+    /// Creator of the token and how active their wallet has been (expensive)
+    pub const CREATOR_HISTORY: Self = Self(1 << 5);
+
+    /// All checks answered from the batched account fetch
+    pub const CHEAP: Self = Self(
+        Self::MINT_AUTHORITY.0
+            | Self::FREEZE_AUTHORITY.0
+            | Self::METADATA.0
+            | Self::BONDING_CURVE.0,
+    );
+    /// All checks that need extra RPC calls per token
+    pub const EXPENSIVE: Self = Self(Self::HOLDERS.0 | Self::CREATOR_HISTORY.0);
+    /// Every check
+    pub const ALL: Self = Self(Self::CHEAP.0 | Self::EXPENSIVE.0);
+
+    /// Returns an empty set of checks
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Returns whether every check in `other` is selected
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns whether no checks are selected
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl BitOr for ScreenChecks {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for ScreenChecks {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+/// Who can still mint more of a token
+///
+/// The Pump.fun program mints the whole supply through its mint authority PDA at creation and
+/// then revokes the authority. Any other state is non-standard: the authority may not have
+/// been revoked, or the token was not created by the program at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintAuthority {
+    /// The authority has been revoked, so no more tokens can be minted
+    Renounced,
+    /// The authority is still the Pump.fun mint authority PDA
+    PumpFun(Pubkey),
+    /// The authority is an unexpected account, which can mint at will
+    Other(Pubkey),
+}
+
+impl MintAuthority {
+    /// Classifies the authority of a mint
+    ///
+    /// # Arguments
+    ///
+    /// * `mint_data` - Data of the mint account
+    /// * `program_authority` - Mint authority PDA of the Pump.fun program
+    ///
+    /// # Returns
+    ///
+    /// Returns the classified authority, or a ClientError if the mint cannot be decoded
+    pub fn from_mint_data(
+        mint_data: &[u8],
+        program_authority: &Pubkey,
+    ) -> Result<Self, ClientError> {
+        let mint = decode_mint(mint_data)?;
+        Ok(match Option::<Pubkey>::from(mint.mint_authority) {
+            None => Self::Renounced,
+            Some(authority) if authority == *program_authority => Self::PumpFun(authority),
+            Some(authority) => Self::Other(authority),
+        })
+    }
+
+    /// Returns whether the authority has been revoked
+    pub fn is_renounced(&self) -> bool {
+        matches!(self, Self::Renounced)
+    }
+
+    /// Returns the account that can still mint, if any
+    pub fn authority(&self) -> Option<Pubkey> {
+        match *self {
+            Self::Renounced => None,
+            Self::PumpFun(authority) | Self::Other(authority) => Some(authority),
+        }
+    }
+}
+
+/// Results of the selected safety checks for a single token
+///
+/// Each field is `None` when its check was not selected, or, for expensive checks, when the
+/// token failed a cheap check and the expensive check was skipped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyReport {
+    /// Token mint that was screened
+    pub mint: Pubkey,
+    /// Whether the mint authority has been renounced
+    pub mint_authority_renounced: Option<bool>,
+    /// Whether the freeze authority has been renounced
+    pub freeze_authority_renounced: Option<bool>,
+    /// Whether the token metadata can still be changed
+    pub metadata_mutable: Option<bool>,
+    /// Whether the bonding curve has completed
+    pub bonding_curve_complete: Option<bool>,
+    /// Whether the bonding curve's total supply matches the program's current configuration
+    pub standard_supply: Option<bool>,
+    /// Percentage of the supply held by the largest holders, excluding the bonding curve
+    pub top_holders_pct: Option<f64>,
+    /// Wallet that created the token
+    pub creator: Option<Pubkey>,
+    /// Number of transactions signed by the creator, capped at 1000
+    pub creator_transaction_count: Option<usize>,
+}
+
+impl SafetyReport {
+    /// Builds a report from the accounts fetched for the cheap checks
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `checks` - Checks to evaluate
+    /// * `mint_data` - Data of the mint account, if it exists
+    /// * `metadata_data` - Data of the metadata account, if it exists and was fetched
+    /// * `bonding_curve_data` - Data of the bonding curve account, if it exists and was fetched
+    /// * `global` - Global account shared across the batch, if it could be fetched
+    ///
+    /// # Returns
+    ///
+    /// Returns the report with the cheap checks filled in, or a ClientError if a required
+    /// account is missing or cannot be decoded
+    pub fn from_accounts(
+        mint: Pubkey,
+        checks: ScreenChecks,
+        mint_data: Option<&[u8]>,
+        metadata_data: Option<&[u8]>,
+        bonding_curve_data: Option<&[u8]>,
+        global: Option<&accounts::GlobalAccount>,
+    ) -> Result<Self, ClientError> {
+        let mut report = Self {
+            mint,
+            mint_authority_renounced: None,
+            freeze_authority_renounced: None,
+            metadata_mutable: None,
+            bonding_curve_complete: None,
+            standard_supply: None,
+            top_holders_pct: None,
+            creator: None,
+            creator_transaction_count: None,
+        };
+
+        let mint_account = mint_data
+            .ok_or(ClientError::InvalidInput("Mint account not found"))
+            .and_then(decode_mint)?;
+        if checks.contains(ScreenChecks::MINT_AUTHORITY) {
+            report.mint_authority_renounced = Some(mint_account.mint_authority.is_none());
+        }
+        if checks.contains(ScreenChecks::FREEZE_AUTHORITY) {
+            report.freeze_authority_renounced = Some(mint_account.freeze_authority.is_none());
+        }
+
+        if checks.contains(ScreenChecks::METADATA) {
+            let metadata = metadata_data
+                .ok_or(ClientError::InvalidInput("Metadata account not found"))
+                .and_then(|data| {
+                    Metadata::safe_deserialize(data).map_err(ClientError::BorshError)
+                })?;
+            report.metadata_mutable = Some(metadata.is_mutable);
+        }
+
+        if checks.contains(ScreenChecks::BONDING_CURVE) {
+            let bonding_curve = bonding_curve_data
+                .ok_or(ClientError::BondingCurveNotFound)
+                .and_then(|data| {
+                    accounts::BondingCurveAccount::try_from_slice(data)
+                        .map_err(ClientError::BorshError)
+                })?;
+            report.bonding_curve_complete = Some(bonding_curve.complete);
+            report.standard_supply =
+                global.map(|global| global.token_total_supply == bonding_curve.token_total_supply);
+        }
+
+        Ok(report)
+    }
+
+    /// Returns whether the token passed every cheap check that was run
+    pub fn passes_cheap_checks(&self) -> bool {
+        self.mint_authority_renounced != Some(false)
+            && self.freeze_authority_renounced != Some(false)
+            && self.metadata_mutable != Some(true)
+            && self.bonding_curve_complete != Some(true)
+            && self.standard_supply != Some(false)
+    }
+}
+
+/// Decodes the base mint layout, shared by Token and Token-2022 mints
+pub(crate) fn decode_mint(data: &[u8]) -> Result<Mint, ClientError> {
+    data.get(..Mint::LEN)
+        .and_then(|data| Mint::unpack(data).ok())
+        .ok_or(ClientError::InvalidInput("Invalid mint account"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::program_option::COption;
+    use borsh::BorshSerialize;
+
+    fn get_mint_data(mint_authority: Option<Pubkey>) -> Vec<u8> {
+        let mint = Mint {
+            mint_authority: mint_authority.map_or(COption::None, COption::Some),
+            supply: 1_000_000_000_000_000,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        let mut data = vec![0; Mint::LEN];
+        Mint::pack(mint, &mut data).unwrap();
+        data
+    }
+
+    fn get_bonding_curve_data(complete: bool) -> Vec<u8> {
+        let mut data = Vec::new();
+        accounts::BondingCurveAccount::new(
+            1,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            1_000_000_000_000_000,
+            complete,
+        )
+        .serialize(&mut data)
+        .unwrap();
+        data
+    }
+
+    #[test]
+    fn test_screen_checks() {
+        let checks = ScreenChecks::MINT_AUTHORITY | ScreenChecks::HOLDERS;
+        assert!(checks.contains(ScreenChecks::MINT_AUTHORITY));
+        assert!(!checks.contains(ScreenChecks::METADATA));
+        assert!(!checks.contains(ScreenChecks::CHEAP));
+        assert!(ScreenChecks::ALL.contains(ScreenChecks::CHEAP | ScreenChecks::EXPENSIVE));
+        assert!(ScreenChecks::empty().is_empty());
+    }
+
+    #[test]
+    fn test_from_accounts() {
+        let mint = Pubkey::new_unique();
+        let mint_data = get_mint_data(None);
+        let curve_data = get_bonding_curve_data(false);
+        let checks = ScreenChecks::MINT_AUTHORITY
+            | ScreenChecks::FREEZE_AUTHORITY
+            | ScreenChecks::BONDING_CURVE;
+
+        let report = SafetyReport::from_accounts(
+            mint,
+            checks,
+            Some(&mint_data),
+            None,
+            Some(&curve_data),
+            None,
+        )
+        .unwrap();
+        assert_eq!(report.mint_authority_renounced, Some(true));
+        assert_eq!(report.freeze_authority_renounced, Some(true));
+        assert_eq!(report.metadata_mutable, None);
+        assert_eq!(report.bonding_curve_complete, Some(false));
+        assert_eq!(report.standard_supply, None);
+        assert!(report.passes_cheap_checks());
+
+        // A live mint authority fails the cheap tier
+        let mint_data = get_mint_data(Some(Pubkey::new_unique()));
+        let report = SafetyReport::from_accounts(
+            mint,
+            checks,
+            Some(&mint_data),
+            None,
+            Some(&curve_data),
+            None,
+        )
+        .unwrap();
+        assert!(!report.passes_cheap_checks());
+    }
+
+    #[test]
+    fn test_mint_authority() {
+        let program_authority = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+
+        let renounced =
+            MintAuthority::from_mint_data(&get_mint_data(None), &program_authority).unwrap();
+        assert!(renounced.is_renounced());
+        assert_eq!(renounced.authority(), None);
+
+        let pumpfun = MintAuthority::from_mint_data(
+            &get_mint_data(Some(program_authority)),
+            &program_authority,
+        )
+        .unwrap();
+        assert_eq!(pumpfun, MintAuthority::PumpFun(program_authority));
+        assert!(!pumpfun.is_renounced());
+
+        let anomaly =
+            MintAuthority::from_mint_data(&get_mint_data(Some(other)), &program_authority).unwrap();
+        assert_eq!(anomaly, MintAuthority::Other(other));
+        assert_eq!(anomaly.authority(), Some(other));
+
+        assert!(MintAuthority::from_mint_data(&[0; 10], &program_authority).is_err());
+    }
+
+    #[test]
+    fn test_from_accounts_missing() {
+        let mint = Pubkey::new_unique();
+        let mint_data = get_mint_data(None);
+
+        assert!(matches!(
+            SafetyReport::from_accounts(mint, ScreenChecks::CHEAP, None, None, None, None),
+            Err(ClientError::InvalidInput(_))
+        ));
+        assert!(matches!(
+            SafetyReport::from_accounts(
+                mint,
+                ScreenChecks::BONDING_CURVE,
+                Some(&mint_data),
+                None,
+                None,
+                None
+            ),
+            Err(ClientError::BondingCurveNotFound)
+        ));
+    }
+}
@@ -0,0 +1,544 @@
+//! Shedding of optional load while the RPC node is under pressure
+//!
+//! A node that rate limits or starts failing requests slows down every call the client makes,
+//! trades included. When a [`LoadShedder`] is attached with
+//! [`crate::PumpFun::with_load_shedder`], optional features fail fast with
+//! `ClientError::Shedded` under pressure instead of adding requests to a struggling node, so
+//! the trade path keeps what capacity is left:
+//!
+//! - `Feature`: The optional features that can be shed.
+//! - `Priority`: How long a feature is kept as pressure rises.
+//! - `Pressure`: How hard the node is struggling, and which priorities it sheds.
+//! - `PressureThreshold`: Failure rates at which a pressure level is entered and left.
+//! - `RpcPressure`: Decaying rate of transient failures among recent requests.
+//! - `PressureSender`: RPC transport wrapper recording the outcome of each request.
+//! - `LoadShedder`: Feature priorities and the current pressure level.
+//!
+//! Failures are classified with [`SubmitErrorKind::classify`]: only transient failures, such
+//! as connection errors, rate limited HTTP responses and unhealthy nodes, count as pressure.
+//! Rejected transactions and missing accounts are answers, not pressure. A rate limiter in
+//! front of the client reports saturation with [`crate::PumpFun::record_rate_limited`].
+//!
+//! Each level is entered at a higher failure rate than it is left at, so a rate hovering around
+//! a threshold does not flap between shedding and serving. Failures weigh less as they age, and
+//! too few recent requests count as no pressure, so a client left idle while shedding recovers
+//! on its own.
+
+use crate::{
+    error::ClientError,
+    submit::SubmitErrorKind,
+    utils::clock::{Clock, SystemClock},
+};
+use anchor_client::solana_client::{
+    client_error::Result as ClientResult,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use std::{
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+/// Age at which a request counts for half as much as one made now in the failure rate
+pub const PRESSURE_HALF_LIFE: Duration = Duration::from_secs(30);
+
+/// Weighted number of recent requests below which the failure rate is not trusted
+const MIN_SAMPLES: f64 = 5.0;
+
+/// Optional features that can be shed under pressure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Feature {
+    /// Token snapshots enriched with on-chain metadata
+    MetadataEnrichment,
+    /// Holder concentration of a token's supply
+    HolderCounts,
+    /// Fetches of off-chain metadata JSON, such as token logos
+    OffchainMetadata,
+    /// Polling of watched bonding curves by a refresh scheduler
+    BackgroundRefresh,
+}
+
+impl Feature {
+    /// Every feature
+    pub const ALL: [Feature; 4] = [
+        Feature::MetadataEnrichment,
+        Feature::HolderCounts,
+        Feature::OffchainMetadata,
+        Feature::BackgroundRefresh,
+    ];
+}
+
+/// How long a feature is kept as pressure rises
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Shed as soon as pressure is elevated
+    Low,
+    /// Shed once pressure is severe
+    High,
+    /// Never shed
+    Critical,
+}
+
+/// How hard the RPC node is struggling
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Pressure {
+    /// Requests succeed, nothing is shed
+    #[default]
+    Normal,
+    /// Some requests fail, low priority features are shed
+    Elevated,
+    /// Many requests fail, every feature short of critical is shed
+    Severe,
+}
+
+impl Pressure {
+    /// Returns whether features of a priority are shed at this level
+    ///
+    /// # Arguments
+    ///
+    /// * `priority` - Priority of the feature
+    pub fn sheds(self, priority: Priority) -> bool {
+        match priority {
+            Priority::Low => self >= Pressure::Elevated,
+            Priority::High => self >= Pressure::Severe,
+            Priority::Critical => false,
+        }
+    }
+}
+
+/// Failure rates at which a pressure level is entered and left
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PressureThreshold {
+    /// Failure rate from 0 to 1 at or above which the level is entered
+    pub enter: f64,
+    /// Failure rate from 0 to 1 at or below which the level is left
+    pub exit: f64,
+}
+
+impl PressureThreshold {
+    /// Creates a threshold
+    ///
+    /// # Arguments
+    ///
+    /// * `enter` - Failure rate at or above which the level is entered
+    /// * `exit` - Failure rate at or below which the level is left, capped at `enter`
+    pub fn new(enter: f64, exit: f64) -> Self {
+        Self {
+            enter,
+            exit: exit.min(enter),
+        }
+    }
+}
+
+/// Decayed counts of recent requests
+#[derive(Debug, Default)]
+struct PressureState {
+    /// Weighted number of requests
+    requests: f64,
+    /// Weighted number of transient failures
+    failures: f64,
+    /// When the counts were last decayed
+    updated: Option<Instant>,
+}
+
+impl PressureState {
+    /// Decays the counts to the given time
+    fn decay(&mut self, now: Instant) {
+        if let Some(updated) = self.updated {
+            let age = now.saturating_duration_since(updated);
+            let weight = 0.5f64.powf(age.as_secs_f64() / PRESSURE_HALF_LIFE.as_secs_f64());
+            self.requests *= weight;
+            self.failures *= weight;
+        }
+        self.updated = Some(now.max(self.updated.unwrap_or(now)));
+    }
+}
+
+/// Rate of transient failures among recent requests, weighted towards the latest
+///
+/// Each request counts for half as much every [`PRESSURE_HALF_LIFE`]. Safe to share between
+/// threads.
+#[derive(Debug)]
+pub struct RpcPressure {
+    /// Decayed counts of recent requests
+    state: Mutex<PressureState>,
+    /// Clock [`PressureSender`] reads the completion time of requests from
+    clock: RwLock<Arc<dyn Clock>>,
+}
+
+impl Default for RpcPressure {
+    fn default() -> Self {
+        Self {
+            state: Mutex::default(),
+            clock: RwLock::new(Arc::new(SystemClock)),
+        }
+    }
+}
+
+impl RpcPressure {
+    /// Creates a gauge with no requests recorded
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the clock request completion times are read from
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - Clock to read the time from, usually the client's
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        *self
+            .clock
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = clock;
+    }
+
+    /// Gets the current time on the gauge's clock
+    pub fn now(&self) -> Instant {
+        self.clock
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .now()
+    }
+
+    /// Records the outcome of a request
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the request completed
+    /// * `failed` - Whether it failed in a way that signals pressure
+    pub fn record(&self, now: Instant, failed: bool) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.decay(now);
+        state.requests += 1.0;
+        if failed {
+            state.failures += 1.0;
+        }
+    }
+
+    /// Records the outcome of a request from the error it returned, if any
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - When the request completed
+    /// * `result` - Result of the request
+    pub fn record_result<T>(&self, now: Instant, result: &ClientResult<T>) {
+        let failed = result
+            .as_ref()
+            .is_err_and(|err| SubmitErrorKind::classify(err) == SubmitErrorKind::Transient);
+        self.record(now, failed);
+    }
+
+    /// Gets the weighted failure rate
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Time to weigh the requests at
+    ///
+    /// # Returns
+    ///
+    /// Returns the failure rate from 0 to 1, or None if too few requests were made recently
+    pub fn failure_rate(&self, now: Instant) -> Option<f64> {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.decay(now);
+        (state.requests >= MIN_SAMPLES).then(|| state.failures / state.requests)
+    }
+}
+
+/// RPC transport that records the outcome of each request after passing it to another
+/// transport
+pub struct PressureSender<S> {
+    /// Transport the requests are sent with
+    inner: S,
+    /// Gauge the outcomes are recorded in
+    pressure: Arc<RpcPressure>,
+}
+
+impl<S> PressureSender<S> {
+    /// Wraps a transport
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Transport the requests are sent with
+    /// * `pressure` - Gauge the outcomes are recorded in
+    pub fn new(inner: S, pressure: Arc<RpcPressure>) -> Self {
+        Self { inner, pressure }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for PressureSender<S> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let result = self.inner.send(request, params).await;
+        self.pressure.record_result(self.pressure.now(), &result);
+        result
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+/// Feature priorities and the current pressure level
+///
+/// Defaults to shedding metadata enrichment, holder counts and off-chain metadata fetches once
+/// 20% of recent requests fail, and background refreshes as well once 50% do. Pressure drops
+/// a level once the failure rate falls to 10% and 30% respectively.
+#[derive(Debug)]
+pub struct LoadShedder {
+    /// Priority of each feature, indexed by feature
+    priorities: [Priority; Feature::ALL.len()],
+    /// Thresholds of the elevated level
+    elevated: PressureThreshold,
+    /// Thresholds of the severe level
+    severe: PressureThreshold,
+    /// Current pressure level
+    level: Mutex<Pressure>,
+}
+
+impl Default for LoadShedder {
+    fn default() -> Self {
+        Self {
+            priorities: Feature::ALL.map(|feature| match feature {
+                Feature::BackgroundRefresh => Priority::High,
+                _ => Priority::Low,
+            }),
+            elevated: PressureThreshold::new(0.2, 0.1),
+            severe: PressureThreshold::new(0.5, 0.3),
+            level: Mutex::new(Pressure::Normal),
+        }
+    }
+}
+
+impl LoadShedder {
+    /// Creates a shedder with the default priorities and thresholds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the priority of a feature
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - Feature to prioritize
+    /// * `priority` - How long the feature is kept as pressure rises
+    ///
+    /// # Returns
+    ///
+    /// Returns the shedder with the priority applied
+    pub fn with_priority(mut self, feature: Feature, priority: Priority) -> Self {
+        self.priorities[feature as usize] = priority;
+        self
+    }
+
+    /// Sets the failure rates at which pressure levels are entered and left
+    ///
+    /// # Arguments
+    ///
+    /// * `elevated` - Thresholds of the elevated level
+    /// * `severe` - Thresholds of the severe level
+    ///
+    /// # Returns
+    ///
+    /// Returns the shedder with the thresholds applied
+    pub fn with_thresholds(
+        mut self,
+        elevated: PressureThreshold,
+        severe: PressureThreshold,
+    ) -> Self {
+        self.elevated = elevated;
+        self.severe = severe;
+        self
+    }
+
+    /// Gets the priority of a feature
+    pub fn priority(&self, feature: Feature) -> Priority {
+        self.priorities[feature as usize]
+    }
+
+    /// Gets the pressure level last evaluated
+    pub fn pressure(&self) -> Pressure {
+        *self
+            .level
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Moves the pressure level according to a failure rate
+    ///
+    /// A level is entered once the rate reaches its `enter` threshold and kept until the rate
+    /// falls to its `exit` threshold. Transitions are logged.
+    ///
+    /// # Arguments
+    ///
+    /// * `failure_rate` - Recent failure rate from 0 to 1, None if too few requests were made
+    ///
+    /// # Returns
+    ///
+    /// Returns the new pressure level
+    pub fn evaluate(&self, failure_rate: Option<f64>) -> Pressure {
+        let rate = failure_rate.unwrap_or(0.0);
+        let mut level = self
+            .level
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let crosses = |threshold: PressureThreshold, held: bool| {
+            if held {
+                rate > threshold.exit
+            } else {
+                rate >= threshold.enter
+            }
+        };
+
+        let next = if crosses(self.severe, *level == Pressure::Severe) {
+            Pressure::Severe
+        } else if crosses(self.elevated, *level >= Pressure::Elevated) {
+            Pressure::Elevated
+        } else {
+            Pressure::Normal
+        };
+        if next > *level {
+            tracing::warn!(from = ?*level, to = ?next, rate, "RPC pressure rising, shedding load");
+        } else if next < *level {
+            tracing::info!(from = ?*level, to = ?next, rate, "RPC pressure easing");
+        }
+        *level = next;
+        next
+    }
+
+    /// Checks whether a feature may run at the current failure rate
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - Feature about to run
+    /// * `failure_rate` - Recent failure rate from 0 to 1, None if too few requests were made
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok if the feature may run, or a ClientError::Shedded if it is shed
+    pub fn check(&self, feature: Feature, failure_rate: Option<f64>) -> Result<(), ClientError> {
+        let pressure = self.evaluate(failure_rate);
+        if pressure.sheds(self.priority(feature)) {
+            tracing::debug!(?feature, ?pressure, "Shedding feature");
+            return Err(ClientError::Shedded { feature, pressure });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_client::{
+        client_error::ClientError as SolanaClientError, rpc_request::RpcError,
+    };
+
+    #[test]
+    fn test_failure_rate_decays() {
+        let pressure = RpcPressure::new();
+        let start = Instant::now();
+        assert_eq!(pressure.failure_rate(start), None);
+
+        for index in 0..10 {
+            pressure.record(start, index % 2 == 0);
+        }
+        assert_eq!(pressure.failure_rate(start), Some(0.5));
+
+        // Recent successes outweigh older failures
+        let later = start + PRESSURE_HALF_LIFE;
+        for _ in 0..5 {
+            pressure.record(later, false);
+        }
+        let rate = pressure.failure_rate(later).unwrap();
+        assert!((rate - 2.5 / 10.0).abs() < 1e-9, "{}", rate);
+
+        // An idle gauge forgets its failures
+        assert_eq!(pressure.failure_rate(later + PRESSURE_HALF_LIFE * 2), None);
+    }
+
+    #[test]
+    fn test_record_result_classifies_errors() {
+        let pressure = RpcPressure::new();
+        let now = Instant::now();
+        let transient: ClientResult<()> = Err(SolanaClientError::from(std::io::Error::new(
+            std::io::ErrorKind::ConnectionReset,
+            "reset",
+        )));
+        let answered: ClientResult<()> = Err(SolanaClientError::from(RpcError::ForUser(
+            "account not found".to_string(),
+        )));
+        for _ in 0..2 {
+            pressure.record_result(now, &transient);
+            pressure.record_result(now, &answered);
+            pressure.record_result(now, &Ok(()));
+        }
+        assert_eq!(pressure.failure_rate(now), Some(2.0 / 6.0));
+    }
+
+    #[test]
+    fn test_pressure_hysteresis() {
+        let shedder = LoadShedder::new();
+        assert_eq!(shedder.evaluate(None), Pressure::Normal);
+        assert_eq!(shedder.evaluate(Some(0.15)), Pressure::Normal);
+        assert_eq!(shedder.evaluate(Some(0.2)), Pressure::Elevated);
+
+        // Held between the thresholds, left at the exit threshold
+        assert_eq!(shedder.evaluate(Some(0.15)), Pressure::Elevated);
+        assert_eq!(shedder.evaluate(Some(0.1)), Pressure::Normal);
+
+        assert_eq!(shedder.evaluate(Some(0.6)), Pressure::Severe);
+        assert_eq!(shedder.evaluate(Some(0.4)), Pressure::Severe);
+        assert_eq!(shedder.evaluate(Some(0.25)), Pressure::Elevated);
+        assert_eq!(shedder.evaluate(Some(0.3)), Pressure::Elevated);
+
+        // A sudden recovery drops straight to normal
+        assert_eq!(shedder.evaluate(Some(0.6)), Pressure::Severe);
+        assert_eq!(shedder.evaluate(None), Pressure::Normal);
+        assert_eq!(shedder.pressure(), Pressure::Normal);
+    }
+
+    #[test]
+    fn test_check_sheds_by_priority() {
+        let shedder = LoadShedder::new()
+            .with_priority(Feature::HolderCounts, Priority::Critical)
+            .with_thresholds(
+                PressureThreshold::new(0.1, 0.05),
+                PressureThreshold::new(0.3, 0.5),
+            );
+        assert_eq!(shedder.priority(Feature::OffchainMetadata), Priority::Low);
+        assert_eq!(shedder.priority(Feature::BackgroundRefresh), Priority::High);
+
+        assert!(shedder.check(Feature::OffchainMetadata, None).is_ok());
+        assert!(matches!(
+            shedder.check(Feature::OffchainMetadata, Some(0.1)),
+            Err(ClientError::Shedded {
+                feature: Feature::OffchainMetadata,
+                pressure: Pressure::Elevated,
+            })
+        ));
+        assert!(shedder.check(Feature::BackgroundRefresh, Some(0.1)).is_ok());
+        assert!(shedder
+            .check(Feature::BackgroundRefresh, Some(0.35))
+            .is_err());
+        assert!(shedder.check(Feature::HolderCounts, Some(1.0)).is_ok());
+
+        // Exit thresholds are capped at their entry
+        assert_eq!(shedder.evaluate(Some(0.4)), Pressure::Severe);
+    }
+}
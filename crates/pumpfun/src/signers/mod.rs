@@ -0,0 +1,356 @@
+//! Providers of the keypair that signs and pays for trades
+//!
+//! A [`SignerProvider`] resolves the active keypair at trade time, so hot wallets can be rotated
+//! without restarting. Set one with
+//! [`PumpFun::with_signer_provider`](crate::PumpFun::with_signer_provider) and the client
+//! resolves it when each trade starts: a trade in flight keeps the keypair it started with,
+//! including when its transaction is rebuilt and signed again, and the next trade picks up the
+//! new one.
+//!
+//! ```no_run
+//! use pumpfun::{signers::{FileWatchingSigner, SignerProvider}, PumpFun};
+//! use anchor_client::Cluster;
+//! use std::sync::Arc;
+//!
+//! let provider = Arc::new(FileWatchingSigner::new("/etc/bot/payer.json").unwrap());
+//! let payer = provider.current();
+//! let client = PumpFun::new(Cluster::Mainnet, &payer, None, None)
+//!     .with_signer_provider(provider);
+//! ```
+//!
+//! - `SignerProvider`: Resolves the keypair that signs the next trade.
+//! - `StaticSigner`: Always returns the same keypair.
+//! - `FileWatchingSigner`: Reloads the keypair when its file changes.
+//! - `ActiveSigner`: The keypair a client signs with, borrowed or resolved from a provider.
+
+use crate::redact::RedactionPolicy;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use std::{
+    fs,
+    future::Future,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::SystemTime,
+};
+
+/// Resolves the keypair that signs and pays for the next trade
+pub trait SignerProvider: Send + Sync {
+    /// Gets the currently active keypair
+    ///
+    /// The returned handle stays valid after a rotation, so callers holding it keep signing
+    /// with the keypair they started with.
+    fn current(&self) -> Arc<Keypair>;
+}
+
+/// Provider that always returns the same keypair
+pub struct StaticSigner {
+    /// The keypair
+    keypair: Arc<Keypair>,
+}
+
+impl StaticSigner {
+    /// Creates a provider for a fixed keypair
+    ///
+    /// # Arguments
+    ///
+    /// * `keypair` - Keypair to return
+    pub fn new(keypair: Keypair) -> Self {
+        Self {
+            keypair: Arc::new(keypair),
+        }
+    }
+}
+
+impl SignerProvider for StaticSigner {
+    fn current(&self) -> Arc<Keypair> {
+        self.keypair.clone()
+    }
+}
+
+/// Keypair a client signs with
+///
+/// Either the payer the client was created with, or the keypair a [`SignerProvider`] resolved.
+pub enum ActiveSigner<'a> {
+    /// The client's payer, used when no provider is set
+    Payer(&'a Keypair),
+    /// A keypair resolved from the client's provider
+    Provided(Arc<Keypair>),
+}
+
+impl Deref for ActiveSigner<'_> {
+    type Target = Keypair;
+
+    fn deref(&self) -> &Keypair {
+        match self {
+            Self::Payer(keypair) => keypair,
+            Self::Provided(keypair) => keypair,
+        }
+    }
+}
+
+/// Keypair resolved when a trade started, with the provider it came from
+#[derive(Clone)]
+struct PinnedSigner {
+    /// Address of the provider, so other clients' trades never see the keypair
+    provider: usize,
+    /// The keypair
+    keypair: Arc<Keypair>,
+}
+
+tokio::task_local! {
+    /// Keypair of the trade whose future is polled, if it was resolved from a provider
+    static TRADE_SIGNER: Option<PinnedSigner>;
+}
+
+/// Identifies a provider by the address it is shared at
+fn provider_id(provider: &Arc<dyn SignerProvider>) -> usize {
+    Arc::as_ptr(provider) as *const () as usize
+}
+
+/// Gets the keypair the trade in progress resolved from a provider, if any
+pub(crate) fn pinned(provider: &Arc<dyn SignerProvider>) -> Option<Arc<Keypair>> {
+    TRADE_SIGNER
+        .try_with(|pinned| {
+            pinned
+                .as_ref()
+                .filter(|pinned| pinned.provider == provider_id(provider))
+                .map(|pinned| pinned.keypair.clone())
+        })
+        .ok()
+        .flatten()
+}
+
+/// Resolves the keypair of a trade once, keeping it while the trade's future is polled
+///
+/// A trade started inside another trade of the same client keeps the outer keypair, so a
+/// workflow of several trades can be pinned to one keypair as a whole.
+///
+/// # Arguments
+///
+/// * `provider` - Provider of the client, None to keep any keypair already in scope
+/// * `future` - Future of the trade
+pub(crate) fn scoped<F: Future>(
+    provider: Option<&Arc<dyn SignerProvider>>,
+    future: F,
+) -> impl Future<Output = F::Output> {
+    TRADE_SIGNER.scope(resolve(provider), future)
+}
+
+/// Resolves the keypair of a synchronous call once, keeping it until the call returns
+///
+/// # Arguments
+///
+/// * `provider` - Provider of the client, None to keep any keypair already in scope
+/// * `f` - Synchronous call
+pub(crate) fn sync_scoped<R>(
+    provider: Option<&Arc<dyn SignerProvider>>,
+    f: impl FnOnce() -> R,
+) -> R {
+    TRADE_SIGNER.sync_scope(resolve(provider), f)
+}
+
+/// Picks the keypair to pin, keeping the one already pinned by the same provider
+fn resolve(provider: Option<&Arc<dyn SignerProvider>>) -> Option<PinnedSigner> {
+    match provider {
+        Some(provider) => Some(PinnedSigner {
+            provider: provider_id(provider),
+            keypair: pinned(provider).unwrap_or_else(|| provider.current()),
+        }),
+        None => TRADE_SIGNER.try_with(Clone::clone).ok().flatten(),
+    }
+}
+
+/// Active keypair of a [`FileWatchingSigner`] and the file state it was loaded from
+struct LoadedKeypair {
+    /// The keypair
+    keypair: Arc<Keypair>,
+    /// Modification time of the file when it was loaded
+    modified: Option<SystemTime>,
+    /// Length of the file when it was loaded
+    len: u64,
+}
+
+/// Provider that reloads the keypair whenever its file changes
+///
+/// The file is checked on every call to [`SignerProvider::current`]; there is no background
+/// watcher to shut down. A new keypair is swapped in atomically. If the file cannot be read or
+/// parsed, for example while it is being rewritten, the previous keypair stays active. Keypairs
+/// zeroize their secret key when dropped, so a rotated-out key is wiped once the last trade
+/// using it finishes.
+pub struct FileWatchingSigner {
+    /// Path of the keypair file
+    path: PathBuf,
+    /// Currently active keypair
+    loaded: RwLock<LoadedKeypair>,
+    /// Which sensitive values are redacted in log output
+    redaction: RedactionPolicy,
+}
+
+impl FileWatchingSigner {
+    /// Creates a provider that loads the keypair from a file
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of a JSON keypair file, as written by `solana-keygen`
+    ///
+    /// # Returns
+    ///
+    /// Returns the provider, or an error if the file cannot be read or parsed
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, Box<dyn std::error::Error>> {
+        let path = path.as_ref().to_path_buf();
+        let metadata = fs::metadata(&path)?;
+        let keypair = read_keypair_file(&path)?;
+
+        Ok(Self {
+            path,
+            loaded: RwLock::new(LoadedKeypair {
+                keypair: Arc::new(keypair),
+                modified: metadata.modified().ok(),
+                len: metadata.len(),
+            }),
+            redaction: RedactionPolicy::default(),
+        })
+    }
+
+    /// Sets which sensitive values are redacted when logging a rotation
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - Values to redact, usually the client's policy
+    ///
+    /// # Returns
+    ///
+    /// Returns the provider with the redaction policy applied
+    pub fn with_redaction(mut self, policy: RedactionPolicy) -> Self {
+        self.redaction = policy;
+        self
+    }
+
+    /// Reloads the keypair if the file changed since it was last loaded
+    fn reload_if_changed(&self) {
+        let Ok(metadata) = fs::metadata(&self.path) else {
+            return;
+        };
+        let modified = metadata.modified().ok();
+        let len = metadata.len();
+
+        {
+            let loaded = self
+                .loaded
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            if loaded.modified == modified && loaded.len == len {
+                return;
+            }
+        }
+
+        let Ok(keypair) = read_keypair_file(&self.path) else {
+            return;
+        };
+        let mut loaded = self
+            .loaded
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let previous = loaded.keypair.pubkey();
+        if previous != keypair.pubkey() {
+            tracing::info!(
+                previous = %self.redaction.wallet(&previous),
+                current = %self.redaction.wallet(&keypair.pubkey()),
+                "Payer keypair rotated"
+            );
+        }
+        *loaded = LoadedKeypair {
+            keypair: Arc::new(keypair),
+            modified,
+            len,
+        };
+    }
+}
+
+impl SignerProvider for FileWatchingSigner {
+    fn current(&self) -> Arc<Keypair> {
+        self.reload_if_changed();
+        self.loaded
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keypair
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PumpFun;
+    use anchor_client::Cluster;
+    use solana_sdk::signature::write_keypair_file;
+
+    #[test]
+    fn test_static_signer() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let provider = StaticSigner::new(keypair);
+        assert_eq!(provider.current().pubkey(), pubkey);
+        assert_eq!(provider.current().pubkey(), pubkey);
+    }
+
+    #[test]
+    fn test_file_watching_signer_rotation() {
+        let path = std::env::temp_dir().join(format!("pumpfun-signer-{}.json", std::process::id()));
+        let first = Keypair::new();
+        let second = Keypair::new();
+        write_keypair_file(&first, &path).unwrap();
+
+        let provider = FileWatchingSigner::new(&path).unwrap();
+
+        // First trade resolves the original keypair
+        let in_flight = provider.current();
+        let client = PumpFun::new(Cluster::Devnet, &in_flight, None, None);
+        assert_eq!(client.payer.pubkey(), first.pubkey());
+
+        // Rotate the file mid-sequence; a partial write keeps the old key active
+        fs::write(&path, "[1, 2").unwrap();
+        assert_eq!(provider.current().pubkey(), first.pubkey());
+        write_keypair_file(&second, &path).unwrap();
+
+        // Next trade uses the new keypair while the in-flight one is unchanged
+        let next = provider.current();
+        let client = PumpFun::new(Cluster::Devnet, &next, None, None);
+        assert_eq!(client.payer.pubkey(), second.pubkey());
+        assert_eq!(in_flight.pubkey(), first.pubkey());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_rotation_log_redaction() {
+        let path = std::env::temp_dir().join(format!(
+            "pumpfun-signer-redacted-{}.json",
+            std::process::id()
+        ));
+        let first = Keypair::new();
+        let second = Keypair::new();
+        write_keypair_file(&first, &path).unwrap();
+
+        let provider = FileWatchingSigner::new(&path)
+            .unwrap()
+            .with_redaction(RedactionPolicy {
+                redact_wallets: true,
+                ..RedactionPolicy::default()
+            });
+        // Let the modification time move on, since both files may have the same length
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        write_keypair_file(&second, &path).unwrap();
+
+        let events = crate::redact::capture::capture_events(|| {
+            assert_eq!(provider.current().pubkey(), second.pubkey());
+        });
+        assert_eq!(events.len(), 1);
+        assert!(!events[0].contains(&first.pubkey().to_string()));
+        assert!(!events[0].contains(&second.pubkey().to_string()));
+        assert!(events[0].contains(&provider.redaction.wallet(&second.pubkey())));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
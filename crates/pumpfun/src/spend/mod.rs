@@ -0,0 +1,316 @@
+//! Tracking of priority fees spent, with an optional hourly budget
+//!
+//! Priority fees are paid on every transaction but never show up in a trade's fill. When
+//! enabled with [`crate::PumpFun::with_fee_spend_tracking`], the client reads the compute units
+//! each of its confirmed transactions actually consumed and records the priority fee as the
+//! unit price sent times those units, per mint and for the whole session. The totals are
+//! available from [`crate::PumpFun::fee_spend_report`].
+//!
+//! - `FeeBudget`: Maximum priority fees per hour, and what to do once it is reached.
+//! - `BudgetAction`: Whether to downgrade the fee or refuse trades over budget.
+//! - `FeeSpendReport`: Priority fees spent per mint, over the session and the last hour.
+//! - `priority_fee_lamports`: Computes a priority fee from a unit price and consumed units.
+//!
+//! Consumed units are read from the confirmed transaction, which takes one extra
+//! `getTransaction` request per trade unless the trade fetches it anyway, as
+//! [`crate::PumpFun::sell_with_result`] and [`crate::PumpFun::buy_with_outcome`] do. The runtime charges the unit price on the
+//! requested compute unit limit, so the fee actually paid is higher when the limit is loose;
+//! the report shows what the transactions needed, and a tight limit keeps the two close.
+
+use crate::{error::ClientError, PriorityFee};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Window over which spending is compared with the budget
+pub const SPEND_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Number of micro-lamports in a lamport
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+/// What to do with a trade sent while the hourly budget is spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetAction {
+    /// Send the trade with its unit price capped at a lower tier, in micro-lamports per unit
+    Downgrade(u64),
+    /// Refuse the trade with `ClientError::PriorityFeeBudgetExceeded`
+    Block,
+}
+
+/// Maximum priority fees per hour, and what to do once it is reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBudget {
+    /// Maximum priority fees spent over the last hour, in lamports
+    pub max_priority_spend_per_hour: u64,
+    /// What to do with trades sent once the maximum is reached
+    pub on_exceeded: BudgetAction,
+}
+
+impl FeeBudget {
+    /// Creates a budget
+    ///
+    /// # Arguments
+    ///
+    /// * `max_priority_spend_per_hour` - Maximum priority fees over the last hour, in lamports
+    /// * `on_exceeded` - What to do with trades sent once the maximum is reached
+    pub fn new(max_priority_spend_per_hour: u64, on_exceeded: BudgetAction) -> Self {
+        Self {
+            max_priority_spend_per_hour,
+            on_exceeded,
+        }
+    }
+}
+
+/// Priority fees spent by a client's confirmed transactions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeeSpendReport {
+    /// Priority fees spent since tracking started, in lamports
+    pub session_lamports: u64,
+    /// Priority fees spent over the last hour, in lamports
+    pub last_hour_lamports: u64,
+    /// Number of confirmed transactions with a priority fee
+    pub transactions: u64,
+    /// Priority fees spent since tracking started per token mint, in lamports. Swaps outside
+    /// Pump.fun only count towards the session total
+    pub per_mint: HashMap<Pubkey, u64>,
+    /// Number of trades sent at a downgraded unit price because the budget was spent
+    pub downgraded: u64,
+    /// Number of trades refused because the budget was spent
+    pub blocked: u64,
+}
+
+/// Spending recorded so far
+#[derive(Debug, Default)]
+struct SpendState {
+    /// Report totals, except the last hour which is summed from `recent`
+    report: FeeSpendReport,
+    /// Fees spent within the window, oldest first, with when they were recorded
+    recent: VecDeque<(Instant, u64)>,
+}
+
+impl SpendState {
+    /// Forgets fees recorded before the window and sums the rest
+    fn last_hour(&mut self, now: Instant) -> u64 {
+        while let Some((recorded_at, _)) = self.recent.front() {
+            if now.saturating_duration_since(*recorded_at) < SPEND_WINDOW {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        self.recent
+            .iter()
+            .fold(0u64, |total, (_, lamports)| total.saturating_add(*lamports))
+    }
+}
+
+/// Records priority fees and applies the budget to new trades
+#[derive(Debug, Default)]
+pub(crate) struct FeeSpendTracker {
+    /// Budget applied to new trades, if any
+    budget: Option<FeeBudget>,
+    /// Spending recorded so far
+    state: Mutex<SpendState>,
+}
+
+impl FeeSpendTracker {
+    /// Creates a tracker with an optional budget
+    pub(crate) fn new(budget: Option<FeeBudget>) -> Self {
+        Self {
+            budget,
+            state: Mutex::default(),
+        }
+    }
+
+    /// Records the priority fee of a confirmed transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time on the client's clock
+    /// * `mint` - Token mint traded, None for swaps outside Pump.fun
+    /// * `lamports` - Priority fee spent in lamports
+    pub(crate) fn record(&self, now: Instant, mint: Option<Pubkey>, lamports: u64) {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.report.session_lamports = state.report.session_lamports.saturating_add(lamports);
+        state.report.transactions += 1;
+        if let Some(mint) = mint {
+            let spent = state.report.per_mint.entry(mint).or_default();
+            *spent = spent.saturating_add(lamports);
+        }
+        state.recent.push_back((now, lamports));
+    }
+
+    /// Applies the budget to the priority fee of a new trade
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time on the client's clock
+    /// * `priority_fee` - Priority fee requested for the trade
+    ///
+    /// # Returns
+    ///
+    /// Returns the priority fee to send, with its unit price capped if the budget is spent and
+    /// set to downgrade, or a ClientError::PriorityFeeBudgetExceeded if it is set to block
+    pub(crate) fn apply_budget(
+        &self,
+        now: Instant,
+        priority_fee: Option<PriorityFee>,
+    ) -> Result<Option<PriorityFee>, ClientError> {
+        let Some(budget) = self.budget else {
+            return Ok(priority_fee);
+        };
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let spent = state.last_hour(now);
+        if spent < budget.max_priority_spend_per_hour {
+            return Ok(priority_fee);
+        }
+
+        match budget.on_exceeded {
+            BudgetAction::Block => {
+                state.report.blocked += 1;
+                Err(ClientError::PriorityFeeBudgetExceeded {
+                    spent,
+                    budget: budget.max_priority_spend_per_hour,
+                })
+            }
+            BudgetAction::Downgrade(tier) => Ok(priority_fee.map(|fee| match fee.price {
+                Some(price) if price > tier => {
+                    state.report.downgraded += 1;
+                    PriorityFee {
+                        price: Some(tier),
+                        ..fee
+                    }
+                }
+                _ => fee,
+            })),
+        }
+    }
+
+    /// Reports the spending recorded so far
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time on the client's clock
+    pub(crate) fn report(&self, now: Instant) -> FeeSpendReport {
+        let mut state = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let last_hour_lamports = state.last_hour(now);
+        FeeSpendReport {
+            last_hour_lamports,
+            ..state.report.clone()
+        }
+    }
+}
+
+/// Computes the priority fee of a transaction from the compute units it consumed
+///
+/// # Arguments
+///
+/// * `unit_price` - Price sent with the transaction, in micro-lamports per compute unit
+/// * `units_consumed` - Compute units consumed by the confirmed transaction
+///
+/// # Returns
+///
+/// Returns the priority fee in lamports, rounded up as the runtime does
+pub fn priority_fee_lamports(unit_price: u64, units_consumed: u64) -> u64 {
+    let micro_lamports = unit_price as u128 * units_consumed as u128;
+    micro_lamports
+        .div_ceil(MICRO_LAMPORTS_PER_LAMPORT)
+        .try_into()
+        .unwrap_or(u64::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fee(price: u64) -> Option<PriorityFee> {
+        Some(PriorityFee {
+            limit: Some(100_000),
+            price: Some(price),
+        })
+    }
+
+    #[test]
+    fn test_priority_fee_lamports() {
+        assert_eq!(priority_fee_lamports(0, 200_000), 0);
+        assert_eq!(priority_fee_lamports(1_000_000, 50_000), 50_000);
+        assert_eq!(priority_fee_lamports(1, 1), 1);
+        assert_eq!(priority_fee_lamports(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn test_spend_report() {
+        let tracker = FeeSpendTracker::new(None);
+        let start = Instant::now();
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+
+        tracker.record(start, Some(first), 1_000);
+        tracker.record(start + Duration::from_secs(10), Some(first), 2_000);
+        tracker.record(start + Duration::from_secs(20), Some(second), 500);
+        tracker.record(start + Duration::from_secs(30), None, 100);
+
+        let report = tracker.report(start + Duration::from_secs(60));
+        assert_eq!(report.session_lamports, 3_600);
+        assert_eq!(report.last_hour_lamports, 3_600);
+        assert_eq!(report.transactions, 4);
+        assert_eq!(report.per_mint[&first], 3_000);
+        assert_eq!(report.per_mint[&second], 500);
+        assert_eq!(report.per_mint.len(), 2);
+
+        // Fees leave the hourly total once they are older than the window
+        let report = tracker.report(start + SPEND_WINDOW + Duration::from_secs(15));
+        assert_eq!(report.session_lamports, 3_600);
+        assert_eq!(report.last_hour_lamports, 600);
+
+        // Without a budget, fees are never changed
+        assert_eq!(tracker.apply_budget(start, fee(5_000)).unwrap(), fee(5_000));
+    }
+
+    #[test]
+    fn test_budget_actions() {
+        let start = Instant::now();
+        let tracker = FeeSpendTracker::new(Some(FeeBudget::new(10_000, BudgetAction::Block)));
+        tracker.record(start, None, 9_999);
+        assert_eq!(tracker.apply_budget(start, fee(5_000)).unwrap(), fee(5_000));
+
+        tracker.record(start, None, 1);
+        assert!(matches!(
+            tracker.apply_budget(start, fee(5_000)),
+            Err(ClientError::PriorityFeeBudgetExceeded {
+                spent: 10_000,
+                budget: 10_000
+            })
+        ));
+        assert!(tracker.apply_budget(start, None).is_err());
+        assert_eq!(tracker.report(start).blocked, 2);
+
+        // Trades are allowed again once the spending leaves the window
+        assert!(tracker
+            .apply_budget(start + SPEND_WINDOW, fee(5_000))
+            .is_ok());
+
+        let tracker =
+            FeeSpendTracker::new(Some(FeeBudget::new(10_000, BudgetAction::Downgrade(1_000))));
+        tracker.record(start, None, 10_000);
+        let downgraded = tracker.apply_budget(start, fee(5_000)).unwrap().unwrap();
+        assert_eq!(downgraded.price, Some(1_000));
+        assert_eq!(downgraded.limit, Some(100_000));
+
+        // Fees already at or below the tier, or without a price, are sent unchanged
+        assert_eq!(tracker.apply_budget(start, fee(500)).unwrap(), fee(500));
+        assert_eq!(tracker.apply_budget(start, None).unwrap(), None);
+        assert_eq!(tracker.report(start).downgraded, 1);
+    }
+}
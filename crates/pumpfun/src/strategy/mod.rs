@@ -0,0 +1,539 @@
+//! Execution strategies that split one order into several trades
+//!
+//! A large buy moves the curve against itself. [`TwapExecutor`] spreads a target amount of SOL
+//! over a number of smaller child buys spaced some slots apart, sending each through
+//! [`PumpFun::buy`] with its own slippage bound, and reports the aggregate fill against what a
+//! single buy would have received when the order started:
+//!
+//! - `ChildSizing`: How the target amount is split between the child buys.
+//! - `TwapExecutor`: The order settings, and the executor that runs it.
+//! - `ChildFill`: The outcome of one child buy.
+//! - `TwapStop`: Why the executor stopped.
+//! - `TwapReport`: The aggregate fill, with the single-shot comparison.
+//!
+//! The executor holds the client's lock on the mint for the whole order, so two orders for the
+//! same mint cannot interleave their children. Other trades on the mint made directly through
+//! the client are not blocked.
+//!
+//! A child whose transaction may still land is never rebuilt: the same signed transaction is
+//! resent until it is found processed or its blockhash expires, so a retry cannot buy twice.
+
+use crate::{
+    error::ClientError,
+    params::BuyParams,
+    usage::{scoped, Operation},
+    utils::SlippageMode,
+    PriorityFee, PumpFun,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_sdk::{
+    commitment_config::CommitmentConfig, pubkey::Pubkey, signature::Signature,
+    transaction::Transaction,
+};
+
+/// How the target amount of an order is split between its child buys
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildSizing {
+    /// Every child spends the same amount, with the remainder added to the last one
+    Equal,
+    /// Each child's share deviates randomly from an equal split by up to the given fraction,
+    /// reproducible from the seed
+    Randomized {
+        /// Largest deviation from an equal share in basis points, capped at 10000
+        jitter_bps: u64,
+        /// Seed of the random number generator
+        seed: u64,
+    },
+}
+
+/// Outcome of one child buy of an order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChildFill {
+    /// Position of the child in the order
+    pub index: usize,
+    /// SOL the child was sized to spend in lamports, excluding fees
+    pub sol_amount: u64,
+    /// Signature of the confirmed buy
+    pub signature: Signature,
+    /// Tokens received in base units, None if the trade event could not be found
+    pub token_amount: Option<u64>,
+    /// SOL that entered the curve in lamports, None if the trade event could not be found
+    pub sol_spent: Option<u64>,
+    /// Number of attempts the buy took
+    pub attempts: u32,
+}
+
+/// Why an order stopped
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum TwapStop {
+    /// Every child buy was executed
+    Completed,
+    /// The spot price rose above the ceiling before the next child
+    PriceCeiling {
+        /// Position of the child that was not sent
+        index: usize,
+        /// Spot price observed in lamports per token base unit
+        price: f64,
+    },
+    /// The curve completed before the next child
+    CurveComplete {
+        /// Position of the child that was not sent
+        index: usize,
+    },
+    /// A child buy failed on every attempt
+    ChildFailed {
+        /// Position of the child that failed
+        index: usize,
+        /// Number of attempts made
+        attempts: u32,
+    },
+}
+
+/// Aggregate outcome of an order
+#[derive(Debug)]
+pub struct TwapReport {
+    /// Mint the order bought
+    pub mint: Pubkey,
+    /// Fill of each child buy that confirmed, in order
+    pub fills: Vec<ChildFill>,
+    /// Why the order stopped
+    pub stop: TwapStop,
+    /// Error of the last attempt when a child failed
+    pub failure: Option<ClientError>,
+    /// Target amount of SOL of the order in lamports, excluding fees
+    pub target_sol: u64,
+    /// SOL that entered the curve across all fills in lamports
+    pub total_sol: u64,
+    /// Tokens received across all fills in base units
+    pub total_tokens: u64,
+    /// Tokens a single buy of the target amount would have received when the order started
+    pub single_shot_tokens: u64,
+}
+
+impl TwapReport {
+    /// Whether every child buy was executed
+    pub fn is_complete(&self) -> bool {
+        self.stop == TwapStop::Completed
+    }
+
+    /// Gets the average price paid in lamports per token base unit, 0 if nothing was bought
+    pub fn average_price(&self) -> f64 {
+        if self.total_tokens == 0 {
+            0.0
+        } else {
+            self.total_sol as f64 / self.total_tokens as f64
+        }
+    }
+
+    /// Gets the average price a single buy of the target amount would have paid when the order
+    /// started, in lamports per token base unit
+    pub fn single_shot_price(&self) -> f64 {
+        if self.single_shot_tokens == 0 {
+            0.0
+        } else {
+            self.target_sol as f64 / self.single_shot_tokens as f64
+        }
+    }
+
+    /// Gets how much cheaper the average price was than the single-shot price, as a fraction
+    ///
+    /// Positive when splitting the order paid less per token. Other traders moving the curve
+    /// between children affect this as much as the order's own price impact.
+    pub fn improvement_vs_single_shot(&self) -> f64 {
+        let single_shot = self.single_shot_price();
+        if single_shot == 0.0 || self.total_tokens == 0 {
+            0.0
+        } else {
+            (single_shot - self.average_price()) / single_shot
+        }
+    }
+}
+
+/// Splits a buy into smaller child buys spaced over slots
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TwapExecutor {
+    /// Target amount of SOL to spend in lamports, excluding fees
+    pub target_sol: u64,
+    /// Number of child buys
+    pub children: usize,
+    /// Slots between the starts of consecutive children
+    pub spacing_slots: u64,
+    /// How the target amount is split between the children
+    pub sizing: ChildSizing,
+    /// Slippage bound applied to each child buy, defaults to the buy path's default
+    pub slippage: Option<SlippageMode>,
+    /// Spot price in lamports per token base unit above which no further child is sent
+    pub price_ceiling: Option<f64>,
+    /// Number of times a failed child buy is retried before the order gives up
+    pub max_retries: u32,
+    /// Priority fee applied to each child buy
+    pub priority_fee: Option<PriorityFee>,
+}
+
+impl TwapExecutor {
+    /// Creates an order split equally into child buys sent on consecutive slots
+    ///
+    /// # Arguments
+    ///
+    /// * `target_sol` - Total SOL to spend in lamports, excluding fees
+    /// * `children` - Number of child buys, at least 1
+    pub fn new(target_sol: u64, children: usize) -> Self {
+        Self {
+            target_sol,
+            children: children.max(1),
+            spacing_slots: 1,
+            sizing: ChildSizing::Equal,
+            slippage: None,
+            price_ceiling: None,
+            max_retries: 2,
+            priority_fee: None,
+        }
+    }
+
+    /// Sets the slots between the starts of consecutive children
+    pub fn with_spacing_slots(mut self, slots: u64) -> Self {
+        self.spacing_slots = slots;
+        self
+    }
+
+    /// Sets how the target amount is split between the children
+    pub fn with_sizing(mut self, sizing: ChildSizing) -> Self {
+        self.sizing = sizing;
+        self
+    }
+
+    /// Sets the slippage bound applied to each child buy
+    pub fn with_slippage(mut self, slippage: SlippageMode) -> Self {
+        self.slippage = Some(slippage);
+        self
+    }
+
+    /// Sets the spot price above which no further child is sent
+    ///
+    /// # Arguments
+    ///
+    /// * `price` - Price ceiling in lamports per token base unit
+    pub fn with_price_ceiling(mut self, price: f64) -> Self {
+        self.price_ceiling = Some(price);
+        self
+    }
+
+    /// Sets the number of times a failed child buy is retried
+    pub fn with_max_retries(mut self, retries: u32) -> Self {
+        self.max_retries = retries;
+        self
+    }
+
+    /// Sets the priority fee applied to each child buy
+    pub fn with_priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.priority_fee = Some(priority_fee);
+        self
+    }
+
+    /// Splits the target amount between the children
+    ///
+    /// # Returns
+    ///
+    /// Returns the SOL each child spends in lamports, in order, summing to the target amount
+    pub fn child_amounts(&self) -> Vec<u64> {
+        let children = self.children.max(1);
+        let weights: Vec<u64> = match self.sizing {
+            ChildSizing::Equal => vec![1; children],
+            ChildSizing::Randomized { jitter_bps, seed } => {
+                let jitter = jitter_bps.min(10000);
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..children)
+                    .map(|_| rng.gen_range(10000 - jitter..=10000 + jitter).max(1))
+                    .collect()
+            }
+        };
+
+        let total_weight: u128 = weights.iter().map(|weight| *weight as u128).sum();
+        let mut amounts: Vec<u64> = weights
+            .iter()
+            .map(|weight| ((self.target_sol as u128) * (*weight as u128) / total_weight) as u64)
+            .collect();
+        let allocated: u64 = amounts.iter().sum();
+        if let Some(last) = amounts.last_mut() {
+            *last += self.target_sol - allocated;
+        }
+
+        amounts
+    }
+
+    /// Runs the order
+    ///
+    /// Takes the client's lock on the mint, quotes a single buy of the target amount for
+    /// comparison, then sends each child the way [`PumpFun::buy`] does once its slot is reached.
+    /// Before each child the curve is re-read, and the order stops if the curve completed or
+    /// its spot price is above the ceiling. A failed child is retried up to `max_retries`
+    /// times, resending the same transaction while it may still land; if it still fails the
+    /// order stops and reports the fills so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `client` - Client used to send the buys
+    /// * `mint` - Public key of the token mint to buy
+    ///
+    /// # Returns
+    ///
+    /// Returns the aggregate fill, complete or partial, or a ClientError::MintLocked if another
+    /// order holds the mint, or a ClientError if the order could not be started
+    pub async fn execute(
+        &self,
+        client: &PumpFun<'_>,
+        mint: &Pubkey,
+    ) -> Result<TwapReport, ClientError> {
+        scoped(Operation::Buy, async move {
+            let _lock = client.lock_mint(mint)?;
+
+            let start_curve = client.get_bonding_curve_account(mint)?;
+            let single_shot_tokens = start_curve
+                .get_buy_price(self.target_sol)
+                .map_err(ClientError::BondingCurveError)?;
+            let start_slot = client
+                .rpc
+                .get_slot()
+                .map_err(ClientError::SolanaClientError)?;
+
+            let mut report = TwapReport {
+                mint: *mint,
+                fills: Vec::with_capacity(self.children),
+                stop: TwapStop::Completed,
+                failure: None,
+                target_sol: self.target_sol,
+                total_sol: 0,
+                total_tokens: 0,
+                single_shot_tokens,
+            };
+
+            for (index, sol_amount) in self.child_amounts().into_iter().enumerate() {
+                if index > 0 && self.spacing_slots > 0 {
+                    client
+                        .await_slot(start_slot + self.spacing_slots * index as u64)
+                        .await?;
+                }
+
+                let curve = if index == 0 {
+                    start_curve.clone()
+                } else {
+                    client.get_bonding_curve_account(mint)?
+                };
+                if curve.complete {
+                    report.stop = TwapStop::CurveComplete { index };
+                    break;
+                }
+                let price = if curve.virtual_token_reserves == 0 {
+                    0.0
+                } else {
+                    curve.virtual_sol_reserves as f64 / curve.virtual_token_reserves as f64
+                };
+                if self.price_ceiling.is_some_and(|ceiling| price > ceiling) {
+                    report.stop = TwapStop::PriceCeiling { index, price };
+                    break;
+                }
+                if sol_amount == 0 {
+                    continue;
+                }
+
+                let mut attempts = 0;
+                let signature = match self
+                    .send_child(client, mint, index, sol_amount, &mut attempts)
+                    .await
+                {
+                    Ok(signature) => signature,
+                    Err(err) => {
+                        report.failure = Some(err);
+                        report.stop = TwapStop::ChildFailed { index, attempts };
+                        break;
+                    }
+                };
+
+                let event = client.get_trade_event(&signature, mint).ok().flatten();
+                let fill = ChildFill {
+                    index,
+                    sol_amount,
+                    signature,
+                    token_amount: event.as_ref().map(|event| event.token_amount),
+                    sol_spent: event.as_ref().map(|event| event.sol_amount),
+                    attempts,
+                };
+                report.total_sol += fill.sol_spent.unwrap_or(sol_amount);
+                report.total_tokens += fill.token_amount.unwrap_or_default();
+                report.fills.push(fill);
+            }
+
+            Ok(report)
+        })
+        .await
+    }
+
+    /// Sends one child buy, retrying without ever having two of its transactions in flight
+    ///
+    /// After a failed attempt the transaction's status is read. A transaction that landed is
+    /// the fill, and one that failed on chain bought nothing, so the next attempt rebuilds it.
+    /// Otherwise the same transaction is resent while its blockhash is valid, since it may
+    /// still land, and only rebuilt once it no longer can.
+    async fn send_child(
+        &self,
+        client: &PumpFun<'_>,
+        mint: &Pubkey,
+        index: usize,
+        sol_amount: u64,
+        attempts: &mut u32,
+    ) -> Result<Signature, ClientError> {
+        let mut params = BuyParams::new(*mint, sol_amount);
+        params.slippage = self.slippage;
+        params.priority_fee = client.apply_fee_budget(self.priority_fee)?;
+
+        let mut pending: Option<Transaction> = None;
+        loop {
+            *attempts += 1;
+            let result = match &pending {
+                Some(transaction) => client.send_transaction(transaction).await,
+                None => match Self::sign_child(client, &params).await {
+                    Ok(transaction) => {
+                        let result = client.send_transaction(&transaction).await;
+                        pending = Some(transaction);
+                        result
+                    }
+                    Err(err) => Err(err),
+                },
+            };
+            let err = match result {
+                Ok(signature) => {
+                    client
+                        .track_fee_spend(&signature, Some(mint), params.priority_fee)
+                        .await;
+                    return Ok(signature);
+                }
+                Err(err) => err,
+            };
+
+            if let Some(transaction) = pending.take() {
+                let signature = transaction.signatures[0];
+                match client.processed_status(&signature).await {
+                    Some(Ok(())) => {
+                        client
+                            .track_fee_spend(&signature, Some(mint), params.priority_fee)
+                            .await;
+                        return Ok(signature);
+                    }
+                    Some(Err(_)) => {}
+                    None => {
+                        let expired = !client
+                            .async_rpc()
+                            .is_blockhash_valid(
+                                &transaction.message.recent_blockhash,
+                                CommitmentConfig::processed(),
+                            )
+                            .await
+                            .unwrap_or(true);
+                        if !expired {
+                            pending = Some(transaction);
+                        }
+                    }
+                }
+            }
+
+            if *attempts > self.max_retries {
+                return Err(err);
+            }
+            let redaction = client.redaction_policy();
+            tracing::debug!(
+                mint = %redaction.mint(mint),
+                sol_amount = %redaction.amount(sol_amount),
+                index,
+                attempts = *attempts,
+                resending = pending.is_some(),
+                "TWAP child failed: {}",
+                err
+            );
+        }
+    }
+
+    /// Builds and signs a child buy at the current state of the curve
+    async fn sign_child(
+        client: &PumpFun<'_>,
+        params: &BuyParams,
+    ) -> Result<Transaction, ClientError> {
+        // Build and sign with the same keypair, even if the signer rotates in between
+        client
+            .trade_scoped(Operation::Buy, async {
+                let request = client.buy_request(params).await?;
+                client.sign_request(&request, &[]).await
+            })
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_child_amounts() {
+        let equal = TwapExecutor::new(1_000_000_003, 4);
+        assert_eq!(
+            equal.child_amounts(),
+            vec![250_000_000, 250_000_000, 250_000_000, 250_000_003]
+        );
+        assert_eq!(TwapExecutor::new(10, 0).child_amounts(), vec![10]);
+
+        let randomized = TwapExecutor::new(1_000_000_000, 8).with_sizing(ChildSizing::Randomized {
+            jitter_bps: 2_000,
+            seed: 7,
+        });
+        let amounts = randomized.child_amounts();
+        assert_eq!(amounts.len(), 8);
+        assert_eq!(amounts.iter().sum::<u64>(), 1_000_000_000);
+        assert_eq!(amounts, randomized.child_amounts());
+        assert_ne!(amounts, TwapExecutor::new(1_000_000_000, 8).child_amounts());
+
+        // Shares stay within the jitter of an equal split, up to normalization
+        for amount in &amounts {
+            assert!((80_000_000..=180_000_000).contains(amount), "{}", amount);
+        }
+    }
+
+    #[test]
+    fn test_report_prices() {
+        let fill = ChildFill {
+            index: 0,
+            sol_amount: 500,
+            signature: Signature::new_unique(),
+            token_amount: Some(1_000),
+            sol_spent: Some(500),
+            attempts: 1,
+        };
+        let report = TwapReport {
+            mint: Pubkey::new_unique(),
+            fills: vec![fill, ChildFill { index: 1, ..fill }],
+            stop: TwapStop::Completed,
+            failure: None,
+            target_sol: 1_000,
+            total_sol: 1_000,
+            total_tokens: 2_000,
+            single_shot_tokens: 1_600,
+        };
+        assert!(report.is_complete());
+        assert_eq!(report.average_price(), 0.5);
+        assert_eq!(report.single_shot_price(), 0.625);
+        assert!((report.improvement_vs_single_shot() - 0.2).abs() < 1e-12);
+
+        let partial = TwapReport {
+            fills: Vec::new(),
+            stop: TwapStop::ChildFailed {
+                index: 0,
+                attempts: 3,
+            },
+            total_sol: 0,
+            total_tokens: 0,
+            ..report
+        };
+        assert!(!partial.is_complete());
+        assert_eq!(partial.average_price(), 0.0);
+        assert_eq!(partial.improvement_vs_single_shot(), 0.0);
+    }
+}
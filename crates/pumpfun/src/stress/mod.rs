@@ -0,0 +1,515 @@
+//! Randomized buy/sell interleavings for validating the client against a live program
+//!
+//! [`run`] drives a seeded sequence of trades from several wallets through a
+//! [`CurveExecutor`], which submits each trade to a program instance and reads back the curve.
+//! With the `program-test` feature, `testing::harness::HarnessExecutor` executes them on the
+//! program binary in an in-process bank. After every trade the on-chain curve must match the
+//! state predicted by [`BondingCurveAccount::simulate_sequence`] exactly. The sequence mixes
+//! dust trades, random buys, full-balance sells and buys large enough to straddle graduation.
+//!
+//! - `StressConfig`: Seed, wallet count and sizing of a run.
+//! - `StressStep`: A single generated trade.
+//! - `CurveExecutor`: Executes trades against a program instance.
+//! - `Divergence`: The first trade whose outcome did not match the prediction.
+//! - `StressReport`: Summary of a run that matched throughout.
+//!
+//! Every run is reproducible from its seed, which divergences report.
+
+use crate::accounts::{BondingCurveAccount, PlannedTrade, TradeFill};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Configuration of a stress run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StressConfig {
+    /// Seed the whole run is reproducible from
+    pub seed: u64,
+    /// Number of wallets trading
+    pub wallets: usize,
+    /// Maximum number of trades; the run ends early if the curve graduates
+    pub trades: usize,
+    /// Largest random buy in lamports, excluding fees
+    pub max_buy: u64,
+    /// Fee charged on each trade in basis points
+    pub fee_basis_points: u64,
+}
+
+impl Default for StressConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            wallets: 4,
+            trades: 200,
+            max_buy: 5_000_000_000,
+            fee_basis_points: 100,
+        }
+    }
+}
+
+/// A generated trade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StressStep {
+    /// Index of the wallet making the trade
+    pub wallet: usize,
+    /// Trade to make
+    pub trade: PlannedTrade,
+}
+
+/// Executes trades against a program instance
+pub trait CurveExecutor {
+    /// Submits a trade and reads back the curve once it lands
+    ///
+    /// # Arguments
+    ///
+    /// * `step` - Wallet and trade to execute
+    /// * `fill` - Fill predicted by the client, from which the instruction arguments are built
+    ///
+    /// # Returns
+    ///
+    /// Returns the curve after the trade, or a description of why it failed
+    fn execute(
+        &mut self,
+        step: &StressStep,
+        fill: &TradeFill,
+    ) -> Result<BondingCurveAccount, String>;
+}
+
+/// The first trade whose outcome did not match the prediction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// Seed of the run
+    pub seed: u64,
+    /// Position of the trade in the run
+    pub index: usize,
+    /// Trade that diverged
+    pub step: StressStep,
+    /// Curve predicted after the trade
+    pub expected: BondingCurveAccount,
+    /// Curve read back after the trade, or why the trade failed
+    pub actual: Result<BondingCurveAccount, String>,
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "divergence at trade {} (seed {}): {:?} from wallet {}: expected {:?}, got {:?}",
+            self.index, self.seed, self.step.trade, self.step.wallet, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Summary of a run that matched the predictions throughout
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StressReport {
+    /// Number of buys executed
+    pub buys: usize,
+    /// Number of sells executed
+    pub sells: usize,
+    /// Whether the curve graduated during the run
+    pub graduated: bool,
+    /// Token balance of each wallet at the end of the run in base units
+    pub balances: Vec<u64>,
+    /// Curve at the end of the run
+    pub final_curve: BondingCurveAccount,
+}
+
+/// Runs a stress sequence against a program instance
+///
+/// # Arguments
+///
+/// * `config` - Seed and sizing of the run
+/// * `curve` - Curve the program instance starts from
+/// * `executor` - Executes the trades on the program instance
+///
+/// # Returns
+///
+/// Returns a summary of the run, or the first divergence from the predicted state
+pub fn run(
+    config: &StressConfig,
+    curve: &BondingCurveAccount,
+    executor: &mut impl CurveExecutor,
+) -> Result<StressReport, Divergence> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut balances = vec![0u64; config.wallets.max(1)];
+    let mut curve = curve.clone();
+    let mut buys = 0;
+    let mut sells = 0;
+
+    for index in 0..config.trades {
+        if curve.complete {
+            break;
+        }
+
+        let step = next_step(&mut rng, config, &curve, &balances);
+        // Skip trades the client would never send: infeasible ones and buys of no tokens
+        let Ok(outcome) = curve.simulate_sequence(&[step.trade], config.fee_basis_points) else {
+            continue;
+        };
+        let fill = outcome.fills[0];
+        if fill.token_amount == 0 {
+            continue;
+        }
+
+        let actual = executor.execute(&step, &fill);
+        if actual.as_ref() != Ok(&outcome.final_curve) {
+            return Err(Divergence {
+                seed: config.seed,
+                index,
+                step,
+                expected: outcome.final_curve,
+                actual,
+            });
+        }
+
+        match step.trade {
+            PlannedTrade::Buy { .. } => {
+                balances[step.wallet] += fill.token_amount;
+                buys += 1;
+            }
+            PlannedTrade::Sell { .. } => {
+                balances[step.wallet] -= fill.token_amount;
+                sells += 1;
+            }
+        }
+        curve = outcome.final_curve;
+    }
+
+    Ok(StressReport {
+        buys,
+        sells,
+        graduated: curve.complete,
+        balances,
+        final_curve: curve,
+    })
+}
+
+/// Picks the next trade, favouring the edge cases the program is most likely to disagree on
+fn next_step(
+    rng: &mut StdRng,
+    config: &StressConfig,
+    curve: &BondingCurveAccount,
+    balances: &[u64],
+) -> StressStep {
+    let wallet = rng.gen_range(0..balances.len());
+    let balance = balances[wallet];
+    let random_buy = |rng: &mut StdRng| PlannedTrade::Buy {
+        sol_amount: rng.gen_range(1..=config.max_buy.max(1)),
+    };
+
+    let trade = match rng.gen_range(0..100) {
+        // Dust buys of at most 100 lamports
+        0..=9 => PlannedTrade::Buy {
+            sol_amount: rng.gen_range(1..=100),
+        },
+        // Buys around the size that completes the curve, on either side of it
+        10..=11 => {
+            let to_graduate = curve.sol_to_graduate();
+            PlannedTrade::Buy {
+                sol_amount: to_graduate
+                    .saturating_sub(1_000)
+                    .saturating_add(rng.gen_range(0..=2_000))
+                    .max(1),
+            }
+        }
+        12..=21 if balance > 0 => PlannedTrade::Sell { token_amount: 1 },
+        22..=36 if balance > 0 => PlannedTrade::Sell {
+            token_amount: balance,
+        },
+        37..=54 if balance > 0 => PlannedTrade::Sell {
+            token_amount: rng.gen_range(1..=balance),
+        },
+        _ => random_buy(rng),
+    };
+
+    StressStep { wallet, trade }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::BigUint;
+
+    const TOTAL_SUPPLY: u64 = 1_000_000_000_000_000;
+
+    fn get_curve() -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            0,
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            0,
+            TOTAL_SUPPLY,
+            false,
+        )
+    }
+
+    fn big(value: u64) -> BigUint {
+        BigUint::from(value)
+    }
+
+    fn small(value: BigUint) -> Result<u64, String> {
+        u64::try_from(&value).map_err(|_| "Overflow".to_string())
+    }
+
+    /// Restates the program's buy and sell handlers in unbounded integers
+    ///
+    /// Prices are derived from the constant product rather than from the client's closed form:
+    /// a buy of `amount` tokens must leave a product strictly above the old one, and a sell
+    /// pays out the most SOL that keeps the product from decreasing. Instruction arguments are
+    /// built from the client's fill the same way the client builds them, so slippage bounds,
+    /// wallet balances and the curve's real reserves are all enforced as the program would.
+    struct ProgramModel {
+        curve: BondingCurveAccount,
+        fee_basis_points: u64,
+        /// Token balance of each wallet in base units
+        balances: Vec<u64>,
+        /// Fees collected so far in lamports
+        fees: BigUint,
+        /// Lamports added to every buy's cost, to inject a fault
+        skew: u64,
+    }
+
+    impl ProgramModel {
+        fn new(curve: &BondingCurveAccount, fee_basis_points: u64) -> Self {
+            Self {
+                curve: curve.clone(),
+                fee_basis_points,
+                balances: Vec::new(),
+                fees: BigUint::default(),
+                skew: 0,
+            }
+        }
+
+        fn fee(&self, sol: &BigUint) -> BigUint {
+            sol * big(self.fee_basis_points) / 10_000u32
+        }
+
+        fn buy(&mut self, wallet: usize, amount: u64, max_sol_cost: u64) -> Result<(), String> {
+            let curve = &self.curve;
+            if amount > curve.real_token_reserves {
+                return Err("NotEnoughTokensToBuy".to_string());
+            }
+            let product = big(curve.virtual_sol_reserves) * big(curve.virtual_token_reserves);
+            let remaining = big(curve.virtual_token_reserves) - big(amount);
+            if remaining == BigUint::default() {
+                return Err("NotEnoughTokensToBuy".to_string());
+            }
+            // Smallest SOL reserves whose product with the remaining tokens exceeds the old one
+            let new_sol = product / &remaining + 1u32;
+            let cost = new_sol - big(curve.virtual_sol_reserves) + big(self.skew);
+            let fee = self.fee(&cost);
+            if &cost + &fee > big(max_sol_cost) {
+                return Err("TooMuchSolRequired".to_string());
+            }
+
+            let cost = small(cost)?;
+            let curve = &mut self.curve;
+            curve.virtual_token_reserves -= amount;
+            curve.real_token_reserves -= amount;
+            curve.virtual_sol_reserves = small(big(curve.virtual_sol_reserves) + big(cost))?;
+            curve.real_sol_reserves = small(big(curve.real_sol_reserves) + big(cost))?;
+            curve.complete = curve.real_token_reserves == 0;
+            self.balances[wallet] += amount;
+            self.fees += fee;
+            Ok(())
+        }
+
+        fn sell(&mut self, wallet: usize, amount: u64, min_sol_output: u64) -> Result<(), String> {
+            if amount > self.balances[wallet] {
+                return Err("InsufficientFunds".to_string());
+            }
+            let curve = &self.curve;
+            let product = big(curve.virtual_sol_reserves) * big(curve.virtual_token_reserves);
+            let new_tokens = big(curve.virtual_token_reserves) + big(amount);
+            // Smallest SOL reserves whose product with the new tokens is at least the old one
+            let new_sol = (product + &new_tokens - 1u32) / new_tokens;
+            let output = big(curve.virtual_sol_reserves) - new_sol;
+            if output > big(curve.real_sol_reserves) {
+                return Err("NotEnoughSolInCurve".to_string());
+            }
+            let fee = self.fee(&output);
+            if &output - &fee < big(min_sol_output) {
+                return Err("TooLittleSolReceived".to_string());
+            }
+
+            let output = small(output)?;
+            let curve = &mut self.curve;
+            curve.virtual_token_reserves = small(big(curve.virtual_token_reserves) + big(amount))?;
+            curve.real_token_reserves = small(big(curve.real_token_reserves) + big(amount))?;
+            curve.virtual_sol_reserves -= output;
+            curve.real_sol_reserves -= output;
+            self.balances[wallet] -= amount;
+            self.fees += fee;
+            Ok(())
+        }
+    }
+
+    impl CurveExecutor for ProgramModel {
+        fn execute(
+            &mut self,
+            step: &StressStep,
+            fill: &TradeFill,
+        ) -> Result<BondingCurveAccount, String> {
+            if self.curve.complete {
+                return Err("BondingCurveComplete".to_string());
+            }
+            if self.balances.len() <= step.wallet {
+                self.balances.resize(step.wallet + 1, 0);
+            }
+            match step.trade {
+                PlannedTrade::Buy { .. } => self.buy(
+                    step.wallet,
+                    fill.token_amount,
+                    fill.sol_amount.saturating_add(fill.fee),
+                )?,
+                PlannedTrade::Sell { .. } => self.sell(
+                    step.wallet,
+                    fill.token_amount,
+                    fill.sol_amount.saturating_sub(fill.fee),
+                )?,
+            }
+            Ok(self.curve.clone())
+        }
+    }
+
+    /// Draws a curve part way along its life, as a run may start from any live curve
+    fn random_curve(rng: &mut StdRng) -> BondingCurveAccount {
+        let real_token_reserves = rng.gen_range(1..=793_100_000_000_000);
+        let virtual_token_reserves = real_token_reserves + rng.gen_range(1..=1_000_000_000_000_000);
+        let virtual_sol_reserves = rng.gen_range(1_000..=500_000_000_000);
+        BondingCurveAccount::new(
+            0,
+            virtual_token_reserves,
+            virtual_sol_reserves,
+            real_token_reserves,
+            rng.gen_range(0..=virtual_sol_reserves),
+            TOTAL_SUPPLY,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_runs_match_the_program() {
+        for seed in 0..20 {
+            let config = StressConfig {
+                seed,
+                ..StressConfig::default()
+            };
+            let mut program = ProgramModel::new(&get_curve(), config.fee_basis_points);
+            let report = run(&config, &get_curve(), &mut program).unwrap();
+
+            assert!(report.buys > 0);
+            assert_eq!(report.final_curve, program.curve);
+            // Supply is conserved between the wallets and the curve
+            assert_eq!(
+                report.balances.iter().sum::<u64>() + report.final_curve.real_token_reserves,
+                793_100_000_000_000
+            );
+        }
+    }
+
+    #[test]
+    fn test_randomized_runs_match_the_program() {
+        let mut rng = StdRng::seed_from_u64(0x57e55);
+        let mut graduated = 0;
+        let mut sells = 0;
+
+        for seed in 0..300 {
+            let config = StressConfig {
+                seed,
+                wallets: rng.gen_range(1..=8),
+                trades: rng.gen_range(1..=300),
+                max_buy: 1u64 << rng.gen_range(0..40),
+                fee_basis_points: rng.gen_range(0..=1_000),
+            };
+            let curve = random_curve(&mut rng);
+            let mut program = ProgramModel::new(&curve, config.fee_basis_points);
+            let report = run(&config, &curve, &mut program).unwrap_or_else(|e| panic!("{}", e));
+
+            assert_eq!(report.final_curve, program.curve);
+            // Wallet balances agree with the program's, padded to the wallets that never traded
+            program.balances.resize(report.balances.len(), 0);
+            assert_eq!(report.balances, program.balances);
+            assert_eq!(
+                report.balances.iter().sum::<u64>() + report.final_curve.real_token_reserves,
+                curve.real_token_reserves
+            );
+            assert_eq!(report.graduated, program.curve.real_token_reserves == 0);
+            graduated += report.graduated as usize;
+            sells += report.sells;
+        }
+
+        // The generator reaches both graduation and the sell handler
+        assert!(graduated > 0);
+        assert!(sells > 0);
+    }
+
+    #[test]
+    fn test_program_rejects_trades_past_the_client_bounds() {
+        let mut program = ProgramModel::new(&get_curve(), 100);
+        program.balances = vec![0];
+        let fill = get_curve()
+            .simulate_sequence(
+                &[PlannedTrade::Buy {
+                    sol_amount: 1_000_000_000,
+                }],
+                100,
+            )
+            .unwrap()
+            .fills[0];
+
+        // One lamport less than the predicted cost including fees is too little
+        assert_eq!(
+            program.buy(0, fill.token_amount, fill.sol_amount + fill.fee - 1),
+            Err("TooMuchSolRequired".to_string())
+        );
+        program
+            .buy(0, fill.token_amount, fill.sol_amount + fill.fee)
+            .unwrap();
+        assert_eq!(
+            program.sell(0, fill.token_amount + 1, 0),
+            Err("InsufficientFunds".to_string())
+        );
+    }
+
+    #[test]
+    fn test_runs_are_reproducible() {
+        let config = StressConfig {
+            seed: 42,
+            trades: 500,
+            ..StressConfig::default()
+        };
+        let first = run(
+            &config,
+            &get_curve(),
+            &mut ProgramModel::new(&get_curve(), config.fee_basis_points),
+        )
+        .unwrap();
+        let second = run(
+            &config,
+            &get_curve(),
+            &mut ProgramModel::new(&get_curve(), config.fee_basis_points),
+        )
+        .unwrap();
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn test_divergence_reports_seed() {
+        let config = StressConfig {
+            seed: 7,
+            ..StressConfig::default()
+        };
+        let mut program = ProgramModel {
+            skew: 1,
+            ..ProgramModel::new(&get_curve(), config.fee_basis_points)
+        };
+        let divergence = run(&config, &get_curve(), &mut program).unwrap_err();
+
+        assert_eq!(divergence.index, 0);
+        assert!(matches!(divergence.step.trade, PlannedTrade::Buy { .. }));
+        assert!(divergence.to_string().contains("(seed 7)"));
+    }
+}
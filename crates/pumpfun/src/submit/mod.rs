@@ -0,0 +1,186 @@
+//! Classification of transaction submission errors
+//!
+//! Errors returned when sending a transaction need different reactions depending on their
+//! cause:
+//!
+//! - `SubmitErrorKind::BlockhashNotFound`: The blockhash expired; rebuild and re-sign.
+//! - `SubmitErrorKind::AlreadyProcessed`: The transaction already landed; treat it as sent.
+//! - `SubmitErrorKind::Transient`: The node or connection failed; resend as-is.
+//! - `SubmitErrorKind::Fatal`: Anything else; give up.
+//!
+//! `SubmitPath` records which reactions a submission needed, and is reported in
+//! [`crate::TradeOutcome`].
+
+use anchor_client::solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_custom_error::{
+        JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE,
+        JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED, JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+    },
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use anchor_client::solana_sdk::transaction::TransactionError;
+use std::time::Duration;
+
+/// Maximum number of times a transaction is sent before giving up
+pub const MAX_SUBMIT_ATTEMPTS: u32 = 4;
+
+/// Delay before resending after a transient error, multiplied by the attempt number
+pub const SUBMIT_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// Class of an error returned when sending a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SubmitErrorKind {
+    /// The transaction's blockhash is unknown to the node, usually because it expired
+    BlockhashNotFound,
+    /// The transaction has already been processed
+    AlreadyProcessed,
+    /// The node or connection failed in a way that may not happen again
+    Transient,
+    /// The transaction was rejected and resending it will not help
+    Fatal,
+}
+
+impl SubmitErrorKind {
+    /// Classifies an error returned when sending a transaction
+    ///
+    /// # Arguments
+    ///
+    /// * `err` - Error returned by the RPC client
+    ///
+    /// # Returns
+    ///
+    /// Returns the class of the error
+    pub fn classify(err: &ClientError) -> Self {
+        match err.get_transaction_error() {
+            Some(TransactionError::BlockhashNotFound) => return Self::BlockhashNotFound,
+            Some(TransactionError::AlreadyProcessed) => return Self::AlreadyProcessed,
+            Some(_) => return Self::Fatal,
+            None => {}
+        }
+
+        match err.kind() {
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) => Self::Transient,
+            ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => Self::Transient,
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, data, .. }) => {
+                match (code, data) {
+                    (_, RpcResponseErrorData::NodeUnhealthy { .. })
+                    | (
+                        &JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY
+                        | &JSON_RPC_SERVER_ERROR_BLOCK_NOT_AVAILABLE
+                        | &JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+                        _,
+                    ) => Self::Transient,
+                    _ => Self::Fatal,
+                }
+            }
+            _ => Self::Fatal,
+        }
+    }
+}
+
+/// Reactions a transaction submission needed before it was accepted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubmitPath {
+    /// Number of times a transaction was sent
+    pub attempts: u32,
+    /// Number of times the transaction was rebuilt with a fresh blockhash
+    pub rebuilds: u32,
+    /// Number of times the transaction was resent after a transient error
+    pub resends: u32,
+    /// Whether the node reported the transaction as already processed
+    pub already_processed: bool,
+}
+
+impl SubmitPath {
+    /// Returns whether the first send was accepted without any special handling
+    pub fn is_direct(&self) -> bool {
+        self.attempts <= 1 && !self.already_processed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_client::rpc_response::RpcSimulateTransactionResult;
+
+    /// Error payload of a `sendTransaction` call rejected in preflight
+    fn preflight_failure(err: TransactionError) -> ClientError {
+        let result: RpcSimulateTransactionResult = serde_json::from_value(serde_json::json!({
+            "err": err,
+            "logs": [],
+            "accounts": null,
+            "unitsConsumed": 0,
+            "returnData": null,
+            "innerInstructions": null,
+        }))
+        .unwrap();
+        ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32002,
+            message: "Transaction simulation failed".to_string(),
+            data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+        }))
+    }
+
+    fn response_error(code: i64, data: RpcResponseErrorData) -> ClientError {
+        ClientError::from(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code,
+            message: "Server error".to_string(),
+            data,
+        }))
+    }
+
+    #[test]
+    fn test_classify_submit_errors() {
+        assert_eq!(
+            SubmitErrorKind::classify(&preflight_failure(TransactionError::BlockhashNotFound)),
+            SubmitErrorKind::BlockhashNotFound
+        );
+        assert_eq!(
+            SubmitErrorKind::classify(&preflight_failure(TransactionError::AlreadyProcessed)),
+            SubmitErrorKind::AlreadyProcessed
+        );
+        assert_eq!(
+            SubmitErrorKind::classify(&preflight_failure(
+                TransactionError::InsufficientFundsForFee
+            )),
+            SubmitErrorKind::Fatal
+        );
+
+        // The same transaction errors can also arrive outside of a preflight payload
+        assert_eq!(
+            SubmitErrorKind::classify(&ClientError::from(ClientErrorKind::TransactionError(
+                TransactionError::AlreadyProcessed
+            ))),
+            SubmitErrorKind::AlreadyProcessed
+        );
+
+        assert_eq!(
+            SubmitErrorKind::classify(&response_error(
+                JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                RpcResponseErrorData::NodeUnhealthy {
+                    num_slots_behind: Some(42)
+                },
+            )),
+            SubmitErrorKind::Transient
+        );
+        assert_eq!(
+            SubmitErrorKind::classify(&response_error(
+                JSON_RPC_SERVER_ERROR_MIN_CONTEXT_SLOT_NOT_REACHED,
+                RpcResponseErrorData::Empty,
+            )),
+            SubmitErrorKind::Transient
+        );
+        assert_eq!(
+            SubmitErrorKind::classify(&ClientError::from(ClientErrorKind::Io(
+                std::io::Error::from(std::io::ErrorKind::ConnectionReset)
+            ))),
+            SubmitErrorKind::Transient
+        );
+        assert_eq!(
+            SubmitErrorKind::classify(&response_error(-32602, RpcResponseErrorData::Empty)),
+            SubmitErrorKind::Fatal
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Dashboard summaries of bonding curves
+//!
+//! This module contains the types used by [`crate::PumpFun::get_curve_summary`] and
+//! [`crate::PumpFun::get_token_snapshot`]:
+//!
+//! - `CurveSummary`: Price, market cap and progress of a curve, with optional creation data
+//!   and graduation estimate.
+//! - `CreationInfo`: When and by whom a token was created.
+//! - `SummaryEnricher`: Source of creation data, such as an event store or external index.
+//! - `TokenSnapshot`: Curve, mint and metadata of a token as of one slot, serializable as is.
+//!
+//! Creation data needs a walk of the curve's history to look up, so summaries never fetch it
+//! themselves. It is filled in from an attached enricher, or from creation data the client has
+//! already cached, and left as None otherwise.
+
+use crate::{
+    accounts::{BondingCurveAccount, GlobalAccount},
+    analytics::EtaEstimate,
+    error::ClientError,
+    export::TokenInfo,
+    screening::decode_mint,
+    TokenAge,
+};
+use anchor_client::solana_sdk::{pubkey::Pubkey, signature::Signature};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// When and by whom a token was created
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CreationInfo {
+    /// Unix timestamp of the creation transaction, if known
+    pub created_at: Option<i64>,
+    /// Wallet that created the token, if known
+    pub creator: Option<Pubkey>,
+    /// Signature of the creation transaction, if known
+    pub creation_signature: Option<Signature>,
+}
+
+impl From<TokenAge> for CreationInfo {
+    fn from(age: TokenAge) -> Self {
+        Self {
+            created_at: age.creation_block_time,
+            creator: None,
+            creation_signature: Some(age.creation_signature),
+        }
+    }
+}
+
+/// Source of token creation data for curve summaries
+///
+/// Implementations must answer from data they already hold; summaries are expected to be
+/// cheap to build.
+pub trait SummaryEnricher: Send + Sync {
+    /// Gets what is known about a token's creation
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    ///
+    /// # Returns
+    ///
+    /// Returns the known creation data, or None if the token is unknown
+    fn creation_info(&self, mint: &Pubkey) -> Option<CreationInfo>;
+}
+
+impl SummaryEnricher for HashMap<Pubkey, CreationInfo> {
+    fn creation_info(&self, mint: &Pubkey) -> Option<CreationInfo> {
+        self.get(mint).copied()
+    }
+}
+
+impl SummaryEnricher for HashMap<Pubkey, TokenAge> {
+    fn creation_info(&self, mint: &Pubkey) -> Option<CreationInfo> {
+        self.get(mint).copied().map(CreationInfo::from)
+    }
+}
+
+/// Summary of a bonding curve for dashboards
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurveSummary {
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// Spot price in lamports per token base unit
+    pub price: f64,
+    /// Market cap in lamports
+    pub market_cap_sol: u64,
+    /// SOL held by the curve in lamports
+    pub real_sol_reserves: u64,
+    /// Share of the initially sellable tokens bought so far, as a percentage
+    pub progress_pct: f64,
+    /// Whether the curve has completed
+    pub complete: bool,
+    /// Unix timestamp of the creation transaction, if known
+    pub created_at: Option<i64>,
+    /// Wallet that created the token, if known
+    pub creator: Option<Pubkey>,
+    /// Signature of the creation transaction, if known
+    pub creation_signature: Option<Signature>,
+    /// Estimate of when the curve completes, if the client tracks the mint's trades
+    pub graduation_eta: Option<EtaEstimate>,
+}
+
+impl CurveSummary {
+    /// Summarizes a bonding curve without creation data
+    ///
+    /// # Arguments
+    ///
+    /// * `mint` - Public key of the token mint
+    /// * `curve` - The token's bonding curve account
+    /// * `initial_real_token_reserves` - Sellable tokens the curve was created with
+    pub fn new(
+        mint: Pubkey,
+        curve: &BondingCurveAccount,
+        initial_real_token_reserves: u64,
+    ) -> Self {
+        let price = if curve.virtual_token_reserves == 0 {
+            0.0
+        } else {
+            curve.virtual_sol_reserves as f64 / curve.virtual_token_reserves as f64
+        };
+        let progress_pct = if curve.complete {
+            100.0
+        } else if initial_real_token_reserves == 0 {
+            0.0
+        } else {
+            initial_real_token_reserves.saturating_sub(curve.real_token_reserves) as f64
+                / initial_real_token_reserves as f64
+                * 100.0
+        };
+
+        Self {
+            mint,
+            price,
+            market_cap_sol: curve.get_market_cap_sol(),
+            real_sol_reserves: curve.real_sol_reserves,
+            progress_pct,
+            complete: curve.complete,
+            created_at: None,
+            creator: None,
+            creation_signature: None,
+            graduation_eta: None,
+        }
+    }
+
+    /// Fills in creation data the summary does not have yet
+    ///
+    /// # Arguments
+    ///
+    /// * `info` - Known creation data
+    pub fn enrich(&mut self, info: CreationInfo) {
+        self.created_at = self.created_at.or(info.created_at);
+        self.creator = self.creator.or(info.creator);
+        self.creation_signature = self.creation_signature.or(info.creation_signature);
+    }
+}
+
+/// Curve, mint and metadata of a token, read from accounts fetched together at one slot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TokenSnapshot {
+    /// Slot at which the accounts were read
+    pub slot: u64,
+    /// Public key of the token mint
+    pub mint: Pubkey,
+    /// The token's bonding curve account
+    pub bonding_curve: BondingCurveAccount,
+    /// Number of decimals of the mint
+    pub decimals: u8,
+    /// Current supply of the mint in base units
+    pub supply: u64,
+    /// Authority that can mint more tokens, None if renounced
+    pub mint_authority: Option<Pubkey>,
+    /// Authority that can freeze token accounts, None if renounced
+    pub freeze_authority: Option<Pubkey>,
+    /// Name of the token sanitized for display, None if it has no metadata account
+    pub name: Option<String>,
+    /// Token symbol sanitized for display, None if it has no metadata account
+    pub symbol: Option<String>,
+    /// Name of the token as stored in its metadata, None if it has no metadata account
+    #[serde(default)]
+    pub raw_name: Option<String>,
+    /// Token symbol as stored in its metadata, None if it has no metadata account
+    #[serde(default)]
+    pub raw_symbol: Option<String>,
+    /// URI of the off-chain metadata JSON, None if unset or the token has no metadata account
+    pub uri: Option<String>,
+    /// Spot price in lamports per token base unit
+    pub price: f64,
+    /// Market cap in lamports
+    pub market_cap_sol: u64,
+    /// Share of the initially sellable tokens bought so far, as a percentage
+    pub progress_pct: f64,
+}
+
+impl TokenSnapshot {
+    /// Builds a snapshot from the accounts of a token
+    ///
+    /// # Arguments
+    ///
+    /// * `slot` - Slot at which the accounts were read
+    /// * `mint` - Public key of the token mint
+    /// * `global` - The program's global account
+    /// * `bonding_curve` - The token's bonding curve account
+    /// * `mint_data` - Data of the mint account
+    /// * `metadata_data` - Data of the token's metadata account, if it exists
+    ///
+    /// # Returns
+    ///
+    /// Returns the snapshot, or a ClientError if the mint or metadata account cannot be decoded
+    pub fn from_accounts(
+        slot: u64,
+        mint: Pubkey,
+        global: &GlobalAccount,
+        bonding_curve: BondingCurveAccount,
+        mint_data: &[u8],
+        metadata_data: Option<&[u8]>,
+    ) -> Result<Self, ClientError> {
+        let mint_account = decode_mint(mint_data)?;
+        let info = metadata_data
+            .map(|data| TokenInfo::from_accounts(mint, mint_data, data))
+            .transpose()?;
+        let curve_summary =
+            CurveSummary::new(mint, &bonding_curve, global.initial_real_token_reserves);
+
+        Ok(Self {
+            slot,
+            mint,
+            bonding_curve,
+            decimals: mint_account.decimals,
+            supply: mint_account.supply,
+            mint_authority: mint_account.mint_authority.into(),
+            freeze_authority: mint_account.freeze_authority.into(),
+            name: info.as_ref().map(|info| info.name.clone()),
+            symbol: info.as_ref().map(|info| info.symbol.clone()),
+            raw_name: info.as_ref().map(|info| info.raw_name.clone()),
+            raw_symbol: info.as_ref().map(|info| info.raw_symbol.clone()),
+            uri: info.and_then(|info| info.uri),
+            price: curve_summary.price,
+            market_cap_sol: curve_summary.market_cap_sol,
+            progress_pct: curve_summary.progress_pct,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::program_option::COption;
+    use anchor_spl::token::spl_token::{solana_program::program_pack::Pack, state::Mint};
+
+    fn get_curve() -> BondingCurveAccount {
+        BondingCurveAccount::new(
+            0,
+            873_000_000_000_000,
+            36_872_852_233,
+            593_100_000_000_000,
+            6_872_852_233,
+            1_000_000_000_000_000,
+            false,
+        )
+    }
+
+    #[test]
+    fn test_unenriched_summary() {
+        let mint = Pubkey::new_unique();
+        let summary = CurveSummary::new(mint, &get_curve(), 793_100_000_000_000);
+
+        assert_eq!(summary.mint, mint);
+        assert_eq!(summary.real_sol_reserves, 6_872_852_233);
+        assert!((summary.progress_pct - 25.217).abs() < 0.001);
+        assert_eq!(summary.created_at, None);
+        assert_eq!(summary.creator, None);
+        assert_eq!(summary.creation_signature, None);
+    }
+
+    #[test]
+    fn test_enriched_summary() {
+        let mint = Pubkey::new_unique();
+        let creator = Pubkey::new_unique();
+        let signature = Signature::new_unique();
+
+        // An index that knows the creator fills in everything
+        let index: HashMap<Pubkey, CreationInfo> = HashMap::from([(
+            mint,
+            CreationInfo {
+                created_at: Some(1_700_000_000),
+                creator: Some(creator),
+                creation_signature: Some(signature),
+            },
+        )]);
+        let mut summary = CurveSummary::new(mint, &get_curve(), 793_100_000_000_000);
+        summary.enrich(index.creation_info(&mint).unwrap());
+        assert_eq!(summary.created_at, Some(1_700_000_000));
+        assert_eq!(summary.creator, Some(creator));
+        assert_eq!(summary.creation_signature, Some(signature));
+
+        // Cached token ages know when, but not who
+        let ages: HashMap<Pubkey, TokenAge> = HashMap::from([(
+            mint,
+            TokenAge {
+                creation_slot: 250_000_000,
+                creation_block_time: Some(1_700_000_000),
+                creation_signature: signature,
+            },
+        )]);
+        let mut summary = CurveSummary::new(mint, &get_curve(), 793_100_000_000_000);
+        summary.enrich(ages.creation_info(&mint).unwrap());
+        assert_eq!(summary.created_at, Some(1_700_000_000));
+        assert_eq!(summary.creator, None);
+        assert_eq!(summary.creation_signature, Some(signature));
+
+        assert_eq!(ages.creation_info(&Pubkey::new_unique()), None);
+    }
+
+    #[test]
+    fn test_token_snapshot() {
+        let mint = Pubkey::new_unique();
+        let global = GlobalAccount::new(
+            1,
+            true,
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            1_073_000_000_000_000,
+            30_000_000_000,
+            793_100_000_000_000,
+            1_000_000_000_000_000,
+            100,
+        );
+        let mut mint_data = vec![0; Mint::LEN];
+        Mint::pack(
+            Mint {
+                mint_authority: COption::None,
+                supply: 1_000_000_000_000_000,
+                decimals: 6,
+                is_initialized: true,
+                freeze_authority: COption::None,
+            },
+            &mut mint_data,
+        )
+        .unwrap();
+
+        let snapshot =
+            TokenSnapshot::from_accounts(300_000_000, mint, &global, get_curve(), &mint_data, None)
+                .unwrap();
+        let curve_summary = CurveSummary::new(mint, &get_curve(), 793_100_000_000_000);
+        assert_eq!(snapshot.slot, 300_000_000);
+        assert_eq!(snapshot.decimals, 6);
+        assert_eq!(snapshot.supply, 1_000_000_000_000_000);
+        assert_eq!(snapshot.mint_authority, None);
+        assert_eq!(snapshot.name, None);
+        assert_eq!(snapshot.raw_name, None);
+        assert_eq!(snapshot.price, curve_summary.price);
+        assert_eq!(snapshot.market_cap_sol, curve_summary.market_cap_sol);
+        assert_eq!(snapshot.progress_pct, curve_summary.progress_pct);
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let round_trip: TokenSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip, snapshot);
+
+        // Undecodable accounts fail rather than yield a partial snapshot
+        assert!(
+            TokenSnapshot::from_accounts(0, mint, &global, get_curve(), &[0; 8], None).is_err()
+        );
+        assert!(TokenSnapshot::from_accounts(
+            0,
+            mint,
+            &global,
+            get_curve(),
+            &mint_data,
+            Some(&[0; 8])
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,445 @@
+//! Helpers for swapping through AMMs that trade against wrapped SOL
+//!
+//! Once a bonding curve completes, its liquidity moves to an AMM pool that trades the token
+//! against wrapped SOL (WSOL). This module hides the WSOL account lifecycle so a swap can be
+//! paid for and settled in native SOL within a single transaction:
+//!
+//! 1. Create the owner's WSOL associated token account if needed.
+//! 2. Transfer the SOL to swap into it and sync its wrapped balance.
+//! 3. Run the caller's swap instructions.
+//! 4. Close the WSOL account, unwrapping the remaining balance and reclaiming its rent.
+//!
+//! Closing the owner's WSOL account would also unwrap any WSOL they already held, and that
+//! balance would then be counted as swap proceeds. When the owner already has a WSOL account,
+//! [`WsolSession`] instead swaps through a temporary WSOL account derived from the owner with a
+//! seed, leaving the existing account and its balance untouched. [`with_wsol_preserving`] keeps
+//! the owner's account too, by closing it only when the swap creates it.
+
+use anchor_spl::{
+    associated_token::{
+        get_associated_token_address,
+        spl_associated_token_account::instruction::create_associated_token_account_idempotent,
+    },
+    token::spl_token::{
+        instruction::{close_account, initialize_account3, sync_native},
+        native_mint,
+        solana_program::program_pack::Pack,
+        state::Account as TokenAccount,
+    },
+};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, rent::Rent, system_instruction};
+
+use crate::constants;
+
+/// Gets the owner's wrapped SOL associated token account
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the account owner
+///
+/// # Returns
+///
+/// Returns the address of the owner's WSOL associated token account
+pub fn get_wsol_account(owner: &Pubkey) -> Pubkey {
+    get_associated_token_address(owner, &native_mint::ID)
+}
+
+/// Creates instructions that wrap SOL into the owner's WSOL account
+///
+/// The account is created idempotently, so an existing WSOL account is topped up instead.
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the owner, who pays for the account and the wrapped SOL
+/// * `lamports` - Amount of SOL to wrap in lamports
+///
+/// # Returns
+///
+/// Returns the create account, transfer and sync native instructions
+pub fn wrap_sol(owner: &Pubkey, lamports: u64) -> Vec<Instruction> {
+    let wsol_account = get_wsol_account(owner);
+
+    vec![
+        create_associated_token_account_idempotent(
+            owner,
+            owner,
+            &native_mint::ID,
+            &constants::accounts::TOKEN_PROGRAM,
+        ),
+        system_instruction::transfer(owner, &wsol_account, lamports),
+        // Token program ID is always valid, so this cannot fail
+        sync_native(&constants::accounts::TOKEN_PROGRAM, &wsol_account).unwrap(),
+    ]
+}
+
+/// Creates an instruction that unwraps the owner's WSOL account back to native SOL
+///
+/// Closing a WSOL account returns its whole lamport balance to the owner: the wrapped SOL as
+/// well as the rent, whether or not the wrapped balance is empty.
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the WSOL account owner
+///
+/// # Returns
+///
+/// Returns the close account instruction
+pub fn unwrap_sol(owner: &Pubkey) -> Instruction {
+    // Token program ID is always valid, so this cannot fail
+    close_account(
+        &constants::accounts::TOKEN_PROGRAM,
+        &get_wsol_account(owner),
+        owner,
+        owner,
+        &[],
+    )
+    .unwrap()
+}
+
+/// Wraps swap instructions with the WSOL account lifecycle
+///
+/// Any WSOL the owner already held is unwrapped along with the swap's leftover balance.
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the owner paying for and receiving the swap
+/// * `lamports` - Amount of SOL to wrap for the swap in lamports, 0 for sells
+/// * `swap_instructions` - Instructions that perform the swap against the owner's WSOL account
+///
+/// # Returns
+///
+/// Returns the wrap, swap and unwrap instructions in transaction order
+pub fn with_wsol(
+    owner: &Pubkey,
+    lamports: u64,
+    swap_instructions: Vec<Instruction>,
+) -> Vec<Instruction> {
+    let mut instructions = wrap_sol(owner, lamports);
+    instructions.extend(swap_instructions);
+    instructions.push(unwrap_sol(owner));
+    instructions
+}
+
+/// Wraps swap instructions with the WSOL account lifecycle, closing the account only if the
+/// swap creates it
+///
+/// Without an existing WSOL account, this is [`with_wsol`]. With one, the SOL to swap is
+/// wrapped into it and the account is left open, so the WSOL the owner already held is never
+/// unwrapped and the swap's proceeds stay wrapped in it.
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the owner paying for and receiving the swap
+/// * `lamports` - Amount of SOL to wrap for the swap in lamports, 0 for sells
+/// * `swap_instructions` - Instructions that perform the swap against the owner's WSOL account
+/// * `ata_exists` - Whether the owner's WSOL associated token account already exists
+///
+/// # Returns
+///
+/// Returns the wrap, swap and, if the account is created, unwrap instructions in transaction
+/// order
+pub fn with_wsol_preserving(
+    owner: &Pubkey,
+    lamports: u64,
+    swap_instructions: Vec<Instruction>,
+    ata_exists: bool,
+) -> Vec<Instruction> {
+    let mut instructions = wrap_sol(owner, lamports);
+    instructions.extend(swap_instructions);
+    if !ata_exists {
+        instructions.push(unwrap_sol(owner));
+    }
+    instructions
+}
+
+/// Gets the temporary wrapped SOL account derived from the owner and a seed
+///
+/// # Arguments
+///
+/// * `owner` - Public key of the account owner
+/// * `seed` - Seed of at most 32 bytes, unique to the swap
+///
+/// # Returns
+///
+/// Returns the address of the temporary WSOL account
+///
+/// # Panics
+///
+/// Panics if the seed is longer than 32 bytes
+pub fn get_temporary_wsol_account(owner: &Pubkey, seed: &str) -> Pubkey {
+    Pubkey::create_with_seed(owner, seed, &constants::accounts::TOKEN_PROGRAM).unwrap()
+}
+
+/// Wrapped SOL account used for a single swap, with the instructions around it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WsolSession {
+    /// WSOL account the swap instructions must trade against
+    pub account: Pubkey,
+    /// Whether the account is a temporary one, because the owner already had a WSOL account
+    pub temporary: bool,
+    /// Instructions that create and fund the account, run before the swap
+    pub setup: Vec<Instruction>,
+    /// Instruction that closes the account, unwrapping it to native SOL, run after the swap
+    pub cleanup: Instruction,
+}
+
+impl WsolSession {
+    /// Plans the WSOL account for a swap
+    ///
+    /// Without an existing WSOL account, the owner's associated token account is created and
+    /// closed as in [`with_wsol`]. With one, a temporary account is created from the seed,
+    /// funded with its rent and the SOL to swap, and closed after the swap, so the existing
+    /// account is never closed and its balance never mixes with the swap.
+    ///
+    /// # Arguments
+    ///
+    /// * `owner` - Public key of the owner paying for and receiving the swap
+    /// * `lamports` - Amount of SOL to wrap for the swap in lamports, 0 for sells
+    /// * `ata_exists` - Whether the owner's WSOL associated token account already exists
+    /// * `seed` - Seed of at most 32 bytes for the temporary account, unique to the swap
+    ///
+    /// # Returns
+    ///
+    /// Returns the account and the instructions to run around the swap
+    ///
+    /// # Panics
+    ///
+    /// Panics if the WSOL account exists and the seed is longer than 32 bytes
+    pub fn new(owner: &Pubkey, lamports: u64, ata_exists: bool, seed: &str) -> Self {
+        if !ata_exists {
+            return Self {
+                account: get_wsol_account(owner),
+                temporary: false,
+                setup: wrap_sol(owner, lamports),
+                cleanup: unwrap_sol(owner),
+            };
+        }
+
+        let account = get_temporary_wsol_account(owner, seed);
+        let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+        // A native account starts with its lamports above rent as the wrapped balance
+        let setup = vec![
+            system_instruction::create_account_with_seed(
+                owner,
+                &account,
+                owner,
+                seed,
+                rent.saturating_add(lamports),
+                TokenAccount::LEN as u64,
+                &constants::accounts::TOKEN_PROGRAM,
+            ),
+            // Token program ID is always valid, so this cannot fail
+            initialize_account3(
+                &constants::accounts::TOKEN_PROGRAM,
+                &account,
+                &native_mint::ID,
+                owner,
+            )
+            .unwrap(),
+        ];
+        // Token program ID is always valid, so this cannot fail
+        let cleanup = close_account(
+            &constants::accounts::TOKEN_PROGRAM,
+            &account,
+            owner,
+            owner,
+            &[],
+        )
+        .unwrap();
+
+        Self {
+            account,
+            temporary: true,
+            setup,
+            cleanup,
+        }
+    }
+
+    /// Wraps swap instructions with the session's setup and cleanup
+    ///
+    /// # Arguments
+    ///
+    /// * `swap_instructions` - Instructions that perform the swap against the session account
+    ///
+    /// # Returns
+    ///
+    /// Returns the setup, swap and cleanup instructions in transaction order
+    pub fn wrap(self, swap_instructions: Vec<Instruction>) -> Vec<Instruction> {
+        let mut instructions = self.setup;
+        instructions.extend(swap_instructions);
+        instructions.push(self.cleanup);
+        instructions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token::spl_token::instruction::TokenInstruction;
+    use solana_sdk::system_instruction::SystemInstruction;
+
+    /// Runs WSOL instructions against the owner's native and WSOL balances
+    ///
+    /// `swap` moves `swap_delta` lamports into the WSOL account, negative for buys. Returns the
+    /// change of the owner's native balance and the WSOL account's lamports afterwards, None
+    /// once closed.
+    fn run(
+        instructions: &[Instruction],
+        owner: &Pubkey,
+        mut wsol: Option<u64>,
+        swap: &Instruction,
+        swap_delta: i64,
+    ) -> (i64, Option<u64>) {
+        let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+        let wsol_account = get_wsol_account(owner);
+        let mut native: i64 = 0;
+        for ix in instructions {
+            if ix == swap {
+                wsol = wsol.map(|lamports| (lamports as i64 + swap_delta) as u64);
+            } else if ix.program_id == constants::accounts::ASSOCIATED_TOKEN_PROGRAM {
+                if wsol.is_none() {
+                    native -= rent as i64;
+                    wsol = Some(rent);
+                }
+            } else if ix.program_id == solana_sdk::system_program::ID {
+                let SystemInstruction::Transfer { lamports } =
+                    bincode::deserialize(&ix.data).unwrap()
+                else {
+                    panic!("unexpected instruction {:?}", ix);
+                };
+                assert_eq!(ix.accounts[1].pubkey, wsol_account);
+                native -= lamports as i64;
+                wsol = wsol.map(|balance| balance + lamports);
+            } else {
+                match TokenInstruction::unpack(&ix.data).unwrap() {
+                    TokenInstruction::SyncNative => {}
+                    TokenInstruction::CloseAccount => {
+                        native += wsol.take().unwrap() as i64;
+                    }
+                    other => panic!("unexpected instruction {:?}", other),
+                }
+            }
+        }
+        (native, wsol)
+    }
+
+    #[test]
+    fn test_with_wsol() {
+        let owner = Pubkey::new_unique();
+        let swap = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let instructions = with_wsol(&owner, 1_000_000_000, vec![swap.clone()]);
+
+        // Wrap, swap, then unwrap
+        assert_eq!(instructions.len(), 5);
+        assert_eq!(
+            instructions[0].program_id,
+            constants::accounts::ASSOCIATED_TOKEN_PROGRAM
+        );
+        assert_eq!(instructions[1].program_id, solana_sdk::system_program::ID);
+        assert_eq!(
+            instructions[2].program_id,
+            constants::accounts::TOKEN_PROGRAM
+        );
+        assert_eq!(instructions[3], swap);
+        assert_eq!(
+            instructions[4].program_id,
+            constants::accounts::TOKEN_PROGRAM
+        );
+
+        // The WSOL account is funded and then closed back to the owner
+        let wsol_account = get_wsol_account(&owner);
+        assert_eq!(instructions[1].accounts[1].pubkey, wsol_account);
+        assert_eq!(instructions[4].accounts[0].pubkey, wsol_account);
+        assert_eq!(instructions[4].accounts[1].pubkey, owner);
+    }
+
+    #[test]
+    fn test_session_without_wsol_account() {
+        let owner = Pubkey::new_unique();
+        let session = WsolSession::new(&owner, 1_000_000_000, false, "wsol-1");
+        assert!(!session.temporary);
+        assert_eq!(session.account, get_wsol_account(&owner));
+
+        let swap = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        assert_eq!(
+            session.wrap(vec![swap.clone()]),
+            with_wsol(&owner, 1_000_000_000, vec![swap])
+        );
+    }
+
+    #[test]
+    fn test_session_keeps_existing_wsol_account() {
+        let owner = Pubkey::new_unique();
+        let session = WsolSession::new(&owner, 1_000_000_000, true, "wsol-2");
+        assert!(session.temporary);
+        assert_eq!(
+            session.account,
+            get_temporary_wsol_account(&owner, "wsol-2")
+        );
+        assert_ne!(
+            session.account,
+            get_temporary_wsol_account(&owner, "wsol-3")
+        );
+
+        let instructions = session.clone().wrap(vec![]);
+        assert_eq!(instructions.len(), 3);
+
+        // The temporary account holds its rent and exactly the SOL to swap
+        let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+        let create = &instructions[0];
+        assert_eq!(create.program_id, solana_sdk::system_program::ID);
+        assert_eq!(create.accounts[1].pubkey, session.account);
+        let funded = match bincode::deserialize::<SystemInstruction>(&create.data).unwrap() {
+            SystemInstruction::CreateAccountWithSeed { lamports, .. } => lamports,
+            other => panic!("unexpected instruction {:?}", other),
+        };
+        assert_eq!(funded, rent + 1_000_000_000);
+
+        // Only the temporary account is closed, never the owner's WSOL account
+        let wsol_account = get_wsol_account(&owner);
+        assert_eq!(instructions[2].accounts[0].pubkey, session.account);
+        assert_eq!(instructions[2].accounts[1].pubkey, owner);
+        assert!(instructions
+            .iter()
+            .flat_map(|ix| &ix.accounts)
+            .all(|meta| meta.pubkey != wsol_account));
+    }
+
+    #[test]
+    fn test_with_wsol_preserving_balances() {
+        let owner = Pubkey::new_unique();
+        let swap = Instruction::new_with_bytes(Pubkey::new_unique(), &[1, 2, 3], vec![]);
+        let rent = Rent::default().minimum_balance(TokenAccount::LEN);
+        let held = rent + 5_000_000_000;
+
+        // A buy spending 1 SOL against an existing account costs exactly 1 SOL, and the WSOL
+        // already held stays wrapped
+        let instructions = with_wsol_preserving(&owner, 1_000_000_000, vec![swap.clone()], true);
+        assert_eq!(
+            run(&instructions, &owner, Some(held), &swap, -1_000_000_000),
+            (-1_000_000_000, Some(held))
+        );
+
+        // Closing the account would have unwrapped it as if it were proceeds
+        let instructions = with_wsol(&owner, 1_000_000_000, vec![swap.clone()]);
+        assert_eq!(
+            run(&instructions, &owner, Some(held), &swap, -1_000_000_000),
+            (held as i64 - 1_000_000_000, None)
+        );
+
+        // A sell without an account unwraps exactly its proceeds and reclaims the rent
+        let instructions = with_wsol_preserving(&owner, 0, vec![swap.clone()], false);
+        assert_eq!(instructions, with_wsol(&owner, 0, vec![swap.clone()]));
+        assert_eq!(
+            run(&instructions, &owner, None, &swap, 2_000_000_000),
+            (2_000_000_000, None)
+        );
+
+        // A sell with an account leaves its proceeds wrapped alongside the balance held
+        let instructions = with_wsol_preserving(&owner, 0, vec![swap.clone()], true);
+        assert_eq!(
+            run(&instructions, &owner, Some(held), &swap, 2_000_000_000),
+            (0, Some(held + 2_000_000_000))
+        );
+    }
+}
@@ -0,0 +1,793 @@
+//! In-process bank running the Pump.fun program, for testing the whole client
+//!
+//! [`Harness`] starts a bank with `solana-program-test` and loads the program binary into it,
+//! so the transactions the client builds are executed by the program itself. The program is
+//! initialized with [`super::ensure_initialized`] before the harness is returned. Clients reach
+//! the bank through [`BanksSender`], which answers their RPC requests from it:
+//!
+//! - `Harness`: A bank running the program, with a funded payer.
+//! - `BanksSender`: Transport answering RPC requests from a bank.
+//! - `HarnessExecutor`: Executes the trades of a [`crate::stress`] run on the harness.
+//!
+//! The program binaries are not distributed with the crate. The harness loads `pump.so`, and
+//! `mpl_token_metadata.so` if present, from the directory named by `SBF_OUT_DIR` or from
+//! `tests/fixtures`. Both can be dumped from a cluster running them:
+//!
+//! ```text
+//! solana program dump 6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P tests/fixtures/pump.so --url mainnet-beta
+//! solana program dump metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s tests/fixtures/mpl_token_metadata.so --url mainnet-beta
+//! ```
+//!
+//! The bank is driven by tasks on the Tokio runtime the harness is started on. The client
+//! makes blocking requests from async code, so the runtime must be multi-threaded.
+
+use super::{ensure_initialized, GlobalParams};
+use crate::{
+    accounts::{BondingCurveAccount, PlannedTrade, TradeFill},
+    constants,
+    error::ClientError,
+    params::TradeAccounts,
+    stress::{CurveExecutor, StressStep},
+    PumpFun,
+};
+use anchor_client::{
+    solana_client::{
+        client_error::Result as ClientResult,
+        rpc_request::{RpcError, RpcRequest},
+        rpc_sender::{RpcSender, RpcTransportStats},
+    },
+    Cluster,
+};
+use anchor_spl::associated_token::spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use pumpfun_cpi as cpi;
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_banks_interface::TransactionConfirmationStatus as BanksConfirmation;
+use solana_program_test::{
+    find_file, BanksClient, BanksClientError, ProgramTest, ProgramTestBanksClientExt,
+};
+use solana_sdk::{
+    account::Account,
+    hash::Hash,
+    message::Message,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+    system_instruction,
+    transaction::{Transaction, VersionedTransaction},
+};
+use solana_transaction_status::{TransactionConfirmationStatus, TransactionStatus};
+use std::str::FromStr;
+
+/// Hint shown when the program binary is not found
+pub const PUMPFUN_BINARY_HINT: &str = "dump it into the test fixtures with `solana program dump \
+6EF8rrecthR5Dkzon8Nwu78hRvfCKubJ14M5uBEwF6P tests/fixtures/pump.so --url mainnet-beta`";
+
+/// Bank running the initialized Pump.fun program, with a payer funded at genesis
+pub struct Harness {
+    /// Client of the bank
+    banks: BanksClient,
+    /// Payer funded at genesis, and the program's authority
+    payer: Keypair,
+    /// Parameters the program was initialized with
+    params: GlobalParams,
+}
+
+impl Harness {
+    /// Starts a bank running the Pump.fun program, and the token metadata program if found
+    ///
+    /// The fee recipient is funded so the first fees it receives leave it rent exempt, and the
+    /// program is initialized with the genesis payer as its authority. Without the token
+    /// metadata program, tokens cannot be created.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Parameters to initialize the program with
+    ///
+    /// # Returns
+    ///
+    /// Returns the harness, a ClientError::MissingDependencyProgram if the program binary is
+    /// not found, a ClientError::InvalidGlobalParams if the parameters are invalid, or a
+    /// ClientError if the initialization fails
+    pub async fn start(params: &GlobalParams) -> Result<Self, ClientError> {
+        if find_file("pump.so").is_none() {
+            return Err(ClientError::MissingDependencyProgram {
+                program: constants::accounts::PUMPFUN,
+                hint: PUMPFUN_BINARY_HINT,
+            });
+        }
+
+        let mut program_test = ProgramTest::default();
+        program_test.add_program("pump", constants::accounts::PUMPFUN, None);
+        if find_file("mpl_token_metadata.so").is_some() {
+            program_test.add_program(
+                "mpl_token_metadata",
+                constants::accounts::MPL_TOKEN_METADATA,
+                None,
+            );
+        }
+        let (mut banks, payer, _) = program_test.start().await;
+        let rent = banks
+            .get_rent()
+            .await
+            .map_err(|err| ClientError::SolanaClientError(banks_error(err)))?;
+
+        let harness = Self {
+            banks,
+            payer,
+            params: *params,
+        };
+        harness
+            .fund(&params.fee_recipient, rent.minimum_balance(0))
+            .await?;
+        ensure_initialized(&harness.client(), params, true).await?;
+        Ok(harness)
+    }
+
+    /// Gets the parameters the program was initialized with
+    pub fn params(&self) -> &GlobalParams {
+        &self.params
+    }
+
+    /// Gets the payer funded at genesis
+    pub fn payer(&self) -> &Keypair {
+        &self.payer
+    }
+
+    /// Creates a transport answering RPC requests from the bank
+    pub fn sender(&self) -> BanksSender {
+        BanksSender::new(self.banks.clone())
+    }
+
+    /// Creates a client of the bank paying with the genesis payer
+    pub fn client(&self) -> PumpFun<'_> {
+        self.client_for(&self.payer)
+    }
+
+    /// Creates a client of the bank paying with another keypair
+    ///
+    /// # Arguments
+    ///
+    /// * `payer` - Keypair paying for and signing the client's transactions
+    pub fn client_for<'k>(&self, payer: &'k Keypair) -> PumpFun<'k> {
+        PumpFun::new_with_sender(Cluster::Localnet, payer, None, self.sender())
+    }
+
+    /// Transfers lamports from the genesis payer
+    ///
+    /// # Arguments
+    ///
+    /// * `account` - Account receiving the lamports
+    /// * `lamports` - Amount to transfer
+    ///
+    /// # Returns
+    ///
+    /// Returns the signature of the transfer, or a ClientError if it fails
+    pub async fn fund(&self, account: &Pubkey, lamports: u64) -> Result<Signature, ClientError> {
+        let client = self.client();
+        let blockhash = client
+            .async_rpc()
+            .get_latest_blockhash()
+            .await
+            .map_err(ClientError::SolanaClientError)?;
+        let transfer = Transaction::new_signed_with_payer(
+            &[system_instruction::transfer(
+                &self.payer.pubkey(),
+                account,
+                lamports,
+            )],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            blockhash,
+        );
+        client.send_transaction(&transfer).await
+    }
+}
+
+/// Executes the trades of a stress run on the harness, each from the client of its wallet
+///
+/// Instruction arguments are built from the client's predicted fill: a buy of exactly the
+/// predicted tokens for at most the predicted cost and fee, and a sell for at least the
+/// predicted proceeds less the fee. Each trade is sent with a blockhash newer than the last
+/// one, so a trade repeating an earlier one is still a distinct transaction.
+pub struct HarnessExecutor<'w> {
+    /// Runtime the harness was started on
+    runtime: tokio::runtime::Handle,
+    /// Client of the bank
+    banks: BanksClient,
+    /// Clients of the trading wallets, by wallet index
+    clients: Vec<PumpFun<'w>>,
+    /// Mint of the traded curve
+    mint: Pubkey,
+    /// Accounts of every trade
+    accounts: TradeAccounts,
+    /// Blockhash of the last trade
+    blockhash: Hash,
+}
+
+impl<'w> HarnessExecutor<'w> {
+    /// Creates an executor trading a mint from funded wallets
+    ///
+    /// # Arguments
+    ///
+    /// * `harness` - Harness the mint's curve is on
+    /// * `wallets` - Funded wallets, at least as many as the run uses
+    /// * `mint` - Mint of the traded curve
+    /// * `runtime` - Runtime the harness was started on, which trades are executed on
+    pub fn new(
+        harness: &Harness,
+        wallets: &'w [Keypair],
+        mint: Pubkey,
+        runtime: tokio::runtime::Handle,
+    ) -> Self {
+        Self {
+            runtime,
+            banks: harness.banks.clone(),
+            clients: wallets
+                .iter()
+                .map(|wallet| harness.client_for(wallet))
+                .collect(),
+            mint,
+            accounts: TradeAccounts::new(harness.params.fee_recipient),
+            blockhash: Hash::default(),
+        }
+    }
+
+    /// Sends a trade and reads back the curve
+    async fn trade(
+        &mut self,
+        step: &StressStep,
+        fill: &TradeFill,
+    ) -> Result<BondingCurveAccount, ClientError> {
+        let client = &self.clients[step.wallet];
+        let wallet = client.signer();
+        let instructions = match step.trade {
+            PlannedTrade::Buy { .. } => vec![
+                create_associated_token_account_idempotent(
+                    &wallet.pubkey(),
+                    &wallet.pubkey(),
+                    &self.mint,
+                    &self.accounts.token_program,
+                ),
+                client.buy_instruction(
+                    &self.mint,
+                    &self.accounts,
+                    cpi::instruction::Buy {
+                        _amount: fill.token_amount,
+                        _max_sol_cost: fill.sol_amount.saturating_add(fill.fee),
+                    },
+                )?,
+            ],
+            PlannedTrade::Sell { .. } => vec![client.sell_instruction(
+                &self.mint,
+                &self.accounts,
+                cpi::instruction::Sell {
+                    _amount: fill.token_amount,
+                    _min_sol_output: fill.sol_amount.saturating_sub(fill.fee),
+                },
+            )?],
+        };
+
+        self.blockhash = self
+            .banks
+            .get_new_latest_blockhash(&self.blockhash)
+            .await
+            .map_err(|err| ClientError::SolanaClientError(err.into()))?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&wallet.pubkey()),
+            &[&*wallet],
+            self.blockhash,
+        );
+        client.send_transaction(&transaction).await?;
+        client.get_bonding_curve_account(&self.mint)
+    }
+}
+
+impl CurveExecutor for HarnessExecutor<'_> {
+    fn execute(
+        &mut self,
+        step: &StressStep,
+        fill: &TradeFill,
+    ) -> Result<BondingCurveAccount, String> {
+        let runtime = self.runtime.clone();
+        runtime
+            .block_on(self.trade(step, fill))
+            .map_err(|err| err.to_string())
+    }
+}
+
+/// Transport answering RPC requests from a bank
+///
+/// Transactions are processed when they are sent, so a failing transaction is reported by the
+/// send, as a preflight check would, and a landed one is final by the time its status is read.
+/// Requests the harness does not serve fail with an unsupported error.
+#[derive(Clone)]
+pub struct BanksSender {
+    /// Client of the bank
+    banks: BanksClient,
+}
+
+impl BanksSender {
+    /// Creates a transport answering requests from a bank
+    ///
+    /// # Arguments
+    ///
+    /// * `banks` - Client of the bank
+    pub fn new(banks: BanksClient) -> Self {
+        Self { banks }
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcSender for BanksSender {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        let mut banks = self.banks.clone();
+        let value = match request {
+            RpcRequest::GetVersion => {
+                return Ok(serde_json::json!({
+                    "solana-core": "1.18.26",
+                    "feature-set": 0,
+                }))
+            }
+            RpcRequest::GetSlot => {
+                return Ok(serde_json::json!(banks
+                    .get_root_slot()
+                    .await
+                    .map_err(banks_error)?))
+            }
+            RpcRequest::GetBlockHeight => {
+                return Ok(serde_json::json!(banks
+                    .get_root_block_height()
+                    .await
+                    .map_err(banks_error)?))
+            }
+            RpcRequest::GetMinimumBalanceForRentExemption => {
+                let len = params[0]
+                    .as_u64()
+                    .ok_or_else(|| invalid_param(&params[0]))?;
+                let rent = banks.get_rent().await.map_err(banks_error)?;
+                return Ok(serde_json::json!(rent.minimum_balance(len as usize)));
+            }
+            RpcRequest::SendTransaction => {
+                use base64::Engine;
+
+                let transaction: VersionedTransaction = params[0]
+                    .as_str()
+                    .and_then(|encoded| {
+                        base64::engine::general_purpose::STANDARD
+                            .decode(encoded)
+                            .ok()
+                    })
+                    .and_then(|bytes| bincode::deserialize(&bytes).ok())
+                    .ok_or_else(|| invalid_param(&params[0]))?;
+                let signature = transaction.signatures[0];
+                banks
+                    .process_transaction(transaction)
+                    .await
+                    .map_err(banks_error)?;
+                return Ok(serde_json::json!(signature.to_string()));
+            }
+            RpcRequest::GetAccountInfo => {
+                let address = parse_param(&params[0]).ok_or_else(|| invalid_param(&params[0]))?;
+                let account = banks.get_account(address).await.map_err(banks_error)?;
+                encode_account(&address, account)
+            }
+            RpcRequest::GetMultipleAccounts => {
+                let addresses = params[0]
+                    .as_array()
+                    .ok_or_else(|| invalid_param(&params[0]))?;
+                let mut accounts = Vec::with_capacity(addresses.len());
+                for address in addresses {
+                    let address = parse_param(address).ok_or_else(|| invalid_param(address))?;
+                    let account = banks.get_account(address).await.map_err(banks_error)?;
+                    accounts.push(encode_account(&address, account));
+                }
+                serde_json::Value::Array(accounts)
+            }
+            RpcRequest::GetBalance => {
+                let address = parse_param(&params[0]).ok_or_else(|| invalid_param(&params[0]))?;
+                serde_json::json!(banks.get_balance(address).await.map_err(banks_error)?)
+            }
+            RpcRequest::GetLatestBlockhash => {
+                let (blockhash, last_valid_block_height) = banks
+                    .get_latest_blockhash_with_commitment(Default::default())
+                    .await
+                    .map_err(banks_error)?
+                    .ok_or_else(|| banks_error(BanksClientError::ClientError("no blockhash")))?;
+                serde_json::json!({
+                    "blockhash": blockhash.to_string(),
+                    "lastValidBlockHeight": last_valid_block_height,
+                })
+            }
+            RpcRequest::IsBlockhashValid => {
+                // The bank only prices messages whose blockhash it still accepts
+                let blockhash: Hash =
+                    parse_param(&params[0]).ok_or_else(|| invalid_param(&params[0]))?;
+                let message =
+                    Message::new_with_blockhash(&[], Some(&Pubkey::new_unique()), &blockhash);
+                serde_json::json!(banks
+                    .get_fee_for_message(message)
+                    .await
+                    .map_err(banks_error)?
+                    .is_some())
+            }
+            RpcRequest::GetSignatureStatuses => {
+                let mut signatures = Vec::new();
+                for signature in params[0]
+                    .as_array()
+                    .ok_or_else(|| invalid_param(&params[0]))?
+                {
+                    signatures
+                        .push(parse_param(signature).ok_or_else(|| invalid_param(signature))?);
+                }
+                let statuses = banks
+                    .get_transaction_statuses(signatures)
+                    .await
+                    .map_err(banks_error)?
+                    .into_iter()
+                    .map(|status| {
+                        status.map(|status| TransactionStatus {
+                            slot: status.slot,
+                            confirmations: status.confirmations,
+                            status: status.err.clone().map_or(Ok(()), Err),
+                            err: status.err,
+                            confirmation_status: status.confirmation_status.map(|confirmation| {
+                                match confirmation {
+                                    BanksConfirmation::Processed => {
+                                        TransactionConfirmationStatus::Processed
+                                    }
+                                    BanksConfirmation::Confirmed => {
+                                        TransactionConfirmationStatus::Confirmed
+                                    }
+                                    BanksConfirmation::Finalized => {
+                                        TransactionConfirmationStatus::Finalized
+                                    }
+                                }
+                            }),
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                serde_json::json!(statuses)
+            }
+            request => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    format!("request {request} is not served by the harness"),
+                )
+                .into())
+            }
+        };
+
+        let slot = banks.get_root_slot().await.map_err(banks_error)?;
+        Ok(serde_json::json!({ "context": { "slot": slot }, "value": value }))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        Default::default()
+    }
+
+    fn url(&self) -> String {
+        "program-test".to_string()
+    }
+}
+
+/// Encodes an account as the node returns it, or null if it does not exist
+fn encode_account(address: &Pubkey, account: Option<Account>) -> serde_json::Value {
+    account.map_or(serde_json::Value::Null, |account| {
+        serde_json::json!(UiAccount::encode(
+            address,
+            &account,
+            UiAccountEncoding::Base64,
+            None,
+            None,
+        ))
+    })
+}
+
+/// Parses a parameter sent as a string, such as an address or a signature
+fn parse_param<T: FromStr>(param: &serde_json::Value) -> Option<T> {
+    param.as_str().and_then(|param| param.parse().ok())
+}
+
+/// Error for a parameter the harness cannot read
+fn invalid_param(
+    param: &serde_json::Value,
+) -> anchor_client::solana_client::client_error::ClientError {
+    RpcError::RpcRequestError(format!("invalid param {param}")).into()
+}
+
+/// Converts a bank error to the error the node would return
+fn banks_error(err: BanksClientError) -> anchor_client::solana_client::client_error::ClientError {
+    match err {
+        BanksClientError::TransactionError(err) | BanksClientError::SimulationError { err, .. } => {
+            err.into()
+        }
+        err => std::io::Error::other(err.to_string()).into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        instruction,
+        stress::{self, StressConfig},
+        testing::InitOutcome,
+        utils,
+    };
+    use solana_sdk::native_token::LAMPORTS_PER_SOL;
+    use solana_sdk::{instruction::InstructionError, transaction::TransactionError};
+
+    fn runtime() -> tokio::runtime::Runtime {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sender_answers_from_the_bank() {
+        runtime().block_on(async {
+            let (banks, payer, _) = ProgramTest::default().start().await;
+            let client =
+                PumpFun::new_with_sender(Cluster::Localnet, &payer, None, BanksSender::new(banks));
+            let rpc = client.async_rpc();
+            let recipient = Pubkey::new_unique();
+
+            let blockhash = rpc.get_latest_blockhash().await.unwrap();
+            assert!(rpc
+                .is_blockhash_valid(&blockhash, Default::default())
+                .await
+                .unwrap());
+            assert!(!rpc
+                .is_blockhash_valid(&Hash::new_unique(), Default::default())
+                .await
+                .unwrap());
+
+            // A landed transaction is final once sent
+            let transfer = Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &recipient,
+                    1_000_000_000,
+                )],
+                Some(&payer.pubkey()),
+                &[&payer],
+                blockhash,
+            );
+            let signature = client.send_transaction(&transfer).await.unwrap();
+            assert_eq!(
+                rpc.get_signature_status(&signature).await.unwrap(),
+                Some(Ok(()))
+            );
+            assert_eq!(client.rpc.get_balance(&recipient).unwrap(), 1_000_000_000);
+            let accounts = rpc
+                .get_multiple_accounts(&[recipient, Pubkey::new_unique()])
+                .await
+                .unwrap();
+            assert_eq!(accounts[0].as_ref().unwrap().lamports, 1_000_000_000);
+            assert!(accounts[1].is_none());
+
+            // A failing transaction is reported by the send, with the program's error
+            let overdraft = Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(
+                    &payer.pubkey(),
+                    &recipient,
+                    u64::MAX / 2,
+                )],
+                Some(&payer.pubkey()),
+                &[&payer],
+                blockhash,
+            );
+            assert!(matches!(
+                client.send_transaction(&overdraft).await,
+                Err(ClientError::SolanaClientError(err))
+                    if err.get_transaction_error()
+                        == Some(TransactionError::InstructionError(0, InstructionError::Custom(1)))
+            ));
+        });
+    }
+
+    /// Metadata of a test token, with its upload already cached by the client
+    async fn cached_metadata(client: &PumpFun<'_>, symbol: &str) -> utils::CreateTokenMetadata {
+        let path = std::env::temp_dir().join(format!("pumpfun-image-{}.png", Pubkey::new_unique()));
+        std::fs::write(&path, symbol.as_bytes()).unwrap();
+        let metadata = utils::CreateTokenMetadata {
+            name: format!("Token {symbol}"),
+            symbol: symbol.to_string(),
+            description: "Test Description".to_string(),
+            file: path.to_str().unwrap().to_string(),
+            twitter: None,
+            telegram: None,
+            website: None,
+        };
+        let upload = |metadata: utils::CreateTokenMetadata, _image: Vec<u8>| async move {
+            Ok::<_, Box<dyn std::error::Error>>(utils::TokenMetadataResponse {
+                metadata_uri: format!("https://ipfs.io/ipfs/{}", metadata.symbol),
+                metadata: utils::TokenMetadata {
+                    name: metadata.name,
+                    symbol: metadata.symbol,
+                    description: metadata.description,
+                    image: "https://ipfs.io/ipfs/image".to_string(),
+                    show_name: true,
+                    created_on: "https://pump.fun".to_string(),
+                    twitter: metadata.twitter,
+                    telegram: metadata.telegram,
+                    website: metadata.website,
+                },
+            })
+        };
+        client
+            .upload_metadata_with(metadata.clone(), upload)
+            .await
+            .unwrap();
+        metadata
+    }
+
+    #[test]
+    #[ignore = "needs pump.so and mpl_token_metadata.so in tests/fixtures"]
+    fn test_concurrent_creates() {
+        const CREATES: usize = 6;
+        runtime().block_on(async {
+            let harness = Harness::start(&GlobalParams::new(Pubkey::new_unique()))
+                .await
+                .unwrap();
+            let client = harness.client();
+
+            let mints: Vec<Keypair> = (0..CREATES).map(|_| Keypair::new()).collect();
+            let mut metadata = Vec::new();
+            for index in 0..CREATES {
+                metadata.push(cached_metadata(&client, &format!("T{index}")).await);
+            }
+
+            // Every create is in flight at once, and the first mint is launched twice
+            let creates = mints
+                .iter()
+                .zip(&metadata)
+                .map(|(mint, metadata)| client.create(mint, metadata.clone(), None))
+                .chain([client.create(&mints[0], metadata[0].clone(), None)]);
+            let results = futures::future::join_all(creates).await;
+
+            let rejected: Vec<_> = results
+                .iter()
+                .filter_map(|result| match result {
+                    Err(ClientError::MintAlreadyExists(mint)) => Some(*mint),
+                    Err(err) => panic!("create failed: {err}"),
+                    Ok(_) => None,
+                })
+                .collect();
+            assert_eq!(rejected, [mints[0].pubkey()]);
+            for mint in &mints {
+                let curve = client.get_bonding_curve_account(&mint.pubkey()).unwrap();
+                assert!(!curve.complete);
+            }
+
+            // A mint created before is rejected before anything is sent
+            assert!(matches!(
+                client.create(&mints[1], metadata[1].clone(), None).await,
+                Err(ClientError::MintAlreadyExists(mint)) if mint == mints[1].pubkey()
+            ));
+        });
+    }
+
+    #[test]
+    #[ignore = "needs pump.so in tests/fixtures"]
+    fn test_start_initializes_the_program() {
+        runtime().block_on(async {
+            let params = GlobalParams::new(Pubkey::new_unique());
+            let harness = Harness::start(&params).await.unwrap();
+            let client = harness.client();
+
+            // The program holds the parameters, with the payer as its authority
+            let global = client.get_global_account().unwrap();
+            assert!(global.initialized);
+            assert_eq!(global.authority, harness.payer().pubkey());
+            assert_eq!(GlobalParams::from(&global), params);
+
+            // Later calls find the program initialized and compare its parameters
+            assert_eq!(
+                ensure_initialized(&client, &params, true).await.unwrap(),
+                InitOutcome::AlreadyInitialized {
+                    mismatches: Vec::new()
+                }
+            );
+            let other = GlobalParams {
+                fee_basis_points: 95,
+                ..params
+            };
+            assert_eq!(
+                ensure_initialized(&client, &other, false).await.unwrap(),
+                InitOutcome::AlreadyInitialized {
+                    mismatches: vec!["fee_basis_points"]
+                }
+            );
+            assert!(matches!(
+                ensure_initialized(&client, &other, true).await,
+                Err(ClientError::GlobalParamsMismatch { fields }) if fields == vec!["fee_basis_points"]
+            ));
+
+            // Initializing again fails on chain, which concurrent callers rely on
+            let racer = Keypair::new();
+            harness.fund(&racer.pubkey(), 1_000_000_000).await.unwrap();
+            let racing = harness.client_for(&racer);
+            let request = racing
+                .program
+                .request()
+                .instruction(instruction::initialize_with_seeds(
+                    &racing.seeds,
+                    &racing.signer(),
+                ));
+            let transaction = racing.sign_request(&request, &[]).await.unwrap();
+            assert!(racing.send_transaction(&transaction).await.is_err());
+            assert_eq!(
+                client.get_global_account().unwrap().authority,
+                harness.payer().pubkey()
+            );
+        });
+    }
+
+    /// Creates a token from the harness payer, returning its mint
+    async fn launch(harness: &Harness) -> Pubkey {
+        let client = harness.client();
+        let mint = Keypair::new();
+        let instructions = client
+            .create_ix(
+                &mint,
+                cpi::instruction::Create {
+                    _name: "Stress".to_string(),
+                    _symbol: "STRESS".to_string(),
+                    _uri: "https://ipfs.io/ipfs/stress".to_string(),
+                },
+                None,
+            )
+            .unwrap();
+        let request = instructions
+            .into_iter()
+            .fold(client.program.request(), |request, ix| {
+                request.instruction(ix)
+            });
+        let transaction = client.sign_request(&request, &[&mint]).await.unwrap();
+        client.send_transaction(&transaction).await.unwrap();
+        mint.pubkey()
+    }
+
+    #[test]
+    #[ignore = "needs pump.so and mpl_token_metadata.so in tests/fixtures"]
+    fn test_stress_runs_match_the_program() {
+        let runtime = runtime();
+        let params = GlobalParams::new(Pubkey::new_unique());
+        let harness = runtime.block_on(Harness::start(&params)).unwrap();
+
+        for seed in 0..3 {
+            let mint = runtime.block_on(launch(&harness));
+            let wallets: Vec<Keypair> = (0..4).map(|_| Keypair::new()).collect();
+            for wallet in &wallets {
+                runtime
+                    .block_on(harness.fund(&wallet.pubkey(), 100 * LAMPORTS_PER_SOL))
+                    .unwrap();
+            }
+            let curve = harness.client().get_bonding_curve_account(&mint).unwrap();
+
+            let config = StressConfig {
+                seed,
+                wallets: wallets.len(),
+                trades: 60,
+                fee_basis_points: params.fee_basis_points,
+                ..StressConfig::default()
+            };
+            let mut executor =
+                HarnessExecutor::new(&harness, &wallets, mint, runtime.handle().clone());
+            let report = stress::run(&config, &curve, &mut executor)
+                .unwrap_or_else(|divergence| panic!("{}", divergence));
+
+            assert!(report.buys > 0);
+            assert_eq!(
+                report.final_curve,
+                harness.client().get_bonding_curve_account(&mint).unwrap()
+            );
+        }
+    }
+}
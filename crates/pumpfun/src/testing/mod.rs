@@ -0,0 +1,921 @@
+//! Setup of local forks of the program for integration tests
+//!
+//! A fresh fork of the program, such as a test validator loading the program binary, has no
+//! global account until the admin instructions create it and set its parameters.
+//! [`ensure_initialized`] does both in one transaction if needed, and otherwise checks that the
+//! existing parameters are the ones the tests expect:
+//!
+//! - `GlobalParams`: The parameters new bonding curves are created with.
+//! - `InitOutcome`: Whether the call initialized the program or found it initialized.
+//! - `ensure_initialized`: Initializes the program unless it already is.
+//!
+//! The program accepts any parameters, and some leave every curve created afterwards unusable,
+//! such as zero virtual reserves or a fee over 100%. Parameters are checked before they are
+//! sent, and [`set_params`] only takes a [`ValidatedGlobalParams`]:
+//!
+//! - `GlobalParamsBuilder`: Builds parameters from explicitly set values, checking each one.
+//! - `ValidatedGlobalParams`: Parameters known to satisfy every constraint.
+//! - `GlobalParamsViolation`: A constraint parameters violate, and why it matters.
+//! - `set_params`: Sets the parameters of an initialized program.
+//!
+//! Test binaries running in parallel against the same fork may all find the program
+//! uninitialized. No local lock is taken: the global account can only be created once, so all
+//! but one initialization fail on chain, and the losers read back the winner's account and
+//! check it like any existing one.
+//!
+//! With the `program-test` feature, [`harness`] runs the program in an in-process bank, set up
+//! with [`ensure_initialized`].
+
+#[cfg(any(test, feature = "program-test"))]
+pub mod harness;
+
+pub use crate::error::GlobalParamsViolation;
+
+use crate::{accounts::GlobalAccount, error::ClientError, instruction, PumpFun};
+use pumpfun_cpi as cpi;
+use solana_sdk::{pubkey::Pubkey, signature::Signature};
+
+/// Highest trading fee in basis points, taking the whole amount traded
+pub const MAX_FEE_BASIS_POINTS: u64 = 10_000;
+
+/// Parameters new bonding curves are created with, as set by the program's authority
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalParams {
+    /// Account receiving the trading fees
+    pub fee_recipient: Pubkey,
+    /// Virtual token reserves a new curve starts with
+    pub initial_virtual_token_reserves: u64,
+    /// Virtual SOL reserves a new curve starts with, in lamports
+    pub initial_virtual_sol_reserves: u64,
+    /// Tokens a new curve can sell before it completes
+    pub initial_real_token_reserves: u64,
+    /// Total supply of each new token
+    pub token_total_supply: u64,
+    /// Fee charged on each trade in basis points
+    pub fee_basis_points: u64,
+}
+
+impl GlobalParams {
+    /// Creates the parameters of the mainnet program with another fee recipient
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Account receiving the trading fees
+    pub fn new(fee_recipient: Pubkey) -> Self {
+        Self {
+            fee_recipient,
+            initial_virtual_token_reserves: 1_073_000_000_000_000,
+            initial_virtual_sol_reserves: 30_000_000_000,
+            initial_real_token_reserves: 793_100_000_000_000,
+            token_total_supply: 1_000_000_000_000_000,
+            fee_basis_points: 100,
+        }
+    }
+
+    /// Starts building parameters from explicitly set values
+    ///
+    /// # Returns
+    ///
+    /// Returns a builder with no value set
+    pub fn builder() -> GlobalParamsBuilder {
+        GlobalParamsBuilder::default()
+    }
+
+    /// Checks the parameters against the constraints new bonding curves need
+    ///
+    /// # Returns
+    ///
+    /// Returns the validated parameters, or a ClientError::InvalidGlobalParams listing every
+    /// violated constraint
+    pub fn validate(self) -> Result<ValidatedGlobalParams, ClientError> {
+        let violations = self.violations();
+        if !violations.is_empty() {
+            return Err(ClientError::InvalidGlobalParams { violations });
+        }
+        Ok(ValidatedGlobalParams(self))
+    }
+
+    /// Lists the constraints the parameters violate
+    ///
+    /// # Returns
+    ///
+    /// Returns every violation, empty if the parameters are valid
+    pub fn violations(&self) -> Vec<GlobalParamsViolation> {
+        let mut violations = Vec::new();
+        if self.fee_recipient == Pubkey::default() {
+            violations.push(GlobalParamsViolation::DefaultFeeRecipient);
+        }
+        if self.initial_virtual_token_reserves == 0 {
+            violations.push(GlobalParamsViolation::ZeroVirtualTokenReserves);
+        }
+        if self.initial_virtual_sol_reserves == 0 {
+            violations.push(GlobalParamsViolation::ZeroVirtualSolReserves);
+        }
+        if self.initial_real_token_reserves == 0 {
+            violations.push(GlobalParamsViolation::ZeroRealTokenReserves);
+        }
+        if self.token_total_supply == 0 {
+            violations.push(GlobalParamsViolation::ZeroTokenTotalSupply);
+        }
+        if self.fee_basis_points > MAX_FEE_BASIS_POINTS {
+            violations.push(GlobalParamsViolation::FeeTooHigh {
+                fee_basis_points: self.fee_basis_points,
+            });
+        }
+        if self.token_total_supply < self.initial_real_token_reserves {
+            violations.push(GlobalParamsViolation::SupplyBelowRealReserves {
+                token_total_supply: self.token_total_supply,
+                initial_real_token_reserves: self.initial_real_token_reserves,
+            });
+        }
+        if self.initial_real_token_reserves >= self.initial_virtual_token_reserves
+            && self.initial_virtual_token_reserves > 0
+        {
+            violations.push(GlobalParamsViolation::RealReservesNotBelowVirtual {
+                initial_real_token_reserves: self.initial_real_token_reserves,
+                initial_virtual_token_reserves: self.initial_virtual_token_reserves,
+            });
+        }
+        violations
+    }
+
+    /// Compares the parameters with those of a global account
+    ///
+    /// # Arguments
+    ///
+    /// * `global` - Global account read from the cluster
+    ///
+    /// # Returns
+    ///
+    /// Returns the names of the parameters that differ, empty if all match
+    pub fn mismatches(&self, global: &GlobalAccount) -> Vec<&'static str> {
+        let existing = Self::from(global);
+        [
+            (
+                "fee_recipient",
+                self.fee_recipient == existing.fee_recipient,
+            ),
+            (
+                "initial_virtual_token_reserves",
+                self.initial_virtual_token_reserves == existing.initial_virtual_token_reserves,
+            ),
+            (
+                "initial_virtual_sol_reserves",
+                self.initial_virtual_sol_reserves == existing.initial_virtual_sol_reserves,
+            ),
+            (
+                "initial_real_token_reserves",
+                self.initial_real_token_reserves == existing.initial_real_token_reserves,
+            ),
+            (
+                "token_total_supply",
+                self.token_total_supply == existing.token_total_supply,
+            ),
+            (
+                "fee_basis_points",
+                self.fee_basis_points == existing.fee_basis_points,
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, matches)| !matches)
+        .map(|(field, _)| field)
+        .collect()
+    }
+}
+
+impl From<&GlobalAccount> for GlobalParams {
+    fn from(global: &GlobalAccount) -> Self {
+        Self {
+            fee_recipient: global.fee_recipient,
+            initial_virtual_token_reserves: global.initial_virtual_token_reserves,
+            initial_virtual_sol_reserves: global.initial_virtual_sol_reserves,
+            initial_real_token_reserves: global.initial_real_token_reserves,
+            token_total_supply: global.token_total_supply,
+            fee_basis_points: global.fee_basis_points,
+        }
+    }
+}
+
+impl From<GlobalParams> for cpi::instruction::SetParams {
+    fn from(params: GlobalParams) -> Self {
+        Self {
+            _fee_recipient: params.fee_recipient,
+            _initial_virtual_token_reserves: params.initial_virtual_token_reserves,
+            _initial_virtual_sol_reserves: params.initial_virtual_sol_reserves,
+            _initial_real_token_reserves: params.initial_real_token_reserves,
+            _token_total_supply: params.token_total_supply,
+            _fee_basis_points: params.fee_basis_points,
+        }
+    }
+}
+
+/// Builds global parameters from explicitly set values
+///
+/// Nothing has a default, so every parameter a fork runs with is chosen on purpose. Start from
+/// [`GlobalParams::new`] instead to vary the mainnet parameters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GlobalParamsBuilder {
+    fee_recipient: Option<Pubkey>,
+    initial_virtual_token_reserves: Option<u64>,
+    initial_virtual_sol_reserves: Option<u64>,
+    initial_real_token_reserves: Option<u64>,
+    token_total_supply: Option<u64>,
+    fee_basis_points: Option<u64>,
+}
+
+impl GlobalParamsBuilder {
+    /// Sets the account receiving the trading fees
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_recipient` - Account receiving the trading fees
+    pub fn with_fee_recipient(mut self, fee_recipient: Pubkey) -> Self {
+        self.fee_recipient = Some(fee_recipient);
+        self
+    }
+
+    /// Sets the virtual token reserves a new curve starts with
+    ///
+    /// # Arguments
+    ///
+    /// * `reserves` - Virtual token reserves of a new curve
+    pub fn with_initial_virtual_token_reserves(mut self, reserves: u64) -> Self {
+        self.initial_virtual_token_reserves = Some(reserves);
+        self
+    }
+
+    /// Sets the virtual SOL reserves a new curve starts with, in lamports
+    ///
+    /// # Arguments
+    ///
+    /// * `reserves` - Virtual SOL reserves of a new curve in lamports
+    pub fn with_initial_virtual_sol_reserves(mut self, reserves: u64) -> Self {
+        self.initial_virtual_sol_reserves = Some(reserves);
+        self
+    }
+
+    /// Sets the tokens a new curve can sell before it completes
+    ///
+    /// # Arguments
+    ///
+    /// * `reserves` - Tokens a new curve can sell
+    pub fn with_initial_real_token_reserves(mut self, reserves: u64) -> Self {
+        self.initial_real_token_reserves = Some(reserves);
+        self
+    }
+
+    /// Sets the total supply of each new token
+    ///
+    /// # Arguments
+    ///
+    /// * `supply` - Total supply of each new token
+    pub fn with_token_total_supply(mut self, supply: u64) -> Self {
+        self.token_total_supply = Some(supply);
+        self
+    }
+
+    /// Sets the fee charged on each trade in basis points
+    ///
+    /// # Arguments
+    ///
+    /// * `fee_basis_points` - Fee in basis points
+    pub fn with_fee_basis_points(mut self, fee_basis_points: u64) -> Self {
+        self.fee_basis_points = Some(fee_basis_points);
+        self
+    }
+
+    /// Builds the parameters, checking that every one is set and that they are valid together
+    ///
+    /// # Returns
+    ///
+    /// Returns the validated parameters, or a ClientError::InvalidGlobalParams listing the
+    /// parameters not set, or if all are set, every violated constraint
+    pub fn build(self) -> Result<ValidatedGlobalParams, ClientError> {
+        let (
+            Some(fee_recipient),
+            Some(initial_virtual_token_reserves),
+            Some(initial_virtual_sol_reserves),
+            Some(initial_real_token_reserves),
+            Some(token_total_supply),
+            Some(fee_basis_points),
+        ) = (
+            self.fee_recipient,
+            self.initial_virtual_token_reserves,
+            self.initial_virtual_sol_reserves,
+            self.initial_real_token_reserves,
+            self.token_total_supply,
+            self.fee_basis_points,
+        )
+        else {
+            let unset = [
+                ("fee_recipient", self.fee_recipient.is_none()),
+                (
+                    "initial_virtual_token_reserves",
+                    self.initial_virtual_token_reserves.is_none(),
+                ),
+                (
+                    "initial_virtual_sol_reserves",
+                    self.initial_virtual_sol_reserves.is_none(),
+                ),
+                (
+                    "initial_real_token_reserves",
+                    self.initial_real_token_reserves.is_none(),
+                ),
+                ("token_total_supply", self.token_total_supply.is_none()),
+                ("fee_basis_points", self.fee_basis_points.is_none()),
+            ];
+            return Err(ClientError::InvalidGlobalParams {
+                violations: unset
+                    .into_iter()
+                    .filter(|(_, unset)| *unset)
+                    .map(|(field, _)| GlobalParamsViolation::Missing { field })
+                    .collect(),
+            });
+        };
+
+        GlobalParams {
+            fee_recipient,
+            initial_virtual_token_reserves,
+            initial_virtual_sol_reserves,
+            initial_real_token_reserves,
+            token_total_supply,
+            fee_basis_points,
+        }
+        .validate()
+    }
+}
+
+/// Global parameters known to satisfy every constraint of [`GlobalParams::violations`]
+///
+/// Only [`GlobalParams::validate`] and [`GlobalParamsBuilder::build`] create it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidatedGlobalParams(GlobalParams);
+
+impl ValidatedGlobalParams {
+    /// Gets the validated parameters
+    pub fn params(&self) -> &GlobalParams {
+        &self.0
+    }
+}
+
+impl From<ValidatedGlobalParams> for GlobalParams {
+    fn from(params: ValidatedGlobalParams) -> Self {
+        params.0
+    }
+}
+
+impl From<ValidatedGlobalParams> for cpi::instruction::SetParams {
+    fn from(params: ValidatedGlobalParams) -> Self {
+        params.0.into()
+    }
+}
+
+/// How [`ensure_initialized`] found the program
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InitOutcome {
+    /// The program was initialized by this call, in the transaction with this signature
+    Initialized(Signature),
+    /// The program was already initialized, possibly by a concurrent call
+    AlreadyInitialized {
+        /// Names of the parameters that differ from the ones required, empty if all match
+        mismatches: Vec<&'static str>,
+    },
+}
+
+/// Initializes the program's global account with the given parameters, unless it exists
+///
+/// The initialize and set params instructions are sent in a single transaction, so the global
+/// account never exists without its parameters. The client's payer becomes the program's
+/// authority. If the transaction fails because another process initialized the program first,
+/// the account it created is checked instead of returning the error.
+///
+/// # Arguments
+///
+/// * `client` - Client connected to the fork, whose payer pays for the global account
+/// * `params` - Parameters to initialize the program with, or to expect if it is initialized
+/// * `strict` - Whether differing parameters on an initialized program are an error rather
+///   than a warning
+///
+/// # Returns
+///
+/// Returns how the program was found, a ClientError::GlobalParamsMismatch if `strict` is set
+/// and the existing parameters differ, a ClientError::InvalidGlobalParams if the program would
+/// be initialized with invalid parameters, or a ClientError if the initialization fails
+pub async fn ensure_initialized(
+    client: &PumpFun<'_>,
+    params: &GlobalParams,
+    strict: bool,
+) -> Result<InitOutcome, ClientError> {
+    if let Some(global) = fetch_global(client)? {
+        return check_existing(&global, params, strict);
+    }
+    let validated = params.validate()?;
+
+    let authority = client.signer();
+    let request = client
+        .program
+        .request()
+        .instruction(instruction::initialize_with_seeds(
+            &client.seeds,
+            &authority,
+        ))
+        .instruction(instruction::set_params_with_seeds(
+            &client.seeds,
+            &authority,
+            validated.into(),
+        ));
+    let transaction = client.sign_request(&request, &[]).await?;
+
+    match client.send_transaction(&transaction).await {
+        Ok(signature) => {
+            tracing::debug!(%signature, "Program initialized");
+            Ok(InitOutcome::Initialized(signature))
+        }
+        Err(err) => match fetch_global(client)? {
+            // Another process created the global account between the read and the transaction
+            Some(global) => {
+                tracing::debug!("Program initialized concurrently: {}", err);
+                check_existing(&global, params, strict)
+            }
+            None => Err(err),
+        },
+    }
+}
+
+/// Sets the parameters of an initialized program, with the client's payer as its authority
+///
+/// # Arguments
+///
+/// * `client` - Client whose payer is the program's authority
+/// * `params` - Validated parameters to set
+///
+/// # Returns
+///
+/// Returns the signature of the transaction, or a ClientError if it fails
+pub async fn set_params(
+    client: &PumpFun<'_>,
+    params: &ValidatedGlobalParams,
+) -> Result<Signature, ClientError> {
+    let request = client
+        .program
+        .request()
+        .instruction(instruction::set_params_with_seeds(
+            &client.seeds,
+            &client.signer(),
+            (*params).into(),
+        ));
+    let transaction = client.sign_request(&request, &[]).await?;
+    let signature = client.send_transaction(&transaction).await?;
+    tracing::debug!(%signature, "Global params set");
+    Ok(signature)
+}
+
+/// Reads the global account, or None if it does not exist
+fn fetch_global(client: &PumpFun<'_>) -> Result<Option<GlobalAccount>, ClientError> {
+    let global = PumpFun::get_global_pda_with_seeds(&client.seeds);
+    client
+        .rpc
+        .get_account_with_commitment(&global, client.rpc.commitment())
+        .map_err(ClientError::SolanaClientError)?
+        .value
+        .map(|account| client.decode_account(&global, &account.data))
+        .transpose()
+}
+
+/// Checks the parameters of an initialized program
+fn check_existing(
+    global: &GlobalAccount,
+    params: &GlobalParams,
+    strict: bool,
+) -> Result<InitOutcome, ClientError> {
+    let mismatches = params.mismatches(global);
+    if !mismatches.is_empty() {
+        if strict {
+            return Err(ClientError::GlobalParamsMismatch { fields: mismatches });
+        }
+        tracing::warn!(
+            ?mismatches,
+            "Program initialized with other global parameters"
+        );
+    }
+    Ok(InitOutcome::AlreadyInitialized { mismatches })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::{
+        anchor_lang::InstructionData,
+        solana_client::{
+            client_error::Result as ClientResult,
+            rpc_request::RpcRequest,
+            rpc_sender::{RpcSender, RpcTransportStats},
+        },
+        Cluster,
+    };
+    use solana_sdk::{
+        instruction::InstructionError,
+        signature::Keypair,
+        transaction::{Transaction, TransactionError},
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier, Mutex,
+    };
+
+    fn get_global(params: &GlobalParams) -> GlobalAccount {
+        GlobalAccount::new(
+            1,
+            true,
+            Pubkey::new_unique(),
+            params.fee_recipient,
+            params.initial_virtual_token_reserves,
+            params.initial_virtual_sol_reserves,
+            params.initial_real_token_reserves,
+            params.token_total_supply,
+            params.fee_basis_points,
+        )
+    }
+
+    #[test]
+    fn test_global_params_conversions() {
+        let params = GlobalParams::new(Pubkey::new_unique());
+        let global = get_global(&params);
+        assert_eq!(GlobalParams::from(&global), params);
+
+        let args: cpi::instruction::SetParams = params.into();
+        assert_eq!(args._fee_recipient, params.fee_recipient);
+        assert_eq!(args._fee_basis_points, params.fee_basis_points);
+        assert_eq!(
+            instruction::set_params(&solana_sdk::signature::Keypair::new(), args).data,
+            cpi::instruction::SetParams::from(params).data()
+        );
+    }
+
+    #[test]
+    fn test_check_existing() {
+        let params = GlobalParams::new(Pubkey::new_unique());
+        let global = get_global(&params);
+        assert_eq!(
+            check_existing(&global, &params, true).unwrap(),
+            InitOutcome::AlreadyInitialized {
+                mismatches: Vec::new()
+            }
+        );
+
+        let other = GlobalParams {
+            fee_recipient: Pubkey::new_unique(),
+            fee_basis_points: 95,
+            ..params
+        };
+        assert_eq!(
+            other.mismatches(&global),
+            vec!["fee_recipient", "fee_basis_points"]
+        );
+
+        // Lenient checks warn and carry on, strict ones fail
+        assert_eq!(
+            check_existing(&global, &other, false).unwrap(),
+            InitOutcome::AlreadyInitialized {
+                mismatches: vec!["fee_recipient", "fee_basis_points"]
+            }
+        );
+        assert!(matches!(
+            check_existing(&global, &other, true),
+            Err(ClientError::GlobalParamsMismatch { fields })
+                if fields == vec!["fee_recipient", "fee_basis_points"]
+        ));
+    }
+
+    /// Fork shared by every caller, where only the first initialization lands
+    struct Fork {
+        /// Parameters the landed initialization sets
+        params: GlobalParams,
+        /// Data of the global account, None until it is created
+        global: Mutex<Option<Vec<u8>>>,
+        /// Holds every transaction until all callers are sending one
+        senders: Barrier,
+        /// Number of reads of the global account
+        reads: AtomicUsize,
+        /// Number of transactions that initialized the program
+        initializations: AtomicUsize,
+    }
+
+    /// Transport of one caller of the shared fork
+    struct ForkSender(Arc<Fork>);
+
+    #[async_trait::async_trait]
+    impl RpcSender for ForkSender {
+        async fn send(
+            &self,
+            request: RpcRequest,
+            params: serde_json::Value,
+        ) -> ClientResult<serde_json::Value> {
+            use base64::Engine;
+
+            let fork = &self.0;
+            let value = match request {
+                RpcRequest::GetAccountInfo => {
+                    fork.reads.fetch_add(1, Ordering::SeqCst);
+                    match &*fork.global.lock().unwrap() {
+                        Some(data) => serde_json::json!({
+                            "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                            "executable": false,
+                            "lamports": 1,
+                            "owner": crate::constants::accounts::PUMPFUN.to_string(),
+                            "rentEpoch": 0,
+                        }),
+                        None => serde_json::Value::Null,
+                    }
+                }
+                RpcRequest::GetVersion => {
+                    return Ok(serde_json::json!({
+                        "solana-core": "1.18.26",
+                        "feature-set": 0,
+                    }))
+                }
+                RpcRequest::GetLatestBlockhash => serde_json::json!({
+                    "blockhash": solana_sdk::hash::Hash::new_unique().to_string(),
+                    "lastValidBlockHeight": 100,
+                }),
+                RpcRequest::SendTransaction => {
+                    fork.senders.wait();
+                    let encoded = params[0].as_str().unwrap();
+                    let transaction: Transaction = bincode::deserialize(
+                        &base64::engine::general_purpose::STANDARD
+                            .decode(encoded)
+                            .unwrap(),
+                    )
+                    .unwrap();
+                    let mut global = fork.global.lock().unwrap();
+                    if global.is_some() {
+                        // The global account already exists, so creating it again fails
+                        return Err(TransactionError::InstructionError(
+                            0,
+                            InstructionError::Custom(0),
+                        )
+                        .into());
+                    }
+                    let created = GlobalAccount::new(
+                        1,
+                        true,
+                        transaction.message.account_keys[0],
+                        fork.params.fee_recipient,
+                        fork.params.initial_virtual_token_reserves,
+                        fork.params.initial_virtual_sol_reserves,
+                        fork.params.initial_real_token_reserves,
+                        fork.params.token_total_supply,
+                        fork.params.fee_basis_points,
+                    );
+                    *global = Some(borsh::to_vec(&created).unwrap());
+                    fork.initializations.fetch_add(1, Ordering::SeqCst);
+                    return Ok(serde_json::json!(transaction.signatures[0].to_string()));
+                }
+                RpcRequest::GetSignatureStatuses => params[0]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|_| {
+                        serde_json::json!({
+                            "slot": 1,
+                            "confirmations": null,
+                            "err": null,
+                            "status": { "Ok": null },
+                            "confirmationStatus": "finalized",
+                        })
+                    })
+                    .collect(),
+                request => panic!("unexpected request {request}"),
+            };
+            Ok(serde_json::json!({ "context": { "slot": 1 }, "value": value }))
+        }
+
+        fn get_transport_stats(&self) -> RpcTransportStats {
+            Default::default()
+        }
+
+        fn url(&self) -> String {
+            "fork".to_string()
+        }
+    }
+
+    #[test]
+    fn test_concurrent_callers_initialize_once() {
+        const CALLERS: usize = 4;
+        let params = GlobalParams::new(Pubkey::new_unique());
+        let fork = Arc::new(Fork {
+            params,
+            global: Mutex::new(None),
+            senders: Barrier::new(CALLERS),
+            reads: AtomicUsize::new(0),
+            initializations: AtomicUsize::new(0),
+        });
+
+        // Every caller finds the program uninitialized before any transaction lands
+        let callers: Vec<_> = (0..CALLERS)
+            .map(|_| {
+                let fork = fork.clone();
+                std::thread::spawn(move || {
+                    let payer = Keypair::new();
+                    let client =
+                        PumpFun::new_with_sender(Cluster::Localnet, &payer, None, ForkSender(fork));
+                    futures::executor::block_on(ensure_initialized(&client, &params, true)).unwrap()
+                })
+            })
+            .collect();
+        let outcomes: Vec<InitOutcome> = callers
+            .into_iter()
+            .map(|caller| caller.join().unwrap())
+            .collect();
+
+        // One caller initializes the program, and the others check its account
+        assert_eq!(fork.initializations.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            outcomes
+                .iter()
+                .filter(|outcome| matches!(outcome, InitOutcome::Initialized(_)))
+                .count(),
+            1
+        );
+        assert_eq!(
+            outcomes
+                .iter()
+                .filter(|outcome| **outcome
+                    == InitOutcome::AlreadyInitialized {
+                        mismatches: Vec::new()
+                    })
+                .count(),
+            CALLERS - 1
+        );
+        assert_eq!(fork.reads.load(Ordering::SeqCst), 2 * CALLERS - 1);
+    }
+
+    fn violations(
+        result: Result<ValidatedGlobalParams, ClientError>,
+    ) -> Vec<GlobalParamsViolation> {
+        match result {
+            Err(ClientError::InvalidGlobalParams { violations }) => violations,
+            other => panic!("expected invalid params, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mainnet_params_are_valid() {
+        let recipient = Pubkey::new_unique();
+        let params = GlobalParams::new(recipient);
+        assert!(params.violations().is_empty());
+        assert_eq!(*params.validate().unwrap().params(), params);
+
+        let built = GlobalParams::builder()
+            .with_fee_recipient(recipient)
+            .with_initial_virtual_token_reserves(1_073_000_000_000_000)
+            .with_initial_virtual_sol_reserves(30_000_000_000)
+            .with_initial_real_token_reserves(793_100_000_000_000)
+            .with_token_total_supply(1_000_000_000_000_000)
+            .with_fee_basis_points(100)
+            .build()
+            .unwrap();
+        assert_eq!(GlobalParams::from(built), params);
+        assert_eq!(
+            cpi::instruction::SetParams::from(built).data(),
+            cpi::instruction::SetParams::from(params).data()
+        );
+    }
+
+    #[test]
+    fn test_builder_requires_every_param() {
+        assert_eq!(
+            violations(GlobalParams::builder().build()),
+            [
+                "fee_recipient",
+                "initial_virtual_token_reserves",
+                "initial_virtual_sol_reserves",
+                "initial_real_token_reserves",
+                "token_total_supply",
+                "fee_basis_points",
+            ]
+            .map(|field| GlobalParamsViolation::Missing { field })
+        );
+        assert_eq!(
+            violations(
+                GlobalParams::builder()
+                    .with_fee_recipient(Pubkey::new_unique())
+                    .with_initial_virtual_token_reserves(1)
+                    .with_initial_virtual_sol_reserves(1)
+                    .with_initial_real_token_reserves(1)
+                    .with_token_total_supply(1)
+                    .build()
+            ),
+            [GlobalParamsViolation::Missing {
+                field: "fee_basis_points"
+            }]
+        );
+    }
+
+    #[test]
+    fn test_each_violation() {
+        let valid = GlobalParams::new(Pubkey::new_unique());
+        let cases = [
+            (
+                GlobalParams {
+                    fee_recipient: Pubkey::default(),
+                    ..valid
+                },
+                vec![GlobalParamsViolation::DefaultFeeRecipient],
+            ),
+            (
+                GlobalParams {
+                    initial_virtual_token_reserves: 0,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::ZeroVirtualTokenReserves],
+            ),
+            (
+                GlobalParams {
+                    initial_virtual_sol_reserves: 0,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::ZeroVirtualSolReserves],
+            ),
+            (
+                GlobalParams {
+                    initial_real_token_reserves: 0,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::ZeroRealTokenReserves],
+            ),
+            (
+                GlobalParams {
+                    token_total_supply: 0,
+                    ..valid
+                },
+                vec![
+                    GlobalParamsViolation::ZeroTokenTotalSupply,
+                    GlobalParamsViolation::SupplyBelowRealReserves {
+                        token_total_supply: 0,
+                        initial_real_token_reserves: 793_100_000_000_000,
+                    },
+                ],
+            ),
+            (
+                GlobalParams {
+                    fee_basis_points: 10_001,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::FeeTooHigh {
+                    fee_basis_points: 10_001,
+                }],
+            ),
+            (
+                GlobalParams {
+                    token_total_supply: 793_099_999_999_999,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::SupplyBelowRealReserves {
+                    token_total_supply: 793_099_999_999_999,
+                    initial_real_token_reserves: 793_100_000_000_000,
+                }],
+            ),
+            (
+                GlobalParams {
+                    initial_real_token_reserves: 1_073_000_000_000_000,
+                    token_total_supply: 1_073_000_000_000_000,
+                    ..valid
+                },
+                vec![GlobalParamsViolation::RealReservesNotBelowVirtual {
+                    initial_real_token_reserves: 1_073_000_000_000_000,
+                    initial_virtual_token_reserves: 1_073_000_000_000_000,
+                }],
+            ),
+        ];
+        for (params, expected) in cases {
+            assert_eq!(params.violations(), expected);
+            assert_eq!(violations(params.validate()), expected);
+        }
+
+        // The whole fee and a supply equal to the real reserves are still valid
+        assert!(GlobalParams {
+            fee_basis_points: MAX_FEE_BASIS_POINTS,
+            token_total_supply: 793_100_000_000_000,
+            ..valid
+        }
+        .validate()
+        .is_ok());
+
+        // Every violation is reported together, each with its explanation
+        let err = GlobalParams {
+            fee_recipient: Pubkey::default(),
+            fee_basis_points: 20_000,
+            ..valid
+        }
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid global params: fee_recipient is the default address, so the fees could \
+             never be withdrawn; fee_basis_points is 20000, so the fee would exceed the \
+             amount traded"
+        );
+    }
+}
@@ -0,0 +1,176 @@
+//! Latency instrumentation for the trade path
+//!
+//! This module records monotonic timestamps at well-defined stages of a trade so callers can see
+//! where time is spent between quoting and confirmation:
+//!
+//! - `Stage`: The stages of a trade, in the order they happen.
+//! - `StageTimer`: Captures an `Instant` as each stage is reached.
+//! - `StageTimings`: The recorded offsets from the start of the quote, with derived durations.
+
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Stages of a trade, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Global and bonding curve accounts have been fetched
+    AccountsFetched,
+    /// The transaction instructions have been assembled
+    Built,
+    /// The transaction has been signed
+    Signed,
+    /// The transaction is about to be sent for the first time
+    Sent,
+    /// The RPC node acknowledged the transaction
+    Acknowledged,
+    /// The transaction reached the client's commitment level
+    Confirmed,
+}
+
+/// Records monotonic timestamps as a trade moves through its stages, from the start of the quote
+#[derive(Debug, Clone, Copy)]
+pub struct StageTimer {
+    /// When the quote started
+    start: Instant,
+    /// Offsets recorded so far
+    timings: StageTimings,
+}
+
+impl StageTimer {
+    /// Starts a new timer at the beginning of the quote
+    pub fn start() -> Self {
+        Self {
+            start: Instant::now(),
+            timings: StageTimings::default(),
+        }
+    }
+
+    /// Records that a stage has been reached
+    ///
+    /// # Arguments
+    ///
+    /// * `stage` - The stage that was reached
+    pub fn record(&mut self, stage: Stage) {
+        let elapsed = self.start.elapsed();
+        let slot = match stage {
+            Stage::AccountsFetched => &mut self.timings.accounts_fetched,
+            Stage::Built => &mut self.timings.built,
+            Stage::Signed => &mut self.timings.signed,
+            Stage::Sent => &mut self.timings.sent,
+            Stage::Acknowledged => &mut self.timings.acknowledged,
+            Stage::Confirmed => &mut self.timings.confirmed,
+        };
+        *slot = elapsed;
+    }
+
+    /// Returns the timings recorded so far
+    pub fn timings(&self) -> StageTimings {
+        self.timings
+    }
+}
+
+/// Offsets of each trade stage from the start of the quote
+///
+/// Stages that were not reached are left at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StageTimings {
+    /// Time until the global and bonding curve accounts were fetched
+    pub accounts_fetched: Duration,
+    /// Time until the transaction instructions were assembled
+    pub built: Duration,
+    /// Time until the transaction was signed
+    pub signed: Duration,
+    /// Time until the transaction was first sent
+    pub sent: Duration,
+    /// Time until the RPC node acknowledged the transaction
+    pub acknowledged: Duration,
+    /// Time until the transaction was confirmed
+    pub confirmed: Duration,
+}
+
+impl StageTimings {
+    /// Time spent fetching accounts for the quote
+    pub fn fetch_duration(&self) -> Duration {
+        self.accounts_fetched
+    }
+
+    /// Time spent assembling the transaction instructions
+    pub fn build_duration(&self) -> Duration {
+        self.built.saturating_sub(self.accounts_fetched)
+    }
+
+    /// Time spent fetching a blockhash and signing the transaction
+    pub fn sign_duration(&self) -> Duration {
+        self.signed.saturating_sub(self.built)
+    }
+
+    /// Time between the first send and the RPC acknowledgement
+    pub fn send_duration(&self) -> Duration {
+        self.acknowledged.saturating_sub(self.sent)
+    }
+
+    /// Time between the RPC acknowledgement and confirmation
+    pub fn confirm_duration(&self) -> Duration {
+        self.confirmed.saturating_sub(self.acknowledged)
+    }
+
+    /// Total time from the start of the quote to confirmation
+    pub fn total(&self) -> Duration {
+        self.confirmed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stage_timer_records_in_order() {
+        let mut timer = StageTimer::start();
+        for stage in [
+            Stage::AccountsFetched,
+            Stage::Built,
+            Stage::Signed,
+            Stage::Sent,
+            Stage::Acknowledged,
+            Stage::Confirmed,
+        ] {
+            timer.record(stage);
+        }
+
+        let timings = timer.timings();
+        assert!(timings.accounts_fetched <= timings.built);
+        assert!(timings.built <= timings.signed);
+        assert!(timings.signed <= timings.sent);
+        assert!(timings.sent <= timings.acknowledged);
+        assert!(timings.acknowledged <= timings.confirmed);
+    }
+
+    #[test]
+    fn test_stage_timings_durations() {
+        let timings = StageTimings {
+            accounts_fetched: Duration::from_millis(40),
+            built: Duration::from_millis(41),
+            signed: Duration::from_millis(90),
+            sent: Duration::from_millis(90),
+            acknowledged: Duration::from_millis(120),
+            confirmed: Duration::from_millis(520),
+        };
+
+        assert_eq!(timings.fetch_duration(), Duration::from_millis(40));
+        assert_eq!(timings.build_duration(), Duration::from_millis(1));
+        assert_eq!(timings.sign_duration(), Duration::from_millis(49));
+        assert_eq!(timings.send_duration(), Duration::from_millis(30));
+        assert_eq!(timings.confirm_duration(), Duration::from_millis(400));
+        assert_eq!(timings.total(), Duration::from_millis(520));
+
+        // Unreached stages never produce negative durations
+        assert_eq!(StageTimings::default().confirm_duration(), Duration::ZERO);
+
+        let json = serde_json::to_string(&timings).unwrap();
+        assert_eq!(
+            serde_json::from_str::<StageTimings>(&json).unwrap(),
+            timings
+        );
+    }
+}
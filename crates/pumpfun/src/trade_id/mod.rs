@@ -0,0 +1,104 @@
+//! Client-generated identifiers for logical trades
+//!
+//! A transaction signature only exists once a trade is signed and changes whenever the
+//! transaction is rebuilt, so it cannot correlate everything that happens for one logical trade.
+//! Each client assigns a [`TradeId`] at the start of every trade method instead, which is carried
+//! through outcomes, journal entries and tracing fields.
+//!
+//! - `TradeId`: Identifier of a logical trade.
+//! - `TradeIdGenerator`: Hands out unique, increasing identifiers for a client instance.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Identifier of a logical trade, unique across client instances
+///
+/// The random session prefix distinguishes client instances, including restarts of the same
+/// process, and the sequence increases with every trade started by the instance. Formats as
+/// `{session:08x}-{sequence}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct TradeId {
+    /// Random prefix chosen when the client was created
+    pub session: u32,
+    /// Position of the trade among those started by the client
+    pub sequence: u64,
+}
+
+impl std::fmt::Display for TradeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:08x}-{}", self.session, self.sequence)
+    }
+}
+
+/// Hands out unique, increasing trade identifiers for a client instance
+///
+/// Safe to share between threads.
+#[derive(Debug)]
+pub struct TradeIdGenerator {
+    /// Random prefix shared by every identifier from this generator
+    session: u32,
+    /// Sequence of the next identifier
+    next: AtomicU64,
+}
+
+impl TradeIdGenerator {
+    /// Creates a generator with a random session prefix
+    pub fn new() -> Self {
+        Self::with_session(rand::random())
+    }
+
+    /// Creates a generator with a fixed session prefix
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - Prefix shared by every identifier from this generator
+    pub fn with_session(session: u32) -> Self {
+        Self {
+            session,
+            next: AtomicU64::new(0),
+        }
+    }
+
+    /// Assigns the identifier for a new logical trade
+    pub fn next_id(&self) -> TradeId {
+        TradeId {
+            session: self.session,
+            sequence: self.next.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+}
+
+impl Default for TradeIdGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{collections::HashSet, sync::Arc, thread};
+
+    #[test]
+    fn test_trade_ids_are_unique_across_threads() {
+        let generator = Arc::new(TradeIdGenerator::with_session(0xdead_beef));
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let generator = generator.clone();
+                thread::spawn(move || (0..250).map(|_| generator.next_id()).collect::<Vec<_>>())
+            })
+            .collect();
+
+        let ids: HashSet<TradeId> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        assert_eq!(ids.len(), 1000);
+        assert!(ids.iter().all(|id| id.session == 0xdead_beef));
+
+        // Identifiers keep increasing after the concurrent burst
+        let next = generator.next_id();
+        assert_eq!(next.sequence, 1000);
+        assert_eq!(next.to_string(), "deadbeef-1000");
+    }
+}
@@ -0,0 +1,176 @@
+//! Detection of Pump.fun program upgrades at runtime
+//!
+//! When the program is upgraded, the first symptom is usually a wave of opaque transaction
+//! failures, as instructions built for the old program are rejected. The slot the program was
+//! last deployed at is recorded in its program data account, so the client can poll it with
+//! [`crate::PumpFun::check_program_upgrade`] and react as soon as it changes:
+//!
+//! - Cached program state, such as the detected program version, is dropped.
+//! - A `ClientEvent::ProgramUpgraded` is sent to every receiver returned by
+//!   [`crate::PumpFun::subscribe_client_events`].
+//! - If enabled with [`crate::PumpFun::with_pause_on_upgrade`], transactions are refused with
+//!   `ClientError::TradingPaused` until [`crate::PumpFun::acknowledge_program_upgrade`] is
+//!   called.
+//!
+//! The first check only records the deployment slot; an upgrade is a later check reading a
+//! different one. Trade plans and quotes held by the caller are not tracked by the client and
+//! should be rebuilt on the event.
+//!
+//! - `ClientEvent`: Notification sent to the subscribers of a client.
+//! - `decode_deployed_slot`: Reads the deployment slot from a program data account.
+
+use crate::error::ClientError;
+use anchor_client::solana_sdk::bpf_loader_upgradeable::UpgradeableLoaderState;
+use futures::channel::mpsc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+/// Notification sent to the subscribers of a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ClientEvent {
+    /// The Pump.fun program was redeployed
+    ProgramUpgraded {
+        /// Slot the program was redeployed at
+        slot: u64,
+    },
+}
+
+/// Last known deployment slot, subscribers and pause state of a client
+#[derive(Debug, Default)]
+pub(crate) struct UpgradeWatch {
+    /// Slot the program was deployed at when last checked, None before the first check
+    deployed_slot: Mutex<Option<u64>>,
+    /// Senders of the receivers returned to subscribers
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<ClientEvent>>>,
+    /// Whether transactions are refused until an upgrade is acknowledged
+    paused: AtomicBool,
+    /// Whether a detected upgrade pauses transactions
+    pub(crate) pause_on_upgrade: bool,
+}
+
+impl UpgradeWatch {
+    /// Gets the slot the program was deployed at when last checked
+    pub(crate) fn deployed_slot(&self) -> Option<u64> {
+        *self
+            .deployed_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Records the program's deployment slot
+    ///
+    /// # Returns
+    ///
+    /// Returns whether the slot differs from the one recorded by a previous check
+    pub(crate) fn observe(&self, slot: u64) -> bool {
+        let mut deployed_slot = self
+            .deployed_slot
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let upgraded = deployed_slot.is_some_and(|previous| previous != slot);
+        *deployed_slot = Some(slot);
+        upgraded
+    }
+
+    /// Creates a receiver of the events sent from now on
+    pub(crate) fn subscribe(&self) -> mpsc::UnboundedReceiver<ClientEvent> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .push(sender);
+        receiver
+    }
+
+    /// Sends an event to every subscriber, forgetting those that dropped their receiver
+    pub(crate) fn notify(&self, event: ClientEvent) {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .retain(|sender| sender.unbounded_send(event).is_ok());
+    }
+
+    /// Whether transactions are refused until an upgrade is acknowledged
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    /// Pauses or resumes transactions
+    pub(crate) fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Release);
+    }
+}
+
+/// Reads the slot a program was last deployed at from its program data account
+///
+/// Only the metadata at the start of the account is read, so the data may be truncated to
+/// [`UpgradeableLoaderState::size_of_programdata_metadata`] bytes.
+///
+/// # Arguments
+///
+/// * `data` - Data of the program's program data account
+///
+/// # Returns
+///
+/// Returns the deployment slot, or a ClientError if the data is not a program data account
+pub fn decode_deployed_slot(data: &[u8]) -> Result<u64, ClientError> {
+    match bincode::deserialize::<UpgradeableLoaderState>(data) {
+        Ok(UpgradeableLoaderState::ProgramData { slot, .. }) => Ok(slot),
+        _ => Err(ClientError::InvalidInput("Invalid program data account")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_decode_deployed_slot() {
+        let data = bincode::serialize(&UpgradeableLoaderState::ProgramData {
+            slot: 250_000_000,
+            upgrade_authority_address: Some(Pubkey::new_unique()),
+        })
+        .unwrap();
+        assert_eq!(
+            data.len(),
+            UpgradeableLoaderState::size_of_programdata_metadata()
+        );
+        assert_eq!(decode_deployed_slot(&data).unwrap(), 250_000_000);
+
+        let program = bincode::serialize(&UpgradeableLoaderState::Program {
+            programdata_address: Pubkey::new_unique(),
+        })
+        .unwrap();
+        assert!(decode_deployed_slot(&program).is_err());
+        assert!(decode_deployed_slot(&[]).is_err());
+    }
+
+    #[test]
+    fn test_upgrade_watch() {
+        let watch = UpgradeWatch::default();
+        let mut receiver = watch.subscribe();
+        let dropped = watch.subscribe();
+        drop(dropped);
+
+        // The first reading is a baseline, not an upgrade
+        assert!(!watch.observe(100));
+        assert!(!watch.observe(100));
+        assert!(watch.observe(200));
+        assert_eq!(watch.deployed_slot(), Some(200));
+
+        watch.notify(ClientEvent::ProgramUpgraded { slot: 200 });
+        assert_eq!(
+            receiver.try_next().unwrap(),
+            Some(ClientEvent::ProgramUpgraded { slot: 200 })
+        );
+        assert_eq!(watch.subscribers.lock().unwrap().len(), 1);
+
+        assert!(!watch.is_paused());
+        watch.set_paused(true);
+        assert!(watch.is_paused());
+    }
+}
@@ -0,0 +1,573 @@
+//! Accounting of RPC requests per operation
+//!
+//! Paid RPC plans bill per request or per credit, so this module counts every request the
+//! client sends and attributes it to the high-level operation that initiated it:
+//!
+//! - `Operation`: The operations requests are attributed to.
+//! - `RpcMethod`: The RPC methods requests are counted under.
+//! - `CostTable`: Credits charged per request of each method, for a rough bill estimate.
+//! - `RpcUsage`: Request counters per operation and method.
+//! - `UsageReport`: A snapshot of the counters with the estimated credits.
+//! - `OperationScope`: Attributes requests made on the current thread to an operation.
+//! - `scoped`: Attributes requests made while a future is polled to an operation.
+//! - `UsageSender`: RPC transport wrapper recording each request before sending it.
+//!
+//! Synchronous methods attribute the requests of the thread they run on with an
+//! [`OperationScope`]. Asynchronous methods wrap their future with [`scoped`] instead, which sets
+//! the operation only while that future is polled, so futures polled concurrently on one
+//! thread, such as a buy and a sell joined together, each keep their own operation. An
+//! operation started inside another one is attributed to the outer one, so a caller can scope
+//! a whole workflow, such as refreshing a portfolio, under a single operation: with
+//! [`OperationScope`] for synchronous code, and with [`scoped`] for asynchronous code, since a
+//! scope held across an `.await` is shared with every future polled on the thread meanwhile.
+
+use anchor_client::solana_client::{
+    client_error::Result as ClientResult,
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use std::{
+    cell::Cell,
+    future::Future,
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// High-level operations RPC requests are attributed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    /// Buying tokens, including token creation with an initial buy
+    Buy,
+    /// Selling tokens
+    Sell,
+    /// Creating tokens
+    Create,
+    /// Swapping through wrapped SOL
+    Swap,
+    /// Quoting trades from curve state
+    Quote,
+    /// Reading account and token state
+    Query,
+    /// Fetching transaction and account history
+    Backfill,
+    /// Keeping subscriptions and watchers alive
+    Subscription,
+    /// Closing empty token accounts
+    Cleanup,
+    /// Requests made outside any operation
+    Untagged,
+}
+
+impl Operation {
+    /// Every operation, in report order
+    pub const ALL: [Operation; 10] = [
+        Operation::Buy,
+        Operation::Sell,
+        Operation::Create,
+        Operation::Swap,
+        Operation::Quote,
+        Operation::Query,
+        Operation::Backfill,
+        Operation::Subscription,
+        Operation::Cleanup,
+        Operation::Untagged,
+    ];
+
+    /// Gets the operation that requests made here are attributed to
+    ///
+    /// The operation of the future being polled, see [`scoped`], takes precedence over the
+    /// scope open on the thread.
+    pub fn current() -> Self {
+        TASK_OPERATION
+            .try_with(|operation| *operation)
+            .unwrap_or_else(|_| CURRENT_OPERATION.with(Cell::get))
+    }
+}
+
+/// RPC methods requests are counted under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RpcMethod {
+    /// `getAccountInfo`
+    GetAccountInfo,
+    /// `getMultipleAccounts`
+    GetMultipleAccounts,
+    /// `getProgramAccounts`
+    GetProgramAccounts,
+    /// `getBalance`
+    GetBalance,
+    /// `getTokenLargestAccounts`
+    GetTokenLargestAccounts,
+    /// `getSignaturesForAddress`
+    GetSignaturesForAddress,
+    /// `getTransaction`
+    GetTransaction,
+    /// `getBlock`
+    GetBlock,
+    /// `getSignatureStatuses`
+    GetSignatureStatuses,
+    /// `getLatestBlockhash`
+    GetLatestBlockhash,
+    /// `getSlot`
+    GetSlot,
+    /// `sendTransaction`
+    SendTransaction,
+    /// `simulateTransaction`
+    SimulateTransaction,
+    /// Any other method
+    Other,
+}
+
+impl RpcMethod {
+    /// Every method, in report order
+    pub const ALL: [RpcMethod; 14] = [
+        RpcMethod::GetAccountInfo,
+        RpcMethod::GetMultipleAccounts,
+        RpcMethod::GetProgramAccounts,
+        RpcMethod::GetBalance,
+        RpcMethod::GetTokenLargestAccounts,
+        RpcMethod::GetSignaturesForAddress,
+        RpcMethod::GetTransaction,
+        RpcMethod::GetBlock,
+        RpcMethod::GetSignatureStatuses,
+        RpcMethod::GetLatestBlockhash,
+        RpcMethod::GetSlot,
+        RpcMethod::SendTransaction,
+        RpcMethod::SimulateTransaction,
+        RpcMethod::Other,
+    ];
+
+    /// Classifies a request by its method
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - Request sent by the RPC client
+    pub fn from_request(request: &RpcRequest) -> Self {
+        match request {
+            RpcRequest::GetAccountInfo => Self::GetAccountInfo,
+            RpcRequest::GetMultipleAccounts => Self::GetMultipleAccounts,
+            RpcRequest::GetProgramAccounts => Self::GetProgramAccounts,
+            RpcRequest::GetBalance => Self::GetBalance,
+            RpcRequest::GetTokenLargestAccounts => Self::GetTokenLargestAccounts,
+            RpcRequest::GetSignaturesForAddress => Self::GetSignaturesForAddress,
+            RpcRequest::GetTransaction => Self::GetTransaction,
+            RpcRequest::GetBlock => Self::GetBlock,
+            RpcRequest::GetSignatureStatuses => Self::GetSignatureStatuses,
+            RpcRequest::GetLatestBlockhash => Self::GetLatestBlockhash,
+            RpcRequest::GetSlot => Self::GetSlot,
+            RpcRequest::SendTransaction => Self::SendTransaction,
+            RpcRequest::SimulateTransaction => Self::SimulateTransaction,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// Credits charged per request of each RPC method
+///
+/// Defaults to one credit per request. Providers price heavier methods higher, so set their
+/// costs to match the plan in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CostTable {
+    /// Credits per request, indexed by method
+    credits: [u64; RpcMethod::ALL.len()],
+}
+
+impl Default for CostTable {
+    fn default() -> Self {
+        Self {
+            credits: [1; RpcMethod::ALL.len()],
+        }
+    }
+}
+
+impl CostTable {
+    /// Sets the credits charged per request of a method
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - RPC method
+    /// * `credits` - Credits charged per request
+    ///
+    /// # Returns
+    ///
+    /// Returns the table with the cost applied
+    pub fn with_cost(mut self, method: RpcMethod, credits: u64) -> Self {
+        self.credits[method as usize] = credits;
+        self
+    }
+
+    /// Gets the credits charged per request of a method
+    pub fn cost(&self, method: RpcMethod) -> u64 {
+        self.credits[method as usize]
+    }
+}
+
+/// Request counters per operation and method
+///
+/// Recording a request is a single relaxed atomic increment, so the counters can stay enabled
+/// in production. Safe to share between threads.
+#[derive(Debug)]
+pub struct RpcUsage {
+    /// Number of requests, indexed by operation then method
+    counts: [[AtomicU64; RpcMethod::ALL.len()]; Operation::ALL.len()],
+}
+
+impl Default for RpcUsage {
+    fn default() -> Self {
+        Self {
+            counts: std::array::from_fn(|_| std::array::from_fn(|_| AtomicU64::new(0))),
+        }
+    }
+}
+
+impl RpcUsage {
+    /// Creates counters starting at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a request
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Operation the request is attributed to
+    /// * `method` - Method of the request
+    pub fn record(&self, operation: Operation, method: RpcMethod) {
+        self.counts[operation as usize][method as usize].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Gets the number of requests of a method attributed to an operation
+    pub fn count(&self, operation: Operation, method: RpcMethod) -> u64 {
+        self.counts[operation as usize][method as usize].load(Ordering::Relaxed)
+    }
+
+    /// Resets every counter to zero
+    pub fn reset(&self) {
+        self.counts
+            .iter()
+            .flatten()
+            .for_each(|count| count.store(0, Ordering::Relaxed));
+    }
+
+    /// Takes a snapshot of the counters
+    ///
+    /// # Arguments
+    ///
+    /// * `costs` - Credits charged per request of each method
+    ///
+    /// # Returns
+    ///
+    /// Returns the operations and methods with at least one request, with estimated credits
+    pub fn report(&self, costs: &CostTable) -> UsageReport {
+        let operations: Vec<OperationUsage> = Operation::ALL
+            .into_iter()
+            .filter_map(|operation| {
+                let requests: Vec<(RpcMethod, u64)> = RpcMethod::ALL
+                    .into_iter()
+                    .map(|method| (method, self.count(operation, method)))
+                    .filter(|(_, count)| *count > 0)
+                    .collect();
+                if requests.is_empty() {
+                    return None;
+                }
+
+                Some(OperationUsage {
+                    operation,
+                    total_requests: requests.iter().map(|(_, count)| count).sum(),
+                    credits: requests
+                        .iter()
+                        .map(|(method, count)| count.saturating_mul(costs.cost(*method)))
+                        .sum(),
+                    requests,
+                })
+            })
+            .collect();
+
+        UsageReport {
+            total_requests: operations.iter().map(|usage| usage.total_requests).sum(),
+            total_credits: operations.iter().map(|usage| usage.credits).sum(),
+            operations,
+        }
+    }
+}
+
+/// Requests attributed to one operation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationUsage {
+    /// Operation the requests are attributed to
+    pub operation: Operation,
+    /// Number of requests per method, in method order
+    pub requests: Vec<(RpcMethod, u64)>,
+    /// Number of requests across all methods
+    pub total_requests: u64,
+    /// Estimated credits charged for the requests
+    pub credits: u64,
+}
+
+/// Snapshot of the RPC usage counters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageReport {
+    /// Usage of each operation with at least one request, in operation order
+    pub operations: Vec<OperationUsage>,
+    /// Number of requests across all operations
+    pub total_requests: u64,
+    /// Estimated credits charged across all operations
+    pub total_credits: u64,
+}
+
+impl UsageReport {
+    /// Gets the usage of an operation, None if it made no requests
+    pub fn operation(&self, operation: Operation) -> Option<&OperationUsage> {
+        self.operations
+            .iter()
+            .find(|usage| usage.operation == operation)
+    }
+}
+
+thread_local! {
+    /// Operation that requests on this thread are attributed to
+    static CURRENT_OPERATION: Cell<Operation> = const { Cell::new(Operation::Untagged) };
+}
+
+tokio::task_local! {
+    /// Operation that requests made while the current future is polled are attributed to
+    static TASK_OPERATION: Operation;
+}
+
+/// Attributes requests made while a future is polled to an operation
+///
+/// The operation is set for each poll of the future only, so it never leaks into other futures
+/// polled on the same thread. If an operation is already in scope where this is called, the
+/// future keeps that one instead, so requests are attributed to the operation that initiated
+/// them.
+///
+/// # Arguments
+///
+/// * `operation` - Operation requests are attributed to, unless one is already in scope
+/// * `future` - Future whose requests are attributed
+///
+/// # Returns
+///
+/// Returns a future resolving to the output of `future`
+pub fn scoped<F: Future>(operation: Operation, future: F) -> impl Future<Output = F::Output> {
+    let operation = match Operation::current() {
+        Operation::Untagged => operation,
+        outer => outer,
+    };
+    TASK_OPERATION.scope(operation, future)
+}
+
+/// Attributes requests made on the current thread to an operation until dropped
+///
+/// Entering a scope inside another leaves the outer operation in place, so requests are
+/// attributed to the operation that initiated them. Only hold it in synchronous code; futures
+/// should use [`scoped`] instead.
+#[derive(Debug)]
+pub struct OperationScope {
+    /// Whether this scope set the operation, and so clears it when it ends
+    entered: bool,
+    /// Keeps the scope on the thread it was entered on
+    _thread: PhantomData<*const ()>,
+}
+
+impl OperationScope {
+    /// Enters a scope for an operation
+    ///
+    /// # Arguments
+    ///
+    /// * `operation` - Operation requests are attributed to, unless one is already in scope
+    pub fn enter(operation: Operation) -> Self {
+        // An operation in scope may come from the thread or from the future being polled
+        let entered = Operation::current() == Operation::Untagged;
+        if entered {
+            CURRENT_OPERATION.with(|current| current.set(operation));
+        }
+
+        Self {
+            entered,
+            _thread: PhantomData,
+        }
+    }
+}
+
+impl Drop for OperationScope {
+    fn drop(&mut self) {
+        // Only the outermost scope clears the operation, so scopes held by interleaved futures
+        // can end in any order without leaving an operation behind
+        if self.entered {
+            CURRENT_OPERATION.with(|current| current.set(Operation::Untagged));
+        }
+    }
+}
+
+/// RPC transport that records each request before passing it to another transport
+///
+/// The blocking RPC client polls its requests on the calling thread, so each request is
+/// attributed to the operation in scope where it was made.
+pub struct UsageSender<S> {
+    /// Transport the requests are sent with
+    inner: S,
+    /// Counters the requests are recorded in
+    usage: std::sync::Arc<RpcUsage>,
+}
+
+impl<S> UsageSender<S> {
+    /// Wraps a transport
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - Transport the requests are sent with
+    /// * `usage` - Counters the requests are recorded in
+    pub fn new(inner: S, usage: std::sync::Arc<RpcUsage>) -> Self {
+        Self { inner, usage }
+    }
+}
+
+#[async_trait::async_trait]
+impl<S: RpcSender + Send + Sync> RpcSender for UsageSender<S> {
+    async fn send(
+        &self,
+        request: RpcRequest,
+        params: serde_json::Value,
+    ) -> ClientResult<serde_json::Value> {
+        self.usage
+            .record(Operation::current(), RpcMethod::from_request(&request));
+        self.inner.send(request, params).await
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.inner.get_transport_stats()
+    }
+
+    fn url(&self) -> String {
+        self.inner.url()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes_attribute_to_outer_operation() {
+        assert_eq!(Operation::current(), Operation::Untagged);
+        {
+            let _portfolio = OperationScope::enter(Operation::Query);
+            {
+                let _buy = OperationScope::enter(Operation::Buy);
+                assert_eq!(Operation::current(), Operation::Query);
+            }
+            assert_eq!(Operation::current(), Operation::Query);
+        }
+        assert_eq!(Operation::current(), Operation::Untagged);
+
+        // Scopes of interleaved futures may end out of order
+        let first = OperationScope::enter(Operation::Buy);
+        let second = OperationScope::enter(Operation::Sell);
+        drop(first);
+        drop(second);
+        assert_eq!(Operation::current(), Operation::Untagged);
+
+        // Other threads keep their own scope
+        let _buy = OperationScope::enter(Operation::Buy);
+        std::thread::spawn(|| assert_eq!(Operation::current(), Operation::Untagged))
+            .join()
+            .unwrap();
+        assert_eq!(Operation::current(), Operation::Buy);
+    }
+
+    /// Future that is pending on its first poll, so joined futures interleave
+    struct YieldOnce(bool);
+
+    impl Future for YieldOnce {
+        type Output = ();
+
+        fn poll(
+            mut self: std::pin::Pin<&mut Self>,
+            cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<()> {
+            if self.0 {
+                return std::task::Poll::Ready(());
+            }
+            self.0 = true;
+            cx.waker().wake_by_ref();
+            std::task::Poll::Pending
+        }
+    }
+
+    #[test]
+    fn test_concurrent_futures_keep_their_operation() {
+        let usage = RpcUsage::new();
+        let request = |usage: &RpcUsage| usage.record(Operation::current(), RpcMethod::GetSlot);
+        let trade = |operation| {
+            let usage = &usage;
+            scoped(operation, async move {
+                request(usage);
+                YieldOnce(false).await;
+                request(usage);
+                YieldOnce(false).await;
+                request(usage);
+            })
+        };
+
+        // The futures interleave at every await, yet each request keeps its future's operation
+        futures::executor::block_on(async {
+            futures::join!(
+                trade(Operation::Buy),
+                trade(Operation::Sell),
+                trade(Operation::Quote)
+            )
+        });
+        for operation in [Operation::Buy, Operation::Sell, Operation::Quote] {
+            assert_eq!(usage.count(operation, RpcMethod::GetSlot), 3);
+        }
+        assert_eq!(usage.count(Operation::Untagged, RpcMethod::GetSlot), 0);
+        assert_eq!(Operation::current(), Operation::Untagged);
+
+        // An outer operation wins over the scopes of the futures and threads inside it
+        futures::executor::block_on(scoped(Operation::Query, async {
+            trade(Operation::Buy).await;
+            let _sell = OperationScope::enter(Operation::Sell);
+            request(&usage);
+        }));
+        assert_eq!(usage.count(Operation::Query, RpcMethod::GetSlot), 4);
+        assert_eq!(usage.count(Operation::Buy, RpcMethod::GetSlot), 3);
+        assert_eq!(Operation::current(), Operation::Untagged);
+
+        let _backfill = OperationScope::enter(Operation::Backfill);
+        futures::executor::block_on(trade(Operation::Buy));
+        assert_eq!(usage.count(Operation::Backfill, RpcMethod::GetSlot), 3);
+    }
+
+    #[test]
+    fn test_report_and_reset() {
+        let usage = RpcUsage::new();
+        for _ in 0..3 {
+            usage.record(Operation::Buy, RpcMethod::GetMultipleAccounts);
+        }
+        usage.record(Operation::Buy, RpcMethod::SendTransaction);
+        usage.record(Operation::Backfill, RpcMethod::GetTransaction);
+
+        let costs = CostTable::default().with_cost(RpcMethod::GetTransaction, 10);
+        let report = usage.report(&costs);
+        assert_eq!(report.total_requests, 5);
+        assert_eq!(report.total_credits, 14);
+        assert_eq!(report.operations.len(), 2);
+
+        let buy = report.operation(Operation::Buy).unwrap();
+        assert_eq!(
+            buy.requests,
+            vec![
+                (RpcMethod::GetMultipleAccounts, 3),
+                (RpcMethod::SendTransaction, 1)
+            ]
+        );
+        assert_eq!(buy.credits, 4);
+        assert_eq!(report.operation(Operation::Backfill).unwrap().credits, 10);
+        assert_eq!(report.operation(Operation::Sell), None);
+
+        usage.reset();
+        assert_eq!(usage.report(&costs).total_requests, 0);
+    }
+}
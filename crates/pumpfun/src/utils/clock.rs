@@ -0,0 +1,292 @@
+//! Cluster time and slot utilities for strategy timing
+//!
+//! Strategies are usually written in wall-clock terms, but the chain advances in slots and a
+//! local clock can drift from the cluster's `block_time`. The helpers here convert between slots
+//! and durations using the slot time measured from recent performance samples rather than the
+//! nominal 400ms, and cache cluster readings so they can be polled cheaply.
+//!
+//! Time-based features read the local time through a [`Clock`], so tests can swap the
+//! [`SystemClock`] for a `MockClock` (with the `test-utils` feature) and advance time
+//! deterministically instead of sleeping:
+//!
+//! ```rust
+//! # #[cfg(feature = "test-utils")]
+//! # {
+//! use pumpfun::utils::clock::{Clock, MockClock};
+//! use std::time::Duration;
+//!
+//! let clock = MockClock::at_unix_timestamp(1_700_000_000);
+//! let start = clock.now();
+//! clock.advance(Duration::from_secs(90));
+//! assert_eq!(clock.now() - start, Duration::from_secs(90));
+//! assert_eq!(clock.unix_timestamp(), 1_700_000_090);
+//! # }
+//! ```
+
+use anchor_client::solana_client::rpc_response::RpcPerfSample;
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Nominal duration of a slot, used when no measurement is available
+pub const NOMINAL_SLOT_DURATION: Duration = Duration::from_millis(400);
+
+/// How long cached cluster readings are trusted before being refreshed
+pub const CLOCK_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Number of recent performance samples used to measure the slot duration
+pub const SLOT_DURATION_SAMPLES: usize = 5;
+
+/// Cluster time at a known slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClusterTime {
+    /// Slot the time was read at
+    pub slot: u64,
+    /// Unix timestamp of the slot, as reported by the cluster
+    pub unix_timestamp: i64,
+    /// When the reading was taken, on the local monotonic clock
+    pub fetched_at: Instant,
+}
+
+impl ClusterTime {
+    /// Estimates the current cluster time by advancing the reading by the local time elapsed
+    ///
+    /// # Returns
+    ///
+    /// The estimated current Unix timestamp on the cluster
+    pub fn now(&self) -> i64 {
+        self.estimate_at(Instant::now())
+    }
+
+    /// Estimates the cluster time at a local instant by advancing the reading to it
+    ///
+    /// # Arguments
+    /// * `now` - Local monotonic time to estimate the cluster time at
+    ///
+    /// # Returns
+    ///
+    /// The estimated Unix timestamp on the cluster, never earlier than the reading
+    pub fn estimate_at(&self, now: Instant) -> i64 {
+        self.unix_timestamp + now.saturating_duration_since(self.fetched_at).as_secs() as i64
+    }
+}
+
+/// Source of the local time
+///
+/// The client and time-based helpers take the time from a clock rather than reading it
+/// directly, so tests can control it. [`SystemClock`] is used unless another is provided.
+pub trait Clock: std::fmt::Debug + Send + Sync {
+    /// Gets the current time on the monotonic clock
+    fn now(&self) -> Instant;
+
+    /// Gets the current wall-clock time
+    fn system_time(&self) -> SystemTime;
+
+    /// Gets the current Unix timestamp in seconds
+    fn unix_timestamp(&self) -> i64 {
+        self.system_time()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default()
+    }
+}
+
+/// Clock reading the operating system's time
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// Clock that only moves when advanced, for deterministic tests
+///
+/// Both the monotonic and the wall-clock time start from when the clock was created, or
+/// from a chosen Unix timestamp, and move forward together.
+#[cfg(any(test, feature = "test-utils"))]
+#[derive(Debug)]
+pub struct MockClock {
+    /// Monotonic time the clock started at
+    start: Instant,
+    /// Wall-clock time the clock started at
+    start_system: SystemTime,
+    /// Time advanced since the start
+    elapsed: Mutex<Duration>,
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl MockClock {
+    /// Creates a clock stopped at the current time
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_system: SystemTime::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Creates a clock stopped at a Unix timestamp
+    ///
+    /// # Arguments
+    /// * `unix_timestamp` - Wall-clock time to start at, in seconds
+    pub fn at_unix_timestamp(unix_timestamp: u64) -> Self {
+        Self {
+            start_system: UNIX_EPOCH + Duration::from_secs(unix_timestamp),
+            ..Self::new()
+        }
+    }
+
+    /// Moves the clock forward
+    ///
+    /// # Arguments
+    /// * `duration` - Time to advance by
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock().unwrap() += duration;
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(any(test, feature = "test-utils"))]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.lock().unwrap()
+    }
+
+    fn system_time(&self) -> SystemTime {
+        self.start_system + *self.elapsed.lock().unwrap()
+    }
+}
+
+/// Cached cluster readings shared by a client
+#[derive(Debug, Default)]
+pub struct ClockCache {
+    /// Last cluster time reading
+    pub(crate) cluster_time: Mutex<Option<ClusterTime>>,
+    /// Last slot duration measurement and when it was taken
+    pub(crate) slot_duration: Mutex<Option<(Duration, Instant)>>,
+}
+
+/// Measures the average slot duration from recent performance samples
+///
+/// # Arguments
+/// * `samples` - Recent performance samples from `getRecentPerformanceSamples`
+///
+/// # Returns
+/// The average slot duration, or None if the samples cover no slots
+pub fn measure_slot_duration(samples: &[RpcPerfSample]) -> Option<Duration> {
+    let slots: u64 = samples.iter().map(|sample| sample.num_slots).sum();
+    let secs: u64 = samples
+        .iter()
+        .map(|sample| sample.sample_period_secs as u64)
+        .sum();
+    if slots == 0 {
+        return None;
+    }
+
+    Some(Duration::from_secs(secs) / slots as u32)
+}
+
+/// Converts a number of slots to a duration
+///
+/// # Arguments
+/// * `slots` - Number of slots
+/// * `slot_duration` - Duration of a single slot
+///
+/// # Returns
+/// The time it takes for that many slots to pass
+pub fn slots_to_duration(slots: u64, slot_duration: Duration) -> Duration {
+    slot_duration.saturating_mul(slots.min(u32::MAX as u64) as u32)
+}
+
+/// Converts a duration to a number of slots, rounding up
+///
+/// # Arguments
+/// * `duration` - Duration to convert
+/// * `slot_duration` - Duration of a single slot
+///
+/// # Returns
+/// The number of slots that pass in that duration, or 0 if the slot duration is zero
+pub fn duration_to_slots(duration: Duration, slot_duration: Duration) -> u64 {
+    let slot_nanos = slot_duration.as_nanos();
+    if slot_nanos == 0 {
+        return 0;
+    }
+
+    duration.as_nanos().div_ceil(slot_nanos) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_sample(num_slots: u64, sample_period_secs: u16) -> RpcPerfSample {
+        RpcPerfSample {
+            slot: 0,
+            num_transactions: 0,
+            num_non_vote_transactions: None,
+            num_slots,
+            sample_period_secs,
+        }
+    }
+
+    #[test]
+    fn test_measure_slot_duration() {
+        // A slow cluster producing 120 slots per minute instead of 150
+        let samples = vec![get_sample(120, 60), get_sample(120, 60)];
+        assert_eq!(
+            measure_slot_duration(&samples),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(measure_slot_duration(&[]), None);
+    }
+
+    #[test]
+    fn test_mock_clock() {
+        let clock = MockClock::at_unix_timestamp(1_700_000_000);
+        let start = clock.now();
+        assert_eq!(clock.now(), start);
+
+        clock.advance(Duration::from_secs(45));
+        assert_eq!(clock.now() - start, Duration::from_secs(45));
+        assert_eq!(clock.unix_timestamp(), 1_700_000_045);
+
+        // Cluster time readings advance with the clock, not the system time
+        let reading = ClusterTime {
+            slot: 1,
+            unix_timestamp: 1_700_000_000,
+            fetched_at: start,
+        };
+        assert_eq!(reading.estimate_at(clock.now()), 1_700_000_045);
+    }
+
+    #[test]
+    fn test_slot_conversions() {
+        let slot_duration = Duration::from_millis(500);
+        assert_eq!(
+            slots_to_duration(180, slot_duration),
+            Duration::from_secs(90)
+        );
+        assert_eq!(
+            duration_to_slots(Duration::from_secs(90), slot_duration),
+            180
+        );
+        assert_eq!(
+            duration_to_slots(Duration::from_millis(501), slot_duration),
+            2
+        );
+        assert_eq!(duration_to_slots(Duration::from_secs(1), Duration::ZERO), 0);
+    }
+}
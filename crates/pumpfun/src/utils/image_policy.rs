@@ -0,0 +1,255 @@
+//! Limits on token images, checked and applied before upload
+//!
+//! Uploads of images over the size limit fail only after the whole image has been sent. A
+//! policy set with [`crate::PumpFun::with_image_policy`] catches them before any request:
+//!
+//! - `ImagePolicy`: Size cap and maximum dimension of an image, and whether it may be resized.
+//! - `prepare_image`: Checks an image against a policy, resizing it if allowed.
+//!
+//! Resizing needs the `image` feature. It downscales the image to the maximum dimension and
+//! re-encodes it as PNG, or as JPEG at falling quality when the PNG is over the cap, which
+//! also strips EXIF and other metadata. Without the feature, images over the cap are rejected
+//! whatever the policy.
+
+use crate::error::ClientError;
+
+/// Default size cap of an image, in bytes
+pub const DEFAULT_MAX_IMAGE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Default maximum width and height of a resized image, in pixels
+pub const DEFAULT_MAX_IMAGE_DIMENSION: u32 = 1000;
+
+/// JPEG qualities tried in turn when a PNG does not fit under the size cap
+#[cfg(feature = "image")]
+const JPEG_QUALITIES: [u8; 5] = [90, 80, 70, 55, 40];
+
+/// Dimension below which an image is not halved further to fit under the size cap
+#[cfg(feature = "image")]
+const MIN_IMAGE_DIMENSION: u32 = 64;
+
+/// Limits an image must meet before upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImagePolicy {
+    /// Size cap of the uploaded image, in bytes
+    pub max_bytes: usize,
+    /// Maximum width and height a resized image is scaled down to, in pixels
+    pub max_dimension: u32,
+    /// Whether images may be resized and re-encoded, which needs the `image` feature
+    pub allow_resize: bool,
+}
+
+impl Default for ImagePolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_IMAGE_BYTES, DEFAULT_MAX_IMAGE_DIMENSION)
+    }
+}
+
+impl ImagePolicy {
+    /// Creates a policy that rejects images over the size cap without resizing them
+    ///
+    /// # Arguments
+    ///
+    /// * `max_bytes` - Size cap of the uploaded image, in bytes
+    /// * `max_dimension` - Maximum width and height a resized image is scaled down to
+    pub fn new(max_bytes: usize, max_dimension: u32) -> Self {
+        Self {
+            max_bytes,
+            max_dimension,
+            allow_resize: false,
+        }
+    }
+
+    /// Sets whether images may be resized and re-encoded
+    ///
+    /// # Arguments
+    ///
+    /// * `allow_resize` - Whether to resize images, which needs the `image` feature
+    ///
+    /// # Returns
+    ///
+    /// Returns the policy with the setting applied
+    pub fn with_resize(mut self, allow_resize: bool) -> Self {
+        self.allow_resize = allow_resize;
+        self
+    }
+}
+
+/// Checks an image against a policy, resizing it if the policy allows
+///
+/// Without resizing, the image is returned untouched if it fits under the size cap. With
+/// resizing and the `image` feature, it is always decoded and re-encoded, scaled down to the
+/// maximum dimension and then halved until it fits, so the result carries no EXIF data.
+///
+/// # Arguments
+///
+/// * `image` - Bytes of the image file
+/// * `policy` - Limits the image must meet
+///
+/// # Returns
+///
+/// Returns the bytes to upload, a ClientError::ImageTooLarge with the size of the image, or of
+/// the smallest encoding tried, if it does not fit under the cap, or a
+/// ClientError::UploadMetadataError if the image cannot be decoded or encoded
+pub fn prepare_image(image: Vec<u8>, policy: &ImagePolicy) -> Result<Vec<u8>, ClientError> {
+    #[cfg(feature = "image")]
+    if policy.allow_resize {
+        return resize_to_fit(&image, policy);
+    }
+
+    if image.len() > policy.max_bytes {
+        return Err(ClientError::ImageTooLarge {
+            size: image.len(),
+            max: policy.max_bytes,
+        });
+    }
+    Ok(image)
+}
+
+/// Scales an image down and re-encodes it until it fits under the size cap
+#[cfg(feature = "image")]
+fn resize_to_fit(image: &[u8], policy: &ImagePolicy) -> Result<Vec<u8>, ClientError> {
+    use ::image::{codecs::jpeg::JpegEncoder, imageops::FilterType, DynamicImage, ImageFormat};
+    use std::io::Cursor;
+
+    let image_error = |err: ::image::ImageError| ClientError::UploadMetadataError(err.into());
+    let decoded = ::image::load_from_memory(image).map_err(image_error)?;
+    let longest = decoded.width().max(decoded.height());
+    let mut dimension = longest.min(policy.max_dimension).max(1);
+    let mut smallest = usize::MAX;
+
+    loop {
+        let scaled = if longest > dimension {
+            decoded.resize(dimension, dimension, FilterType::Lanczos3)
+        } else {
+            decoded.clone()
+        };
+
+        // PNG is lossless and keeps transparency, so it is preferred when it fits
+        let mut png = Vec::new();
+        scaled
+            .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+            .map_err(image_error)?;
+        if png.len() <= policy.max_bytes {
+            return Ok(png);
+        }
+        smallest = smallest.min(png.len());
+
+        let rgb = DynamicImage::ImageRgb8(scaled.to_rgb8());
+        for quality in JPEG_QUALITIES {
+            let mut jpeg = Vec::new();
+            rgb.write_with_encoder(JpegEncoder::new_with_quality(&mut jpeg, quality))
+                .map_err(image_error)?;
+            if jpeg.len() <= policy.max_bytes {
+                return Ok(jpeg);
+            }
+            smallest = smallest.min(jpeg.len());
+        }
+
+        if dimension <= MIN_IMAGE_DIMENSION {
+            return Err(ClientError::ImageTooLarge {
+                size: smallest,
+                max: policy.max_bytes,
+            });
+        }
+        dimension = (dimension / 2).max(MIN_IMAGE_DIMENSION);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 1800x1200 RGBA gradient with an EXIF chunk, 12.5 KiB
+    const GRADIENT_EXIF_PNG: &[u8] = include_bytes!("fixtures/gradient_exif.png");
+    /// 160x160 RGB noise, 75 KiB
+    const NOISE_PNG: &[u8] = include_bytes!("fixtures/noise.png");
+
+    #[test]
+    fn test_prepare_image_without_resize() {
+        let policy = ImagePolicy::new(24_000, 512);
+        assert!(!policy.allow_resize);
+
+        // Rejected locally with the exact size
+        match prepare_image(NOISE_PNG.to_vec(), &policy) {
+            Err(ClientError::ImageTooLarge { size, max }) => {
+                assert_eq!(size, NOISE_PNG.len());
+                assert_eq!(max, 24_000);
+            }
+            other => panic!("unexpected result: {:?}", other.map(|image| image.len())),
+        }
+
+        // Images under the cap are uploaded as they are, whatever their dimensions
+        assert_eq!(
+            prepare_image(GRADIENT_EXIF_PNG.to_vec(), &policy).unwrap(),
+            GRADIENT_EXIF_PNG
+        );
+    }
+
+    #[cfg(not(feature = "image"))]
+    #[test]
+    fn test_prepare_image_cannot_resize_without_feature() {
+        let policy = ImagePolicy::new(24_000, 512).with_resize(true);
+        assert!(matches!(
+            prepare_image(NOISE_PNG.to_vec(), &policy),
+            Err(ClientError::ImageTooLarge { size, .. }) if size == NOISE_PNG.len()
+        ));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_prepare_image_resizes_oversized_dimensions() {
+        use ::image::{GenericImageView, ImageFormat};
+
+        let policy = ImagePolicy::new(1_000_000, 512).with_resize(true);
+        let prepared = prepare_image(GRADIENT_EXIF_PNG.to_vec(), &policy).unwrap();
+        assert!(prepared.len() <= policy.max_bytes);
+
+        // Scaled to fit, keeping the aspect ratio and the transparency
+        assert_eq!(::image::guess_format(&prepared).unwrap(), ImageFormat::Png);
+        let decoded = ::image::load_from_memory(&prepared).unwrap();
+        assert_eq!(decoded.dimensions(), (512, 341));
+        assert!(decoded.color().has_alpha());
+
+        // Re-encoding drops the EXIF data
+        let contains = |needle: &[u8]| prepared.windows(needle.len()).any(|w| w == needle);
+        assert!(GRADIENT_EXIF_PNG.windows(4).any(|w| w == b"eXIf"));
+        assert!(!contains(b"eXIf"));
+        assert!(!contains(b"pumpfun-test-exif"));
+
+        // Images within the limits are re-encoded too
+        let policy = ImagePolicy::new(1_000_000, 4_000).with_resize(true);
+        let prepared = prepare_image(GRADIENT_EXIF_PNG.to_vec(), &policy).unwrap();
+        let decoded = ::image::load_from_memory(&prepared).unwrap();
+        assert_eq!(decoded.dimensions(), (1800, 1200));
+        assert!(!prepared.windows(4).any(|w| w == b"eXIf"));
+    }
+
+    #[cfg(feature = "image")]
+    #[test]
+    fn test_prepare_image_shrinks_oversized_bytes() {
+        use ::image::GenericImageView;
+
+        let policy = ImagePolicy::new(24_000, 512).with_resize(true);
+        let prepared = prepare_image(NOISE_PNG.to_vec(), &policy).unwrap();
+        assert!(prepared.len() <= policy.max_bytes, "{}", prepared.len());
+        let (width, height) = ::image::load_from_memory(&prepared).unwrap().dimensions();
+        assert!(width <= 160 && height <= 160);
+        assert_eq!(width, height);
+
+        // A cap no encoding fits under reports the smallest size reached
+        let policy = ImagePolicy::new(100, 512).with_resize(true);
+        match prepare_image(NOISE_PNG.to_vec(), &policy) {
+            Err(ClientError::ImageTooLarge { size, max }) => {
+                assert!(size > 100 && size < NOISE_PNG.len());
+                assert_eq!(max, 100);
+            }
+            other => panic!("unexpected result: {:?}", other.map(|image| image.len())),
+        }
+
+        // Files that are not images cannot be resized
+        assert!(matches!(
+            prepare_image(b"not an image".to_vec(), &policy),
+            Err(ClientError::UploadMetadataError(_))
+        ));
+    }
+}
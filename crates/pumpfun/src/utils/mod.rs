@@ -1,14 +1,35 @@
 //! Utilities for working with token metadata and IPFS uploads.
 //!
 //! This module provides functionality for creating and managing token metadata,
-//! including uploading image and metadata to IPFS via the Pump.fun API.
+//! including uploading image and metadata to IPFS via the Pump.fun API. Images can be
+//! checked against an `ImagePolicy`, and resized to meet it, before they are uploaded.
+//! Names and symbols read back are cleaned up for display with a `SanitizePolicy`.
 
+pub mod clock;
+mod image_policy;
+mod sanitize;
+
+pub use image_policy::*;
+pub use sanitize::*;
+
+use crate::{constants, error, instruction};
+use anchor_client::solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
 use isahc::AsyncReadResponseExt;
 use serde::{Deserialize, Serialize};
-use std::{fs::File, io::Read};
+use solana_sdk::{
+    hash::{Hash, Hasher},
+    instruction::Instruction,
+    message::{Message, VersionedMessage},
+    packet::PACKET_DATA_SIZE,
+    pubkey::Pubkey,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedConfirmedTransactionWithStatusMeta,
+};
+use std::{collections::HashSet, fs::File, io::Read, str::FromStr, time::Duration};
 
 /// Metadata structure for a token, matching the format expected by Pump.fun.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenMetadata {
     /// Name of the token
@@ -31,8 +52,20 @@ pub struct TokenMetadata {
     pub website: Option<String>,
 }
 
+impl TokenMetadata {
+    /// Gets the name cleaned up for display with the default policy, see [`SanitizePolicy`]
+    pub fn name_sanitized(&self) -> String {
+        sanitize(&self.name)
+    }
+
+    /// Gets the symbol cleaned up for display with the default policy, see [`SanitizePolicy`]
+    pub fn symbol_sanitized(&self) -> String {
+        sanitize(&self.symbol)
+    }
+}
+
 /// Response received after successfully uploading token metadata.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TokenMetadataResponse {
     /// The uploaded token metadata
@@ -98,6 +131,32 @@ pub struct CreateTokenMetadata {
 /// ```
 pub async fn create_token_metadata(
     metadata: CreateTokenMetadata,
+) -> Result<TokenMetadataResponse, Box<dyn std::error::Error>> {
+    // Read the file contents
+    let mut file = File::open(&metadata.file)?;
+    let mut file_contents = Vec::new();
+    file.read_to_end(&mut file_contents)?;
+
+    upload_token_metadata(metadata, file_contents).await
+}
+
+/// Uploads token metadata with image bytes already in memory to IPFS
+///
+/// Use this to upload an image prepared with [`prepare_image`]; the `file` field of the
+/// metadata is ignored.
+///
+/// # Arguments
+///
+/// * `metadata` - Token metadata
+/// * `image` - Bytes of the image file
+///
+/// # Returns
+///
+/// Returns a `Result` containing the `TokenMetadataResponse` with IPFS locations on success,
+/// or an error if the upload fails.
+pub async fn upload_token_metadata(
+    metadata: CreateTokenMetadata,
+    image: Vec<u8>,
 ) -> Result<TokenMetadataResponse, Box<dyn std::error::Error>> {
     let boundary = "------------------------f4d9c2e8b7a5310f";
     let mut body = Vec::new();
@@ -136,11 +195,7 @@ pub async fn create_token_metadata(
     body.extend_from_slice(b"Content-Disposition: form-data; name=\"file\"; filename=\"file\"\r\n");
     body.extend_from_slice(b"Content-Type: application/octet-stream\r\n\r\n");
 
-    // Read the file contents
-    let mut file = File::open(&metadata.file)?;
-    let mut file_contents = Vec::new();
-    file.read_to_end(&mut file_contents)?;
-    body.extend_from_slice(&file_contents);
+    body.extend_from_slice(&image);
 
     // Close the boundary
     body.extend_from_slice(b"\r\n--");
@@ -166,6 +221,55 @@ pub async fn create_token_metadata(
     Ok(json)
 }
 
+/// Metadata uploaded to IPFS, or found among the uploads already made
+#[derive(Debug, Clone, PartialEq)]
+pub struct UploadedMetadata {
+    /// Uploaded metadata and the IPFS URI it is pinned at
+    pub response: TokenMetadataResponse,
+    /// Hash of the metadata fields and image bytes, see [`metadata_content_hash`]
+    pub content_hash: Hash,
+    /// Whether the metadata had already been uploaded and no request was made
+    pub from_cache: bool,
+}
+
+/// Hashes the content of token metadata, image included
+///
+/// Identical metadata hashes the same whatever the image file is called, so an upload can be
+/// recognised as a repeat before it is made. Each field is length-prefixed, so content cannot
+/// move between fields without changing the hash.
+///
+/// # Arguments
+///
+/// * `metadata` - Token metadata and the path of its image file
+///
+/// # Returns
+///
+/// Returns the SHA-256 hash of the metadata fields and image bytes, or an I/O error if the
+/// image cannot be read
+pub fn metadata_content_hash(metadata: &CreateTokenMetadata) -> std::io::Result<Hash> {
+    fn append_field(hasher: &mut Hasher, value: Option<&[u8]>) {
+        match value {
+            Some(value) => {
+                hasher.hash(&[1]);
+                hasher.hash(&(value.len() as u64).to_le_bytes());
+                hasher.hash(value);
+            }
+            None => hasher.hash(&[0]),
+        }
+    }
+
+    let image = std::fs::read(&metadata.file)?;
+    let mut hasher = Hasher::default();
+    append_field(&mut hasher, Some(metadata.name.as_bytes()));
+    append_field(&mut hasher, Some(metadata.symbol.as_bytes()));
+    append_field(&mut hasher, Some(metadata.description.as_bytes()));
+    append_field(&mut hasher, metadata.twitter.as_deref().map(str::as_bytes));
+    append_field(&mut hasher, metadata.telegram.as_deref().map(str::as_bytes));
+    append_field(&mut hasher, metadata.website.as_deref().map(str::as_bytes));
+    append_field(&mut hasher, Some(&image));
+    Ok(hasher.result())
+}
+
 /// Calculates the maximum amount to pay when buying tokens, accounting for slippage tolerance
 ///
 /// # Arguments
@@ -212,6 +316,320 @@ pub fn calculate_with_slippage_sell(amount: u64, basis_points: u64) -> u64 {
     amount - (amount * basis_points) / 10000
 }
 
+/// How the maximum SOL cost of a buy is derived from its quote
+///
+/// `None` where a mode is accepted means the default of 500 basis points, and a bare `u64`
+/// converts into [`SlippageMode::BasisPoints`].
+///
+/// # Example
+/// ```rust
+/// use pumpfun::utils::SlippageMode;
+///
+/// let amount = 1_000_000_000; // 1 SOL in lamports
+///
+/// // Allow 1% over the quote
+/// assert_eq!(SlippageMode::BasisPoints(100).max_sol_cost(amount), 1_010_000_000);
+///
+/// // Never pay more than 110% of the quote
+/// assert_eq!(SlippageMode::MaxMultiplier(1.1).max_sol_cost(amount), 1_100_000_000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SlippageMode {
+    /// Slippage tolerance in basis points over the quote (1% = 100 basis points)
+    BasisPoints(u64),
+    /// Largest multiple of the quote to pay, e.g. `1.1` for 110%
+    ///
+    /// Multipliers below 1.0 are treated as 1.0, so the cost never drops under the quote.
+    MaxMultiplier(f64),
+}
+
+impl SlippageMode {
+    /// Calculates the maximum amount to pay for a buy quoted at `amount` lamports
+    pub fn max_sol_cost(&self, amount: u64) -> u64 {
+        match *self {
+            Self::BasisPoints(basis_points) => calculate_with_slippage_buy(amount, basis_points),
+            Self::MaxMultiplier(multiplier) => {
+                // The float cast saturates, and NaN falls back to the quote itself
+                let max = (amount as f64 * multiplier.max(1.0)).floor() as u64;
+                max.max(amount)
+            }
+        }
+    }
+}
+
+impl Default for SlippageMode {
+    fn default() -> Self {
+        Self::BasisPoints(crate::quote::DEFAULT_SLIPPAGE_BASIS_POINTS)
+    }
+}
+
+impl From<u64> for SlippageMode {
+    fn from(basis_points: u64) -> Self {
+        Self::BasisPoints(basis_points)
+    }
+}
+
+/// Calculates the slippage actually incurred by a fill, relative to its quote
+///
+/// Both amounts are what the trader receives: tokens for a buy, SOL for a sell. Receiving less
+/// than quoted is positive slippage; receiving more is negative slippage (a better fill).
+///
+/// # Arguments
+/// * `quoted` - The amount quoted before the trade, in base units
+/// * `filled` - The amount actually received, in base units
+///
+/// # Returns
+/// The signed slippage as a percentage of the quote, or 0.0 if the quote was zero
+///
+/// # Example
+/// ```rust
+/// use pumpfun::utils;
+///
+/// let quoted = 1_000_000_000; // Quoted 1 SOL for a sell
+/// let filled = 990_000_000; // Received 0.99 SOL
+///
+/// assert_eq!(utils::realized_slippage(quoted, filled), 1.0); // 1% worse than quoted
+/// assert_eq!(utils::realized_slippage(quoted, 1_010_000_000), -1.0); // 1% better
+/// ```
+pub fn realized_slippage(quoted: u64, filled: u64) -> f64 {
+    if quoted == 0 {
+        return 0.0;
+    }
+
+    (quoted as f64 - filled as f64) / quoted as f64 * 100.0
+}
+
+/// Calculates the serialized size of a legacy transaction containing the given instructions
+///
+/// # Arguments
+/// * `instructions` - Instructions in transaction order
+/// * `payer` - Fee payer of the transaction
+///
+/// # Returns
+/// The size in bytes of the signed transaction on the wire
+pub fn get_transaction_size(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let message = Message::new(instructions, Some(payer));
+    let signatures = message.header.num_required_signatures as usize;
+
+    // Compact-u16 signature count, the signatures and the serialized message
+    let signature_count_len = if signatures < 0x80 { 1 } else { 2 };
+    signature_count_len + signatures * 64 + message.serialize().len()
+}
+
+/// Checks that the given instructions fit in a single transaction
+///
+/// # Arguments
+/// * `instructions` - Instructions in transaction order
+/// * `payer` - Fee payer of the transaction
+///
+/// # Returns
+/// Ok if the transaction fits within the packet size limit, or a ClientError::TransactionTooLarge otherwise
+pub fn validate_transaction_size(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+) -> Result<(), error::ClientError> {
+    let size = get_transaction_size(instructions, payer);
+    if size > PACKET_DATA_SIZE {
+        return Err(error::ClientError::TransactionTooLarge {
+            size,
+            max: PACKET_DATA_SIZE,
+        });
+    }
+
+    Ok(())
+}
+
+/// Calculates the longest memo that fits in a transaction alongside the given instructions
+///
+/// # Arguments
+/// * `instructions` - Instructions in transaction order, without the memo
+/// * `payer` - Fee payer of the transaction
+///
+/// # Returns
+/// The number of memo bytes left before the transaction exceeds the packet size limit
+pub fn memo_capacity(instructions: &[Instruction], payer: &Pubkey) -> usize {
+    let mut with_memo = instructions.to_vec();
+    with_memo.push(instruction::memo(""));
+    let remaining = PACKET_DATA_SIZE.saturating_sub(get_transaction_size(&with_memo, payer));
+
+    // Data of 128 bytes or more takes a second byte for its compact-u16 length
+    if remaining > 0x7f {
+        remaining - 1
+    } else {
+        remaining
+    }
+}
+
+/// Checks that a memo fits in a transaction alongside the given instructions
+///
+/// # Arguments
+/// * `instructions` - Instructions in transaction order, without the memo
+/// * `memo` - Memo to append to the transaction
+/// * `payer` - Fee payer of the transaction
+///
+/// # Returns
+/// Ok if the memo fits, or a ClientError::MemoTooLong with the space left otherwise
+pub fn validate_memo(
+    instructions: &[Instruction],
+    memo: &str,
+    payer: &Pubkey,
+) -> Result<(), error::ClientError> {
+    let max = memo_capacity(instructions, payer);
+    if memo.len() > max {
+        return Err(error::ClientError::MemoTooLong {
+            len: memo.len(),
+            max,
+        });
+    }
+
+    Ok(())
+}
+
+/// Extracts the memos of the SPL Memo instructions in a transaction message
+///
+/// # Arguments
+/// * `message` - Decoded transaction message
+///
+/// # Returns
+/// The memos in instruction order, skipping any that are not valid UTF-8
+pub fn extract_memos(message: &VersionedMessage) -> Vec<String> {
+    // Program IDs are always static keys, never loaded from lookup tables
+    let keys = message.static_account_keys();
+    message
+        .instructions()
+        .iter()
+        .filter(|instruction| {
+            keys.get(instruction.program_id_index as usize)
+                == Some(&constants::accounts::MEMO_PROGRAM)
+        })
+        .filter_map(|instruction| String::from_utf8(instruction.data.clone()).ok())
+        .collect()
+}
+
+/// Splits the memo field of a signature listing into its memos
+///
+/// The RPC formats each memo as `[len] text`, with the length in bytes, and joins them with
+/// `; `. The length is used to find the end of each memo, so memos containing `; ` survive.
+///
+/// # Arguments
+/// * `field` - Memo field of an RPC signature status
+///
+/// # Returns
+/// The memos in instruction order
+pub fn parse_memo_field(field: &str) -> Vec<String> {
+    let mut memos = Vec::new();
+    let mut rest = field;
+    while let Some((len, text)) = rest
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once("] "))
+    {
+        let end = len
+            .parse::<usize>()
+            .ok()
+            .filter(|&end| text.is_char_boundary(end))
+            .unwrap_or_else(|| text.find("; ").unwrap_or(text.len()));
+        memos.push(text[..end].to_string());
+        rest = text[end..].strip_prefix("; ").unwrap_or_default();
+    }
+    memos
+}
+
+/// Gets the lamport balance change of an account within a confirmed transaction
+///
+/// # Arguments
+/// * `transaction` - Confirmed transaction fetched with a binary encoding
+/// * `account` - Account whose balance change to read
+///
+/// # Returns
+/// The post balance minus the pre balance, or None if the account is not part of the transaction
+pub fn get_lamport_change(
+    transaction: &EncodedConfirmedTransactionWithStatusMeta,
+    account: &Pubkey,
+) -> Option<i64> {
+    let meta = transaction.transaction.meta.as_ref()?;
+    let decoded = transaction.transaction.transaction.decode()?;
+
+    // Static keys come first, followed by writable then readonly lookup table addresses
+    let mut keys: Vec<Pubkey> = decoded.message.static_account_keys().to_vec();
+    if let OptionSerializer::Some(loaded) = &meta.loaded_addresses {
+        for address in loaded.writable.iter().chain(loaded.readonly.iter()) {
+            keys.push(Pubkey::from_str(address).ok()?);
+        }
+    }
+
+    let index = keys.iter().position(|key| key == account)?;
+    let pre = *meta.pre_balances.get(index)? as i64;
+    let post = *meta.post_balances.get(index)? as i64;
+    Some(post - pre)
+}
+
+/// Extrapolates the time until a bonding curve completes from its recent SOL inflow
+///
+/// # Arguments
+/// * `sol_remaining` - SOL in lamports still required for the curve to complete
+/// * `net_inflow` - Net SOL in lamports that flowed into the curve over `window`
+/// * `window` - Period over which `net_inflow` was measured
+///
+/// # Returns
+/// The estimated time to completion, or None if the inflow is zero or negative, or the
+/// estimate is too far out to be represented
+///
+/// # Example
+/// ```rust
+/// use pumpfun::utils;
+/// use std::time::Duration;
+///
+/// // 10 SOL flowed in over 5 minutes, 20 SOL remain
+/// let eta = utils::extrapolate_time_to_graduate(20_000_000_000, 10_000_000_000, Duration::from_secs(300));
+/// assert_eq!(eta, Some(Duration::from_secs(600)));
+/// ```
+pub fn extrapolate_time_to_graduate(
+    sol_remaining: u64,
+    net_inflow: i64,
+    window: Duration,
+) -> Option<Duration> {
+    if net_inflow <= 0 || window.is_zero() {
+        return None;
+    }
+
+    // Net inflow per minute over the window
+    let per_minute = net_inflow as f64 / (window.as_secs_f64() / 60.0);
+
+    let seconds = sol_remaining as f64 / per_minute * 60.0;
+    if !seconds.is_finite() {
+        return None;
+    }
+    Duration::try_from_secs_f64(seconds).ok()
+}
+
+/// Reassembles signature history fetched in windows into a single newest-first list
+///
+/// # Arguments
+///
+/// * `windows` - Signatures of each window, newest window first
+/// * `boundaries` - The boundary signature after each window, if it belongs to the history
+///
+/// # Returns
+///
+/// Returns the signatures in order with duplicates at window boundaries removed
+pub fn merge_signature_windows(
+    windows: Vec<Vec<RpcConfirmedTransactionStatusWithSignature>>,
+    boundaries: Vec<Option<RpcConfirmedTransactionStatusWithSignature>>,
+) -> Vec<RpcConfirmedTransactionStatusWithSignature> {
+    let mut seen = HashSet::new();
+    let mut boundaries = boundaries.into_iter();
+    let mut merged = Vec::new();
+    for window in windows {
+        let boundary = boundaries.next().flatten();
+        for status in window.into_iter().chain(boundary) {
+            if seen.insert(status.signature.clone()) {
+                merged.push(status);
+            }
+        }
+    }
+    merged
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +670,73 @@ mod tests {
         assert!(response.metadata_uri.starts_with("https://ipfs.io/ipfs/"));
     }
 
+    #[test]
+    fn test_token_metadata_sanitized() {
+        let metadata: TokenMetadata = serde_json::from_value(serde_json::json!({
+            "name": "\u{1b}[1mPump\0 \u{202e}Token",
+            "symbol": "\u{2067}PUMP\u{2069}",
+            "description": "",
+            "image": "",
+            "showName": true,
+            "createdOn": "https://pump.fun",
+            "twitter": null,
+            "telegram": null,
+            "website": null,
+        }))
+        .unwrap();
+
+        assert_eq!(metadata.name_sanitized(), "Pump Token");
+        assert_eq!(metadata.symbol_sanitized(), "PUMP");
+        // The raw values are left untouched
+        assert_eq!(metadata.name, "\u{1b}[1mPump\0 \u{202e}Token");
+    }
+
+    #[test]
+    fn test_metadata_content_hash() {
+        let temp_dir = std::env::temp_dir();
+        let image = temp_dir.join(format!("pumpfun-image-{}.png", Pubkey::new_unique()));
+        let copy = temp_dir.join(format!("pumpfun-image-{}.png", Pubkey::new_unique()));
+        write(&image, b"fake image data").unwrap();
+        write(&copy, b"fake image data").unwrap();
+
+        let metadata = CreateTokenMetadata {
+            name: "Test Token".to_string(),
+            symbol: "TEST".to_string(),
+            description: "Test Description".to_string(),
+            file: image.to_str().unwrap().to_string(),
+            twitter: None,
+            telegram: None,
+            website: Some("https://example.com".to_string()),
+        };
+        let hash = metadata_content_hash(&metadata).unwrap();
+
+        // The same content under another file name hashes the same
+        let renamed = CreateTokenMetadata {
+            file: copy.to_str().unwrap().to_string(),
+            ..metadata.clone()
+        };
+        assert_eq!(metadata_content_hash(&renamed).unwrap(), hash);
+
+        // Any change to the fields or the image changes the hash
+        let shifted = CreateTokenMetadata {
+            name: "Test TokenTEST".to_string(),
+            symbol: String::new(),
+            ..metadata.clone()
+        };
+        assert_ne!(metadata_content_hash(&shifted).unwrap(), hash);
+        let without_website = CreateTokenMetadata {
+            website: None,
+            ..metadata.clone()
+        };
+        assert_ne!(metadata_content_hash(&without_website).unwrap(), hash);
+        write(&copy, b"other image data").unwrap();
+        assert_ne!(metadata_content_hash(&renamed).unwrap(), hash);
+
+        std::fs::remove_file(&image).unwrap();
+        std::fs::remove_file(&copy).unwrap();
+        assert!(metadata_content_hash(&metadata).is_err());
+    }
+
     #[test]
     fn test_calculate_with_slippage_buy() {
         let amount = 1_000_000_000; // 1 SOL in lamports
@@ -267,4 +752,178 @@ mod tests {
         let min_amount = calculate_with_slippage_sell(amount, slippage);
         assert_eq!(min_amount, 990_000_000); // 0.99 SOL
     }
+
+    #[test]
+    fn test_slippage_mode() {
+        let amount = 1_000_000_000; // 1 SOL in lamports
+        assert_eq!(SlippageMode::default().max_sol_cost(amount), 1_050_000_000);
+        assert_eq!(SlippageMode::from(100).max_sol_cost(amount), 1_010_000_000);
+        assert_eq!(
+            SlippageMode::MaxMultiplier(1.1).max_sol_cost(amount),
+            1_100_000_000
+        );
+        assert_eq!(
+            SlippageMode::MaxMultiplier(0.5).max_sol_cost(amount),
+            amount
+        );
+        assert_eq!(
+            SlippageMode::MaxMultiplier(f64::NAN).max_sol_cost(amount),
+            amount
+        );
+        assert_eq!(
+            SlippageMode::MaxMultiplier(1e30).max_sol_cost(amount),
+            u64::MAX
+        );
+    }
+
+    #[test]
+    fn test_realized_slippage() {
+        assert_eq!(realized_slippage(1_000_000, 1_000_000), 0.0);
+        assert_eq!(realized_slippage(1_000_000, 950_000), 5.0);
+        assert_eq!(realized_slippage(1_000_000, 1_050_000), -5.0);
+        assert_eq!(realized_slippage(0, 1_000), 0.0);
+    }
+
+    #[test]
+    fn test_transaction_size() {
+        let payer = Pubkey::new_unique();
+        let memo =
+            |len: usize| Instruction::new_with_bytes(Pubkey::new_unique(), &vec![0; len], vec![]);
+
+        // Matches the bincode encoding of the unsigned transaction
+        let instructions = vec![memo(100), memo(200)];
+        let transaction =
+            solana_sdk::transaction::Transaction::new_with_payer(&instructions, Some(&payer));
+        assert_eq!(
+            get_transaction_size(&instructions, &payer),
+            bincode::serialized_size(&transaction).unwrap() as usize
+        );
+        assert!(validate_transaction_size(&instructions, &payer).is_ok());
+
+        // Too much instruction data for one packet
+        let instructions = vec![memo(700), memo(700)];
+        assert!(matches!(
+            validate_transaction_size(&instructions, &payer),
+            Err(error::ClientError::TransactionTooLarge {
+                max: PACKET_DATA_SIZE,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_memo_capacity() {
+        let payer = Pubkey::new_unique();
+        let data =
+            |len: usize| Instruction::new_with_bytes(Pubkey::new_unique(), &vec![0; len], vec![]);
+
+        // The longest memo that fits fills the packet exactly or up to a length prefix byte
+        for used in [100, 800, 1000] {
+            let instructions = vec![data(used)];
+            let max = memo_capacity(&instructions, &payer);
+            let fits = "a".repeat(max);
+            assert!(validate_memo(&instructions, &fits, &payer).is_ok());
+            let mut with_memo = instructions.clone();
+            with_memo.push(instruction::memo(&fits));
+            assert!(get_transaction_size(&with_memo, &payer) <= PACKET_DATA_SIZE);
+
+            let too_long = "a".repeat(max + 1);
+            with_memo.pop();
+            with_memo.push(instruction::memo(&too_long));
+            assert!(get_transaction_size(&with_memo, &payer) > PACKET_DATA_SIZE);
+            assert!(matches!(
+                validate_memo(&instructions, &too_long, &payer),
+                Err(error::ClientError::MemoTooLong { len, max: m }) if len == max + 1 && m == max
+            ));
+        }
+
+        // No space left at all
+        let instructions = vec![data(1200)];
+        assert_eq!(memo_capacity(&instructions, &payer), 0);
+        assert!(validate_memo(&instructions, "", &payer).is_ok());
+    }
+
+    #[test]
+    fn test_extract_memos() {
+        let payer = Pubkey::new_unique();
+        let instructions = vec![
+            instruction::memo("strategy-v1"),
+            Instruction::new_with_bytes(Pubkey::new_unique(), b"not a memo", vec![]),
+            instruction::memo("run 42"),
+            Instruction::new_with_bytes(constants::accounts::MEMO_PROGRAM, &[0xff, 0xfe], vec![]),
+        ];
+        let message = VersionedMessage::Legacy(Message::new(&instructions, Some(&payer)));
+        assert_eq!(extract_memos(&message), vec!["strategy-v1", "run 42"]);
+    }
+
+    #[test]
+    fn test_parse_memo_field() {
+        assert_eq!(parse_memo_field("[11] strategy-v1"), vec!["strategy-v1"]);
+        assert_eq!(
+            parse_memo_field("[11] strategy-v1; [6] run 42"),
+            vec!["strategy-v1", "run 42"]
+        );
+        // Separators inside a memo are covered by its length
+        assert_eq!(parse_memo_field("[4] a; b; [1] c"), vec!["a; b", "c"]);
+        // Lengths count bytes, not characters
+        assert_eq!(parse_memo_field("[5] héllo"), vec!["héll"]);
+        assert_eq!(parse_memo_field("[6] héllo"), vec!["héllo"]);
+        assert!(parse_memo_field("").is_empty());
+    }
+
+    #[test]
+    fn test_extrapolate_time_to_graduate() {
+        let window = Duration::from_secs(300);
+
+        // Steady inflow extrapolates linearly
+        let eta = extrapolate_time_to_graduate(20_000_000_000, 10_000_000_000, window);
+        assert_eq!(eta, Some(Duration::from_secs(600)));
+
+        // Stalling or reversing flows have no estimate
+        assert_eq!(
+            extrapolate_time_to_graduate(20_000_000_000, 0, window),
+            None
+        );
+        assert_eq!(
+            extrapolate_time_to_graduate(20_000_000_000, -1, window),
+            None
+        );
+
+        // Nothing remaining graduates immediately
+        let eta = extrapolate_time_to_graduate(0, 10_000_000_000, window);
+        assert_eq!(eta, Some(Duration::ZERO));
+
+        // A trickle over a huge window is too slow to estimate, rather than a panic
+        assert_eq!(
+            extrapolate_time_to_graduate(u64::MAX, 1, Duration::MAX),
+            None
+        );
+    }
+
+    fn status(signature: &str, slot: u64) -> RpcConfirmedTransactionStatusWithSignature {
+        RpcConfirmedTransactionStatusWithSignature {
+            signature: signature.to_string(),
+            slot,
+            err: None,
+            memo: None,
+            block_time: None,
+            confirmation_status: None,
+        }
+    }
+
+    #[test]
+    fn test_merge_signature_windows() {
+        let windows = vec![
+            vec![status("e", 50), status("d", 40)],
+            // An empty window still keeps the boundaries around it in place
+            vec![],
+            // "b" overlaps the boundary before it and is only kept once
+            vec![status("b", 20), status("a", 10)],
+        ];
+        let boundaries = vec![Some(status("c", 30)), Some(status("b", 20))];
+
+        let merged = merge_signature_windows(windows, boundaries);
+        let signatures: Vec<&str> = merged.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(signatures, vec!["e", "d", "c", "b", "a"]);
+    }
 }
@@ -0,0 +1,302 @@
+//! Sanitization of token names and symbols for display
+//!
+//! Names and symbols are chosen by whoever creates a token, and nothing on chain stops them
+//! from holding null bytes, ANSI escape sequences or bidirectional overrides. Written as is to
+//! a terminal, log or CSV file, these reorder or recolor the output around them:
+//!
+//! - `SanitizePolicy`: How a value is cleaned up, with `sanitize` applying it.
+//! - `Normalization`: Unicode normalization form applied to the value.
+//!
+//! Sanitized values are for display and export. The raw values are kept next to them, since
+//! they are what the token's accounts actually hold.
+
+use unicode_normalization::UnicodeNormalization;
+
+/// Escape character starting ANSI escape sequences
+const ESC: char = '\u{1b}';
+
+/// Unicode normalization form applied by a [`SanitizePolicy`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Normalization {
+    /// Values are left as they are
+    None,
+    /// Canonical composition, which only merges equivalent encodings of the same text
+    Nfc,
+    /// Compatibility composition, which also folds look-alikes such as fullwidth letters
+    #[default]
+    Nfkc,
+}
+
+/// How token names and symbols are cleaned up for display
+///
+/// Control characters and ANSI escape sequences are always removed. Line breaks and tabs are
+/// replaced by spaces, runs of whitespace collapsed and the result trimmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanitizePolicy {
+    /// Unicode normalization form applied before filtering
+    pub normalization: Normalization,
+    /// Whether to remove bidirectional controls and invisible formatting characters
+    pub strip_format: bool,
+    /// Whether to only keep printable ASCII, dropping emoji and other scripts
+    pub ascii_only: bool,
+    /// Maximum number of characters kept, None for no limit
+    pub max_chars: Option<usize>,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SanitizePolicy {
+    /// Creates a policy applying NFKC and removing format characters, keeping any script
+    pub fn new() -> Self {
+        Self {
+            normalization: Normalization::Nfkc,
+            strip_format: true,
+            ascii_only: false,
+            max_chars: None,
+        }
+    }
+
+    /// Sets the Unicode normalization form
+    ///
+    /// # Arguments
+    ///
+    /// * `normalization` - Form applied before filtering
+    ///
+    /// # Returns
+    ///
+    /// Returns the policy with the setting applied
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Sets whether bidirectional controls and invisible formatting characters are removed
+    ///
+    /// Joiners inside emoji sequences are kept either way, unless only ASCII is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `strip_format` - Whether to remove them
+    ///
+    /// # Returns
+    ///
+    /// Returns the policy with the setting applied
+    pub fn with_strip_format(mut self, strip_format: bool) -> Self {
+        self.strip_format = strip_format;
+        self
+    }
+
+    /// Sets whether only printable ASCII is kept
+    ///
+    /// # Arguments
+    ///
+    /// * `ascii_only` - Whether to drop every other character
+    ///
+    /// # Returns
+    ///
+    /// Returns the policy with the setting applied
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Caps the number of characters kept
+    ///
+    /// # Arguments
+    ///
+    /// * `max_chars` - Maximum number of characters of the sanitized value
+    ///
+    /// # Returns
+    ///
+    /// Returns the policy with the setting applied
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = Some(max_chars);
+        self
+    }
+
+    /// Cleans up a value according to the policy
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - Raw name or symbol
+    ///
+    /// # Returns
+    ///
+    /// Returns the sanitized value, possibly empty
+    pub fn sanitize(&self, value: &str) -> String {
+        let unescaped = strip_ansi(value);
+        let normalized: String = match self.normalization {
+            Normalization::None => unescaped,
+            Normalization::Nfc => unescaped.nfc().collect(),
+            Normalization::Nfkc => unescaped.nfkc().collect(),
+        };
+
+        let mut sanitized = String::with_capacity(normalized.len());
+        let mut pending_space = false;
+        let mut kept = 0;
+        for c in normalized.chars() {
+            if c.is_whitespace() {
+                pending_space = !sanitized.is_empty();
+                continue;
+            }
+            if c.is_control()
+                || (self.strip_format && is_format(c))
+                || (self.ascii_only && !c.is_ascii_graphic())
+            {
+                continue;
+            }
+            if self
+                .max_chars
+                .is_some_and(|max| kept + 1 + usize::from(pending_space) > max)
+            {
+                break;
+            }
+            if pending_space {
+                sanitized.push(' ');
+                kept += 1;
+                pending_space = false;
+            }
+            sanitized.push(c);
+            kept += 1;
+        }
+        sanitized
+    }
+}
+
+/// Sanitizes a value with the default policy
+///
+/// # Arguments
+///
+/// * `value` - Raw name or symbol
+///
+/// # Returns
+///
+/// Returns the value cleaned up by [`SanitizePolicy::default`]
+pub fn sanitize(value: &str) -> String {
+    SanitizePolicy::default().sanitize(value)
+}
+
+/// Removes ANSI escape sequences, leaving other characters for the control filter
+///
+/// CSI sequences (`ESC [`) run to their final byte and OSC sequences (`ESC ]`) to their
+/// terminator, so their parameters don't leak into the output as plain text.
+fn strip_ansi(value: &str) -> String {
+    let mut stripped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != ESC {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\u{40}'..='\u{7e}').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' {
+                        break;
+                    }
+                    if c == ESC && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    stripped
+}
+
+/// Whether a character is a bidirectional control or an invisible formatting character
+///
+/// The zero-width joiner and non-joiner are not included, as emoji sequences and several
+/// scripts rely on them.
+fn is_format(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061c}'
+            | '\u{200b}'
+            | '\u{200e}'
+            | '\u{200f}'
+            | '\u{202a}'..='\u{202e}'
+            | '\u{2060}'..='\u{2064}'
+            | '\u{2066}'..='\u{2069}'
+            | '\u{feff}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_control_characters_removed() {
+        assert_eq!(sanitize("PUMP\0\0\0"), "PUMP");
+        assert_eq!(sanitize("Pump\u{7}\u{7f}\u{9b}Token"), "PumpToken");
+        assert_eq!(sanitize("  Pump\n\tToken\r\n "), "Pump Token");
+        assert_eq!(sanitize("\0\0"), "");
+    }
+
+    #[test]
+    fn test_ansi_sequences_removed() {
+        assert_eq!(sanitize("\u{1b}[31;1mRED\u{1b}[0m"), "RED");
+        assert_eq!(sanitize("\u{1b}[2J\u{1b}[HClear"), "Clear");
+        assert_eq!(
+            sanitize("\u{1b}]8;;https://evil.example\u{7}Link\u{1b}]8;;\u{1b}\\"),
+            "Link"
+        );
+        // A lone escape only loses the character after it
+        assert_eq!(sanitize("A\u{1b}BC"), "AC");
+    }
+
+    #[test]
+    fn test_format_characters() {
+        // Right-to-left override reversing what follows it on screen
+        let spoofed = "Token\u{202e}gnp.exe";
+        assert_eq!(sanitize(spoofed), "Tokengnp.exe");
+        assert_eq!(
+            SanitizePolicy::new()
+                .with_strip_format(false)
+                .sanitize(spoofed),
+            spoofed
+        );
+        assert_eq!(sanitize("Pu\u{200b}mp\u{feff}"), "Pump");
+
+        // Emoji sequences keep their joiners
+        let family = "\u{1f468}\u{200d}\u{1f469}\u{200d}\u{1f467}";
+        assert_eq!(sanitize(family), family);
+    }
+
+    #[test]
+    fn test_normalization() {
+        // Fullwidth letters fold into ASCII under NFKC only
+        assert_eq!(sanitize("\u{ff30}\u{ff35}\u{ff2d}\u{ff30}"), "PUMP");
+        let nfc = SanitizePolicy::new().with_normalization(Normalization::Nfc);
+        assert_eq!(nfc.sanitize("\u{ff30}UMP"), "\u{ff30}UMP");
+        assert_eq!(nfc.sanitize("Cafe\u{301}"), "Caf\u{e9}");
+        let none = SanitizePolicy::new().with_normalization(Normalization::None);
+        assert_eq!(none.sanitize("Cafe\u{301}"), "Cafe\u{301}");
+    }
+
+    #[test]
+    fn test_ascii_only_and_length() {
+        let ascii = SanitizePolicy::new().with_ascii_only(true);
+        assert_eq!(ascii.sanitize("\u{1f680} Moon \u{05e9}\u{05dc}"), "Moon");
+
+        let short = SanitizePolicy::new().with_max_chars(6);
+        assert_eq!(short.sanitize("Pump Token"), "Pump T");
+        // A space is never left dangling at the cut
+        assert_eq!(short.sanitize("Pumps Token"), "Pumps");
+        assert_eq!(short.sanitize("\u{1f680}\u{1f680}"), "\u{1f680}\u{1f680}");
+    }
+}
@@ -0,0 +1,582 @@
+//! Deterministic test vectors shared with SDKs in other languages
+//!
+//! SDKs for other languages reimplement the curve math, PDA derivations and event decoding of
+//! this crate. The vectors here pin down what this crate computes for fixed inputs, so those
+//! SDKs can check they agree with it. The corpus lives in `testdata/` at the root of the
+//! repository, one JSON file per kind of vector:
+//!
+//! - `curve.json`: `CurveVector`, a curve state with the expected buy and sell outputs.
+//! - `slippage.json`: `SlippageVector`, an amount with its slippage bounds.
+//! - `pdas.json`: `PdaVector`, the inputs of a derived address and the expected address.
+//! - `events.json`: `EventVector`, a raw log line and the event expected to decode from it.
+//!
+//! The corpus is the source of truth: this crate's tests load it and check every vector
+//! against the current code. A change to the math must regenerate it on purpose, with
+//! `cargo run --bin gen-vectors`, so the change shows up in review.
+//!
+//! Public keys are written in base58. Every integer is kept below 2^53, so JavaScript can read
+//! the files without losing precision.
+
+use crate::{
+    accounts::BondingCurveAccount,
+    constants,
+    events::{self, CompleteEvent, CreateEvent, SetParamsEvent, TradeEvent},
+    instruction, utils, PumpFun,
+};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use borsh::BorshSerialize;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{fs, io, path::Path};
+
+/// Largest integer every JSON reader represents exactly
+pub const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+/// Name of the file holding each kind of vector
+const CURVE_FILE: &str = "curve.json";
+const SLIPPAGE_FILE: &str = "slippage.json";
+const PDAS_FILE: &str = "pdas.json";
+const EVENTS_FILE: &str = "events.json";
+
+/// State of a bonding curve used as the input of a vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveState {
+    /// Virtual token reserves in base units
+    pub virtual_token_reserves: u64,
+    /// Virtual SOL reserves in lamports
+    pub virtual_sol_reserves: u64,
+    /// Real token reserves in base units
+    pub real_token_reserves: u64,
+    /// Real SOL reserves in lamports
+    pub real_sol_reserves: u64,
+    /// Total token supply in base units
+    pub token_total_supply: u64,
+    /// Whether the curve has completed
+    pub complete: bool,
+}
+
+impl From<CurveState> for BondingCurveAccount {
+    fn from(state: CurveState) -> Self {
+        BondingCurveAccount::new(
+            1,
+            state.virtual_token_reserves,
+            state.virtual_sol_reserves,
+            state.real_token_reserves,
+            state.real_sol_reserves,
+            state.token_total_supply,
+            state.complete,
+        )
+    }
+}
+
+/// Buy and sell outputs of a curve state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CurveVector {
+    /// What the vector covers
+    pub name: String,
+    /// Curve the trades are quoted against
+    pub curve: CurveState,
+    /// Fee charged on sells in basis points
+    pub fee_basis_points: u64,
+    /// SOL spent by the buy in lamports
+    pub buy_sol: u64,
+    /// Tokens received for `buy_sol` in base units, None if the curve refuses the buy
+    pub expected_buy_tokens: Option<u64>,
+    /// Tokens sold in base units
+    pub sell_tokens: u64,
+    /// SOL received for `sell_tokens` in lamports after fees, None if the curve refuses the sell
+    pub expected_sell_sol: Option<u64>,
+}
+
+/// Slippage bounds of an amount
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlippageVector {
+    /// Quoted amount in lamports
+    pub amount: u64,
+    /// Slippage tolerance in basis points
+    pub basis_points: u64,
+    /// Maximum SOL cost of a buy quoted at `amount`
+    pub expected_max_buy_cost: u64,
+    /// Minimum SOL output of a sell quoted at `amount`
+    pub expected_min_sell_output: u64,
+}
+
+/// Inputs and expected result of an address derivation
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PdaVector {
+    /// Address derived: `global`, `mint_authority`, `bonding_curve`, `creator_vault`,
+    /// `metadata` or `associated_token`
+    pub kind: String,
+    /// Public keys the address is derived from, in the order of the derivation's arguments
+    pub inputs: Vec<String>,
+    /// Derived address, None if no address can be derived
+    pub expected: Option<String>,
+}
+
+/// A log line and the event expected to decode from it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventVector {
+    /// What the vector covers
+    pub name: String,
+    /// Event decoded: `CreateEvent`, `TradeEvent`, `CompleteEvent` or `SetParamsEvent`
+    pub kind: String,
+    /// Raw log line, as found in a transaction's log messages
+    pub log: String,
+    /// Fields of the decoded event, None if the line does not decode as `kind`
+    pub expected: Option<Value>,
+}
+
+/// Every vector of the corpus
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Corpus {
+    /// Buy and sell outputs of curve states
+    pub curve: Vec<CurveVector>,
+    /// Slippage bounds of amounts
+    pub slippage: Vec<SlippageVector>,
+    /// Address derivations
+    pub pdas: Vec<PdaVector>,
+    /// Event decodings
+    pub events: Vec<EventVector>,
+}
+
+impl Corpus {
+    /// Generates the corpus from fixed inputs with the current code
+    pub fn generate() -> Self {
+        Self {
+            curve: curve_vectors(),
+            slippage: slippage_vectors(),
+            pdas: pda_vectors(),
+            events: event_vectors(),
+        }
+    }
+
+    /// Loads the corpus from a directory
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory holding the corpus files
+    ///
+    /// # Returns
+    ///
+    /// Returns the corpus, or an io::Error if a file is missing or malformed
+    pub fn load(dir: impl AsRef<Path>) -> io::Result<Self> {
+        let dir = dir.as_ref();
+        Ok(Self {
+            curve: read(&dir.join(CURVE_FILE))?,
+            slippage: read(&dir.join(SLIPPAGE_FILE))?,
+            pdas: read(&dir.join(PDAS_FILE))?,
+            events: read(&dir.join(EVENTS_FILE))?,
+        })
+    }
+
+    /// Writes the corpus to a directory, replacing the files of a previous corpus
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - Directory to write the corpus files to, created if missing
+    ///
+    /// # Returns
+    ///
+    /// Returns Ok once every file is written, or an io::Error
+    pub fn write(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        write(&dir.join(CURVE_FILE), &self.curve)?;
+        write(&dir.join(SLIPPAGE_FILE), &self.slippage)?;
+        write(&dir.join(PDAS_FILE), &self.pdas)?;
+        write(&dir.join(EVENTS_FILE), &self.events)
+    }
+
+    /// Checks every vector against the current code
+    ///
+    /// # Returns
+    ///
+    /// Returns a description of each vector the code disagrees with, empty if there are none
+    pub fn check(&self) -> Vec<String> {
+        let curve = self.curve.iter().map(|vector| {
+            let actual = curve_vector(
+                &vector.name,
+                vector.curve,
+                vector.fee_basis_points,
+                vector.buy_sol,
+                vector.sell_tokens,
+            );
+            mismatch(&vector.name, vector, &actual)
+        });
+        let slippage = self.slippage.iter().map(|vector| {
+            let actual = slippage_vector(vector.amount, vector.basis_points);
+            mismatch(&format!("slippage {}", vector.amount), vector, &actual)
+        });
+        let pdas = self.pdas.iter().map(|vector| {
+            let inputs: Result<Vec<Pubkey>, _> =
+                vector.inputs.iter().map(|input| input.parse()).collect();
+            let actual = inputs.map(|inputs| derive(&vector.kind, &inputs));
+            match actual {
+                Ok(actual) if actual == vector.expected => None,
+                actual => Some(format!(
+                    "{} {:?}: expected {:?}, got {:?}",
+                    vector.kind, vector.inputs, vector.expected, actual
+                )),
+            }
+        });
+        let events = self.events.iter().map(|vector| {
+            let actual = decode(&vector.kind, &vector.log);
+            (actual != vector.expected).then(|| {
+                format!(
+                    "{}: expected {:?}, got {:?}",
+                    vector.name, vector.expected, actual
+                )
+            })
+        });
+
+        curve
+            .chain(slippage)
+            .chain(pdas)
+            .chain(events)
+            .flatten()
+            .collect()
+    }
+}
+
+/// Describes a vector that differs from the one the current code generates
+fn mismatch<T: PartialEq + std::fmt::Debug>(
+    name: &str,
+    expected: &T,
+    actual: &T,
+) -> Option<String> {
+    (expected != actual).then(|| format!("{}: expected {:?}, got {:?}", name, expected, actual))
+}
+
+/// Reads one corpus file
+fn read<T: DeserializeOwned>(path: &Path) -> io::Result<T> {
+    let data = fs::read_to_string(path)?;
+    serde_json::from_str(&data).map_err(io::Error::other)
+}
+
+/// Writes one corpus file as pretty-printed JSON ending with a newline
+fn write<T: Serialize>(path: &Path, vectors: &T) -> io::Result<()> {
+    let mut data = serde_json::to_string_pretty(vectors).map_err(io::Error::other)?;
+    data.push('\n');
+    fs::write(path, data)
+}
+
+/// Fixed public key whose bytes are all `byte`
+fn key(byte: u8) -> Pubkey {
+    Pubkey::new_from_array([byte; 32])
+}
+
+/// Computes the outputs of a curve vector
+fn curve_vector(
+    name: &str,
+    curve: CurveState,
+    fee_basis_points: u64,
+    buy_sol: u64,
+    sell_tokens: u64,
+) -> CurveVector {
+    let account = BondingCurveAccount::from(curve);
+    CurveVector {
+        name: name.to_string(),
+        curve,
+        fee_basis_points,
+        buy_sol,
+        expected_buy_tokens: account.get_buy_price(buy_sol).ok(),
+        sell_tokens,
+        expected_sell_sol: account.get_sell_price(sell_tokens, fee_basis_points).ok(),
+    }
+}
+
+/// Computes the bounds of a slippage vector
+fn slippage_vector(amount: u64, basis_points: u64) -> SlippageVector {
+    SlippageVector {
+        amount,
+        basis_points,
+        expected_max_buy_cost: utils::calculate_with_slippage_buy(amount, basis_points),
+        expected_min_sell_output: utils::calculate_with_slippage_sell(amount, basis_points),
+    }
+}
+
+/// Derives an address of the given kind from its inputs
+fn derive(kind: &str, inputs: &[Pubkey]) -> Option<String> {
+    let address = match (kind, inputs) {
+        ("global", []) => Some(PumpFun::get_global_pda()),
+        ("mint_authority", []) => Some(PumpFun::get_mint_authority_pda()),
+        ("bonding_curve", [mint]) => PumpFun::get_bonding_curve_pda(mint),
+        ("creator_vault", [creator]) => PumpFun::get_creator_vault_pda(creator),
+        ("metadata", [mint]) => Some(PumpFun::get_metadata_pda(mint)),
+        ("associated_token", [owner, mint, token_program]) => {
+            Some(instruction::derive_user_ata(owner, mint, token_program))
+        }
+        _ => None,
+    };
+    address.map(|address| address.to_string())
+}
+
+/// Decodes the event of the given kind from a log line, as JSON
+fn decode(kind: &str, log: &str) -> Option<Value> {
+    let logs = [log.to_string()];
+    match kind {
+        "CreateEvent" => events::parse_logs::<CreateEvent>(&logs)
+            .first()
+            .map(|event| {
+                json!({
+                    "name": event.name,
+                    "symbol": event.symbol,
+                    "uri": event.uri,
+                    "mint": event.mint.to_string(),
+                    "bonding_curve": event.bonding_curve.to_string(),
+                    "user": event.user.to_string(),
+                })
+            }),
+        "TradeEvent" => events::parse_logs::<TradeEvent>(&logs)
+            .first()
+            .map(|event| {
+                json!({
+                    "mint": event.mint.to_string(),
+                    "sol_amount": event.sol_amount,
+                    "token_amount": event.token_amount,
+                    "is_buy": event.is_buy,
+                    "user": event.user.to_string(),
+                    "timestamp": event.timestamp,
+                    "virtual_sol_reserves": event.virtual_sol_reserves,
+                    "virtual_token_reserves": event.virtual_token_reserves,
+                    "real_sol_reserves": event.real_sol_reserves,
+                    "real_token_reserves": event.real_token_reserves,
+                })
+            }),
+        "CompleteEvent" => events::parse_logs::<CompleteEvent>(&logs)
+            .first()
+            .map(|event| {
+                json!({
+                    "user": event.user.to_string(),
+                    "mint": event.mint.to_string(),
+                    "bonding_curve": event.bonding_curve.to_string(),
+                    "timestamp": event.timestamp,
+                })
+            }),
+        "SetParamsEvent" => events::parse_logs::<SetParamsEvent>(&logs)
+            .first()
+            .map(|event| {
+                json!({
+                    "fee_recipient": event.fee_recipient.to_string(),
+                    "initial_virtual_token_reserves": event.initial_virtual_token_reserves,
+                    "initial_virtual_sol_reserves": event.initial_virtual_sol_reserves,
+                    "initial_real_token_reserves": event.initial_real_token_reserves,
+                    "token_total_supply": event.token_total_supply,
+                    "fee_basis_points": event.fee_basis_points,
+                })
+            }),
+        _ => None,
+    }
+}
+
+/// Encodes an event as the log line the program writes for it
+fn to_log<T: events::Event + BorshSerialize>(event: &T) -> String {
+    let mut data = T::DISCRIMINATOR.to_vec();
+    event
+        .serialize(&mut data)
+        .expect("Serializing to a vector cannot fail");
+    format!("{}{}", events::PROGRAM_DATA_PREFIX, STANDARD.encode(data))
+}
+
+/// Curve states at the start, middle and end of a curve's life
+fn curve_vectors() -> Vec<CurveVector> {
+    let initial = CurveState {
+        virtual_token_reserves: 1_073_000_000_000_000,
+        virtual_sol_reserves: 30_000_000_000,
+        real_token_reserves: 793_100_000_000_000,
+        real_sol_reserves: 0,
+        token_total_supply: 1_000_000_000_000_000,
+        complete: false,
+    };
+    let midway = CurveState {
+        virtual_token_reserves: 873_000_000_000_000,
+        virtual_sol_reserves: 36_872_852_233,
+        real_token_reserves: 593_100_000_000_000,
+        real_sol_reserves: 6_872_852_233,
+        ..initial
+    };
+    let nearly_complete = CurveState {
+        virtual_token_reserves: 280_000_000_000_000,
+        virtual_sol_reserves: 114_964_285_714,
+        real_token_reserves: 100_000_000,
+        real_sol_reserves: 84_964_285_714,
+        ..initial
+    };
+    let complete = CurveState {
+        real_token_reserves: 0,
+        complete: true,
+        ..nearly_complete
+    };
+
+    vec![
+        curve_vector("initial", initial, 100, 1_000_000_000, 10_000_000_000_000),
+        curve_vector("initial_dust", initial, 100, 1, 1),
+        curve_vector("initial_zero", initial, 100, 0, 0),
+        curve_vector("midway", midway, 100, 2_500_000_000, 50_000_000_000_000),
+        curve_vector("midway_no_fee", midway, 0, 123_456_789, 987_654_321),
+        curve_vector(
+            "nearly_complete_capped_buy",
+            nearly_complete,
+            100,
+            10_000_000_000,
+            100_000_000,
+        ),
+        curve_vector("complete", complete, 100, 1_000_000_000, 1_000_000_000),
+    ]
+}
+
+/// Amounts and tolerances, including rounding and the zero cases
+fn slippage_vectors() -> Vec<SlippageVector> {
+    [
+        (1_000_000_000, 100),
+        (1_000_000_000, 500),
+        (123_456_789, 250),
+        (999, 1),
+        (0, 500),
+        (5_000_000_000, 10_000),
+    ]
+    .into_iter()
+    .map(|(amount, basis_points)| slippage_vector(amount, basis_points))
+    .collect()
+}
+
+/// Derivations of every address the client computes
+fn pda_vectors() -> Vec<PdaVector> {
+    let cases: Vec<(&str, Vec<Pubkey>)> = vec![
+        ("global", vec![]),
+        ("mint_authority", vec![]),
+        ("bonding_curve", vec![key(1)]),
+        ("bonding_curve", vec![key(2)]),
+        ("creator_vault", vec![key(3)]),
+        ("metadata", vec![key(1)]),
+        (
+            "associated_token",
+            vec![key(4), key(1), constants::accounts::TOKEN_PROGRAM],
+        ),
+        (
+            "associated_token",
+            vec![key(4), key(1), constants::accounts::TOKEN_2022_PROGRAM],
+        ),
+    ];
+    cases
+        .into_iter()
+        .map(|(kind, inputs)| PdaVector {
+            kind: kind.to_string(),
+            expected: derive(kind, &inputs),
+            inputs: inputs.iter().map(Pubkey::to_string).collect(),
+        })
+        .collect()
+}
+
+/// Log lines of each event, and lines that must not decode
+fn event_vectors() -> Vec<EventVector> {
+    let create = CreateEvent {
+        name: "Pump Token".to_string(),
+        symbol: "PUMP".to_string(),
+        uri: "https://ipfs.io/ipfs/QmPumpToken".to_string(),
+        mint: key(1),
+        bonding_curve: key(5),
+        user: key(4),
+    };
+    let trade = TradeEvent {
+        mint: key(1),
+        sol_amount: 1_000_000_000,
+        token_amount: 34_612_903_225_806,
+        is_buy: true,
+        user: key(4),
+        timestamp: 1_735_689_600,
+        virtual_sol_reserves: 31_000_000_000,
+        virtual_token_reserves: 1_038_387_096_774_194,
+        real_sol_reserves: 1_000_000_000,
+        real_token_reserves: 758_487_096_774_194,
+    };
+    let complete = CompleteEvent {
+        user: key(4),
+        mint: key(1),
+        bonding_curve: key(5),
+        timestamp: 1_735_689_601,
+    };
+    let set_params = SetParamsEvent {
+        fee_recipient: key(6),
+        initial_virtual_token_reserves: 1_073_000_000_000_000,
+        initial_virtual_sol_reserves: 30_000_000_000,
+        initial_real_token_reserves: 793_100_000_000_000,
+        token_total_supply: 1_000_000_000_000_000,
+        fee_basis_points: 100,
+    };
+    let sell = TradeEvent {
+        is_buy: false,
+        ..trade.clone()
+    };
+
+    [
+        ("create", "CreateEvent", to_log(&create)),
+        ("trade_buy", "TradeEvent", to_log(&trade)),
+        ("trade_sell", "TradeEvent", to_log(&sell)),
+        ("complete", "CompleteEvent", to_log(&complete)),
+        ("set_params", "SetParamsEvent", to_log(&set_params)),
+        ("wrong_discriminator", "CompleteEvent", to_log(&trade)),
+        (
+            "not_program_data",
+            "TradeEvent",
+            "Program log: Instruction: Buy".to_string(),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, kind, log)| EventVector {
+        name: name.to_string(),
+        kind: kind.to_string(),
+        expected: decode(kind, &log),
+        log,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Directory of the corpus at the root of the repository
+    const TESTDATA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/../../testdata");
+
+    #[test]
+    fn test_corpus_matches_code() {
+        let corpus = Corpus::load(TESTDATA).unwrap();
+        assert_eq!(corpus.check(), Vec::<String>::new());
+
+        // Regenerating must reproduce the corpus exactly, inputs included
+        assert_eq!(corpus, Corpus::generate());
+    }
+
+    #[test]
+    fn test_corpus_is_json_safe() {
+        let corpus = serde_json::to_value(Corpus::generate()).unwrap();
+        let mut pending = vec![&corpus];
+        while let Some(value) = pending.pop() {
+            match value {
+                Value::Number(number) => assert!(number
+                    .as_u64()
+                    .or(number.as_i64().map(i64::unsigned_abs))
+                    .is_some_and(|number| number <= MAX_SAFE_INTEGER)),
+                Value::Array(values) => pending.extend(values),
+                Value::Object(fields) => pending.extend(fields.values()),
+                _ => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_check_reports_mismatches() {
+        let mut corpus = Corpus::generate();
+        corpus.curve[0].expected_buy_tokens = Some(1);
+        corpus.pdas[0].expected = Some(key(9).to_string());
+        corpus.events[0].expected = None;
+        assert_eq!(corpus.check().len(), 3);
+
+        let dir = std::env::temp_dir().join(format!("pumpfun-vectors-{}", std::process::id()));
+        corpus.write(&dir).unwrap();
+        assert_eq!(Corpus::load(&dir).unwrap(), corpus);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
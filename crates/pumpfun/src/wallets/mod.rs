@@ -0,0 +1,441 @@
+//! Wallet funding for multi-wallet launch bundles.
+//!
+//! This module plans and executes the SOL transfers needed to fund each wallet of a launch
+//! bundle with enough SOL for its buy, the trading fee, the token account rent and the
+//! transaction fees.
+//!
+//! # Strategies
+//!
+//! - `Direct`: Each wallet is funded by a transfer from the master wallet.
+//! - `Chained`: The master funds the first wallet, which forwards the remainder to the next,
+//!   and so on (A→B→C).
+//! - `Randomized`: Direct transfers with each amount increased by a random share of a tolerance,
+//!   so the transfers don't share an obvious pattern. Wallets are never underfunded.
+//!
+//! Planning is separate from execution: [`FundingPlanner::plan`] fixes every transfer (including
+//! randomized amounts) up front and the resulting [`FundingPlan`] can be printed as a dry run
+//! for review before [`FundingPlan::execute_funding`] sends exactly those transfers.
+
+use crate::{error, utils, PumpFun};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+use std::fmt;
+
+/// Rent-exempt balance of an SPL token account in lamports
+pub const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+
+/// Base fee of a single-signature transaction in lamports
+pub const TRANSACTION_FEE: u64 = 5_000;
+
+/// Strategy used to route funding transfers to the bundle wallets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FundingStrategy {
+    /// Transfer directly from the master wallet to each wallet
+    Direct,
+    /// Transfer from the master to the first wallet, which forwards to the next, and so on
+    Chained,
+    /// Transfer directly, increasing each amount by up to `tolerance_basis_points`
+    Randomized {
+        /// Maximum increase over the required amount in basis points
+        tolerance_basis_points: u64,
+    },
+}
+
+/// A single SOL transfer in a funding plan
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedTransfer {
+    /// Wallet sending the SOL
+    pub from: Pubkey,
+    /// Wallet receiving the SOL
+    pub to: Pubkey,
+    /// Amount of SOL in lamports
+    pub lamports: u64,
+}
+
+/// Result of executing a funding plan
+#[derive(Debug, Clone)]
+pub struct FundingReport {
+    /// Signature of each confirmed transfer, in plan order
+    pub signatures: Vec<Signature>,
+    /// Final balance in lamports of each bundle wallet
+    pub balances: Vec<(Pubkey, u64)>,
+}
+
+/// Plans the funding of the wallets in a launch bundle
+pub struct FundingPlanner<'a> {
+    /// Wallet providing the SOL
+    master: &'a Keypair,
+    /// Bundle wallets and the amount of SOL each will spend on its buy in lamports
+    wallets: Vec<(&'a Keypair, u64)>,
+    /// Strategy used to route the transfers
+    strategy: FundingStrategy,
+    /// Slippage tolerance applied to each buy in basis points
+    slippage_basis_points: u64,
+    /// Trading fee charged on each buy in basis points
+    fee_basis_points: u64,
+    /// Extra lamports left in each wallet for priority fees
+    fee_reserve: u64,
+    /// Seed for randomized amounts, for reproducible plans
+    seed: Option<u64>,
+}
+
+impl<'a> FundingPlanner<'a> {
+    /// Creates a new funding planner
+    ///
+    /// Defaults to direct transfers, 500 basis points of slippage, a 100 basis point trading fee
+    /// and no priority fee reserve.
+    ///
+    /// # Arguments
+    /// * `master` - Wallet providing the SOL
+    pub fn new(master: &'a Keypair) -> Self {
+        Self {
+            master,
+            wallets: Vec::new(),
+            strategy: FundingStrategy::Direct,
+            slippage_basis_points: 500,
+            fee_basis_points: 100,
+            fee_reserve: 0,
+            seed: None,
+        }
+    }
+
+    /// Adds a bundle wallet and the amount of SOL in lamports it will spend on its buy
+    pub fn with_wallet(mut self, wallet: &'a Keypair, buy_amount_sol: u64) -> Self {
+        self.wallets.push((wallet, buy_amount_sol));
+        self
+    }
+
+    /// Sets the strategy used to route the transfers
+    pub fn with_strategy(mut self, strategy: FundingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the slippage tolerance applied to each buy in basis points
+    pub fn with_slippage_basis_points(mut self, slippage_basis_points: u64) -> Self {
+        self.slippage_basis_points = slippage_basis_points;
+        self
+    }
+
+    /// Sets the trading fee charged on each buy in basis points
+    pub fn with_fee_basis_points(mut self, fee_basis_points: u64) -> Self {
+        self.fee_basis_points = fee_basis_points;
+        self
+    }
+
+    /// Sets the extra lamports left in each wallet for priority fees
+    pub fn with_fee_reserve(mut self, fee_reserve: u64) -> Self {
+        self.fee_reserve = fee_reserve;
+        self
+    }
+
+    /// Sets the seed used for randomized amounts so the plan is reproducible
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Calculates the SOL a wallet needs for its buy in lamports
+    ///
+    /// Covers the maximum buy cost after slippage, the trading fee on it, the rent for the
+    /// wallet's token account, the buy transaction fee and the priority fee reserve.
+    ///
+    /// # Arguments
+    /// * `buy_amount_sol` - Amount of SOL the wallet will spend on its buy in lamports
+    pub fn required_balance(&self, buy_amount_sol: u64) -> u64 {
+        let max_sol_cost =
+            utils::calculate_with_slippage_buy(buy_amount_sol, self.slippage_basis_points);
+        let fee = ((max_sol_cost as u128) * (self.fee_basis_points as u128) / 10000) as u64;
+
+        max_sol_cost + fee + TOKEN_ACCOUNT_RENT + TRANSACTION_FEE + self.fee_reserve
+    }
+
+    /// Plans every funding transfer without sending anything
+    ///
+    /// # Returns
+    /// The funding plan, which can be reviewed as a dry run before being executed
+    pub fn plan(&self) -> FundingPlan<'a> {
+        let required: Vec<u64> = self
+            .wallets
+            .iter()
+            .map(|(_, buy_amount_sol)| self.required_balance(*buy_amount_sol))
+            .collect();
+
+        let mut transfers = Vec::with_capacity(self.wallets.len());
+        match self.strategy {
+            FundingStrategy::Direct => {
+                for ((wallet, _), lamports) in self.wallets.iter().zip(&required) {
+                    transfers.push((self.master, wallet.pubkey(), *lamports));
+                }
+            }
+            FundingStrategy::Chained => {
+                let mut from = self.master;
+                for (index, (wallet, _)) in self.wallets.iter().enumerate() {
+                    // Everything still needed downstream, plus the fee of each forwarding hop
+                    let hops = (self.wallets.len() - index - 1) as u64;
+                    let lamports = required[index..].iter().sum::<u64>() + hops * TRANSACTION_FEE;
+                    transfers.push((from, wallet.pubkey(), lamports));
+                    from = *wallet;
+                }
+            }
+            FundingStrategy::Randomized {
+                tolerance_basis_points,
+            } => {
+                let mut rng = match self.seed {
+                    Some(seed) => StdRng::seed_from_u64(seed),
+                    None => StdRng::from_entropy(),
+                };
+                for ((wallet, _), lamports) in self.wallets.iter().zip(&required) {
+                    let max_extra =
+                        ((*lamports as u128) * (tolerance_basis_points as u128) / 10000) as u64;
+                    let extra = rng.gen_range(0..=max_extra);
+                    transfers.push((self.master, wallet.pubkey(), lamports + extra));
+                }
+            }
+        }
+
+        FundingPlan {
+            transfers,
+            wallets: self
+                .wallets
+                .iter()
+                .map(|(wallet, _)| wallet.pubkey())
+                .collect(),
+        }
+    }
+}
+
+/// A reviewed set of funding transfers ready to execute
+pub struct FundingPlan<'a> {
+    /// Signer, recipient and lamports of each transfer, in execution order
+    transfers: Vec<(&'a Keypair, Pubkey, u64)>,
+    /// Bundle wallets being funded
+    wallets: Vec<Pubkey>,
+}
+
+impl FundingPlan<'_> {
+    /// Gets every planned transfer in execution order
+    pub fn transfers(&self) -> Vec<PlannedTransfer> {
+        self.transfers
+            .iter()
+            .map(|(from, to, lamports)| PlannedTransfer {
+                from: from.pubkey(),
+                to: *to,
+                lamports: *lamports,
+            })
+            .collect()
+    }
+
+    /// Gets the total SOL leaving the master wallet in lamports, including transaction fees
+    pub fn total_cost(&self) -> u64 {
+        let master = self.transfers.first().map(|(from, _, _)| from.pubkey());
+        self.transfers
+            .iter()
+            .filter(|(from, _, _)| Some(from.pubkey()) == master)
+            .map(|(_, _, lamports)| lamports + TRANSACTION_FEE)
+            .sum()
+    }
+
+    /// Sends every planned transfer, waiting for each to confirm before the next
+    ///
+    /// # Arguments
+    /// * `client` - Client used to send the transfers
+    ///
+    /// # Returns
+    /// The transfer signatures and the final balance of each bundle wallet, or a ClientError
+    /// if a transfer fails. Transfers before the failing one will already have landed.
+    pub fn execute_funding(&self, client: &PumpFun) -> Result<FundingReport, error::ClientError> {
+        let mut signatures = Vec::with_capacity(self.transfers.len());
+        for (from, to, lamports) in &self.transfers {
+            let blockhash = client
+                .rpc
+                .get_latest_blockhash()
+                .map_err(error::ClientError::SolanaClientError)?;
+            let transaction = Transaction::new_signed_with_payer(
+                &[system_instruction::transfer(&from.pubkey(), to, *lamports)],
+                Some(&from.pubkey()),
+                &[*from],
+                blockhash,
+            );
+            let signature = client
+                .rpc
+                .send_and_confirm_transaction(&transaction)
+                .map_err(error::ClientError::SolanaClientError)?;
+            signatures.push(signature);
+        }
+
+        let mut balances = Vec::with_capacity(self.wallets.len());
+        for wallet in &self.wallets {
+            let balance = client
+                .rpc
+                .get_balance(wallet)
+                .map_err(error::ClientError::SolanaClientError)?;
+            balances.push((*wallet, balance));
+        }
+
+        Ok(FundingReport {
+            signatures,
+            balances,
+        })
+    }
+}
+
+impl fmt::Display for FundingPlan<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Funding plan ({} transfers):", self.transfers.len())?;
+        for (index, transfer) in self.transfers().iter().enumerate() {
+            writeln!(
+                f,
+                "  {}. {} -> {}: {} lamports",
+                index + 1,
+                transfer.from,
+                transfer.to,
+                transfer.lamports
+            )?;
+        }
+        write!(f, "Total cost to master: {} lamports", self.total_cost())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_balance() {
+        let master = Keypair::new();
+        let planner = FundingPlanner::new(&master)
+            .with_slippage_basis_points(100)
+            .with_fee_basis_points(100)
+            .with_fee_reserve(1_000);
+
+        // 1.01 SOL max cost, 1% fee on it, rent, transaction fee and reserve
+        assert_eq!(
+            planner.required_balance(1_000_000_000),
+            1_010_000_000 + 10_100_000 + TOKEN_ACCOUNT_RENT + TRANSACTION_FEE + 1_000
+        );
+    }
+
+    #[test]
+    fn test_direct_plan() {
+        let master = Keypair::new();
+        let (a, b) = (Keypair::new(), Keypair::new());
+        let planner = FundingPlanner::new(&master)
+            .with_wallet(&a, 1_000_000_000)
+            .with_wallet(&b, 500_000_000);
+        let transfers = planner.plan().transfers();
+
+        assert_eq!(
+            transfers,
+            vec![
+                PlannedTransfer {
+                    from: master.pubkey(),
+                    to: a.pubkey(),
+                    lamports: planner.required_balance(1_000_000_000),
+                },
+                PlannedTransfer {
+                    from: master.pubkey(),
+                    to: b.pubkey(),
+                    lamports: planner.required_balance(500_000_000),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chained_plan() {
+        let master = Keypair::new();
+        let (a, b, c) = (Keypair::new(), Keypair::new(), Keypair::new());
+        let planner = FundingPlanner::new(&master)
+            .with_strategy(FundingStrategy::Chained)
+            .with_wallet(&a, 300_000_000)
+            .with_wallet(&b, 200_000_000)
+            .with_wallet(&c, 100_000_000);
+        let plan = planner.plan();
+        let transfers = plan.transfers();
+        let (need_a, need_b, need_c) = (
+            planner.required_balance(300_000_000),
+            planner.required_balance(200_000_000),
+            planner.required_balance(100_000_000),
+        );
+
+        assert_eq!(transfers.len(), 3);
+        assert_eq!(
+            (transfers[0].from, transfers[0].to),
+            (master.pubkey(), a.pubkey())
+        );
+        assert_eq!(
+            (transfers[1].from, transfers[1].to),
+            (a.pubkey(), b.pubkey())
+        );
+        assert_eq!(
+            (transfers[2].from, transfers[2].to),
+            (b.pubkey(), c.pubkey())
+        );
+
+        // Each wallet keeps exactly what it needs after forwarding and paying the hop fee
+        assert_eq!(transfers[2].lamports, need_c);
+        assert_eq!(
+            transfers[1].lamports - transfers[2].lamports - TRANSACTION_FEE,
+            need_b
+        );
+        assert_eq!(
+            transfers[0].lamports - transfers[1].lamports - TRANSACTION_FEE,
+            need_a
+        );
+
+        // Only the first hop is paid by the master
+        assert_eq!(plan.total_cost(), transfers[0].lamports + TRANSACTION_FEE);
+    }
+
+    #[test]
+    fn test_randomized_plan() {
+        let master = Keypair::new();
+        let wallets: Vec<Keypair> = (0..10).map(|_| Keypair::new()).collect();
+        let planner = wallets.iter().fold(
+            FundingPlanner::new(&master)
+                .with_strategy(FundingStrategy::Randomized {
+                    tolerance_basis_points: 1_000,
+                })
+                .with_seed(42),
+            |planner, wallet| planner.with_wallet(wallet, 1_000_000_000),
+        );
+        let required = planner.required_balance(1_000_000_000);
+        let transfers = planner.plan().transfers();
+
+        // Never underfunded and never over the tolerance
+        for transfer in &transfers {
+            assert!(transfer.lamports >= required);
+            assert!(transfer.lamports <= required + required / 10);
+        }
+
+        // Amounts differ from each other and are reproducible from the seed
+        assert!(transfers
+            .iter()
+            .any(|t| t.lamports != transfers[0].lamports));
+        assert_eq!(planner.plan().transfers(), transfers);
+    }
+
+    #[test]
+    fn test_dry_run_output() {
+        let master = Keypair::new();
+        let wallet = Keypair::new();
+        let plan = FundingPlanner::new(&master)
+            .with_wallet(&wallet, 1_000_000_000)
+            .plan();
+        let output = plan.to_string();
+
+        assert!(output.starts_with("Funding plan (1 transfers):"));
+        assert!(output.contains(&format!("1. {} -> {}", master.pubkey(), wallet.pubkey())));
+        assert!(output.ends_with(&format!(
+            "Total cost to master: {} lamports",
+            plan.total_cost()
+        )));
+    }
+}
@@ -0,0 +1,1553 @@
+//! Snapshot of the crate's public API
+//!
+//! This file uses the public surface the way a downstream crate would: it names every error and
+//! outcome variant, destructures the public structs, and pins the signatures of the client's
+//! methods by coercing them to function pointers or awaiting them. Nothing here talks to a
+//! cluster; the checks happen when `cargo test` compiles the file.
+//!
+//! A compile error here means a public item was removed or changed incompatibly. If the change
+//! is deliberate, update this snapshot in the same change so the break is visible in review.
+//! New items should be added here as well, so that they are covered from then on.
+//!
+//! Error and outcome enums are `#[non_exhaustive]`, so adding a variant to them only requires
+//! extending the list of constructed variants. Closed enums are matched exhaustively, so adding
+//! a variant to one of them is flagged as the breaking change it is.
+
+use anchor_client::{
+    solana_client::{
+        client_error::ClientError as SolanaClientError,
+        nonblocking::pubsub_client::PubsubClientError, rpc_response,
+    },
+    solana_sdk::{
+        instruction::Instruction,
+        message::VersionedMessage,
+        transaction::{Transaction, TransactionError, VersionedTransaction},
+    },
+};
+use pumpfun::{
+    accounts::DecodeFailure,
+    bundle::{BundleSimulation, SimulatedTransaction},
+    cleanup::CleanupEntry,
+    devnet::DevnetSetupReport,
+    events::{filters, CompleteEvent, LogParser},
+    instruction::ProgramVersion,
+    mev::{CompetitionReport, SandwichReport},
+    prelude::*,
+    repl::{Command, ScriptError, Session},
+    screening::SafetyReport,
+    shed,
+    strategy::{ChildFill, ChildSizing, TwapExecutor, TwapReport, TwapStop},
+    submit::SubmitErrorKind,
+    upgrade::ClientEvent,
+    usage::{
+        CostTable, Operation, OperationScope, OperationUsage, RpcMethod, RpcUsage, UsageReport,
+    },
+    utils::{TokenMetadata, TokenMetadataResponse, UploadedMetadata},
+    vectors::{Corpus, CurveState, CurveVector, EventVector, PdaVector, SlippageVector},
+    CurveAtaIssue, CurveAtaReport,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Every variant of the error and outcome enums, as a downstream crate constructs them
+#[test]
+fn test_enum_variants() {
+    let pubkey = Pubkey::new_unique();
+    let client_errors = [
+        ClientError::BondingCurveNotFound,
+        ClientError::BondingCurveError("error"),
+        ClientError::BorshError(std::io::Error::other("error")),
+        ClientError::UploadMetadataError("error".into()),
+        ClientError::InvalidInput("error"),
+        ClientError::InsufficientFunds,
+        ClientError::SimulationError("error".to_string()),
+        ClientError::RateLimitExceeded,
+        ClientError::SubscriptionClosed,
+        ClientError::Timeout,
+        ClientError::TransactionTooLarge { size: 1, max: 1 },
+        ClientError::FillBelowExpected {
+            expected: 1,
+            actual: 0,
+        },
+        ClientError::MintAlreadyExists(pubkey),
+        ClientError::JournalError(std::io::Error::other("error")),
+        ClientError::MissingDependencyProgram {
+            program: pubkey,
+            hint: "hint",
+        },
+        ClientError::Cancelled,
+        ClientError::StalePlan {
+            observed_slot: 1,
+            current_slot: 2,
+        },
+        ClientError::ExportError(std::io::Error::other("error")),
+        ClientError::ProgramError(PumpFunProgramError::BondingCurveComplete),
+        ClientError::MintLocked(pubkey),
+        ClientError::TradingPaused,
+        ClientError::PriorityFeeBudgetExceeded {
+            spent: 2,
+            budget: 1,
+        },
+        ClientError::GlobalParamsMismatch {
+            fields: vec!["fee_basis_points"],
+        },
+        ClientError::MemoTooLong { len: 2, max: 1 },
+        ClientError::Shedded {
+            feature: shed::Feature::HolderCounts,
+            pressure: Pressure::Elevated,
+        },
+        ClientError::ImageTooLarge { size: 2, max: 1 },
+        ClientError::InvalidGlobalParams {
+            violations: vec![
+                GlobalParamsViolation::Missing {
+                    field: "fee_recipient",
+                },
+                GlobalParamsViolation::DefaultFeeRecipient,
+                GlobalParamsViolation::ZeroVirtualTokenReserves,
+                GlobalParamsViolation::ZeroVirtualSolReserves,
+                GlobalParamsViolation::ZeroRealTokenReserves,
+                GlobalParamsViolation::ZeroTokenTotalSupply,
+                GlobalParamsViolation::FeeTooHigh {
+                    fee_basis_points: 10_001,
+                },
+                GlobalParamsViolation::SupplyBelowRealReserves {
+                    token_total_supply: 1,
+                    initial_real_token_reserves: 2,
+                },
+                GlobalParamsViolation::RealReservesNotBelowVirtual {
+                    initial_real_token_reserves: 2,
+                    initial_virtual_token_reserves: 1,
+                },
+            ],
+        },
+        ClientError::BondingCurveComplete,
+    ];
+    let _: fn(SolanaClientError) -> ClientError = ClientError::SolanaClientError;
+    let _: fn(anchor_client::ClientError) -> ClientError = ClientError::AnchorClientError;
+    let _: fn(PubsubClientError) -> ClientError = ClientError::PubsubClientError;
+    let _: fn(Box<DecodeFailure>) -> ClientError = ClientError::AccountDecodeError;
+    for err in &client_errors {
+        let _: &dyn std::error::Error = err;
+        assert!(!err.to_string().is_empty());
+    }
+
+    let curve_errors = [
+        CurveError::Complete,
+        CurveError::TargetBelowCurrent,
+        CurveError::TargetBeyondGraduation,
+        CurveError::InfeasibleTrade {
+            index: 0,
+            reason: "reason",
+        },
+        CurveError::InvariantBelowInitial {
+            invariant: 0,
+            initial: 1,
+        },
+        CurveError::ReserveOffsetMismatch,
+    ];
+    for err in &curve_errors {
+        let _: &dyn std::error::Error = err;
+    }
+
+    let _ = [
+        CurveAtaIssue::Missing,
+        CurveAtaIssue::WrongProgram { actual: pubkey },
+        CurveAtaIssue::InvalidData,
+        CurveAtaIssue::WrongMint { actual: pubkey },
+        CurveAtaIssue::WrongAuthority { actual: pubkey },
+        CurveAtaIssue::BalanceBelowReserves {
+            balance: 0,
+            reserves: 1,
+        },
+    ];
+
+    let _ = [
+        EntryState::Pending,
+        EntryState::Confirmed,
+        EntryState::Failed,
+        EntryState::Expired,
+        EntryState::Unknown,
+    ];
+
+    let _ = [
+        CleanupState::Queued,
+        CleanupState::Closed,
+        CleanupState::SkippedNonzeroBalance,
+        CleanupState::SkippedMissing,
+        CleanupState::SkippedInvalid,
+        CleanupState::Failed,
+    ];
+
+    let program_errors = [
+        PumpFunProgramError::NotAuthorized,
+        PumpFunProgramError::AlreadyInitialized,
+        PumpFunProgramError::TooMuchSolRequired,
+        PumpFunProgramError::TooLittleSolReceived,
+        PumpFunProgramError::MintDoesNotMatchBondingCurve,
+        PumpFunProgramError::BondingCurveComplete,
+        PumpFunProgramError::BondingCurveNotComplete,
+        PumpFunProgramError::NotInitialized,
+        PumpFunProgramError::WithdrawTooFrequent,
+    ];
+    for err in &program_errors {
+        let _: &dyn std::error::Error = err;
+        let _: u32 = err.code();
+        let _: &'static str = err.message();
+    }
+    let _: fn(u32) -> Option<PumpFunProgramError> = PumpFunProgramError::from_code;
+    let _: fn(&TransactionError, &VersionedMessage) -> Option<PumpFunProgramError> =
+        PumpFunProgramError::from_transaction_error;
+
+    let _ = [
+        SubmitErrorKind::BlockhashNotFound,
+        SubmitErrorKind::AlreadyProcessed,
+        SubmitErrorKind::Transient,
+        SubmitErrorKind::Fatal,
+    ];
+
+    let _ = [
+        Operation::Buy,
+        Operation::Sell,
+        Operation::Create,
+        Operation::Swap,
+        Operation::Quote,
+        Operation::Query,
+        Operation::Backfill,
+        Operation::Subscription,
+        Operation::Cleanup,
+        Operation::Untagged,
+    ];
+
+    let _ = [
+        RpcMethod::GetAccountInfo,
+        RpcMethod::GetMultipleAccounts,
+        RpcMethod::GetProgramAccounts,
+        RpcMethod::GetBalance,
+        RpcMethod::GetTokenLargestAccounts,
+        RpcMethod::GetSignaturesForAddress,
+        RpcMethod::GetTransaction,
+        RpcMethod::GetBlock,
+        RpcMethod::GetSignatureStatuses,
+        RpcMethod::GetLatestBlockhash,
+        RpcMethod::GetSlot,
+        RpcMethod::SendTransaction,
+        RpcMethod::SimulateTransaction,
+        RpcMethod::Other,
+    ];
+
+    let _ = [ClientEvent::ProgramUpgraded { slot: 1 }];
+
+    let _ = [
+        TwapStop::Completed,
+        TwapStop::PriceCeiling {
+            index: 0,
+            price: 1.0,
+        },
+        TwapStop::CurveComplete { index: 0 },
+        TwapStop::ChildFailed {
+            index: 0,
+            attempts: 1,
+        },
+    ];
+
+    // Closed enums stay exhaustive
+    for side in [TradeSide::Buy, TradeSide::Sell] {
+        match side {
+            TradeSide::Buy | TradeSide::Sell => {}
+        }
+    }
+    for trade in [
+        PlannedTrade::Buy { sol_amount: 1 },
+        PlannedTrade::Sell { token_amount: 1 },
+    ] {
+        match trade {
+            PlannedTrade::Buy { sol_amount: _ } | PlannedTrade::Sell { token_amount: _ } => {}
+        }
+    }
+    for mode in [
+        SlippageMode::BasisPoints(1),
+        SlippageMode::MaxMultiplier(1.0),
+    ] {
+        match mode {
+            SlippageMode::BasisPoints(_) | SlippageMode::MaxMultiplier(_) => {}
+        }
+    }
+    for sizing in [
+        ChildSizing::Equal,
+        ChildSizing::Randomized {
+            jitter_bps: 1,
+            seed: 1,
+        },
+    ] {
+        match sizing {
+            ChildSizing::Equal | ChildSizing::Randomized { .. } => {}
+        }
+    }
+    for authority in [
+        MintAuthority::Renounced,
+        MintAuthority::PumpFun(pubkey),
+        MintAuthority::Other(pubkey),
+    ] {
+        match authority {
+            MintAuthority::Renounced | MintAuthority::PumpFun(_) | MintAuthority::Other(_) => {}
+        }
+    }
+    for action in [BudgetAction::Downgrade(1), BudgetAction::Block] {
+        match action {
+            BudgetAction::Downgrade(_) | BudgetAction::Block => {}
+        }
+    }
+}
+
+#[cfg(feature = "api")]
+#[test]
+fn test_api_error_variants() {
+    use pumpfun::api::ApiError;
+
+    let _: fn(isahc::Error) -> ApiError = ApiError::Http;
+    let _: fn(isahc::http::Error) -> ApiError = ApiError::Request;
+    let _: fn(std::io::Error) -> ApiError = ApiError::Io;
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_testing_types() {
+    use pumpfun::testing::{GlobalParams, GlobalParamsBuilder, InitOutcome, ValidatedGlobalParams};
+
+    let params = GlobalParams::new(Pubkey::new_unique());
+    let GlobalParams {
+        fee_recipient: _,
+        initial_virtual_token_reserves: _,
+        initial_virtual_sol_reserves: _,
+        initial_real_token_reserves: _,
+        token_total_supply: _,
+        fee_basis_points: _,
+    } = params;
+    let _: fn(&GlobalParams, &GlobalAccount) -> Vec<&'static str> = GlobalParams::mismatches;
+    let _: fn(&GlobalParams) -> Vec<GlobalParamsViolation> = GlobalParams::violations;
+    let _: u64 = pumpfun::testing::MAX_FEE_BASIS_POINTS;
+    let validated: ValidatedGlobalParams = params.validate().unwrap();
+    let _: &GlobalParams = validated.params();
+    let _: GlobalParams = validated.into();
+    let _: pumpfun_cpi::instruction::SetParams = validated.into();
+    let _: Result<ValidatedGlobalParams, ClientError> = GlobalParams::builder()
+        .with_fee_recipient(Pubkey::new_unique())
+        .with_initial_virtual_token_reserves(1)
+        .with_initial_virtual_sol_reserves(1)
+        .with_initial_real_token_reserves(1)
+        .with_token_total_supply(1)
+        .with_fee_basis_points(1)
+        .build();
+    let _: GlobalParamsBuilder = GlobalParamsBuilder::default();
+    let _ = [
+        InitOutcome::Initialized(Signature::default()),
+        InitOutcome::AlreadyInitialized {
+            mismatches: Vec::new(),
+        },
+    ];
+}
+
+/// Builders of the admin instructions used to set up local forks
+#[test]
+fn test_admin_instructions() {
+    let _: fn(&Keypair) -> Instruction = pumpfun::instruction::initialize;
+}
+
+/// Instruction builders for forks with their own seeds or token program
+#[test]
+fn test_seeded_instructions() {
+    use pumpfun::{cpi::instruction as args, instruction};
+
+    let _: fn(&SeedConfig, &Keypair, &Keypair, args::Create) -> Instruction =
+        instruction::create_with_seeds;
+    let _: fn(&SeedConfig, &Keypair, &Pubkey, &Pubkey, args::Buy) -> Instruction =
+        instruction::buy_with_seeds;
+    let _: fn(&SeedConfig, &Keypair, &Pubkey, &Pubkey, args::Sell) -> Instruction =
+        instruction::sell_with_seeds;
+    let _: fn(&SeedConfig, &Pubkey, &Keypair, &Keypair, args::Create) -> Instruction =
+        instruction::create_with_token_program;
+    let _: fn(&SeedConfig, &Pubkey, &Keypair, &Pubkey, &Pubkey, args::Buy) -> Instruction =
+        instruction::buy_with_token_program;
+    let _: fn(&SeedConfig, &Pubkey, &Keypair, &Pubkey, &Pubkey, args::Sell) -> Instruction =
+        instruction::sell_with_token_program;
+}
+
+/// Memo instruction and the helpers sizing and reading memos
+#[test]
+fn test_memo_helpers() {
+    let _: fn(&str) -> Instruction = pumpfun::instruction::memo;
+    let _: fn(&[Instruction], &Pubkey) -> usize = pumpfun::utils::memo_capacity;
+    let _: fn(&[Instruction], &str, &Pubkey) -> Result<(), ClientError> =
+        pumpfun::utils::validate_memo;
+    let _: fn(&VersionedMessage) -> Vec<String> = pumpfun::utils::extract_memos;
+    let _: fn(&str) -> Vec<String> = pumpfun::utils::parse_memo_field;
+    let _: fn(JournalEntry, String) -> JournalEntry = JournalEntry::with_memo;
+    let _: fn(&TradeOutcome) -> Option<JournalEntry> = JournalEntry::from_outcome;
+    let _: fn(
+        TradeId,
+        Signature,
+        Pubkey,
+        TradeSide,
+        u64,
+        u64,
+        u64,
+        &dyn pumpfun::utils::clock::Clock,
+    ) -> JournalEntry = JournalEntry::pending;
+}
+
+/// Image limits checked before metadata uploads
+#[test]
+fn test_image_policy() {
+    let ImagePolicy {
+        max_bytes: _,
+        max_dimension: _,
+        allow_resize: _,
+    } = ImagePolicy::default();
+    let policy = ImagePolicy::new(pumpfun::utils::DEFAULT_MAX_IMAGE_BYTES, 1000).with_resize(false);
+    let _: u32 = pumpfun::utils::DEFAULT_MAX_IMAGE_DIMENSION;
+    let _: fn(Vec<u8>, &ImagePolicy) -> Result<Vec<u8>, ClientError> =
+        pumpfun::utils::prepare_image;
+    assert!(pumpfun::utils::prepare_image(vec![0; 2], &policy).is_ok());
+}
+
+/// Sanitization of token names and symbols, and where it is applied
+#[test]
+fn test_sanitize_policy() {
+    use pumpfun::utils::Normalization;
+
+    let SanitizePolicy {
+        normalization: _,
+        strip_format: _,
+        ascii_only: _,
+        max_chars: _,
+    } = SanitizePolicy::default();
+    let policy = SanitizePolicy::new()
+        .with_normalization(Normalization::Nfc)
+        .with_strip_format(true)
+        .with_ascii_only(false)
+        .with_max_chars(32);
+    for normalization in [Normalization::None, Normalization::Nfc, Normalization::Nfkc] {
+        let _ = policy.with_normalization(normalization);
+    }
+    let _: String = policy.sanitize("PUMP");
+    let _: fn(&str) -> String = pumpfun::utils::sanitize;
+    let _: fn(&TokenMetadata) -> String = TokenMetadata::name_sanitized;
+    let _: fn(&TokenMetadata) -> String = TokenMetadata::symbol_sanitized;
+    let _: fn(&CreateEvent) -> String = CreateEvent::name_sanitized;
+    let _: fn(&CreateEvent) -> String = CreateEvent::symbol_sanitized;
+
+    let TokenInfo {
+        mint: _,
+        decimals: _,
+        name: _,
+        symbol: _,
+        raw_name: _,
+        raw_symbol: _,
+        uri: _,
+        logo_uri: _,
+        tags: _,
+    } = TokenInfo {
+        mint: Pubkey::new_unique(),
+        decimals: 6,
+        name: String::new(),
+        symbol: String::new(),
+        raw_name: String::new(),
+        raw_symbol: String::new(),
+        uri: None,
+        logo_uri: None,
+        tags: Vec::new(),
+    }
+    .with_sanitize_policy(&policy);
+}
+
+/// Load shedding priorities, thresholds and pressure tracking
+#[test]
+fn test_load_shedding_types() {
+    for feature in shed::Feature::ALL {
+        match feature {
+            shed::Feature::MetadataEnrichment
+            | shed::Feature::HolderCounts
+            | shed::Feature::OffchainMetadata
+            | shed::Feature::BackgroundRefresh => {}
+            _ => {}
+        }
+    }
+    match shed::Priority::Low {
+        shed::Priority::Low | shed::Priority::High | shed::Priority::Critical => {}
+    }
+    match Pressure::default() {
+        Pressure::Normal | Pressure::Elevated | Pressure::Severe => {}
+    }
+    let _: fn(Pressure, shed::Priority) -> bool = Pressure::sheds;
+
+    let shed::PressureThreshold { enter: _, exit: _ } = shed::PressureThreshold::new(0.5, 0.3);
+    let shedder = LoadShedder::new()
+        .with_priority(shed::Feature::HolderCounts, shed::Priority::Critical)
+        .with_thresholds(
+            shed::PressureThreshold::new(0.2, 0.1),
+            shed::PressureThreshold::new(0.5, 0.3),
+        );
+    let _: fn(&LoadShedder, shed::Feature) -> shed::Priority = LoadShedder::priority;
+    let _: fn(&LoadShedder) -> Pressure = LoadShedder::pressure;
+    let _: fn(&LoadShedder, Option<f64>) -> Pressure = LoadShedder::evaluate;
+    let _: fn(&LoadShedder, shed::Feature, Option<f64>) -> Result<(), ClientError> =
+        LoadShedder::check;
+    assert!(shedder
+        .check(shed::Feature::HolderCounts, Some(1.0))
+        .is_ok());
+
+    let pressure = Arc::new(shed::RpcPressure::new());
+    let _: fn(&shed::RpcPressure, std::time::Instant, bool) = shed::RpcPressure::record;
+    let _: fn(&shed::RpcPressure, std::time::Instant) -> Option<f64> =
+        shed::RpcPressure::failure_rate;
+    let _: fn(&shed::RpcPressure, Arc<dyn pumpfun::utils::clock::Clock>) =
+        shed::RpcPressure::set_clock;
+    let _: fn(&shed::RpcPressure) -> std::time::Instant = shed::RpcPressure::now;
+    let _ = shed::PressureSender::new((), pressure);
+}
+
+#[cfg(feature = "test-utils")]
+#[allow(dead_code)]
+async fn testing_functions(client: &PumpFun<'_>, params: &pumpfun::testing::GlobalParams) {
+    let _: Result<pumpfun::testing::InitOutcome, ClientError> =
+        pumpfun::testing::ensure_initialized(client, params, true).await;
+    let validated = params.validate().unwrap();
+    let _: Result<Signature, ClientError> = pumpfun::testing::set_params(client, &validated).await;
+}
+
+/// Fields of the public structs, as a downstream crate reads and builds them
+#[test]
+fn test_struct_fields() {
+    let BondingCurveAccount {
+        discriminator: _,
+        virtual_token_reserves: _,
+        virtual_sol_reserves: _,
+        real_token_reserves: _,
+        real_sol_reserves: _,
+        token_total_supply: _,
+        complete: _,
+    } = BondingCurveAccount::new(0, 2, 2, 1, 1, 2, false);
+
+    let GlobalAccount {
+        discriminator: _,
+        initialized: _,
+        authority: _,
+        fee_recipient: _,
+        initial_virtual_token_reserves: _,
+        initial_virtual_sol_reserves: _,
+        initial_real_token_reserves: _,
+        token_total_supply: _,
+        fee_basis_points: _,
+    } = GlobalAccount::new(
+        0,
+        true,
+        Pubkey::new_unique(),
+        Pubkey::new_unique(),
+        2,
+        2,
+        1,
+        2,
+        100,
+    );
+
+    let PriorityFee { limit: _, price: _ } = PriorityFee {
+        limit: Some(200_000),
+        price: Some(1),
+    };
+
+    let TradeId {
+        session: _,
+        sequence: _,
+    } = TradeId {
+        session: 1,
+        sequence: 1,
+    };
+
+    let TradePlan {
+        mint: _,
+        side: _,
+        amount_in: _,
+        expected_out: _,
+        limit: _,
+        observed_slot: _,
+    } = TradePlan {
+        mint: Pubkey::new_unique(),
+        side: TradeSide::Buy,
+        amount_in: 1,
+        expected_out: 1,
+        limit: 1,
+        observed_slot: None,
+    };
+
+    let BuyQuote {
+        sol_amount: _,
+        token_amount: _,
+        fee_amount: _,
+        price_per_token: _,
+        max_sol_cost: _,
+        slippage_basis_points: _,
+        virtual_sol_reserves: _,
+        virtual_token_reserves: _,
+        market_cap_sol: _,
+    } = BuyQuote {
+        sol_amount: 1,
+        token_amount: 1,
+        fee_amount: 0,
+        price_per_token: 1.0,
+        max_sol_cost: 1,
+        slippage_basis_points: pumpfun::quote::DEFAULT_SLIPPAGE_BASIS_POINTS,
+        virtual_sol_reserves: 1,
+        virtual_token_reserves: 1,
+        market_cap_sol: 1,
+    };
+
+    let SellQuote {
+        token_amount: _,
+        sol_amount: _,
+        fee_amount: _,
+        price_per_token: _,
+        min_sol_output: _,
+        slippage_basis_points: _,
+        virtual_sol_reserves: _,
+        virtual_token_reserves: _,
+        market_cap_sol: _,
+    } = SellQuote {
+        token_amount: 1,
+        sol_amount: 1,
+        fee_amount: 0,
+        price_per_token: 1.0,
+        min_sol_output: 1,
+        slippage_basis_points: 0,
+        virtual_sol_reserves: 1,
+        virtual_token_reserves: 1,
+        market_cap_sol: 1,
+    };
+
+    let TradeFill {
+        token_amount: _,
+        sol_amount: _,
+        fee: _,
+    } = TradeFill {
+        token_amount: 1,
+        sol_amount: 1,
+        fee: 0,
+    };
+
+    let BuyBreakdown {
+        sol_in: _,
+        protocol_fee: _,
+        creator_fee: _,
+        curve_sol: _,
+        unspent: _,
+        token_amount: _,
+    } = BuyBreakdown {
+        sol_in: 2,
+        protocol_fee: 0,
+        creator_fee: 0,
+        curve_sol: 1,
+        unspent: 1,
+        token_amount: 1,
+    };
+
+    let CompleteEvent {
+        user: _,
+        mint: _,
+        bonding_curve: _,
+        timestamp: _,
+    } = CompleteEvent {
+        user: Pubkey::new_unique(),
+        mint: Pubkey::new_unique(),
+        bonding_curve: Pubkey::new_unique(),
+        timestamp: 0,
+    };
+}
+
+/// Fields of the structs returned by the client
+#[allow(dead_code)]
+fn returned_struct_fields(
+    outcome: TradeOutcome,
+    report: CurveAtaReport,
+    sequence: SequenceOutcome,
+    usage: UsageReport,
+) {
+    let TradeOutcome {
+        trade_id: _,
+        signature: _,
+        trade_event: _,
+        timings: _,
+        transaction: _,
+        submit_path: _,
+        memo: _,
+    } = outcome;
+    let _: fn(&TradeOutcome) -> Option<String> = TradeOutcome::transaction_base64;
+
+    let CurveAtaReport {
+        bonding_curve: _,
+        expected_ata: _,
+        balance: _,
+        reserves: _,
+        issues: _,
+    } = report;
+
+    let SequenceOutcome {
+        fills: _,
+        total_fees: _,
+        final_curve: _,
+        final_price: _,
+    } = sequence;
+
+    let UsageReport {
+        operations,
+        total_requests: _,
+        total_credits: _,
+    } = usage;
+    for OperationUsage {
+        operation: _,
+        requests: _,
+        total_requests: _,
+        credits: _,
+    } in operations
+    {}
+}
+
+/// Fields and methods of bundle simulations
+#[allow(dead_code)]
+fn bundle_simulation_fields(simulation: BundleSimulation) {
+    let _: fn(&BundleSimulation) -> bool = BundleSimulation::succeeded;
+    let _: for<'r> fn(&'r BundleSimulation) -> Option<&'r SimulatedTransaction> =
+        BundleSimulation::first_failure;
+    let _: fn(&BundleSimulation) -> Result<(), ClientError> = BundleSimulation::ensure_success;
+    let _: fn(Vec<SimulatedTransaction>) -> BundleSimulation = BundleSimulation::new;
+    let _: fn(&VersionedTransaction) -> Vec<Pubkey> = pumpfun::bundle::referenced_curves;
+    let _: fn(
+        &[VersionedTransaction],
+        &GlobalAccount,
+        &[(Pubkey, Option<BondingCurveAccount>)],
+    ) -> BundleSimulation = pumpfun::bundle::replay;
+
+    let BundleSimulation {
+        transactions,
+        final_curves: _,
+    } = simulation;
+    for SimulatedTransaction {
+        index: _,
+        signature: _,
+        err: _,
+        logs: _,
+        units_consumed: _,
+        curves: _,
+    } in transactions
+    {}
+}
+
+/// Fields and methods of the TWAP executor and its report
+#[allow(dead_code)]
+async fn twap_executor(client: &PumpFun<'_>, mint: &Pubkey) {
+    let executor = TwapExecutor::new(1, 1)
+        .with_spacing_slots(1)
+        .with_sizing(ChildSizing::Equal)
+        .with_slippage(SlippageMode::BasisPoints(1))
+        .with_price_ceiling(1.0)
+        .with_max_retries(1)
+        .with_priority_fee(PriorityFee {
+            limit: None,
+            price: None,
+        });
+    let TwapExecutor {
+        target_sol: _,
+        children: _,
+        spacing_slots: _,
+        sizing: _,
+        slippage: _,
+        price_ceiling: _,
+        max_retries: _,
+        priority_fee: _,
+    } = executor;
+    let _: Vec<u64> = executor.child_amounts();
+
+    let report: Result<TwapReport, ClientError> = executor.execute(client, mint).await;
+    let TwapReport {
+        mint: _,
+        fills,
+        stop: _,
+        failure: _,
+        target_sol: _,
+        total_sol: _,
+        total_tokens: _,
+        single_shot_tokens: _,
+    } = report.unwrap();
+    for ChildFill {
+        index: _,
+        sol_amount: _,
+        signature: _,
+        token_amount: _,
+        sol_spent: _,
+        attempts: _,
+    } in fills
+    {}
+    let _: fn(&TwapReport) -> bool = TwapReport::is_complete;
+    let _: fn(&TwapReport) -> f64 = TwapReport::average_price;
+    let _: fn(&TwapReport) -> f64 = TwapReport::single_shot_price;
+    let _: fn(&TwapReport) -> f64 = TwapReport::improvement_vs_single_shot;
+}
+
+/// Fields of a token snapshot, which must stay serializable
+#[allow(dead_code)]
+fn token_snapshot(snapshot: TokenSnapshot) -> serde_json::Result<String> {
+    let json = serde_json::to_string(&snapshot);
+    let TokenSnapshot {
+        slot: _,
+        mint: _,
+        bonding_curve: _,
+        decimals: _,
+        supply: _,
+        mint_authority: _,
+        freeze_authority: _,
+        name: _,
+        symbol: _,
+        raw_name: _,
+        raw_symbol: _,
+        uri: _,
+        price: _,
+        market_cap_sol: _,
+        progress_pct: _,
+    } = snapshot;
+    json
+}
+
+/// Fields of a portfolio valuation
+#[allow(dead_code)]
+fn portfolio_valuation(valuation: PortfolioValuation) {
+    let holding = Holding::new(Pubkey::new_unique(), 1).with_cost_basis(1);
+    let Holding {
+        mint: _,
+        amount: _,
+        cost_basis_sol: _,
+    } = holding;
+    let _: usize = pumpfun::portfolio::SNAPSHOT_RETRIES;
+
+    let PortfolioValuation {
+        snapshot_slot: _,
+        consistent: _,
+        holdings,
+        total_value_sol: _,
+        total_pnl_sol: _,
+    } = valuation;
+    for HoldingValuation {
+        mint: _,
+        amount: _,
+        price: _,
+        value_sol: _,
+        pnl_sol: _,
+    } in holdings
+    {}
+}
+
+/// Conversions between the client's accounts and the CPI crate's account types
+#[allow(dead_code)]
+fn cpi_account_conversions(curve: pumpfun::cpi::BondingCurve, global: pumpfun::cpi::Global) {
+    let curve: BondingCurveAccount = curve.into();
+    let _: pumpfun::cpi::BondingCurve = curve.into();
+    let global: GlobalAccount = global.into();
+    let _: pumpfun::cpi::Global = global.into();
+}
+
+/// Fields and methods of the log redaction policy
+#[test]
+fn test_redaction_policy() {
+    let RedactionPolicy {
+        redact_amounts: _,
+        redact_wallets: _,
+        hash_mints: _,
+    } = RedactionPolicy::ALL;
+    let policy = RedactionPolicy::default();
+    let pubkey = Pubkey::new_unique();
+    let _: String = policy.wallet(&pubkey);
+    let _: String = policy.mint(&pubkey);
+    let _: String = policy.amount(1);
+    let _: fn(
+        pumpfun::signers::FileWatchingSigner,
+        RedactionPolicy,
+    ) -> pumpfun::signers::FileWatchingSigner =
+        pumpfun::signers::FileWatchingSigner::with_redaction;
+    let keypair = Keypair::new();
+    let pubkey = keypair.pubkey();
+    let signer = pumpfun::signers::ActiveSigner::Provided(std::sync::Arc::new(keypair));
+    assert_eq!(signer.pubkey(), pubkey);
+    let _ = pumpfun::signers::ActiveSigner::Payer(&Keypair::new());
+}
+
+/// Fields and constants of the devnet setup report
+#[allow(dead_code)]
+fn devnet_setup_report(report: DevnetSetupReport) {
+    let DevnetSetupReport {
+        payer: _,
+        airdrop_lamports: _,
+        airdrop_signature: _,
+        airdrop_attempts: _,
+        account_created: _,
+        balance_before: _,
+        balance_after: _,
+    } = report;
+    let _: u32 = pumpfun::devnet::AIRDROP_ATTEMPTS;
+    let _: Duration = pumpfun::devnet::AIRDROP_RETRY_DELAY;
+    let _: &str = pumpfun::devnet::MAINNET_GENESIS_HASH;
+    let _: &str = pumpfun::devnet::PUMPFUN_PROGRAM_HINT;
+}
+
+/// Fields and builders of the buy and sell arguments
+#[test]
+fn test_trade_params() {
+    let pubkey = Pubkey::new_unique();
+    let fee = PriorityFee {
+        limit: None,
+        price: None,
+    };
+    let BuyParams {
+        mint: _,
+        sol_amount: _,
+        slippage: _,
+        priority_fee: _,
+        extra_instructions: _,
+        confirm: _,
+        memo: _,
+        fee_recipient: _,
+        ..
+    } = BuyParams::new(pubkey, 1)
+        .with_slippage(SlippageMode::MaxMultiplier(1.5))
+        .with_slippage_bps(300)
+        .with_priority_fee(fee)
+        .with_instructions(Vec::<Instruction>::new())
+        .with_confirmation(true)
+        .with_memo("strategy-v1")
+        .with_fee_recipient(pubkey);
+    let SellParams {
+        mint: _,
+        token_amount: _,
+        slippage_basis_points: _,
+        priority_fee: _,
+        extra_instructions: _,
+        confirm: _,
+        memo: _,
+        fee_recipient: _,
+        ..
+    } = SellParams::new(pubkey)
+        .with_amount(1)
+        .with_slippage_bps(300)
+        .with_priority_fee(fee)
+        .with_instructions(Vec::<Instruction>::new())
+        .with_confirmation(false)
+        .with_memo(String::from("strategy-v1"))
+        .with_fee_recipient(pubkey);
+    let TradeAccounts {
+        fee_recipient: _,
+        token_program: _,
+        creator: _,
+        ..
+    } = TradeAccounts::new(pubkey)
+        .with_token_program(pubkey)
+        .with_creator(pubkey);
+}
+
+/// Fields of the priority fee budget and spend report
+#[test]
+fn test_fee_spend_types() {
+    let FeeBudget {
+        max_priority_spend_per_hour: _,
+        on_exceeded: _,
+    } = FeeBudget::new(1, BudgetAction::Block);
+    let FeeSpendReport {
+        session_lamports: _,
+        last_hour_lamports: _,
+        transactions: _,
+        per_mint: _,
+        downgraded: _,
+        blocked: _,
+    } = FeeSpendReport::default();
+    let _: Duration = pumpfun::spend::SPEND_WINDOW;
+    let _: fn(u64, u64) -> u64 = pumpfun::spend::priority_fee_lamports;
+}
+
+/// Fields and methods of the test vector corpus
+#[test]
+fn test_vector_corpus() {
+    let Corpus {
+        curve,
+        slippage,
+        pdas,
+        events,
+    } = Corpus::generate();
+    let CurveVector {
+        name: _,
+        curve:
+            CurveState {
+                virtual_token_reserves: _,
+                virtual_sol_reserves: _,
+                real_token_reserves: _,
+                real_sol_reserves: _,
+                token_total_supply: _,
+                complete: _,
+            },
+        fee_basis_points: _,
+        buy_sol: _,
+        expected_buy_tokens: _,
+        sell_tokens: _,
+        expected_sell_sol: _,
+    } = curve[0].clone();
+    let SlippageVector {
+        amount: _,
+        basis_points: _,
+        expected_max_buy_cost: _,
+        expected_min_sell_output: _,
+    } = slippage[0].clone();
+    let PdaVector {
+        kind: _,
+        inputs: _,
+        expected: _,
+    } = pdas[0].clone();
+    let EventVector {
+        name: _,
+        kind: _,
+        log: _,
+        expected: _,
+    } = events[0].clone();
+    let _: BondingCurveAccount = curve[0].curve.into();
+    let _: u64 = pumpfun::vectors::MAX_SAFE_INTEGER;
+    let _: fn(&Corpus) -> Vec<String> = Corpus::check;
+    let _: fn(&str) -> std::io::Result<Corpus> = |dir| Corpus::load(dir);
+    let _: fn(&Corpus, &str) -> std::io::Result<()> = |corpus, dir| corpus.write(dir);
+}
+
+/// Whale criteria, their annotations and the trade stream combinators
+#[test]
+fn test_trade_filters() {
+    fn stream_of<T>(_: impl futures::Stream<Item = T>) {}
+
+    let tracker = Arc::new(Mutex::new(NetFlowTracker::new(Duration::from_secs(300))));
+    let _: u64 = tracker
+        .lock()
+        .unwrap()
+        .volume_at(Duration::from_secs(60), 0);
+    let _: u64 = tracker.lock().unwrap().volume(Duration::from_secs(60));
+    let criteria = WhaleCriteria::new()
+        .with_min_sol(1)
+        .with_min_reserve_pct(1.0)
+        .with_min_volume_pct(1.0, tracker);
+    let _: fn(&WhaleCriteria, &TradeEvent) -> Option<WhaleReason> = WhaleCriteria::classify;
+    let _: Duration = filters::WHALE_VOLUME_WINDOW;
+    let _ = [
+        WhaleReason::SolAmount { lamports: 1 },
+        WhaleReason::ReserveShare { pct: 1.0 },
+        WhaleReason::VolumeShare { pct: 1.0 },
+    ];
+    let _ = |whale: WhaleTrade| {
+        let WhaleTrade {
+            event: _,
+            reason: _,
+        } = whale;
+    };
+
+    let trades = futures::stream::iter(Vec::<TradeEvent>::new());
+    let whales = filters::whale_filter(trades, criteria);
+    let whales = filters::mint_filter(whales, Pubkey::new_unique());
+    let whales = filters::side_filter(whales, TradeSide::Buy);
+    stream_of::<WhaleTrade>(whales);
+    let trades = futures::stream::iter(Vec::<TradeEvent>::new());
+    stream_of::<TradeEvent>(filters::side_filter(trades, TradeSide::Sell));
+}
+
+/// Log parsing over owned and borrowed lines, and the events it decodes
+#[test]
+fn test_log_parser() {
+    let mint = Pubkey::new_unique();
+    let mut parser: LogParser<TradeEvent> =
+        LogParser::new().with_filter(move |payload: &[u8]| payload.starts_with(mint.as_ref()));
+    let _: Vec<TradeEvent> = parser.parse(&["Program log: Instruction: Buy"]);
+    let _: Vec<TradeEvent> = parser.parse(&[String::new()]);
+    let _: Vec<CompleteEvent> = pumpfun::events::parse_logs(&[String::new()]);
+    let _: Vec<CompleteEvent> = pumpfun::events::parse_logs::<CompleteEvent>(&["log"]);
+    let _: LogParser<CompleteEvent> = LogParser::default();
+    let _: LogParser<TradeEvent> = LogParser::new().with_payload_prefix(mint);
+    let _: LogParser<TradeEvent> = LogParser::new().with_payload_prefix([0u8; 4]);
+
+    let create = CreateEvent {
+        name: String::new(),
+        symbol: String::new(),
+        uri: String::new(),
+        mint,
+        bonding_curve: Pubkey::new_unique(),
+        user: Pubkey::new_unique(),
+    };
+    let _: Vec<PumpFunEvent> = pumpfun::events::parse_all_logs(&["log"]);
+    let _: Option<PumpFunEvent> = PumpFunEvent::decode(&[]);
+    match PumpFunEvent::Create(create) {
+        PumpFunEvent::Create(CreateEvent {
+            name: _,
+            symbol: _,
+            uri: _,
+            mint: _,
+            bonding_curve: _,
+            user: _,
+        }) => {}
+        PumpFunEvent::Trade(_) | PumpFunEvent::Complete(_) | PumpFunEvent::SetParams(_) => {}
+    }
+}
+
+/// Fields of the graduation estimate and the trackers it is computed from
+#[test]
+fn test_graduation_eta() {
+    let mint = Pubkey::new_unique();
+    let tracker = NetFlowTracker::new(Duration::from_secs(300)).with_mint(mint);
+    let _: f64 = tracker.weighted_rate(Duration::from_secs(60));
+    let _: f64 = tracker.weighted_rate_at(Duration::from_secs(60), 0);
+    let _: Option<Pubkey> = tracker.mint();
+    let _: Duration = tracker.max_window();
+    let _: fn(&BondingCurveAccount, &GlobalAccount, &NetFlowTracker) -> Option<EtaEstimate> =
+        pumpfun::analytics::graduation_eta;
+    let _: Duration = pumpfun::analytics::ETA_HALF_LIFE;
+    let EtaEstimate {
+        sol_remaining: _,
+        eta: _,
+        confidence: _,
+    } = EtaEstimate {
+        sol_remaining: 1,
+        eta: Duration::from_secs(1),
+        confidence: 1.0,
+    };
+}
+
+/// Fields of the parameter eras and the helpers classifying curves into them
+#[test]
+fn test_parameter_eras() {
+    let eras: &[ParameterEra] = pumpfun::analytics::PARAMETER_ERAS;
+    let _: u128 = pumpfun::analytics::ERA_INVARIANT_TOLERANCE_BPS;
+    let ParameterEra {
+        name: _,
+        effective_from: _,
+        initial_virtual_token_reserves: _,
+        initial_virtual_sol_reserves: _,
+        token_total_supply: _,
+    } = eras[0];
+    let _: fn(&ParameterEra) -> u128 = ParameterEra::initial_invariant_k;
+    let _: fn(&ParameterEra, u128, u64) -> bool = ParameterEra::matches;
+    let _: fn(u128, u64) -> Option<&'static ParameterEra> = ParameterEra::classify;
+    let _: fn(&BondingCurveAccount) -> Option<&'static ParameterEra> = ParameterEra::of_curve;
+}
+
+/// Fields of the wallet clusters and the helpers building them
+#[test]
+fn test_wallet_clusters() {
+    use pumpfun::analytics::{
+        ClusterConfig, EvidenceKind, LinkEvidence, Transfer, WalletCluster, WalletLink,
+    };
+
+    let pubkey = Pubkey::new_unique();
+    let transfer = Transfer {
+        from: pubkey,
+        to: pubkey,
+        lamports: 1,
+        timestamp: 0,
+    };
+    let ClusterConfig {
+        funding_window_secs: _,
+        co_trade_window_secs: _,
+        min_co_trades: _,
+        min_co_trade_ratio: _,
+        max_fanout: _,
+        min_confidence: _,
+        ..
+    } = ClusterConfig::default()
+        .with_funding_window(60)
+        .with_co_trading(2, 3, 0.8)
+        .with_max_fanout(20)
+        .with_min_confidence(0.5);
+    let evidence = LinkEvidence {
+        kind: EvidenceKind::CommonFunder {
+            source: pubkey,
+            gap_secs: 0,
+        },
+        confidence: 0.5,
+    };
+    let _ = [
+        EvidenceKind::CoTrading {
+            matched: 1,
+            ratio: 1.0,
+        },
+        EvidenceKind::CommonDestination {
+            destination: pubkey,
+        },
+    ];
+    let link = WalletLink {
+        a: pubkey,
+        b: pubkey,
+        confidence: 0.5,
+        evidence: vec![evidence],
+    };
+    let WalletCluster {
+        wallets: _,
+        confidence: _,
+        links: _,
+    } = WalletCluster {
+        wallets: vec![pubkey],
+        confidence: 0.5,
+        links: vec![link],
+    };
+    let _: Vec<WalletCluster> = pumpfun::analytics::cluster_wallets(&[], &[transfer]);
+    let _: fn(&[TradeEvent], &[Transfer], &ClusterConfig) -> Vec<WalletCluster> =
+        pumpfun::analytics::cluster_wallets_with;
+    let _: fn(&VersionedTransaction, i64) -> Vec<Transfer> = pumpfun::analytics::decode_transfers;
+}
+
+/// Commands, errors and methods of the interactive session
+#[test]
+fn test_repl_types() {
+    let mint = Pubkey::new_unique();
+    let _ = [
+        Command::Help,
+        Command::Exit,
+        Command::Global,
+        Command::Curve(mint),
+        Command::QuoteBuy {
+            mint,
+            sol_amount: 1,
+        },
+        Command::QuoteSell {
+            mint,
+            token_amount: 1,
+        },
+        Command::Buy {
+            mint,
+            sol_amount: 1,
+            slippage_basis_points: None,
+        },
+        Command::Sell {
+            mint,
+            token_amount: None,
+            slippage_basis_points: None,
+        },
+        Command::Usage,
+        Command::WatchAdd(mint),
+        Command::WatchRemove(mint),
+        Command::WatchList,
+        Command::Positions,
+        Command::JournalTail(1),
+    ];
+    let _: fn(&str) -> Result<Option<Command>, ClientError> = Command::parse;
+    let _: fn(&Command) -> bool = Command::is_session_only;
+    let _: fn(&Command) -> bool = Command::needs_keypair;
+    let ScriptError {
+        line: _,
+        command: _,
+        error: _,
+    } = ScriptError {
+        line: 1,
+        command: "help".to_string(),
+        error: ClientError::Timeout,
+    };
+    let _: [&str; 12] = pumpfun::repl::VERBS;
+    let _: &str = pumpfun::repl::HELP;
+    let _: usize = pumpfun::repl::DEFAULT_JOURNAL_TAIL;
+}
+
+/// Signatures of the interactive session
+#[allow(dead_code)]
+async fn session_methods<'c, 'a>(client: &'c PumpFun<'a>, journal: TradeJournal) {
+    let mut session: Session<'c, 'a> = Session::new(client).with_journal(journal);
+    let _: &PumpFun<'a> = session.client();
+    let _: &std::collections::BTreeSet<Pubkey> = session.watched();
+    let _: Option<&GlobalAccount> = session.global();
+    let _: Result<(), ClientError> = session.warm_up();
+    let _: Vec<String> = session.complete("bu");
+    let _: Result<String, ClientError> = session.execute(&Command::Help).await;
+    let _: Result<usize, ScriptError> = session.run_script("help", |_: &str, _: &str| {}).await;
+}
+
+/// Signatures of the RPC usage accounting types
+#[allow(dead_code)]
+fn usage_methods() {
+    let _: fn() -> Operation = Operation::current;
+    let _: fn(&anchor_client::solana_client::rpc_request::RpcRequest) -> RpcMethod =
+        RpcMethod::from_request;
+    let _: fn(CostTable, RpcMethod, u64) -> CostTable = CostTable::with_cost;
+    let _: fn(&CostTable, RpcMethod) -> u64 = CostTable::cost;
+    let _: fn() -> RpcUsage = RpcUsage::new;
+    let _: fn(&RpcUsage, Operation, RpcMethod) = RpcUsage::record;
+    let _: fn(&RpcUsage, Operation, RpcMethod) -> u64 = RpcUsage::count;
+    let _: fn(&RpcUsage) = RpcUsage::reset;
+    let _: fn(&RpcUsage, &CostTable) -> UsageReport = RpcUsage::report;
+    let _: for<'r> fn(&'r UsageReport, Operation) -> Option<&'r OperationUsage> =
+        UsageReport::operation;
+    let _: fn(Operation) -> OperationScope = OperationScope::enter;
+    let _: u64 = futures::executor::block_on(pumpfun::usage::scoped(Operation::Buy, async { 1 }));
+}
+
+/// Signatures of the curve and global account methods
+#[allow(dead_code)]
+fn account_methods() {
+    let _: fn(&BondingCurveAccount, u64) -> Result<u64, &'static str> =
+        BondingCurveAccount::get_buy_price;
+    let _: fn(&BondingCurveAccount, u64, u64) -> Result<u64, &'static str> =
+        BondingCurveAccount::get_sell_price;
+    let _: fn(&BondingCurveAccount) -> u64 = BondingCurveAccount::get_market_cap_sol;
+    let _: fn(&BondingCurveAccount, u64) -> u64 = BondingCurveAccount::get_final_market_cap_sol;
+    let _: fn(&BondingCurveAccount, u64, u64) -> u64 = BondingCurveAccount::get_buy_out_price;
+    let _: fn(&BondingCurveAccount) -> u64 = BondingCurveAccount::sol_to_graduate;
+    let _: fn(&BondingCurveAccount) -> u128 = BondingCurveAccount::invariant_k;
+    let _: fn(&BondingCurveAccount, &GlobalAccount) -> Result<(), CurveError> =
+        BondingCurveAccount::check_invariant;
+    let _: fn(&BondingCurveAccount, u64) -> Result<u64, CurveError> =
+        BondingCurveAccount::sol_to_reach_market_cap;
+    let _: fn(&BondingCurveAccount, &[PlannedTrade], u64) -> Result<SequenceOutcome, CurveError> =
+        BondingCurveAccount::simulate_sequence;
+    let _: fn(&BondingCurveAccount, u64) -> Result<f64, CurveError> =
+        BondingCurveAccount::price_after_buy;
+    let _: fn(&BondingCurveAccount, u64) -> Result<f64, CurveError> =
+        BondingCurveAccount::price_after_sell;
+    let _: fn(&BondingCurveAccount, DepthSide, usize, u64, u64) -> Vec<DepthPoint> =
+        BondingCurveAccount::depth;
+    let _: fn(&BondingCurveAccount, u64, &GlobalAccount) -> BuyBreakdown =
+        BondingCurveAccount::buy_breakdown;
+    let _: fn(&BondingCurveAccount, u64, u64, u64) -> BuyBreakdown =
+        BondingCurveAccount::buy_breakdown_with_fees;
+
+    let _: fn(&GlobalAccount, u64) -> u64 = GlobalAccount::get_initial_buy_price;
+    let _: fn(&GlobalAccount) -> u64 = GlobalAccount::creator_fee_basis_points;
+    let _: fn(&GlobalAccount) -> u64 = GlobalAccount::total_fee_bps;
+    let _: fn(&GlobalAccount) -> u128 = GlobalAccount::initial_invariant_k;
+}
+
+/// Signatures of the client's constructor, builders and synchronous methods
+#[allow(dead_code)]
+fn client_methods<'a>() {
+    let _: fn(Cluster, &'a Keypair, Option<CommitmentConfig>, Option<bool>) -> PumpFun<'a> =
+        PumpFun::new;
+    let _: fn(PumpFun<'a>, SeedConfig) -> PumpFun<'a> = PumpFun::with_seeds;
+    let _: fn(PumpFun<'a>, CommitmentConfig) -> PumpFun<'a> = PumpFun::with_quote_commitment;
+    let _: fn(PumpFun<'a>, bool) -> PumpFun<'a> = PumpFun::with_return_transaction;
+    let _: fn(PumpFun<'a>, u64) -> PumpFun<'a> = PumpFun::with_plan_slot_budget;
+    let _: fn(PumpFun<'a>, ProgramVersion) -> PumpFun<'a> = PumpFun::with_program_version;
+    let _: fn(PumpFun<'a>, usize) -> PumpFun<'a> = PumpFun::with_max_concurrent_requests;
+    let _: fn(PumpFun<'a>, CostTable) -> PumpFun<'a> = PumpFun::with_rpc_cost_table;
+    let _: fn(PumpFun<'a>, bool) -> PumpFun<'a> = PumpFun::with_pause_on_upgrade;
+    let _: fn(PumpFun<'a>, RedactionPolicy) -> PumpFun<'a> = PumpFun::with_redaction;
+    let _: fn(&PumpFun<'a>) -> RedactionPolicy = PumpFun::redaction_policy;
+    let _: fn(PumpFun<'a>, u64, Option<u32>) -> PumpFun<'a> = PumpFun::with_priority_fee;
+    let _: fn(PumpFun<'a>, Pubkey) -> PumpFun<'a> = PumpFun::with_fee_recipient;
+    let _: fn(PumpFun<'a>, std::sync::Arc<dyn pumpfun::signers::SignerProvider>) -> PumpFun<'a> =
+        PumpFun::with_signer_provider;
+    let _: fn(&PumpFun<'a>) -> pumpfun::signers::ActiveSigner<'a> = PumpFun::signer;
+    let _: fn(PumpFun<'a>, Option<FeeBudget>) -> PumpFun<'a> = PumpFun::with_fee_spend_tracking;
+    let _: fn(&PumpFun<'a>) -> FeeSpendReport = PumpFun::fee_spend_report;
+    let _: fn(PumpFun<'a>, Arc<Mutex<NetFlowTracker>>) -> PumpFun<'a> =
+        PumpFun::with_volume_tracker;
+    let _: fn(PumpFun<'a>, LoadShedder) -> PumpFun<'a> = PumpFun::with_load_shedder;
+    let _: fn(PumpFun<'a>, ImagePolicy) -> PumpFun<'a> = PumpFun::with_image_policy;
+    let _: fn(&PumpFun<'a>) -> Pressure = PumpFun::load_pressure;
+    let _: fn(&PumpFun<'a>) = PumpFun::record_rate_limited;
+
+    let _: fn(&PumpFun<'a>) -> TradeId = PumpFun::next_trade_id;
+    let _: fn(&PumpFun<'a>) -> CommitmentConfig = PumpFun::get_quote_commitment;
+    let _: fn(&PumpFun<'a>) -> UsageReport = PumpFun::rpc_usage;
+    let _: fn(&PumpFun<'a>) = PumpFun::reset_rpc_usage;
+    let _: fn(&PumpFun<'a>) -> Result<ProgramVersion, ClientError> = PumpFun::get_program_version;
+    let _: fn(
+        &PumpFun<'a>,
+        &Pubkey,
+        usize,
+    ) -> Result<Vec<pumpfun::analytics::Transfer>, ClientError> = PumpFun::get_sol_transfers;
+    let _: fn(
+        &PumpFun<'a>,
+        &Pubkey,
+        &TradeAccounts,
+        pumpfun::cpi::instruction::Buy,
+    ) -> Result<Instruction, ClientError> = PumpFun::buy_instruction;
+    let _: fn(
+        &PumpFun<'a>,
+        &Pubkey,
+        &TradeAccounts,
+        pumpfun::cpi::instruction::Sell,
+    ) -> Result<Instruction, ClientError> = PumpFun::sell_instruction;
+    let _: fn(&PumpFun<'a>, &Keypair, pumpfun::cpi::instruction::Create) -> Instruction =
+        PumpFun::create_instruction;
+    let _: fn(
+        &PumpFun<'a>,
+        &Keypair,
+        pumpfun::cpi::instruction::Create,
+        Option<PriorityFee>,
+    ) -> Result<Vec<Instruction>, ClientError> = PumpFun::create_ix;
+    let _: fn(&PumpFun<'a>) -> futures::channel::mpsc::UnboundedReceiver<ClientEvent> =
+        PumpFun::subscribe_client_events;
+    let _: fn(&PumpFun<'a>) -> Result<Option<u64>, ClientError> = PumpFun::check_program_upgrade;
+    let _: fn(&PumpFun<'a>) -> Option<u64> = PumpFun::last_deployed_slot;
+    let _: fn(&PumpFun<'a>) -> bool = PumpFun::is_trading_paused;
+    let _: fn(&PumpFun<'a>) = PumpFun::acknowledge_program_upgrade;
+    let _: fn(&[u8]) -> Result<u64, ClientError> = pumpfun::upgrade::decode_deployed_slot;
+    let _: fn(&PumpFun<'a>, &Pubkey, u64) -> Result<u64, ClientError> = PumpFun::quote_buy;
+    let _: fn(&PumpFun<'a>, &Pubkey, u64) -> Result<u64, ClientError> = PumpFun::quote_sell;
+    let _: fn(&PumpFun<'a>, &Pubkey, u64, Option<u64>) -> Result<BuyQuote, ClientError> =
+        PumpFun::get_buy_quote;
+    let _: fn(&PumpFun<'a>, &Pubkey, u64, Option<u64>) -> Result<SellQuote, ClientError> =
+        PumpFun::get_sell_quote;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<CurveSummary, ClientError> =
+        PumpFun::get_curve_summary;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<Pubkey, ClientError> = PumpFun::get_token_program;
+    let _: fn(&PumpFun<'a>) -> Result<GlobalAccount, ClientError> = PumpFun::get_global_account;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<BondingCurveAccount, ClientError> =
+        PumpFun::get_bonding_curve_account;
+    let _: fn(&PumpFun<'a>, &[Pubkey]) -> Result<Vec<Pubkey>, ClientError> =
+        PumpFun::filter_pumpfun_mints;
+    let _: fn(&PumpFun<'a>, &Signature, &Pubkey) -> Result<Option<SandwichReport>, ClientError> =
+        PumpFun::detect_sandwich;
+    let _: fn(&PumpFun<'a>, &Pubkey, u64) -> Result<CompetitionReport, ClientError> =
+        PumpFun::slot_competition_report;
+    let _: fn(&PumpFun<'a>, &[VersionedTransaction]) -> Result<BundleSimulation, ClientError> =
+        PumpFun::simulate_bundle;
+    let _: fn(&PumpFun<'a>, &[VersionedTransaction]) -> Result<BundleSimulation, ClientError> =
+        PumpFun::replay_bundle;
+    let _: fn(&PumpFun<'a>) -> Result<Vec<(Pubkey, u64)>, ClientError> =
+        PumpFun::get_all_fee_recipient_balances;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<TokenContext, ClientError> =
+        PumpFun::get_token_context;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<TokenSnapshot, ClientError> =
+        PumpFun::get_token_snapshot;
+    let _: fn(&PumpFun<'a>, &[Holding]) -> Result<PortfolioValuation, ClientError> =
+        PumpFun::value_portfolio;
+    let _: fn(&PumpFun<'a>, &[Pubkey], ScreenChecks) -> Vec<Result<SafetyReport, ClientError>> =
+        PumpFun::screen_tokens;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<CurveAtaReport, ClientError> =
+        PumpFun::verify_curve_ata;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<f64, ClientError> = PumpFun::get_top_holders_pct;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<MintAuthority, ClientError> =
+        PumpFun::get_mint_authority;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<bool, ClientError> =
+        PumpFun::is_mint_authority_renounced;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<f64, ClientError> =
+        PumpFun::get_creator_holdings_pct;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<Pubkey, ClientError> = PumpFun::get_creator;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<TokenAge, ClientError> = PumpFun::get_token_age;
+    let _: fn(&TokenAge, &dyn pumpfun::utils::clock::Clock) -> Option<Duration> = TokenAge::age;
+    let _: for<'b> fn(&'b PumpFun<'a>) -> &'b dyn pumpfun::utils::clock::Clock = PumpFun::clock;
+    let _: fn(&PumpFun<'a>) -> Result<i64, ClientError> = PumpFun::get_cluster_time;
+    let _: fn(&PumpFun<'a>) -> Result<Duration, ClientError> = PumpFun::get_slot_duration;
+    let _: fn(&PumpFun<'a>, u64) -> Result<Duration, ClientError> = PumpFun::slots_to_duration;
+    let _: fn(&PumpFun<'a>, Duration) -> Result<u64, ClientError> = PumpFun::duration_to_slots;
+    let _: fn(&PumpFun<'a>, &Pubkey, Duration) -> Result<i64, ClientError> =
+        PumpFun::get_net_sol_inflow;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<Option<Duration>, ClientError> =
+        PumpFun::estimate_time_to_graduate;
+    let _: fn(&PumpFun<'a>, &Pubkey) -> Result<bool, ClientError> = PumpFun::queue_ata_cleanup;
+
+    let _: fn() -> Pubkey = PumpFun::get_global_pda;
+    let _: fn(&SeedConfig) -> Pubkey = PumpFun::get_global_pda_with_seeds;
+    let _: fn() -> Pubkey = PumpFun::get_mint_authority_pda;
+    let _: fn(&SeedConfig) -> Pubkey = PumpFun::get_mint_authority_pda_with_seeds;
+    let _: fn(&Pubkey) -> Option<Pubkey> = PumpFun::get_bonding_curve_pda;
+    let _: fn(&SeedConfig, &Pubkey) -> Option<Pubkey> = PumpFun::get_bonding_curve_pda_with_seeds;
+    let _: fn(&Pubkey) -> Option<Pubkey> = PumpFun::get_creator_vault_pda;
+    let _: fn(&SeedConfig, &Pubkey) -> Option<Pubkey> = PumpFun::get_creator_vault_pda_with_seeds;
+    let _: fn(&Pubkey) -> Pubkey = PumpFun::get_metadata_pda;
+    let _: fn(&Pubkey, &Pubkey) -> Pubkey = PumpFun::get_user_ata;
+    let _: fn(&Pubkey, &Pubkey, &Pubkey) -> Pubkey = PumpFun::get_user_ata_with_token_program;
+}
+
+/// Signatures of the client's asynchronous methods
+#[allow(dead_code)]
+async fn client_async_methods(
+    client: &PumpFun<'_>,
+    mint: &Keypair,
+    metadata: CreateTokenMetadata,
+    plan: &TradePlan,
+    cache: &pumpfun::refresh::CurveCache,
+    transaction: &Transaction,
+) {
+    let pubkey = mint.pubkey();
+    let fee = Some(PriorityFee {
+        limit: None,
+        price: None,
+    });
+
+    let _: Result<GlobalAccount, ClientError> = client.get_global_account_async().await;
+    let _: Result<BondingCurveAccount, ClientError> =
+        client.get_bonding_curve_account_async(&pubkey).await;
+    let _: Result<Signature, ClientError> = client.create(mint, metadata.clone(), fee).await;
+    let _: Result<Signature, ClientError> = client
+        .create_with_instructions(mint, metadata.clone(), fee, Vec::<Instruction>::new())
+        .await;
+    let _: Result<Signature, ClientError> = client
+        .create_and_buy(mint, metadata.clone(), 1, Some(500), fee)
+        .await;
+    let _: Result<Signature, ClientError> = client
+        .create_and_buy_with_instructions(mint, metadata.clone(), 1, Some(500), fee, Vec::new())
+        .await;
+    let _: Result<TokenMetadataResponse, Box<dyn std::error::Error>> =
+        pumpfun::utils::upload_token_metadata(metadata.clone(), Vec::new()).await;
+    let uploaded: Result<UploadedMetadata, ClientError> = client.upload_metadata(metadata).await;
+    if let Ok(UploadedMetadata {
+        response:
+            TokenMetadataResponse {
+                metadata: _,
+                metadata_uri: _,
+            },
+        content_hash: _,
+        from_cache: _,
+    }) = uploaded
+    {}
+    let _: fn(&CreateTokenMetadata) -> std::io::Result<anchor_client::solana_sdk::hash::Hash> =
+        pumpfun::utils::metadata_content_hash;
+    let _: Result<Signature, ClientError> = client.buy(&pubkey, 1, Some(500), fee).await;
+    let _: Result<Signature, ClientError> = client
+        .buy_with_slippage_mode(&pubkey, 1, Some(SlippageMode::default()), fee)
+        .await;
+    let _: Result<Signature, ClientError> = client.sell(&pubkey, Some(1), Some(500), fee).await;
+    let _: Result<Signature, ClientError> = client.buy_with(BuyParams::new(pubkey, 1)).await;
+    let _: Result<Signature, ClientError> = client.sell_with(SellParams::new(pubkey)).await;
+    let _: Result<Vec<Instruction>, ClientError> = client.buy_ix(&BuyParams::new(pubkey, 1));
+    let _: Result<Vec<Instruction>, ClientError> = client.sell_ix(&SellParams::new(pubkey));
+    let _: Result<TradeOutcome, ClientError> = client
+        .sell_with_result(&pubkey, None, None, fee, true)
+        .await;
+    let _: Result<TradeOutcome, ClientError> = client
+        .sell_with_outcome(SellParams::new(pubkey).with_memo("v1"), true)
+        .await;
+    let _: Result<TradeOutcome, ClientError> = client
+        .buy_with_outcome(BuyParams::new(pubkey, 1).with_memo("v1"))
+        .await;
+    let _: Result<Signature, ClientError> = client.execute(plan, cache, fee).await;
+    let _: Result<Signature, ClientError> = client.request_airdrop(1, None).await;
+    let _: Result<DevnetSetupReport, ClientError> = client.devnet_setup(1.0).await;
+    let _: Result<Signature, ClientError> = client.send_transaction(transaction).await;
+    let _: Result<Signature, ClientError> = client.swap_with_wsol(1, Vec::new(), fee).await;
+    let _: Result<Signature, ClientError> = client
+        .swap_with_wsol_account(1, |_: &Pubkey| Vec::new(), fee)
+        .await;
+    let _: Vec<(Pubkey, Result<Option<BondingCurveAccount>, ClientError>)> =
+        client.get_bonding_curve_accounts(&[pubkey]).await;
+    let _: Result<Vec<rpc_response::RpcConfirmedTransactionStatusWithSignature>, ClientError> =
+        client.get_signatures_in_windows(&pubkey, &[]).await;
+    let _: Result<Vec<GlobalParamChange>, ClientError> =
+        client.get_global_param_changes(10, None).await;
+    let _: Result<TokenListJson, ClientError> = client
+        .export_watchlist_token_list(&[pubkey], "tokens.json")
+        .await;
+    let _: Result<u64, ClientError> = client.await_slot(1).await;
+    let _: Result<CompleteEvent, ClientError> = client
+        .wait_for_graduation(&pubkey, Duration::from_secs(1))
+        .await;
+    let _: Result<CompleteEvent, ClientError> = client
+        .wait_for_graduation_with_cancel(&pubkey, Duration::from_secs(1), &CancellationToken::new())
+        .await;
+    let _: Result<(), ClientError> = client
+        .subscribe_events(|event: PumpFunEvent| {
+            let _: Option<&Pubkey> = event.mint();
+        })
+        .await;
+    let _: Result<(), ClientError> = client
+        .subscribe_events_with_cancel(|_| {}, &CancellationToken::new())
+        .await;
+    let _: Result<Vec<CleanupEntry>, ClientError> = client.process_ata_cleanup().await;
+    let _: () = client
+        .watch_program_upgrades_until_cancelled(Duration::from_secs(1), &CancellationToken::new())
+        .await;
+}